@@ -0,0 +1,330 @@
+//! This module contains the machine mode of Naval Battle.
+//!
+//! The machine mode lets an external program, like a bot written in another language or a
+//! test harness, play against the computer players of the [engine](crate::engine). It reads
+//! one [BotCommand] per line from the standard input and writes one [BotEvent] per line to the
+//! standard output, both as JSON objects:
+//!
+//! ```text
+//! < {"event":"start","rules":"Classic","fleet":"A1H/A3H/A5H/A7H/A9H"}
+//! < {"event":"your_turn","turn":1}
+//! > {"command":"shoot","cell":"B7"}
+//! < {"event":"shot","turn":1,"shooter":"human","cell":"B7","result":"miss","ship":null}
+//! < {"event":"shot","turn":1,"shooter":"computer","cell":"E3","result":"hit","ship":null}
+//! < {"event":"your_turn","turn":2}
+//! ```
+//!
+//! Every command is answered either by the shots it caused, followed by the next `your_turn`
+//! or by the `game_over` event, or by an `error` event.
+//!
+use crate::engine::fleet::ShipKind;
+use crate::engine::game::{Game, ShotReport, Side};
+use crate::engine::grid::Cell;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+
+/// A command sent by the external program.
+#[derive(Debug, PartialEq, Eq, Clone, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum BotCommand {
+    /// Shoots at the cell, like `{"command":"shoot","cell":"B7"}`.
+    Shoot { cell: String },
+
+    /// Gives up the game: `{"command":"resign"}`.
+    Resign,
+}
+
+/// The result of a shot, as seen by the external program.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum ShotResult {
+    Miss,
+    Hit,
+    Sunk,
+    Mine,
+
+    /// The shot has been fired into the fog: its result is hidden.
+    Fogged,
+}
+
+/// An event written for the external program.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum BotEvent {
+    /// The game has started, with the given rules and the fleet of the external program.
+    Start { rules: String, fleet: String },
+
+    /// The engine is waiting for a command.
+    YourTurn { turn: u32 },
+
+    /// A shot has been fired by one of the sides.
+    ///
+    /// The kind of the ship is given only when it has been sunk.
+    Shot {
+        turn: u32,
+        shooter: Side,
+        cell: String,
+        result: ShotResult,
+        ship: Option<ShipKind>,
+    },
+
+    /// The game is over: the winner is missing when the game is drawn.
+    GameOver { winner: Option<Side> },
+
+    /// The command can't be played, and it should be sent again.
+    Error { message: String },
+}
+
+impl From<(u32, &ShotReport)> for BotEvent {
+    /// Describes the shot fired in the turn, hiding the results that the human player doesn't
+    /// know yet.
+    fn from((turn, shot): (u32, &ShotReport)) -> Self {
+        let result = match (&shot.hit, shot.sunk) {
+            _ if shot.fogged && shot.shooter == Side::Human => ShotResult::Fogged,
+            _ if shot.mine => ShotResult::Mine,
+            (Some(_), true) => ShotResult::Sunk,
+            (Some(_), false) => ShotResult::Hit,
+            (None, _) => ShotResult::Miss,
+        };
+        let ship = match result {
+            ShotResult::Sunk => shot.hit.clone(),
+            _ => None,
+        };
+
+        BotEvent::Shot {
+            turn,
+            shooter: shot.shooter,
+            cell: shot.cell.to_string(),
+            result,
+            ship,
+        }
+    }
+}
+
+/// The machine mode of Naval Battle.
+///
+/// The external program plays the human side of the game, against its computer player.
+pub struct NavalBattleBot<R, W> {
+    game: Game,
+    input: R,
+    output: W,
+    reported: (u32, usize),
+}
+
+impl<R: BufRead, W: Write> NavalBattleBot<R, W> {
+    /// Creates the machine mode to play the given game, reading the commands from `input` and
+    /// writing the events to `output`.
+    pub fn new(game: Game, input: R, output: W) -> Self {
+        Self {
+            game,
+            input,
+            output,
+            reported: (0, 0),
+        }
+    }
+
+    /// Returns the game being played.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Plays the game until it is over or until the input is over.
+    ///
+    /// Empty lines are ignored, and an invalid command is answered with an error event.
+    pub fn run(&mut self) -> io::Result<()> {
+        let start = BotEvent::Start {
+            rules: self.game.rules().name().to_string(),
+            fleet: self
+                .game
+                .human()
+                .map(|human| human.fleet().to_code())
+                .unwrap_or_default(),
+        };
+        self.emit(&start)?;
+        self.emit_next()?;
+
+        while !self.game.is_over() {
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                return Ok(());
+            } else if line.trim().is_empty() {
+                continue;
+            }
+
+            let played = match serde_json::from_str::<BotCommand>(&line) {
+                Ok(BotCommand::Shoot { cell }) => Cell::from_str(&cell)
+                    .map_err(|e| e.to_string())
+                    .and_then(|cell| {
+                        self.game
+                            .play_turn(&cell)
+                            .map(|_| ())
+                            .map_err(|e| e.to_string())
+                    }),
+                Ok(BotCommand::Resign) => self.game.resign(Side::Human).map_err(|e| e.to_string()),
+                Err(e) => Err(e.to_string()),
+            };
+
+            match played {
+                Ok(()) => {
+                    self.report_turn()?;
+                    self.emit_next()?;
+                }
+                Err(message) => self.emit(&BotEvent::Error { message })?,
+            }
+        }
+
+        Ok(())
+    }
+
+    fn emit(&mut self, event: &BotEvent) -> io::Result<()> {
+        serde_json::to_writer(&mut self.output, event)?;
+        writeln!(self.output)?;
+        self.output.flush()
+    }
+
+    // Tells the external program that the engine is waiting for a command, or that the game
+    // is over.
+    fn emit_next(&mut self) -> io::Result<()> {
+        let event = if self.game.is_over() {
+            BotEvent::GameOver {
+                winner: self.game.winner(),
+            }
+        } else {
+            BotEvent::YourTurn {
+                turn: self.game.turns_played() + 1,
+            }
+        };
+
+        self.emit(&event)
+    }
+
+    // Reports the shots of the last turn which have not been reported yet, as the human
+    // player may shoot more than once in a turn.
+    fn report_turn(&mut self) -> io::Result<()> {
+        let Some(turn) = self.game.last_turn() else {
+            return Ok(());
+        };
+        let (reported_turn, reported) = self.reported;
+        let skipped = if reported_turn == turn.turn {
+            reported
+        } else {
+            0
+        };
+        let events: Vec<BotEvent> = turn
+            .shots
+            .iter()
+            .skip(skipped)
+            .map(|shot| BotEvent::from((turn.turn, shot)))
+            .collect();
+        self.reported = (turn.turn, turn.shots.len());
+
+        events.iter().try_for_each(|event| self.emit(event))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::tests::fixed_fleet;
+    use crate::engine::game::{Difficulty, GameBuilder};
+    use rstest::rstest;
+    use std::io::Cursor;
+
+    fn play(input: &str) -> (Game, Vec<serde_json::Value>) {
+        let game = GameBuilder::new()
+            .seed(7)
+            .computer(Difficulty::Easy)
+            .human("Bot", fixed_fleet(0))
+            .build()
+            .unwrap();
+        let mut output = Vec::new();
+        let mut bot = NavalBattleBot::new(game, Cursor::new(input.to_string()), &mut output);
+        bot.run().unwrap();
+        let game = bot.game;
+        let events = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        (game, events)
+    }
+
+    #[rstest]
+    #[case(r#"{"command":"shoot","cell":"B7"}"#, Ok(BotCommand::Shoot { cell: "B7".to_string() }))]
+    #[case(r#"{"command":"resign"}"#, Ok(BotCommand::Resign))]
+    #[case(r#"{"command":"fire","cell":"B7"}"#, Err(()))]
+    #[case(r#"{"command":"shoot"}"#, Err(()))]
+    #[case("B7", Err(()))]
+    fn test_command(#[case] line: &str, #[case] expected: Result<BotCommand, ()>) {
+        assert_eq!(
+            serde_json::from_str::<BotCommand>(line).map_err(|_| ()),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_events() {
+        let event = BotEvent::Shot {
+            turn: 3,
+            shooter: Side::Computer,
+            cell: "E3".to_string(),
+            result: ShotResult::Sunk,
+            ship: Some(ShipKind::Destroyer),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&event).unwrap(),
+            r#"{"event":"shot","turn":3,"shooter":"computer","cell":"E3","result":"sunk","ship":"destroyer"}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&BotEvent::GameOver { winner: None }).unwrap(),
+            r#"{"event":"game_over","winner":null}"#
+        );
+    }
+
+    #[rstest]
+    fn test_turn() {
+        let (game, events) = play("\n{\"command\":\"shoot\",\"cell\":\"J10\"}\n");
+
+        assert_eq!(events[0]["event"], "start");
+        assert_eq!(events[0]["fleet"], fixed_fleet(0).to_code());
+        assert_eq!(events[1]["event"], "your_turn");
+        assert_eq!(events[1]["turn"], 1);
+        assert_eq!(events[2]["event"], "shot");
+        assert_eq!(events[2]["shooter"], "human");
+        assert_eq!(events[2]["cell"], "J10");
+        assert_eq!(events[3]["shooter"], "computer");
+        assert_eq!(events[4]["event"], "your_turn");
+        assert_eq!(events[4]["turn"], 2);
+        assert_eq!(game.turns_played(), 1);
+    }
+
+    #[rstest]
+    #[case(r#"{"command":"shoot","cell":"K1"}"#)]
+    #[case(r#"{"command":"jump"}"#)]
+    #[case("not json")]
+    fn test_error(#[case] line: &str) {
+        let (game, events) = play(&format!("{line}\n"));
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2]["event"], "error");
+        assert!(events[2]["message"].is_string());
+        assert_eq!(game.turns_played(), 0);
+    }
+
+    #[rstest]
+    fn test_resign() {
+        let (game, events) =
+            play("{\"command\":\"resign\"}\n{\"command\":\"shoot\",\"cell\":\"A1\"}\n");
+
+        assert_eq!(events.len(), 3);
+        assert_eq!(events[2]["event"], "game_over");
+        assert_eq!(events[2]["winner"], "computer");
+        assert!(game.is_over());
+    }
+}
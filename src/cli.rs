@@ -0,0 +1,538 @@
+//! This module contains the Naval Battle command line game.
+//!
+//! The command line game is played by typing the shots on the standard input, like `B7`. It
+//! plays the same [Game] as the [tui](crate::tui): the rules, the computer players and the
+//! coordinates, from `A1` to `J10`, are the ones of the [engine](crate::engine).
+//!
+//! Besides the shots, the player can type a [Command], like `help` to list all of them.
+//!
+//! The messages are printed in the [Locale] of the player, but the record of the game is always
+//! written in English.
+//!
+//! Like in the TUI, the shots grid of the player and their tactical grid, with their fleet and
+//! the computer shots, are printed side by side, in colors when the output is a terminal.
+//!
+use crate::config::Config;
+use crate::engine::{
+    fleet::Fleet,
+    game::{Game, GameBuilder, GameError, GamePhase, ShotReport, Side},
+    grid::{self, Cell, CellState, CoordinateFormat, Grid},
+};
+use crate::locale::{Locale, Text};
+use crossterm::style::Stylize;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// The errors of a line typed by the player.
+#[derive(Debug, PartialEq, Eq, Error)]
+pub enum CommandError {
+    /// The line is neither a command nor a cell.
+    #[error("{0}. Type help for the commands")]
+    InvalidCell(#[from] grid::Error),
+
+    /// The `save` command has no file name.
+    #[error("save needs a file name, like save game.txt")]
+    MissingFile,
+}
+
+/// A line typed by the player during the game.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Command {
+    /// Shoots at the cell, like `B7`.
+    Shoot(Cell),
+
+    /// Lists the commands.
+    Help,
+
+    /// Shows the fleet and the shots of the player.
+    Show,
+
+    /// Leaves the game.
+    Quit,
+
+    /// Saves the record of the game to the file.
+    Save(PathBuf),
+}
+
+impl FromStr for Command {
+    type Err = CommandError;
+
+    /// Parses a command, whose name is case-insensitive, or a cell.
+    ///
+    /// The spaces around the command and its argument are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (name, argument) = s
+            .trim()
+            .split_once(char::is_whitespace)
+            .map(|(name, argument)| (name, argument.trim()))
+            .unwrap_or((s.trim(), ""));
+
+        match name.to_ascii_lowercase().as_str() {
+            "help" => Ok(Command::Help),
+            "show" => Ok(Command::Show),
+            "quit" => Ok(Command::Quit),
+            "save" if argument.is_empty() => Err(CommandError::MissingFile),
+            "save" => Ok(Command::Save(PathBuf::from(argument))),
+            _ => Ok(Command::Shoot(Cell::from_str(s.trim())?)),
+        }
+    }
+}
+
+/// The Naval Battle command line game.
+///
+/// The human fleet is deployed at random, away from the islands of the configured map. The
+/// game asks for a shot at every turn, then it prints the result of the shots of both sides and
+/// the shots grid of the human player, until a side has won, the player quits or the input is
+/// over.
+pub struct NavalBattleCli<R, W> {
+    game: Game,
+    input: R,
+    output: W,
+    reported: (u32, usize),
+    record: Vec<(u32, ShotReport)>,
+    colors: bool,
+    locale: Locale,
+    notation: CoordinateFormat,
+}
+
+impl<R: BufRead, W: Write> NavalBattleCli<R, W> {
+    /// Creates a new [configured game](configured_game), reading the shots from `input` and
+    /// writing the results to `output` in the configured language.
+    pub fn new(config: &Config, input: R, output: W) -> Result<Self, GameError> {
+        let mut cli = Self::with_game(configured_game(config)?, input, output);
+        cli.set_locale(config.locale());
+        cli.set_coordinate_format(config.coordinate_format);
+
+        Ok(cli)
+    }
+
+    /// Creates a command line front-end to play the given game.
+    pub fn with_game(game: Game, input: R, output: W) -> Self {
+        Self {
+            game,
+            input,
+            output,
+            reported: (0, 0),
+            record: Vec::new(),
+            colors: false,
+            locale: Locale::default(),
+            notation: CoordinateFormat::default(),
+        }
+    }
+
+    /// Prints the grids in colors, or in plain text when `colors` is `false` (the default).
+    pub fn set_colors(&mut self, colors: bool) {
+        self.colors = colors;
+    }
+
+    /// Prints the messages in the given language, English by default.
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+    }
+
+    /// Writes the cells in the given notation, like `B7` (the default) or `r7c2`. The typed cells
+    /// are read in any notation.
+    pub fn set_coordinate_format(&mut self, notation: CoordinateFormat) {
+        self.notation = notation;
+    }
+
+    /// Returns the game being played.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Plays the game until it is over, until the player quits or until the input is over.
+    ///
+    /// An invalid command is reported, and the shot is asked again.
+    pub fn run(&mut self) -> io::Result<()> {
+        writeln!(self.output, "{}", self.locale.text(Text::TypeHelp))?;
+        self.show_grids()?;
+
+        while !self.game.is_over() {
+            write!(self.output, "{}", self.locale.text(Text::YourShot))?;
+            self.output.flush()?;
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                writeln!(self.output)?;
+                return Ok(());
+            } else if line.trim().is_empty() {
+                continue;
+            }
+
+            match Command::from_str(&line) {
+                Ok(Command::Shoot(cell)) => match self.game.play_turn(&cell) {
+                    Ok(_) => self.report_turn()?,
+                    Err(e) => writeln!(self.output, "{}", self.locale.error(&e))?,
+                },
+                Ok(Command::Help) => {
+                    writeln!(self.output, "{}", self.locale.text(Text::CommandsHelp))?
+                }
+                Ok(Command::Show) => self.show_grids()?,
+                Ok(Command::Quit) => return Ok(()),
+                Ok(Command::Save(path)) => {
+                    let message = match fs::write(&path, self.record()) {
+                        Ok(()) => self.locale.format(Text::GameSaved, &[&path.display()]),
+                        Err(e) => self.locale.format(Text::CannotSave, &[&path.display(), &e]),
+                    };
+                    writeln!(self.output, "{message}")?
+                }
+                Err(e) => writeln!(self.output, "{}", command_error(&e, self.locale))?,
+            }
+        }
+
+        self.report_result()
+    }
+
+    /// Returns the record of the game: the fleet of the human player, then every shot fired,
+    /// one per line, with its turn.
+    pub fn record(&self) -> String {
+        let mut record = format!("rules: {}\n", self.game.rules().name());
+        if let Some(human) = self.game.human() {
+            record.push_str(&format!("fleet: {}\n", human.fleet().to_code()));
+        }
+        for (turn, shot) in &self.record {
+            record.push_str(&format!(
+                "{turn}. {}\n",
+                describe(shot, Locale::English, CoordinateFormat::Letter)
+            ));
+        }
+
+        record
+    }
+
+    // Prints the shots grid of the human player next to their tactical grid, like the TUI.
+    //
+    // The tactical grid shows the human fleet with the computer shots, and its damage from the
+    // handicap.
+    fn show_grids(&mut self) -> io::Result<()> {
+        let (Some(human), Some(computer)) = (self.game.human(), self.game.computer()) else {
+            return Ok(());
+        };
+
+        let mut tactical = human.fleet().to_grid();
+        for cell in (0..10).flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y))) {
+            match computer.shots_grid().at(&cell) {
+                CellState::Empty | CellState::Occupied => {}
+                state => tactical.mark(&cell, state.clone()),
+            }
+        }
+        let hidden = self.game.fog().hidden(Side::Human);
+        let grids = side_by_side(
+            human.shots_grid(),
+            &hidden,
+            &tactical,
+            self.colors,
+            self.locale,
+        );
+
+        writeln!(self.output, "{grids}\n")
+    }
+
+    // Prints the shots of the turn which have not been reported yet, and the shots grid.
+    //
+    // With a streak or a salvo, the same turn is played by several calls to the game.
+    fn report_turn(&mut self) -> io::Result<()> {
+        let Some(turn) = self.game.last_turn() else {
+            return Ok(());
+        };
+        let (reported_turn, reported) = self.reported;
+        let skipped = if reported_turn == turn.turn {
+            reported
+        } else {
+            0
+        };
+        for shot in turn.shots.iter().skip(skipped) {
+            writeln!(
+                self.output,
+                "{}",
+                describe(shot, self.locale, self.notation)
+            )?;
+            self.record.push((turn.turn, shot.clone()));
+        }
+        self.reported = (turn.turn, turn.shots.len());
+
+        self.show_grids()?;
+        if self.game.human_shoots_again() {
+            writeln!(self.output, "{}", self.locale.text(Text::ShootAgain))?;
+        }
+
+        Ok(())
+    }
+
+    fn report_result(&mut self) -> io::Result<()> {
+        let result = match self.game.phase() {
+            GamePhase::Finished {
+                winner: Side::Human,
+            } => Text::CliYouWin,
+            GamePhase::Finished {
+                winner: Side::Computer,
+            } => Text::CliYouLose,
+            GamePhase::Drawn => Text::CliDraw,
+            GamePhase::Setup | GamePhase::InProgress => return Ok(()),
+        };
+
+        writeln!(self.output, "{}", self.locale.text(result))
+    }
+}
+
+/// Creates a new game with the configured player name, difficulty, solver budget, adaptation,
+/// variant, map, script, opponent command and handicap.
+///
+/// The human fleet is deployed at random, away from the islands of the map. A map or a script
+/// which can't be loaded is left out, like in the TUI.
+pub fn configured_game(config: &Config) -> Result<Game, GameError> {
+    let map = config.map().ok().flatten();
+    let fleet = Fleet::build(|kind| {
+        loop {
+            let ship = kind.random();
+            if map.as_ref().is_none_or(|map| map.allows(&ship)) {
+                break ship;
+            }
+        }
+    });
+    let mut builder = GameBuilder::new()
+        .variant(config.variant)
+        .human(&config.player_name, fleet)
+        .computer(config.difficulty)
+        .solver_budget(config.solver_budget as usize)
+        .adaptation(config.adaptation)
+        .handicap_option(config.handicap);
+    if let Some(map) = map {
+        builder = builder.map(map);
+    }
+    #[cfg(feature = "scripting")]
+    if let Some(script) = config.script().ok().flatten() {
+        builder = builder.script(script);
+    }
+    if let Some(command) = &config.opponent_command {
+        builder = builder.opponent_command(command);
+    }
+
+    builder.build()
+}
+
+// Draws the shots grid of the player next to their tactical grid, with their titles and
+// coordinates. The hidden cells of the shots grid are marked with "?".
+pub(crate) fn side_by_side(
+    shots: &Grid,
+    hidden: &[Cell],
+    tactical: &Grid,
+    colors: bool,
+    locale: Locale,
+) -> String {
+    let header = "   A B C D E F G H I J";
+    let mut output = format!(
+        "   {:<23}   {}\n{header}    {header}",
+        locale.text(Text::EnemyWaters),
+        locale.text(Text::YourFleetWaters)
+    );
+    for y in 0..10 {
+        let _ = write!(output, "\n{:02} ", y + 1);
+        let _ = shots.render_row_to(&mut output, y, |cell, state| match hidden.contains(cell) {
+            true => paint('?', None, colors),
+            false => paint(' ', Some(state), colors),
+        });
+        let _ = write!(output, "    {:02} ", y + 1);
+        let _ = tactical.render_row_to(&mut output, y, |_, state| paint(' ', Some(state), colors));
+    }
+
+    output
+}
+
+// Returns the symbol of the cell in the given state, or the given symbol without a state,
+// in colors if they are enabled.
+pub(crate) fn paint(symbol: char, state: Option<&CellState>, colors: bool) -> String {
+    let symbol = match state {
+        Some(CellState::Empty) => '.',
+        Some(CellState::Occupied) => '#',
+        Some(CellState::Miss) => 'o',
+        Some(CellState::Hit) => 'X',
+        Some(CellState::Blocked) => '^',
+        None => symbol,
+    };
+    if !colors {
+        return symbol.to_string();
+    }
+
+    let styled = match state {
+        Some(CellState::Empty) => symbol.dark_grey(),
+        Some(CellState::Occupied) => symbol.white().bold(),
+        Some(CellState::Miss) => symbol.blue(),
+        Some(CellState::Hit) => symbol.red().bold(),
+        Some(CellState::Blocked) => symbol.yellow(),
+        None => symbol.magenta().bold(),
+    };
+
+    styled.to_string()
+}
+
+// Describes the result of a shot, as the human player sees it, with its cell in the given notation.
+fn describe(shot: &ShotReport, locale: Locale, notation: CoordinateFormat) -> String {
+    let shooter = match shot.shooter {
+        Side::Human => Text::YouFire,
+        Side::Computer => Text::ComputerFires,
+    };
+    let result = match (&shot.hit, shot.sunk) {
+        _ if shot.fogged && shot.shooter == Side::Human => locale.text(Text::FogHidden).to_string(),
+        _ if shot.mine => locale.text(Text::MineExplodes).to_string(),
+        (Some(kind), true) => locale.format(Text::ShipSunk, &[kind]),
+        (Some(_), false) => locale.text(Text::HitShot).to_string(),
+        (None, _) => locale.text(Text::MissShot).to_string(),
+    };
+
+    locale.format(shooter, &[&notation.format(&shot.cell), &result])
+}
+
+// Returns the message of an invalid line, in the given language.
+fn command_error(error: &CommandError, locale: Locale) -> String {
+    match error {
+        CommandError::InvalidCell(e) => locale.format(Text::InvalidCommand, &[e]),
+        CommandError::MissingFile => locale.text(Text::MissingFile).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::tests::fixed_fleet;
+    use crate::engine::game::Difficulty;
+    use rstest::rstest;
+    use std::io::Cursor;
+
+    fn new_game(fleet: Fleet) -> Game {
+        GameBuilder::new()
+            .seed(8)
+            .computer(Difficulty::Easy)
+            .human("Human", fleet)
+            .build()
+            .unwrap()
+    }
+
+    fn play(game: Game, input: &str) -> (Game, String) {
+        let mut output = Vec::new();
+        let mut cli = NavalBattleCli::with_game(game, Cursor::new(input.to_string()), &mut output);
+        cli.run().unwrap();
+        let game = cli.game;
+
+        (game, String::from_utf8(output).unwrap())
+    }
+
+    #[rstest]
+    fn test_shots(fixed_fleet: Fleet) {
+        let (game, output) = play(new_game(fixed_fleet), "K1\nj10\n");
+
+        assert!(output.contains("K1 does not represent a valid cell. Type help"));
+        assert!(output.contains("You fire at J10: "));
+        assert!(output.contains("The computer fires at "));
+        assert_eq!(game.turns_played(), 1);
+    }
+
+    #[rstest]
+    #[case("A1", Ok(Command::Shoot(Cell::bounded(0, 0))))]
+    #[case(" j10 ", Ok(Command::Shoot(Cell::bounded(9, 9))))]
+    #[case("HELP", Ok(Command::Help))]
+    #[case("show", Ok(Command::Show))]
+    #[case("quit", Ok(Command::Quit))]
+    #[case("save  my game.txt ", Ok(Command::Save(PathBuf::from("my game.txt"))))]
+    #[case("save", Err(CommandError::MissingFile))]
+    #[case("fire", Err(grid::Error::InvalidFormat("fire".to_string()).into()))]
+    #[case("A11", Err(grid::Error::InvalidFormat("A11".to_string()).into()))]
+    fn test_command(#[case] line: &str, #[case] expected: Result<Command, CommandError>) {
+        assert_eq!(Command::from_str(line), expected);
+    }
+
+    #[rstest]
+    fn test_commands(fixed_fleet: Fleet) {
+        let path = std::env::temp_dir().join(format!("naval-cli-{}.txt", std::process::id()));
+        let input = format!("help\nB2\nshow\nsave {}\nquit\nC3\n", path.display());
+        let (game, output) = play(new_game(fixed_fleet.clone()), &input);
+
+        assert!(output.contains(Locale::English.text(Text::CommandsHelp)));
+        assert!(output.contains("   Enemy waters              Your fleet"));
+        assert!(output.contains("Game saved to"));
+        assert_eq!(game.turns_played(), 1);
+
+        let record = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let lines: Vec<&str> = record.lines().collect();
+        assert_eq!(lines[0], "rules: Classic");
+        assert_eq!(lines[1], format!("fleet: {}", fixed_fleet.to_code()));
+        assert!(
+            lines[2..]
+                .iter()
+                .any(|line| line.starts_with("1. You fire at B2: "))
+        );
+        assert_eq!(lines.len(), 4);
+    }
+
+    #[rstest]
+    fn test_locale(fixed_fleet: Fleet) {
+        let mut output = Vec::new();
+        let input = Cursor::new("J10\nsave\n".to_string());
+        let mut cli = NavalBattleCli::with_game(new_game(fixed_fleet), input, &mut output);
+        cli.set_locale(Locale::Italian);
+        cli.run().unwrap();
+        let record = cli.record();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("Spari in J10: "));
+        assert!(output.contains("Il computer spara in "));
+        assert!(output.contains("save ha bisogno del nome di un file"));
+        assert!(record.contains("1. You fire at J10: "));
+    }
+
+    #[rstest]
+    fn test_grids(fixed_fleet: Fleet) {
+        let (game, output) = play(new_game(fixed_fleet), "J10\n");
+        let computer = game.last_computer_move().unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        let grids = lines
+            .iter()
+            .rposition(|line| line.contains("Enemy waters"))
+            .unwrap();
+
+        // The first row has the Aircraft Carrier, and the last one the shot at J10.
+        assert_eq!(
+            lines[grids + 1],
+            "   A B C D E F G H I J       A B C D E F G H I J"
+        );
+        assert!(lines[grids + 2].ends_with("    01 # # # # # . . . . ."));
+        assert!(lines[grids + 11].starts_with("10 . . . . . . . . . o    "));
+
+        let row = lines[grids + 2 + usize::from(computer.y())];
+        let tactical = row.split("    ").nth(1).unwrap();
+        let symbol = tactical
+            .chars()
+            .nth(3 + 2 * usize::from(computer.x()))
+            .unwrap();
+        assert!(symbol == 'o' || symbol == 'X');
+    }
+
+    #[rstest]
+    fn test_human_wins(fixed_fleet: Fleet) {
+        let game = new_game(fixed_fleet);
+        let input: Vec<String> = game
+            .computer()
+            .unwrap()
+            .fleet()
+            .as_ref()
+            .iter()
+            .flat_map(|ship| ship.occupied_cells())
+            .map(|cell| cell.to_string())
+            .collect();
+        let (game, output) = play(game, &input.join("\n"));
+
+        assert_eq!(
+            game.phase(),
+            GamePhase::Finished {
+                winner: Side::Human
+            }
+        );
+        assert!(output.contains("Destroyer sunk!"));
+        assert!(output.ends_with("You win!\n"));
+    }
+}
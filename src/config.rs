@@ -0,0 +1,1438 @@
+//! This module contains the user configuration of the game.
+//!
+//! The configuration is read at startup from the `naval.toml` file, which lives in the `naval`
+//! folder of the XDG config directory (usually `~/.config/naval/naval.toml`). Every key of the file
+//! is optional: a missing key takes its default value, and a missing file is the same as an
+//! empty one.
+//!
+//! A configuration file looks like this:
+//!
+//! ```toml
+//! player_name = "Ishmael"
+//! mode = "battle"
+//! variant = "classic"
+//! difficulty = "normal"
+//! solver_budget = 100000
+//! adaptation = "moderate"
+//! theme = "classic"
+//! animated_water = false
+//! turn_pace = "instant"
+//! map = "archipelago"
+//! script = "hunter"
+//! opponent_command = ["python3", "my_bot.py"]
+//! time_control = 5
+//! best_of = 3
+//! power_ups = false
+//! sound = false
+//! confirm_shots = false
+//! accessibility = false
+//! coordinate_format = "letter"
+//! handicap = "none"
+//! locale = "it"
+//! server = "127.0.0.1:8080"
+//! server_certificate = "/home/ishmael/server.pem"
+//!
+//! [keybindings]
+//! quit = "q"
+//! horizontal = "h"
+//! vertical = "v"
+//! settings = "s"
+//! save_layout = "p"
+//! auto_place = "a"
+//! leaderboard = "l"
+//! network = "n"
+//! ```
+//!
+//! The configuration can also be changed within the game, from the settings screen, which saves
+//! it back to the same file.
+//!
+//! In the same folder, the `layouts.toml` file keeps the fleet layouts saved by the player, as
+//! [LayoutPresets], so that a fleet can be deployed again in one keystroke, and the
+//! `leaderboard.toml` file keeps the results of every local player, as a [Leaderboard]. The
+//! `daily.toml` file keeps the results of the daily challenges, as [DailyResults]. The
+//! `campaign.toml` file keeps the progress of the player in every campaign, as
+//! [CampaignProgress]. The `habits.toml` file keeps where every local player has placed their
+//! ships, as [PlacementHabits]. The `replay.toml` file keeps the [Replay] of the last battle
+//! played in the TUI. The `correspondence.toml` file keeps the side of the player in the
+//! game by [Correspondence] in progress. The `network.toml` file keeps the network [Session] of
+//! the duel the player has left before its end, so that they can rejoin it.
+//!
+use crate::engine::correspondence::Correspondence;
+use crate::engine::fleet::{Fleet, FleetLayout};
+use crate::engine::game::{Difficulty, GameMode, Handicap};
+use crate::engine::grid::CoordinateFormat;
+use crate::engine::map::{Map, MapError};
+use crate::engine::replay::Replay;
+use crate::engine::rules::Variant;
+#[cfg(feature = "scripting")]
+use crate::engine::script::{Script, ScriptError};
+use crate::engine::strategy::Adaptation;
+use crate::locale::Locale;
+use crate::net::Session;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::{env, fs, io};
+use strum::Display;
+use strum_macros::EnumIter;
+use thiserror::Error;
+
+/// Represents a configuration error.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The configuration file can't be read or written.
+    #[error("cannot access the configuration file: {0}")]
+    Io(#[from] io::Error),
+
+    /// The configuration file is not a valid TOML document or has unknown values.
+    #[error("invalid configuration file: {0}")]
+    Parse(#[from] toml::de::Error),
+
+    /// The configuration can't be written as a TOML document.
+    #[error("cannot write the configuration: {0}")]
+    Serialize(#[from] toml::ser::Error),
+
+    /// A series must have an odd number of games, so that there is always a winner.
+    #[error("a series of {0} games is not supported, it must be an odd number")]
+    InvalidSeriesLength(u8),
+
+    /// The map is unknown or its definition is not valid.
+    #[error("invalid map: {0}")]
+    Map(#[from] MapError),
+
+    /// The script is unknown or it is not valid.
+    #[cfg(feature = "scripting")]
+    #[error("invalid script: {0}")]
+    Script(#[from] ScriptError),
+}
+
+/// The color palette used to draw the grids.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    /// Light colors, blue sea and green ships.
+    #[default]
+    Classic,
+
+    /// Dark sea and white ships, for terminals with poor colors.
+    #[strum(serialize = "High Contrast")]
+    HighContrast,
+}
+
+/// How the turn of the computer is shown after the player has fired.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TurnPace {
+    /// The shots of both sides are shown at once.
+    #[default]
+    Instant,
+
+    /// The computer thinks for a moment before its shots are shown.
+    Delayed,
+
+    /// The computer thinks until a key is pressed, one turn after the other.
+    #[strum(serialize = "Step by step")]
+    Step,
+}
+
+/// The keys bound to the game commands.
+///
+/// Arrow keys and Enter are always used to move the cursor and confirm actions, the other
+/// commands can be bound to any character. Bindings are case-insensitive.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    /// Leaves the game.
+    pub quit: char,
+
+    /// Places the ship horizontally during the setup, and turns the moving Submarine.
+    pub horizontal: char,
+
+    /// Places the ship vertically during the setup, and turns the moving Submarine.
+    pub vertical: char,
+
+    /// Opens the settings screen during the setup.
+    pub settings: char,
+
+    /// Saves the deployed fleet as a layout preset during the setup.
+    pub save_layout: char,
+
+    /// Deploys the fleet with the next placement strategy during the setup.
+    pub auto_place: char,
+
+    /// Opens the leaderboard during the setup.
+    pub leaderboard: char,
+
+    /// Opens the lobby of the network games during the setup.
+    pub network: char,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            quit: 'q',
+            horizontal: 'h',
+            vertical: 'v',
+            settings: 's',
+            save_layout: 'p',
+            auto_place: 'a',
+            leaderboard: 'l',
+            network: 'n',
+        }
+    }
+}
+
+/// The user configuration.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// The name of the human player.
+    pub player_name: String,
+
+    /// The kind of match played: a battle against the computer, a puzzle, the daily challenge or
+    /// a campaign.
+    pub mode: GameMode,
+
+    /// The rules of the games: the classic ones or a variant, like the sea mines.
+    pub variant: Variant,
+
+    /// The difficulty of the computer player.
+    pub difficulty: Difficulty,
+
+    /// How many placements of the ships the computer player can explore to play the endgame
+    /// perfectly, at the hard difficulty. A larger budget makes it stronger, but slower.
+    pub solver_budget: u32,
+
+    /// How strongly the computer player keeps the games close, at the adaptive difficulty.
+    pub adaptation: Adaptation,
+
+    /// The color palette of the grids.
+    pub theme: Theme,
+
+    /// Whether waves roll across the water of the grids during the battle.
+    pub animated_water: bool,
+
+    /// How the turn of the computer is shown after the player has fired.
+    pub turn_pace: TurnPace,
+
+    /// The name of the map whose islands block some cells of the boards, or `None` to play on
+    /// the open sea. See [Config::map].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub map: Option<String>,
+
+    /// The name of the script playing the computer player, instead of the strategy of its
+    /// difficulty, or `None` to play against the built-in computer. See [Config::script].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
+
+    /// The command line of the external program playing the computer player, instead of the
+    /// strategy of its difficulty: the program, followed by its arguments. See the
+    /// [process](crate::engine::process) module for the protocol.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opponent_command: Option<Vec<String>>,
+
+    /// The minutes each side has to play the whole game, or `None` to play without time limits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub time_control: Option<u16>,
+
+    /// The number of games of a series: the first side winning the majority wins the series.
+    pub best_of: u8,
+
+    /// Whether every side can use special weapons, like one bombardment per game.
+    pub power_ups: bool,
+
+    /// Whether the hits, the sunk ships and the victories ring the terminal bell.
+    pub sound: bool,
+
+    /// Whether every shot must be confirmed, so that a slip of the cursor doesn't waste it.
+    pub confirm_shots: bool,
+
+    /// Whether the battle narrates every turn in plain text and takes the shots as typed
+    /// coordinates, like `B7`, so that it can be played with a screen reader.
+    pub accessibility: bool,
+
+    /// The notation of the cells in the messages, like `B7`, `2-7` or `r7c2`. The typed cells are
+    /// read in any notation.
+    pub coordinate_format: CoordinateFormat,
+
+    /// The advantage given to a side, like an extra Destroyer, to balance the games.
+    pub handicap: Handicap,
+
+    /// The language of the game, or `None` to take it from the environment. See
+    /// [Config::locale].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locale: Option<Locale>,
+
+    /// The address of the server of the network games, like `127.0.0.1:8080`.
+    pub server: String,
+
+    /// The certificate of the server, as a PEM file, to encrypt the network games with TLS, or
+    /// `None` to play them in plaintext. Needs the `tls` feature.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub server_certificate: Option<PathBuf>,
+
+    /// The keys bound to the game commands.
+    pub keybindings: KeyBindings,
+}
+
+impl Config {
+    const DIR_NAME: &'static str = "naval";
+    const FILE_NAME: &'static str = "naval.toml";
+    const MAPS_DIR_NAME: &'static str = "maps";
+    #[cfg(feature = "scripting")]
+    const SCRIPTS_DIR_NAME: &'static str = "scripts";
+    const SOLVER_BUDGET: u32 = 100_000;
+
+    /// The number of rows and columns of the board, the only size played by the engine.
+    pub const BOARD_SIZE: u8 = 10;
+
+    /// Returns the folder of the game files, if a config directory can be found.
+    ///
+    /// The config directory is `$XDG_CONFIG_HOME` or, if it is not set, `$HOME/.config`.
+    pub fn dir() -> Option<PathBuf> {
+        env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+            .map(|dir| dir.join(Self::DIR_NAME))
+    }
+
+    /// Returns the path of the configuration file, if a config directory can be found.
+    pub fn path() -> Option<PathBuf> {
+        Self::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads the configuration from the default path.
+    ///
+    /// If there is no configuration file, the default configuration is returned.
+    pub fn load() -> Result<Self, Error> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads the configuration from the given file.
+    ///
+    /// If the file doesn't exist, the default configuration is returned.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => content.parse(),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the configuration to the default path.
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&Self::path().ok_or_else(no_config_dir)?)
+    }
+
+    /// Saves the configuration to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+
+    /// Returns the configured language or, if it is not set, the language of the environment.
+    pub fn locale(&self) -> Locale {
+        self.locale.unwrap_or_else(Locale::from_env)
+    }
+
+    /// Returns the configured map, if any.
+    ///
+    /// The map is a built-in one, or it is defined by the `<name>.map` file of the `maps`
+    /// folder, next to the configuration file.
+    pub fn map(&self) -> Result<Option<Map>, Error> {
+        self.map_in(Self::dir().as_deref())
+    }
+
+    /// Returns the configured map, if any, looking for its definition in the `maps` folder of
+    /// the given directory.
+    pub fn map_in(&self, dir: Option<&Path>) -> Result<Option<Map>, Error> {
+        let Some(name) = &self.map else {
+            return Ok(None);
+        };
+        if let Ok(map) = Map::built_in(name) {
+            return Ok(Some(map));
+        }
+
+        let path = dir
+            .ok_or_else(no_config_dir)?
+            .join(Self::MAPS_DIR_NAME)
+            .join(format!("{name}.map"));
+        match fs::read_to_string(path) {
+            Ok(definition) => Ok(Some(Map::parse(name, &definition)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Err(MapError::Unknown(name.clone()).into())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Returns the configured script, if any.
+    ///
+    /// The script is defined by the `<name>.rhai` file of the `scripts` folder, next to the
+    /// configuration file.
+    #[cfg(feature = "scripting")]
+    pub fn script(&self) -> Result<Option<Script>, Error> {
+        self.script_in(Self::dir().as_deref())
+    }
+
+    /// Returns the configured script, if any, looking for it in the `scripts` folder of the
+    /// given directory.
+    #[cfg(feature = "scripting")]
+    pub fn script_in(&self, dir: Option<&Path>) -> Result<Option<Script>, Error> {
+        let Some(name) = &self.script else {
+            return Ok(None);
+        };
+
+        let path = dir
+            .ok_or_else(no_config_dir)?
+            .join(Self::SCRIPTS_DIR_NAME)
+            .join(format!("{name}.rhai"));
+        match fs::read_to_string(path) {
+            Ok(source) => Ok(Some(Script::compile(name, &source)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => {
+                Err(ScriptError::Unknown(name.clone()).into())
+            }
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // Series must have an odd number of games.
+    fn validate(self) -> Result<Self, Error> {
+        if self.best_of.is_multiple_of(2) {
+            Err(Error::InvalidSeriesLength(self.best_of))
+        } else {
+            Ok(self)
+        }
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            player_name: "player 1".to_string(),
+            mode: GameMode::default(),
+            variant: Variant::default(),
+            difficulty: Difficulty::default(),
+            solver_budget: Self::SOLVER_BUDGET,
+            adaptation: Adaptation::default(),
+            theme: Theme::default(),
+            animated_water: false,
+            turn_pace: TurnPace::default(),
+            map: None,
+            script: None,
+            opponent_command: None,
+            time_control: None,
+            best_of: 3,
+            power_ups: false,
+            sound: false,
+            confirm_shots: false,
+            accessibility: false,
+            coordinate_format: CoordinateFormat::Letter,
+            handicap: Handicap::default(),
+            locale: None,
+            server: "127.0.0.1:8080".to_string(),
+            server_certificate: None,
+            keybindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl FromStr for Config {
+    type Err = Error;
+
+    /// Parses a configuration from the content of a TOML file.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        toml::from_str::<Self>(s)?.validate()
+    }
+}
+
+/// The fleet layouts saved by the player, by name.
+///
+/// Layouts are stored in the `layouts.toml` file as fleet codes, like:
+///
+/// ```toml
+/// my-corner-layout = "A1H/A3H/A5H/A7H/A9H"
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct LayoutPresets(BTreeMap<String, String>);
+
+impl LayoutPresets {
+    const FILE_NAME: &'static str = "layouts.toml";
+
+    /// Returns the path of the layouts file, if a config directory can be found.
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads the layouts from the default path.
+    ///
+    /// If there is no layouts file, no layout is returned.
+    pub fn load() -> Result<Self, Error> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads the layouts from the given file.
+    ///
+    /// If the file doesn't exist, no layout is returned.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the layouts to the default path.
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&Self::path().ok_or_else(no_config_dir)?)
+    }
+
+    /// Saves the layouts to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+
+    /// Returns the names of the saved layouts, in alphabetical order.
+    pub fn names(&self) -> Vec<&str> {
+        self.0.keys().map(String::as_str).collect()
+    }
+
+    /// Returns the layout saved with the given name.
+    ///
+    /// `None` is returned if there is no such layout, or if it has been saved with an invalid code.
+    pub fn get(&self, name: &str) -> Option<FleetLayout> {
+        self.0
+            .get(name)
+            .and_then(|code| FleetLayout::from_str(code).ok())
+    }
+
+    /// Saves a layout with the given name, replacing any other layout with the same name.
+    pub fn insert(&mut self, name: &str, layout: &FleetLayout) {
+        self.0.insert(name.to_string(), layout.to_string());
+    }
+}
+
+/// The results of a local player, as kept by the [Leaderboard].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerRecord {
+    /// The number of games played to the end.
+    pub games: u32,
+
+    /// The number of games won.
+    pub wins: u32,
+
+    /// The shots fired in all the games won.
+    pub shots_to_win: u32,
+}
+
+impl PlayerRecord {
+    /// Returns the fraction of the games won, between 0 and 1.
+    pub fn win_rate(&self) -> f64 {
+        if self.games == 0 {
+            0.0
+        } else {
+            f64::from(self.wins) / f64::from(self.games)
+        }
+    }
+
+    /// Returns the average number of shots fired to win a game, if any game has been won.
+    pub fn average_shots_to_win(&self) -> Option<f64> {
+        (self.wins > 0).then(|| f64::from(self.shots_to_win) / f64::from(self.wins))
+    }
+}
+
+/// The results of the local players, by name.
+///
+/// Every player name is a profile: the results are stored in the `leaderboard.toml` file like:
+///
+/// ```toml
+/// [Ishmael]
+/// games = 4
+/// wins = 3
+/// shots_to_win = 171
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Leaderboard(BTreeMap<String, PlayerRecord>);
+
+impl Leaderboard {
+    const FILE_NAME: &'static str = "leaderboard.toml";
+    const CSV_FILE_NAME: &'static str = "leaderboard.csv";
+
+    /// Returns the path of the leaderboard file, if a config directory can be found.
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads the leaderboard from the default path.
+    ///
+    /// If there is no leaderboard file, an empty leaderboard is returned.
+    pub fn load() -> Result<Self, Error> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads the leaderboard from the given file.
+    ///
+    /// If the file doesn't exist, an empty leaderboard is returned.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the leaderboard to the default path.
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&Self::path().ok_or_else(no_config_dir)?)
+    }
+
+    /// Saves the leaderboard to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+
+    /// Returns the results of the given player, if they have played any game.
+    pub fn get(&self, name: &str) -> Option<&PlayerRecord> {
+        self.0.get(name)
+    }
+
+    /// Adds the result of a game to the record of the given player.
+    ///
+    /// The shots fired are counted only for the games won.
+    pub fn record(&mut self, name: &str, won: bool, shots: u32) {
+        let record = self.0.entry(name.to_string()).or_default();
+        record.games += 1;
+        if won {
+            record.wins += 1;
+            record.shots_to_win += shots;
+        }
+    }
+
+    /// Returns the players ranked by win rate, then by the fewest average shots to win.
+    pub fn ranking(&self) -> Vec<(&str, &PlayerRecord)> {
+        let mut ranking: Vec<_> = self.0.iter().map(|(n, r)| (n.as_str(), r)).collect();
+        ranking.sort_by(|(_, a), (_, b)| {
+            b.win_rate().total_cmp(&a.win_rate()).then_with(|| {
+                let shots = |r: &PlayerRecord| r.average_shots_to_win().unwrap_or(f64::MAX);
+                shots(a).total_cmp(&shots(b))
+            })
+        });
+
+        ranking
+    }
+
+    /// Returns the ranking as CSV, with a header line.
+    pub fn to_csv(&self) -> String {
+        let mut csv = String::from("rank,player,games,wins,win_rate,average_shots_to_win\n");
+        for (i, (name, record)) in self.ranking().into_iter().enumerate() {
+            let shots = record
+                .average_shots_to_win()
+                .map(|shots| format!("{shots:.1}"))
+                .unwrap_or_default();
+            csv.push_str(&format!(
+                "{},{},{},{},{:.3},{shots}\n",
+                i + 1,
+                csv_field(name),
+                record.games,
+                record.wins,
+                record.win_rate(),
+            ));
+        }
+
+        csv
+    }
+
+    /// Exports the ranking as CSV in the `leaderboard.csv` file, next to the leaderboard file.
+    ///
+    /// The path of the exported file is returned.
+    pub fn export_csv(&self) -> Result<PathBuf, Error> {
+        let path = Config::dir()
+            .ok_or_else(no_config_dir)?
+            .join(Self::CSV_FILE_NAME);
+        self.export_csv_to(&path)?;
+
+        Ok(path)
+    }
+
+    /// Exports the ranking as CSV in the given file, creating its folder if needed.
+    pub fn export_csv_to(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+
+        fs::write(path, self.to_csv())?;
+        Ok(())
+    }
+}
+
+/// The result of a daily challenge, as kept by [DailyResults].
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct DailyResult {
+    /// Whether the player has won the battle.
+    pub won: bool,
+
+    /// The shots fired by the player.
+    pub shots: u32,
+
+    /// The par of the challenge.
+    pub par: u32,
+}
+
+/// The results of the daily challenges, by date.
+///
+/// Only the first game of every day counts: the results are stored in the `daily.toml` file like:
+///
+/// ```toml
+/// [2024-02-29]
+/// won = true
+/// shots = 52
+/// par = 61
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct DailyResults(BTreeMap<String, DailyResult>);
+
+impl DailyResults {
+    const FILE_NAME: &'static str = "daily.toml";
+
+    /// Returns the path of the daily results file, if a config directory can be found.
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads the daily results from the default path.
+    ///
+    /// If there is no daily results file, no result is returned.
+    pub fn load() -> Result<Self, Error> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads the daily results from the given file.
+    ///
+    /// If the file doesn't exist, no result is returned.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the daily results to the default path.
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&Self::path().ok_or_else(no_config_dir)?)
+    }
+
+    /// Saves the daily results to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+
+    /// Returns the result of the challenge of the given date, like `"2024-02-29"`.
+    pub fn get(&self, date: &str) -> Option<&DailyResult> {
+        self.0.get(date)
+    }
+
+    /// Records the result of the challenge of the given date.
+    ///
+    /// Only the first result of a date is kept: `false` is returned if the date already has one.
+    pub fn record(&mut self, date: &str, result: DailyResult) -> bool {
+        if self.0.contains_key(date) {
+            return false;
+        }
+
+        self.0.insert(date.to_string(), result);
+        true
+    }
+}
+
+/// The number of battles won in every campaign, by campaign identifier.
+///
+/// The progress is stored in the `campaign.toml` file like:
+///
+/// ```toml
+/// coastal-patrol = 2
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct CampaignProgress(BTreeMap<String, usize>);
+
+impl CampaignProgress {
+    const FILE_NAME: &'static str = "campaign.toml";
+
+    /// Returns the path of the campaign progress file, if a config directory can be found.
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads the campaign progress from the default path.
+    ///
+    /// If there is no campaign progress file, no battle has been won yet.
+    pub fn load() -> Result<Self, Error> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads the campaign progress from the given file.
+    ///
+    /// If the file doesn't exist, no battle has been won yet.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the campaign progress to the default path.
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&Self::path().ok_or_else(no_config_dir)?)
+    }
+
+    /// Saves the campaign progress to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+
+    /// Returns the number of battles won in the given campaign.
+    pub fn won(&self, campaign: &str) -> usize {
+        self.0.get(campaign).copied().unwrap_or_default()
+    }
+
+    /// Records the victory in the battle at the given position of the campaign.
+    ///
+    /// Battles are won in order: winning again a battle already won doesn't change the progress.
+    pub fn record_win(&mut self, campaign: &str, battle: usize) {
+        let won = self.0.entry(campaign.to_string()).or_default();
+        *won = (*won).max(battle + 1);
+    }
+
+    /// Starts the given campaign again from its first battle.
+    pub fn reset(&mut self, campaign: &str) {
+        self.0.remove(campaign);
+    }
+}
+
+/// How many times every local player has placed a ship on every cell, by name.
+///
+/// The learning computer player fires first where the player usually places their ships. The
+/// habits are stored in the `habits.toml` file, with a count for every cell from `A1` to `J10`,
+/// row by row, like:
+///
+/// ```toml
+/// Ishmael = [3, 3, 3, 2, 0, 0, 1, 0, 0, 0, ...]
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PlacementHabits(BTreeMap<String, Vec<u32>>);
+
+impl PlacementHabits {
+    const FILE_NAME: &'static str = "habits.toml";
+
+    /// Returns the path of the habits file, if a config directory can be found.
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads the habits from the default path.
+    ///
+    /// If there is no habits file, no fleet has been placed yet.
+    pub fn load() -> Result<Self, Error> {
+        match Self::path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Loads the habits from the given file.
+    ///
+    /// If the file doesn't exist, no fleet has been placed yet.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(toml::from_str(&content)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the habits to the default path.
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&Self::path().ok_or_else(no_config_dir)?)
+    }
+
+    /// Saves the habits to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+
+    /// Returns the habits of the given player, or no habit if they have never placed a fleet.
+    pub fn get(&self, name: &str) -> &[u32] {
+        self.0.get(name).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// Counts the cells occupied by the fleet in the habits of the given player.
+    pub fn record(&mut self, name: &str, fleet: &Fleet) {
+        let habits = self.0.entry(name.to_string()).or_default();
+        habits.resize(100, 0);
+        for cell in fleet.as_ref().iter().flat_map(|ship| ship.occupied_cells()) {
+            habits[10 * usize::from(cell.y()) + usize::from(cell.x())] += 1;
+        }
+    }
+}
+
+/// Replays are saved as TOML documents, like:
+///
+/// ```toml
+/// players = ["Ishmael", "Computer"]
+/// fleets = ["A1H/A3H/A5H/A7H/A9H", "A1V/C1V/E1V/G1V/I1V"]
+///
+/// [[shots]]
+/// turn = 1
+/// shooter = "human"
+/// cell = "E5"
+/// ```
+impl Replay {
+    const FILE_NAME: &'static str = "replay.toml";
+
+    /// Returns the path of the replay of the last battle, if a config directory can be found.
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads a replay from the given file.
+    pub fn load_from(path: &Path) -> Result<Self, Error> {
+        Ok(toml::from_str(&fs::read_to_string(path)?)?)
+    }
+
+    /// Saves the replay as the one of the last battle.
+    pub fn save(&self) -> Result<(), Error> {
+        self.save_to(&Self::path().ok_or_else(no_config_dir)?)
+    }
+
+    /// Saves the replay to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+}
+
+impl Correspondence {
+    const FILE_NAME: &'static str = "correspondence.toml";
+
+    /// Returns the path of the game by correspondence in progress, if a config directory can
+    /// be found.
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads the game by correspondence in progress from the given file, or `None` if there is
+    /// no game in progress.
+    pub fn load_from(path: &Path) -> Result<Option<Self>, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(toml::from_str(&content)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the game to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+
+    /// Removes the game from the given file, once it is over.
+    pub fn remove_from(path: &Path) -> Result<(), Error> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Session {
+    const FILE_NAME: &'static str = "network.toml";
+
+    /// Returns the path of the network session of the player, if a config directory can be
+    /// found.
+    pub fn path() -> Option<PathBuf> {
+        Config::dir().map(|dir| dir.join(Self::FILE_NAME))
+    }
+
+    /// Loads the network session from the given file, or `None` if the player has no duel to
+    /// rejoin.
+    pub fn load_from(path: &Path) -> Result<Option<Self>, Error> {
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(toml::from_str(&content)?)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Saves the session to the given file, creating its folder if needed.
+    pub fn save_to(&self, path: &Path) -> Result<(), Error> {
+        write_toml(path, self)
+    }
+
+    /// Removes the session from the given file, once its duel is over.
+    pub fn remove_from(path: &Path) -> Result<(), Error> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Quotes a CSV field when it contains a separator, a quote or a line break.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn no_config_dir() -> Error {
+    io::Error::new(io::ErrorKind::NotFound, "no config directory available").into()
+}
+
+// Writes the value as a TOML document, creating the folder of the file if needed.
+fn write_toml<T: Serialize>(path: &Path, value: &T) -> Result<(), Error> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    fs::write(path, toml::to_string(value)?)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_empty_config_is_default() {
+        assert_eq!(Config::from_str("").unwrap(), Config::default());
+    }
+
+    #[rstest]
+    fn test_partial_config() {
+        let config = Config::from_str(
+            r#"
+            player_name = "Ishmael"
+            difficulty = "easy"
+
+            [keybindings]
+            quit = "x"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.player_name, "Ishmael");
+        assert_eq!(config.mode, GameMode::Battle);
+        assert_eq!(config.variant, Variant::Classic);
+        assert_eq!(config.handicap, Handicap::None);
+        assert_eq!(config.difficulty, Difficulty::Easy);
+        assert_eq!(config.solver_budget, 100_000);
+        assert_eq!(config.adaptation, Adaptation::Moderate);
+        assert_eq!(config.theme, Theme::Classic);
+        assert!(!config.animated_water);
+        assert_eq!(config.turn_pace, TurnPace::Instant);
+        assert!(!config.sound);
+        assert!(!config.confirm_shots);
+        assert!(!config.accessibility);
+        assert_eq!(config.coordinate_format, CoordinateFormat::Letter);
+        assert_eq!(config.locale, None);
+        assert_eq!(config.server, "127.0.0.1:8080");
+        assert_eq!(config.server_certificate, None);
+        assert_eq!(config.keybindings.quit, 'x');
+        assert_eq!(config.keybindings.horizontal, 'h');
+    }
+
+    #[rstest]
+    #[case("difficulty = \"impossible\"")]
+    #[case("mode = \"tournament\"")]
+    #[case("variant = \"chess\"")]
+    #[case("handicap = \"extra_battleship\"")]
+    #[case("solver_budget = -1")]
+    #[case("adaptation = \"extreme\"")]
+    #[case("turn_pace = \"slow\"")]
+    #[case("locale = \"fr\"")]
+    #[case("player_name = ")]
+    fn test_invalid_config(#[case] content: &str) {
+        assert!(matches!(Config::from_str(content), Err(Error::Parse(_))));
+    }
+
+    #[rstest]
+    #[case(0)]
+    #[case(4)]
+    fn test_invalid_series_length(#[case] best_of: u8) {
+        assert!(matches!(
+            Config::from_str(&format!("best_of = {best_of}")),
+            Err(Error::InvalidSeriesLength(n)) if n == best_of
+        ));
+    }
+
+    #[rstest]
+    fn test_save_and_load() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-{}", std::process::id()))
+            .join(Config::FILE_NAME);
+        let config = Config {
+            player_name: "Ahab".to_string(),
+            theme: Theme::HighContrast,
+            ..Config::default()
+        };
+
+        config.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, config);
+    }
+
+    #[rstest]
+    fn test_load_missing_file() {
+        let path = env::temp_dir()
+            .join("naval-test-missing")
+            .join(Config::FILE_NAME);
+        assert_eq!(Config::load_from(&path).unwrap(), Config::default());
+    }
+
+    #[rstest]
+    fn test_map() {
+        let dir = env::temp_dir().join(format!("naval-test-maps-{}", std::process::id()));
+        let maps = dir.join(Config::MAPS_DIR_NAME);
+        fs::create_dir_all(&maps).unwrap();
+        fs::write(
+            maps.join("rock.map"),
+            format!("^.........\n{}", "..........\n".repeat(9)),
+        )
+        .unwrap();
+        fs::write(maps.join("broken.map"), "^^^").unwrap();
+        let map = |name: &str| {
+            Config {
+                map: Some(name.to_string()),
+                ..Config::default()
+            }
+            .map_in(Some(&dir))
+        };
+
+        assert_eq!(Config::default().map_in(Some(&dir)).unwrap(), None);
+        assert_eq!(
+            map("archipelago").unwrap(),
+            Some(Map::built_in("archipelago").unwrap())
+        );
+        assert_eq!(map("rock").unwrap().unwrap().islands().len(), 1);
+        assert!(matches!(
+            map("broken"),
+            Err(Error::Map(MapError::WrongRowCount(1)))
+        ));
+        assert!(matches!(
+            map("atlantis"),
+            Err(Error::Map(MapError::Unknown(_)))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[cfg(feature = "scripting")]
+    #[rstest]
+    fn test_script() {
+        let dir = env::temp_dir().join(format!("naval-test-scripts-{}", std::process::id()));
+        let scripts = dir.join(Config::SCRIPTS_DIR_NAME);
+        fs::create_dir_all(&scripts).unwrap();
+        fs::write(scripts.join("corner.rhai"), "fn next_move(view) { \"A1\" }").unwrap();
+        fs::write(scripts.join("broken.rhai"), "fn next_move(view) {").unwrap();
+        let script = |name: &str| {
+            Config {
+                script: Some(name.to_string()),
+                ..Config::default()
+            }
+            .script_in(Some(&dir))
+        };
+
+        assert!(Config::default().script_in(Some(&dir)).unwrap().is_none());
+        assert_eq!(script("corner").unwrap().unwrap().name(), "corner");
+        assert!(matches!(
+            script("broken"),
+            Err(Error::Script(ScriptError::Compile { .. }))
+        ));
+        assert!(matches!(
+            script("skynet"),
+            Err(Error::Script(ScriptError::Unknown(_)))
+        ));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[rstest]
+    fn test_layout_presets() {
+        let mut presets = LayoutPresets::default();
+        assert!(presets.names().is_empty());
+
+        let corner = FleetLayout::from_str("A1H/A3H/A5H/A7H/A9H").unwrap();
+        let columns = FleetLayout::from_str("A1V/C1V/E1V/G1V/I1V").unwrap();
+        presets.insert("my-corner-layout", &corner);
+        presets.insert("columns", &columns);
+
+        assert_eq!(presets.names(), vec!["columns", "my-corner-layout"]);
+        assert_eq!(presets.get("my-corner-layout"), Some(corner));
+        assert_eq!(presets.get("columns"), Some(columns.clone()));
+        assert_eq!(presets.get("missing"), None);
+
+        presets.insert("my-corner-layout", &columns);
+        assert_eq!(presets.get("my-corner-layout"), Some(columns));
+    }
+
+    #[rstest]
+    fn test_layout_presets_save_and_load() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-layouts-{}", std::process::id()))
+            .join(LayoutPresets::FILE_NAME);
+        let mut presets = LayoutPresets::default();
+        presets.insert(
+            "my corner",
+            &FleetLayout::from_str("A1H/A3H/A5H/A7H/A9H").unwrap(),
+        );
+
+        presets.save_to(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let loaded = LayoutPresets::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(content.trim(), "\"my corner\" = \"A1H/A3H/A5H/A7H/A9H\"");
+        assert_eq!(loaded, presets);
+    }
+
+    #[rstest]
+    fn test_leaderboard_ranking() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Ahab", true, 60);
+        leaderboard.record("Ahab", false, 30);
+        leaderboard.record("Ishmael", true, 50);
+        leaderboard.record("Ishmael", false, 40);
+        leaderboard.record("Queequeg", true, 45);
+        leaderboard.record("Starbuck", false, 20);
+
+        let ranking: Vec<_> = leaderboard.ranking().iter().map(|(n, _)| *n).collect();
+        assert_eq!(ranking, vec!["Queequeg", "Ishmael", "Ahab", "Starbuck"]);
+
+        let ahab = leaderboard.get("Ahab").unwrap();
+        assert_eq!(
+            *ahab,
+            PlayerRecord {
+                games: 2,
+                wins: 1,
+                shots_to_win: 60
+            }
+        );
+        assert_eq!(ahab.win_rate(), 0.5);
+        assert_eq!(ahab.average_shots_to_win(), Some(60.0));
+        assert_eq!(
+            leaderboard.get("Starbuck").unwrap().average_shots_to_win(),
+            None
+        );
+        assert_eq!(leaderboard.get("Flask"), None);
+    }
+
+    #[rstest]
+    fn test_leaderboard_csv() {
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Ahab, captain", true, 61);
+        leaderboard.record("Starbuck", false, 20);
+
+        assert_eq!(
+            leaderboard.to_csv(),
+            "rank,player,games,wins,win_rate,average_shots_to_win\n\
+             1,\"Ahab, captain\",1,1,1.000,61.0\n\
+             2,Starbuck,1,0,0.000,\n"
+        );
+    }
+
+    #[rstest]
+    fn test_leaderboard_save_and_load() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-leaderboard-{}", std::process::id()))
+            .join(Leaderboard::FILE_NAME);
+        let mut leaderboard = Leaderboard::default();
+        leaderboard.record("Ishmael", true, 57);
+
+        leaderboard.save_to(&path).unwrap();
+        let loaded = Leaderboard::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, leaderboard);
+    }
+
+    #[rstest]
+    fn test_correspondence_save_load_and_remove() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-correspondence-{}", std::process::id()))
+            .join(Correspondence::FILE_NAME);
+        let fleet = Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap();
+        let side = Correspondence::with_salt(&fleet, [3; 16]);
+
+        assert!(Correspondence::load_from(&path).unwrap().is_none());
+        side.save_to(&path).unwrap();
+        let loaded = Correspondence::load_from(&path).unwrap().unwrap();
+        Correspondence::remove_from(&path).unwrap();
+        Correspondence::remove_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded.opening(), side.opening());
+        assert!(Correspondence::load_from(&path).unwrap().is_none());
+    }
+
+    #[rstest]
+    fn test_network_session_save_load_and_remove() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-network-{}", std::process::id()))
+            .join(Session::FILE_NAME);
+        let session = Session {
+            server: "127.0.0.1:8080".to_string(),
+            id: 7,
+            token: "c0ffee".to_string(),
+        };
+
+        assert!(Session::load_from(&path).unwrap().is_none());
+        session.save_to(&path).unwrap();
+        let loaded = Session::load_from(&path).unwrap();
+        Session::remove_from(&path).unwrap();
+        Session::remove_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, Some(session));
+        assert!(Session::load_from(&path).unwrap().is_none());
+    }
+
+    #[rstest]
+    fn test_replay_save_and_load() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-replay-{}", std::process::id()))
+            .join(Replay::FILE_NAME);
+        let replay: Replay = toml::from_str(
+            r#"
+            players = ["Ishmael", "Computer"]
+            fleets = ["A1H/A3H/A5H/A7H/A9H", "A1V/C1V/E1V/G1V/I1V"]
+
+            [[shots]]
+            turn = 1
+            shooter = "human"
+            cell = "E5"
+            "#,
+        )
+        .unwrap();
+
+        replay.save_to(&path).unwrap();
+        let loaded = Replay::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, replay);
+        assert_eq!(loaded.shots.len(), 1);
+        assert!(Replay::load_from(&path).is_err());
+    }
+
+    #[rstest]
+    fn test_daily_results() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-daily-{}", std::process::id()))
+            .join(DailyResults::FILE_NAME);
+        let first = DailyResult {
+            won: true,
+            shots: 52,
+            par: 61,
+        };
+        let mut results = DailyResults::default();
+
+        assert!(results.record("2024-02-29", first));
+        assert!(!results.record(
+            "2024-02-29",
+            DailyResult {
+                won: false,
+                ..first
+            }
+        ));
+        assert_eq!(results.get("2024-02-29"), Some(&first));
+        assert_eq!(results.get("2024-03-01"), None);
+
+        results.save_to(&path).unwrap();
+        let loaded = DailyResults::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, results);
+    }
+
+    #[rstest]
+    fn test_campaign_progress() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-campaign-{}", std::process::id()))
+            .join(CampaignProgress::FILE_NAME);
+        let mut progress = CampaignProgress::default();
+        assert_eq!(progress.won("coastal-patrol"), 0);
+
+        progress.record_win("coastal-patrol", 0);
+        progress.record_win("coastal-patrol", 1);
+        progress.record_win("coastal-patrol", 0);
+        progress.record_win("open-ocean", 0);
+        assert_eq!(progress.won("coastal-patrol"), 2);
+
+        progress.reset("open-ocean");
+        assert_eq!(progress.won("open-ocean"), 0);
+
+        progress.save_to(&path).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        let loaded = CampaignProgress::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(content.trim(), "coastal-patrol = 2");
+        assert_eq!(loaded, progress);
+    }
+
+    #[rstest]
+    fn test_placement_habits() {
+        let path = env::temp_dir()
+            .join(format!("naval-test-habits-{}", std::process::id()))
+            .join(PlacementHabits::FILE_NAME);
+        let fleet = Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap();
+        let mut habits = PlacementHabits::default();
+        assert!(habits.get("Ishmael").is_empty());
+
+        habits.record("Ishmael", &fleet);
+        habits.record("Ishmael", &fleet);
+        let ishmael = habits.get("Ishmael");
+        assert_eq!(ishmael.len(), 100);
+        assert_eq!(ishmael[0], 2);
+        assert_eq!(ishmael[4], 2);
+        assert_eq!(ishmael[5], 0);
+        assert_eq!(ishmael.iter().sum::<u32>(), 34);
+
+        habits.save_to(&path).unwrap();
+        let loaded = PlacementHabits::load_from(&path).unwrap();
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+
+        assert_eq!(loaded, habits);
+    }
+
+    #[rstest]
+    fn test_layout_presets_with_invalid_code() {
+        let presets: LayoutPresets = toml::from_str("broken = \"A1X\"").unwrap();
+        assert_eq!(presets.names(), vec!["broken"]);
+        assert_eq!(presets.get("broken"), None);
+    }
+}
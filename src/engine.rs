@@ -1,7 +1,27 @@
 //! Engine module for the game, containing core components such as fleet, grid, and player.
 //!
+pub mod action;
+pub mod campaign;
+pub mod clock;
+pub mod correspondence;
+pub mod daily;
+pub mod duel;
 pub mod fleet;
 pub mod game;
 pub mod grid;
+pub mod map;
+pub mod minefield;
+pub mod observer;
+pub mod parallel;
+pub mod placement;
 pub mod player;
-mod strategy;
+pub mod process;
+pub mod puzzle;
+pub mod replay;
+pub mod rules;
+#[cfg(feature = "scripting")]
+pub mod script;
+pub mod series;
+pub mod strategy;
+pub mod tutorial;
+pub mod weather;
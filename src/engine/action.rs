@@ -0,0 +1,179 @@
+//! Actions a player can take in a turn.
+//!
+//! The basic action is a single [shot](PlayerAction::Shot). When the game has power-ups, a
+//! player can also use the special weapons granted by its [PowerUps], like a
+//! [bombardment](PlayerAction::Bombard) which shoots a whole 3x3 area at once, or a
+//! [scan](PlayerAction::Scan) which detects the ships in a 3x3 area without damaging them.
+//!
+//! When the rules allow it, a player can also [relocate](PlayerAction::Relocate) their
+//! Submarine instead of firing.
+//!
+use crate::engine::fleet::{ShipKind, ShipOrientation};
+use crate::engine::grid::Cell;
+
+/// The action of a player in a turn.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum PlayerAction {
+    /// Shoots a single cell.
+    Shot(Cell),
+
+    /// Shoots the 3x3 area centered on the cell. It needs a bombardment power-up.
+    Bombard(Cell),
+
+    /// Reveals whether a ship occupies the 3x3 area centered on the cell, without shooting.
+    /// It needs a scan power-up.
+    Scan(Cell),
+
+    /// Moves the Submarine of the player to start from the cell, with the given orientation,
+    /// without shooting. It is allowed only by some rules.
+    Relocate(Cell, ShipOrientation),
+}
+
+impl PlayerAction {
+    /// Returns the cell the action is aimed at.
+    pub fn target(&self) -> Cell {
+        match self {
+            PlayerAction::Shot(cell)
+            | PlayerAction::Bombard(cell)
+            | PlayerAction::Scan(cell)
+            | PlayerAction::Relocate(cell, _) => *cell,
+        }
+    }
+
+    /// Returns the cells shot or scanned by the action, from the top-left one, or the cells
+    /// the relocated Submarine would occupy.
+    ///
+    /// Only the cells on the board are returned, so a bombardment on the edge shoots 6 cells,
+    /// and one in a corner only 4. A Submarine which exceeds the board occupies no cell.
+    pub fn cells(&self) -> Vec<Cell> {
+        match self {
+            PlayerAction::Shot(cell) => vec![*cell],
+            PlayerAction::Bombard(cell) | PlayerAction::Scan(cell) => area(cell),
+            PlayerAction::Relocate(cell, orientation) => ShipKind::Submarine
+                .ship(*cell, *orientation)
+                .map(|ship| ship.occupied_cells().to_vec())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The special weapons a side can use during a game.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct PowerUps {
+    /// The number of bombardments left.
+    pub bombardments: u8,
+
+    /// The number of scans left.
+    pub scans: u8,
+}
+
+impl PowerUps {
+    /// The power-ups of every side when they are enabled: one bombardment and one scan per
+    /// game.
+    pub const STANDARD: PowerUps = PowerUps {
+        bombardments: 1,
+        scans: 1,
+    };
+
+    /// Returns `true` when the power-up needed by the action is available.
+    ///
+    /// A single shot and a relocation need no power-up, so they are always available.
+    pub fn allows(&self, action: &PlayerAction) -> bool {
+        match action {
+            PlayerAction::Shot(_) | PlayerAction::Relocate(..) => true,
+            PlayerAction::Bombard(_) => self.bombardments > 0,
+            PlayerAction::Scan(_) => self.scans > 0,
+        }
+    }
+
+    /// Uses the power-up needed by the action, if any.
+    pub(crate) fn consume(&mut self, action: &PlayerAction) {
+        match action {
+            PlayerAction::Shot(_) | PlayerAction::Relocate(..) => {}
+            PlayerAction::Bombard(_) => self.bombardments = self.bombardments.saturating_sub(1),
+            PlayerAction::Scan(_) => self.scans = self.scans.saturating_sub(1),
+        }
+    }
+}
+
+// Returns the cells of the 3x3 area centered on the cell, which are on the board.
+fn area(center: &Cell) -> Vec<Cell> {
+    (-1..=1)
+        .flat_map(|dy| (-1..=1).map(move |dx| (dx, dy)))
+        .filter_map(|(dx, dy)| center.offset(dx, dy))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[rstest]
+    #[case("E5", 9)]
+    #[case("A5", 6)]
+    #[case("J10", 4)]
+    fn test_bombard_cells(#[case] target: &str, #[case] count: usize) {
+        let target = Cell::from_str(target).unwrap();
+        let cells = PlayerAction::Bombard(target).cells();
+        assert_eq!(cells.len(), count);
+        assert!(cells.contains(&target));
+        assert!(cells.iter().all(|cell| {
+            cell.x().abs_diff(target.x()) <= 1 && cell.y().abs_diff(target.y()) <= 1
+        }));
+    }
+
+    #[rstest]
+    fn test_shot_cells() {
+        let target = Cell::bounded(3, 4);
+        assert_eq!(PlayerAction::Shot(target).cells(), vec![target]);
+        assert_eq!(PlayerAction::Shot(target).target(), target);
+    }
+
+    #[rstest]
+    fn test_relocate_cells() {
+        let target = Cell::bounded(3, 4);
+        let relocate = PlayerAction::Relocate(target, ShipOrientation::Vertical);
+        assert_eq!(relocate.target(), target);
+        assert_eq!(
+            relocate.cells(),
+            [target, Cell::bounded(3, 5), Cell::bounded(3, 6)]
+        );
+        assert!(PowerUps::default().allows(&relocate));
+
+        let outside = PlayerAction::Relocate(Cell::bounded(8, 0), ShipOrientation::Horizontal);
+        assert!(outside.cells().is_empty());
+    }
+
+    #[rstest]
+    fn test_power_ups() {
+        let shot = PlayerAction::Shot(Cell::bounded(0, 0));
+        let bombard = PlayerAction::Bombard(Cell::bounded(0, 0));
+        let mut power_ups = PowerUps::STANDARD;
+        assert!(power_ups.allows(&shot));
+        assert!(power_ups.allows(&bombard));
+
+        power_ups.consume(&shot);
+        assert_eq!(power_ups, PowerUps::STANDARD);
+        power_ups.consume(&bombard);
+        assert!(power_ups.allows(&shot));
+        assert!(!power_ups.allows(&bombard));
+        assert!(!PowerUps::default().allows(&bombard));
+    }
+
+    #[rstest]
+    fn test_scan_power_up() {
+        let scan = PlayerAction::Scan(Cell::bounded(4, 4));
+        let mut power_ups = PowerUps::STANDARD;
+        assert!(power_ups.allows(&scan));
+        assert_eq!(
+            scan.cells(),
+            PlayerAction::Bombard(Cell::bounded(4, 4)).cells()
+        );
+
+        power_ups.consume(&scan);
+        assert!(!power_ups.allows(&scan));
+        assert_eq!(power_ups.bombardments, 1);
+    }
+}
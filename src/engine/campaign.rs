@@ -0,0 +1,130 @@
+//! Campaigns: sequences of battles with increasing difficulty.
+//!
+//! A [Campaign] is a fixed sequence of [CampaignBattle]s. Every battle is a single game against
+//! the computer, which gets harder as the campaign goes on: the computer plays at a higher
+//! [Difficulty], and the human fleet starts the battle with some damaged cells as a handicap.
+//!
+//! The battles must be won in order: the progress of the player is kept by the application.
+//!
+use crate::engine::game::Difficulty;
+
+/// A battle of a campaign.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CampaignBattle {
+    /// The name of the battle.
+    pub name: &'static str,
+
+    /// The difficulty of the computer player.
+    pub difficulty: Difficulty,
+
+    /// The number of cells of the human fleet damaged before the battle starts.
+    pub handicap: u8,
+}
+
+impl CampaignBattle {
+    const fn new(name: &'static str, difficulty: Difficulty, handicap: u8) -> Self {
+        Self {
+            name,
+            difficulty,
+            handicap,
+        }
+    }
+}
+
+/// A sequence of battles to win in order.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Campaign {
+    /// The identifier of the campaign, used to keep the progress of the player.
+    pub id: &'static str,
+
+    /// The name of the campaign.
+    pub name: &'static str,
+
+    /// The battles of the campaign, from the first to the last one.
+    pub battles: &'static [CampaignBattle],
+}
+
+impl Campaign {
+    /// The campaigns available in the game, from the easiest one.
+    pub const ALL: [Campaign; 2] = [
+        Campaign {
+            id: "coastal-patrol",
+            name: "Coastal Patrol",
+            battles: &[
+                CampaignBattle::new("Harbour Skirmish", Difficulty::Easy, 0),
+                CampaignBattle::new("Reef Ambush", Difficulty::Easy, 2),
+                CampaignBattle::new("Lighthouse Siege", Difficulty::Normal, 0),
+            ],
+        },
+        Campaign {
+            id: "open-ocean",
+            name: "Open Ocean",
+            battles: &[
+                CampaignBattle::new("Convoy Escort", Difficulty::Easy, 3),
+                CampaignBattle::new("Storm Front", Difficulty::Normal, 2),
+                CampaignBattle::new("Wolf Pack", Difficulty::Normal, 4),
+                CampaignBattle::new("The Last Stand", Difficulty::Normal, 6),
+            ],
+        },
+    ];
+
+    /// Returns the campaign with the given identifier.
+    pub fn find(id: &str) -> Option<Campaign> {
+        Self::ALL.into_iter().find(|campaign| campaign.id == id)
+    }
+
+    /// Returns the battle at the given position, starting from 0.
+    pub fn battle(&self, index: usize) -> Option<&CampaignBattle> {
+        self.battles.get(index)
+    }
+
+    /// Returns the position of the battle to play after the given number of battles won.
+    ///
+    /// Once the campaign is complete, its last battle can be played again.
+    pub fn next_battle(&self, won: usize) -> usize {
+        won.min(self.battles.len() - 1)
+    }
+
+    /// Returns `true` when the given number of battles won completes the campaign.
+    pub fn is_complete(&self, won: usize) -> bool {
+        won >= self.battles.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_campaigns_get_harder() {
+        for campaign in Campaign::ALL {
+            assert!(!campaign.battles.is_empty());
+            assert_eq!(Campaign::find(campaign.id), Some(campaign));
+            for pair in campaign.battles.windows(2) {
+                assert!(
+                    pair[0].difficulty <= pair[1].difficulty,
+                    "{} gets easier",
+                    campaign.name
+                );
+            }
+        }
+
+        assert_eq!(Campaign::find("missing"), None);
+    }
+
+    #[rstest]
+    #[case(0, 0, false)]
+    #[case(2, 2, false)]
+    #[case(3, 2, true)]
+    #[case(7, 2, true)]
+    fn test_next_battle(#[case] won: usize, #[case] next: usize, #[case] complete: bool) {
+        let campaign = Campaign::ALL[0];
+        assert_eq!(campaign.next_battle(won), next);
+        assert_eq!(campaign.is_complete(won), complete);
+        assert_eq!(
+            campaign.battle(next).unwrap().name,
+            campaign.battles[next].name
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Chess-clock style time controls.
+//!
+//! When a game has a time control, every side has a [Clock] with the same amount of time. The
+//! clock of a side runs only while that side is choosing its move, and a side whose clock
+//! expires loses the game.
+//!
+//! The clocks read the time from [Instant], which is the one of the standard library, or the one
+//! of the browser on `wasm32-unknown-unknown`, where the standard library has no clock.
+//!
+use std::time::Duration;
+
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub use std::time::Instant;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use web_time::Instant;
+
+/// The time left to a side, which decrements while the clock is running.
+///
+/// Every method takes the current instant, so that the clock doesn't depend on the system time
+/// and can be driven by the caller.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Clock {
+    remaining: Duration,
+    started: Option<Instant>,
+}
+
+impl Clock {
+    /// Creates a stopped clock with the given time.
+    pub fn new(time: Duration) -> Self {
+        Self {
+            remaining: time,
+            started: None,
+        }
+    }
+
+    /// Starts the clock. Nothing happens if it is already running.
+    pub fn start(&mut self, now: Instant) {
+        self.started.get_or_insert(now);
+    }
+
+    /// Stops the clock, keeping the time left.
+    pub fn stop(&mut self, now: Instant) {
+        self.remaining = self.remaining(now);
+        self.started = None;
+    }
+
+    /// Returns `true` if the clock is running.
+    pub fn is_running(&self) -> bool {
+        self.started.is_some()
+    }
+
+    /// Returns the time left at the given instant.
+    pub fn remaining(&self, now: Instant) -> Duration {
+        match self.started {
+            Some(started) => self
+                .remaining
+                .saturating_sub(now.saturating_duration_since(started)),
+            None => self.remaining,
+        }
+    }
+
+    /// Returns `true` if there is no time left at the given instant.
+    pub fn is_expired(&self, now: Instant) -> bool {
+        self.remaining(now).is_zero()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_clock_runs_only_when_started() {
+        let now = Instant::now();
+        let mut clock = Clock::new(Duration::from_secs(60));
+        assert!(!clock.is_running());
+        assert_eq!(
+            clock.remaining(now + Duration::from_secs(10)),
+            Duration::from_secs(60)
+        );
+
+        clock.start(now);
+        assert!(clock.is_running());
+        assert_eq!(
+            clock.remaining(now + Duration::from_secs(10)),
+            Duration::from_secs(50)
+        );
+
+        clock.stop(now + Duration::from_secs(10));
+        assert!(!clock.is_running());
+        assert_eq!(
+            clock.remaining(now + Duration::from_secs(30)),
+            Duration::from_secs(50)
+        );
+    }
+
+    #[rstest]
+    fn test_clock_start_twice() {
+        let now = Instant::now();
+        let mut clock = Clock::new(Duration::from_secs(60));
+        clock.start(now);
+        clock.start(now + Duration::from_secs(20));
+
+        assert_eq!(
+            clock.remaining(now + Duration::from_secs(30)),
+            Duration::from_secs(30)
+        );
+    }
+
+    #[rstest]
+    fn test_clock_expires() {
+        let now = Instant::now();
+        let mut clock = Clock::new(Duration::from_secs(60));
+        clock.start(now);
+
+        assert!(!clock.is_expired(now + Duration::from_secs(59)));
+        assert!(clock.is_expired(now + Duration::from_secs(60)));
+        assert_eq!(
+            clock.remaining(now + Duration::from_secs(90)),
+            Duration::ZERO
+        );
+    }
+}
@@ -0,0 +1,903 @@
+//! Play by correspondence: a battle between two players, each one with their own client,
+//! exchanging short codes over a chat or an email instead of a live connection.
+//!
+//! A [Correspondence] is the side of a player. It starts with an opening code, which holds a
+//! commitment of the hidden fleet of the player: a salted hash of its layout, which doesn't tell
+//! where the ships are. Once both players have pasted the opening code of their opponent, the
+//! one with the lowest commitment fires first.
+//!
+//! Every turn is a code too, holding the report of the last shot of the opponent, and the shot
+//! of the player. The shots are numbered, so that a code pasted twice or out of order is
+//! refused. When the fleet of a player is sunk, their client makes a last code which reports the
+//! sinking shot and reveals the fleet with its salt, and the game is over. The winner reveals
+//! their fleet in turn.
+//!
+//! Once the fleet of the opponent is revealed, a side [verifies](Correspondence::verify) it: the
+//! fleet must match the commitment of the opening, so that it can't have been moved during the
+//! battle, and every shot must have been reported as it hit the fleet. A player who never sends
+//! their last code can't be verified, though.
+//!
+//! A code is the base64 encoding of a few bytes: a version, the kind of the message, then the
+//! commitment for an opening, the number of the turn, the report and the shot for a turn, or the
+//! number of the turn, the report, the salt and the fleet layout for a reveal.
+//!
+use crate::engine::fleet::{Fleet, FleetError, FleetLayout, ShipKind, ShipOrientation};
+use crate::engine::grid::{Cell, CellState, Grid};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Represents a correspondence error.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum CorrespondenceError {
+    /// The code can't be decoded.
+    #[error("{0} is not a valid code")]
+    InvalidCode(String),
+
+    /// The code has been made by this side.
+    #[error("this is your own code, paste the one of your opponent")]
+    OwnCode,
+
+    /// The code is valid, but not the one expected now, like an opening during the battle.
+    #[error("this code was not expected now")]
+    UnexpectedCode,
+
+    /// The code is not the next one of the opponent, like a code pasted twice.
+    #[error("this is the code of turn {found}, but the one of turn {expected} was expected")]
+    OutOfOrder { expected: u16, found: u16 },
+
+    /// The player fired while waiting for the opponent.
+    #[error("it is not your turn, paste the code of your opponent")]
+    NotYourTurn,
+
+    /// The player has already fired at the cell.
+    #[error("you have already fired at {0}")]
+    AlreadyShot(Cell),
+
+    /// The game is over.
+    #[error("the game is over")]
+    Over,
+
+    /// The saved fleet of the player is not valid.
+    #[error(transparent)]
+    Fleet(#[from] FleetError),
+}
+
+/// An inconsistency between the fleet revealed by the opponent and the game.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum Inconsistency {
+    /// The revealed fleet is not the one of the commitment: it has been changed.
+    #[error("the revealed fleet doesn't match the commitment of the opening")]
+    Commitment,
+
+    /// The revealed fleet is not valid.
+    #[error("the revealed fleet is not valid: {0}")]
+    Fleet(FleetError),
+
+    /// A shot has not been reported as it hit the revealed fleet.
+    #[error("the shot at {cell} was reported as {reported}, but it was {actual}")]
+    Report {
+        cell: Cell,
+        reported: Report,
+        actual: Report,
+    },
+}
+
+/// The result of a shot, as reported by the side which has been shot at.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Report {
+    Miss,
+    Hit,
+    Sunk(ShipKind),
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Report::Miss => write!(f, "a miss"),
+            Report::Hit => write!(f, "a hit"),
+            Report::Sunk(kind) => write!(f, "{kind} sunk"),
+        }
+    }
+}
+
+/// A message from a side to the other, exchanged as a short code.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Message {
+    /// The opening of a side, with the commitment of its fleet.
+    Opening([u8; 32]),
+
+    /// A turn of a side, numbered from 1: the report of the last shot of the opponent, if any,
+    /// and the shot of the side.
+    Turn {
+        number: u16,
+        report: Option<Report>,
+        shot: Cell,
+    },
+
+    /// The last message of a side, numbered like a turn: the report of the last shot of the
+    /// opponent, if any, and the fleet of the side with the salt of its commitment.
+    Reveal {
+        number: u16,
+        report: Option<Report>,
+        salt: [u8; 16],
+        layout: FleetLayout,
+    },
+}
+
+impl Message {
+    const VERSION: u8 = 2;
+    const OPENING: u8 = b'O';
+    const TURN: u8 = b'T';
+    const REVEAL: u8 = b'R';
+    const NONE: u8 = u8::MAX;
+    // The bit of a ship placement set for a vertical ship.
+    const VERTICAL: u8 = 0x80;
+
+    /// Encodes the message as a code made of URL-safe base64 characters.
+    pub fn encode(&self) -> String {
+        let mut bytes = vec![Self::VERSION];
+        match self {
+            Message::Opening(commitment) => {
+                bytes.push(Self::OPENING);
+                bytes.extend(commitment);
+            }
+            Message::Turn {
+                number,
+                report,
+                shot,
+            } => {
+                bytes.push(Self::TURN);
+                bytes.extend(number.to_be_bytes());
+                bytes.push(Self::encode_report(report));
+                bytes.push(Self::encode_cell(shot));
+            }
+            Message::Reveal {
+                number,
+                report,
+                salt,
+                layout,
+            } => {
+                bytes.push(Self::REVEAL);
+                bytes.extend(number.to_be_bytes());
+                bytes.push(Self::encode_report(report));
+                bytes.extend(salt);
+                bytes.extend(layout.placements().iter().map(
+                    |(cell, orientation)| match orientation {
+                        ShipOrientation::Horizontal => Self::encode_cell(cell),
+                        ShipOrientation::Vertical => Self::encode_cell(cell) | Self::VERTICAL,
+                    },
+                ));
+            }
+        }
+
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    fn encode_report(report: &Option<Report>) -> u8 {
+        match report {
+            None => Self::NONE,
+            Some(Report::Miss) => 0,
+            Some(Report::Hit) => 1,
+            Some(Report::Sunk(kind)) => {
+                2 + Fleet::COMPOSITION.iter().position(|k| k == kind).unwrap() as u8
+            }
+        }
+    }
+
+    fn decode_report(byte: u8) -> Option<Option<Report>> {
+        match byte {
+            Self::NONE => Some(None),
+            0 => Some(Some(Report::Miss)),
+            1 => Some(Some(Report::Hit)),
+            n => Fleet::COMPOSITION
+                .get(usize::from(n - 2))
+                .map(|kind| Some(Report::Sunk(kind.clone()))),
+        }
+    }
+
+    fn encode_cell(cell: &Cell) -> u8 {
+        cell.y() * 10 + cell.x()
+    }
+
+    fn decode_cell(byte: u8) -> Option<Cell> {
+        Cell::new(byte % 10, byte / 10).ok()
+    }
+}
+
+impl FromStr for Message {
+    type Err = CorrespondenceError;
+
+    /// Decodes a code made by [Message::encode]. The spaces around the code are ignored.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = s.trim();
+        let invalid = || CorrespondenceError::InvalidCode(code.to_string());
+        let bytes = URL_SAFE_NO_PAD.decode(code).map_err(|_| invalid())?;
+        match bytes.as_slice() {
+            [Self::VERSION, Self::OPENING, commitment @ ..] => Ok(Message::Opening(
+                commitment.try_into().map_err(|_| invalid())?,
+            )),
+            [Self::VERSION, Self::TURN, high, low, report, shot] => Ok(Message::Turn {
+                number: u16::from_be_bytes([*high, *low]),
+                report: Self::decode_report(*report).ok_or_else(invalid)?,
+                shot: Self::decode_cell(*shot).ok_or_else(invalid)?,
+            }),
+            [Self::VERSION, Self::REVEAL, high, low, report, rest @ ..] if rest.len() == 21 => {
+                let (salt, placements) = rest.split_at(16);
+                let placements = placements
+                    .iter()
+                    .map(|&byte| {
+                        let orientation = match byte & Self::VERTICAL {
+                            0 => ShipOrientation::Horizontal,
+                            _ => ShipOrientation::Vertical,
+                        };
+                        Some((Self::decode_cell(byte & !Self::VERTICAL)?, orientation))
+                    })
+                    .collect::<Option<Vec<_>>>()
+                    .ok_or_else(invalid)?;
+
+                Ok(Message::Reveal {
+                    number: u16::from_be_bytes([*high, *low]),
+                    report: Self::decode_report(*report).ok_or_else(invalid)?,
+                    salt: salt.try_into().map_err(|_| invalid())?,
+                    layout: FleetLayout::new(placements),
+                })
+            }
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Where a game by correspondence stands, for a side.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Status {
+    /// The side waits for the opening code of the opponent.
+    Opening,
+
+    /// The side fires the next shot.
+    YourTurn,
+
+    /// The side waits for the code of the opponent.
+    TheirTurn,
+
+    /// The side has sunk the fleet of the opponent, or the opponent has revealed their fleet
+    /// before.
+    Won,
+
+    /// The fleet of the side has been sunk.
+    Lost,
+}
+
+/// What a side learns from a turn of the opponent.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Received {
+    /// The last shot of the side, with its result.
+    pub yours: Option<(Cell, Report)>,
+
+    /// The shot of the opponent, with its result.
+    pub theirs: Option<(Cell, Report)>,
+}
+
+/// A shot of a game by correspondence, with its result once it has been reported.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Shot {
+    pub cell: Cell,
+    pub report: Option<Report>,
+}
+
+/// The side of a player in a game by correspondence.
+///
+/// The side can be saved between two turns, so that the game goes on when the code of the
+/// opponent arrives, even days later.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "Session", into = "Session")]
+pub struct Correspondence {
+    fleet: Fleet,
+    salt: [u8; 16],
+    opponent: Option<[u8; 32]>,
+    shots: Vec<Shot>,
+    received: Vec<Shot>,
+    revealed: Option<(FleetLayout, [u8; 16])>,
+}
+
+impl Correspondence {
+    /// Creates the side of a player with the given fleet, which is kept hidden from the
+    /// opponent by a random salt.
+    pub fn new(fleet: &Fleet) -> Self {
+        Self::with_salt(fleet, rand::rng().random())
+    }
+
+    /// Creates the side of a player with the given fleet and salt.
+    pub fn with_salt(fleet: &Fleet, salt: [u8; 16]) -> Self {
+        Self {
+            fleet: fleet.clone(),
+            salt,
+            opponent: None,
+            shots: Vec::new(),
+            received: Vec::new(),
+            revealed: None,
+        }
+    }
+
+    /// Returns the commitment of the fleet: the hash of the salt and of the fleet code.
+    pub fn commitment(&self) -> [u8; 32] {
+        commitment(&self.fleet.layout(), &self.salt)
+    }
+
+    /// Returns the opening code of the side, to send to the opponent.
+    pub fn opening(&self) -> String {
+        Message::Opening(self.commitment()).encode()
+    }
+
+    /// Returns where the game stands.
+    pub fn status(&self) -> Status {
+        let Some(opponent) = self.opponent else {
+            return Status::Opening;
+        };
+        let sunk = self
+            .shots
+            .iter()
+            .filter(|shot| matches!(shot.report, Some(Report::Sunk(_))))
+            .count();
+
+        if self.fleet.is_sunk() {
+            Status::Lost
+        } else if sunk == Fleet::COMPOSITION.len() || self.revealed.is_some() {
+            Status::Won
+        } else if self.shots.len() + usize::from(opponent < self.commitment())
+            == self.received.len()
+        {
+            Status::YourTurn
+        } else {
+            Status::TheirTurn
+        }
+    }
+
+    /// Returns the fleet of the side, with the damage of the shots of the opponent.
+    pub fn fleet(&self) -> &Fleet {
+        &self.fleet
+    }
+
+    /// Returns the grid of the shots of the side, with their reported results.
+    pub fn shots_grid(&self) -> Grid {
+        let mut grid = Grid::default();
+        for shot in &self.shots {
+            match shot.report {
+                Some(Report::Miss) => grid.mark(&shot.cell, CellState::Miss),
+                Some(_) => grid.mark(&shot.cell, CellState::Hit),
+                None => {}
+            }
+        }
+
+        grid
+    }
+
+    /// Returns the grid of the fleet of the side, with the shots of the opponent.
+    pub fn fleet_grid(&self) -> Grid {
+        let mut grid = Grid::from_ships(self.fleet.as_ref());
+        for shot in &self.received {
+            match shot.report {
+                Some(Report::Miss) => grid.mark(&shot.cell, CellState::Miss),
+                _ => grid.mark(&shot.cell, CellState::Hit),
+            }
+        }
+
+        grid
+    }
+
+    /// Fires at the cell, and returns the code of the turn to send to the opponent.
+    ///
+    /// An error is returned if it is not the turn of the side, or if it has already fired at
+    /// the cell.
+    pub fn fire(&mut self, cell: Cell) -> Result<String, CorrespondenceError> {
+        match self.status() {
+            Status::YourTurn => {}
+            Status::Won | Status::Lost => return Err(CorrespondenceError::Over),
+            Status::Opening | Status::TheirTurn => return Err(CorrespondenceError::NotYourTurn),
+        }
+        if self.shots.iter().any(|shot| shot.cell == cell) {
+            return Err(CorrespondenceError::AlreadyShot(cell));
+        }
+
+        self.shots.push(Shot { cell, report: None });
+        Ok(self.turn_code(cell))
+    }
+
+    /// Returns the last code of the side, which the opponent waits for, or `None` when the side
+    /// fires next.
+    ///
+    /// Before the battle, it is the opening code. Once the game is over, it is the code which
+    /// reveals the fleet of the side.
+    pub fn outgoing(&self) -> Option<String> {
+        match self.status() {
+            Status::YourTurn => None,
+            Status::Won | Status::Lost => Some(self.reveal_code()),
+            Status::Opening | Status::TheirTurn => match self.shots.last() {
+                Some(shot) => Some(self.turn_code(shot.cell)),
+                None => Some(self.opening()),
+            },
+        }
+    }
+
+    /// Verifies the fleet revealed by the opponent against their commitment and their reports,
+    /// and returns the inconsistencies found, or `None` if the opponent has not revealed their
+    /// fleet yet.
+    pub fn verify(&self) -> Option<Vec<Inconsistency>> {
+        let (layout, salt) = self.revealed.as_ref()?;
+        let mut fleet = match Fleet::from_layout(layout) {
+            Ok(fleet) => fleet,
+            Err(e) => return Some(vec![Inconsistency::Fleet(e)]),
+        };
+
+        let mut inconsistencies = Vec::new();
+        if self.opponent != Some(commitment(layout, salt)) {
+            inconsistencies.push(Inconsistency::Commitment);
+        }
+        for shot in &self.shots {
+            let actual = shoot(&mut fleet, &shot.cell);
+            if let Some(reported) = &shot.report
+                && *reported != actual
+            {
+                inconsistencies.push(Inconsistency::Report {
+                    cell: shot.cell,
+                    reported: reported.clone(),
+                    actual,
+                });
+            }
+        }
+
+        Some(inconsistencies)
+    }
+
+    /// Reads a code of the opponent: its opening, its turn, or the reveal of its fleet.
+    ///
+    /// An error is returned if the code can't be decoded, or if it is not the next code of the
+    /// opponent.
+    pub fn receive(&mut self, code: &str) -> Result<Received, CorrespondenceError> {
+        let message = Message::from_str(code)?;
+        let status = self.status();
+        match (status, message) {
+            (Status::Opening, Message::Opening(commitment)) => {
+                if commitment == self.commitment() {
+                    return Err(CorrespondenceError::OwnCode);
+                }
+                self.opponent = Some(commitment);
+                Ok(Received {
+                    yours: None,
+                    theirs: None,
+                })
+            }
+            (
+                Status::TheirTurn,
+                Message::Turn {
+                    number,
+                    report,
+                    shot,
+                },
+            ) => {
+                self.check_number(number)?;
+                let yours = self.record_report(report)?;
+                let report = shoot(&mut self.fleet, &shot);
+                self.received.push(Shot {
+                    cell: shot,
+                    report: Some(report.clone()),
+                });
+
+                Ok(Received {
+                    yours,
+                    theirs: Some((shot, report)),
+                })
+            }
+            (
+                Status::TheirTurn | Status::Won | Status::Lost,
+                Message::Reveal {
+                    number,
+                    report,
+                    salt,
+                    layout,
+                },
+            ) if self.revealed.is_none() => {
+                self.check_number(number)?;
+                let yours = self.record_report(report)?;
+                self.revealed = Some((layout, salt));
+
+                Ok(Received {
+                    yours,
+                    theirs: None,
+                })
+            }
+            (Status::Won | Status::Lost, _) => Err(CorrespondenceError::Over),
+            _ => Err(CorrespondenceError::UnexpectedCode),
+        }
+    }
+
+    // Checks that the number of a message is the one of the next message of the opponent.
+    fn check_number(&self, number: u16) -> Result<(), CorrespondenceError> {
+        let expected = self.received.len() as u16 + 1;
+        match number == expected {
+            true => Ok(()),
+            false => Err(CorrespondenceError::OutOfOrder {
+                expected,
+                found: number,
+            }),
+        }
+    }
+
+    // Records the report of the last shot of the side, which the opponent reports if it has
+    // not been reported yet.
+    fn record_report(
+        &mut self,
+        report: Option<Report>,
+    ) -> Result<Option<(Cell, Report)>, CorrespondenceError> {
+        let pending = self.shots.last_mut().filter(|shot| shot.report.is_none());
+        match (pending, report) {
+            (Some(pending), Some(report)) => {
+                pending.report = Some(report.clone());
+                Ok(Some((pending.cell, report)))
+            }
+            (None, None) => Ok(None),
+            _ => Err(CorrespondenceError::UnexpectedCode),
+        }
+    }
+
+    // Encodes the next turn of the side, reporting the last shot of the opponent.
+    fn turn_code(&self, shot: Cell) -> String {
+        Message::Turn {
+            number: self.shots.len() as u16,
+            report: self.received.last().and_then(|shot| shot.report.clone()),
+            shot,
+        }
+        .encode()
+    }
+
+    // Encodes the reveal of the fleet of the side, reporting the sinking shot of the opponent
+    // if the side has lost.
+    fn reveal_code(&self) -> String {
+        let report = match self.status() {
+            Status::Lost => self.received.last().and_then(|shot| shot.report.clone()),
+            _ => None,
+        };
+
+        Message::Reveal {
+            number: self.shots.len() as u16 + 1,
+            report,
+            salt: self.salt,
+            layout: self.fleet.layout(),
+        }
+        .encode()
+    }
+}
+
+// Shoots the fleet at the cell, and reports the result.
+fn shoot(fleet: &mut Fleet, cell: &Cell) -> Report {
+    let outcome = fleet.hit_at(cell);
+    match outcome.ship {
+        Some(kind) if outcome.sunk => Report::Sunk(kind),
+        Some(_) => Report::Hit,
+        None => Report::Miss,
+    }
+}
+
+// Returns the hash of the salt and of the fleet code.
+fn commitment(layout: &FleetLayout, salt: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(salt);
+    hasher.update(layout.to_string().as_bytes());
+    hasher.finalize().into()
+}
+
+// The side of a player as it is saved: the fleets as their codes, the salts and the commitment
+// of the opponent in base64. The damage of the fleet is applied again from the received shots.
+#[derive(Debug, Serialize, Deserialize)]
+struct Session {
+    fleet: String,
+    salt: String,
+    opponent: Option<String>,
+    opponent_fleet: Option<String>,
+    opponent_salt: Option<String>,
+    #[serde(default)]
+    shots: Vec<Shot>,
+    #[serde(default)]
+    received: Vec<Shot>,
+}
+
+impl From<Correspondence> for Session {
+    fn from(side: Correspondence) -> Self {
+        Self {
+            fleet: side.fleet.to_code(),
+            salt: URL_SAFE_NO_PAD.encode(side.salt),
+            opponent: side
+                .opponent
+                .map(|opponent| URL_SAFE_NO_PAD.encode(opponent)),
+            opponent_fleet: side.revealed.as_ref().map(|(layout, _)| layout.to_string()),
+            opponent_salt: side
+                .revealed
+                .as_ref()
+                .map(|(_, salt)| URL_SAFE_NO_PAD.encode(salt)),
+            shots: side.shots,
+            received: side.received,
+        }
+    }
+}
+
+impl TryFrom<Session> for Correspondence {
+    type Error = CorrespondenceError;
+
+    fn try_from(session: Session) -> Result<Self, Self::Error> {
+        fn decode<const N: usize>(value: &str) -> Result<[u8; N], CorrespondenceError> {
+            let invalid = || CorrespondenceError::InvalidCode(value.to_string());
+            URL_SAFE_NO_PAD
+                .decode(value)
+                .map_err(|_| invalid())?
+                .try_into()
+                .map_err(|_| invalid())
+        }
+
+        let mut fleet = Fleet::from_code(&session.fleet)?;
+        session.received.iter().for_each(|shot| {
+            fleet.hit_at(&shot.cell);
+        });
+
+        let revealed = match (session.opponent_fleet, session.opponent_salt) {
+            (Some(layout), Some(salt)) => Some((FleetLayout::from_str(&layout)?, decode(&salt)?)),
+            _ => None,
+        };
+
+        Ok(Self {
+            fleet,
+            salt: decode(&session.salt)?,
+            opponent: session.opponent.as_deref().map(decode).transpose()?,
+            shots: session.shots,
+            received: session.received,
+            revealed,
+        })
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    // Returns the sides of a game whose openings have been exchanged, the one to fire first
+    // first.
+    pub fn opened() -> (Correspondence, Correspondence) {
+        let mut a =
+            Correspondence::with_salt(&Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap(), [1; 16]);
+        let mut b =
+            Correspondence::with_salt(&Fleet::from_code("A1V/C1V/E1V/G1V/I1V").unwrap(), [2; 16]);
+        a.receive(&b.opening()).unwrap();
+        b.receive(&a.opening()).unwrap();
+
+        match a.status() {
+            Status::YourTurn => (a, b),
+            _ => (b, a),
+        }
+    }
+
+    #[rstest]
+    #[case(Message::Opening([7; 32]))]
+    #[case(Message::Turn { number: 1, report: None, shot: Cell::bounded(0, 0) })]
+    #[case(Message::Turn { number: 300, report: Some(Report::Miss), shot: Cell::bounded(9, 9) })]
+    #[case(Message::Reveal {
+        number: 17,
+        report: Some(Report::Sunk(ShipKind::Destroyer)),
+        salt: [9; 16],
+        layout: FleetLayout::from_str("A1V/C1V/E1V/G1V/J9V").unwrap(),
+    })]
+    fn test_message_roundtrip(#[case] message: Message) {
+        let code = message.encode();
+
+        assert!(
+            code.chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        );
+        assert_eq!(Message::from_str(&format!(" {code}\n")), Ok(message));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("not a code!")]
+    // The version 1 is not supported anymore.
+    #[case("AVQAAQBj")]
+    // The shot is out of the grid.
+    #[case("AlQAAQBk")]
+    // The report is not a ship.
+    #[case("AlQAAQlj")]
+    // A turn without a shot.
+    #[case("AlQAAQD_")]
+    // The fleet of the reveal has a ship less.
+    #[case("AlIAAf8AAAAAAAAAAAAAAAAAAAAAABQoPA")]
+    fn test_invalid_code(#[case] code: &str) {
+        assert_eq!(
+            Message::from_str(code),
+            Err(CorrespondenceError::InvalidCode(code.trim().to_string()))
+        );
+    }
+
+    // Plays a game where the first side sinks the fleet of the second side, which misses every
+    // shot, and returns the sinking shot.
+    pub fn sink(first: &mut Correspondence, second: &mut Correspondence) -> Option<(Cell, Report)> {
+        let mut targets = (0..10)
+            .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+            .filter(|cell| second.fleet().is_occupied(cell))
+            .collect::<Vec<_>>()
+            .into_iter();
+        let mut misses = (0..10)
+            .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+            .filter(|cell| !first.fleet().is_occupied(cell))
+            .collect::<Vec<_>>()
+            .into_iter();
+
+        let mut last = None;
+        while second.status() != Status::Lost {
+            let code = first.fire(targets.next().unwrap()).unwrap();
+            assert_eq!(first.status(), Status::TheirTurn);
+            assert_eq!(first.outgoing(), Some(code.clone()));
+            last = second.receive(&code).unwrap().theirs;
+            if second.status() == Status::YourTurn {
+                let code = second.fire(misses.next().unwrap()).unwrap();
+                first.receive(&code).unwrap();
+            }
+        }
+
+        last
+    }
+
+    #[rstest]
+    fn test_full_game() {
+        let (mut first, mut second) = opened();
+
+        let last = sink(&mut first, &mut second);
+        assert_eq!(second.verify(), None);
+        let received = first.receive(&second.outgoing().unwrap()).unwrap();
+        second.receive(&first.outgoing().unwrap()).unwrap();
+
+        assert!(matches!(last, Some((_, Report::Sunk(_)))));
+        assert_eq!(received.yours, last);
+        assert_eq!(received.theirs, None);
+        assert_eq!(first.status(), Status::Won);
+        assert_eq!(second.status(), Status::Lost);
+        assert_eq!(first.verify(), Some(Vec::new()));
+        assert_eq!(second.verify(), Some(Vec::new()));
+        let resumed: Correspondence = toml::from_str(&toml::to_string(&second).unwrap()).unwrap();
+        assert_eq!(resumed.verify(), Some(Vec::new()));
+        assert_eq!(
+            first.fire(Cell::bounded(9, 9)),
+            Err(CorrespondenceError::Over)
+        );
+        assert_eq!(
+            second.receive(&first.outgoing().unwrap()),
+            Err(CorrespondenceError::Over)
+        );
+        assert!(second.fleet().is_sunk());
+        assert!(!first.fleet().is_sunk());
+        assert_eq!(first.shots_grid().at(&last.unwrap().0), &CellState::Hit);
+    }
+
+    #[rstest]
+    fn test_inconsistent_reports() {
+        let (mut first, mut second) = opened();
+        sink(&mut first, &mut second);
+        // The second side has claimed a miss for the first hit.
+        let hit = first.shots[0].cell;
+        first.shots[0].report = Some(Report::Miss);
+
+        first.receive(&second.outgoing().unwrap()).unwrap();
+
+        assert_eq!(
+            first.verify(),
+            Some(vec![Inconsistency::Report {
+                cell: hit,
+                reported: Report::Miss,
+                actual: Report::Hit,
+            }])
+        );
+    }
+
+    #[rstest]
+    fn test_changed_fleet() {
+        let (mut first, mut second) = opened();
+        sink(&mut first, &mut second);
+        // The second side reveals another fleet than the one of its opening.
+        second.fleet = Fleet::from_code("B1V/D1V/F1V/H1V/J1V").unwrap();
+        for ship in second.fleet.clone().as_ref() {
+            for cell in ship.occupied_cells() {
+                second.fleet.hit_at(&cell);
+            }
+        }
+
+        first.receive(&second.outgoing().unwrap()).unwrap();
+
+        assert_eq!(first.verify().unwrap()[0], Inconsistency::Commitment);
+    }
+
+    #[rstest]
+    fn test_invalid_revealed_fleet() {
+        let (mut first, mut second) = opened();
+        sink(&mut first, &mut second);
+        let code = Message::Reveal {
+            number: second.shots.len() as u16 + 1,
+            report: second.received.last().unwrap().report.clone(),
+            salt: second.salt,
+            layout: FleetLayout::from_str("A1H/A1V/C1V/E1V/G1V").unwrap(),
+        }
+        .encode();
+
+        first.receive(&code).unwrap();
+
+        assert!(matches!(
+            first.verify().as_deref(),
+            Some([Inconsistency::Fleet(_)])
+        ));
+    }
+
+    #[rstest]
+    fn test_errors() {
+        let (mut first, mut second) = opened();
+        let opening = second.opening();
+
+        assert_eq!(
+            second.fire(Cell::bounded(0, 0)),
+            Err(CorrespondenceError::NotYourTurn)
+        );
+        assert_eq!(
+            first.receive(&opening),
+            Err(CorrespondenceError::UnexpectedCode)
+        );
+        let one = first.fire(Cell::bounded(9, 9)).unwrap();
+        second.receive(&one).unwrap();
+        assert_eq!(
+            second.receive(&one),
+            Err(CorrespondenceError::UnexpectedCode)
+        );
+        let two = second.fire(Cell::bounded(9, 9)).unwrap();
+        first.receive(&two).unwrap();
+        assert_eq!(
+            first.fire(Cell::bounded(9, 9)),
+            Err(CorrespondenceError::AlreadyShot(Cell::bounded(9, 9)))
+        );
+        let three = first.fire(Cell::bounded(9, 8)).unwrap();
+        second.receive(&three).unwrap();
+        second.fire(Cell::bounded(9, 8)).unwrap();
+        assert_eq!(
+            first.receive(&two),
+            Err(CorrespondenceError::OutOfOrder {
+                expected: 2,
+                found: 1
+            })
+        );
+
+        let mut own = Correspondence::new(first.fleet());
+        assert_eq!(
+            own.receive(&own.opening()),
+            Err(CorrespondenceError::OwnCode)
+        );
+    }
+
+    #[rstest]
+    fn test_serialization() {
+        let (mut first, mut second) = opened();
+        let code = first.fire(Cell::bounded(0, 0)).unwrap();
+        second.receive(&code).unwrap();
+
+        let toml = toml::to_string(&second).unwrap();
+        let resumed: Correspondence = toml::from_str(&toml).unwrap();
+
+        assert!(toml.contains(&format!("fleet = \"{}\"", second.fleet().to_code())));
+        assert!(toml.contains("cell = \"A1\""));
+        assert_eq!(resumed.status(), Status::YourTurn);
+        assert_eq!(resumed.commitment(), second.commitment());
+        assert_eq!(resumed.fleet().status(), second.fleet().status());
+        assert!(
+            toml::from_str::<Correspondence>(&toml.replace(&second.fleet().to_code(), "A1H"))
+                .is_err()
+        );
+    }
+}
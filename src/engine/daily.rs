@@ -0,0 +1,116 @@
+//! The daily challenge: the same battle for every player, every day.
+//!
+//! The [DailyChallenge] of a day seeds the game with the number of days since 1970-01-01, in
+//! UTC, so that the computer fleet and the players' order are the same for everybody on the
+//! same day.
+//!
+use crate::engine::game::GameBuilder;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The challenge of a day.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct DailyChallenge {
+    day: u64,
+}
+
+impl DailyChallenge {
+    const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+    /// Returns the challenge of the current day, in UTC.
+    pub fn today() -> Self {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs())
+            .unwrap_or_default();
+
+        Self::for_day(seconds / Self::SECONDS_PER_DAY)
+    }
+
+    /// Returns the challenge of the given day, counted from 1970-01-01.
+    pub fn for_day(day: u64) -> Self {
+        Self { day }
+    }
+
+    /// Returns the day of the challenge, counted from 1970-01-01.
+    pub fn day(&self) -> u64 {
+        self.day
+    }
+
+    /// Returns the seed of the games of the challenge.
+    pub fn seed(&self) -> u64 {
+        self.day
+    }
+
+    /// Returns the date of the challenge, like `"2024-02-29"`.
+    pub fn date(&self) -> String {
+        // Converts the days to a date of the proleptic Gregorian calendar, by eras of 400 years.
+        let days = self.day + 719_468;
+        let era = days / 146_097;
+        let day_of_era = days % 146_097;
+        let year_of_era =
+            (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let month_index = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+        let month = if month_index < 10 {
+            month_index + 3
+        } else {
+            month_index - 9
+        };
+        let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+        format!("{year:04}-{month:02}-{day:02}")
+    }
+
+    /// Returns a builder for the game of the challenge.
+    ///
+    /// The human player and the difficulty are still to be set.
+    pub fn builder(&self) -> GameBuilder {
+        GameBuilder::new().seed(self.seed())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::Fleet;
+    use crate::engine::fleet::tests::fixed_fleet;
+    use crate::engine::game::{Difficulty, Game};
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, "1970-01-01")]
+    #[case(59, "1970-03-01")]
+    #[case(11_016, "2000-02-29")]
+    #[case(20_742, "2026-10-16")]
+    fn test_date(#[case] day: u64, #[case] date: &str) {
+        assert_eq!(DailyChallenge::for_day(day).date(), date);
+    }
+
+    #[rstest]
+    fn test_today() {
+        let today = DailyChallenge::today();
+        assert!(today.day() >= 20_742);
+        assert_eq!(today.seed(), today.day());
+    }
+
+    #[rstest]
+    fn test_same_day_same_board(fixed_fleet: Fleet) {
+        let game = |challenge: DailyChallenge, difficulty| {
+            challenge
+                .builder()
+                .human("Human", fixed_fleet.clone())
+                .computer(difficulty)
+                .build()
+                .unwrap()
+        };
+        let computer_layout = |game: &Game| game.computer().unwrap().fleet().layout();
+
+        let first = game(DailyChallenge::for_day(20_000), Difficulty::Easy);
+        let second = game(DailyChallenge::for_day(20_000), Difficulty::Normal);
+        let next_day = game(DailyChallenge::for_day(20_001), Difficulty::Easy);
+
+        assert_eq!(computer_layout(&first), computer_layout(&second));
+        assert_ne!(computer_layout(&first), computer_layout(&next_day));
+    }
+}
@@ -0,0 +1,494 @@
+//! A duel: a battle between two human players sharing the same engine, like the players of a
+//! server. The first fleet sunk loses.
+//!
+//! The players take a seat, 0 for the one who opens the duel and 1 for the one who joins it,
+//! then deploy their fleets. Once both fleets are deployed, a random seat fires first.
+//!
+//! A duel is played on the classic rules, or on a [Variant] which only changes how many shots a
+//! player fires in a row: a [streak](crate::engine::rules::StreakRules) or a
+//! [salvo](crate::engine::rules::SalvoRules).
+//!
+//! A duel is saved by the names of its players, their fleets, the seat which fired first and the
+//! shots fired, and [resumed](Duel::resume) by playing them again.
+//!
+use crate::engine::correspondence::Report;
+use crate::engine::fleet::Fleet;
+use crate::engine::game::{self, ShotReport, Side};
+use crate::engine::grid::{Cell, CellState};
+use crate::engine::player::Player;
+use crate::engine::rules::{Rules, Variant};
+use rand::Rng;
+use thiserror::Error;
+
+/// Represents a duel error.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum DuelError {
+    /// Both seats have already been taken.
+    #[error("the duel already has two players")]
+    Full,
+
+    /// Nobody has taken the seat.
+    #[error("seat {0} is empty")]
+    EmptySeat(usize),
+
+    /// The player has already deployed their fleet.
+    #[error("the fleet has already been deployed")]
+    AlreadyDeployed,
+
+    /// The fleets of both players have not been deployed yet.
+    #[error("the battle has not started yet")]
+    NotStarted,
+
+    /// The player fired while waiting for the opponent.
+    #[error("it is not your turn")]
+    NotYourTurn,
+
+    /// The player has already fired at the cell.
+    #[error("you have already fired at {0}")]
+    AlreadyShot(Cell),
+
+    /// The duel is over.
+    #[error("the duel is over")]
+    Over,
+
+    /// The rules of the variant can't be played in a duel.
+    #[error("a duel can't be played with the {0} rules")]
+    Unsupported(Variant),
+}
+
+/// A shot fired during a duel.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DuelShot {
+    /// The seat of the player who fired the shot.
+    pub seat: usize,
+
+    /// The turn of the shot, from 1: a turn is made of the shots of each player.
+    pub turn: u32,
+
+    pub cell: Cell,
+    pub report: Report,
+}
+
+/// A battle between two human players.
+#[derive(Debug)]
+pub struct Duel {
+    variant: Variant,
+    rules: Box<dyn Rules>,
+    names: Vec<String>,
+    players: [Option<Player>; 2],
+    first: usize,
+    next: usize,
+    fired: usize,
+    turn: u32,
+    shots: Vec<DuelShot>,
+}
+
+impl Duel {
+    /// The variants which can be played in a duel.
+    pub const VARIANTS: [Variant; 3] = [Variant::Classic, Variant::Streak, Variant::Salvo];
+
+    /// Opens a duel on the classic rules, with the given player on seat 0.
+    pub fn new(name: &str) -> Self {
+        Self {
+            variant: Variant::Classic,
+            rules: Variant::Classic.rules(),
+            names: vec![name.to_string()],
+            players: [None, None],
+            first: 0,
+            next: 0,
+            fired: 0,
+            turn: 0,
+            shots: Vec::new(),
+        }
+    }
+
+    /// Opens a duel on the rules of the variant, with the given player on seat 0.
+    ///
+    /// An error is returned if the variant is not one of the [Duel::VARIANTS].
+    pub fn with_variant(name: &str, variant: Variant) -> Result<Self, DuelError> {
+        if !Self::VARIANTS.contains(&variant) {
+            return Err(DuelError::Unsupported(variant));
+        }
+
+        Ok(Self {
+            variant,
+            rules: variant.rules(),
+            ..Self::new(name)
+        })
+    }
+
+    /// Returns the variant played.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Lets the given player join the duel, and returns their seat.
+    ///
+    /// An error is returned if both seats have already been taken.
+    pub fn join(&mut self, name: &str) -> Result<usize, DuelError> {
+        if self.names.len() == 2 {
+            return Err(DuelError::Full);
+        }
+        self.names.push(name.to_string());
+
+        Ok(self.names.len() - 1)
+    }
+
+    /// Returns the name of the player on the seat, if it has been taken.
+    pub fn name(&self, seat: usize) -> Option<&str> {
+        self.names.get(seat).map(String::as_str)
+    }
+
+    /// Deploys the fleet of the player on the seat. The battle starts once both fleets are
+    /// deployed.
+    ///
+    /// An error is returned if nobody has taken the seat, or if its fleet has already been
+    /// deployed.
+    pub fn deploy(&mut self, seat: usize, fleet: Fleet) -> Result<(), DuelError> {
+        let name = self.name(seat).ok_or(DuelError::EmptySeat(seat))?;
+        if self.players[seat].is_some() {
+            return Err(DuelError::AlreadyDeployed);
+        }
+        self.players[seat] = Some(Player::new(name, fleet));
+        if self.is_started() {
+            self.first = rand::rng().random_range(0..2);
+            self.next = self.first;
+        }
+
+        Ok(())
+    }
+
+    /// Checks whether both fleets have been deployed.
+    pub fn is_started(&self) -> bool {
+        self.players.iter().all(Option::is_some)
+    }
+
+    /// Returns the player on the seat, once their fleet has been deployed.
+    pub fn player(&self, seat: usize) -> Option<&Player> {
+        self.players.get(seat)?.as_ref()
+    }
+
+    /// Returns the seat of the player who fires next, or `None` if the battle has not started
+    /// or is over.
+    pub fn to_move(&self) -> Option<usize> {
+        if !self.is_started() || self.winner().is_some() {
+            return None;
+        }
+
+        Some(self.next)
+    }
+
+    /// Returns the seat of the winner, once the fleet of the other player is sunk.
+    pub fn winner(&self) -> Option<usize> {
+        (0..2).find(|&seat| {
+            self.player(1 - seat)
+                .is_some_and(|opponent| opponent.has_lost())
+        })
+    }
+
+    /// Returns the seat of the player who fired first, once the battle has started.
+    pub fn first(&self) -> Option<usize> {
+        self.is_started().then_some(self.first)
+    }
+
+    /// Returns the number of turns started.
+    pub fn turn(&self) -> u32 {
+        self.turn
+    }
+
+    /// Returns a stable hash of the state of the duel, like [Game::state_hash]: the fleets of
+    /// both seats as they were placed, the shots they have fired and the turn.
+    ///
+    /// [Game::state_hash]: crate::engine::game::Game::state_hash
+    pub fn state_hash(&self) -> u64 {
+        game::state_hash(self.turn, [self.player(0), self.player(1)])
+    }
+
+    /// Returns the shots fired by both players, in order.
+    pub fn shots(&self) -> &[DuelShot] {
+        &self.shots
+    }
+
+    /// Fires the shot of the player on the seat. The player keeps the turn while the rules let
+    /// them shoot again.
+    ///
+    /// An error is returned if the battle has not started or is over, if it is the turn of the
+    /// opponent, or if the player has already fired at the cell.
+    pub fn fire(&mut self, seat: usize, cell: &Cell) -> Result<&DuelShot, DuelError> {
+        match self.to_move() {
+            _ if self.winner().is_some() => return Err(DuelError::Over),
+            None => return Err(DuelError::NotStarted),
+            Some(next) if next != seat => return Err(DuelError::NotYourTurn),
+            Some(_) => {}
+        }
+
+        let [first, second] = &mut self.players;
+        let (Some(player), Some(opponent)) = (first.as_mut(), second.as_mut()) else {
+            return Err(DuelError::NotStarted);
+        };
+        let (shooter, target) = match seat {
+            0 => (player, opponent),
+            _ => (opponent, player),
+        };
+        if !matches!(shooter.shots_grid().at(cell), CellState::Empty) {
+            return Err(DuelError::AlreadyShot(*cell));
+        }
+
+        let outcome = shooter.attack(target, cell);
+        let report = match &outcome.ship {
+            Some(kind) if outcome.sunk => Report::Sunk(kind.clone()),
+            Some(_) => Report::Hit,
+            None => Report::Miss,
+        };
+
+        // The rules only look at whether the shot has hit, whoever the shooter is.
+        let shot = ShotReport {
+            shooter: Side::Human,
+            cell: *cell,
+            hit: outcome.ship,
+            sunk: outcome.sunk,
+            sunk_cells: Vec::new(),
+            mine: false,
+            lost: None,
+            fogged: false,
+        };
+        if self.fired == 0 && seat == self.first {
+            self.turn += 1;
+        }
+        self.fired += 1;
+        if !self.rules.shoots_again(&shot) && self.fired >= self.rules.shots(shooter) {
+            self.next = 1 - seat;
+            self.fired = 0;
+        }
+        self.shots.push(DuelShot {
+            seat,
+            turn: self.turn,
+            cell: *cell,
+            report,
+        });
+
+        Ok(self.shots.last().unwrap())
+    }
+
+    /// Resumes a duel of the variant from the names of its players, the fleets they have
+    /// deployed, the seat of the player who fired first and the shots fired, in order.
+    ///
+    /// An error is returned if the variant can't be played in a duel, if a fleet has no player,
+    /// or if a shot can't be fired.
+    pub fn resume(
+        variant: Variant,
+        names: &[String],
+        fleets: [Option<Fleet>; 2],
+        first: usize,
+        shots: &[(usize, Cell)],
+    ) -> Result<Self, DuelError> {
+        let mut duel = Self::with_variant(names.first().map_or("", String::as_str), variant)?;
+        for name in names.iter().skip(1) {
+            duel.join(name)?;
+        }
+        for (seat, fleet) in fleets.into_iter().enumerate() {
+            if let Some(fleet) = fleet {
+                duel.deploy(seat, fleet)?;
+            }
+        }
+        duel.first = first.min(1);
+        duel.next = duel.first;
+        for (seat, cell) in shots {
+            duel.fire(*seat, cell)?;
+        }
+
+        Ok(duel)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    fn started() -> Duel {
+        let mut duel = Duel::new("Ishmael");
+        duel.join("Queequeg").unwrap();
+        duel.deploy(0, Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap())
+            .unwrap();
+        duel.deploy(1, Fleet::from_code("A1V/C1V/E1V/G1V/I1V").unwrap())
+            .unwrap();
+
+        duel
+    }
+
+    #[rstest]
+    fn test_join_and_deploy() {
+        let mut duel = Duel::new("Ishmael");
+
+        assert_eq!(duel.join("Queequeg"), Ok(1));
+        assert_eq!(duel.join("Starbuck"), Err(DuelError::Full));
+        assert_eq!(duel.name(1), Some("Queequeg"));
+
+        duel.deploy(0, Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap())
+            .unwrap();
+        assert!(!duel.is_started());
+        assert_eq!(duel.to_move(), None);
+        assert_eq!(
+            duel.deploy(0, Fleet::from_code("A1V/C1V/E1V/G1V/I1V").unwrap()),
+            Err(DuelError::AlreadyDeployed)
+        );
+        assert_eq!(
+            duel.fire(0, &Cell::from_str("A1").unwrap()),
+            Err(DuelError::NotStarted)
+        );
+
+        duel.deploy(1, Fleet::from_code("A1V/C1V/E1V/G1V/I1V").unwrap())
+            .unwrap();
+        assert!(duel.is_started());
+        assert!(duel.to_move().is_some());
+    }
+
+    #[rstest]
+    fn test_fire() {
+        let mut duel = started();
+        let first = duel.to_move().unwrap();
+        let a1 = Cell::from_str("A1").unwrap();
+
+        assert_eq!(duel.fire(1 - first, &a1), Err(DuelError::NotYourTurn));
+        assert_eq!(duel.fire(first, &a1).unwrap().report, Report::Hit);
+        assert_eq!(duel.fire(first, &a1), Err(DuelError::NotYourTurn));
+        assert_eq!(duel.fire(1 - first, &a1).unwrap().report, Report::Hit);
+        assert_eq!(duel.fire(first, &a1), Err(DuelError::AlreadyShot(a1)));
+        assert_eq!(duel.turn(), 1);
+        assert_eq!(
+            duel.shots()
+                .iter()
+                .map(|shot| shot.seat)
+                .collect::<Vec<_>>(),
+            vec![first, 1 - first]
+        );
+    }
+
+    #[rstest]
+    fn test_winner() {
+        let mut duel = started();
+        let first = duel.to_move().unwrap();
+        let targets = match first {
+            0 => duel.player(1).unwrap().fleet().clone(),
+            _ => duel.player(0).unwrap().fleet().clone(),
+        };
+        let cells: Vec<Cell> = targets
+            .as_ref()
+            .iter()
+            .flat_map(|ship| ship.occupied_cells())
+            .collect();
+
+        // The opponent fires at the last rows, far from sinking the whole fleet.
+        let mut others = (0..10)
+            .map(|x| Cell::bounded(x, 9))
+            .chain((0..10).map(|x| Cell::bounded(x, 8)));
+        for cell in &cells {
+            duel.fire(first, cell).unwrap();
+            if duel.winner().is_none() {
+                let other = others
+                    .find(|cell| {
+                        duel.player(1 - first)
+                            .is_some_and(|player| player.shots_grid().at(cell) == &CellState::Empty)
+                    })
+                    .unwrap();
+                duel.fire(1 - first, &other).unwrap();
+            }
+        }
+
+        assert_eq!(duel.winner(), Some(first));
+        assert_eq!(duel.to_move(), None);
+        assert!(matches!(
+            duel.shots().last().unwrap().report,
+            Report::Sunk(_)
+        ));
+        assert_eq!(duel.fire(1 - first, &cells[0]), Err(DuelError::Over));
+    }
+
+    #[rstest]
+    #[case(Variant::Classic, 1)]
+    #[case(Variant::Streak, 3)]
+    #[case(Variant::Salvo, 5)]
+    fn test_variants(#[case] variant: Variant, #[case] shots: usize) {
+        let mut duel = Duel::with_variant("Ishmael", variant).unwrap();
+        duel.join("Queequeg").unwrap();
+        duel.deploy(0, Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap())
+            .unwrap();
+        duel.deploy(1, Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap())
+            .unwrap();
+        let first = duel.to_move().unwrap();
+
+        // Two hits on the Aircraft Carrier, then a miss.
+        for cell in ["A1", "B1", "J10", "J9", "J8"].iter().take(shots) {
+            assert_eq!(duel.to_move(), Some(first));
+            duel.fire(first, &Cell::from_str(cell).unwrap()).unwrap();
+        }
+
+        assert_eq!(duel.to_move(), Some(1 - first));
+        assert_eq!(duel.variant(), variant);
+        assert_eq!(duel.turn(), 1);
+    }
+
+    #[rstest]
+    fn test_resume() {
+        let mut duel = Duel::with_variant("Ishmael", Variant::Streak).unwrap();
+        duel.join("Queequeg").unwrap();
+        duel.deploy(0, Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap())
+            .unwrap();
+        duel.deploy(1, Fleet::from_code("A1V/C1V/E1V/G1V/I1V").unwrap())
+            .unwrap();
+        let first = duel.first().unwrap();
+        // Two hits on the Aircraft Carrier of the opponent, then a miss.
+        let second = ["A2", "B1"][first];
+        for cell in ["A1", second, "J10"] {
+            duel.fire(first, &Cell::from_str(cell).unwrap()).unwrap();
+        }
+        duel.fire(1 - first, &Cell::from_str("J10").unwrap())
+            .unwrap();
+
+        let shots: Vec<(usize, Cell)> = duel.shots().iter().map(|s| (s.seat, s.cell)).collect();
+        let resumed = Duel::resume(
+            Variant::Streak,
+            &["Ishmael".to_string(), "Queequeg".to_string()],
+            [0, 1].map(|seat| duel.player(seat).map(|player| player.fleet().clone())),
+            first,
+            &shots,
+        )
+        .unwrap();
+
+        assert_eq!(resumed.shots(), duel.shots());
+        assert_eq!(resumed.state_hash(), duel.state_hash());
+        assert_eq!(resumed.to_move(), duel.to_move());
+        assert_eq!(resumed.turn(), 1);
+        assert_eq!(resumed.name(1), Some("Queequeg"));
+    }
+
+    #[rstest]
+    fn test_resume_unstarted() {
+        let names = ["Ishmael".to_string()];
+        let duel = Duel::resume(Variant::Classic, &names, [None, None], 0, &[]).unwrap();
+
+        assert_eq!(duel.name(1), None);
+        assert_eq!(duel.first(), None);
+        assert_eq!(
+            Duel::resume(
+                Variant::Classic,
+                &names,
+                [None, Some(Fleet::from_code("A1H/A3H/A5H/A7H/A9H").unwrap())],
+                0,
+                &[]
+            )
+            .unwrap_err(),
+            DuelError::EmptySeat(1)
+        );
+    }
+
+    #[rstest]
+    fn test_unsupported_variant() {
+        assert_eq!(
+            Duel::with_variant("Ishmael", Variant::SeaMines).unwrap_err(),
+            DuelError::Unsupported(Variant::SeaMines)
+        );
+    }
+}
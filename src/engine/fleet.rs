@@ -1,23 +1,99 @@
 //! In the naval battle the main object is the Ship. Players deploy their fleet up on a [crate::engine::grid::Grid].
-//! Each fleet consists of 5 ships, each of a different kind.
+//! The classic fleet consists of 5 ships, each of a different kind, but a custom fleet can hold
+//! several ships of the same kind: every ship of a fleet is told apart by its [ShipId].
 //!
 //! The [ShipKind] defines the different ship types, and can be used to make a new [Ship]
 //! of a given type.
 //!
 //! A [Ship] is a set of [Cell]s in a row. User can try to hit a cell of a ship, and when all the
-//! ship cells have been hit, the ship is sunk.
+//! ship cells have been hit, the ship is sunk. A shot at a [Fleet] tells with a [FleetShot]
+//! which ship it has hit, and whether the ship has gone down.
 //!
 //! You have to use a given [ShipKind] in order to create a new [Ship].
 //!
-use crate::engine::grid::Cell;
-use strum::Display;
+//! When a ship or a fleet cannot be built as requested, a [PlacementError] or a [FleetError]
+//! explains why.
+//!
+//! A [FleetSampler] draws the fleets which may hide behind the shots fired at them, so that a
+//! computer player can guess where the ships are. The same placements draw the random fleets of
+//! [Fleet::random_valid], made as a [FleetConfig] describes, and of [Fleet::random], without
+//! rejecting the overlapping ships.
+//!
+use crate::engine::grid::{Cell, CellState, Grid};
+use crate::engine::parallel;
+use crate::engine::rules::{ClassicRules, Rules};
+use rand::Rng;
+use rand::seq::{IndexedRandom, SliceRandom};
+use serde::{Deserialize, Serialize};
+use smallvec::SmallVec;
+use std::cmp::Reverse;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use strum_macros::EnumIter;
+use thiserror::Error;
+
+/// Reasons why a ship cannot be placed on the board.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum PlacementError {
+    /// The ship doesn't fit within the board from its first cell.
+    #[error("{kind} at {cell} exceeds the board")]
+    OutOfBounds {
+        kind: ShipKind,
+        cell: Cell,
+        orientation: ShipOrientation,
+    },
+
+    /// The ship is in the space of another ship.
+    ///
+    /// `at` is the first cell of the `kind` ship found in the space of the `other` ship.
+    #[error("{kind} overlaps {other} at {at}")]
+    Overlap {
+        kind: ShipKind,
+        other: ShipKind,
+        at: Cell,
+    },
+}
+
+/// Reasons why a fleet cannot be built.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum FleetError {
+    /// The fleet doesn't have one ship for each kind.
+    #[error("A fleet must contain exactly {expected} ships, but {found} were provided")]
+    WrongShipCount { expected: usize, found: usize },
+
+    /// The ships are not in the fleet order.
+    #[error("A ship of kind {expected} was expected, but a {found} was provided")]
+    UnexpectedKind { expected: ShipKind, found: ShipKind },
+
+    /// A ship is out of the board or overlaps another one.
+    #[error(transparent)]
+    Placement(#[from] PlacementError),
+
+    /// The string is not a valid ship placement in a layout code.
+    #[error("{0} is not a valid ship placement")]
+    InvalidPlacementCode(String),
+
+    /// The ship has been sunk, so it can't be moved.
+    #[error("The {0} has been sunk")]
+    ShipSunk(ShipKind),
+
+    /// The fleet has no such ship of the kind.
+    #[error("The fleet has no such {0}")]
+    MissingShip(ShipKind),
+
+    /// The ships can't be placed on the board, or no placement is allowed by the rules.
+    #[error("The ships can't be placed on the board")]
+    Unplaceable,
+}
 
 /// The different types of ship in the game.
 ///
 /// Use this type to create new ships.
 ///
-#[derive(Debug, PartialEq, Eq, Clone, Display, EnumIter)]
+#[derive(Debug, PartialEq, Eq, Clone, strum::Display, EnumIter, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
 pub enum ShipKind {
     /// Aircraft Carrier: the longest ship in the game, occupying 5 consecutive cells.
     #[strum(serialize = "Aircraft Carrier")]
@@ -39,13 +115,30 @@ pub enum ShipKind {
     Destroyer,
 }
 
+/// The cells of a ship, kept inline as no ship is longer than [ShipKind::MAX_SIZE].
+pub type ShipCells = SmallVec<[Cell; ShipKind::MAX_SIZE as usize]>;
+
 impl ShipKind {
+    /// The number of cells of the longest kind of ship.
+    pub const MAX_SIZE: u8 = Self::AIRCRAFT_CARRIER_SIZE;
+
     const AIRCRAFT_CARRIER_SIZE: u8 = 5;
     const BATTLESHIP_SIZE: u8 = 4;
     const CRUISER_SIZE: u8 = 3;
     const SUBMARINE_SIZE: u8 = 3;
     const DESTROYER_SIZE: u8 = 2;
 
+    /// Returns the letter which stands for this kind of ship in the diagram of a [Fleet].
+    pub fn letter(&self) -> char {
+        match self {
+            ShipKind::AircraftCarrier => 'A',
+            ShipKind::Battleship => 'B',
+            ShipKind::Cruiser => 'C',
+            ShipKind::Submarine => 'S',
+            ShipKind::Destroyer => 'D',
+        }
+    }
+
     /// Creates a new [`Ship`] of this kind starting from the given cell.
     ///
     /// A ship is defined by its starting cell (`first`) and its [`ShipOrientation`],
@@ -63,7 +156,18 @@ impl ShipKind {
     /// ```
     ///
     pub fn ship(&self, first: Cell, orientation: ShipOrientation) -> Option<Ship> {
-        Ship::new(self.size(), first, orientation)
+        Ship::new(self.clone(), first, orientation)
+    }
+
+    /// Like [ShipKind::ship], but returns a [PlacementError::OutOfBounds] if the ship would
+    /// exceed the board boundaries.
+    pub fn place(&self, first: Cell, orientation: ShipOrientation) -> Result<Ship, PlacementError> {
+        self.ship(first, orientation)
+            .ok_or_else(|| PlacementError::OutOfBounds {
+                kind: self.clone(),
+                cell: first,
+                orientation,
+            })
     }
 
     /// Returns a randomly placed [`Ship`] of this kind.
@@ -71,11 +175,21 @@ impl ShipKind {
     /// Both the starting cell and the orientation are chosen at random.
     /// The returned ship is guaranteed to fit within the game board.
     pub fn random(&self) -> Ship {
-        loop {
-            if let Some(ship) = self.ship(Cell::random(), ShipOrientation::random()) {
-                break ship;
-            }
-        }
+        self.random_with(&mut rand::rng())
+    }
+
+    /// Like [ShipKind::random], but the placement is taken from the given generator.
+    pub fn random_with<R: Rng + ?Sized>(&self, rng: &mut R) -> Ship {
+        let orientation = ShipOrientation::random_with(rng);
+        let long = rng.random_range(0..=10 - self.size());
+        let short = rng.random_range(0..10);
+        let first = match orientation {
+            ShipOrientation::Horizontal => Cell::bounded(long, short),
+            ShipOrientation::Vertical => Cell::bounded(short, long),
+        };
+
+        self.ship(first, orientation)
+            .expect("the ship starts where it fits on the board")
     }
 
     /// Returns the number of cells for this kind of ship.
@@ -93,6 +207,7 @@ impl ShipKind {
 /// Describes a ship as an item of the game.
 #[derive(Debug, PartialEq, Clone)]
 pub struct Ship {
+    kind: ShipKind,
     first_cell: Cell,
     ship_size: u8,
     orientation: ShipOrientation,
@@ -100,7 +215,8 @@ pub struct Ship {
 }
 
 impl Ship {
-    fn new(ship_size: u8, first_cell: Cell, direction: ShipOrientation) -> Option<Self> {
+    fn new(kind: ShipKind, first_cell: Cell, direction: ShipOrientation) -> Option<Self> {
+        let ship_size = kind.size();
         let (long, short) = match direction {
             ShipOrientation::Horizontal => (first_cell.x(), first_cell.y()),
             ShipOrientation::Vertical => (first_cell.y(), first_cell.x()),
@@ -108,6 +224,7 @@ impl Ship {
 
         if long <= 9 && long + ship_size - 1 <= 9 && short <= 9 {
             Some(Ship {
+                kind,
                 first_cell,
                 ship_size,
                 orientation: direction,
@@ -118,11 +235,33 @@ impl Ship {
         }
     }
 
+    /// Returns the kind of this ship.
+    pub fn kind(&self) -> &ShipKind {
+        &self.kind
+    }
+
+    /// Returns the orientation of this ship.
+    pub fn orientation(&self) -> ShipOrientation {
+        self.orientation
+    }
+
+    /// Returns the cell where this ship starts.
+    ///
+    /// It is the leftmost cell of a horizontal ship, or the topmost cell of a vertical one.
+    pub fn first_cell(&self) -> Cell {
+        self.first_cell
+    }
+
+    /// Returns the number of cells of this ship.
+    pub fn size(&self) -> u8 {
+        self.ship_size
+    }
+
     /// Returns all cells occupied by this ship.
     ///
     /// In a [crate::engine::grid::Grid], all these cells will be set to [crate::engine::grid::CellState::Occupied].
-    pub fn occupied_cells(&self) -> Vec<Cell> {
-        let mut cells = Vec::with_capacity(self.ship_size as usize);
+    pub fn occupied_cells(&self) -> ShipCells {
+        let mut cells = ShipCells::new();
         match self.orientation {
             ShipOrientation::Horizontal => {
                 for dx in 0..self.ship_size {
@@ -143,6 +282,21 @@ impl Ship {
         self.state == 0
     }
 
+    /// Returns the cells of this ship which have been hit, from the first one.
+    pub fn hits(&self) -> impl Iterator<Item = Cell> {
+        let state = self.state;
+        self.occupied_cells()
+            .into_iter()
+            .enumerate()
+            .filter(move |(bit, _)| state & (1u8 << bit) == 0)
+            .map(|(_, cell)| cell)
+    }
+
+    /// Returns how many cells of the ship have not been hit yet.
+    pub fn remaining(&self) -> u8 {
+        self.state.count_ones() as u8
+    }
+
     /// Checks whether the given cell is a part of the ship and records the hit.
     pub fn hit_at(&mut self, cell: &Cell) -> bool {
         let bit = self.contains(cell);
@@ -159,13 +313,16 @@ impl Ship {
     /// The space a ship occupies includes all the cells that define it, plus a one-cell border around them.
     /// If the second ship is on one or more of those cells, the ships are considered to be overlapping.
     pub fn is_overlapping(&self, other: &Ship) -> bool {
-        for cell in self.area() {
-            if other.contains(&cell).is_some() {
-                return true;
-            }
-        }
+        self.overlap(other).is_some()
+    }
 
-        false
+    /// Returns the first cell of the other ship which is in the space of this ship, if any.
+    ///
+    /// See [Ship::is_overlapping] for the definition of the space of a ship.
+    pub fn overlap(&self, other: &Ship) -> Option<Cell> {
+        self.area()
+            .into_iter()
+            .find(|cell| other.contains(cell).is_some())
     }
 
     /// Returns the cells that the ship occupies, including the surrounding border.
@@ -224,7 +381,8 @@ impl Ship {
     }
 }
 
-/// A fleet is a collection of ships, one for each [`ShipKind`].
+/// A fleet is a collection of ships, one for each [`ShipKind`], which may be reinforced by
+/// extra ships as a handicap.
 ///
 /// A fleet is constructed using a builder function that, given a ship kind,
 /// returns a ship of that kind. A generated ship may be rejected if it overlaps
@@ -240,11 +398,14 @@ impl Ship {
 /// ```
 ///
 #[derive(Debug, Clone)]
-pub struct Fleet([Ship; 5]);
+pub struct Fleet(Vec<Ship>);
 
 impl Fleet {
-    // defines the order in which the ships are stored into internal array
-    const COMPOSITION: [ShipKind; 5] = [
+    /// How many fleets [Fleet::random_valid] draws before giving up, when the rules refuse them.
+    pub const MAX_DRAWS: usize = 1000;
+
+    /// The kinds of ship in a fleet, in the order they are stored and built.
+    pub const COMPOSITION: [ShipKind; 5] = [
         ShipKind::AircraftCarrier,
         ShipKind::Battleship,
         ShipKind::Cruiser,
@@ -264,12 +425,23 @@ impl Fleet {
     /// let fleet = Fleet::build(|kind| kind.random());
     /// ```
     ///
-    pub fn build<Builder>(mut builder: Builder) -> Self
+    pub fn build<Builder>(builder: Builder) -> Self
     where
         Builder: FnMut(&ShipKind) -> Ship,
     {
-        let mut ships = Vec::<Ship>::with_capacity(Self::COMPOSITION.len());
-        for kind in Self::COMPOSITION.iter() {
+        Self::build_with(&Self::COMPOSITION, builder)
+    }
+
+    /// Builds a fleet with a ship for every kind of the given composition, in its order, like
+    /// [Fleet::build] does for the classic fleet.
+    ///
+    /// A kind can appear several times in the composition, to build several ships of that kind.
+    pub fn build_with<Builder>(composition: &[ShipKind], mut builder: Builder) -> Self
+    where
+        Builder: FnMut(&ShipKind) -> Ship,
+    {
+        let mut ships = Vec::<Ship>::with_capacity(composition.len());
+        for kind in composition.iter() {
             loop {
                 let ship = builder(kind);
                 if ships.iter().any(|s| s.is_overlapping(&ship)) {
@@ -280,37 +452,116 @@ impl Fleet {
                 break;
             }
         }
-        let tmp: [Ship; 5] = ships.try_into().unwrap();
 
-        Self(tmp)
+        Self(ships)
+    }
+
+    /// Draws a random classic fleet, whose ships don't touch each other.
+    ///
+    /// An error is returned if the ships can't be placed, see [Fleet::random_valid].
+    pub fn random() -> Result<Self, FleetError> {
+        Self::random_with(&mut rand::rng())
+    }
+
+    /// Draws a random classic fleet with the given generator, like [Fleet::random].
+    pub fn random_with<R: Rng + ?Sized>(rng: &mut R) -> Result<Self, FleetError> {
+        Self::random_valid(&FleetConfig::default(), &ClassicRules, rng)
+    }
+
+    /// Draws a random fleet as described by the configuration, which the rules allow.
+    ///
+    /// Every ship is placed only where it fits with the ones already placed, and a ship which
+    /// can't be placed anymore moves the previous ones to other placements, so that no fleet is
+    /// thrown away for overlapping ships. The rules, instead, judge the whole fleet: a fleet they
+    /// don't allow is drawn again, until [Fleet::MAX_DRAWS] fleets have been refused.
+    ///
+    /// An error is returned if the ships can't be placed within the budget of the sampler (see
+    /// [FleetSampler::DRAW_BUDGET]), or if the rules refuse every fleet.
+    pub fn random_valid<R: Rng + ?Sized>(
+        config: &FleetConfig,
+        rules: &dyn Rules,
+        rng: &mut R,
+    ) -> Result<Self, FleetError> {
+        let mut islands = Grid::default();
+        config
+            .islands
+            .iter()
+            .for_each(|cell| islands.mark(cell, CellState::Blocked));
+        let sampler = FleetSampler::new(&config.composition, &islands, &[]);
+
+        for _ in 0..Self::MAX_DRAWS {
+            let ships = sampler
+                .draw_with(FleetSampler::DRAW_BUDGET, rng)
+                .ok_or(FleetError::Unplaceable)?;
+            let fleet = Self(config.arrange(ships));
+            if rules.allows_fleet(&fleet) {
+                return Ok(fleet);
+            }
+        }
+
+        Err(FleetError::Unplaceable)
     }
 
     /// Builds a fleet using a given slice.
     ///
     /// The ships in the slice must match the composition of the fleet in the same order.
-    /// An error is returned if the slice does not contain exactly 5 ships or if any ship does not match the expected size.
-    pub fn new(ships: &[Ship]) -> Result<Self, String> {
-        if ships.len() != Self::COMPOSITION.len() {
-            return Err(format!(
-                "A fleet must contain exactly {} ships, but {} were provided",
-                Self::COMPOSITION.len(),
-                ships.len()
-            ));
-        }
-
-        for (i, kind) in Self::COMPOSITION.iter().enumerate() {
-            let ship = &ships[i];
-            let expected_size = kind.size();
-            if ship.ship_size != expected_size {
-                return Err(format!(
-                    "Ship of kind {} must have size {}, but size {} was provided",
-                    kind, expected_size, ship.ship_size
-                ));
+    /// An error is returned if the slice does not contain exactly 5 ships, if any ship does not
+    /// match the expected kind, or if two ships are overlapping.
+    pub fn new(ships: &[Ship]) -> Result<Self, FleetError> {
+        Self::check_ship_count(ships.len())?;
+
+        for (kind, ship) in Self::COMPOSITION.iter().zip(ships.iter()) {
+            if ship.kind != *kind {
+                return Err(FleetError::UnexpectedKind {
+                    expected: kind.clone(),
+                    found: ship.kind.clone(),
+                });
+            }
+        }
+
+        Self::with_ships(ships.to_vec())
+    }
+
+    /// Builds a custom fleet with the given ships, in any number and of any kind.
+    ///
+    /// An error is returned if two ships are overlapping.
+    pub fn with_ships(ships: Vec<Ship>) -> Result<Self, FleetError> {
+        for (i, ship) in ships.iter().enumerate() {
+            for other in &ships[..i] {
+                if let Some(at) = other.overlap(ship) {
+                    return Err(PlacementError::Overlap {
+                        kind: ship.kind.clone(),
+                        other: other.kind.clone(),
+                        at,
+                    }
+                    .into());
+                }
             }
         }
 
-        let ships_array: [Ship; 5] = Vec::from(ships).try_into().unwrap();
-        Ok(Self(ships_array))
+        Ok(Self(ships))
+    }
+
+    /// Adds an extra ship to the fleet, like the Destroyer given by a handicap, and returns its
+    /// identifier.
+    ///
+    /// An error is returned if the ship overlaps another ship of the fleet.
+    pub fn reinforce(&mut self, ship: Ship) -> Result<ShipId, FleetError> {
+        if let Some((other, at)) = self
+            .0
+            .iter()
+            .find_map(|other| other.overlap(&ship).map(|at| (other, at)))
+        {
+            return Err(PlacementError::Overlap {
+                kind: ship.kind.clone(),
+                other: other.kind.clone(),
+                at,
+            }
+            .into());
+        }
+
+        self.0.push(ship);
+        Ok(ShipId(self.0.len() - 1))
     }
 
     /// Evaluates if the attacked cell hits a ship
@@ -333,14 +584,97 @@ impl Fleet {
     ///  });
     ///
     ///  let cell = Cell::new(0, 0).unwrap();
-    ///  assert_eq!(fleet.hit_at(&cell), Some(ShipKind::AircraftCarrier));
+    ///  assert_eq!(fleet.hit_at(&cell).ship, Some(ShipKind::AircraftCarrier));
     ///
     ///  let cell = Cell::new(1, 0).unwrap();
-    ///  assert_eq!(fleet.hit_at(&cell), None);
+    ///  assert!(!fleet.hit_at(&cell).is_hit());
     /// ```
-    pub fn hit_at(&mut self, cell: &Cell) -> Option<ShipKind> {
-        let index = self.0.iter_mut().position(|ship| ship.hit_at(cell));
-        index.map(|index| Self::COMPOSITION[index].clone())
+    pub fn hit_at(&mut self, cell: &Cell) -> FleetShot {
+        let Some((index, ship)) = self
+            .0
+            .iter_mut()
+            .enumerate()
+            .find(|(_, ship)| ship.contains(cell).is_some())
+        else {
+            return FleetShot::default();
+        };
+
+        let afloat = !ship.is_sunk();
+        ship.hit_at(cell);
+        let sunk = afloat && ship.is_sunk();
+
+        FleetShot {
+            id: Some(ShipId(index)),
+            ship: Some(ship.kind.clone()),
+            sunk,
+            sunk_cells: sunk.then(|| ship.occupied_cells().to_vec()),
+        }
+    }
+
+    /// Checks whether a ship of the fleet occupies the cell, whether it has been hit or not.
+    pub fn is_occupied(&self, cell: &Cell) -> bool {
+        self.0
+            .iter()
+            .any(|ship| ship.occupied_cells().contains(cell))
+    }
+
+    /// Hits a random cell of the fleet which has not been hit yet.
+    ///
+    /// Returns the cell and the outcome of the hit, or `None` when the fleet is sunk.
+    pub fn hit_random_with<R: Rng + ?Sized>(&mut self, rng: &mut R) -> Option<(Cell, FleetShot)> {
+        let cells: Vec<Cell> = self
+            .0
+            .iter()
+            .flat_map(|ship| {
+                let hit: ShipCells = ship.hits().collect();
+                ship.occupied_cells()
+                    .into_iter()
+                    .filter(move |cell| !hit.contains(cell))
+            })
+            .collect();
+        let cell = *cells.choose(rng)?;
+
+        Some((cell, self.hit_at(&cell)))
+    }
+
+    /// Moves the ship with the given identifier to the position of the given ship, of the same
+    /// kind, keeping its damage.
+    ///
+    /// An error is returned if the fleet has no such ship, if it is of another kind, if it has
+    /// been sunk, or if it overlaps another ship of the fleet at the new position.
+    pub fn relocate(&mut self, id: ShipId, ship: &Ship) -> Result<(), FleetError> {
+        let index = id.0;
+        let current = self
+            .0
+            .get(index)
+            .ok_or_else(|| FleetError::MissingShip(ship.kind.clone()))?;
+        if current.kind != ship.kind {
+            return Err(FleetError::UnexpectedKind {
+                expected: current.kind.clone(),
+                found: ship.kind.clone(),
+            });
+        }
+        if current.is_sunk() {
+            return Err(FleetError::ShipSunk(ship.kind.clone()));
+        }
+
+        let others = self.0.iter().enumerate().filter(|(i, _)| *i != index);
+        for (_, other) in others {
+            if let Some(at) = other.overlap(ship) {
+                return Err(PlacementError::Overlap {
+                    kind: ship.kind.clone(),
+                    other: other.kind.clone(),
+                    at,
+                }
+                .into());
+            }
+        }
+
+        self.0[index] = Ship {
+            state: self.0[index].state,
+            ..ship.clone()
+        };
+        Ok(())
     }
 
     /// Checks whether all the ships are sunk
@@ -348,13 +682,138 @@ impl Fleet {
         self.0.iter().all(|ship| ship.is_sunk())
     }
 
-    /// Returns the ship by its type
-    pub fn get(&self, kind: &ShipKind) -> &Ship {
-        Self::COMPOSITION
+    /// Returns the health of every ship in the fleet, in the fleet order.
+    pub fn status(&self) -> Vec<ShipStatus> {
+        self.ships()
+            .map(|(id, ship)| ShipStatus {
+                id,
+                kind: ship.kind.clone(),
+                size: ship.ship_size,
+                hits: ship.ship_size - ship.remaining(),
+                sunk: ship.is_sunk(),
+            })
+            .collect()
+    }
+
+    /// Returns the kinds of the ships which are still afloat, in the fleet order.
+    ///
+    /// A damaged ship is still afloat until it is sunk.
+    pub fn remaining_ships(&self) -> Vec<ShipKind> {
+        self.0
             .iter()
-            .position(|ship| ship == kind)
-            .map(|index| &self.0[index])
-            .unwrap()
+            .filter(|ship| !ship.is_sunk())
+            .map(|ship| ship.kind.clone())
+            .collect()
+    }
+
+    /// Returns the layout of the fleet: where and how every ship has been placed.
+    ///
+    /// The extra ships added by [Fleet::reinforce] are not part of the layout.
+    pub fn layout(&self) -> FleetLayout {
+        FleetLayout(
+            self.0
+                .iter()
+                .take(Self::COMPOSITION.len())
+                .map(|ship| (ship.first_cell, ship.orientation))
+                .collect(),
+        )
+    }
+
+    /// Encodes the fleet layout in a compact string, which can be decoded by [Fleet::from_code].
+    ///
+    /// The damages of the ships are not encoded. See [FleetLayout] for the code format.
+    pub fn to_code(&self) -> String {
+        self.layout().to_string()
+    }
+
+    /// Builds a brand-new fleet from a string made by [Fleet::to_code].
+    ///
+    /// An error is returned if the code is not valid, if a ship exceeds the board boundaries,
+    /// or if two ships are overlapping.
+    pub fn from_code(code: &str) -> Result<Self, FleetError> {
+        Self::from_layout(&FleetLayout::from_str(code)?)
+    }
+
+    /// Builds a brand-new fleet with the ships placed as in the given layout.
+    ///
+    /// An error is returned if the layout doesn't have a placement for every ship, if a ship
+    /// exceeds the board boundaries, or if two ships are overlapping.
+    pub fn from_layout(layout: &FleetLayout) -> Result<Self, FleetError> {
+        Self::check_ship_count(layout.0.len())?;
+
+        let ships = Self::COMPOSITION
+            .iter()
+            .zip(layout.0.iter())
+            .map(|(kind, (cell, orientation))| kind.place(*cell, *orientation))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::new(&ships)
+    }
+
+    fn check_ship_count(found: usize) -> Result<(), FleetError> {
+        if found == Self::COMPOSITION.len() {
+            Ok(())
+        } else {
+            Err(FleetError::WrongShipCount {
+                expected: Self::COMPOSITION.len(),
+                found,
+            })
+        }
+    }
+
+    /// Returns every ship of the fleet with its identifier, in the fleet order.
+    pub fn ships(&self) -> impl Iterator<Item = (ShipId, &Ship)> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, ship)| (ShipId(index), ship))
+    }
+
+    /// Returns the ship with the given identifier, if the fleet has it.
+    pub fn ship(&self, id: ShipId) -> Option<&Ship> {
+        self.0.get(id.0)
+    }
+
+    /// Returns the first ship of the given kind, if the fleet has one.
+    pub fn get(&self, kind: &ShipKind) -> Option<&Ship> {
+        self.0.iter().find(|ship| ship.kind == *kind)
+    }
+
+    /// Returns the grid of the fleet, where the cells of the ships are occupied, or hit where
+    /// they have been damaged.
+    pub fn to_grid(&self) -> Grid {
+        let mut grid = Grid::from_ships(&self.0);
+        self.0
+            .iter()
+            .flat_map(Ship::hits)
+            .for_each(|cell| grid.mark(&cell, CellState::Hit));
+        grid
+    }
+}
+
+impl Display for Fleet {
+    /// Draws the fleet in the table of a [Grid], where every ship is written with the
+    /// [letter](ShipKind::letter) of its kind: uppercase where it is intact, and lowercase where
+    /// it has been hit.
+    ///
+    /// ```text
+    ///    A B C D E F G H I J
+    /// 01 a a A A A
+    /// 02
+    /// 03 B B B B
+    /// ```
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.to_grid().render_with(f, |cell, state| {
+            let letter = self
+                .0
+                .iter()
+                .find(|ship| ship.contains(cell).is_some())
+                .map_or(' ', |ship| ship.kind.letter());
+            match state {
+                CellState::Hit => letter.to_ascii_lowercase(),
+                _ => letter,
+            }
+        })
     }
 }
 
@@ -364,7 +823,7 @@ impl AsRef<[Ship]> for Fleet {
     /// # Examples
     ///
     /// ```rust
-    /// let fleet = Fleet::random();
+    /// let fleet = Fleet::random().unwrap();
     /// let grid = Grid::new(fleet.ships());
     /// println!("{:?}", grid);
     /// ```
@@ -373,6 +832,183 @@ impl AsRef<[Ship]> for Fleet {
     }
 }
 
+/// Describes the random fleets drawn by [Fleet::random_valid]: the kinds of their ships, and the
+/// islands where no ship can be placed.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FleetConfig {
+    composition: Vec<ShipKind>,
+    islands: Vec<Cell>,
+}
+
+impl FleetConfig {
+    /// Creates the configuration of a fleet made of the given kinds of ships, in the fleet
+    /// order. A kind can appear several times.
+    pub fn new(composition: &[ShipKind]) -> Self {
+        Self {
+            composition: composition.to_vec(),
+            islands: Vec::new(),
+        }
+    }
+
+    /// Keeps the ships off the given islands.
+    pub fn with_islands(mut self, islands: &[Cell]) -> Self {
+        self.islands = islands.to_vec();
+        self
+    }
+
+    /// Returns the kinds of the ships, in the fleet order.
+    pub fn composition(&self) -> &[ShipKind] {
+        &self.composition
+    }
+
+    // Puts the ships drawn by a sampler, which places the largest ones first, back in the fleet
+    // order.
+    fn arrange(&self, mut ships: Vec<Ship>) -> Vec<Ship> {
+        self.composition
+            .iter()
+            .filter_map(|kind| {
+                let index = ships.iter().position(|ship| ship.kind == *kind)?;
+                Some(ships.swap_remove(index))
+            })
+            .collect()
+    }
+}
+
+impl Default for FleetConfig {
+    /// Returns the configuration of the classic fleet, on a board without islands.
+    fn default() -> Self {
+        Self::new(&Fleet::COMPOSITION)
+    }
+}
+
+/// The identifier of a ship in a [Fleet].
+///
+/// Ships are never removed from a fleet, so a ship keeps its identifier for the whole game, even
+/// when it is relocated.
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy, PartialOrd, Ord)]
+pub struct ShipId(usize);
+
+impl ShipId {
+    /// Returns the position of the ship in the fleet order.
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// The result of a shot on a [Fleet].
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct FleetShot {
+    /// The identifier of the ship which has been hit, if any.
+    pub id: Option<ShipId>,
+
+    /// The kind of the ship which has been hit, if any.
+    pub ship: Option<ShipKind>,
+
+    /// Whether the ship hit has been sunk by this shot.
+    pub sunk: bool,
+
+    /// The cells of the ship sunk by this shot, if any.
+    pub sunk_cells: Option<Vec<Cell>>,
+}
+
+impl FleetShot {
+    /// Checks whether the shot has hit a ship.
+    pub fn is_hit(&self) -> bool {
+        self.ship.is_some()
+    }
+}
+
+/// The layout of a [Fleet]: the first cell and the orientation of every ship, in the fleet order.
+///
+/// A layout can be written as a compact code and parsed back from it. In the code, every ship
+/// is written as its first cell followed by `H` if horizontal or `V` if vertical, and ships are
+/// separated by a `/`. For example, `A1H/A3H/A5H/A7H/A9H` is a fleet with every ship on the left
+/// side of an odd row.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FleetLayout(Vec<(Cell, ShipOrientation)>);
+
+impl FleetLayout {
+    /// Creates a layout from the first cell and the orientation of every ship, in the fleet
+    /// order.
+    pub fn new(placements: Vec<(Cell, ShipOrientation)>) -> Self {
+        Self(placements)
+    }
+
+    /// Returns the first cell and the orientation of every ship.
+    pub fn placements(&self) -> &[(Cell, ShipOrientation)] {
+        &self.0
+    }
+}
+
+impl Display for FleetLayout {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let ships: Vec<String> = self
+            .0
+            .iter()
+            .map(|(cell, orientation)| match orientation {
+                ShipOrientation::Horizontal => format!("{cell}H"),
+                ShipOrientation::Vertical => format!("{cell}V"),
+            })
+            .collect();
+
+        write!(f, "{}", ships.join("/"))
+    }
+}
+
+impl FromStr for FleetLayout {
+    type Err = FleetError;
+
+    /// Parses a layout code, like `A1H/A3H/A5H/A7H/A9H`.
+    ///
+    /// Orientations are case-insensitive, like cells are.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split('/')
+            .map(|ship| {
+                let invalid = || FleetError::InvalidPlacementCode(ship.to_string());
+                let (cell, orientation) = ship
+                    .char_indices()
+                    .last()
+                    .map(|(i, _)| ship.split_at(i))
+                    .ok_or_else(invalid)?;
+                let orientation = match orientation {
+                    "H" | "h" => ShipOrientation::Horizontal,
+                    "V" | "v" => ShipOrientation::Vertical,
+                    _ => return Err(invalid()),
+                };
+
+                Ok((Cell::from_str(cell).map_err(|_| invalid())?, orientation))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+}
+
+/// A summary of the health of a ship in a [Fleet].
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ShipStatus {
+    /// The identifier of the ship in its fleet.
+    pub id: ShipId,
+
+    /// The kind of the ship.
+    pub kind: ShipKind,
+
+    /// The number of cells of the ship.
+    pub size: u8,
+
+    /// How many cells of the ship have been hit.
+    pub hits: u8,
+
+    /// Whether all the cells of the ship have been hit.
+    pub sunk: bool,
+}
+
+impl ShipStatus {
+    /// Returns `true` if the ship has been hit, but it is not sunk yet.
+    pub fn is_damaged(&self) -> bool {
+        self.hits > 0 && !self.sunk
+    }
+}
+
 /// Defines the orientation of a ship.
 ///
 /// In this game, a ship can be placed either horizontally (the same Y coordinate shared by all cells)
@@ -388,13 +1024,287 @@ impl ShipOrientation {
     /// Return a random orientation
     ///
     pub fn random() -> Self {
-        match rand::random::<u8>() % 2 {
+        Self::random_with(&mut rand::rng())
+    }
+
+    /// Return a random orientation taken from the given generator.
+    pub fn random_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        match rng.random::<u8>() % 2 {
             0 => ShipOrientation::Horizontal,
             _ => ShipOrientation::Vertical,
         }
     }
 }
 
+/// Draws random fleets which agree with what a player knows about the opponent's ships.
+///
+/// The ships are never placed on the cells missed or blocked on the shots grid, the sunk ships
+/// lie on hit cells only, and every hit cell is occupied by a ship. The cells are handled as
+/// bitmasks, so that thousands of fleets can be drawn for every move, and every search is bound
+/// by a budget of placements, so that a fleet which doesn't fit fails fast.
+#[derive(Debug, Clone)]
+pub struct FleetSampler {
+    // The allowed placements of every ship to place, with the masks of their cells and of their
+    // area. The sunk ships come first, then the largest ones, as they are the hardest to fit.
+    placements: Vec<Vec<(Ship, u128, u128)>>,
+    hits: u128,
+}
+
+impl FleetSampler {
+    /// How many placements of the ships [FleetSampler::draw_with] usually tries before giving up
+    /// a fleet, so that a crowded board fails fast.
+    pub const DRAW_BUDGET: usize = 10_000;
+
+    /// Creates a sampler of fleets made of the given kinds of ships, given the shots grid of
+    /// the player and the kinds of the ships they have sunk.
+    pub fn new(kinds: &[ShipKind], shots: &Grid, sunk: &[ShipKind]) -> Self {
+        let empty = shots.bits(&CellState::Empty) | shots.bits(&CellState::Occupied);
+        let hits = shots.bits(&CellState::Hit);
+
+        let mut sunk = sunk.to_vec();
+        let mut ships: Vec<(ShipKind, bool)> = kinds
+            .iter()
+            .map(|kind| {
+                let position = sunk.iter().position(|sunk| sunk == kind);
+                (kind.clone(), position.map(|i| sunk.remove(i)).is_some())
+            })
+            .collect();
+        ships.sort_by_key(|(kind, sunk)| (!sunk, Reverse(kind.size())));
+
+        let placements = ships
+            .into_iter()
+            .map(|(kind, sunk)| {
+                (0..100)
+                    .flat_map(|i| {
+                        [ShipOrientation::Horizontal, ShipOrientation::Vertical].map(
+                            |orientation| kind.ship(Cell::bounded(i % 10, i / 10), orientation),
+                        )
+                    })
+                    .flatten()
+                    .map(|ship| {
+                        let (cells, area) = (mask(&ship.occupied_cells()), mask(&ship.area()));
+                        (ship, cells, area)
+                    })
+                    .filter(|(_, cells, _)| match sunk {
+                        true => cells & !hits == 0,
+                        false => cells & !(hits | empty) == 0 && cells & empty != 0,
+                    })
+                    .collect()
+            })
+            .collect();
+
+        Self { placements, hits }
+    }
+
+    /// Keeps the ships to draw away from the given ones, as if they were already in the fleet.
+    pub fn avoiding(mut self, ships: &[Ship]) -> Self {
+        let (cells, area) = ships.iter().fold((0, 0), |(cells, area), ship| {
+            (
+                cells | mask(&ship.occupied_cells()),
+                area | mask(&ship.area()),
+            )
+        });
+        for placements in &mut self.placements {
+            placements.retain(|(_, other_cells, other_area)| {
+                other_cells & area == 0 && other_area & cells == 0
+            });
+        }
+
+        self
+    }
+
+    /// Draws a fleet with the given generator, without giving up on the ships which don't fit,
+    /// trying at most `budget` placements of the ships.
+    ///
+    /// Every ship is drawn among the placements which fit with the ships already drawn. When a
+    /// ship has none left, the previous ship is drawn again among its other placements, and so
+    /// on. Returns `None` when no fleet agrees with what the player knows, or when the budget is
+    /// over before a fleet is found.
+    pub fn draw_with<R: Rng + ?Sized>(&self, budget: usize, rng: &mut R) -> Option<Vec<Ship>> {
+        let mut remaining = budget;
+        let mut fleet = Vec::with_capacity(self.placements.len());
+        self.draw(0, 0, 0, &mut fleet, &mut remaining, rng)
+            .then_some(fleet)
+    }
+
+    /// Visits every fleet which agrees with what the player knows, exploring at most `budget`
+    /// placements of the ships.
+    ///
+    /// Returns the number of fleets visited, or `None` when the budget is not enough to find all
+    /// of them: in this case, the fleets visited so far are only a part of the solutions. The
+    /// search is not even started when the combinations of the placements exceed the budget.
+    pub fn enumerate<F: FnMut(&[Ship])>(&self, budget: usize, mut visit: F) -> Option<usize> {
+        if !self.fits(budget) {
+            return None;
+        }
+
+        let mut remaining = budget;
+        let mut fleet = Vec::with_capacity(self.placements.len());
+        self.explore(0, 0, 0, &mut fleet, &mut remaining, &mut visit)
+    }
+
+    /// Like [FleetSampler::enumerate], but the fleets are visited into an accumulator for every
+    /// placement of the first ship, and the accumulators are merged in the order of the
+    /// placements. Returns the merged accumulator, or `None` when the budget is not enough.
+    ///
+    /// With the `parallel` feature, the placements of the first ship are explored on all the
+    /// cores: the result is the same as long as merging doesn't depend on the order.
+    pub fn enumerate_with<T, I, V, M>(
+        &self,
+        budget: usize,
+        init: I,
+        visit: V,
+        merge: M,
+    ) -> Option<T>
+    where
+        T: Send,
+        I: Fn() -> T + Sync + Send,
+        V: Fn(&mut T, &[Ship]) + Sync + Send,
+        M: Fn(T, T) -> T,
+    {
+        if !self.fits(budget) {
+            return None;
+        }
+        let Some(first) = self.placements.first() else {
+            let mut accumulator = init();
+            visit(&mut accumulator, &[]);
+            return Some(accumulator);
+        };
+
+        // Every placement is explored with what is left of the budget when it starts, so the
+        // search fails as a whole exactly when it would fail on a single thread.
+        let used = AtomicUsize::new(first.len());
+        let accumulators = parallel::map(first, |(ship, cells, area)| {
+            let mut accumulator = init();
+            let mut remaining = budget.checked_sub(used.load(Ordering::Relaxed))?;
+            let available = remaining;
+            let mut fleet = vec![ship.clone()];
+            self.explore(
+                1,
+                *area,
+                *cells,
+                &mut fleet,
+                &mut remaining,
+                &mut |fleet: &[Ship]| visit(&mut accumulator, fleet),
+            )?;
+            used.fetch_add(available - remaining, Ordering::Relaxed);
+            Some(accumulator)
+        });
+
+        let accumulators: Option<Vec<T>> = accumulators.into_iter().collect();
+        let merged = accumulators?.into_iter().fold(init(), merge);
+        (used.into_inner() <= budget).then_some(merged)
+    }
+
+    // Returns whether the combinations of the placements fit in the budget.
+    fn fits(&self, budget: usize) -> bool {
+        let combinations = self
+            .placements
+            .iter()
+            .try_fold(1usize, |combinations, placements| {
+                combinations.checked_mul(placements.len())
+            });
+        combinations.is_some_and(|combinations| combinations <= budget)
+    }
+
+    // Places the ship at the given depth in a random position which doesn't touch the ships
+    // already in the fleet, then the next ships, trying the other positions in turn when they
+    // can't be placed. Returns whether the fleet has been completed before the remaining budget
+    // is over.
+    fn draw<R: Rng + ?Sized>(
+        &self,
+        depth: usize,
+        taken: u128,
+        occupied: u128,
+        fleet: &mut Vec<Ship>,
+        remaining: &mut usize,
+        rng: &mut R,
+    ) -> bool {
+        // A hit next to the ships placed can't be covered by the next ones anymore.
+        if self.hits & !occupied & taken != 0 {
+            return false;
+        }
+        let Some(placements) = self.placements.get(depth) else {
+            return self.hits & !occupied == 0;
+        };
+
+        let mut candidates: Vec<_> = placements
+            .iter()
+            .filter(|(_, cells, area)| cells & taken == 0 && area & occupied == 0)
+            .collect();
+        candidates.shuffle(rng);
+        for (ship, cells, area) in candidates {
+            let Some(left) = remaining.checked_sub(1) else {
+                return false;
+            };
+            *remaining = left;
+            fleet.push(ship.clone());
+            if self.draw(
+                depth + 1,
+                taken | area,
+                occupied | cells,
+                fleet,
+                remaining,
+                rng,
+            ) {
+                return true;
+            }
+            fleet.pop();
+        }
+
+        false
+    }
+
+    // Places the ship at the given depth in every position which doesn't touch the ships already
+    // in the fleet, then the next ships. Returns the number of fleets found, or `None` when the
+    // remaining budget is over.
+    fn explore<F: FnMut(&[Ship])>(
+        &self,
+        depth: usize,
+        taken: u128,
+        occupied: u128,
+        fleet: &mut Vec<Ship>,
+        remaining: &mut usize,
+        visit: &mut F,
+    ) -> Option<usize> {
+        let Some(placements) = self.placements.get(depth) else {
+            if self.hits & !occupied != 0 {
+                return Some(0);
+            }
+            visit(fleet);
+            return Some(1);
+        };
+
+        let mut found = 0;
+        for (ship, cells, area) in placements {
+            *remaining = remaining.checked_sub(1)?;
+            if cells & taken != 0 || area & occupied != 0 {
+                continue;
+            }
+
+            fleet.push(ship.clone());
+            found += self.explore(
+                depth + 1,
+                taken | area,
+                occupied | cells,
+                fleet,
+                remaining,
+                visit,
+            )?;
+            fleet.pop();
+        }
+
+        Some(found)
+    }
+}
+
+// Returns the bitmask of the cells, where the cell at X, Y is the bit 10 * Y + X.
+fn mask(cells: &[Cell]) -> u128 {
+    cells
+        .iter()
+        .fold(0, |mask, cell| mask | 1 << (10 * cell.y() + cell.x()))
+}
+
 fn get_ship_state(size: u8) -> u8 {
     let mut state = 0u8;
     for i in 0u8..size {
@@ -407,13 +1317,27 @@ fn get_ship_state(size: u8) -> u8 {
 #[cfg(test)]
 pub(crate) mod tests {
     use crate::engine::{
-        fleet::{Fleet, Ship, ShipKind, ShipOrientation},
-        grid::Cell,
+        fleet::{
+            Fleet, FleetConfig, FleetError, FleetLayout, FleetSampler, FleetShot, PlacementError,
+            Ship, ShipId, ShipKind, ShipOrientation, ShipStatus,
+        },
+        grid::{Cell, CellState, Grid},
+        rules::{ClassicRules, Rules},
     };
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
     use rstest::{fixture, rstest};
+    use std::str::FromStr;
+    use std::time::{Duration, Instant};
+    use strum::IntoEnumIterator;
 
     #[fixture]
     pub fn fixed_fleet(#[default(0)] col: u8) -> Fleet {
+        fixed_fleet_at(col)
+    }
+
+    // Builds a fleet with every ship horizontal on an even row, starting from the given column.
+    fn fixed_fleet_at(col: u8) -> Fleet {
         let aircraft_carrier = ShipKind::AircraftCarrier
             .ship(Cell::bounded(col, 0), ShipOrientation::Horizontal)
             .unwrap();
@@ -458,7 +1382,7 @@ pub(crate) mod tests {
         #[case] direction: ShipOrientation,
         #[case] expected: bool,
     ) {
-        let ship = ShipKind::AircraftCarrier.ship(Cell::bounded(x, y), direction.clone());
+        let ship = ShipKind::AircraftCarrier.ship(Cell::bounded(x, y), direction);
         if expected {
             assert!(ship.is_some());
 
@@ -766,7 +1690,7 @@ pub(crate) mod tests {
 
     #[rstest]
     fn test_hit_fleet_at() {
-        let mut x_ships = (0u8..9).into_iter().step_by(2);
+        let mut x_ships = (0u8..9).step_by(2);
         let mut fleet = Fleet::build(|kind| {
             kind.ship(
                 Cell::bounded(x_ships.next().unwrap(), 0),
@@ -776,10 +1700,308 @@ pub(crate) mod tests {
         });
 
         let cell = Cell::new(0, 0).unwrap();
-        assert_eq!(fleet.hit_at(&cell), Some(ShipKind::AircraftCarrier));
+        let outcome = fleet.hit_at(&cell);
+        assert!(outcome.is_hit());
+        assert_eq!(outcome.ship, Some(ShipKind::AircraftCarrier));
+        assert!(!outcome.sunk);
+        assert_eq!(outcome.sunk_cells, None);
 
         let cell = Cell::new(1, 0).unwrap();
-        assert_eq!(fleet.hit_at(&cell), None);
+        assert_eq!(fleet.hit_at(&cell), FleetShot::default());
+
+        fleet.hit_at(&Cell::bounded(8, 0));
+        let outcome = fleet.hit_at(&Cell::bounded(8, 1));
+        assert_eq!(outcome.ship, Some(ShipKind::Destroyer));
+        assert!(outcome.sunk);
+        assert_eq!(
+            outcome.sunk_cells,
+            Some(vec![Cell::bounded(8, 0), Cell::bounded(8, 1)])
+        );
+        assert!(!fleet.hit_at(&Cell::bounded(8, 1)).sunk);
+    }
+
+    #[rstest]
+    fn test_display_fleet(mut fixed_fleet: Fleet) {
+        fixed_fleet.hit_at(&Cell::bounded(0, 0));
+        fixed_fleet.hit_at(&Cell::bounded(1, 0));
+        fixed_fleet.hit_at(&Cell::bounded(3, 1));
+
+        let grid = fixed_fleet.to_grid();
+        assert_eq!(grid.at(&Cell::bounded(0, 0)), &CellState::Hit);
+        assert_eq!(grid.at(&Cell::bounded(2, 0)), &CellState::Occupied);
+        assert_eq!(grid.at(&Cell::bounded(3, 1)), &CellState::Empty);
+
+        let diagram = fixed_fleet.to_string();
+        let rows: Vec<&str> = diagram.lines().map(str::trim_end).collect();
+        assert_eq!(rows.len(), 11);
+        assert_eq!(rows[0], "   A B C D E F G H I J");
+        assert_eq!(rows[1], "01 a a A A A");
+        assert_eq!(rows[2], "02");
+        assert_eq!(rows[3], "03 B B B B");
+        assert_eq!(rows[5], "05 C C C");
+        assert_eq!(rows[7], "07 S S S");
+        assert_eq!(rows[9], "09 D D");
+    }
+
+    #[rstest]
+    fn test_fleet_status(mut fixed_fleet: Fleet) {
+        fixed_fleet.hit_at(&Cell::bounded(0, 0));
+        fixed_fleet.hit_at(&Cell::bounded(1, 0));
+        fixed_fleet.hit_at(&Cell::bounded(0, 8));
+        fixed_fleet.hit_at(&Cell::bounded(1, 8));
+
+        let status = fixed_fleet.status();
+        assert_eq!(status.len(), 5);
+        assert_eq!(
+            status[0],
+            ShipStatus {
+                id: ShipId(0),
+                kind: ShipKind::AircraftCarrier,
+                size: 5,
+                hits: 2,
+                sunk: false
+            }
+        );
+        assert_eq!(
+            status[1],
+            ShipStatus {
+                id: ShipId(1),
+                kind: ShipKind::Battleship,
+                size: 4,
+                hits: 0,
+                sunk: false
+            }
+        );
+        assert_eq!(
+            status[4],
+            ShipStatus {
+                id: ShipId(4),
+                kind: ShipKind::Destroyer,
+                size: 2,
+                hits: 2,
+                sunk: true
+            }
+        );
+    }
+
+    #[rstest]
+    fn test_ship_hits() {
+        let mut ship = ShipKind::Cruiser
+            .ship(Cell::bounded(2, 2), ShipOrientation::Vertical)
+            .unwrap();
+        assert_eq!(ship.hits().count(), 0);
+        assert_eq!(ship.remaining(), 3);
+
+        ship.hit_at(&Cell::bounded(2, 3));
+        assert_eq!(ship.hits().count(), 1);
+        assert_eq!(ship.remaining(), 2);
+
+        // hitting twice the same cell or missing the ship doesn't count
+        ship.hit_at(&Cell::bounded(2, 3));
+        ship.hit_at(&Cell::bounded(3, 3));
+        assert_eq!(ship.hits().count(), 1);
+        assert_eq!(ship.remaining(), 2);
+
+        ship.hit_at(&Cell::bounded(2, 2));
+        ship.hit_at(&Cell::bounded(2, 4));
+        assert_eq!(ship.hits().count(), 3);
+        assert_eq!(ship.remaining(), 0);
+    }
+
+    #[rstest]
+    #[case(
+        ShipKind::AircraftCarrier,
+        Cell::bounded(2, 3),
+        ShipOrientation::Horizontal
+    )]
+    #[case(ShipKind::Cruiser, Cell::bounded(7, 0), ShipOrientation::Vertical)]
+    #[case(ShipKind::Submarine, Cell::bounded(0, 9), ShipOrientation::Horizontal)]
+    fn test_ship_accessors(
+        #[case] kind: ShipKind,
+        #[case] first_cell: Cell,
+        #[case] orientation: ShipOrientation,
+    ) {
+        let ship = kind.ship(first_cell, orientation).unwrap();
+        assert_eq!(ship.kind(), &kind);
+        assert_eq!(ship.first_cell(), first_cell);
+        assert_eq!(ship.orientation(), orientation);
+        assert_eq!(ship.size(), kind.size());
+        // The cells of the ship are kept inline.
+        let cells = ship.occupied_cells();
+        assert_eq!(cells.len(), kind.size() as usize);
+        assert!(!cells.spilled());
+        assert_eq!(cells[0], first_cell);
+    }
+
+    #[rstest]
+    fn test_ship_hit_cells() {
+        let mut ship = ShipKind::Battleship
+            .ship(Cell::bounded(3, 5), ShipOrientation::Horizontal)
+            .unwrap();
+        assert_eq!(ship.hits().next(), None);
+
+        ship.hit_at(&Cell::bounded(6, 5));
+        ship.hit_at(&Cell::bounded(4, 5));
+        ship.hit_at(&Cell::bounded(7, 5));
+        assert_eq!(
+            ship.hits().collect::<Vec<_>>(),
+            vec![Cell::bounded(4, 5), Cell::bounded(6, 5)]
+        );
+    }
+
+    #[rstest]
+    fn test_new_fleet_with_wrong_kinds(fixed_fleet: Fleet) {
+        let mut ships = fixed_fleet.as_ref().to_vec();
+        ships.swap(2, 3);
+        assert_eq!(
+            Fleet::new(&ships).unwrap_err(),
+            FleetError::UnexpectedKind {
+                expected: ShipKind::Cruiser,
+                found: ShipKind::Submarine
+            }
+        );
+        assert_eq!(
+            Fleet::new(&ships[1..]).unwrap_err(),
+            FleetError::WrongShipCount {
+                expected: 5,
+                found: 4
+            }
+        );
+
+        ships.swap(2, 3);
+        assert!(Fleet::new(&ships).is_ok());
+    }
+
+    #[rstest]
+    #[case(0, false, false)]
+    #[case(1, true, false)]
+    #[case(2, false, true)]
+    fn test_ship_status_is_damaged(#[case] hits: u8, #[case] damaged: bool, #[case] sunk: bool) {
+        let status = ShipStatus {
+            id: ShipId(4),
+            kind: ShipKind::Destroyer,
+            size: 2,
+            hits,
+            sunk,
+        };
+        assert_eq!(status.is_damaged(), damaged);
+    }
+
+    #[rstest]
+    fn test_fleet_remaining_ships(mut fixed_fleet: Fleet) {
+        assert_eq!(fixed_fleet.remaining_ships(), Fleet::COMPOSITION.to_vec());
+
+        // damage the cruiser and sink the destroyer
+        fixed_fleet.hit_at(&Cell::bounded(0, 4));
+        fixed_fleet.hit_at(&Cell::bounded(0, 8));
+        fixed_fleet.hit_at(&Cell::bounded(1, 8));
+
+        assert_eq!(
+            fixed_fleet.remaining_ships(),
+            vec![
+                ShipKind::AircraftCarrier,
+                ShipKind::Battleship,
+                ShipKind::Cruiser,
+                ShipKind::Submarine,
+            ]
+        );
+    }
+
+    #[rstest]
+    fn test_fleet_to_code(fixed_fleet: Fleet) {
+        assert_eq!(fixed_fleet.to_code(), "A1H/A3H/A5H/A7H/A9H");
+
+        let fleet = fixed_fleet_at(5);
+        assert_eq!(fleet.to_code(), "F1H/F3H/F5H/F7H/F9H");
+    }
+
+    #[rstest]
+    fn test_fleet_code_round_trip() {
+        for _ in 0..20 {
+            let fleet = Fleet::build(|kind| kind.random());
+            let decoded = Fleet::from_code(&fleet.to_code()).unwrap();
+            assert_eq!(decoded.as_ref(), fleet.as_ref());
+        }
+    }
+
+    #[rstest]
+    fn test_fleet_from_code_is_not_damaged(mut fixed_fleet: Fleet) {
+        fixed_fleet.hit_at(&Cell::bounded(0, 0));
+        let decoded = Fleet::from_code(&fixed_fleet.to_code()).unwrap();
+        assert!(
+            decoded
+                .get(&ShipKind::AircraftCarrier)
+                .unwrap()
+                .hits()
+                .next()
+                .is_none()
+        );
+    }
+
+    #[rstest]
+    #[case("a1v/c1v/e1V/G1v/I1V")]
+    #[case("A1H/A3H/A5H/A7H/A9H")]
+    fn test_fleet_from_code(#[case] code: &str) {
+        assert!(Fleet::from_code(code).is_ok());
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::missing_ship("A1H/A3H/A5H/A7H")]
+    #[case::too_many_ships("A1H/A3H/A5H/A7H/A9H/J1V")]
+    #[case::bad_orientation("A1H/A3H/A5H/A7H/A9D")]
+    #[case::missing_orientation("A1H/A3H/A5H/A7H/A9")]
+    #[case::bad_cell("A1H/A3H/A5H/A7H/K9H")]
+    #[case::out_of_board("A1H/A3H/A5H/A7H/J9H")]
+    #[case::overlapping("A1H/A2H/A5H/A7H/A9H")]
+    fn test_fleet_from_invalid_code(#[case] code: &str) {
+        assert!(Fleet::from_code(code).is_err());
+    }
+
+    #[rstest]
+    #[case::out_of_board(
+        "A1H/A3H/A5H/A7H/J9H",
+        PlacementError::OutOfBounds {
+            kind: ShipKind::Destroyer,
+            cell: Cell::bounded(9, 8),
+            orientation: ShipOrientation::Horizontal,
+        }
+    )]
+    #[case::overlapping(
+        "A1H/A2H/A5H/A7H/A9H",
+        PlacementError::Overlap {
+            kind: ShipKind::Battleship,
+            other: ShipKind::AircraftCarrier,
+            at: Cell::bounded(0, 1),
+        }
+    )]
+    fn test_fleet_placement_errors(#[case] code: &str, #[case] expected: PlacementError) {
+        assert_eq!(
+            Fleet::from_code(code).unwrap_err(),
+            FleetError::Placement(expected)
+        );
+    }
+
+    #[rstest]
+    fn test_fleet_from_layout(fixed_fleet: Fleet) {
+        let fleet = Fleet::from_layout(&fixed_fleet.layout()).unwrap();
+        assert_eq!(fleet.as_ref(), fixed_fleet.as_ref());
+
+        let layout = FleetLayout::from_str("A1H/A3H/A5H/A7H").unwrap();
+        assert!(Fleet::from_layout(&layout).is_err());
+    }
+
+    #[rstest]
+    fn test_fleet_layout_display_and_from_str() {
+        let layout = FleetLayout::from_str("b2v/J10H").unwrap();
+        assert_eq!(
+            layout.placements(),
+            &[
+                (Cell::bounded(1, 1), ShipOrientation::Vertical),
+                (Cell::bounded(9, 9), ShipOrientation::Horizontal),
+            ]
+        );
+        assert_eq!(layout.to_string(), "B2V/J10H");
     }
 
     #[rstest]
@@ -801,4 +2023,352 @@ pub(crate) mod tests {
 
         assert!(fleet.is_sunk());
     }
+
+    #[rstest]
+    pub fn test_fleet_hit_random(fixed_fleet: Fleet) {
+        let mut fleet = fixed_fleet;
+        let mut rng = StdRng::seed_from_u64(7);
+        assert!(fleet.is_occupied(&Cell::bounded(0, 0)));
+        assert!(!fleet.is_occupied(&Cell::bounded(0, 1)));
+
+        for _ in 0..17 {
+            let (cell, outcome) = fleet.hit_random_with(&mut rng).unwrap();
+            let ship = fleet.ship(outcome.id.unwrap()).unwrap();
+            assert!(ship.hits().any(|hit| hit == cell));
+        }
+
+        assert!(fleet.is_sunk());
+        assert_eq!(fleet.hit_random_with(&mut rng), None);
+    }
+
+    #[rstest]
+    pub fn test_fleet_reinforce(fixed_fleet: Fleet) {
+        let mut fleet = fixed_fleet;
+        let overlapping = ShipKind::Destroyer
+            .ship(Cell::bounded(1, 8), ShipOrientation::Horizontal)
+            .unwrap();
+        assert_eq!(
+            fleet.reinforce(overlapping),
+            Err(FleetError::Placement(PlacementError::Overlap {
+                kind: ShipKind::Destroyer,
+                other: ShipKind::Destroyer,
+                at: Cell::bounded(1, 8),
+            }))
+        );
+
+        let extra = ShipKind::Destroyer
+            .ship(Cell::bounded(9, 0), ShipOrientation::Vertical)
+            .unwrap();
+        let id = fleet.reinforce(extra).unwrap();
+        assert_eq!(id.index(), 5);
+        assert_eq!(fleet.as_ref().len(), 6);
+        assert_eq!(fleet.status().len(), 6);
+        assert_eq!(fleet.layout().placements().len(), 5);
+        assert_eq!(
+            fleet.get(&ShipKind::Destroyer).unwrap().first_cell(),
+            Cell::bounded(0, 8)
+        );
+
+        // The extra ship must be sunk too.
+        let cells: Vec<Cell> = fleet.as_ref()[..5]
+            .iter()
+            .flat_map(|ship| ship.occupied_cells())
+            .collect();
+        cells.iter().for_each(|cell| {
+            fleet.hit_at(cell);
+        });
+        assert!(!fleet.is_sunk());
+        fleet.hit_at(&Cell::bounded(9, 0));
+        let outcome = fleet.hit_at(&Cell::bounded(9, 1));
+        assert_eq!(outcome.id, Some(id));
+        assert!(outcome.sunk);
+        assert!(fleet.is_sunk());
+    }
+
+    #[rstest]
+    fn test_custom_fleet() {
+        let destroyers = [
+            Cell::bounded(0, 0),
+            Cell::bounded(0, 2),
+            Cell::bounded(0, 4),
+        ];
+        let mut cells = destroyers.iter();
+        let mut fleet = Fleet::build_with(&[const { ShipKind::Destroyer }; 3], |kind| {
+            kind.ship(*cells.next().unwrap(), ShipOrientation::Horizontal)
+                .unwrap()
+        });
+        assert_eq!(fleet.ships().count(), 3);
+        assert_eq!(
+            fleet.get(&ShipKind::Destroyer).unwrap().first_cell(),
+            destroyers[0]
+        );
+        assert!(fleet.get(&ShipKind::Submarine).is_none());
+        assert_eq!(
+            fleet.relocate(ShipId(3), &ShipKind::Destroyer.random()),
+            Err(FleetError::MissingShip(ShipKind::Destroyer))
+        );
+        assert_eq!(
+            fleet.relocate(ShipId(0), &ShipKind::Submarine.random()),
+            Err(FleetError::UnexpectedKind {
+                expected: ShipKind::Destroyer,
+                found: ShipKind::Submarine
+            })
+        );
+
+        fleet.hit_at(&Cell::bounded(0, 2));
+        let outcome = fleet.hit_at(&Cell::bounded(1, 2));
+        let id = outcome.id.unwrap();
+        assert_eq!(id.index(), 1);
+        assert!(outcome.sunk);
+        assert_eq!(fleet.ship(id).unwrap().first_cell(), destroyers[1]);
+        let sunk: Vec<ShipId> = fleet
+            .status()
+            .into_iter()
+            .filter(|status| status.sunk)
+            .map(|status| status.id)
+            .collect();
+        assert_eq!(sunk, [id]);
+        assert_eq!(fleet.remaining_ships().len(), 2);
+
+        let ships: Vec<Ship> = fleet.as_ref().to_vec();
+        assert!(Fleet::with_ships(ships.clone()).is_ok());
+        assert!(Fleet::new(&ships).is_err());
+        assert!(Fleet::with_ships(vec![ships[0].clone(), ships[0].clone()]).is_err());
+    }
+
+    #[rstest]
+    fn test_relocate_same_kind() {
+        let destroyers = [0, 2, 4].map(|y| {
+            ShipKind::Destroyer
+                .ship(Cell::bounded(0, y), ShipOrientation::Horizontal)
+                .unwrap()
+        });
+        let mut fleet = Fleet::with_ships(destroyers.to_vec()).unwrap();
+        fleet.hit_at(&Cell::bounded(0, 0));
+        fleet.hit_at(&Cell::bounded(1, 0));
+        let moved = ShipKind::Destroyer
+            .ship(Cell::bounded(8, 8), ShipOrientation::Vertical)
+            .unwrap();
+
+        // The first Destroyer has been sunk, but the others can still move.
+        assert_eq!(
+            fleet.relocate(ShipId(0), &moved),
+            Err(FleetError::ShipSunk(ShipKind::Destroyer))
+        );
+        fleet.relocate(ShipId(2), &moved).unwrap();
+        let cells: Vec<Cell> = fleet.ships().map(|(_, ship)| ship.first_cell()).collect();
+        assert_eq!(
+            cells,
+            [
+                Cell::bounded(0, 0),
+                Cell::bounded(0, 2),
+                Cell::bounded(8, 8)
+            ]
+        );
+    }
+
+    #[derive(Debug)]
+    struct NoFleet;
+
+    impl Rules for NoFleet {
+        fn name(&self) -> &str {
+            "No fleet"
+        }
+
+        fn allows_fleet(&self, _fleet: &Fleet) -> bool {
+            false
+        }
+    }
+
+    #[rstest]
+    fn test_random_valid() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let fleet = Fleet::random_valid(&FleetConfig::default(), &ClassicRules, &mut rng);
+            assert!(Fleet::new(fleet.unwrap().as_ref()).is_ok());
+        }
+
+        let islands: Vec<Cell> = (0..10).map(|x| Cell::bounded(x, 0)).collect();
+        let config = FleetConfig::new(&[
+            ShipKind::Submarine,
+            ShipKind::Destroyer,
+            ShipKind::Destroyer,
+        ])
+        .with_islands(&islands);
+        let fleet = Fleet::random_valid(&config, &ClassicRules, &mut rng).unwrap();
+        let kinds: Vec<&ShipKind> = fleet.as_ref().iter().map(Ship::kind).collect();
+        assert_eq!(kinds, config.composition().iter().collect::<Vec<_>>());
+        assert!(islands.iter().all(|cell| !fleet.is_occupied(cell)));
+
+        // Only the first row is left for two Aircraft Carriers, which can't both lie there.
+        let islands: Vec<Cell> = (10..100).map(|i| Cell::bounded(i % 10, i / 10)).collect();
+        let config = FleetConfig::new(&[ShipKind::AircraftCarrier, ShipKind::AircraftCarrier])
+            .with_islands(&islands);
+        assert_eq!(
+            Fleet::random_valid(&config, &ClassicRules, &mut rng).unwrap_err(),
+            FleetError::Unplaceable
+        );
+        assert_eq!(
+            Fleet::random_valid(&FleetConfig::default(), &NoFleet, &mut rng).unwrap_err(),
+            FleetError::Unplaceable
+        );
+    }
+
+    #[rstest]
+    fn test_random_valid_fails_fast() {
+        let mut rng = StdRng::seed_from_u64(7);
+        // Twenty Aircraft Carriers don't fit the board, in any of the countless orders.
+        let config = FleetConfig::new(&[const { ShipKind::AircraftCarrier }; 20]);
+
+        let start = Instant::now();
+        assert_eq!(
+            Fleet::random_valid(&config, &ClassicRules, &mut rng).unwrap_err(),
+            FleetError::Unplaceable
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[rstest]
+    fn test_random_fleet() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for _ in 0..20 {
+            let fleet = Fleet::random_with(&mut rng).unwrap();
+            assert!(Fleet::new(fleet.as_ref()).is_ok());
+        }
+    }
+
+    #[rstest]
+    fn test_ship_kind_random_fits() {
+        let mut rng = StdRng::seed_from_u64(7);
+        for kind in ShipKind::iter() {
+            for _ in 0..100 {
+                let ship = kind.random_with(&mut rng);
+                assert_eq!(ship.occupied_cells().len(), kind.size() as usize);
+            }
+        }
+    }
+
+    #[rstest]
+    pub fn test_fleet_relocate(fixed_fleet: Fleet) {
+        let mut fleet = fixed_fleet;
+        fleet.hit_at(&Cell::bounded(1, 6));
+        let submarine = ShipKind::Submarine
+            .ship(Cell::bounded(8, 0), ShipOrientation::Vertical)
+            .unwrap();
+        let id = ShipId(3);
+
+        fleet.relocate(id, &submarine).unwrap();
+        let relocated = fleet.get(&ShipKind::Submarine).unwrap();
+        assert_eq!(relocated.first_cell(), Cell::bounded(8, 0));
+        assert_eq!(relocated.orientation(), ShipOrientation::Vertical);
+        assert!(relocated.hits().eq([Cell::bounded(8, 1)]));
+        assert!(!fleet.is_occupied(&Cell::bounded(0, 6)));
+
+        let overlapping = ShipKind::Submarine
+            .ship(Cell::bounded(5, 0), ShipOrientation::Vertical)
+            .unwrap();
+        assert_eq!(
+            fleet.relocate(id, &overlapping),
+            Err(FleetError::Placement(PlacementError::Overlap {
+                kind: ShipKind::Submarine,
+                other: ShipKind::AircraftCarrier,
+                at: Cell::bounded(5, 0),
+            }))
+        );
+
+        fleet.hit_at(&Cell::bounded(8, 0));
+        fleet.hit_at(&Cell::bounded(8, 2));
+        assert_eq!(
+            fleet.relocate(id, &submarine),
+            Err(FleetError::ShipSunk(ShipKind::Submarine))
+        );
+    }
+
+    #[rstest]
+    fn test_sampler() {
+        let mut shots = Grid::default();
+        for x in 0..10 {
+            shots.mark(&Cell::bounded(x, 0), CellState::Miss);
+        }
+        shots.mark(&Cell::bounded(3, 3), CellState::Blocked);
+        for cell in [(0, 9), (1, 9), (5, 5)] {
+            shots.mark(&Cell::bounded(cell.0, cell.1), CellState::Hit);
+        }
+        let kinds: Vec<ShipKind> = ShipKind::iter().collect();
+        let sampler = FleetSampler::new(&kinds, &shots, &[ShipKind::Destroyer]);
+        let mut rng = StdRng::seed_from_u64(7);
+
+        let fleets: Vec<Vec<Ship>> = (0..1000)
+            .filter_map(|_| sampler.draw_with(FleetSampler::DRAW_BUDGET, &mut rng))
+            .collect();
+        assert!(fleets.len() > 10);
+        for fleet in fleets {
+            assert_eq!(fleet.len(), 5);
+            let cells: Vec<Cell> = fleet
+                .iter()
+                .flat_map(|ship| ship.occupied_cells())
+                .collect();
+            assert!(cells.iter().all(|cell| cell.y() > 0));
+            assert!(!cells.contains(&Cell::bounded(3, 3)));
+            assert!(cells.contains(&Cell::bounded(5, 5)));
+
+            let destroyer = fleet.iter().find(|ship| ship.kind == ShipKind::Destroyer);
+            assert_eq!(
+                destroyer.unwrap().occupied_cells().as_slice(),
+                [Cell::bounded(0, 9), Cell::bounded(1, 9)]
+            );
+            for (i, ship) in fleet.iter().enumerate() {
+                assert!(
+                    fleet[i + 1..]
+                        .iter()
+                        .all(|other| !ship.is_overlapping(other))
+                );
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_enumerate(fixed_fleet: Fleet) {
+        // Every cell is explored but the row of the Destroyer.
+        let mut shots = Grid::default();
+        for cell in (0..100).map(|i| Cell::bounded(i % 10, i / 10)) {
+            let state = match fixed_fleet.is_occupied(&cell) {
+                true => CellState::Hit,
+                false => CellState::Miss,
+            };
+            if cell.y() != 8 {
+                shots.mark(&cell, state);
+            }
+        }
+        let kinds: Vec<ShipKind> = ShipKind::iter().collect();
+        let mut sunk = kinds.clone();
+        sunk.pop();
+        let sampler = FleetSampler::new(&kinds, &shots, &sunk);
+
+        // The Cruiser and the Submarine can be swapped, and the Destroyer fits in 9 places.
+        let mut fleets = Vec::new();
+        let solutions = sampler.enumerate(10_000, |fleet| fleets.push(fleet.to_vec()));
+        assert_eq!(solutions, Some(2 * 9));
+        assert_eq!(fleets.len(), 2 * 9);
+        assert!(fleets.iter().all(|fleet| fleet.len() == 5));
+
+        assert_eq!(sampler.enumerate(1000, |_| {}), None);
+        assert_eq!(sampler.enumerate(0, |_| {}), None);
+
+        // The accumulators visit the same fleets, merged in the same order.
+        let visited = sampler.enumerate_with(
+            10_000,
+            Vec::new,
+            |fleets: &mut Vec<Vec<Ship>>, fleet| fleets.push(fleet.to_vec()),
+            |mut all, fleets| {
+                all.extend(fleets);
+                all
+            },
+        );
+        assert_eq!(visited, Some(fleets));
+        assert_eq!(
+            sampler.enumerate_with(1000, || 0, |_, _| {}, |a, b| a + b),
+            None
+        );
+    }
 }
@@ -2,43 +2,613 @@
 //! Every game requires 2 players: a human player and a computer one.
 //! The game proceeds in turns, where each player attacks the other until one of them loses all
 //! their ships.
+//!
+//! A game goes through the phases described by [GamePhase]: it is set up, then it is played
+//! until a player wins.
+//!
+//! A game can be assembled in one go with a [GameBuilder], and its events can be followed by
+//! subscribing a [GameObserver]. Simulations can let two strategies play the whole game with
+//! [Game::autoplay], and go through its turns as [TurnEvent]s.
+//!
+//! A game can have a time control: every side has a [Clock], and a side loses when its clock
+//! expires.
+//!
+//! A game can also have [PowerUps]: the human player can then use special weapons, by playing a
+//! [PlayerAction] instead of a single shot.
+//!
+//! When the rules have [Fog], the fog moves at the start of every turn, and the result of the
+//! shots into it is reported as unknown until it lifts.
+//!
+//! When the rules have a streak or a salvo, a side fires more shots in a turn: the computer fires
+//! all of them in a single call to [Game::play_action], while the human player plays an action
+//! for each of them in the same turn.
+//!
+//! When the rules allow it, a side can relocate its Submarine instead of firing: the human
+//! player with a [PlayerAction::Relocate], and the computer as soon as its Submarine is damaged.
+//!
+//! A [Snapshot] of a game can be taken and restored later, to take back the moves played since.
+//!
+//! A [Handicap] balances a game between players of different skill, giving a side an extra
+//! Destroyer or revealing one of its ships to the opponent.
 
-use crate::engine::fleet::Fleet;
-use crate::engine::grid::Cell;
-use crate::engine::player::Player;
-use crate::engine::strategy::{RandomStrategy, SmartStrategy};
-use rand::random_bool;
+use crate::engine::action::{PlayerAction, PowerUps};
+use crate::engine::clock::{Clock, Instant};
+use crate::engine::fleet::{Fleet, Ship, ShipId, ShipKind, ShipOrientation};
+use crate::engine::grid::{Cell, CellState};
+use crate::engine::map::Map;
+use crate::engine::minefield::{MineError, Minefield};
+use crate::engine::observer::GameObserver;
+use crate::engine::placement::Placement;
+use crate::engine::player::{Player, PlayerState};
+use crate::engine::process::ProcessStrategy;
+use crate::engine::rules::{ClassicRules, CombinedRules, Rules, Variant};
+#[cfg(feature = "scripting")]
+use crate::engine::script::{Script, ScriptStrategy};
+use crate::engine::strategy::{
+    Adaptation, AdaptiveStrategy, MonteCarloStrategy, RandomStrategy, SmartStrategy, Strategy,
+    StrategyView,
+};
+use crate::engine::weather::Fog;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::pin::pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+use strum::Display;
+use strum_macros::EnumIter;
+use thiserror::Error;
+use tracing::field::Empty;
+use tracing::{Instrument, Span, debug, info, info_span};
+
+// The number of the games created, which tells the games apart in the logs.
+static GAMES: AtomicU64 = AtomicU64::new(0);
+
+// The number of the changes of all the games, which the revision of a game is drawn from.
+static REVISIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Reasons why a turn cannot be played.
+#[derive(Debug, Error, PartialEq, Eq, Clone, Copy)]
+pub enum GameError {
+    /// The players have not been set yet.
+    #[error("Game is not ready: the players have not been set")]
+    NotReady,
+
+    /// The players have already been set and the game is being played.
+    #[error("Game has already started")]
+    AlreadyStarted,
+
+    /// One of the players has already lost.
+    #[error("Game is already over")]
+    AlreadyOver,
+
+    /// The game has been built without the human player.
+    #[error("Game needs a human player")]
+    MissingHuman,
+
+    /// The board size is not supported.
+    #[error("{0}x{0} boards are not supported")]
+    UnsupportedBoardSize(u8),
+
+    /// The fleet composition is not supported.
+    #[error("Only the standard fleet composition is supported")]
+    UnsupportedFleet,
+
+    /// A side has already won the series, so no more games can be started.
+    #[error("The series is already over")]
+    SeriesOver,
+
+    /// The rules don't allow the deployment of the human fleet.
+    #[error("The rules don't allow this fleet")]
+    FleetNotAllowed,
+
+    /// A ship or a mine of the human player is on an island of the map.
+    #[error("{0} is an island")]
+    OnIsland(Cell),
+
+    /// The action needs a power-up which the player doesn't have.
+    #[error("No power-up left for this action")]
+    PowerUpUnavailable,
+
+    /// The Submarine can't relocate anymore, because it has been sunk or because the rules
+    /// don't allow more relocations.
+    #[error("The Submarine can't relocate anymore")]
+    RelocationUnavailable,
+
+    /// The human player has used all the hints allowed by the rules.
+    #[error("No hint left")]
+    HintUnavailable,
+
+    /// The Submarine can't relocate to the position starting from the cell.
+    #[error("The Submarine can't relocate to {0}")]
+    InvalidRelocation(Cell),
+
+    /// The mines of the human player can't be placed.
+    #[error(transparent)]
+    Mines(#[from] MineError),
+}
+
+/// The two sides of a game.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Display, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Side {
+    Human,
+    Computer,
+}
+
+impl Side {
+    /// Returns the other side.
+    pub fn opponent(&self) -> Self {
+        match self {
+            Side::Human => Side::Computer,
+            Side::Computer => Side::Human,
+        }
+    }
+
+    fn of(player: &Player) -> Self {
+        if player.is_human() {
+            Side::Human
+        } else {
+            Side::Computer
+        }
+    }
+}
+
+/// The phases of a game.
+///
+/// A game starts in the [GamePhase::Setup] phase, it moves to [GamePhase::InProgress] when the
+/// players are set, and to [GamePhase::Finished] when one of them has lost all their ships. When
+/// both sides lose in the same turn, the game moves to [GamePhase::Drawn] instead.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum GamePhase {
+    /// The players have not been set yet.
+    #[default]
+    Setup,
+
+    /// The players take turns to attack each other.
+    InProgress,
+
+    /// The game is over, and the winner is known.
+    Finished { winner: Side },
+
+    /// The game is over without a winner, as both fleets have been sunk in the same turn.
+    Drawn,
+}
+
+impl GamePhase {
+    /// Moves from [GamePhase::Setup] to [GamePhase::InProgress].
+    pub fn start(self) -> Result<Self, GameError> {
+        match self {
+            GamePhase::Setup => Ok(GamePhase::InProgress),
+            GamePhase::InProgress => Err(GameError::AlreadyStarted),
+            GamePhase::Finished { .. } | GamePhase::Drawn => Err(GameError::AlreadyOver),
+        }
+    }
+
+    /// Moves from [GamePhase::InProgress] to [GamePhase::Finished] with the given winner.
+    pub fn finish(self, winner: Side) -> Result<Self, GameError> {
+        match self {
+            GamePhase::Setup => Err(GameError::NotReady),
+            GamePhase::InProgress => Ok(GamePhase::Finished { winner }),
+            GamePhase::Finished { .. } | GamePhase::Drawn => Err(GameError::AlreadyOver),
+        }
+    }
+
+    /// Moves from [GamePhase::InProgress] to [GamePhase::Drawn].
+    pub fn draw(self) -> Result<Self, GameError> {
+        match self {
+            GamePhase::Setup => Err(GameError::NotReady),
+            GamePhase::InProgress => Ok(GamePhase::Drawn),
+            GamePhase::Finished { .. } | GamePhase::Drawn => Err(GameError::AlreadyOver),
+        }
+    }
+}
+
+/// How hard the computer player is to beat, from the easiest level.
+#[derive(
+    Debug,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Clone,
+    Copy,
+    Default,
+    Display,
+    EnumIter,
+    Serialize,
+    Deserialize,
+)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    /// The computer shoots at random.
+    Easy,
+
+    /// The computer chases the ships it has hit.
+    #[default]
+    Normal,
+
+    /// The computer fires where the ships are most likely to be.
+    Hard,
+
+    /// The computer plays better or worse to keep the game close.
+    Adaptive,
+
+    /// Like the hard computer, but it remembers where the human player has placed their ships
+    /// in the past games.
+    Learning,
+}
+
+/// The kind of match to play.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GameMode {
+    /// A [Game] against the computer, which deploys its fleet and shoots back.
+    #[default]
+    Battle,
+
+    /// A [Puzzle](crate::engine::puzzle::Puzzle): sink a hidden fleet in as few shots as
+    /// possible.
+    Puzzle,
+
+    /// The [daily challenge](crate::engine::daily::DailyChallenge): a battle against the same
+    /// computer fleet as every other player today.
+    Daily,
+
+    /// A [campaign](crate::engine::campaign::Campaign): a sequence of harder and harder battles.
+    Campaign,
+}
+
+/// An advantage given to a side, to balance a game between players of different skill.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Handicap {
+    /// Both sides play with the same fleet, and nothing is revealed.
+    #[default]
+    None,
+
+    /// The human player has an extra Destroyer, placed at random.
+    #[strum(to_string = "Extra Destroyer for you")]
+    HumanExtraDestroyer,
+
+    /// The computer has an extra Destroyer, placed at random.
+    #[strum(to_string = "Extra Destroyer for the computer")]
+    ComputerExtraDestroyer,
+
+    /// A random ship of the human player is revealed to the computer.
+    #[strum(to_string = "Your ship revealed")]
+    HumanShipRevealed,
+
+    /// A random ship of the computer is revealed to the human player.
+    #[strum(to_string = "Enemy ship revealed")]
+    ComputerShipRevealed,
+}
+
+impl Handicap {
+    /// Returns the side which has an extra Destroyer, if any.
+    pub fn extra_destroyer(&self) -> Option<Side> {
+        match self {
+            Handicap::HumanExtraDestroyer => Some(Side::Human),
+            Handicap::ComputerExtraDestroyer => Some(Side::Computer),
+            _ => None,
+        }
+    }
+
+    /// Returns the side whose ship is revealed to the opponent, if any.
+    pub fn revealed_ship(&self) -> Option<Side> {
+        match self {
+            Handicap::HumanShipRevealed => Some(Side::Human),
+            Handicap::ComputerShipRevealed => Some(Side::Computer),
+            _ => None,
+        }
+    }
+}
 
 /// The Naval Battle game
+#[derive(Debug)]
 pub struct Game {
     players: Vec<Player>,
+    phase: GamePhase,
     last_computer_move: Option<Cell>,
+    difficulty: Difficulty,
+    solver_budget: usize,
+    adaptation: Adaptation,
+    habits: Vec<u32>,
+    #[cfg(feature = "scripting")]
+    script: Option<Script>,
+    opponent_command: Option<Vec<String>>,
+    rules: Box<dyn Rules>,
+    rng: StdRng,
+    turns: u32,
+    revision: u64,
+    last_turn: Option<TurnEvent>,
+    observers: Vec<Box<dyn GameObserver>>,
+    time_control: Option<Duration>,
+    clocks: Option<[Clock; 2]>,
+    power_ups: PowerUps,
+    remaining_power_ups: [PowerUps; 2],
+    remaining_relocations: [u8; 2],
+    hint_limit: Option<u8>,
+    remaining_hints: u8,
+    hint_rng: StdRng,
+    streak: bool,
+    map: Option<Map>,
+    fog: Fog,
+    handicap: Handicap,
+    revealed: [Option<Ship>; 2],
+    span: Span,
 }
 
 impl Game {
-    const HUMAN_MOVE_FIRST_PROBABILITY: f64 = 0.5;
     const COMPUTER_NAME: &'static str = "Computer";
+    const MAX_RELOCATION_ATTEMPTS: usize = 100;
+    const PLACEMENTS: [Placement; 5] = [
+        Placement::Random,
+        Placement::EdgeHugger,
+        Placement::Spread,
+        Placement::Clustered,
+        Placement::AntiParity,
+    ];
 
-    /// Creates a new game, not ready to play.
+    /// Creates a new game, not ready to play, with the classic rules.
     ///
     /// This new game must have been set upped with 2 players
     pub fn new() -> Self {
+        Self::with(Box::new(ClassicRules), StdRng::from_os_rng())
+    }
+
+    fn with(rules: Box<dyn Rules>, rng: StdRng) -> Self {
         Self {
             players: Vec::new(),
+            phase: GamePhase::Setup,
             last_computer_move: None,
+            difficulty: Difficulty::default(),
+            solver_budget: MonteCarloStrategy::NODE_BUDGET,
+            adaptation: Adaptation::default(),
+            habits: Vec::new(),
+            #[cfg(feature = "scripting")]
+            script: None,
+            opponent_command: None,
+            rules,
+            rng,
+            turns: 0,
+            revision: 0,
+            last_turn: None,
+            observers: Vec::new(),
+            time_control: None,
+            clocks: None,
+            power_ups: PowerUps::default(),
+            remaining_power_ups: [PowerUps::default(); 2],
+            remaining_relocations: [0; 2],
+            hint_limit: None,
+            remaining_hints: 0,
+            hint_rng: StdRng::from_os_rng(),
+            streak: false,
+            map: None,
+            fog: Fog::default(),
+            handicap: Handicap::default(),
+            revealed: [None, None],
+            span: info_span!("game", id = GAMES.fetch_add(1, Ordering::Relaxed) + 1),
         }
     }
 
+    /// Subscribes an observer to the events of this game.
+    ///
+    /// Observers are kept when a new human player is set.
+    pub fn subscribe(&mut self, observer: Box<dyn GameObserver>) {
+        self.observers.push(observer);
+    }
+
+    /// Returns the rules of this game.
+    pub fn rules(&self) -> &dyn Rules {
+        self.rules.as_ref()
+    }
+
+    /// Set the difficulty of the computer player.
+    ///
+    /// The difficulty is applied to the computer player created by the next call to
+    /// [Game::set_human_player].
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) {
+        self.difficulty = difficulty;
+    }
+
+    /// Set how many placements of the ships the computer player can explore to solve the
+    /// endgame exactly, at the hard difficulty.
+    ///
+    /// Like the difficulty, the budget is applied to the computer player created by the next
+    /// call to [Game::set_human_player].
+    pub fn set_solver_budget(&mut self, nodes: usize) {
+        self.solver_budget = nodes;
+    }
+
+    /// Set how strongly the computer player keeps the game close, at the adaptive difficulty.
+    ///
+    /// The adaptation is applied to the computer player created by the next call to
+    /// [Game::set_human_player].
+    pub fn set_adaptation(&mut self, adaptation: Adaptation) {
+        self.adaptation = adaptation;
+    }
+
+    /// Set how many times the human player has placed a ship on every cell in the past games,
+    /// from `A1` to `J10` row by row, for the learning computer player.
+    ///
+    /// The habits are applied to the computer player created by the next call to
+    /// [Game::set_human_player].
+    pub fn set_habits(&mut self, habits: &[u32]) {
+        self.habits = habits.to_vec();
+    }
+
+    /// Set the script playing the computer player, instead of the strategy of its difficulty,
+    /// or `None` to play against the built-in computer.
+    ///
+    /// The script is applied to the computer player created by the next call to
+    /// [Game::set_human_player].
+    #[cfg(feature = "scripting")]
+    pub fn set_script(&mut self, script: Option<Script>) {
+        self.script = script;
+    }
+
+    /// Set the command line of the external program playing the computer player, instead of
+    /// the strategy of its difficulty, or `None` to play against the built-in computer.
+    ///
+    /// The program is spawned for the computer player created by the next call to
+    /// [Game::set_human_player]. When it can't be spawned, the computer plays with the strategy
+    /// of its difficulty.
+    pub fn set_opponent_command(&mut self, command: Option<Vec<String>>) {
+        self.opponent_command = command;
+    }
+
+    /// Set the time of each side, or `None` to play without time control.
+    ///
+    /// The time control is applied from the next call to [Game::set_human_player].
+    pub fn set_time_control(&mut self, time: Option<Duration>) {
+        self.time_control = time;
+    }
+
+    /// Set the power-ups of each side, or [PowerUps::default] to play without them.
+    ///
+    /// The power-ups are granted from the next call to [Game::set_human_player].
+    pub fn set_power_ups(&mut self, power_ups: PowerUps) {
+        self.power_ups = power_ups;
+    }
+
+    /// Set how many hints the human player can ask for in a game, or `None` to give the hints
+    /// allowed by the rules.
+    ///
+    /// The hints are granted from the next call to [Game::set_human_player].
+    pub fn set_hint_limit(&mut self, hints: Option<u8>) {
+        self.hint_limit = hints;
+    }
+
+    /// Set the map of the boards, or `None` to play on the open sea.
+    ///
+    /// The map is applied from the next call to [Game::set_human_player], whose fleet must not
+    /// occupy its islands.
+    pub fn set_map(&mut self, map: Option<Map>) {
+        self.map = map;
+    }
+
+    /// Returns the map of the boards, if any.
+    pub fn map(&self) -> Option<&Map> {
+        self.map.as_ref()
+    }
+
+    /// Set the handicap which balances the game, or [Handicap::None] to play even.
+    ///
+    /// The handicap is applied from the next call to [Game::set_human_player].
+    pub fn set_handicap(&mut self, handicap: Handicap) {
+        self.handicap = handicap;
+    }
+
+    /// Returns the handicap of the game.
+    pub fn handicap(&self) -> Handicap {
+        self.handicap
+    }
+
+    /// Returns the ship of the given side revealed to the opponent by the handicap, at the
+    /// position it had when the game started.
+    pub fn revealed_ship(&self, side: Side) -> Option<&Ship> {
+        self.revealed[side as usize].as_ref()
+    }
+
     /// Set human player.
     ///
-    /// When a human player is set, all previous players are cleared, and the game becomes ready
-    /// (computer player is added automatically). The players' order is randomly chosen.
+    /// When a human player is set, all previous players are cleared, and the game starts again
+    /// in the [GamePhase::InProgress] phase (computer player is added automatically). The
+    /// players' order is chosen by the rules.
+    ///
+    /// The computer deploys its fleet with one of the [Placement]s, chosen at random.
+    ///
+    /// When the rules have mines, the computer places them at random, and so are placed the
+    /// mines the human player has not placed yet.
     ///
     /// The game object takes the ownership of the given player.
-    pub fn set_human_player(&mut self, player: Player) {
-        let human_player_first = random_bool(Self::HUMAN_MOVE_FIRST_PROBABILITY);
-        let mut computer = Player::new(Self::COMPUTER_NAME, Fleet::build(|k| k.random()));
-        computer.set_strategy(SmartStrategy::new());
+    pub fn set_human_player(&mut self, mut player: Player) {
+        let human_player_first = self.rules.first_side(&mut self.rng) == Side::Human;
+        let placement = *Self::PLACEMENTS.choose(&mut self.rng).unwrap();
+        let mut placement = placement.strategy(self.rng.random());
+        let islands = self
+            .map
+            .as_ref()
+            .map(|map| map.islands())
+            .unwrap_or_default();
+        let fleet = loop {
+            let fleet = placement.deploy(islands);
+            if self.rules.allows_fleet(&fleet) {
+                break fleet;
+            }
+        };
+        debug!("computer fleet deployed\n{fleet}");
+        let mut computer = Player::new(Self::COMPUTER_NAME, fleet);
+        let seed = self.rng.random();
+        match self.difficulty {
+            Difficulty::Easy => computer.set_strategy(RandomStrategy::with_seed(seed)),
+            Difficulty::Normal => computer.set_strategy(SmartStrategy::with_seed(seed)),
+            Difficulty::Hard => computer.set_strategy(
+                MonteCarloStrategy::with_seed(seed).with_node_budget(self.solver_budget),
+            ),
+            Difficulty::Adaptive => {
+                computer.set_strategy(AdaptiveStrategy::with_seed(self.adaptation, seed))
+            }
+            Difficulty::Learning => computer.set_strategy(
+                MonteCarloStrategy::with_seed(seed)
+                    .with_node_budget(self.solver_budget)
+                    .with_habits(&self.habits),
+            ),
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &self.script {
+            computer.set_strategy(ScriptStrategy::with_seed(script.clone(), seed));
+        }
+        if let Some(command) = &self.opponent_command
+            && let Ok(strategy) = ProcessStrategy::spawn_with_seed(command, seed)
+        {
+            computer.set_strategy(strategy);
+        }
+        match self.handicap.extra_destroyer() {
+            Some(Side::Human) => self.reinforce(&mut player),
+            Some(Side::Computer) => self.reinforce(&mut computer),
+            None => {}
+        }
+        self.revealed = [None, None];
+        if let Some(side) = self.handicap.revealed_ship() {
+            let owner = match side {
+                Side::Human => &player,
+                Side::Computer => &computer,
+            };
+            let ship = owner
+                .fleet()
+                .as_ref()
+                .choose(&mut self.rng)
+                .unwrap()
+                .clone();
+            if side == Side::Human {
+                computer.reveal(&ship);
+            }
+            self.revealed[side as usize] = Some(ship);
+        }
+        let islands = self.map.as_ref().map(Map::islands).unwrap_or_default();
+        let mines = self.rules.mines();
+        if mines > 0 {
+            let computer_mines =
+                Minefield::random_with(mines, computer.fleet(), islands, &mut self.rng);
+            computer.set_mines(computer_mines);
+            let mut human_mines = player.mines().clone();
+            human_mines.fill_with(mines, player.fleet(), islands, &mut self.rng);
+            player.set_mines(human_mines);
+        }
+        if let Some(map) = &self.map {
+            computer.set_map(map);
+            player.set_map(map);
+        }
+        computer.set_ammo(self.rules.ammo());
+        player.set_ammo(self.rules.ammo());
 
         self.players.clear();
         if human_player_first {
@@ -48,18 +618,63 @@ impl Game {
             self.players.push(computer);
             self.players.push(player);
         }
+
+        self.last_computer_move = None;
+        self.turns = 0;
+        self.revise();
+        self.last_turn = None;
+        self.streak = false;
+        self.clocks = self.time_control.map(|time| [Clock::new(time); 2]);
+        self.remaining_power_ups = [self.power_ups; 2];
+        self.remaining_relocations = [self.rules.relocations(); 2];
+        self.remaining_hints = self.hint_limit.unwrap_or_else(|| self.rules.hints());
+        self.fog = Fog::default();
+        self.start_human_clock();
+        self.phase = GamePhase::Setup.start().unwrap();
+    }
+
+    // Adds an extra Destroyer at random to the fleet of the player, away from the islands and
+    // from the mines already placed.
+    fn reinforce(&mut self, player: &mut Player) {
+        loop {
+            let ship = ShipKind::Destroyer.random_with(&mut self.rng);
+            let mined = ship
+                .occupied_cells()
+                .iter()
+                .any(|cell| player.mines().is_mined(cell));
+            if !mined
+                && self.map.as_ref().is_none_or(|map| map.allows(&ship))
+                && player.reinforce(ship).is_ok()
+            {
+                break;
+            }
+        }
+    }
+
+    /// Returns the current phase of the game.
+    pub fn phase(&self) -> GamePhase {
+        self.phase
     }
 
     /// Return whether the game is over.
     ///
-    /// A game is over when one of the two players has lost.
+    /// A game is over when one of the two players has lost, or when both have lost together.
     pub fn is_over(&self) -> bool {
-        self.players.len() == 2 && self.players.iter().any(|p| p.has_lost())
+        matches!(self.phase, GamePhase::Finished { .. } | GamePhase::Drawn)
+    }
+
+    /// Returns the side which has won the game, or `None` while the game is in progress and
+    /// when it ended in a draw.
+    pub fn winner(&self) -> Option<Side> {
+        match self.phase {
+            GamePhase::Finished { winner } => Some(winner),
+            _ => None,
+        }
     }
 
     /// The game is ready to play when it has 2 players and none has lost yet.
     pub fn is_ready(&self) -> bool {
-        self.players.len() == 2 && !self.players.iter().any(|p| p.has_lost())
+        self.phase == GamePhase::InProgress
     }
 
     /// Return the human player.
@@ -72,6 +687,226 @@ impl Game {
         self.players.iter().find(|p| !p.is_human())
     }
 
+    /// Returns how many turns have been played.
+    pub fn turns_played(&self) -> u32 {
+        self.turns
+    }
+
+    /// Returns a number which changes whenever the state of the game may have changed: a new
+    /// human player, an action, a forfeit or a restored [Snapshot]. Two games never share a
+    /// revision.
+    ///
+    /// Unlike [Game::state_hash], it is cheap to read, so that a front-end can check it at
+    /// every frame and redraw the boards only when it changes.
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    // Gives the game a new revision, as its state is about to change.
+    fn revise(&mut self) {
+        self.revision = REVISIONS.fetch_add(1, Ordering::Relaxed) + 1;
+    }
+
+    /// Returns a stable hash of the state of the game: the fleets of both players as they were
+    /// placed, the shots they have fired and the turn.
+    ///
+    /// Two games in the same state have the same hash, whatever the platform and the run, so
+    /// that the copies of a game, like those of a server and of its client, can be compared.
+    pub fn state_hash(&self) -> u64 {
+        let player = |side| self.players.iter().find(|player| Side::of(player) == side);
+        state_hash(self.turns, [player(Side::Human), player(Side::Computer)])
+    }
+
+    /// Takes a [Snapshot] of the state of the game, which [Game::restore] brings back.
+    ///
+    /// A snapshot is a plain copy of the boards, the counters, the random generator and the
+    /// strategies, so that an undo, a replay going back and forth, or a lookahead over
+    /// hypothetical moves can try a move and then take it back.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            players: self.players.iter().map(Player::state).collect(),
+            phase: self.phase,
+            last_computer_move: self.last_computer_move,
+            rng: self.rng.clone(),
+            turns: self.turns,
+            last_turn: self.last_turn.clone(),
+            clocks: self.clocks,
+            remaining_power_ups: self.remaining_power_ups,
+            remaining_relocations: self.remaining_relocations,
+            remaining_hints: self.remaining_hints,
+            streak: self.streak,
+            fog: self.fog.clone(),
+            revealed: self.revealed.clone(),
+        }
+    }
+
+    /// Brings the game back to the state of a [Snapshot] taken from it.
+    ///
+    /// The strategies forget what they have learnt since the snapshot, unless they can't be
+    /// copied, like another program (see [Strategy::snapshot]). The observers aren't told about
+    /// the restore.
+    pub fn restore(&mut self, snapshot: &Snapshot) {
+        self.revise();
+        for (player, state) in self.players.iter_mut().zip(&snapshot.players) {
+            player.restore(state);
+        }
+        self.phase = snapshot.phase;
+        self.last_computer_move = snapshot.last_computer_move;
+        self.rng = snapshot.rng.clone();
+        self.turns = snapshot.turns;
+        self.last_turn = snapshot.last_turn.clone();
+        self.clocks = snapshot.clocks;
+        self.remaining_power_ups = snapshot.remaining_power_ups;
+        self.remaining_relocations = snapshot.remaining_relocations;
+        self.remaining_hints = snapshot.remaining_hints;
+        self.streak = snapshot.streak;
+        self.fog = snapshot.fog.clone();
+        self.revealed = snapshot.revealed.clone();
+    }
+
+    /// Returns the clock of the given side, if the game has a time control.
+    ///
+    /// The clock of the human player runs from the end of a turn until the human moves again.
+    pub fn clock(&self, side: Side) -> Option<&Clock> {
+        self.clocks.as_ref().map(|clocks| &clocks[side as usize])
+    }
+
+    /// Returns the power-ups the given side can still use in this game.
+    pub fn power_ups(&self, side: Side) -> PowerUps {
+        self.remaining_power_ups[side as usize]
+    }
+
+    /// Returns how many times the given side can still relocate its Submarine in this game: none
+    /// once its Submarine has been sunk.
+    pub fn relocations(&self, side: Side) -> u8 {
+        let player = match side {
+            Side::Human => self.human(),
+            Side::Computer => self.computer(),
+        };
+        let afloat = player.is_some_and(|player| {
+            player
+                .fleet()
+                .as_ref()
+                .iter()
+                .any(|ship| *ship.kind() == ShipKind::Submarine && !ship.is_sunk())
+        });
+
+        if afloat {
+            self.remaining_relocations[side as usize]
+        } else {
+            0
+        }
+    }
+
+    /// Returns how many hints the human player can still ask for in this game.
+    pub fn hints(&self) -> u8 {
+        self.remaining_hints
+    }
+
+    /// Uses one of the hints of the human player: returns the cell where a ship is most likely
+    /// to be, the hottest cell of the heatmap of a [MonteCarloStrategy] over the shots of the
+    /// human player.
+    ///
+    /// The hint only knows what the human player knows: the shots still hidden by the fog are
+    /// neither suggested again nor told apart as hits or misses. Asking for a hint doesn't
+    /// change the moves of the computer, even in a seeded game.
+    ///
+    /// If the game is over or not ready, or if the human player has no hint left, an error is
+    /// returned.
+    pub fn hint(&mut self) -> Result<Cell, GameError> {
+        match self.phase {
+            GamePhase::Setup => return Err(GameError::NotReady),
+            GamePhase::Finished { .. } | GamePhase::Drawn => return Err(GameError::AlreadyOver),
+            GamePhase::InProgress => {}
+        }
+        if self.remaining_hints == 0 {
+            return Err(GameError::HintUnavailable);
+        }
+
+        let seed = self.hint_rng.random();
+        let (Some(human), Some(computer)) = (self.human(), self.computer()) else {
+            return Err(GameError::NotReady);
+        };
+
+        // The fogged shots look unexplored, and the ships they sank look afloat.
+        let hidden = self.fog.hidden(Side::Human);
+        let mut shots = human.shots_grid().clone();
+        for cell in &hidden {
+            shots.mark(cell, CellState::Empty);
+        }
+        let sunk: Vec<ShipKind> = human
+            .sunk_ships()
+            .iter()
+            .filter_map(|id| computer.fleet().ship(*id))
+            .filter(|ship| {
+                !ship
+                    .occupied_cells()
+                    .iter()
+                    .any(|cell| hidden.contains(cell))
+            })
+            .map(|ship| ship.kind().clone())
+            .collect();
+        let view = StrategyView::new(&shots, &sunk, computer.shots_grid(), self.rules.as_ref());
+
+        let mut strategy = MonteCarloStrategy::with_seed(seed).with_node_budget(self.solver_budget);
+        let heatmap: Vec<(Cell, f32)> = strategy
+            .heatmap(&view)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(cell, _)| !hidden.contains(cell))
+            .collect();
+        let hottest = heatmap.iter().map(|(_, heat)| *heat).fold(0.0, f32::max);
+        let best: Vec<Cell> = heatmap
+            .into_iter()
+            .filter(|(_, heat)| *heat == hottest)
+            .map(|(cell, _)| cell)
+            .collect();
+        let cell = *best
+            .choose(&mut self.hint_rng)
+            .ok_or(GameError::HintUnavailable)?;
+        self.remaining_hints -= 1;
+
+        Ok(cell)
+    }
+
+    /// Returns how much the computer player likes every cell of the human grid for its next
+    /// move, from 0 to 1, to show why it shoots where it does. The moves of the computer are the
+    /// same whether the heatmap is asked for or not.
+    ///
+    /// It returns `None` when the game is not ready, or the strategy of the computer doesn't
+    /// weigh the cells.
+    pub fn computer_heatmap(&mut self) -> Option<HashMap<Cell, f32>> {
+        if self.players.len() < 2 {
+            return None;
+        }
+
+        let (first, second) = self.players.split_at_mut(1);
+        let (computer, human) = match first[0].is_human() {
+            true => (&mut second[0], &first[0]),
+            false => (&mut first[0], &second[0]),
+        };
+        computer.heatmap(human, self.rules.as_ref())
+    }
+
+    /// Returns `true` when the rules let the human player shoot again in the same turn, before
+    /// the computer plays: after a hit with the streak, or until they have fired their salvo.
+    pub fn human_shoots_again(&self) -> bool {
+        self.streak
+    }
+
+    /// Returns the fog over the boards, and the shots whose result is still hidden by it.
+    pub fn fog(&self) -> &Fog {
+        &self.fog
+    }
+
+    /// Returns what happened in the last turn played.
+    ///
+    /// While the human player [shoots again](Game::human_shoots_again), it is the turn being
+    /// played, with the shots fired so far.
+    pub fn last_turn(&self) -> Option<&TurnEvent> {
+        self.last_turn.as_ref()
+    }
+
     /// return the last computer move made by the computer player.
     pub fn last_computer_move(&self) -> Option<&Cell> {
         self.last_computer_move.as_ref()
@@ -81,110 +916,1088 @@ impl Game {
     ///
     /// The computer player uses its internal policy to evaluate the next move.
     /// Return whether the game is over after this turn: in this case will be returned `true` when
-    /// human wins, otherwise `false`.
+    /// human wins, otherwise `false`. A draw returns `false` too: [Game::phase] tells it apart.
     ///
     /// If the game is over or not ready, an error is returned.
-    pub fn play_turn(&mut self, human_move: &Cell) -> Result<Option<bool>, String> {
-        if !self.is_ready() {
-            return Err("Game is not ready or already over".to_string());
+    pub fn play_turn(&mut self, human_move: &Cell) -> Result<Option<bool>, GameError> {
+        self.play_action(PlayerAction::Shot(*human_move))
+    }
+
+    /// Play a turn with the given action for the human player.
+    ///
+    /// Like [Game::play_turn], but the human player can use a power-up: a bombardment shoots every
+    /// cell of its area which the human player has not shot yet. When the rules allow it, the
+    /// human player can also relocate their Submarine instead of firing.
+    ///
+    /// If the game is over or not ready, if the power-up needed by the action is not available,
+    /// or if the Submarine can't relocate to the given position, an error is returned.
+    pub fn play_action(&mut self, action: PlayerAction) -> Result<Option<bool>, GameError> {
+        // Without preparing the moves of the computer, the turn never waits: it is played at
+        // the first poll.
+        let span = self.turn_span();
+        let turn = pin!(self.act(action, false).instrument(span));
+        match turn.poll(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(result) => result,
+            Poll::Pending => unreachable!("a turn waits only for the moves prepared"),
+        }
+    }
+
+    /// Play a turn with the given move for the human player, like [Game::play_turn], awaiting
+    /// the move of the computer when its strategy prepares it, like the answer of another
+    /// program, instead of blocking the thread.
+    ///
+    /// As the game can't be sent between threads, the future must be run by a single-threaded
+    /// executor.
+    #[cfg(feature = "async")]
+    pub fn play_turn_async<'game>(
+        &'game mut self,
+        human_move: &Cell,
+    ) -> impl Future<Output = Result<Option<bool>, GameError>> + use<'game> {
+        let span = self.turn_span();
+        self.act(PlayerAction::Shot(*human_move), true)
+            .instrument(span)
+    }
+
+    /// Play a turn with the given action for the human player, like [Game::play_action],
+    /// awaiting the moves of the computer like [Game::play_turn_async].
+    #[cfg(feature = "async")]
+    pub async fn play_action_async(
+        &mut self,
+        action: PlayerAction,
+    ) -> Result<Option<bool>, GameError> {
+        let span = self.turn_span();
+        self.act(action, true).instrument(span).await
+    }
+
+    // Returns the span of a turn of this game, whose number is recorded once the turn starts.
+    fn turn_span(&self) -> Span {
+        info_span!(parent: &self.span, "turn", number = Empty)
+    }
+
+    // Plays a turn with the action of the human player, preparing the moves of the computer if
+    // asked to.
+    async fn act(
+        &mut self,
+        action: PlayerAction,
+        prepare: bool,
+    ) -> Result<Option<bool>, GameError> {
+        match self.phase {
+            GamePhase::Setup => return Err(GameError::NotReady),
+            GamePhase::Finished { .. } | GamePhase::Drawn => return Err(GameError::AlreadyOver),
+            GamePhase::InProgress => {}
+        }
+
+        self.revise();
+        if let PlayerAction::Relocate(cell, orientation) = action {
+            self.relocate_human_submarine(cell, orientation)?;
+        }
+        let power_ups = &mut self.remaining_power_ups[Side::Human as usize];
+        if !power_ups.allows(&action) {
+            return Err(GameError::PowerUpUnavailable);
         }
+        power_ups.consume(&action);
 
         self.last_computer_move = None;
 
-        let (first, second) = self.players.split_at_mut(1);
-        let (first, second) = (&mut first[0], &mut second[0]);
+        // A streak of the human player goes on in the same turn, which the opponent may have
+        // already played.
+        let streak = self.streak;
+        self.streak = false;
+        let (mut turn, first_index) = match self.last_turn.take().filter(|_| streak) {
+            Some(turn) => (
+                turn,
+                self.players.iter().position(Player::is_human).unwrap(),
+            ),
+            None => {
+                self.turns += 1;
+                self.fog.roll_with(self.rules.fog_width(), &mut self.rng);
+                let turn = TurnEvent {
+                    turn: self.turns,
+                    shots: Vec::with_capacity(2),
+                    winner: None,
+                    draw: false,
+                    resigned: None,
+                    timed_out: None,
+                    scan: None,
+                    relocated: Vec::new(),
+                };
+                (turn, 0)
+            }
+        };
+        Span::current().record("number", turn.turn);
+        debug!(?action, "human action");
+        let mut committed = Vec::with_capacity(2);
+        for index in first_index..2 {
+            let (player, opposite) = pair_mut(&mut self.players, index);
 
-        let (winner, computer_move) = do_move(first, second, human_move)?;
-        if let Some(winner) = winner {
-            return Ok(Some(winner.is_human()));
+            // The clock of the shooter runs while it chooses its move.
+            let shooter = Side::of(player);
+            if let Some(clock) = self.clocks.as_mut().map(|c| &mut c[shooter as usize]) {
+                let now = Instant::now();
+                clock.start(now);
+                if clock.is_expired(now) {
+                    turn.timed_out = Some(shooter);
+                    turn.winner = Some(shooter.opponent());
+                    self.observers
+                        .iter_mut()
+                        .for_each(|observer| observer.on_time_out(shooter));
+                    break;
+                }
+            }
+
+            // A side which relocates its Submarine fires no shot.
+            let relocated = match shooter {
+                Side::Human => matches!(action, PlayerAction::Relocate(..)),
+                Side::Computer => {
+                    self.remaining_relocations[shooter as usize] > 0
+                        && evade(&mut self.rng, player, opposite)
+                }
+            };
+            if relocated {
+                if shooter == Side::Computer {
+                    self.remaining_relocations[shooter as usize] -= 1;
+                }
+                turn.relocated.push(shooter);
+                self.observers
+                    .iter_mut()
+                    .for_each(|observer| observer.on_relocate(shooter));
+            }
+
+            // The computer chooses its own cell, so it fires a single shot.
+            let cells = match (shooter, action) {
+                _ if relocated => Vec::new(),
+                (Side::Human, PlayerAction::Bombard(_)) => action
+                    .cells()
+                    .into_iter()
+                    .filter(|cell| *player.shots_grid().at(cell) == CellState::Empty)
+                    .collect(),
+                (Side::Human, PlayerAction::Scan(_)) => {
+                    turn.scan = Some(do_scan(&mut self.observers, opposite, action.cells()));
+                    Vec::new()
+                }
+                (Side::Human, _) => vec![action.target()],
+                (Side::Computer, _) => {
+                    let next = computer_move(player, opposite, self.rules.as_ref(), prepare).await;
+                    vec![next.unwrap_or(action.target())]
+                }
+            };
+
+            if let Some(clock) = self.clocks.as_mut().map(|c| &mut c[shooter as usize]) {
+                clock.stop(Instant::now());
+            }
+            if self.rules.simultaneous() {
+                committed.push((index, cells));
+                continue;
+            }
+
+            self.fire(index, cells, &mut turn, prepare).await;
+            if turn.winner.is_some() || self.streak {
+                break;
+            }
         }
 
-        if let Some(computer_move) = computer_move {
-            self.last_computer_move = Some(computer_move);
+        // The shots fired together are resolved together, so both fleets can be sunk in the
+        // same turn.
+        for (index, cells) in committed {
+            self.fire(index, cells, &mut turn, prepare).await;
+        }
+        if self.rules.simultaneous() && turn.winner.is_none() {
+            let lost = |side| {
+                self.players
+                    .iter()
+                    .find(|player| Side::of(player) == side)
+                    .is_some_and(|player| self.rules.has_lost(player))
+            };
+            match (lost(Side::Human), lost(Side::Computer)) {
+                (true, true) => turn.draw = true,
+                (true, false) => turn.winner = Some(Side::Computer),
+                (false, true) => turn.winner = Some(Side::Human),
+                (false, false) => {}
+            }
         }
 
-        let (winner, computer_move) = do_move(second, first, human_move)?;
-        if let Some(winner) = winner {
-            return Ok(Some(winner.is_human()));
+        if turn.winner.is_some() || turn.draw {
+            self.streak = false;
+        }
+        if !self.streak {
+            self.observers
+                .iter_mut()
+                .for_each(|observer| observer.on_turn_end(self.turns));
         }
 
-        if let Some(computer_move) = computer_move {
-            self.last_computer_move = Some(computer_move);
+        let (winner, draw) = (turn.winner, turn.draw);
+        self.last_turn = Some(turn);
+        if winner.is_some() || draw {
+            self.finish(winner)?;
+            return Ok(Some(winner == Some(Side::Human)));
         }
 
+        self.start_human_clock();
         Ok(None)
     }
-}
 
-fn do_move<'player>(
-    player: &'player mut Player,
-    opposite: &'player mut Player,
-    human_move: &Cell,
-) -> Result<(Option<&'player Player>, Option<Cell>), String> {
-    let mut last_computer_move = None;
-    let player_move = if let Some(move_) = player.next_move() {
-        last_computer_move = Some(move_);
-        move_
-    } else {
-        *human_move
-    };
+    // Fires the committed shots of the player at the given position in the players' order.
+    //
+    // In the sequential turns, the side which sinks a fleet wins at once. In the simultaneous
+    // fire, the shots stop at the sunk fleet, but the outcome is decided once both sides have
+    // fired.
+    async fn fire(
+        &mut self,
+        index: usize,
+        mut cells: Vec<Cell>,
+        turn: &mut TurnEvent,
+        prepare: bool,
+    ) {
+        let (player, opposite) = pair_mut(&mut self.players, index);
+        let shooter = Side::of(player);
+        let mut next = 0;
+        while let Some(cell) = cells.get(next).copied() {
+            next += 1;
+            let mut shot = do_move(&mut self.observers, &mut self.rng, player, opposite, &cell);
+            shot.fogged = self.fog.hide(shooter, &shot.cell);
+            info!(
+                shooter = %shot.shooter,
+                cell = %shot.cell,
+                hit = ?shot.hit,
+                sunk = shot.sunk,
+                mine = shot.mine,
+                fogged = shot.fogged,
+                "shot"
+            );
+            if shot.shooter == Side::Computer {
+                self.last_computer_move = Some(shot.cell);
+            }
 
-    player.attack(opposite, &player_move);
+            // When the rules allow it, the computer keeps shooting, while the human player
+            // shoots again after the end of their action.
+            let fired = 1 + turn.shots.iter().filter(|s| s.shooter == shooter).count();
+            let again = self.rules.shoots_again(&shot) || fired < self.rules.shots(player);
+            match shooter {
+                Side::Human => self.streak |= again,
+                Side::Computer if again => {
+                    let next = computer_move(player, opposite, self.rules.as_ref(), prepare).await;
+                    cells.push(next.unwrap_or(cell));
+                }
+                Side::Computer => {}
+            }
+            turn.shots.push(shot);
 
-    if opposite.has_lost() {
-        Ok((Some(player), last_computer_move))
-    } else {
-        Ok((None, last_computer_move))
+            // The shooter may sink their own fleet on a mine, or run out of ammo.
+            let winner = if self.rules.has_lost(opposite) {
+                Some(shooter)
+            } else if self.rules.has_lost(player) {
+                Some(shooter.opponent())
+            } else {
+                None
+            };
+            if winner.is_some() {
+                if !self.rules.simultaneous() {
+                    turn.winner = winner;
+                }
+                break;
+            }
+        }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::engine::fleet::{tests::fixed_fleet, Fleet};
-    use rstest::{fixture, rstest};
+    /// Ends the game if the clock of a side has expired, and returns that side.
+    ///
+    /// The game checks the clocks when a turn is played, but the human clock can expire while
+    /// the human is choosing their move: a front-end calls this method to end the game on time.
+    pub fn check_time(&mut self) -> Option<Side> {
+        self.clocks.as_ref()?;
+        let now = Instant::now();
+        let side = [Side::Human, Side::Computer]
+            .into_iter()
+            .find(|side| self.clock(*side).is_some_and(|clock| clock.is_expired(now)))?;
 
-    #[fixture]
-    fn human_player(fixed_fleet: Fleet) -> Player {
-        Player::new("Human", fixed_fleet)
+        self.forfeit(side, false).ok()?;
+        Some(side)
     }
 
-    #[fixture]
-    fn computer_player(fixed_fleet: Fleet) -> Player {
-        let mut player = Player::new(Game::COMPUTER_NAME, fixed_fleet);
-        player.set_strategy(RandomStrategy);
+    /// The given side resigns, and the game ends with the opponent as winner.
+    ///
+    /// Resigning takes a whole turn, without shots. If the game is over or not ready, an error
+    /// is returned.
+    pub fn resign(&mut self, side: Side) -> Result<(), GameError> {
+        self.forfeit(side, true)
+    }
 
-        player
+    // Ends the game in a turn without shots, because the given side resigned or ran out of time.
+    fn forfeit(&mut self, side: Side, resigned: bool) -> Result<(), GameError> {
+        let winner = side.opponent();
+        self.phase.finish(winner)?; // Fails if the game is not in progress
+
+        self.revise();
+        self.streak = false;
+        self.turns += 1;
+        self.last_computer_move = None;
+        self.last_turn = Some(TurnEvent {
+            turn: self.turns,
+            shots: Vec::new(),
+            winner: Some(winner),
+            draw: false,
+            resigned: Some(side).filter(|_| resigned),
+            timed_out: Some(side).filter(|_| !resigned),
+            scan: None,
+            relocated: Vec::new(),
+        });
+
+        for observer in self.observers.iter_mut() {
+            if resigned {
+                observer.on_resign(side);
+            } else {
+                observer.on_time_out(side);
+            }
+            observer.on_turn_end(self.turns);
+        }
+
+        self.finish(Some(winner))
     }
 
-    #[rstest]
-    fn test_play_turn_not_ready() {
-        let mut game = Game::new();
-        let err = game.play_turn(&Cell::bounded(0, 0)).unwrap_err();
-        assert_eq!(err, "Game is not ready or already over");
+    /// Returns the side which resigned, if the game ended by resignation.
+    pub fn resigned(&self) -> Option<Side> {
+        self.last_turn
+            .as_ref()
+            .filter(|_| self.is_over())
+            .and_then(|turn| turn.resigned)
     }
 
-    #[rstest]
-    fn test_play_turn_when_over(mut human_player: Player, mut computer_player: Player) {
-        // pre-hit every occupied cell of the computer fleet.
-        let mut occupied = Vec::<Cell>::new();
-        for ship in computer_player.fleet().as_ref().iter() {
-            occupied.extend(ship.occupied_cells());
-        }
-        for cell in occupied {
-            human_player.attack(&mut computer_player, &cell);
+    // Relocates the Submarine of the human player before a turn where it fires no shot.
+    fn relocate_human_submarine(
+        &mut self,
+        cell: Cell,
+        orientation: ShipOrientation,
+    ) -> Result<(), GameError> {
+        if self.relocations(Side::Human) == 0 {
+            return Err(GameError::RelocationUnavailable);
         }
-        assert!(computer_player.has_lost());
 
-        let mut game = Game {
-            players: vec![human_player, computer_player],
-            last_computer_move: None,
+        let ship = ShipKind::Submarine
+            .ship(cell, orientation)
+            .ok_or(GameError::InvalidRelocation(cell))?;
+        let (first, second) = self.players.split_at_mut(1);
+        let (player, opposite) = match first[0].is_human() {
+            true => (&mut first[0], &second[0]),
+            false => (&mut second[0], &first[0]),
         };
+        relocate(player, opposite, &ship)?;
+        self.remaining_relocations[Side::Human as usize] -= 1;
+
+        Ok(())
+    }
+
+    // Starts the clock of the human player, if the game has a time control.
+    fn start_human_clock(&mut self) {
+        if let Some(clocks) = &mut self.clocks {
+            clocks[Side::Human as usize].start(Instant::now());
+        }
+    }
+
+    // Moves to the finished phase, or to the drawn one without a winner, stops the clocks and
+    // notifies the observers.
+    fn finish(&mut self, winner: Option<Side>) -> Result<(), GameError> {
+        self.phase = match winner {
+            Some(winner) => self.phase.finish(winner)?,
+            None => self.phase.draw()?,
+        };
+        info!(parent: &self.span, winner = ?winner, turns = self.turns, "game over");
+        if let Some(clocks) = &mut self.clocks {
+            let now = Instant::now();
+            clocks.iter_mut().for_each(|clock| clock.stop(now));
+        }
+
+        for observer in self.observers.iter_mut() {
+            match winner {
+                Some(winner) => observer.on_game_over(winner),
+                None => observer.on_draw(),
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an iterator which plays a turn at every step, using the given strategy to choose
+    /// the moves of the human player.
+    ///
+    /// The iterator ends when the game is over, or if the strategy has no more moves.
+    pub fn turns<S: Strategy>(&mut self, human: S) -> Turns<'_, S> {
+        Turns { game: self, human }
+    }
+
+    /// Lets the given strategies play the game in place of the human and the computer players.
+    ///
+    /// The computer player keeps its fleet, but it chooses its moves with the `computer`
+    /// strategy from now on. The returned iterator works like the one of [Game::turns].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// for event in game.autoplay(SmartStrategy::new(), RandomStrategy::new()) {
+    ///     println!("{event:?}");
+    /// }
+    /// ```
+    pub fn autoplay<A: Strategy, B: Strategy + 'static>(
+        &mut self,
+        human: A,
+        computer: B,
+    ) -> Turns<'_, A> {
+        self.set_computer_strategy(computer);
+
+        self.turns(human)
+    }
+
+    /// Lets the computer player choose its moves with the given strategy from now on, keeping
+    /// its fleet.
+    pub fn set_computer_strategy<S: Strategy + 'static>(&mut self, strategy: S) {
+        if let Some(player) = self.players.iter_mut().find(|p| !p.is_human()) {
+            player.set_strategy(strategy);
+        }
+    }
+}
+
+/// The state of a [Game] at a given moment, taken by [Game::snapshot].
+///
+/// It keeps what the turns change, the strategies included, but neither the rules nor the
+/// observers of the game.
+#[derive(Debug, Clone)]
+pub struct Snapshot {
+    players: Vec<PlayerState>,
+    phase: GamePhase,
+    last_computer_move: Option<Cell>,
+    rng: StdRng,
+    turns: u32,
+    last_turn: Option<TurnEvent>,
+    clocks: Option<[Clock; 2]>,
+    remaining_power_ups: [PowerUps; 2],
+    remaining_relocations: [u8; 2],
+    remaining_hints: u8,
+    streak: bool,
+    fog: Fog,
+    revealed: [Option<Ship>; 2],
+}
+
+/// What happened in a turn of the game.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct TurnEvent {
+    /// The number of the turn, counted from 1.
+    pub turn: u32,
+
+    /// The shots of the turn, in the order they were fired.
+    pub shots: Vec<ShotReport>,
+
+    /// The side which won the game in this turn, if any.
+    pub winner: Option<Side>,
+
+    /// Whether the game ended in a draw in this turn, as both fleets have been sunk.
+    pub draw: bool,
+
+    /// The side which resigned in this turn, if any. Nobody shoots in such a turn.
+    pub resigned: Option<Side>,
+
+    /// The side which ran out of time in this turn, if any.
+    pub timed_out: Option<Side>,
+
+    /// The scan made in this turn, if any. A side which scans fires no shot.
+    pub scan: Option<ScanReport>,
+
+    /// The sides which relocated their Submarine in this turn, instead of firing.
+    pub relocated: Vec<Side>,
+}
+
+/// A shot fired during a turn.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ShotReport {
+    /// The side which fired the shot.
+    pub shooter: Side,
+
+    /// The cell which has been shot.
+    pub cell: Cell,
+
+    /// The kind of the ship which has been hit, if any.
+    pub hit: Option<ShipKind>,
+
+    /// Whether the ship hit has been sunk by this shot.
+    pub sunk: bool,
+
+    /// The cells of the ship sunk by this shot, which the shooter learns as the ship goes down,
+    /// or none if no ship has been sunk.
+    pub sunk_cells: Vec<Cell>,
+
+    /// Whether the shot exploded a mine of the opponent.
+    pub mine: bool,
+
+    /// The cell of the shooter's fleet damaged by the mine, if any.
+    pub lost: Option<Cell>,
+
+    /// Whether the shot has been fired into the fog: its result is unknown to the shooter
+    /// until the fog lifts.
+    pub fogged: bool,
+}
+
+/// A scan made during a turn.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ScanReport {
+    /// The side which made the scan.
+    pub scanner: Side,
+
+    /// The cells which have been scanned.
+    pub cells: Vec<Cell>,
+
+    /// Whether a ship of the opponent occupies any of the cells.
+    pub detected: bool,
+}
+
+/// An iterator over the turns of a game, made by [Game::turns] or [Game::autoplay].
+pub struct Turns<'game, S: Strategy> {
+    game: &'game mut Game,
+    human: S,
+}
+
+impl<S: Strategy> Iterator for Turns<'_, S> {
+    type Item = TurnEvent;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (human, computer) = (self.game.human()?, self.game.computer()?);
+        let cell = self
+            .human
+            .next_move(&human.view(computer, self.game.rules()))?;
+        self.game.play_turn(&cell).ok()?;
+
+        let turn = self.game.last_turn.clone()?;
+        for shot in turn.shots.iter().filter(|shot| shot.shooter == Side::Human) {
+            if let Some(kind) = &shot.hit {
+                self.human.notify_hit(kind.clone());
+            }
+        }
+
+        Some(turn)
+    }
+}
+
+impl Default for Game {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Assembles a [Game] ready to play.
+///
+/// The builder collects the settings of the match and the players, then [GameBuilder::build]
+/// returns a game in the [GamePhase::InProgress] phase. Only the human player is required:
+/// every other setting has a default value.
+///
+/// # Example
+///
+/// ```rust
+/// let game = GameBuilder::new()
+///     .seed(42)
+///     .human("Ishmael", Fleet::build(|kind| kind.random()))
+///     .computer(Difficulty::Easy)
+///     .build()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct GameBuilder {
+    board_size: u8,
+    fleet_config: Vec<ShipKind>,
+    rules: Box<dyn Rules>,
+    seed: Option<u64>,
+    human: Option<(String, Fleet)>,
+    difficulty: Difficulty,
+    solver_budget: usize,
+    adaptation: Adaptation,
+    habits: Vec<u32>,
+    #[cfg(feature = "scripting")]
+    script: Option<Script>,
+    opponent_command: Option<Vec<String>>,
+    time_control: Option<Duration>,
+    handicap: u8,
+    handicap_option: Handicap,
+    power_ups: PowerUps,
+    hints: Option<u8>,
+    mines: Vec<Cell>,
+    map: Option<Map>,
+}
+
+impl GameBuilder {
+    const BOARD_SIZE: u8 = 10;
+
+    /// Creates a builder for a classic game on a 10x10 board with the standard fleet.
+    pub fn new() -> Self {
+        Self {
+            board_size: Self::BOARD_SIZE,
+            fleet_config: Fleet::COMPOSITION.to_vec(),
+            rules: Box::new(ClassicRules),
+            seed: None,
+            human: None,
+            difficulty: Difficulty::default(),
+            solver_budget: MonteCarloStrategy::NODE_BUDGET,
+            adaptation: Adaptation::default(),
+            habits: Vec::new(),
+            #[cfg(feature = "scripting")]
+            script: None,
+            opponent_command: None,
+            time_control: None,
+            handicap: 0,
+            handicap_option: Handicap::default(),
+            power_ups: PowerUps::default(),
+            hints: None,
+            mines: Vec::new(),
+            map: None,
+        }
+    }
+
+    /// Sets the number of rows and columns of the board.
+    ///
+    /// Only 10x10 boards are supported for now.
+    pub fn board_size(mut self, board_size: u8) -> Self {
+        self.board_size = board_size;
+        self
+    }
+
+    /// Sets the kinds of ship in every fleet.
+    ///
+    /// Only the standard fleet composition is supported for now.
+    pub fn fleet_config(mut self, fleet_config: &[ShipKind]) -> Self {
+        self.fleet_config = fleet_config.to_vec();
+        self
+    }
+
+    /// Sets the rules of the game.
+    pub fn rules<R: Rules + 'static>(mut self, rules: R) -> Self {
+        self.rules = Box::new(rules);
+        self
+    }
+
+    /// Sets the rules of the given variant of the game.
+    pub fn variant(mut self, variant: Variant) -> Self {
+        self.rules = variant.rules();
+        self
+    }
+
+    /// Sets the rules of the given variants, played together.
+    pub fn variants(mut self, variants: &[Variant]) -> Self {
+        self.rules = Box::new(CombinedRules::of(variants));
+        self
+    }
+
+    /// Plays on the given map, whose islands block the cells of both boards.
+    pub fn map(mut self, map: Map) -> Self {
+        self.map = Some(map);
+        self
+    }
+
+    /// Sets the seed of the random choices of the game.
+    ///
+    /// Two games built with the same seed and the same players have the same computer fleet,
+    /// the same players' order and the same computer moves.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// Sets the human player, with their name and their fleet.
+    pub fn human(mut self, name: &str, fleet: Fleet) -> Self {
+        self.human = Some((name.to_string(), fleet));
+        self
+    }
+
+    /// Places the mines of the human player on the given free cells of their fleet.
+    ///
+    /// The mines which are not placed, up to the number required by the rules, are placed at
+    /// random.
+    pub fn mines(mut self, cells: &[Cell]) -> Self {
+        self.mines = cells.to_vec();
+        self
+    }
+
+    /// Sets the difficulty of the computer player.
+    pub fn computer(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = difficulty;
+        self
+    }
+
+    /// Sets how many placements the hard computer player can explore to solve the endgame.
+    pub fn solver_budget(mut self, nodes: usize) -> Self {
+        self.solver_budget = nodes;
+        self
+    }
+
+    /// Sets how strongly the adaptive computer player keeps the game close.
+    pub fn adaptation(mut self, adaptation: Adaptation) -> Self {
+        self.adaptation = adaptation;
+        self
+    }
+
+    /// Sets the placement habits of the human player, for the learning computer player.
+    pub fn habits(mut self, habits: &[u32]) -> Self {
+        self.habits = habits.to_vec();
+        self
+    }
+
+    /// Lets the given script play the computer player, whatever its difficulty.
+    #[cfg(feature = "scripting")]
+    pub fn script(mut self, script: Script) -> Self {
+        self.script = Some(script);
+        self
+    }
+
+    /// Lets the external program of the given command line play the computer player, whatever
+    /// its difficulty.
+    pub fn opponent_command(mut self, command: &[String]) -> Self {
+        self.opponent_command = Some(command.to_vec());
+        self
+    }
+
+    /// Gives each side the given time to play the whole game.
+    pub fn time_control(mut self, time: Duration) -> Self {
+        self.time_control = Some(time);
+        self
+    }
+
+    /// Grants the given power-ups to each side.
+    pub fn power_ups(mut self, power_ups: PowerUps) -> Self {
+        self.power_ups = power_ups;
+        self
+    }
+
+    /// Lets the human player ask for the given number of hints, instead of the ones allowed by
+    /// the rules.
+    pub fn hints(mut self, hints: u8) -> Self {
+        self.hints = Some(hints);
+        self
+    }
+
+    /// Damages the given number of cells of the human fleet, chosen at random, before the game
+    /// starts.
+    ///
+    /// At least one cell is always left undamaged, so that the human fleet is never sunk before
+    /// the first shot.
+    pub fn handicap(mut self, cells: u8) -> Self {
+        self.handicap = cells;
+        self
+    }
+
+    /// Sets the handicap which balances the game: an extra Destroyer for a side, or a ship of a
+    /// side revealed to the opponent.
+    pub fn handicap_option(mut self, handicap: Handicap) -> Self {
+        self.handicap_option = handicap;
+        self
+    }
+
+    /// Builds the game, ready to play.
+    ///
+    /// An error is returned if the human player has not been set, if the board size or the
+    /// fleet composition is not supported, if the rules don't allow the human fleet, if the
+    /// human fleet or mines are on an island, or if the mines can't be placed.
+    pub fn build(self) -> Result<Game, GameError> {
+        if self.board_size != Self::BOARD_SIZE {
+            return Err(GameError::UnsupportedBoardSize(self.board_size));
+        } else if self.fleet_config != Fleet::COMPOSITION {
+            return Err(GameError::UnsupportedFleet);
+        }
+
+        let (name, mut fleet) = self.human.ok_or(GameError::MissingHuman)?;
+        if !self.rules.allows_fleet(&fleet) {
+            return Err(GameError::FleetNotAllowed);
+        } else if self.mines.len() > self.rules.mines() {
+            return Err(MineError::TooMany(self.rules.mines()).into());
+        }
+        let mines = Minefield::new(&self.mines, &fleet)?;
+        if let Some(map) = &self.map
+            && let Some(cell) = map
+                .island_under(&fleet)
+                .or_else(|| self.mines.iter().copied().find(|cell| map.is_island(cell)))
+        {
+            return Err(GameError::OnIsland(cell));
+        }
+        let mut rng = match self.seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
+
+        if self.handicap > 0 {
+            let cells: Vec<Cell> = fleet
+                .as_ref()
+                .iter()
+                .flat_map(|ship| ship.occupied_cells())
+                .collect();
+            let damaged = usize::from(self.handicap).min(cells.len() - 1);
+            for cell in cells.choose_multiple(&mut rng, damaged) {
+                fleet.hit_at(cell);
+            }
+        }
+
+        let mut game = Game::with(self.rules, rng);
+        game.set_difficulty(self.difficulty);
+        game.set_solver_budget(self.solver_budget);
+        game.set_adaptation(self.adaptation);
+        game.set_habits(&self.habits);
+        #[cfg(feature = "scripting")]
+        game.set_script(self.script);
+        game.set_opponent_command(self.opponent_command);
+        game.set_time_control(self.time_control);
+        game.set_power_ups(self.power_ups);
+        game.set_hint_limit(self.hints);
+        game.set_map(self.map);
+        game.set_handicap(self.handicap_option);
+        let mut player = Player::new(&name, fleet);
+        player.set_mines(mines);
+        game.set_human_player(player);
+
+        Ok(game)
+    }
+}
+
+impl Default for GameBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Plays the move of the player and notifies the observers about it.
+//
+// When the shot explodes a mine, the player loses a random cell of their own fleet.
+fn do_move(
+    observers: &mut [Box<dyn GameObserver>],
+    rng: &mut StdRng,
+    player: &mut Player,
+    opposite: &mut Player,
+    cell: &Cell,
+) -> ShotReport {
+    let cell = *cell;
+    let outcome = player.attack(opposite, &cell);
+    let mine = opposite.explode_mine(&cell);
+    let lost = if mine {
+        player.damage_random_cell(rng)
+    } else {
+        None
+    };
+
+    let shot = ShotReport {
+        shooter: Side::of(player),
+        cell,
+        hit: outcome.ship,
+        sunk: outcome.sunk,
+        sunk_cells: outcome.sunk_cells.unwrap_or_default(),
+        mine,
+        lost: lost.as_ref().map(|(cell, _)| *cell),
+        fogged: false,
+    };
+    for observer in observers.iter_mut() {
+        observer.on_shot(shot.shooter, &shot.cell, shot.hit.as_ref());
+        if let Some(kind) = shot.hit.as_ref().filter(|_| shot.sunk) {
+            observer.on_ship_sunk(Side::of(opposite), kind);
+        }
+        if shot.mine {
+            observer.on_mine(shot.shooter, &shot.cell, shot.lost.as_ref());
+        }
+        if let Some(kind) = lost
+            .as_ref()
+            .filter(|(_, outcome)| outcome.sunk)
+            .and_then(|(_, outcome)| outcome.ship.as_ref())
+        {
+            observer.on_ship_sunk(shot.shooter, kind);
+        }
+    }
+
+    shot
+}
+
+// Moves the first ship afloat of the player of the same kind as the given one to its position,
+// which must be empty: free of mines, and never shot by the opposite player, whose shots grid
+// charts the islands too.
+fn relocate(player: &mut Player, opposite: &Player, ship: &Ship) -> Result<(), GameError> {
+    let id = player
+        .fleet()
+        .ships()
+        .find(|(_, afloat)| afloat.kind() == ship.kind() && !afloat.is_sunk())
+        .map(|(id, _)| id)
+        .ok_or(GameError::RelocationUnavailable)?;
+    relocate_ship(player, opposite, id, ship)
+}
+
+// Moves the ship of the player with the given identifier to a valid empty position.
+fn relocate_ship(
+    player: &mut Player,
+    opposite: &Player,
+    id: ShipId,
+    ship: &Ship,
+) -> Result<(), GameError> {
+    let first = ship.first_cell();
+    let empty = ship.occupied_cells().iter().all(|cell| {
+        !player.mines().is_mined(cell) && *opposite.shots_grid().at(cell) == CellState::Empty
+    });
+    if !empty {
+        return Err(GameError::InvalidRelocation(first));
+    }
+
+    player
+        .relocate(id, ship)
+        .map_err(|_| GameError::InvalidRelocation(first))
+}
+
+// Moves a damaged Submarine of the player to a random valid empty position, if any.
+//
+// Returns `true` if the Submarine has moved.
+fn evade(rng: &mut StdRng, player: &mut Player, opposite: &Player) -> bool {
+    let Some(id) = player
+        .fleet()
+        .ships()
+        .find(|(_, ship)| {
+            *ship.kind() == ShipKind::Submarine && ship.remaining() < ship.size() && !ship.is_sunk()
+        })
+        .map(|(id, _)| id)
+    else {
+        return false;
+    };
+
+    (0..Game::MAX_RELOCATION_ATTEMPTS).any(|_| {
+        let ship = ShipKind::Submarine.random_with(rng);
+        relocate_ship(player, opposite, id, &ship).is_ok()
+    })
+}
+
+// Returns the next move of the computer player. When the move is prepared, its strategy is
+// awaited until it can answer at once, like another program thinking about its move.
+#[cfg_attr(not(feature = "async"), allow(unused_variables))]
+async fn computer_move(
+    player: &mut Player,
+    opposite: &Player,
+    rules: &dyn Rules,
+    prepare: bool,
+) -> Option<Cell> {
+    #[cfg(feature = "async")]
+    if prepare && let Some(preparation) = player.prepare_move(opposite, rules) {
+        preparation.await;
+    }
+
+    let next = player.next_move(opposite, rules);
+    debug!(cell = ?next, "computer move");
+    next
+}
+
+// Returns the hash of the state of a battle between the players, in the given order: the first
+// 8 bytes of the SHA-256 digest of the turn, then of the placements and of the shots grid of
+// every player, or of nothing for a missing player.
+pub(crate) fn state_hash(turn: u32, players: [Option<&Player>; 2]) -> u64 {
+    let mut hasher = Sha256::new();
+    hasher.update(turn.to_be_bytes());
+    for player in players {
+        let Some(player) = player else {
+            hasher.update([0]);
+            continue;
+        };
+        let code = player.fleet().to_code();
+        hasher.update([1]);
+        hasher.update((code.len() as u32).to_be_bytes());
+        hasher.update(code.as_bytes());
+        let grid = player.shots_grid();
+        for cell in (0..10).flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y))) {
+            hasher.update([match grid.at(&cell) {
+                CellState::Empty => 0,
+                CellState::Occupied => 1,
+                CellState::Miss => 2,
+                CellState::Hit => 3,
+                CellState::Blocked => 4,
+            }]);
+        }
+    }
+    let digest = hasher.finalize();
+
+    u64::from_be_bytes(digest[..8].try_into().expect("a digest has 32 bytes"))
+}
+
+// Returns the player at the given position in the players' order, and their opposite.
+fn pair_mut(players: &mut [Player], index: usize) -> (&mut Player, &mut Player) {
+    let (first, second) = players.split_at_mut(1);
+    match index {
+        0 => (&mut first[0], &mut second[0]),
+        _ => (&mut second[0], &mut first[0]),
+    }
+}
+
+// Scans the cells of the opposite fleet for the human player and notifies the observers about it.
+fn do_scan(
+    observers: &mut [Box<dyn GameObserver>],
+    opposite: &Player,
+    cells: Vec<Cell>,
+) -> ScanReport {
+    let scan = ScanReport {
+        scanner: Side::Human,
+        detected: cells.iter().any(|cell| opposite.fleet().is_occupied(cell)),
+        cells,
+    };
+    for observer in observers.iter_mut() {
+        observer.on_scan(scan.scanner, &scan.cells, scan.detected);
+    }
+
+    scan
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::{Fleet, Ship, tests::fixed_fleet};
+    use crate::engine::strategy::{StrategyKind, StrategyView};
+    use rstest::{fixture, rstest};
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use std::rc::Rc;
+    use std::str::FromStr;
+
+    // Makes a game with the given players, in the given phase.
+    fn game_with(players: Vec<Player>, phase: GamePhase) -> Game {
+        let mut game = Game::new();
+        game.players = players;
+        game.phase = phase;
+
+        game
+    }
+
+    #[fixture]
+    fn human_player(fixed_fleet: Fleet) -> Player {
+        Player::new("Human", fixed_fleet)
+    }
+
+    #[fixture]
+    fn computer_player(fixed_fleet: Fleet) -> Player {
+        let mut player = Player::new(Game::COMPUTER_NAME, fixed_fleet);
+        player.set_strategy(RandomStrategy::new());
+
+        player
+    }
+
+    #[rstest]
+    fn test_play_turn_not_ready() {
+        let mut game = Game::new();
+        let err = game.play_turn(&Cell::bounded(0, 0)).unwrap_err();
+        assert_eq!(err, GameError::NotReady);
+    }
+
+    #[rstest]
+    fn test_play_turn_when_over(mut human_player: Player, mut computer_player: Player) {
+        // pre-hit every occupied cell of the computer fleet.
+        let mut occupied = Vec::<Cell>::new();
+        for ship in computer_player.fleet().as_ref().iter() {
+            occupied.extend(ship.occupied_cells());
+        }
+        for cell in occupied {
+            human_player.attack(&mut computer_player, &cell);
+        }
+        assert!(computer_player.has_lost());
+
+        let mut game = game_with(
+            vec![human_player, computer_player],
+            GamePhase::Finished {
+                winner: Side::Human,
+            },
+        );
 
         assert!(game.is_over());
 
         let err = game.play_turn(&Cell::bounded(0, 0)).unwrap_err();
-        assert_eq!(err, "Game is not ready or already over");
+        assert_eq!(err, GameError::AlreadyOver);
     }
 
     #[rstest]
@@ -204,16 +2017,1307 @@ mod tests {
         assert!(!computer_player.has_lost());
 
         // Force order: human plays first (so the "human_move" is actually used).
-        let mut game = Game {
-            players: vec![human_player, computer_player],
-            last_computer_move: None,
-        };
+        let mut game = game_with(vec![human_player, computer_player], GamePhase::InProgress);
         assert!(game.is_ready());
 
         let winner = game.play_turn(&winning_cell).unwrap();
 
         assert_eq!(winner, Some(true));
         assert!(game.is_over());
+        assert_eq!(
+            game.phase(),
+            GamePhase::Finished {
+                winner: Side::Human
+            }
+        );
+    }
+
+    // Collects the JSON logs of a game.
+    #[derive(Debug, Default, Clone)]
+    struct Logs(std::sync::Arc<std::sync::Mutex<Vec<u8>>>);
+
+    impl std::io::Write for Logs {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[rstest]
+    fn test_logs_turns(human_player: Player, computer_player: Player) {
+        let logs = Logs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .json()
+            .with_writer(move || writer.clone())
+            .with_max_level(tracing::Level::DEBUG)
+            .finish();
+        tracing::subscriber::with_default(subscriber, || {
+            let mut game = game_with(vec![human_player, computer_player], GamePhase::InProgress);
+            game.play_turn(&Cell::bounded(0, 0)).unwrap();
+        });
+
+        let logs = String::from_utf8(logs.0.lock().unwrap().clone()).unwrap();
+        let shots: Vec<serde_json::Value> = logs
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|log| log["fields"]["message"] == "shot")
+            .collect();
+        assert_eq!(shots.len(), 2);
+        assert_eq!(shots[0]["fields"]["shooter"], "Human");
+        assert_eq!(shots[0]["fields"]["cell"], "A1");
+        assert_eq!(shots[1]["fields"]["shooter"], "Computer");
+        for shot in &shots {
+            assert_eq!(shot["spans"][0]["name"], "game");
+            assert_eq!(shot["span"]["name"], "turn");
+            assert_eq!(shot["span"]["number"], 1);
+        }
+    }
+
+    #[derive(Debug, Default, Clone)]
+    struct Recorder(Rc<RefCell<Vec<String>>>);
+
+    impl GameObserver for Recorder {
+        fn on_shot(&mut self, shooter: Side, cell: &Cell, hit: Option<&ShipKind>) {
+            let hit = hit.map_or("miss".to_string(), |kind| kind.to_string());
+            self.0.borrow_mut().push(format!("{shooter} {cell} {hit}"));
+        }
+
+        fn on_ship_sunk(&mut self, owner: Side, kind: &ShipKind) {
+            self.0.borrow_mut().push(format!("{owner} {kind} sunk"));
+        }
+
+        fn on_turn_end(&mut self, turn: u32) {
+            self.0.borrow_mut().push(format!("turn {turn}"));
+        }
+
+        fn on_game_over(&mut self, winner: Side) {
+            self.0.borrow_mut().push(format!("{winner} wins"));
+        }
+    }
+
+    #[rstest]
+    fn test_observers(mut human_player: Player, mut computer_player: Player) {
+        // Leave only the last cell of the Destroyer afloat, so the human wins with the next shot.
+        let mut occupied = Vec::<Cell>::new();
+        for ship in computer_player.fleet().as_ref().iter() {
+            occupied.extend(ship.occupied_cells());
+        }
+        let winning_cell = occupied.pop().unwrap();
+        for cell in occupied {
+            human_player.attack(&mut computer_player, &cell);
+        }
+
+        let mut game = game_with(vec![human_player, computer_player], GamePhase::InProgress);
+        let recorder = Recorder::default();
+        game.subscribe(Box::new(recorder.clone()));
+
+        game.play_turn(&winning_cell).unwrap();
+        assert_eq!(
+            *recorder.0.borrow(),
+            vec![
+                format!("Human {winning_cell} Destroyer"),
+                "Computer Destroyer sunk".to_string(),
+                "turn 1".to_string(),
+                "Human wins".to_string(),
+            ]
+        );
+        assert_eq!(game.turns_played(), 1);
+    }
+
+    #[rstest]
+    fn test_autoplay(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+
+        let events: Vec<TurnEvent> = game
+            .autoplay(SmartStrategy::with_seed(1), SmartStrategy::with_seed(2))
+            .collect();
+        let last = events.last().unwrap();
+
+        assert_eq!(events.len() as u32, game.turns_played());
+        assert!(
+            events[..events.len() - 1]
+                .iter()
+                .all(|e| e.winner.is_none())
+        );
+        assert_eq!(
+            game.phase(),
+            GamePhase::Finished {
+                winner: last.winner.unwrap()
+            }
+        );
+        assert_eq!(game.last_turn(), Some(last));
+    }
+
+    #[rstest]
+    fn test_state_hash(human_player: Player, computer_player: Player, fixed_fleet: Fleet) {
+        let game = game_with(vec![human_player, computer_player], GamePhase::InProgress);
+        // The hash doesn't depend on the platform nor on the run.
+        assert_eq!(game.state_hash(), 12922593381800695265);
+
+        // The same game, played again, goes through the same states.
+        let play = |seed: u64| {
+            let mut game = GameBuilder::new()
+                .seed(7)
+                .human("Human", fixed_fleet.clone())
+                .build()
+                .unwrap();
+            let mut human = SmartStrategy::with_seed(seed);
+            game.set_computer_strategy(SmartStrategy::with_seed(2));
+            let mut hashes = vec![game.state_hash()];
+            while game.turns(&mut human).next().is_some() {
+                hashes.push(game.state_hash());
+            }
+            hashes
+        };
+        let hashes = play(1);
+
+        assert_eq!(hashes, play(1));
+        assert_ne!(hashes, play(3));
+        assert_eq!(hashes[0], play(3)[0]);
+        let mut unique = hashes.clone();
+        unique.sort();
+        unique.dedup();
+        assert_eq!(unique.len(), hashes.len());
+    }
+
+    #[rstest]
+    fn test_revision(fixed_fleet: Fleet) {
+        let build = || {
+            GameBuilder::new()
+                .seed(7)
+                .human("Human", fixed_fleet.clone())
+                .build()
+                .unwrap()
+        };
+        let (mut game, other) = (build(), build());
+        assert_ne!(game.revision(), other.revision());
+
+        let started = game.revision();
+        let snapshot = game.snapshot();
+        game.hint().unwrap();
+        assert_eq!(game.revision(), started);
+
+        game.play_turn(&Cell::bounded(0, 0)).unwrap();
+        let played = game.revision();
+        assert_ne!(played, started);
+
+        game.restore(&snapshot);
+        assert_ne!(game.revision(), played);
+        assert_ne!(game.revision(), started);
+
+        let restored = game.revision();
+        game.resign(Side::Human).unwrap();
+        assert_ne!(game.revision(), restored);
+    }
+
+    #[rstest]
+    fn test_snapshot(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        game.set_computer_strategy(SmartStrategy::with_seed(2));
+        game.play_turn(&Cell::bounded(0, 0)).unwrap();
+        let snapshot = game.snapshot();
+        let hash = game.state_hash();
+        game.play_turn(&Cell::bounded(5, 5)).unwrap();
+        let played = (game.state_hash(), game.last_computer_move().copied());
+
+        let mut human = SmartStrategy::with_seed(1);
+        while game.turns(&mut human).next().is_some() {}
+        assert!(game.is_over());
+
+        game.restore(&snapshot);
+        assert_eq!(game.state_hash(), hash);
+        assert_eq!(game.phase(), GamePhase::InProgress);
+        assert_eq!(game.turns_played(), 1);
+        assert_eq!(game.last_turn().unwrap().turn, 1);
+
+        // The strategy forgets the moves played since: the same move has the same outcome.
+        game.play_turn(&Cell::bounded(5, 5)).unwrap();
+        assert_eq!(
+            (game.state_hash(), game.last_computer_move().copied()),
+            played
+        );
+
+        // The snapshot can be restored again, and the computer fires at every cell once.
+        game.restore(&snapshot);
+        let mut human = SmartStrategy::with_seed(3);
+        let mut fired = HashSet::new();
+        while game.turns(&mut human).next().is_some() {
+            assert!(fired.insert(*game.last_computer_move().unwrap()));
+        }
+        assert!(game.is_over());
+    }
+
+    #[rstest]
+    fn test_autoplay_turn_by_turn(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        let mut human: Box<dyn Strategy> = StrategyKind::Heatmap.strategy();
+        game.set_computer_strategy(StrategyKind::Smart.strategy());
+
+        while !game.is_over() {
+            let turns = game.turns_played();
+            assert!(game.turns(human.as_mut()).next().is_some());
+            assert_eq!(game.turns_played(), turns + 1);
+        }
+        assert!(game.winner().is_some());
+    }
+
+    #[rstest]
+    fn test_resign(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        let recorder = Recorder::default();
+        game.subscribe(Box::new(recorder.clone()));
+
+        game.play_turn(&Cell::bounded(0, 0)).unwrap();
+        assert_eq!(game.resigned(), None);
+
+        game.resign(Side::Human).unwrap();
+        assert_eq!(
+            game.phase(),
+            GamePhase::Finished {
+                winner: Side::Computer
+            }
+        );
+        assert_eq!(game.resigned(), Some(Side::Human));
+        assert_eq!(
+            game.last_turn(),
+            Some(&TurnEvent {
+                turn: 2,
+                shots: Vec::new(),
+                winner: Some(Side::Computer),
+                draw: false,
+                resigned: Some(Side::Human),
+                timed_out: None,
+                scan: None,
+                relocated: Vec::new(),
+            })
+        );
+        assert_eq!(
+            recorder.0.borrow()[recorder.0.borrow().len() - 2..],
+            ["turn 2".to_string(), "Computer wins".to_string()]
+        );
+
+        assert_eq!(game.resign(Side::Computer), Err(GameError::AlreadyOver));
+    }
+
+    #[rstest]
+    fn test_time_control(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .time_control(Duration::from_secs(60))
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert!(game.clock(Side::Human).unwrap().is_running());
+        assert!(!game.clock(Side::Computer).unwrap().is_running());
+
+        game.play_turn(&Cell::bounded(0, 0)).unwrap();
+        assert_eq!(game.check_time(), None);
+        assert!(game.clock(Side::Human).unwrap().is_running());
+        assert!(!game.clock(Side::Computer).unwrap().is_running());
+        assert!(
+            game.clock(Side::Human).unwrap().remaining(Instant::now()) < Duration::from_secs(60)
+        );
+    }
+
+    #[rstest]
+    fn test_time_out(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .time_control(Duration::ZERO)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+
+        assert_eq!(game.check_time(), Some(Side::Human));
+        assert_eq!(
+            game.phase(),
+            GamePhase::Finished {
+                winner: Side::Computer
+            }
+        );
+        assert_eq!(game.last_turn().unwrap().timed_out, Some(Side::Human));
+        assert_eq!(game.resigned(), None);
+        assert!(!game.clock(Side::Human).unwrap().is_running());
+    }
+
+    #[rstest]
+    fn test_time_out_while_playing(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .time_control(Duration::ZERO)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+
+        let human_won = game.play_turn(&Cell::bounded(0, 0)).unwrap();
+        let turn = game.last_turn().unwrap();
+        let loser = turn.timed_out.unwrap();
+        assert!(turn.shots.is_empty());
+        assert_eq!(turn.winner, Some(loser.opponent()));
+        assert_eq!(human_won, Some(loser == Side::Computer));
+    }
+
+    #[rstest]
+    fn test_no_time_control(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+
+        assert!(game.clock(Side::Human).is_none());
+        assert_eq!(game.check_time(), None);
+    }
+
+    #[rstest]
+    fn test_resign_when_not_ready() {
+        let mut game = Game::new();
+        assert_eq!(game.resign(Side::Human), Err(GameError::NotReady));
+    }
+
+    #[rstest]
+    fn test_turns_when_not_ready() {
+        let mut game = Game::new();
+        assert_eq!(game.turns(RandomStrategy::new()).count(), 0);
+    }
+
+    #[rstest]
+    #[case(Difficulty::Easy, "RandomStrategy")]
+    #[case(Difficulty::Normal, "SmartStrategy")]
+    #[case(Difficulty::Hard, "MonteCarloStrategy")]
+    #[case(Difficulty::Adaptive, "AdaptiveStrategy")]
+    #[case(Difficulty::Learning, "MonteCarloStrategy")]
+    fn test_difficulty_sets_computer_strategy(
+        human_player: Player,
+        #[case] difficulty: Difficulty,
+        #[case] strategy: &str,
+    ) {
+        let mut game = Game::new();
+        game.set_difficulty(difficulty);
+        game.set_human_player(human_player);
+
+        let computer = format!("{:?}", game.computer().unwrap());
+        assert!(computer.contains(strategy));
+    }
+
+    #[rstest]
+    #[case(&["sh", "-c", "while read line; do echo J10; done"], "ProcessStrategy")]
+    #[case(&["naval-test-no-such-bot"], "SmartStrategy")]
+    fn test_opponent_command_sets_computer_strategy(
+        human_player: Player,
+        #[case] command: &[&str],
+        #[case] strategy: &str,
+    ) {
+        let command: Vec<String> = command.iter().map(|arg| arg.to_string()).collect();
+        let mut game = Game::new();
+        game.set_opponent_command(Some(command));
+        game.set_human_player(human_player);
+
+        let computer = format!("{:?}", game.computer().unwrap());
+        assert!(computer.contains(strategy));
+    }
+
+    #[cfg(feature = "async")]
+    #[rstest]
+    fn test_play_turn_async(human_player: Player) {
+        use crate::engine::strategy::tests::block_on;
+
+        let command = ["sh", "-c", "while read line; do sleep 0.2; echo J10; done"];
+        let mut game = Game::new();
+        game.set_opponent_command(Some(command.map(String::from).to_vec()));
+        game.set_human_player(human_player);
+
+        // The turn waits for the answer of the program without blocking the thread.
+        let mut turn = Box::pin(game.play_turn_async(&Cell::bounded(0, 0)));
+        let context = &mut Context::from_waker(Waker::noop());
+        assert!(turn.as_mut().poll(context).is_pending());
+        assert_eq!(block_on(turn), Ok(None));
+
+        assert_eq!(game.last_computer_move(), Some(&Cell::bounded(9, 9)));
+        assert_eq!(game.turns_played(), 1);
+    }
+
+    #[rstest]
+    fn test_hint(human_player: Player) {
+        let mut game = Game::new();
+        assert_eq!(game.hint(), Err(GameError::NotReady));
+
+        game.set_human_player(human_player);
+        assert_eq!(game.hints(), 3);
+        for left in (0..3).rev() {
+            let cell = game.hint().unwrap();
+            assert_eq!(
+                *game.human().unwrap().shots_grid().at(&cell),
+                CellState::Empty
+            );
+            assert_eq!(game.hints(), left);
+        }
+        assert_eq!(game.hint(), Err(GameError::HintUnavailable));
+
+        game.resign(Side::Human).unwrap();
+        assert_eq!(game.hint(), Err(GameError::AlreadyOver));
+    }
+
+    #[rstest]
+    fn test_hint_limit(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .hints(1)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert_eq!(game.hints(), 1);
+        assert!(game.hint().is_ok());
+        assert_eq!(game.hint(), Err(GameError::HintUnavailable));
+    }
+
+    #[rstest]
+    fn test_hint_keeps_seeded_games(fixed_fleet: Fleet) {
+        let build = || {
+            GameBuilder::new()
+                .seed(7)
+                .computer(Difficulty::Hard)
+                .hints(10)
+                .human("Human", fixed_fleet.clone())
+                .build()
+                .unwrap()
+        };
+        let (mut hinted, mut plain) = (build(), build());
+        for cell in (0..10).map(|i| Cell::bounded(i, i)) {
+            hinted.hint().unwrap();
+            assert_eq!(hinted.play_turn(&cell), plain.play_turn(&cell));
+            assert_eq!(hinted.last_computer_move(), plain.last_computer_move());
+        }
+    }
+
+    #[rstest]
+    fn test_hint_in_the_fog(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::Fog)
+            .hints(20)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        for cell in (0..100).map(|i| Cell::bounded(i % 10, i / 10)).take(20) {
+            game.play_turn(&cell).unwrap();
+            let cell = game.hint().unwrap();
+            assert!(!game.fog().is_hidden(Side::Human, &cell));
+            assert_eq!(
+                *game.human().unwrap().shots_grid().at(&cell),
+                CellState::Empty
+            );
+        }
+    }
+
+    #[rstest]
+    fn test_set_human_player_starts_the_game(human_player: Player) {
+        let mut game = Game::new();
+        assert_eq!(game.phase(), GamePhase::Setup);
+
+        game.set_human_player(human_player);
+        assert_eq!(game.phase(), GamePhase::InProgress);
+    }
+
+    #[rstest]
+    #[case(GamePhase::Setup, Ok(GamePhase::InProgress))]
+    #[case(GamePhase::InProgress, Err(GameError::AlreadyStarted))]
+    #[case(GamePhase::Finished { winner: Side::Human }, Err(GameError::AlreadyOver))]
+    fn test_phase_start(#[case] phase: GamePhase, #[case] expected: Result<GamePhase, GameError>) {
+        assert_eq!(phase.start(), expected);
+    }
+
+    #[rstest]
+    #[case(GamePhase::Setup, Err(GameError::NotReady))]
+    #[case(GamePhase::InProgress, Ok(GamePhase::Finished { winner: Side::Computer }))]
+    #[case(GamePhase::Finished { winner: Side::Human }, Err(GameError::AlreadyOver))]
+    #[case(GamePhase::Drawn, Err(GameError::AlreadyOver))]
+    fn test_phase_finish(#[case] phase: GamePhase, #[case] expected: Result<GamePhase, GameError>) {
+        assert_eq!(phase.finish(Side::Computer), expected);
+    }
+
+    #[rstest]
+    #[case(GamePhase::Setup, Err(GameError::NotReady))]
+    #[case(GamePhase::InProgress, Ok(GamePhase::Drawn))]
+    #[case(GamePhase::Finished { winner: Side::Human }, Err(GameError::AlreadyOver))]
+    #[case(GamePhase::Drawn, Err(GameError::AlreadyOver))]
+    fn test_phase_draw(#[case] phase: GamePhase, #[case] expected: Result<GamePhase, GameError>) {
+        assert_eq!(phase.draw(), expected);
+    }
+
+    #[rstest]
+    fn test_builder(human_player: Player) {
+        let name = human_player.name().to_string();
+        let fleet = human_player.fleet().clone();
+        let game = GameBuilder::new()
+            .human(&name, fleet.clone())
+            .computer(Difficulty::Easy)
+            .build()
+            .unwrap();
+
+        assert_eq!(game.phase(), GamePhase::InProgress);
+        assert_eq!(game.human().unwrap().name(), name);
+        assert_eq!(game.human().unwrap().fleet().to_code(), fleet.to_code());
+        assert_eq!(game.rules().name(), "Classic");
+        assert!(format!("{:?}", game.computer().unwrap()).contains("RandomStrategy"));
+    }
+
+    #[rstest]
+    fn test_builder_with_seed(fixed_fleet: Fleet) {
+        let build = || {
+            GameBuilder::new()
+                .seed(42)
+                .human("Human", fixed_fleet.clone())
+                .build()
+                .unwrap()
+        };
+
+        let (mut game1, mut game2) = (build(), build());
+        assert_eq!(
+            game1.computer().unwrap().fleet().to_code(),
+            game2.computer().unwrap().fleet().to_code()
+        );
+
+        for cell in ["A1", "B2", "C3", "D4", "E5"] {
+            let cell = Cell::from_str(cell).unwrap();
+            game1.play_turn(&cell).unwrap();
+            game2.play_turn(&cell).unwrap();
+            assert_eq!(game1.last_computer_move(), game2.last_computer_move());
+        }
+    }
+
+    #[rstest]
+    fn test_sunk_cells(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(42)
+            .human("Human", fixed_fleet)
+            .computer(Difficulty::Easy)
+            .build()
+            .unwrap();
+        let destroyer = game
+            .computer()
+            .unwrap()
+            .fleet()
+            .get(&ShipKind::Destroyer)
+            .unwrap()
+            .occupied_cells();
+
+        for (i, cell) in destroyer.iter().enumerate() {
+            game.play_turn(cell).unwrap();
+            let turn = game.last_turn().unwrap();
+            let shot = turn
+                .shots
+                .iter()
+                .find(|shot| shot.shooter == Side::Human)
+                .unwrap();
+            assert_eq!(shot.sunk, i == destroyer.len() - 1);
+            match shot.sunk {
+                true => assert_eq!(shot.sunk_cells, destroyer.as_slice()),
+                false => assert!(shot.sunk_cells.is_empty()),
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_computer_heatmap(fixed_fleet: Fleet) {
+        let build = |difficulty| {
+            GameBuilder::new()
+                .seed(42)
+                .human("Human", fixed_fleet.clone())
+                .computer(difficulty)
+                .build()
+                .unwrap()
+        };
+        assert_eq!(Game::new().computer_heatmap(), None);
+        assert_eq!(build(Difficulty::Easy).computer_heatmap(), None);
+
+        let (mut game1, mut game2) = (build(Difficulty::Hard), build(Difficulty::Hard));
+        for cell in ["A1", "B2", "C3"] {
+            let heatmap = game1.computer_heatmap().unwrap();
+            let shots = game1.computer().unwrap().shots_grid();
+            assert_eq!(
+                heatmap.len(),
+                (0..100)
+                    .filter(|i| *shots.at(&Cell::bounded(i % 10, i / 10)) == CellState::Empty)
+                    .count()
+            );
+            assert!(heatmap.values().all(|heat| (0.0..=1.0).contains(heat)));
+            assert!(heatmap.values().any(|heat| *heat == 1.0));
+
+            // The heatmap doesn't change the moves of the computer.
+            let cell = Cell::from_str(cell).unwrap();
+            game1.play_turn(&cell).unwrap();
+            game2.play_turn(&cell).unwrap();
+            assert_eq!(game1.last_computer_move(), game2.last_computer_move());
+        }
+    }
+
+    #[rstest]
+    fn test_bombard(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .power_ups(PowerUps::STANDARD)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        let target = Cell::from_str("E5").unwrap();
+        let human_shots = |game: &Game| {
+            game.last_turn()
+                .unwrap()
+                .shots
+                .iter()
+                .filter(|shot| shot.shooter == Side::Human)
+                .count()
+        };
+
+        game.play_turn(&target).unwrap();
+        assert_eq!(human_shots(&game), 1);
+        assert_eq!(game.power_ups(Side::Human), PowerUps::STANDARD);
+
+        game.play_action(PlayerAction::Bombard(target)).unwrap();
+        assert_eq!(human_shots(&game), 8);
+        assert_eq!(game.power_ups(Side::Human).bombardments, 0);
+        assert_eq!(game.power_ups(Side::Computer), PowerUps::STANDARD);
+        assert_eq!(game.turns_played(), 2);
+
+        assert_eq!(
+            game.play_action(PlayerAction::Bombard(target)),
+            Err(GameError::PowerUpUnavailable)
+        );
+        assert_eq!(game.turns_played(), 2);
+    }
+
+    #[rstest]
+    fn test_bombard_without_power_ups(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+
+        assert_eq!(
+            game.play_action(PlayerAction::Bombard(Cell::bounded(4, 4))),
+            Err(GameError::PowerUpUnavailable)
+        );
+        assert_eq!(game.turns_played(), 0);
+    }
+
+    #[rstest]
+    #[case("A1")]
+    #[case("E5")]
+    #[case("J10")]
+    fn test_scan(fixed_fleet: Fleet, #[case] target: &str) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .power_ups(PowerUps::STANDARD)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        let action = PlayerAction::Scan(Cell::from_str(target).unwrap());
+        let fleet = game.computer().unwrap().fleet().clone();
+
+        game.play_action(action).unwrap();
+        let turn = game.last_turn().unwrap();
+        assert_eq!(
+            turn.scan,
+            Some(ScanReport {
+                scanner: Side::Human,
+                cells: action.cells(),
+                detected: action.cells().iter().any(|cell| fleet.is_occupied(cell)),
+            })
+        );
+        assert!(turn.shots.iter().all(|shot| shot.shooter == Side::Computer));
+        assert_eq!(game.computer().unwrap().fleet().status(), fleet.status());
+        assert_eq!(game.power_ups(Side::Human).scans, 0);
+        assert_eq!(game.play_action(action), Err(GameError::PowerUpUnavailable));
+    }
+
+    #[rstest]
+    fn test_sea_mines(fixed_fleet: Fleet) {
+        let mine = Cell::from_str("J10").unwrap();
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::SeaMines)
+            .mines(&[mine])
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert_eq!(game.rules().name(), "Sea mines");
+        assert_eq!(game.human().unwrap().mines().len(), 2);
+        assert_eq!(game.human().unwrap().mines().cells()[0], mine);
+        assert_eq!(game.computer().unwrap().mines().len(), 2);
+
+        let target = game.computer().unwrap().mines().cells()[0];
+        game.play_turn(&target).unwrap();
+        let shot = game
+            .last_turn()
+            .unwrap()
+            .shots
+            .iter()
+            .find(|shot| shot.shooter == Side::Human)
+            .cloned()
+            .unwrap();
+        assert!(shot.mine);
+        assert_eq!(shot.hit, None);
+        let lost = shot.lost.unwrap();
+        assert!(
+            game.human()
+                .unwrap()
+                .fleet()
+                .as_ref()
+                .iter()
+                .any(|ship| ship.hits().any(|cell| cell == lost))
+        );
+        assert_eq!(game.computer().unwrap().mines().exploded(), [target]);
+    }
+
+    #[rstest]
+    fn test_moving_submarine(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::MovingSubmarine)
+            .human("Human", fixed_fleet.clone())
+            .build()
+            .unwrap();
+        assert_eq!(game.rules().name(), "Moving submarine");
+        assert_eq!(game.relocations(Side::Human), 1);
+
+        let overlapping = PlayerAction::Relocate(Cell::bounded(0, 1), ShipOrientation::Horizontal);
+        let outside = PlayerAction::Relocate(Cell::bounded(8, 0), ShipOrientation::Horizontal);
+        let valid = PlayerAction::Relocate(Cell::bounded(8, 0), ShipOrientation::Vertical);
+        assert_eq!(
+            game.play_action(overlapping),
+            Err(GameError::InvalidRelocation(Cell::bounded(0, 1)))
+        );
+        assert_eq!(
+            game.play_action(outside),
+            Err(GameError::InvalidRelocation(Cell::bounded(8, 0)))
+        );
+        assert_eq!(game.turns_played(), 0);
+
+        game.play_action(valid).unwrap();
+        let turn = game.last_turn().unwrap();
+        assert!(turn.relocated.contains(&Side::Human));
+        assert!(turn.shots.iter().all(|shot| shot.shooter == Side::Computer));
+        let submarine = game
+            .human()
+            .unwrap()
+            .fleet()
+            .get(&ShipKind::Submarine)
+            .unwrap();
+        assert_eq!(submarine.first_cell(), Cell::bounded(8, 0));
+        assert_eq!(game.relocations(Side::Human), 0);
+        assert_eq!(
+            game.play_action(valid),
+            Err(GameError::RelocationUnavailable)
+        );
+
+        // The computer moves its Submarine as soon as it is damaged.
+        let target = game
+            .computer()
+            .unwrap()
+            .fleet()
+            .get(&ShipKind::Submarine)
+            .unwrap()
+            .first_cell();
+        game.play_turn(&target).unwrap();
+        game.play_turn(&Cell::bounded(9, 9)).unwrap();
+        let submarine = game
+            .computer()
+            .unwrap()
+            .fleet()
+            .get(&ShipKind::Submarine)
+            .unwrap();
+        assert_ne!(submarine.first_cell(), target);
+        assert_eq!(submarine.remaining(), submarine.size() - 1);
+        assert_eq!(game.relocations(Side::Computer), 0);
+
+        let mut classic = GameBuilder::new()
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert_eq!(
+            classic.play_action(valid),
+            Err(GameError::RelocationUnavailable)
+        );
+    }
+
+    #[rstest]
+    fn test_no_relocation_once_sunk(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::MovingSubmarine)
+            .human("Human", fixed_fleet.clone())
+            .build()
+            .unwrap();
+        assert_eq!(game.relocations(Side::Human), 1);
+
+        let submarine = fixed_fleet.get(&ShipKind::Submarine).unwrap();
+        let mut opponent = Player::new("Opponent", fixed_fleet.clone());
+        let human = game.players.iter_mut().find(|p| p.is_human()).unwrap();
+        for cell in submarine.occupied_cells().iter() {
+            opponent.attack(human, cell);
+        }
+        assert_eq!(game.relocations(Side::Human), 0);
+        assert_eq!(
+            game.play_action(PlayerAction::Relocate(
+                Cell::bounded(8, 0),
+                ShipOrientation::Vertical
+            )),
+            Err(GameError::RelocationUnavailable)
+        );
+    }
+
+    #[rstest]
+    fn test_salvo(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::Salvo)
+            .computer(Difficulty::Easy)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert_eq!(game.rules().name(), "Salvo");
+        let computer = game.computer().unwrap().fleet().clone();
+        let mut misses = (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .filter(|cell| !computer.is_occupied(cell));
+
+        // A shot for every ship afloat.
+        for shot in 1..=5 {
+            game.play_turn(&misses.next().unwrap()).unwrap();
+            assert_eq!(game.turns_played(), 1);
+            assert_eq!(game.human_shoots_again(), shot < 5);
+        }
+        let turn = game.last_turn().unwrap();
+        for side in [Side::Human, Side::Computer] {
+            let shots = turn.shots.iter().filter(|shot| shot.shooter == side);
+            assert_eq!(shots.count(), 5);
+        }
+
+        game.play_turn(&misses.next().unwrap()).unwrap();
+        assert_eq!(game.turns_played(), 2);
+    }
+
+    #[rstest]
+    fn test_combined_rules(fixed_fleet: Fleet) {
+        let game = GameBuilder::new()
+            .seed(7)
+            .variants(&[Variant::Salvo, Variant::LimitedAmmo, Variant::SeaMines])
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        let rules = game.rules();
+        let human = game.human().unwrap();
+        assert_eq!(rules.name(), "Salvo + Limited ammo + Sea mines");
+        assert_eq!(rules.shots(human), 5);
+        assert_eq!(rules.ammo(), Some(60));
+        assert_eq!(rules.mines(), 2);
+        assert_eq!(rules.fog_width(), 0);
+        assert!(!rules.simultaneous());
+        assert_eq!(human.mines().len(), 2);
+        assert_eq!(human.ammo(), Some(60));
+
+        let classic = CombinedRules::of(&[]);
+        assert_eq!(classic.name(), "Classic");
+        assert_eq!(classic.shots(human), 1);
+        assert_eq!(classic.ammo(), None);
+    }
+
+    // Rules which keep the ships off the first row.
+    #[derive(Debug)]
+    struct OffFirstRow;
+
+    impl Rules for OffFirstRow {
+        fn name(&self) -> &str {
+            "Off the first row"
+        }
+
+        fn allows_fleet(&self, fleet: &Fleet) -> bool {
+            (0..10).all(|x| !fleet.is_occupied(&Cell::bounded(x, 0)))
+        }
+    }
+
+    #[rstest]
+    fn test_fleet_not_allowed(fixed_fleet: Fleet) {
+        let builder = || GameBuilder::new().seed(7).rules(OffFirstRow);
+        assert_eq!(
+            builder().human("Human", fixed_fleet).build().err(),
+            Some(GameError::FleetNotAllowed)
+        );
+
+        let fleet = Fleet::from_code("A2H/A4H/A6H/A8H/A10H").unwrap();
+        let game = builder().human("Human", fleet).build().unwrap();
+        let computer = game.computer().unwrap().fleet();
+        assert!(OffFirstRow.allows_fleet(computer));
+    }
+
+    #[rstest]
+    fn test_streak(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::Streak)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert_eq!(game.rules().name(), "Streak");
+        let computer = game.computer().unwrap().fleet();
+        let hit = computer.as_ref()[0].first_cell();
+        let miss = (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .find(|cell| !computer.is_occupied(cell))
+            .unwrap();
+
+        game.play_turn(&hit).unwrap();
+        assert!(game.human_shoots_again());
+        assert_eq!(game.turns_played(), 1);
+        let shots = game.last_turn().unwrap().shots.len();
+
+        game.play_turn(&miss).unwrap();
+        assert!(!game.human_shoots_again());
+        assert_eq!(game.turns_played(), 1);
+        let turn = game.last_turn().unwrap();
+        let human: Vec<Cell> = turn
+            .shots
+            .iter()
+            .filter(|shot| shot.shooter == Side::Human)
+            .map(|shot| shot.cell)
+            .collect();
+        assert_eq!(human, [hit, miss]);
+        assert!(turn.shots.len() > shots);
+
+        // The computer keeps shooting while it hits.
+        let computer: Vec<&ShotReport> = turn
+            .shots
+            .iter()
+            .filter(|shot| shot.shooter == Side::Computer)
+            .collect();
+        let (last, streak) = computer.split_last().unwrap();
+        assert!(streak.iter().all(|shot| shot.hit.is_some()));
+        assert!(last.hit.is_none());
+
+        game.play_turn(&Cell::bounded(9, 9)).unwrap();
+        assert_eq!(game.turns_played(), 2);
+    }
+
+    #[rstest]
+    fn test_limited_ammo(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::LimitedAmmo)
+            .computer(Difficulty::Easy)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert_eq!(game.rules().name(), "Limited ammo");
+        assert_eq!(game.human().unwrap().ammo(), Some(60));
+
+        // The human player misses every shot, until they run out of shells.
+        let computer = game.computer().unwrap().fleet().clone();
+        let mut misses = (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .filter(|cell| !computer.is_occupied(cell));
+        game.play_turn(&misses.next().unwrap()).unwrap();
+        assert_eq!(game.human().unwrap().ammo(), Some(59));
+        assert_eq!(game.computer().unwrap().ammo(), Some(59));
+
+        let mut result = None;
+        while result.is_none() {
+            result = game.play_turn(&misses.next().unwrap()).unwrap();
+        }
+        assert_eq!(result, Some(false));
+        assert_eq!(game.turns_played(), 60);
+        assert!(game.human().unwrap().is_out_of_ammo());
+        assert!(!game.human().unwrap().has_lost());
+    }
+
+    #[rstest]
+    #[case(Handicap::None, 5, 5)]
+    #[case(Handicap::HumanExtraDestroyer, 6, 5)]
+    #[case(Handicap::ComputerExtraDestroyer, 5, 6)]
+    fn test_extra_destroyer(
+        fixed_fleet: Fleet,
+        #[case] handicap: Handicap,
+        #[case] human_ships: usize,
+        #[case] computer_ships: usize,
+    ) {
+        let game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::SeaMines)
+            .map(Map::built_in("strait").unwrap())
+            .human("Human", fixed_fleet)
+            .handicap_option(handicap)
+            .build()
+            .unwrap();
+        assert_eq!(game.handicap(), handicap);
+
+        let map = game.map().unwrap();
+        for (player, ships) in [
+            (game.human().unwrap(), human_ships),
+            (game.computer().unwrap(), computer_ships),
+        ] {
+            let fleet = player.fleet().as_ref();
+            assert_eq!(fleet.len(), ships);
+            assert!(
+                fleet[4..]
+                    .iter()
+                    .all(|ship| *ship.kind() == ShipKind::Destroyer)
+            );
+            assert!(fleet.iter().all(|ship| map.allows(ship)));
+            assert!(
+                player
+                    .mines()
+                    .cells()
+                    .iter()
+                    .all(|cell| { !player.fleet().is_occupied(cell) })
+            );
+        }
+    }
+
+    #[rstest]
+    #[case(Side::Human, Handicap::HumanShipRevealed)]
+    #[case(Side::Computer, Handicap::ComputerShipRevealed)]
+    fn test_revealed_ship(fixed_fleet: Fleet, #[case] side: Side, #[case] handicap: Handicap) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .computer(Difficulty::Normal)
+            .human("Human", fixed_fleet)
+            .handicap_option(handicap)
+            .build()
+            .unwrap();
+        assert_eq!(game.revealed_ship(side.opponent()), None);
+        let ship = game.revealed_ship(side).unwrap().clone();
+        let owner = match side {
+            Side::Human => game.human().unwrap(),
+            Side::Computer => game.computer().unwrap(),
+        };
+        assert!(owner.fleet().as_ref().contains(&ship));
+
+        // The computer aims at the revealed ship of the human player from its first shot.
+        let computer = game.computer().unwrap().fleet().clone();
+        let miss = (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .find(|cell| !computer.is_occupied(cell))
+            .unwrap();
+        game.play_turn(&miss).unwrap();
+        let shot = game.last_computer_move().unwrap();
+        assert_eq!(ship.occupied_cells().contains(shot), side == Side::Human);
+    }
+
+    // A strategy which fires at the given cells, in order.
+    #[derive(Debug)]
+    struct Scripted(std::vec::IntoIter<Cell>);
+
+    impl Strategy for Scripted {
+        fn next_move(&mut self, _view: &StrategyView) -> Option<Cell> {
+            self.0.next()
+        }
+    }
+
+    #[rstest]
+    #[case::draw(0, None)]
+    #[case::human_first(1, Some(Side::Human))]
+    fn test_simultaneous(
+        fixed_fleet: Fleet,
+        #[case] computer_misses: usize,
+        #[case] winner: Option<Side>,
+    ) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::Simultaneous)
+            .human("Human", fixed_fleet.clone())
+            .build()
+            .unwrap();
+        assert_eq!(game.rules().name(), "Simultaneous fire");
+        let targets = |fleet: &Fleet| -> Vec<Cell> {
+            fleet
+                .as_ref()
+                .iter()
+                .flat_map(|ship| ship.occupied_cells())
+                .collect()
+        };
+        let human = targets(game.computer().unwrap().fleet());
+        let mut computer = vec![Cell::bounded(9, 9); computer_misses];
+        computer.extend(targets(&fixed_fleet));
+        let player = game.players.iter_mut().find(|p| !p.is_human()).unwrap();
+        player.set_strategy(Scripted(computer.into_iter()));
+
+        // Both sides fire in every turn, even when the first shot sinks the last ship.
+        let mut result = None;
+        for cell in &human {
+            assert_eq!(result, None);
+            result = game.play_turn(cell).unwrap();
+            assert_eq!(game.last_turn().unwrap().shots.len(), 2);
+        }
+
+        assert_eq!(result, Some(winner == Some(Side::Human)));
+        assert_eq!(game.turns_played(), 17);
+        assert_eq!(game.winner(), winner);
+        assert!(game.is_over());
+        let turn = game.last_turn().unwrap();
+        assert_eq!(turn.draw, winner.is_none());
+        assert_eq!(turn.winner, winner);
+        match winner {
+            Some(winner) => assert_eq!(game.phase(), GamePhase::Finished { winner }),
+            None => assert_eq!(game.phase(), GamePhase::Drawn),
+        }
+        assert_eq!(
+            game.play_turn(&Cell::bounded(0, 0)),
+            Err(GameError::AlreadyOver)
+        );
+    }
+
+    #[rstest]
+    fn test_fog(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .variant(Variant::Fog)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert_eq!(game.rules().name(), "Fog of war");
+        assert!(game.fog().cells().is_empty());
+
+        let mut fogged = 0;
+        for cell in (0..100).map(|i| Cell::bounded(i % 10, i / 10)).take(20) {
+            game.play_turn(&cell).unwrap();
+            assert_eq!(game.fog().cells().len(), 30);
+            for shot in &game.last_turn().unwrap().shots {
+                assert_eq!(shot.fogged, game.fog().covers(&shot.cell));
+                assert_eq!(shot.fogged, game.fog().is_hidden(shot.shooter, &shot.cell));
+                fogged += usize::from(shot.fogged);
+            }
+        }
+        assert!(fogged > 0);
+
+        let mut classic = GameBuilder::new()
+            .seed(7)
+            .human("Human", Fleet::build(|kind| kind.random()))
+            .build()
+            .unwrap();
+        classic.play_turn(&Cell::bounded(0, 0)).unwrap();
+        assert!(classic.fog().cells().is_empty());
+        assert!(classic.last_turn().unwrap().shots.iter().all(|s| !s.fogged));
+    }
+
+    #[rstest]
+    #[case::classic(Variant::Classic, "J10", GameError::Mines(MineError::TooMany(0)))]
+    #[case::on_ship(
+        Variant::SeaMines,
+        "A1",
+        GameError::Mines(MineError::OnShip(Cell::bounded(0, 0)))
+    )]
+    fn test_invalid_mines(
+        fixed_fleet: Fleet,
+        #[case] variant: Variant,
+        #[case] mine: &str,
+        #[case] error: GameError,
+    ) {
+        let result = GameBuilder::new()
+            .variant(variant)
+            .mines(&[Cell::from_str(mine).unwrap()])
+            .human("Human", fixed_fleet)
+            .build();
+
+        assert_eq!(result.err(), Some(error));
+    }
+
+    #[rstest]
+    fn test_map(fixed_fleet: Fleet) {
+        let map = Map::built_in("strait").unwrap();
+        let island = Cell::from_str("A4").unwrap();
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .map(map.clone())
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+        assert_eq!(game.map(), Some(&map));
+        assert_eq!(map.island_under(game.computer().unwrap().fleet()), None);
+        assert_eq!(
+            *game.human().unwrap().shots_grid().at(&island),
+            CellState::Blocked
+        );
+
+        game.play_turn(&island).unwrap();
+        let shot = game
+            .last_turn()
+            .unwrap()
+            .shots
+            .iter()
+            .find(|shot| shot.shooter == Side::Human);
+        assert_eq!(shot.unwrap().hit, None);
+        assert_eq!(
+            *game.human().unwrap().shots_grid().at(&island),
+            CellState::Blocked
+        );
+        assert!(
+            game.last_computer_move()
+                .is_none_or(|cell| !map.is_island(cell))
+        );
+    }
+
+    #[rstest]
+    fn test_fleet_on_island(
+        #[from(fixed_fleet)]
+        #[with(5)]
+        fleet: Fleet,
+    ) {
+        let result = GameBuilder::new()
+            .map(Map::built_in("strait").unwrap())
+            .human("Human", fleet)
+            .build();
+
+        assert_eq!(
+            result.err(),
+            Some(GameError::OnIsland(Cell::from_str("H7").unwrap()))
+        );
+    }
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(3, 3)]
+    #[case(17, 16)]
+    #[case(u8::MAX, 16)]
+    fn test_builder_with_handicap(fixed_fleet: Fleet, #[case] handicap: u8, #[case] hits: usize) {
+        let game = GameBuilder::new()
+            .handicap(handicap)
+            .human("Human", fixed_fleet)
+            .build()
+            .unwrap();
+
+        let fleet = game.human().unwrap().fleet();
+        assert_eq!(fleet.as_ref().iter().flat_map(Ship::hits).count(), hits);
+        assert!(!fleet.is_sunk());
+        assert_eq!(game.phase(), GamePhase::InProgress);
+    }
+
+    #[rstest]
+    #[case::no_human(GameBuilder::new(), GameError::MissingHuman)]
+    #[case::board_size(
+        GameBuilder::new().board_size(12),
+        GameError::UnsupportedBoardSize(12)
+    )]
+    #[case::fleet(
+        GameBuilder::new().fleet_config(&[ShipKind::Destroyer]),
+        GameError::UnsupportedFleet
+    )]
+    fn test_builder_errors(#[case] builder: GameBuilder, #[case] expected: GameError) {
+        assert_eq!(builder.build().unwrap_err(), expected);
     }
 
     #[rstest]
@@ -232,10 +3336,7 @@ mod tests {
     fn test_get_player(human_player: Player, computer_player: Player) {
         let human_name = human_player.name().to_string();
         let computer_name = computer_player.name().to_string();
-        let game = Game {
-            players: vec![human_player, computer_player],
-            last_computer_move: None,
-        };
+        let game = game_with(vec![human_player, computer_player], GamePhase::InProgress);
         assert_eq!(game.human().unwrap().name(), human_name);
         assert_eq!(game.computer().unwrap().name(), computer_name);
     }
@@ -6,18 +6,27 @@
 //!
 //! The battleship grid is divided into cells, each represented by the `Cell` struct with x and y coordinates.
 //! The `Grid` struct represents the entire 10x10 grid and maintains the state of each cell using the `CellState` enum.
-//! The `CellState` enum has four variants: `Empty`, `Occupied`, `Hit`, and `Sunk`.
+//! The `CellState` enum has five variants: `Empty`, `Occupied`, `Miss`, `Hit` and `Blocked`, the
+//! last one for the islands of a [map](crate::engine::map::Map).
+//!
+//! A cell is written as `B7`, or in another [CoordinateFormat], like `2-7` or `r7c2`.
 //!
 use crate::engine::fleet::Ship;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 use std::cmp::min;
-use std::fmt::{Display, Formatter};
+use std::fmt::{self, Display, Formatter};
+use std::iter;
 use std::str::FromStr;
+use strum::IntoEnumIterator;
+use strum_macros::EnumIter;
 use thiserror::Error;
 
 /// Represents the state of a cell in the battleship grid.
 ///
 /// A cell can be empty, occupied by a ship part or report a shoot result: miss or hit.
 /// A hit occurs when you shoot toward a cell with was occupied, a miss if it wasn't.
+/// On a [map](crate::engine::map::Map), a cell can also be blocked by an island.
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
 pub enum CellState {
     /// The default state of a cell, indicating that it is empty and has not been shot at.
@@ -32,11 +41,15 @@ pub enum CellState {
 
     /// Indicates that the cell has been hit by a shoot and was occupied by a ship.
     Hit,
+
+    /// Indicates that the cell is an island: no ship can occupy it, and a shot at it is wasted.
+    Blocked,
 }
 
-/// Represents a Cell error.
+/// Represents a Cell or Grid error.
 ///
-/// A cell is defined only between (0,0) -> (9,9). Any other coordinate is invalid
+/// A cell is defined only between (0,0) -> (9,9). Any other coordinate is invalid.
+/// A cell of a grid can be shot only once.
 ///
 #[derive(Debug, Error, PartialEq, Eq, Hash)]
 pub enum Error {
@@ -55,6 +68,24 @@ pub enum Error {
     /// The string doesn't represent a valid cell.
     #[error("{0} does not represent a valid cell")]
     InvalidFormat(String),
+
+    /// The cell has already been shot.
+    #[error("{0} has already been shot")]
+    AlreadyShot(Cell),
+
+    /// The string is not a valid grid code.
+    #[error("{0} is not a valid grid code")]
+    InvalidCode(String),
+}
+
+/// The result of a shot on a [Grid].
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+pub enum ShotOutcome {
+    /// The shot hit an empty cell.
+    Miss,
+
+    /// The shot hit a cell occupied by a ship.
+    Hit,
 }
 
 /// Represents a cell in the battleship grid with x and y coordinates.
@@ -82,7 +113,18 @@ pub enum Error {
 /// assert_eq!(format!("{}", cell), "F8");
 /// ```
 ///
-#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, PartialOrd, Ord)]
+/// A cell is serialized in the same format, like `"F8"`.
+///
+#[derive(Debug, PartialEq, Eq, Hash, Copy, Clone, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[cfg_attr(
+    feature = "schema",
+    schemars(
+        description = "A cell of the grid, like `\"F8\"`: its column, from A to J, then its row, from 1 to 10.",
+        extend("pattern" = "^[A-J]([1-9]|10)$")
+    )
+)]
+#[serde(try_from = "String", into = "String")]
 pub struct Cell {
     /// The x coordinate (0-9)
     x: u8,
@@ -141,8 +183,13 @@ impl Cell {
 
     /// Return a cell using random coordinates.
     pub fn random() -> Self {
-        let x = rand::random::<u8>() % Self::MAX_X;
-        let y = rand::random::<u8>() % Self::MAX_Y;
+        Self::random_with(&mut rand::rng())
+    }
+
+    /// Return a cell using random coordinates taken from the given generator.
+    pub fn random_with<R: Rng + ?Sized>(rng: &mut R) -> Self {
+        let x = rng.random_range(0..=Self::MAX_X);
+        let y = rng.random_range(0..=Self::MAX_Y);
 
         Cell { x, y }
     }
@@ -157,6 +204,100 @@ impl Cell {
         self.y
     }
 
+    /// Returns the cell at the given distance from this one, if it is on the board.
+    ///
+    /// Unlike the `move_*` methods, this method never wraps around: if the resulting coordinates
+    /// are out of the board, `None` is returned.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let cell = Cell::bounded(5, 5);
+    /// assert_eq!(cell.offset(2, -1), Some(Cell::bounded(7, 4)));
+    /// assert_eq!(cell.offset(-6, 0), None);
+    /// ```
+    pub fn offset(&self, dx: i8, dy: i8) -> Option<Cell> {
+        let x = self.x.checked_add_signed(dx)?;
+        let y = self.y.checked_add_signed(dy)?;
+
+        Cell::new(x, y).ok()
+    }
+
+    /// Returns the cells next to this one on the same row or column.
+    ///
+    /// Cells are returned in this order: right, left, down and up. Only the cells on the board
+    /// are returned, so a cell on the edge has 3 neighbors, and a cell in a corner only 2.
+    pub fn neighbors(&self) -> Vec<Cell> {
+        [(1, 0), (-1, 0), (0, 1), (0, -1)]
+            .iter()
+            .filter_map(|(dx, dy)| self.offset(*dx, *dy))
+            .collect()
+    }
+
+    /// Returns the cells from this one to the other, both included, if they are aligned.
+    ///
+    /// The cells are aligned when they share the same row or the same column; otherwise `None`
+    /// is returned. The cells are ordered from this cell to the other one.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let line = Cell::bounded(3, 2).line_to(&Cell::bounded(1, 2)).unwrap();
+    /// assert_eq!(line, vec![Cell::bounded(3, 2), Cell::bounded(2, 2), Cell::bounded(1, 2)]);
+    /// assert!(Cell::bounded(3, 2).line_to(&Cell::bounded(1, 1)).is_none());
+    /// ```
+    pub fn line_to(&self, other: &Cell) -> Option<Vec<Cell>> {
+        if !self.is_aligned_with(other) {
+            return None;
+        }
+
+        let dx = other.x.cmp(&self.x) as i8;
+        let dy = other.y.cmp(&self.y) as i8;
+        let mut cells = vec![*self];
+        let mut current = *self;
+        while current != *other {
+            current = current.offset(dx, dy)?;
+            cells.push(current);
+        }
+
+        Some(cells)
+    }
+
+    /// Returns the cells strictly between the two given ones, from the first to the second, if
+    /// they are aligned.
+    ///
+    /// Like [Cell::line_to], but the two cells are left out: the same or adjacent cells have no
+    /// cell between them.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let between = Cell::between(&Cell::bounded(1, 4), &Cell::bounded(1, 1)).unwrap();
+    /// assert_eq!(between, vec![Cell::bounded(1, 3), Cell::bounded(1, 2)]);
+    /// assert!(Cell::between(&Cell::bounded(1, 4), &Cell::bounded(2, 2)).is_none());
+    /// ```
+    pub fn between(a: &Cell, b: &Cell) -> Option<Vec<Cell>> {
+        let mut line = a.line_to(b)?;
+        line.pop();
+        if !line.is_empty() {
+            line.remove(0);
+        }
+
+        Some(line)
+    }
+
+    /// Returns the number of steps along the rows and the columns from this cell to the other.
+    ///
+    /// # Examples
+    /// ```rust
+    /// assert_eq!(Cell::bounded(1, 2).manhattan_distance(&Cell::bounded(4, 0)), 5);
+    /// ```
+    pub fn manhattan_distance(&self, other: &Cell) -> u8 {
+        self.x.abs_diff(other.x) + self.y.abs_diff(other.y)
+    }
+
+    /// Returns `true` if this cell is on the same row or on the same column as the other.
+    pub fn is_aligned_with(&self, other: &Cell) -> bool {
+        self.x == other.x || self.y == other.y
+    }
+
     /// Moves this cell to the left.
     ///
     /// This method automatically wraps around if the cell is at the leftmost position.
@@ -192,6 +333,38 @@ impl Cell {
             self.y = self.y.saturating_add(1);
         }
     }
+
+    /// Moves this cell to the left, unless it is on the leftmost column.
+    ///
+    /// Returns `true` if the cell has been moved.
+    pub fn try_move_left(&mut self) -> bool {
+        self.try_move(-1, 0)
+    }
+
+    /// Moves this cell to the right, unless it is on the rightmost column.
+    ///
+    /// Returns `true` if the cell has been moved.
+    pub fn try_move_right(&mut self) -> bool {
+        self.try_move(1, 0)
+    }
+
+    /// Moves this cell up, unless it is on the top row.
+    ///
+    /// Returns `true` if the cell has been moved.
+    pub fn try_move_up(&mut self) -> bool {
+        self.try_move(0, -1)
+    }
+
+    /// Moves this cell down, unless it is on the bottom row.
+    ///
+    /// Returns `true` if the cell has been moved.
+    pub fn try_move_down(&mut self) -> bool {
+        self.try_move(0, 1)
+    }
+
+    fn try_move(&mut self, dx: i8, dy: i8) -> bool {
+        self.offset(dx, dy).map(|cell| *self = cell).is_some()
+    }
 }
 
 impl FromStr for Cell {
@@ -206,6 +379,9 @@ impl FromStr for Cell {
     /// the number with leading zeros, e.g., "A01" is valid and equivalent to "A1".
     /// Anyway the parsed coordinates must be valid, so only values between 1 and 10 (inclusive) will be accepted.
     ///
+    /// A cell can also be written in any other [CoordinateFormat], like "2-7" or "r7c2",
+    /// the same as "B7".
+    ///
     /// The string must be well-formed; otherwise, an error is returned.
     /// The error is always and [`Error::InvalidFormat`], reporting which was the original string submitted.
     ///
@@ -232,23 +408,9 @@ impl FromStr for Cell {
     /// ```
     ///
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut chars = s.chars();
-        let x_char = chars.next().ok_or(Error::InvalidFormat(s.to_string()))?;
-        let x = x_char.to_ascii_uppercase() as u8;
-        let y = chars
-            .as_str()
-            .parse::<u8>()
-            .map_err(|_| Error::InvalidFormat(s.to_string()))?;
-
-        if !x_char.is_ascii_alphabetic()
-            || (x - b'A') > Self::MAX_Y
-            || y == 0
-            || (y - 1) > Self::MAX_Y
-        {
-            Err(Error::InvalidFormat(s.to_string()))
-        } else {
-            Ok(Cell::bounded(x - b'A', y - 1))
-        }
+        CoordinateFormat::iter()
+            .find_map(|format| format.parse(s).ok())
+            .ok_or_else(|| Error::InvalidFormat(s.to_string()))
     }
 }
 
@@ -273,8 +435,101 @@ impl Display for Cell {
     /// ```
     ///
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let x = (self.x + b'A') as char;
-        write!(f, "{}{}", x, self.y + 1)
+        write!(f, "{}{}", char::from(b'A' + self.x), self.y + 1)
+    }
+}
+
+/// The notations of the coordinates of a cell.
+///
+/// A cell is written in the notation chosen by the player, but it is parsed from any of them.
+#[derive(
+    Debug, PartialEq, Eq, Clone, Copy, Default, strum::Display, EnumIter, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CoordinateFormat {
+    /// The letter of the column, then the number of the row, like `B7`.
+    #[default]
+    #[strum(serialize = "B7")]
+    Letter,
+
+    /// The numbers of the column and of the row, separated by a dash, like `2-7`.
+    #[strum(serialize = "2-7")]
+    Numeric,
+
+    /// The numbers of the row and of the column, after an `r` and a `c`, like `r7c2`.
+    #[strum(serialize = "r7c2")]
+    RowColumn,
+}
+
+impl CoordinateFormat {
+    /// Writes the cell in this notation.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let cell = Cell::bounded(1, 6);
+    /// assert_eq!(CoordinateFormat::Letter.format(&cell), "B7");
+    /// assert_eq!(CoordinateFormat::Numeric.format(&cell), "2-7");
+    /// assert_eq!(CoordinateFormat::RowColumn.format(&cell), "r7c2");
+    /// ```
+    pub fn format(&self, cell: &Cell) -> String {
+        let (column, row) = (u16::from(cell.x) + 1, u16::from(cell.y) + 1);
+        match self {
+            CoordinateFormat::Letter => cell.to_string(),
+            CoordinateFormat::Numeric => format!("{column}-{row}"),
+            CoordinateFormat::RowColumn => format!("r{row}c{column}"),
+        }
+    }
+
+    /// Parses a cell written in this notation, in any case.
+    ///
+    /// An [Error::InvalidFormat] is returned if the string is not in this notation, or if the
+    /// cell is not on the board.
+    pub fn parse(&self, s: &str) -> Result<Cell, Error> {
+        let invalid = || Error::InvalidFormat(s.to_string());
+        let (x, y) = match self {
+            CoordinateFormat::Letter => {
+                let mut chars = s.chars();
+                let column = chars.next().filter(char::is_ascii_alphabetic);
+                let column = column.map(|c| c.to_ascii_uppercase() as u8 - b'A');
+                (column, number(chars.as_str()))
+            }
+            CoordinateFormat::Numeric => {
+                let (column, row) = s.split_once('-').ok_or_else(invalid)?;
+                (number(column).and_then(|x| x.checked_sub(1)), number(row))
+            }
+            CoordinateFormat::RowColumn => {
+                let rest = s.strip_prefix(['r', 'R']).ok_or_else(invalid)?;
+                let (row, column) = rest.split_once(['c', 'C']).ok_or_else(invalid)?;
+                (number(column).and_then(|x| x.checked_sub(1)), number(row))
+            }
+        };
+        let (Some(x), Some(y)) = (x, y.and_then(|y| y.checked_sub(1))) else {
+            return Err(invalid());
+        };
+
+        Cell::new(x, y).map_err(|_| invalid())
+    }
+}
+
+// Parses a number made of digits only, with no sign.
+fn number(s: &str) -> Option<u8> {
+    match !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit()) {
+        true => s.parse().ok(),
+        false => None,
+    }
+}
+
+impl TryFrom<String> for Cell {
+    type Error = Error;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Cell::from_str(&value)
+    }
+}
+
+impl From<Cell> for String {
+    fn from(cell: Cell) -> Self {
+        cell.to_string()
     }
 }
 
@@ -282,15 +537,23 @@ impl Display for Cell {
 ///
 /// The grid is a 10x10 matrix of cells, where each cell can be in one of the states defined by the `CellState` enum.
 /// The default state of the grid is empty, with all cells set to [`CellState::Empty`].
-/// The grid just record the state of each cell; it doesn't manage any behavior related to ships or shooting.
-/// Therefore, when you set a cell state, it doesn't check if the transition is valid or not (e.g. from empty to hit).
+/// The grid just record the state of each cell; it doesn't manage any behavior related to ships.
+/// When you set a cell state with [Grid::mark], it doesn't check if the transition is valid or not
+/// (e.g. from empty to hit). Use [Grid::shoot] to only allow the transitions of a real shot.
+///
+/// The grid keeps a bitboard for every state, where the bit `10 * y + x` stands for the cell
+/// `(x, y)`: [Grid::bits] returns it, so that many cells can be checked at once.
 ///
 #[derive(Debug, PartialEq, Eq, Hash, Clone, Default)]
 pub struct Grid {
-    cells: [[CellState; 10]; 10],
+    // The bitboards of the occupied, missed, hit and blocked cells: an empty cell is in none.
+    boards: [u128; 4],
 }
 
 impl Grid {
+    // The bitboard with a bit set for every cell of the grid.
+    const FULL: u128 = (1 << 100) - 1;
+
     /// Build a new grid with only empty or occupied cells.
     ///
     /// The occupied cells match the position and the size of every ship in the slice passed as argument
@@ -313,19 +576,98 @@ impl Grid {
     ///
     /// Return `true` if all the cells in the grid are marked as [CellState::Empty], `false` otherwise.
     pub fn is_empty(&self) -> bool {
-        self.cells
-            .iter()
-            .all(|row| row.iter().all(|cell| cell == &CellState::Empty))
+        self.boards.iter().all(|board| *board == 0)
     }
 
     /// The state of the passed cell
     pub fn at(&self, cell: &Cell) -> &CellState {
-        &self.cells[cell.y as usize][cell.x as usize]
+        let bit = bit(cell);
+        match self.boards.iter().position(|board| board & bit != 0) {
+            None => &CellState::Empty,
+            Some(0) => &CellState::Occupied,
+            Some(1) => &CellState::Miss,
+            Some(2) => &CellState::Hit,
+            Some(_) => &CellState::Blocked,
+        }
     }
 
     /// Overwrite the chosen cell with the passed state, it doesn't mind which was its previous state.
     pub fn mark(&mut self, cell: &Cell, state: CellState) {
-        self.cells[cell.y as usize][cell.x as usize] = state;
+        let bit = bit(cell);
+        for board in self.boards.iter_mut() {
+            *board &= !bit;
+        }
+        if let Some(index) = board_of(&state) {
+            self.boards[index] |= bit;
+        }
+    }
+
+    /// Returns the bitboard of the cells in the given state: the bit `10 * y + x` is set when
+    /// the cell `(x, y)` is in that state.
+    pub fn bits(&self, state: &CellState) -> u128 {
+        match board_of(state) {
+            Some(index) => self.boards[index],
+            None => !self.boards.iter().fold(0, |all, board| all | board) & Self::FULL,
+        }
+    }
+
+    /// Returns how many cells are in the given state.
+    pub fn count(&self, state: &CellState) -> u32 {
+        self.bits(state).count_ones()
+    }
+
+    /// Returns the cells in the given state, row by row.
+    pub fn cells(&self, state: &CellState) -> impl Iterator<Item = Cell> + use<> {
+        let mut bits = self.bits(state);
+        iter::from_fn(move || {
+            let bit = (bits != 0).then(|| bits.trailing_zeros() as u8)?;
+            bits &= bits - 1;
+            Some(Cell::bounded(bit % 10, bit / 10))
+        })
+    }
+
+    /// Returns the share of the shots on the grid which have hit a ship, from 0 to 1, or `None`
+    /// without shots.
+    pub fn hit_ratio(&self) -> Option<f64> {
+        let hits = self.count(&CellState::Hit);
+        let shots = hits + self.count(&CellState::Miss);
+
+        (shots > 0).then(|| f64::from(hits) / f64::from(shots))
+    }
+
+    /// Returns the smallest rectangle holding all the cells in the given state, as its top left
+    /// and bottom right cells, or `None` when no cell is in that state.
+    pub fn bounding_box(&self, state: &CellState) -> Option<(Cell, Cell)> {
+        let bits = self.bits(state);
+        let row = |y: u8| ((bits >> (10 * y)) & 0x3ff) as u16;
+        let mut rows = (0..10).filter(|y| row(*y) != 0);
+        let top = rows.next()?;
+        let bottom = rows.next_back().unwrap_or(top);
+        let columns = (0..10).fold(0, |columns, y| columns | row(y));
+        let (left, right) = (columns.trailing_zeros(), 15 - columns.leading_zeros());
+
+        Some((
+            Cell::bounded(left as u8, top),
+            Cell::bounded(right as u8, bottom),
+        ))
+    }
+
+    /// Shoot the chosen cell and record the result.
+    ///
+    /// An empty cell becomes [CellState::Miss], and an occupied one becomes [CellState::Hit].
+    /// A cell can be shot only once: shooting again a missed or hit cell returns
+    /// [Error::AlreadyShot] and leaves the grid unchanged. A shot at a blocked cell is always a
+    /// miss, which leaves the cell blocked.
+    pub fn shoot(&mut self, cell: &Cell) -> Result<ShotOutcome, Error> {
+        let (state, outcome) = match self.at(cell) {
+            CellState::Empty => (CellState::Miss, ShotOutcome::Miss),
+            CellState::Occupied => (CellState::Hit, ShotOutcome::Hit),
+            CellState::Blocked => return Ok(ShotOutcome::Miss),
+            CellState::Miss | CellState::Hit => return Err(Error::AlreadyShot(*cell)),
+        };
+
+        self.mark(cell, state);
+        Ok(outcome)
     }
 
     /// Add a ship to the grid.
@@ -334,6 +676,149 @@ impl Grid {
             self.mark(cell, CellState::Occupied);
         }
     }
+
+    /// Encodes the grid in a compact string, which can be decoded by [Grid::from_code].
+    ///
+    /// The code lists the rows from the top one, separated by a `/`. In every row, a cell is
+    /// represented by `#` if occupied, `O` if missed, `X` if hit and `^` if blocked, like in the
+    /// grid table, while a run of empty cells is represented by its length.
+    ///
+    /// # Examples
+    /// ```rust
+    /// let mut grid = Grid::default();
+    /// grid.mark(&Cell::bounded(0, 0), CellState::Occupied);
+    /// grid.mark(&Cell::bounded(3, 1), CellState::Miss);
+    /// assert_eq!(grid.to_code(), "#9/3O6/10/10/10/10/10/10/10/10");
+    /// ```
+    pub fn to_code(&self) -> String {
+        let rows: Vec<String> = (0..10)
+            .map(|y| {
+                let mut code = String::new();
+                let mut empty = 0;
+                for cell in (0..10).map(|x| self.at(&Cell::bounded(x, y))) {
+                    let symbol = match cell {
+                        CellState::Empty => {
+                            empty += 1;
+                            continue;
+                        }
+                        CellState::Occupied => '#',
+                        CellState::Miss => 'O',
+                        CellState::Hit => 'X',
+                        CellState::Blocked => '^',
+                    };
+
+                    if empty > 0 {
+                        code.push_str(&empty.to_string());
+                        empty = 0;
+                    }
+                    code.push(symbol);
+                }
+
+                if empty > 0 {
+                    code.push_str(&empty.to_string());
+                }
+                code
+            })
+            .collect();
+
+        rows.join("/")
+    }
+
+    /// Writes the grid in the table of its [Display] to the given writer, without allocating.
+    pub fn render_to<W: fmt::Write>(&self, out: &mut W) -> fmt::Result {
+        self.render_with(out, |_, state| match state {
+            CellState::Empty => ' ',
+            CellState::Occupied => '#',
+            CellState::Miss => 'O',
+            CellState::Hit => 'X',
+            CellState::Blocked => '^',
+        })
+    }
+
+    /// Writes the grid in the table of its [Display] to the given writer, every cell as the
+    /// symbol returned for it by the given function.
+    pub fn render_with<W, S, F>(&self, out: &mut W, mut symbol: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        S: Display,
+        F: FnMut(&Cell, &CellState) -> S,
+    {
+        out.write_str("   A B C D E F G H I J \n")?;
+        for y in 0..10 {
+            write!(out, "{:02} ", y + 1)?;
+            self.render_row_to(out, y, &mut symbol)?;
+            out.write_str(" \n")?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the cells of the given row to the writer, separated by a space, each one as the
+    /// symbol returned for it by the given function.
+    pub fn render_row_to<W, S, F>(&self, out: &mut W, y: u8, mut symbol: F) -> fmt::Result
+    where
+        W: fmt::Write,
+        S: Display,
+        F: FnMut(&Cell, &CellState) -> S,
+    {
+        for x in 0..10 {
+            if x > 0 {
+                out.write_char(' ')?;
+            }
+            let cell = Cell::bounded(x, y);
+            write!(out, "{}", symbol(&cell, self.at(&cell)))?;
+        }
+
+        Ok(())
+    }
+
+    /// Decodes a grid from a string made by [Grid::to_code].
+    ///
+    /// The code must describe exactly 10 rows of 10 cells each, otherwise
+    /// [Error::InvalidCode] is returned.
+    pub fn from_code(code: &str) -> Result<Self, Error> {
+        let invalid = || Error::InvalidCode(code.to_string());
+        let rows: Vec<&str> = code.split('/').collect();
+        if rows.len() != 10 {
+            return Err(invalid());
+        }
+
+        let mut grid = Grid::default();
+        for (y, row) in rows.iter().enumerate() {
+            let mut x = 0usize;
+            let mut chars = row.chars().peekable();
+            while let Some(c) = chars.next() {
+                let state = match c {
+                    '#' => CellState::Occupied,
+                    'O' => CellState::Miss,
+                    'X' => CellState::Hit,
+                    '^' => CellState::Blocked,
+                    '1'..='9' => {
+                        let mut run = c.to_digit(10).unwrap() as usize;
+                        while let Some(digit) = chars.peek().and_then(|c| c.to_digit(10)) {
+                            run = run * 10 + digit as usize;
+                            chars.next();
+                        }
+                        x += run;
+                        continue;
+                    }
+                    _ => return Err(invalid()),
+                };
+
+                if x >= 10 {
+                    return Err(invalid());
+                }
+                grid.mark(&Cell::bounded(x as u8, y as u8), state);
+                x += 1;
+            }
+
+            if x != 10 {
+                return Err(invalid());
+            }
+        }
+
+        Ok(grid)
+    }
 }
 
 impl Display for Grid {
@@ -351,23 +836,24 @@ impl Display for Grid {
     ///  10
     /// ```
     ///
-    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        let mut output = "   A B C D E F G H I J \n".to_string();
-        for (y, row) in self.cells.iter().enumerate() {
-            output = format!("{output}{:02} ", y + 1);
-            for cell in row.iter() {
-                output.push(match cell {
-                    CellState::Empty => ' ',
-                    CellState::Occupied => '#',
-                    CellState::Miss => 'O',
-                    CellState::Hit => 'X',
-                });
-                output.push(' ');
-            }
-            output.push('\n');
-        }
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.render_to(f)
+    }
+}
+
+// Returns the bit of the cell in a bitboard.
+fn bit(cell: &Cell) -> u128 {
+    1 << (10 * cell.y as u32 + cell.x as u32)
+}
 
-        write!(f, "{}", output)
+// Returns the index of the bitboard of the state, or `None` for the empty cells.
+fn board_of(state: &CellState) -> Option<usize> {
+    match state {
+        CellState::Empty => None,
+        CellState::Occupied => Some(0),
+        CellState::Miss => Some(1),
+        CellState::Hit => Some(2),
+        CellState::Blocked => Some(3),
     }
 }
 
@@ -431,6 +917,10 @@ mod tests {
     #[case("A0")]
     #[case("  A5  ")]
     #[case("A15")]
+    #[case("AA1")]
+    #[case("A+5")]
+    #[case("")]
+    #[case("7")]
     fn test_cell_from_str_errors(#[case] s: &str) {
         assert!(matches!(
             Cell::from_str(s),
@@ -446,6 +936,44 @@ mod tests {
         assert_eq!(format!("{}", cell), expected);
     }
 
+    #[rstest]
+    fn test_cell_round_trip() {
+        for cell in (0..100).map(|i| Cell::bounded(i % 10, i / 10)) {
+            assert_eq!(Cell::from_str(&cell.to_string()), Ok(cell));
+            for format in CoordinateFormat::iter() {
+                let written = format.format(&cell);
+                assert_eq!(format.parse(&written), Ok(cell));
+                assert_eq!(Cell::from_str(&written), Ok(cell));
+            }
+        }
+    }
+
+    #[rstest]
+    #[case(CoordinateFormat::Letter, "B7")]
+    #[case(CoordinateFormat::Numeric, "2-7")]
+    #[case(CoordinateFormat::RowColumn, "r7c2")]
+    fn test_coordinate_format(#[case] format: CoordinateFormat, #[case] written: &str) {
+        let cell = Cell::bounded(1, 6);
+        assert_eq!(format.format(&cell), written);
+        assert_eq!(format.parse(written), Ok(cell));
+        assert_eq!(format.parse(&written.to_uppercase()), Ok(cell));
+        assert_eq!(format.to_string(), written);
+    }
+
+    #[rstest]
+    #[case(CoordinateFormat::Letter, "2-7")]
+    #[case(CoordinateFormat::Numeric, "B7")]
+    #[case(CoordinateFormat::Numeric, "2-")]
+    #[case(CoordinateFormat::Numeric, "2,7")]
+    #[case(CoordinateFormat::Numeric, "11-7")]
+    #[case(CoordinateFormat::RowColumn, "7c2")]
+    #[case(CoordinateFormat::RowColumn, "r7-2")]
+    #[case(CoordinateFormat::RowColumn, "r0c2")]
+    #[case(CoordinateFormat::RowColumn, "r7c11")]
+    fn test_coordinate_format_errors(#[case] format: CoordinateFormat, #[case] s: &str) {
+        assert_eq!(format.parse(s), Err(Error::InvalidFormat(s.to_string())));
+    }
+
     #[rstest]
     #[case(Cell::bounded(5, 5), Cell::bounded(4, 5))]
     #[case(Cell::bounded(0, 5), Cell::bounded(9, 5))]
@@ -478,6 +1006,129 @@ mod tests {
         assert_eq!(cell, expected);
     }
 
+    #[rstest]
+    #[case(Cell::bounded(5, 5), Cell::try_move_left, Cell::bounded(4, 5), true)]
+    #[case(Cell::bounded(0, 5), Cell::try_move_left, Cell::bounded(0, 5), false)]
+    #[case(Cell::bounded(5, 5), Cell::try_move_right, Cell::bounded(6, 5), true)]
+    #[case(Cell::bounded(9, 5), Cell::try_move_right, Cell::bounded(9, 5), false)]
+    #[case(Cell::bounded(5, 5), Cell::try_move_up, Cell::bounded(5, 4), true)]
+    #[case(Cell::bounded(5, 0), Cell::try_move_up, Cell::bounded(5, 0), false)]
+    #[case(Cell::bounded(5, 5), Cell::try_move_down, Cell::bounded(5, 6), true)]
+    #[case(Cell::bounded(5, 9), Cell::try_move_down, Cell::bounded(5, 9), false)]
+    fn test_try_move(
+        #[case] mut cell: Cell,
+        #[case] try_move: fn(&mut Cell) -> bool,
+        #[case] expected: Cell,
+        #[case] moved: bool,
+    ) {
+        assert_eq!(try_move(&mut cell), moved);
+        assert_eq!(cell, expected);
+    }
+
+    #[rstest]
+    #[case(Cell::bounded(5, 5), 0, 0, Some(Cell::bounded(5, 5)))]
+    #[case(Cell::bounded(5, 5), 2, -1, Some(Cell::bounded(7, 4)))]
+    #[case(Cell::bounded(5, 5), 4, 4, Some(Cell::bounded(9, 9)))]
+    #[case(Cell::bounded(5, 5), -5, -5, Some(Cell::bounded(0, 0)))]
+    #[case(Cell::bounded(5, 5), -6, 0, None)]
+    #[case(Cell::bounded(5, 5), 0, 5, None)]
+    #[case(Cell::bounded(0, 9), -1, 0, None)]
+    #[case(Cell::bounded(0, 9), 0, 1, None)]
+    #[case(Cell::bounded(9, 0), 127, -128, None)]
+    fn test_offset(
+        #[case] cell: Cell,
+        #[case] dx: i8,
+        #[case] dy: i8,
+        #[case] expected: Option<Cell>,
+    ) {
+        assert_eq!(cell.offset(dx, dy), expected);
+    }
+
+    #[rstest]
+    #[case(Cell::bounded(5, 5), vec![Cell::bounded(6, 5), Cell::bounded(4, 5), Cell::bounded(5, 6), Cell::bounded(5, 4)])]
+    #[case(Cell::bounded(0, 5), vec![Cell::bounded(1, 5), Cell::bounded(0, 6), Cell::bounded(0, 4)])]
+    #[case(Cell::bounded(9, 9), vec![Cell::bounded(8, 9), Cell::bounded(9, 8)])]
+    #[case(Cell::bounded(0, 0), vec![Cell::bounded(1, 0), Cell::bounded(0, 1)])]
+    fn test_neighbors(#[case] cell: Cell, #[case] expected: Vec<Cell>) {
+        assert_eq!(cell.neighbors(), expected);
+    }
+
+    #[rstest]
+    #[case(Cell::bounded(3, 3), Cell::bounded(3, 3), Some(vec![Cell::bounded(3, 3)]))]
+    #[case(Cell::bounded(1, 2), Cell::bounded(3, 2), Some(vec![Cell::bounded(1, 2), Cell::bounded(2, 2), Cell::bounded(3, 2)]))]
+    #[case(Cell::bounded(3, 2), Cell::bounded(1, 2), Some(vec![Cell::bounded(3, 2), Cell::bounded(2, 2), Cell::bounded(1, 2)]))]
+    #[case(Cell::bounded(4, 9), Cell::bounded(4, 7), Some(vec![Cell::bounded(4, 9), Cell::bounded(4, 8), Cell::bounded(4, 7)]))]
+    #[case(Cell::bounded(3, 2), Cell::bounded(1, 1), None)]
+    fn test_line_to(#[case] from: Cell, #[case] to: Cell, #[case] expected: Option<Vec<Cell>>) {
+        assert_eq!(from.line_to(&to), expected);
+    }
+
+    #[rstest]
+    #[case(Cell::bounded(3, 3), Cell::bounded(3, 3), Some(vec![]))]
+    #[case(Cell::bounded(3, 3), Cell::bounded(3, 4), Some(vec![]))]
+    #[case(Cell::bounded(0, 5), Cell::bounded(3, 5), Some(vec![Cell::bounded(1, 5), Cell::bounded(2, 5)]))]
+    #[case(Cell::bounded(6, 9), Cell::bounded(6, 7), Some(vec![Cell::bounded(6, 8)]))]
+    #[case(Cell::bounded(0, 0), Cell::bounded(9, 9), None)]
+    fn test_between(#[case] a: Cell, #[case] b: Cell, #[case] expected: Option<Vec<Cell>>) {
+        assert_eq!(Cell::between(&a, &b), expected);
+    }
+
+    // Every pair of cells of the board.
+    fn pairs() -> impl Iterator<Item = (Cell, Cell)> {
+        let cells = || (0..100).map(|i| Cell::bounded(i % 10, i / 10));
+        cells().flat_map(move |a| cells().map(move |b| (a, b)))
+    }
+
+    #[rstest]
+    fn test_manhattan_distance_properties() {
+        for (a, b) in pairs() {
+            let distance = a.manhattan_distance(&b);
+            assert_eq!(distance, b.manhattan_distance(&a));
+            assert_eq!(distance == 0, a == b);
+            assert!(distance <= 18);
+            for c in [
+                Cell::bounded(0, 0),
+                Cell::bounded(4, 7),
+                Cell::bounded(9, 2),
+            ] {
+                assert!(distance <= a.manhattan_distance(&c) + c.manhattan_distance(&b));
+            }
+            if a.neighbors().contains(&b) {
+                assert_eq!(distance, 1);
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_alignment_properties() {
+        for (a, b) in pairs() {
+            let aligned = a.is_aligned_with(&b);
+            assert_eq!(aligned, b.is_aligned_with(&a));
+            assert_eq!(aligned, a.line_to(&b).is_some());
+            assert!(a.is_aligned_with(&a));
+
+            let Some(between) = Cell::between(&a, &b) else {
+                assert!(!aligned);
+                continue;
+            };
+            assert!(aligned);
+            assert_eq!(
+                between.len(),
+                a.manhattan_distance(&b).saturating_sub(1) as usize
+            );
+            for cell in &between {
+                assert!(cell.is_aligned_with(&a) && cell.is_aligned_with(&b));
+                assert_eq!(
+                    a.manhattan_distance(cell) + cell.manhattan_distance(&b),
+                    a.manhattan_distance(&b)
+                );
+            }
+            let mut reversed = Cell::between(&b, &a).unwrap();
+            reversed.reverse();
+            assert_eq!(between, reversed);
+        }
+    }
+
     #[rstest]
     fn test_new_grid_is_always_empty() {
         assert!(Grid::default().is_empty());
@@ -513,6 +1164,163 @@ mod tests {
         assert_eq!(*grid.at(&Cell::bounded(3, 3)), CellState::Miss);
     }
 
+    #[rstest]
+    fn test_grid_bits() {
+        let mut grid = Grid::default();
+        assert_eq!(grid.bits(&CellState::Empty), (1 << 100) - 1);
+
+        grid.mark(&Cell::bounded(3, 3), CellState::Occupied);
+        grid.mark(&Cell::bounded(9, 9), CellState::Hit);
+        grid.mark(&Cell::bounded(3, 3), CellState::Miss);
+        assert_eq!(grid.bits(&CellState::Occupied), 0);
+        assert_eq!(grid.bits(&CellState::Miss), 1 << 33);
+        assert_eq!(grid.bits(&CellState::Hit), 1 << 99);
+        assert_eq!(grid.bits(&CellState::Empty).count_ones(), 98);
+        assert_eq!(
+            grid.cells(&CellState::Miss).collect::<Vec<_>>(),
+            [Cell::bounded(3, 3)]
+        );
+        assert_eq!(grid.cells(&CellState::Empty).count(), 98);
+
+        grid.mark(&Cell::bounded(9, 9), CellState::Empty);
+        assert_eq!(grid.bits(&CellState::Hit), 0);
+        assert_eq!(*grid.at(&Cell::bounded(9, 9)), CellState::Empty);
+    }
+
+    #[rstest]
+    #[case(CellState::Empty, Ok(ShotOutcome::Miss), CellState::Miss)]
+    #[case(CellState::Occupied, Ok(ShotOutcome::Hit), CellState::Hit)]
+    #[case(
+        CellState::Miss,
+        Err(Error::AlreadyShot(Cell::bounded(4, 2))),
+        CellState::Miss
+    )]
+    #[case(
+        CellState::Hit,
+        Err(Error::AlreadyShot(Cell::bounded(4, 2))),
+        CellState::Hit
+    )]
+    #[case(CellState::Blocked, Ok(ShotOutcome::Miss), CellState::Blocked)]
+    fn test_grid_shoot(
+        #[case] before: CellState,
+        #[case] expected: Result<ShotOutcome, Error>,
+        #[case] after: CellState,
+    ) {
+        let cell = Cell::bounded(4, 2);
+        let mut grid = Grid::default();
+        grid.mark(&cell, before);
+
+        assert_eq!(grid.shoot(&cell), expected);
+        assert_eq!(*grid.at(&cell), after);
+    }
+
+    #[rstest]
+    fn test_grid_shoot_ships() {
+        let ships = [ShipKind::Destroyer
+            .ship(Cell::bounded(0, 0), ShipOrientation::Horizontal)
+            .unwrap()];
+        let mut grid = Grid::from_ships(ships.as_slice());
+
+        assert_eq!(grid.shoot(&Cell::bounded(0, 0)), Ok(ShotOutcome::Hit));
+        assert_eq!(grid.shoot(&Cell::bounded(0, 1)), Ok(ShotOutcome::Miss));
+        assert_eq!(
+            grid.shoot(&Cell::bounded(0, 0)),
+            Err(Error::AlreadyShot(Cell::bounded(0, 0)))
+        );
+        assert_eq!(*grid.at(&Cell::bounded(1, 0)), CellState::Occupied);
+    }
+
+    #[rstest]
+    fn test_grid_to_code() {
+        let mut grid = Grid::default();
+        assert_eq!(grid.to_code(), "10/10/10/10/10/10/10/10/10/10");
+
+        grid.mark(&Cell::bounded(0, 0), CellState::Occupied);
+        grid.mark(&Cell::bounded(9, 0), CellState::Occupied);
+        grid.mark(&Cell::bounded(3, 1), CellState::Miss);
+        grid.mark(&Cell::bounded(4, 1), CellState::Hit);
+        grid.mark(&Cell::bounded(9, 9), CellState::Hit);
+        grid.mark(&Cell::bounded(5, 5), CellState::Blocked);
+        assert_eq!(grid.to_code(), "#8#/3OX5/10/10/10/5^4/10/10/10/9X");
+    }
+
+    #[rstest]
+    fn test_grid_code_round_trip() {
+        let ships = [
+            ShipKind::AircraftCarrier
+                .ship(Cell::bounded(0, 0), ShipOrientation::Horizontal)
+                .unwrap(),
+            ShipKind::Destroyer
+                .ship(Cell::bounded(3, 3), ShipOrientation::Vertical)
+                .unwrap(),
+        ];
+        let mut grid = Grid::from_ships(ships.as_slice());
+        grid.shoot(&Cell::bounded(1, 0)).unwrap();
+        grid.shoot(&Cell::bounded(7, 7)).unwrap();
+
+        assert_eq!(Grid::from_code(&grid.to_code()), Ok(grid));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("10/10/10/10/10/10/10/10/10")]
+    #[case("10/10/10/10/10/10/10/10/10/10/10")]
+    #[case("9/10/10/10/10/10/10/10/10/10")]
+    #[case("11/10/10/10/10/10/10/10/10/10")]
+    #[case("#10/10/10/10/10/10/10/10/10/10")]
+    #[case("5#4#/10/10/10/10/10/10/10/10/10")]
+    #[case("a9/10/10/10/10/10/10/10/10/10")]
+    #[case("09#/10/10/10/10/10/10/10/10/10")]
+    fn test_grid_from_invalid_code(#[case] code: &str) {
+        assert_eq!(
+            Grid::from_code(code),
+            Err(Error::InvalidCode(code.to_string()))
+        );
+    }
+
+    #[rstest]
+    fn test_grid_statistics() {
+        let mut grid = Grid::default();
+        assert_eq!(grid.count(&CellState::Empty), 100);
+        assert_eq!(grid.hit_ratio(), None);
+        assert_eq!(grid.bounding_box(&CellState::Hit), None);
+
+        grid.mark(&Cell::bounded(2, 7), CellState::Hit);
+        grid.mark(&Cell::bounded(6, 3), CellState::Hit);
+        grid.mark(&Cell::bounded(9, 0), CellState::Miss);
+        grid.mark(&Cell::bounded(4, 4), CellState::Hit);
+        assert_eq!(grid.count(&CellState::Hit), 3);
+        assert_eq!(grid.count(&CellState::Empty), 96);
+        assert_eq!(grid.hit_ratio(), Some(0.75));
+        assert_eq!(
+            grid.bounding_box(&CellState::Hit),
+            Some((Cell::bounded(2, 3), Cell::bounded(6, 7)))
+        );
+        assert_eq!(
+            grid.bounding_box(&CellState::Miss),
+            Some((Cell::bounded(9, 0), Cell::bounded(9, 0)))
+        );
+    }
+
+    #[rstest]
+    fn test_render_row_to() {
+        let mut grid = Grid::default();
+        grid.mark(&Cell::bounded(0, 2), CellState::Hit);
+        grid.mark(&Cell::bounded(9, 2), CellState::Miss);
+
+        let mut row = String::new();
+        grid.render_row_to(&mut row, 2, |cell, state| match state {
+            CellState::Empty => cell.x().to_string(),
+            _ => "*".to_string(),
+        })
+        .unwrap();
+        assert_eq!(row, "* 1 2 3 4 5 6 7 8 *");
+
+        let mut table = String::new();
+        grid.render_to(&mut table).unwrap();
+        assert_eq!(table, grid.to_string());
+    }
+
     #[rustfmt::skip]
     #[rstest]
     fn test_display_grid() {
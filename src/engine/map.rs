@@ -0,0 +1,227 @@
+//! Maps: boards with islands.
+//!
+//! A [Map] blocks some cells of both boards: no ship can occupy an island, and a shot at an
+//! island is always wasted. Maps are defined by 10 rows of 10 symbols, where `.` is the sea
+//! and `^` is an island, like:
+//!
+//! ```text
+//! ..........
+//! ..^^......
+//! ..^.......
+//! ..........
+//! .......^..
+//! ......^^..
+//! ..........
+//! ..........
+//! ...^......
+//! ..........
+//! ```
+//!
+//! Empty lines and lines starting with `;` are ignored, so that a definition can have
+//! comments. A few maps are [built in](Map::BUILT_IN).
+//!
+use crate::engine::fleet::{Fleet, Ship};
+use crate::engine::grid::{Cell, CellState, Grid};
+use thiserror::Error;
+
+/// Reasons why a map definition is not valid.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum MapError {
+    /// The definition doesn't have 10 rows.
+    #[error("a map must have 10 rows, but {0} were found")]
+    WrongRowCount(usize),
+
+    /// A row doesn't have 10 cells.
+    #[error("row {0} of the map must have 10 cells")]
+    WrongRowLength(usize),
+
+    /// A symbol is neither the sea nor an island.
+    #[error("'{0}' is not a valid map symbol, use '.' for the sea and '^' for an island")]
+    InvalidSymbol(char),
+
+    /// The islands leave too little room for the fleets.
+    #[error("a map can have at most {max} island cells, but {0} were found", max = Map::MAX_ISLANDS)]
+    TooManyIslands(usize),
+
+    /// No built-in map has this name.
+    #[error("{0} is not a known map")]
+    Unknown(String),
+}
+
+/// A board with islands.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Map {
+    name: String,
+    islands: Vec<Cell>,
+}
+
+impl Map {
+    /// The maximum number of island cells, so that there is always room for the fleets.
+    pub const MAX_ISLANDS: usize = 20;
+
+    /// The names and the definitions of the built-in maps.
+    pub const BUILT_IN: [(&'static str, &'static str); 2] = [
+        (
+            "archipelago",
+            "..........\n..^^......\n..^.......\n..........\n.......^..\n\
+             ......^^..\n..........\n..........\n...^......\n..........",
+        ),
+        (
+            "strait",
+            "..........\n..........\n..........\n^^^.......\n..........\n\
+             ..........\n.......^^^\n..........\n..........\n..........",
+        ),
+    ];
+
+    /// Parses the definition of a map with the given name.
+    pub fn parse(name: &str, definition: &str) -> Result<Self, MapError> {
+        let rows: Vec<&str> = definition
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with(';'))
+            .collect();
+        if rows.len() != 10 {
+            return Err(MapError::WrongRowCount(rows.len()));
+        }
+
+        let mut islands = Vec::new();
+        for (y, row) in rows.iter().enumerate() {
+            if row.chars().count() != 10 {
+                return Err(MapError::WrongRowLength(y + 1));
+            }
+            for (x, symbol) in row.chars().enumerate() {
+                match symbol {
+                    '.' => {}
+                    '^' => islands.push(Cell::bounded(x as u8, y as u8)),
+                    _ => return Err(MapError::InvalidSymbol(symbol)),
+                }
+            }
+        }
+
+        if islands.len() > Self::MAX_ISLANDS {
+            return Err(MapError::TooManyIslands(islands.len()));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            islands,
+        })
+    }
+
+    /// Returns the built-in map with the given name.
+    pub fn built_in(name: &str) -> Result<Self, MapError> {
+        let (name, definition) = Self::BUILT_IN
+            .iter()
+            .find(|(built_in, _)| *built_in == name)
+            .ok_or_else(|| MapError::Unknown(name.to_string()))?;
+
+        Self::parse(name, definition)
+    }
+
+    /// Returns the name of the map.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the island cells, from the top-left one.
+    pub fn islands(&self) -> &[Cell] {
+        &self.islands
+    }
+
+    /// Checks whether the cell is an island.
+    pub fn is_island(&self, cell: &Cell) -> bool {
+        self.islands.contains(cell)
+    }
+
+    /// Checks whether the ship is entirely on the sea.
+    pub fn allows(&self, ship: &Ship) -> bool {
+        ship.occupied_cells()
+            .iter()
+            .all(|cell| !self.is_island(cell))
+    }
+
+    /// Returns the first island occupied by a ship of the fleet, if any.
+    pub fn island_under(&self, fleet: &Fleet) -> Option<Cell> {
+        fleet
+            .as_ref()
+            .iter()
+            .flat_map(|ship| ship.occupied_cells())
+            .find(|cell| self.is_island(cell))
+    }
+
+    /// Returns a grid where the islands are [blocked](CellState::Blocked).
+    pub fn grid(&self) -> Grid {
+        let mut grid = Grid::default();
+        for cell in &self.islands {
+            grid.mark(cell, CellState::Blocked);
+        }
+
+        grid
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::{ShipKind, ShipOrientation, tests::fixed_fleet};
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[rstest]
+    fn test_built_in_maps() {
+        for (name, _) in Map::BUILT_IN {
+            let map = Map::built_in(name).unwrap();
+            assert_eq!(map.name(), name);
+            assert!(!map.islands().is_empty());
+        }
+
+        let archipelago = Map::built_in("archipelago").unwrap();
+        assert!(archipelago.is_island(&Cell::from_str("C2").unwrap()));
+        assert!(!archipelago.is_island(&Cell::from_str("A1").unwrap()));
+        assert_eq!(
+            Map::built_in("atlantis"),
+            Err(MapError::Unknown("atlantis".to_string()))
+        );
+    }
+
+    #[rstest]
+    #[case("..........\n".repeat(9), MapError::WrongRowCount(9))]
+    #[case("..........\n".repeat(9) + ".........", MapError::WrongRowLength(10))]
+    #[case("..........\n".repeat(9) + "....#.....", MapError::InvalidSymbol('#'))]
+    #[case("^^^^^^^^^^\n".repeat(3) + "..........\n".repeat(7).as_str(), MapError::TooManyIslands(30))]
+    fn test_invalid_map(#[case] definition: String, #[case] error: MapError) {
+        assert_eq!(Map::parse("custom", &definition), Err(error));
+    }
+
+    #[rstest]
+    fn test_comments() {
+        let definition = format!(
+            "; a single rock\n\n^.........\n{}",
+            "..........\n".repeat(9)
+        );
+        let map = Map::parse("rock", &definition).unwrap();
+        assert_eq!(map.islands(), [Cell::bounded(0, 0)]);
+        assert_eq!(*map.grid().at(&Cell::bounded(0, 0)), CellState::Blocked);
+        assert_eq!(*map.grid().at(&Cell::bounded(1, 0)), CellState::Empty);
+    }
+
+    #[rstest]
+    fn test_placement(
+        fixed_fleet: Fleet,
+        #[from(fixed_fleet)]
+        #[with(5)]
+        shifted_fleet: Fleet,
+    ) {
+        let map = Map::built_in("strait").unwrap();
+        assert_eq!(map.island_under(&fixed_fleet), None);
+        assert_eq!(
+            map.island_under(&shifted_fleet),
+            Some(Cell::from_str("H7").unwrap())
+        );
+
+        let ship = ShipKind::Destroyer
+            .ship(Cell::from_str("A4").unwrap(), ShipOrientation::Horizontal)
+            .unwrap();
+        assert!(!map.allows(&ship));
+    }
+}
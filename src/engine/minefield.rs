@@ -0,0 +1,196 @@
+//! Sea mines hidden among the ships of a fleet.
+//!
+//! In the [sea mines](crate::engine::rules::SeaMinesRules) variant, every player secretly places
+//! some mines on the free cells of their board. A [Minefield] keeps track of these mines and
+//! of the ones which have already exploded: shooting a mined cell makes the attacker lose a
+//! random cell of their own fleet.
+//!
+use crate::engine::fleet::Fleet;
+use crate::engine::grid::Cell;
+use rand::Rng;
+use rand::seq::IndexedRandom;
+use thiserror::Error;
+
+/// Reasons why mines cannot be placed.
+#[derive(Debug, Error, PartialEq, Eq, Clone, Copy)]
+pub enum MineError {
+    /// A ship of the fleet occupies the cell.
+    #[error("A mine can't be placed on a ship, at {0}")]
+    OnShip(Cell),
+
+    /// The cell has already been mined.
+    #[error("A mine has already been placed at {0}")]
+    Duplicate(Cell),
+
+    /// More mines than the rules allow have been placed.
+    #[error("Only {0} mines can be placed")]
+    TooMany(usize),
+}
+
+/// The mines placed by a player.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Minefield {
+    mines: Vec<Cell>,
+    exploded: Vec<Cell>,
+}
+
+impl Minefield {
+    /// Creates a minefield with mines on the given cells, which must be free cells of the fleet.
+    pub fn new(cells: &[Cell], fleet: &Fleet) -> Result<Self, MineError> {
+        let mut minefield = Self::default();
+        for cell in cells {
+            minefield.place(*cell, fleet)?;
+        }
+
+        Ok(minefield)
+    }
+
+    /// Creates a minefield with the given number of mines on random free cells of the fleet,
+    /// avoiding the given islands.
+    pub fn random_with<R: Rng + ?Sized>(
+        count: usize,
+        fleet: &Fleet,
+        islands: &[Cell],
+        rng: &mut R,
+    ) -> Self {
+        let mut minefield = Self::default();
+        minefield.fill_with(count, fleet, islands, rng);
+
+        minefield
+    }
+
+    /// Places a mine on the cell, which must be a free cell of the fleet.
+    pub fn place(&mut self, cell: Cell, fleet: &Fleet) -> Result<(), MineError> {
+        if fleet.is_occupied(&cell) {
+            return Err(MineError::OnShip(cell));
+        } else if self.is_mined(&cell) {
+            return Err(MineError::Duplicate(cell));
+        }
+
+        self.mines.push(cell);
+        Ok(())
+    }
+
+    /// Places mines on random free cells of the fleet, avoiding the given islands, until there
+    /// are the given number of mines.
+    pub fn fill_with<R: Rng + ?Sized>(
+        &mut self,
+        count: usize,
+        fleet: &Fleet,
+        islands: &[Cell],
+        rng: &mut R,
+    ) {
+        let free: Vec<Cell> = (0..10)
+            .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+            .filter(|cell| {
+                !fleet.is_occupied(cell) && !islands.contains(cell) && !self.is_mined(cell)
+            })
+            .collect();
+        let missing = count.saturating_sub(self.mines.len());
+        self.mines
+            .extend(free.choose_multiple(rng, missing).copied());
+    }
+
+    /// Returns the mined cells, in the order they were placed.
+    pub fn cells(&self) -> &[Cell] {
+        &self.mines
+    }
+
+    /// Returns the number of mines.
+    pub fn len(&self) -> usize {
+        self.mines.len()
+    }
+
+    /// Returns `true` if there are no mines.
+    pub fn is_empty(&self) -> bool {
+        self.mines.is_empty()
+    }
+
+    /// Checks whether a mine has been placed on the cell.
+    pub fn is_mined(&self, cell: &Cell) -> bool {
+        self.mines.contains(cell)
+    }
+
+    /// Explodes the mine on the cell, if any.
+    ///
+    /// Returns `true` only the first time a mine explodes.
+    pub fn explode(&mut self, cell: &Cell) -> bool {
+        if !self.is_mined(cell) || self.exploded.contains(cell) {
+            return false;
+        }
+
+        self.exploded.push(*cell);
+        true
+    }
+
+    /// Returns the mines which have exploded, in the order they exploded.
+    pub fn exploded(&self) -> &[Cell] {
+        &self.exploded
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::tests::fixed_fleet;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_new(fixed_fleet: Fleet) {
+        let free = (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .find(|cell| !fixed_fleet.is_occupied(cell))
+            .unwrap();
+        let ship = fixed_fleet.as_ref()[0].first_cell();
+
+        let minefield = Minefield::new(&[free], &fixed_fleet).unwrap();
+        assert_eq!(minefield.cells(), [free]);
+        assert!(minefield.is_mined(&free));
+        assert_eq!(
+            Minefield::new(&[ship], &fixed_fleet),
+            Err(MineError::OnShip(ship))
+        );
+        assert_eq!(
+            Minefield::new(&[free, free], &fixed_fleet),
+            Err(MineError::Duplicate(free))
+        );
+    }
+
+    #[rstest]
+    fn test_random(fixed_fleet: Fleet) {
+        let mut rng = StdRng::seed_from_u64(7);
+        let minefield = Minefield::random_with(2, &fixed_fleet, &[], &mut rng);
+        assert_eq!(minefield.len(), 2);
+        assert_ne!(minefield.cells()[0], minefield.cells()[1]);
+        assert!(
+            minefield
+                .cells()
+                .iter()
+                .all(|c| !fixed_fleet.is_occupied(c))
+        );
+
+        let mut more = minefield.clone();
+        more.fill_with(3, &fixed_fleet, &[], &mut rng);
+        assert_eq!(more.len(), 3);
+        assert_eq!(more.cells()[..2], *minefield.cells());
+    }
+
+    #[rstest]
+    fn test_explode(fixed_fleet: Fleet) {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut minefield = Minefield::random_with(2, &fixed_fleet, &[], &mut rng);
+        let mine = minefield.cells()[1];
+        let other = (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .find(|cell| !minefield.is_mined(cell))
+            .unwrap();
+
+        assert!(!minefield.explode(&other));
+        assert!(minefield.explode(&mine));
+        assert!(!minefield.explode(&mine));
+        assert_eq!(minefield.exploded(), [mine]);
+        assert!(Minefield::default().is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+//! Observers of a game.
+//!
+//! A [GameObserver] subscribed to a [crate::engine::game::Game] is notified of what happens
+//! during the match, so that loggers, sound effects or statistics collectors don't need to
+//! inspect the players after every turn.
+//!
+use crate::engine::fleet::ShipKind;
+use crate::engine::game::Side;
+use crate::engine::grid::Cell;
+use std::fmt::Debug;
+
+/// Callbacks for the events of a game.
+///
+/// Every callback does nothing by default, so an observer implements only the ones it is
+/// interested in.
+pub trait GameObserver: Debug {
+    /// A side has shot at the given cell, hitting a ship of the given kind or missing.
+    fn on_shot(&mut self, _shooter: Side, _cell: &Cell, _hit: Option<&ShipKind>) {}
+
+    /// A side has scanned the given cells, detecting a ship or not.
+    fn on_scan(&mut self, _scanner: Side, _cells: &[Cell], _detected: bool) {}
+
+    /// A side has shot a mine at the given cell, and lost the given cell of its fleet.
+    fn on_mine(&mut self, _shooter: Side, _cell: &Cell, _lost: Option<&Cell>) {}
+
+    /// A side has relocated its Submarine instead of firing.
+    fn on_relocate(&mut self, _side: Side) {}
+
+    /// A ship of the given side has been sunk.
+    fn on_ship_sunk(&mut self, _owner: Side, _kind: &ShipKind) {}
+
+    /// A side has resigned.
+    fn on_resign(&mut self, _side: Side) {}
+
+    /// A side has run out of time.
+    fn on_time_out(&mut self, _side: Side) {}
+
+    /// A turn has ended. Turns are counted from 1.
+    fn on_turn_end(&mut self, _turn: u32) {}
+
+    /// The game is over, and the given side has won.
+    fn on_game_over(&mut self, _winner: Side) {}
+
+    /// The game is over in a draw, as both fleets have been sunk in the same turn.
+    fn on_draw(&mut self) {}
+}
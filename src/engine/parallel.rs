@@ -0,0 +1,37 @@
+//! This module contains the helpers which spread the work of the computer across the cores, with
+//! the `parallel` feature. Without it, the same work is done on the current thread.
+//!
+//! The results come back in the order of the items, so that the work gives the same results
+//! whether it is spread or not.
+//!
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+/// Applies the function to every item, on all the cores with the `parallel` feature, and returns
+/// the results in the order of the items.
+pub fn map<T, U, F>(items: &[T], f: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    return items.par_iter().map(f).collect();
+    #[cfg(not(feature = "parallel"))]
+    return items.iter().map(f).collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_map_keeps_the_order() {
+        let items: Vec<u64> = (0..1000).collect();
+        assert_eq!(
+            map(&items, |item| item * 2),
+            items.iter().map(|item| item * 2).collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,420 @@
+//! The placement strategies deploy a whole fleet at once, like the computer does before every
+//! game.
+//!
+//! A [PlacementStrategy] is to the deployment what a [Strategy](crate::engine::strategy::Strategy)
+//! is to the battle: every implementation follows its own idea of a fleet hard to find, like
+//! [EdgeHugger], which lays the ships along the edges of the board, or [Spread], which keeps them
+//! far apart. The [Placement] enum lists them, so that they can be chosen by name.
+//!
+use crate::engine::fleet::{Fleet, Ship, ShipKind, ShipOrientation};
+use crate::engine::grid::Cell;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use std::fmt::Debug;
+use strum::Display;
+use strum_macros::EnumIter;
+
+/// The PlacementStrategy trait for implementing different deployments of a fleet.
+pub trait PlacementStrategy: Debug {
+    /// Returns a new fleet, with no ship on the given islands.
+    fn deploy(&mut self, islands: &[Cell]) -> Fleet;
+}
+
+/// The placement strategies, by name.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Display, EnumIter)]
+pub enum Placement {
+    /// The ships are placed anywhere.
+    Random,
+
+    /// The ships are placed along the edges of the board.
+    #[strum(serialize = "Edge Hugger")]
+    EdgeHugger,
+
+    /// The ships are placed far from each other.
+    Spread,
+
+    /// The ships are placed close to each other.
+    Clustered,
+
+    /// The ships are placed off the cells that a parity hunter fires at first.
+    #[strum(serialize = "Anti-Parity")]
+    AntiParity,
+}
+
+impl Placement {
+    /// Returns the strategy with this name, which always deploys the same fleets for the same
+    /// seed.
+    pub fn strategy(self, seed: u64) -> Box<dyn PlacementStrategy> {
+        match self {
+            Placement::Random => Box::new(RandomPlacement::with_seed(seed)),
+            Placement::EdgeHugger => Box::new(EdgeHugger::with_seed(seed)),
+            Placement::Spread => Box::new(Spread::with_seed(seed)),
+            Placement::Clustered => Box::new(Clustered::with_seed(seed)),
+            Placement::AntiParity => Box::new(AntiParity::with_seed(seed)),
+        }
+    }
+}
+
+/// A placement strategy which places the ships anywhere, with the same chance.
+#[derive(Debug)]
+pub struct RandomPlacement {
+    rng: StdRng,
+}
+
+impl RandomPlacement {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Creates a strategy which always deploys the same fleets for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn weight(_ship: &Ship, _placed: &[Ship]) -> f64 {
+        1.0
+    }
+}
+
+impl Default for RandomPlacement {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlacementStrategy for RandomPlacement {
+    fn deploy(&mut self, islands: &[Cell]) -> Fleet {
+        deploy_weighted(&mut self.rng, islands, Self::weight)
+    }
+}
+
+/// A placement strategy which prefers the edges of the board, where a player hunting at
+/// random hits less often, as every cell has fewer neighbours.
+#[derive(Debug)]
+pub struct EdgeHugger {
+    rng: StdRng,
+}
+
+impl EdgeHugger {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Creates a strategy which always deploys the same fleets for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn weight(ship: &Ship, _placed: &[Ship]) -> f64 {
+        let on_edge = ship
+            .occupied_cells()
+            .iter()
+            .filter(|cell| cell.x() == 0 || cell.x() == 9 || cell.y() == 0 || cell.y() == 9)
+            .count();
+
+        1.0 + 4.0 * on_edge as f64
+    }
+}
+
+impl Default for EdgeHugger {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlacementStrategy for EdgeHugger {
+    fn deploy(&mut self, islands: &[Cell]) -> Fleet {
+        deploy_weighted(&mut self.rng, islands, Self::weight)
+    }
+}
+
+/// A placement strategy which keeps the ships far from each other, so that finding one of
+/// them tells nothing about where the others are.
+#[derive(Debug)]
+pub struct Spread {
+    rng: StdRng,
+}
+
+impl Spread {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Creates a strategy which always deploys the same fleets for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn weight(ship: &Ship, placed: &[Ship]) -> f64 {
+        distance(ship, placed).map_or(1.0, |distance| distance.powi(2))
+    }
+}
+
+impl Default for Spread {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlacementStrategy for Spread {
+    fn deploy(&mut self, islands: &[Cell]) -> Fleet {
+        deploy_weighted(&mut self.rng, islands, Self::weight)
+    }
+}
+
+/// A placement strategy which keeps the ships close to each other, against the players
+/// who stop searching around a sunk ship.
+#[derive(Debug)]
+pub struct Clustered {
+    rng: StdRng,
+}
+
+impl Clustered {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Creates a strategy which always deploys the same fleets for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn weight(ship: &Ship, placed: &[Ship]) -> f64 {
+        distance(ship, placed).map_or(1.0, |distance| distance.powi(-2))
+    }
+}
+
+impl Default for Clustered {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlacementStrategy for Clustered {
+    fn deploy(&mut self, islands: &[Cell]) -> Fleet {
+        deploy_weighted(&mut self.rng, islands, Self::weight)
+    }
+}
+
+/// A placement strategy which prefers the placements covering fewer cells of the
+/// checkerboard that a parity hunter fires at first, the ones where `x + y` is even.
+#[derive(Debug)]
+pub struct AntiParity {
+    rng: StdRng,
+}
+
+impl AntiParity {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Creates a strategy which always deploys the same fleets for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    fn weight(ship: &Ship, _placed: &[Ship]) -> f64 {
+        let hunted = ship
+            .occupied_cells()
+            .iter()
+            .filter(|cell| (cell.x() + cell.y()) % 2 == 0)
+            .count();
+
+        if hunted * 2 < ship.size() as usize {
+            4.0
+        } else {
+            1.0
+        }
+    }
+}
+
+impl Default for AntiParity {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PlacementStrategy for AntiParity {
+    fn deploy(&mut self, islands: &[Cell]) -> Fleet {
+        deploy_weighted(&mut self.rng, islands, Self::weight)
+    }
+}
+
+// Deploys the ships one by one, in fleet order. Every ship is drawn among the placements which
+// don't overlap the islands or the ships already placed, according to the given weight. When a
+// ship can't be placed anymore, the deployment starts again.
+fn deploy_weighted<R, W>(rng: &mut R, islands: &[Cell], weight: W) -> Fleet
+where
+    R: Rng + ?Sized,
+    W: Fn(&Ship, &[Ship]) -> f64,
+{
+    loop {
+        let mut ships = Vec::<Ship>::with_capacity(Fleet::COMPOSITION.len());
+        for kind in Fleet::COMPOSITION.iter() {
+            let candidates: Vec<Ship> = placements(kind)
+                .filter(|ship| {
+                    ship.occupied_cells()
+                        .iter()
+                        .all(|cell| !islands.contains(cell))
+                        && ships.iter().all(|other| !other.is_overlapping(ship))
+                })
+                .collect();
+            let Ok(ship) = candidates.choose_weighted(rng, |ship| weight(ship, &ships)) else {
+                break;
+            };
+
+            ships.push(ship.clone());
+        }
+
+        if let Ok(fleet) = Fleet::new(&ships) {
+            break fleet;
+        }
+    }
+}
+
+// Returns every placement of a ship of the given kind on the board.
+fn placements(kind: &ShipKind) -> impl Iterator<Item = Ship> + '_ {
+    (0..10).flat_map(move |y| {
+        (0..10).flat_map(move |x| {
+            [ShipOrientation::Horizontal, ShipOrientation::Vertical]
+                .into_iter()
+                .filter_map(move |orientation| kind.ship(Cell::bounded(x, y), orientation))
+        })
+    })
+}
+
+// Returns the shortest distance, in king moves, between the cells of the ship and the cells of
+// the placed ships, if any.
+fn distance(ship: &Ship, placed: &[Ship]) -> Option<f64> {
+    let cells = ship.occupied_cells();
+    placed
+        .iter()
+        .flat_map(|other| other.occupied_cells())
+        .flat_map(|other| {
+            cells.iter().map(move |cell| {
+                cell.x()
+                    .abs_diff(other.x())
+                    .max(cell.y().abs_diff(other.y()))
+            })
+        })
+        .min()
+        .map(f64::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use strum::IntoEnumIterator;
+
+    fn on_edge(cell: &Cell) -> bool {
+        cell.x() == 0 || cell.x() == 9 || cell.y() == 0 || cell.y() == 9
+    }
+
+    // Returns the average, over many fleets, of the given measure of a fleet.
+    fn average<F: Fn(&Fleet) -> f64>(placement: Placement, measure: F) -> f64 {
+        let mut strategy = placement.strategy(42);
+        (0..50).map(|_| measure(&strategy.deploy(&[]))).sum::<f64>() / 50.0
+    }
+
+    fn cells_on_edge(fleet: &Fleet) -> f64 {
+        fleet
+            .as_ref()
+            .iter()
+            .flat_map(|ship| ship.occupied_cells())
+            .filter(on_edge)
+            .count() as f64
+    }
+
+    fn mean_distance(fleet: &Fleet) -> f64 {
+        let ships = fleet.as_ref();
+        ships
+            .iter()
+            .enumerate()
+            .map(|(i, ship)| {
+                let others: Vec<Ship> = ships
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != i)
+                    .map(|(_, other)| other.clone())
+                    .collect();
+                distance(ship, &others).unwrap()
+            })
+            .sum::<f64>()
+            / ships.len() as f64
+    }
+
+    fn hunted_cells(fleet: &Fleet) -> f64 {
+        fleet
+            .as_ref()
+            .iter()
+            .flat_map(|ship| ship.occupied_cells())
+            .filter(|cell| (cell.x() + cell.y()) % 2 == 0)
+            .count() as f64
+    }
+
+    #[rstest]
+    fn test_deploy_avoids_islands() {
+        let islands: Vec<Cell> = (0..10).map(|x| Cell::bounded(x, 4)).collect();
+        for placement in Placement::iter() {
+            let mut strategy = placement.strategy(7);
+            for _ in 0..10 {
+                let fleet = strategy.deploy(&islands);
+                assert!(islands.iter().all(|cell| !fleet.is_occupied(cell)));
+                assert!(Fleet::new(fleet.as_ref()).is_ok());
+            }
+        }
+    }
+
+    #[rstest]
+    fn test_deploy_is_seeded() {
+        for placement in Placement::iter() {
+            let first = placement.strategy(3).deploy(&[]).to_code();
+            let second = placement.strategy(3).deploy(&[]).to_code();
+            assert_eq!(first, second);
+        }
+    }
+
+    #[rstest]
+    fn test_edge_hugger() {
+        assert!(
+            average(Placement::EdgeHugger, cells_on_edge)
+                > average(Placement::Random, cells_on_edge) + 3.0
+        );
+    }
+
+    #[rstest]
+    fn test_spread_and_clustered() {
+        let random = average(Placement::Random, mean_distance);
+        assert!(average(Placement::Spread, mean_distance) > random);
+        assert!(average(Placement::Clustered, mean_distance) < random);
+    }
+
+    #[rstest]
+    fn test_anti_parity() {
+        assert!(
+            average(Placement::AntiParity, hunted_cells) < average(Placement::Random, hunted_cells)
+        );
+    }
+}
@@ -12,21 +12,36 @@
 //! version will focus on a single-player vs. computer opponent.
 //!
 
-use crate::engine::fleet::{Fleet, ShipKind};
+use crate::engine::fleet::{Fleet, FleetError, FleetShot, Ship, ShipId, ShipKind};
 use crate::engine::grid::{Cell, CellState, Grid};
-use crate::engine::strategy::Strategy;
+use crate::engine::map::Map;
+use crate::engine::minefield::Minefield;
+use crate::engine::rules::Rules;
+#[cfg(feature = "async")]
+use crate::engine::strategy::Preparation;
+use crate::engine::strategy::{Strategy, StrategyView};
+use rand::Rng;
+use std::collections::HashMap;
 
 /// Defines the Player struct and associated methods for managing player-related functionalities.
 #[derive(Debug)]
 pub struct Player {
     name: String,
     fleet: Fleet,
+    mines: Minefield,
+    ammo: Option<u32>,
+    shots: u32,
     grid: Grid,
+    // The opponent's ships sunk by the player, and their kinds, which the strategies see.
+    sunk: Vec<ShipId>,
+    sunk_kinds: Vec<ShipKind>,
     strategy: Box<dyn Strategy>,
     human: bool,
 }
 
 impl Player {
+    const MAX_SKIPPED_MOVES: usize = 100;
+
     /// Creates a new Player instance.
     ///
     /// Initializes a new player with the given name and fleet. The player's grid is initialized to empty.
@@ -34,7 +49,12 @@ impl Player {
         Self {
             name: name.to_string(),
             fleet,
+            mines: Minefield::default(),
+            ammo: None,
+            shots: 0,
             grid: Grid::default(),
+            sunk: Vec::new(),
+            sunk_kinds: Vec::new(),
             strategy: Box::new(NoStrategy),
             human: true,
         }
@@ -52,22 +72,124 @@ impl Player {
         &self.grid
     }
 
+    /// Returns the identifiers of the opponent's ships sunk by the player, in the order they were
+    /// sunk.
+    pub fn sunk_ships(&self) -> &[ShipId] {
+        &self.sunk
+    }
+
+    /// Returns the kinds of the opponent's ships sunk by the player, in the order they were sunk.
+    pub fn sunk_kinds(&self) -> &[ShipKind] {
+        &self.sunk_kinds
+    }
+
+    /// Returns what the player knows about the battle played against the opponent with the
+    /// given rules.
+    pub fn view<'a>(&'a self, opponent: &'a Player, rules: &'a dyn Rules) -> StrategyView<'a> {
+        StrategyView::new(&self.grid, &self.sunk_kinds, &opponent.grid, rules)
+    }
+
     /// Returns the player's fleet.
     pub fn fleet(&self) -> &Fleet {
         &self.fleet
     }
 
+    /// Returns the mines placed by the player.
+    pub fn mines(&self) -> &Minefield {
+        &self.mines
+    }
+
+    /// Set the mines placed by the player among their ships.
+    pub fn set_mines(&mut self, mines: Minefield) {
+        self.mines = mines;
+    }
+
+    /// Explodes the player's mine on the cell, if any, and returns `true` if it exploded.
+    pub fn explode_mine(&mut self, cell: &Cell) -> bool {
+        self.mines.explode(cell)
+    }
+
+    /// Damages a random cell of the player's fleet, which has not been hit yet.
+    ///
+    /// Returns the cell and the outcome of the damage, or `None` when the fleet is sunk.
+    pub fn damage_random_cell<R: Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+    ) -> Option<(Cell, FleetShot)> {
+        self.fleet.hit_random_with(rng)
+    }
+
+    /// Moves the ship of the player's fleet with the given identifier to the position of the
+    /// given ship, keeping its damage.
+    ///
+    /// An error is returned if the ship has been sunk, or if it overlaps another ship.
+    pub fn relocate(&mut self, id: ShipId, ship: &Ship) -> Result<(), FleetError> {
+        self.fleet.relocate(id, ship)
+    }
+
+    /// Adds an extra ship to the fleet of the player, and returns its identifier.
+    ///
+    /// An error is returned if the ship overlaps another ship of the fleet.
+    pub fn reinforce(&mut self, ship: Ship) -> Result<ShipId, FleetError> {
+        self.fleet.reinforce(ship)
+    }
+
+    /// Reveals an enemy ship to the player, whose strategy can aim at it.
+    pub fn reveal(&mut self, ship: &Ship) {
+        self.strategy.notify_revealed(&ship.occupied_cells());
+    }
+
+    /// Returns the shells left to the player, or `None` when they are unlimited.
+    pub fn ammo(&self) -> Option<u32> {
+        self.ammo
+    }
+
+    /// Set the shells of the player, or `None` to make them unlimited.
+    pub fn set_ammo(&mut self, ammo: Option<u32>) {
+        self.ammo = ammo;
+    }
+
+    /// Checks whether the player has fired all their shells.
+    pub fn is_out_of_ammo(&self) -> bool {
+        self.ammo == Some(0)
+    }
+
+    /// Charts the islands of the map on the player's shots grid.
+    pub fn set_map(&mut self, map: &Map) {
+        for cell in map.islands() {
+            self.grid.mark(cell, CellState::Blocked);
+        }
+    }
+
+    /// Returns the number of shots fired by the player, including the ones wasted on islands.
+    pub fn shots(&self) -> u32 {
+        self.shots
+    }
+
     /// Try to hit the opponent's ships.
-    pub fn attack(&mut self, opponent: &mut Player, cell: &Cell) -> Option<ShipKind> {
-        let ship_hit = opponent.fleet.hit_at(cell);
-        if ship_hit.is_some() {
+    ///
+    /// Every shot uses a shell, when they are limited. A shot at an island is wasted: it never
+    /// hits, and it is not recorded.
+    pub fn attack(&mut self, opponent: &mut Player, cell: &Cell) -> FleetShot {
+        self.shots += 1;
+        self.ammo = self.ammo.map(|ammo| ammo.saturating_sub(1));
+        if *self.grid.at(cell) == CellState::Blocked {
+            return FleetShot::default();
+        }
+
+        let outcome = opponent.fleet.hit_at(cell);
+        if let Some(kind) = &outcome.ship {
             self.grid.mark(cell, CellState::Hit);
-            self.strategy.notify_hit(ship_hit.clone().unwrap());
+            if let Some(id) = outcome.id.filter(|_| outcome.sunk) {
+                self.sunk.push(id);
+                self.sunk_kinds.push(kind.clone());
+            }
+            self.strategy.notify_hit(kind.clone());
         } else {
             self.grid.mark(cell, CellState::Miss);
         }
 
-        ship_hit
+        outcome
     }
 
     /// Checks whether this player has lost the battle
@@ -75,9 +197,40 @@ impl Player {
         self.fleet.is_sunk()
     }
 
-    /// return the next move to play, or None if no strategy is supported (human player)
-    pub fn next_move(&mut self) -> Option<Cell> {
-        self.strategy.next_move()
+    /// Returns how much the strategy of the player likes every cell for its next move against
+    /// the opponent with the given rules, or `None` if the strategy doesn't weigh the cells.
+    pub fn heatmap(&mut self, opponent: &Player, rules: &dyn Rules) -> Option<HashMap<Cell, f32>> {
+        let view = StrategyView::new(&self.grid, &self.sunk_kinds, &opponent.grid, rules);
+        self.strategy.heatmap(&view)
+    }
+
+    /// return the next move to play against the opponent with the given rules, or None if no
+    /// strategy is supported (human player)
+    ///
+    /// The moves at the islands charted on the shots grid are skipped, as long as the strategy
+    /// has other moves to suggest.
+    pub fn next_move(&mut self, opponent: &Player, rules: &dyn Rules) -> Option<Cell> {
+        let view = StrategyView::new(&self.grid, &self.sunk_kinds, &opponent.grid, rules);
+        let mut next = self.strategy.next_move(&view);
+        for _ in 0..Self::MAX_SKIPPED_MOVES {
+            match next {
+                Some(cell) if *self.grid.at(&cell) == CellState::Blocked => {
+                    next = self.strategy.next_move(&view)
+                }
+                _ => break,
+            }
+        }
+
+        next
+    }
+
+    /// Starts preparing the next move of the player against the opponent with the given rules,
+    /// and returns the future to await before [Player::next_move], if the strategy needs time to
+    /// prepare it.
+    #[cfg(feature = "async")]
+    pub fn prepare_move(&mut self, opponent: &Player, rules: &dyn Rules) -> Option<Preparation> {
+        let view = StrategyView::new(&self.grid, &self.sunk_kinds, &opponent.grid, rules);
+        self.strategy.prepare_move(&view)
     }
 
     /// Set the strategy to use for this player.
@@ -93,26 +246,90 @@ impl Player {
     pub fn is_human(&self) -> bool {
         self.human
     }
+
+    // Returns a copy of what the shots change: the fleet, the mines, the ammo, the shots, the notes and
+    // what the strategy has learnt, if it can be copied.
+    pub(crate) fn state(&self) -> PlayerState {
+        PlayerState {
+            fleet: self.fleet.clone(),
+            mines: self.mines.clone(),
+            ammo: self.ammo,
+            shots: self.shots,
+            grid: self.grid.clone(),
+            sunk: self.sunk.clone(),
+            sunk_kinds: self.sunk_kinds.clone(),
+            strategy: self.strategy.snapshot(),
+        }
+    }
+
+    // Brings the player back to a state returned by `state`, keeping its name, and its strategy
+    // when it couldn't be copied.
+    pub(crate) fn restore(&mut self, state: &PlayerState) {
+        self.fleet = state.fleet.clone();
+        self.mines = state.mines.clone();
+        self.ammo = state.ammo;
+        self.shots = state.shots;
+        self.grid = state.grid.clone();
+        self.sunk = state.sunk.clone();
+        self.sunk_kinds = state.sunk_kinds.clone();
+        if let Some(strategy) = state.strategy.as_ref().and_then(|s| s.snapshot()) {
+            self.strategy = strategy;
+        }
+    }
+}
+
+// The part of a player changed by the game, as kept by a snapshot of the game.
+#[derive(Debug)]
+pub(crate) struct PlayerState {
+    fleet: Fleet,
+    mines: Minefield,
+    ammo: Option<u32>,
+    shots: u32,
+    grid: Grid,
+    sunk: Vec<ShipId>,
+    sunk_kinds: Vec<ShipKind>,
+    strategy: Option<Box<dyn Strategy>>,
+}
+
+impl Clone for PlayerState {
+    fn clone(&self) -> Self {
+        Self {
+            fleet: self.fleet.clone(),
+            mines: self.mines.clone(),
+            ammo: self.ammo,
+            shots: self.shots,
+            grid: self.grid.clone(),
+            sunk: self.sunk.clone(),
+            sunk_kinds: self.sunk_kinds.clone(),
+            strategy: self.strategy.as_ref().and_then(|s| s.snapshot()),
+        }
+    }
 }
 
 #[derive(Debug)]
 struct NoStrategy;
 
 impl Strategy for NoStrategy {
-    fn next_move(&mut self) -> Option<Cell> {
+    fn next_move(&mut self, _view: &StrategyView) -> Option<Cell> {
         None
     }
+
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        Some(Box::new(NoStrategy))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::engine::fleet::ShipOrientation;
+    use crate::engine::rules::ClassicRules;
+    use crate::engine::strategy::SmartStrategy;
     use rstest::{fixture, rstest};
 
     #[fixture]
     pub fn player1_fleet() -> Fleet {
-        let mut y_coords = (0u8..9).into_iter().step_by(2);
+        let mut y_coords = (0u8..9).step_by(2);
         Fleet::build(|kind| {
             kind.ship(
                 Cell::bounded(0, y_coords.next().unwrap()),
@@ -124,7 +341,7 @@ mod tests {
 
     #[fixture]
     pub fn player2_fleet() -> Fleet {
-        let mut x_coords = (0u8..9).into_iter().step_by(2);
+        let mut x_coords = (0u8..9).step_by(2);
         Fleet::build(|kind| {
             kind.ship(
                 Cell::bounded(x_coords.next().unwrap(), 0),
@@ -168,9 +385,13 @@ mod tests {
             assert!(!player1.has_lost());
         });
 
-        let attack_result = player2.attack(&mut player1, &Cell::bounded(1, 8)).unwrap();
-        assert_eq!(attack_result, ShipKind::Destroyer);
-        assert!(player1.fleet().get(&attack_result).is_sunk());
+        let attack_result = player2.attack(&mut player1, &Cell::bounded(1, 8));
+        assert_eq!(attack_result.ship, Some(ShipKind::Destroyer));
+        assert!(attack_result.sunk);
+        assert_eq!(
+            attack_result.sunk_cells,
+            Some(vec![Cell::bounded(0, 8), Cell::bounded(1, 8)])
+        );
         assert!(player1.fleet().is_sunk());
         assert!(!player2.fleet().is_sunk());
 
@@ -209,5 +430,79 @@ mod tests {
             player1.shots_grid().at(&Cell::bounded(1, 0)),
             &CellState::Miss
         );
+        assert_eq!(player1.shots(), 2);
+        assert_eq!(player2.shots(), 0);
+    }
+
+    #[rstest]
+    pub fn test_view(player1_fleet: Fleet, player2_fleet: Fleet) {
+        let mut player1 = Player::new("One", player1_fleet);
+        let mut player2 = Player::new("Two", player2_fleet);
+
+        // The Destroyer of the second player is vertical at I1.
+        player1.attack(&mut player2, &Cell::bounded(8, 0));
+        player1.attack(&mut player2, &Cell::bounded(9, 0));
+        assert!(player1.sunk_ships().is_empty());
+        player1.attack(&mut player2, &Cell::bounded(8, 1));
+        assert_eq!(player1.sunk_kinds(), [ShipKind::Destroyer]);
+        assert_eq!(
+            player1.sunk_ships(),
+            [player2.fleet().ships().last().unwrap().0]
+        );
+
+        let view = player1.view(&player2, &ClassicRules);
+        assert!(!view.is_unexplored(&Cell::bounded(9, 0)));
+        assert!(view.is_unexplored(&Cell::bounded(9, 9)));
+        assert_eq!(view.rules.name(), "Classic");
+
+        // The strategy never fires twice at the same cell.
+        player1.set_strategy(SmartStrategy::with_seed(7));
+        while !player2.has_lost() {
+            let cell = player1.next_move(&player2, &ClassicRules).unwrap();
+            assert_eq!(player1.shots_grid().at(&cell), &CellState::Empty);
+            player1.attack(&mut player2, &cell);
+        }
+        assert_eq!(player1.sunk_ships().len(), 5);
+    }
+
+    #[rstest]
+    pub fn test_ammo(player1_fleet: Fleet, player2_fleet: Fleet) {
+        let mut player1 = Player::new("One", player1_fleet);
+        let mut player2 = Player::new("Two", player2_fleet);
+        assert_eq!(player1.ammo(), None);
+
+        player1.attack(&mut player2, &Cell::bounded(0, 0));
+        assert!(!player1.is_out_of_ammo());
+
+        player1.set_ammo(Some(2));
+        player1.attack(&mut player2, &Cell::bounded(1, 0));
+        assert_eq!(player1.ammo(), Some(1));
+        player1.attack(&mut player2, &Cell::bounded(2, 0));
+        assert!(player1.is_out_of_ammo());
+        assert!(!player1.has_lost());
+    }
+
+    #[rstest]
+    fn test_sunk_ships_of_the_same_kind(player1_fleet: Fleet, player2_fleet: Fleet) {
+        let mut player1 = Player::new("One", player1_fleet);
+        let mut player2 = Player::new("Two", player2_fleet);
+        let extra = ShipKind::Destroyer
+            .ship(Cell::bounded(8, 8), ShipOrientation::Horizontal)
+            .unwrap();
+        let extra = player2.reinforce(extra).unwrap();
+        let (destroyer, _) = player2
+            .fleet()
+            .ships()
+            .find(|(_, ship)| *ship.kind() == ShipKind::Destroyer)
+            .unwrap();
+
+        for (x, y) in [(8, 8), (9, 8), (8, 0), (8, 1)] {
+            player1.attack(&mut player2, &Cell::bounded(x, y));
+        }
+        assert_eq!(player1.sunk_ships(), [extra, destroyer]);
+        assert_eq!(
+            player1.sunk_kinds(),
+            [ShipKind::Destroyer, ShipKind::Destroyer]
+        );
     }
 }
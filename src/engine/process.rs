@@ -0,0 +1,348 @@
+//! Computer players run by an external program, written in any language.
+//!
+//! The [ProcessStrategy] spawns the program once per game and talks to it over its standard
+//! input and output, one line at a time. For every move, the engine writes the view of the
+//! computer player:
+//!
+//! ```text
+//! next_move ........o.......x......... destroyer,cruiser
+//! ```
+//!
+//! The first word is the command, the second one is the shots grid from `A1` to `J10`, row by
+//! row: `.` for an unexplored cell, `o` for a miss, `x` for a hit and `^` for an island. The
+//! last word lists the kinds of the enemy ships sunk, in the order they were sunk, or is `-`
+//! when none is sunk yet. The program answers with the cell to shoot at, like `B7`.
+//!
+use crate::engine::grid::{Cell, CellState};
+#[cfg(feature = "async")]
+use crate::engine::strategy::{Preparation, Signal};
+use crate::engine::strategy::{Strategy, StrategyView};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use std::io::{self, BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver};
+#[cfg(feature = "async")]
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+/// A strategy which asks an external program for every move.
+///
+/// When the program doesn't answer in time, it exits, or it answers with a cell which is not
+/// unexplored, the strategy fires at a random unexplored cell instead. A program which has
+/// failed once is not asked anymore, so that a broken bot can't slow the game down.
+#[derive(Debug)]
+pub struct ProcessStrategy {
+    child: Child,
+    input: ChildStdin,
+    answers: Receiver<String>,
+    timeout: Duration,
+    failed: bool,
+    rng: StdRng,
+
+    // The signal of the move being prepared, notified by the answer of the program, and whether
+    // the request for the next move has already been written.
+    #[cfg(feature = "async")]
+    preparing: Arc<Mutex<Option<Signal>>>,
+    #[cfg(feature = "async")]
+    prepared: bool,
+}
+
+impl ProcessStrategy {
+    /// How long the program can think about every move, by default.
+    pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Spawns the program of the given command line: the program name, followed by its
+    /// arguments.
+    pub fn spawn(command: &[String]) -> io::Result<Self> {
+        Self::spawn_with_rng(command, StdRng::from_os_rng())
+    }
+
+    /// Like [ProcessStrategy::spawn], but the fallback moves are always the same for the same
+    /// seed.
+    pub fn spawn_with_seed(command: &[String], seed: u64) -> io::Result<Self> {
+        Self::spawn_with_rng(command, StdRng::seed_from_u64(seed))
+    }
+
+    fn spawn_with_rng(command: &[String], rng: StdRng) -> io::Result<Self> {
+        let (program, args) = command
+            .split_first()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "empty command"))?;
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let input = child.stdin.take().expect("the input is piped");
+        let output = child.stdout.take().expect("the output is piped");
+
+        // The answers are read on their own thread, so that a program which doesn't answer
+        // can be given up after the timeout.
+        let (sender, answers) = mpsc::channel();
+        #[cfg(feature = "async")]
+        let (preparing, answered) = {
+            let preparing = Arc::<Mutex<Option<Signal>>>::default();
+            (preparing.clone(), preparing)
+        };
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                let Ok(line) = line else { break };
+                if sender.send(line).is_err() {
+                    break;
+                }
+                #[cfg(feature = "async")]
+                notify(&answered);
+            }
+            #[cfg(feature = "async")]
+            notify(&answered);
+        });
+
+        Ok(Self {
+            child,
+            input,
+            answers,
+            timeout: Self::TIMEOUT,
+            failed: false,
+            rng,
+            #[cfg(feature = "async")]
+            preparing,
+            #[cfg(feature = "async")]
+            prepared: false,
+        })
+    }
+
+    /// Sets how long the program can think about every move.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    // Asks the program for its move, returning the cell it has chosen, if it is a valid one.
+    fn asked_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        if self.failed {
+            return None;
+        }
+
+        let Some(answer) = self.answer(view) else {
+            self.failed = true;
+            return None;
+        };
+
+        Cell::from_str(answer.trim())
+            .ok()
+            .filter(|cell| view.is_unexplored(cell))
+    }
+
+    // Returns the answer of the program to the request of the move prepared, or asks it for
+    // its move and waits for the answer.
+    fn answer(&mut self, view: &StrategyView) -> Option<String> {
+        #[cfg(feature = "async")]
+        if std::mem::take(&mut self.prepared) {
+            return self.answers.try_recv().ok();
+        }
+
+        self.ask(view).ok()?;
+        self.answers.recv_timeout(self.timeout).ok()
+    }
+
+    // Writes the request for the next move to the program.
+    fn ask(&mut self, view: &StrategyView) -> io::Result<()> {
+        writeln!(self.input, "next_move {}", request(view))?;
+        self.input.flush()
+    }
+}
+
+impl Strategy for ProcessStrategy {
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        self.asked_move(view).or_else(|| {
+            let unexplored: Vec<Cell> = (0..10)
+                .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+                .filter(|cell| view.is_unexplored(cell))
+                .collect();
+            let fallback = unexplored.choose(&mut self.rng).copied();
+            fallback.or_else(|| Some(Cell::random_with(&mut self.rng)))
+        })
+    }
+
+    /// Asks the program for its move, which is prepared once it answers or once the time to
+    /// think about the move is over.
+    #[cfg(feature = "async")]
+    fn prepare_move(&mut self, view: &StrategyView) -> Option<Preparation> {
+        if self.failed {
+            return None;
+        }
+
+        let signal = Signal::default();
+        *self.preparing.lock().unwrap() = Some(signal.clone());
+        self.ask(view).ok()?;
+        self.prepared = true;
+
+        let (timer, timeout) = (signal.clone(), self.timeout);
+        thread::spawn(move || {
+            thread::sleep(timeout);
+            timer.notify();
+        });
+
+        Some(signal.preparation())
+    }
+}
+
+impl Drop for ProcessStrategy {
+    // The program is stopped with the game.
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+// Notifies the move being prepared, if any, that the program has answered or exited.
+#[cfg(feature = "async")]
+fn notify(preparing: &Mutex<Option<Signal>>) {
+    if let Some(signal) = preparing.lock().unwrap().take() {
+        signal.notify();
+    }
+}
+
+// Returns the shots grid and the sunk ships of the view, as written to the program.
+fn request(view: &StrategyView) -> String {
+    let shots: String = (0..10)
+        .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+        .map(|cell| match view.shots.at(&cell) {
+            CellState::Miss => 'o',
+            CellState::Hit => 'x',
+            CellState::Blocked => '^',
+            CellState::Empty | CellState::Occupied => '.',
+        })
+        .collect();
+    let sunk: Vec<String> = view
+        .sunk
+        .iter()
+        .filter_map(|kind| serde_json::to_value(kind).ok())
+        .filter_map(|value| value.as_str().map(str::to_string))
+        .collect();
+
+    if sunk.is_empty() {
+        format!("{shots} -")
+    } else {
+        format!("{shots} {}", sunk.join(","))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::ShipKind;
+    use crate::engine::grid::Grid;
+    use crate::engine::rules::ClassicRules;
+    use rstest::rstest;
+
+    fn shell(script: &str) -> ProcessStrategy {
+        let command = ["sh".to_string(), "-c".to_string(), script.to_string()];
+        ProcessStrategy::spawn_with_seed(&command, 1)
+            .unwrap()
+            .with_timeout(Duration::from_secs(1))
+    }
+
+    fn view_move(strategy: &mut ProcessStrategy, shots: &Grid, sunk: &[ShipKind]) -> Cell {
+        let opponent = Grid::default();
+        let view = StrategyView::new(shots, sunk, &opponent, &ClassicRules);
+        strategy.next_move(&view).unwrap()
+    }
+
+    #[rstest]
+    fn test_request() {
+        let mut shots = Grid::default();
+        shots.mark(&Cell::bounded(1, 0), CellState::Miss);
+        shots.mark(&Cell::bounded(0, 1), CellState::Hit);
+        let opponent = Grid::default();
+        let sunk = [ShipKind::Destroyer, ShipKind::AircraftCarrier];
+        let view = StrategyView::new(&shots, &sunk, &opponent, &ClassicRules);
+
+        assert_eq!(
+            request(&view),
+            format!(
+                ".o{}x{} destroyer,aircraft_carrier",
+                ".".repeat(8),
+                ".".repeat(89)
+            )
+        );
+    }
+
+    #[rstest]
+    fn test_process_moves() {
+        // The bot shoots at the first unexplored cell of the grid.
+        let mut strategy = shell(
+            r#"while read command shots sunk; do
+                 explored=${shots%%.*}
+                 i=${#explored}
+                 echo "$(echo ABCDEFGHIJ | cut -c$((i % 10 + 1)))$((i / 10 + 1))"
+               done"#,
+        );
+        let mut shots = Grid::default();
+        assert_eq!(view_move(&mut strategy, &shots, &[]), Cell::bounded(0, 0));
+
+        shots.mark(&Cell::bounded(0, 0), CellState::Miss);
+        assert_eq!(view_move(&mut strategy, &shots, &[]), Cell::bounded(1, 0));
+    }
+
+    #[rstest]
+    #[case::exits("exit 0")]
+    #[case::silent("sleep 5")]
+    #[case::off_board("while read line; do echo K11; done")]
+    #[case::explored("while read line; do echo A1; done")]
+    fn test_faulty_process(#[case] script: &str) {
+        let mut strategy = shell(script);
+        let mut shots = Grid::default();
+        shots.mark(&Cell::bounded(0, 0), CellState::Miss);
+
+        for _ in 0..3 {
+            let cell = view_move(&mut strategy, &shots, &[]);
+            assert_eq!(*shots.at(&cell), CellState::Empty);
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[rstest]
+    fn test_prepared_moves() {
+        use crate::engine::strategy::tests::block_on;
+
+        let mut strategy = shell("while read line; do sleep 0.2; echo B2; done");
+        let (shots, opponent) = (Grid::default(), Grid::default());
+        let view = StrategyView::new(&shots, &[], &opponent, &ClassicRules);
+
+        let mut preparation = strategy.prepare_move(&view).unwrap();
+        let context = &mut std::task::Context::from_waker(std::task::Waker::noop());
+        assert!(preparation.as_mut().poll(context).is_pending());
+        block_on(preparation);
+
+        assert_eq!(strategy.next_move(&view), Some(Cell::bounded(1, 1)));
+        assert_eq!(view_move(&mut strategy, &shots, &[]), Cell::bounded(1, 1));
+    }
+
+    #[cfg(feature = "async")]
+    #[rstest]
+    fn test_prepared_moves_timeout() {
+        use crate::engine::strategy::tests::block_on;
+
+        let mut strategy = shell("sleep 5").with_timeout(Duration::from_millis(100));
+        let (shots, opponent) = (Grid::default(), Grid::default());
+        let view = StrategyView::new(&shots, &[], &opponent, &ClassicRules);
+
+        block_on(strategy.prepare_move(&view).unwrap());
+
+        assert!(strategy.next_move(&view).is_some());
+        assert!(strategy.prepare_move(&view).is_none());
+    }
+
+    #[rstest]
+    #[case(&[])]
+    #[case(&["naval-test-no-such-bot"])]
+    fn test_spawn_error(#[case] command: &[&str]) {
+        let command: Vec<String> = command.iter().map(|arg| arg.to_string()).collect();
+        assert!(ProcessStrategy::spawn(&command).is_err());
+    }
+}
@@ -0,0 +1,262 @@
+//! Puzzles: sink a hidden fleet in as few shots as possible.
+//!
+//! A [Puzzle] is played on a single board, against a fleet placed at random and hidden to the
+//! player. There is no opponent shooting back: the player is scored by the shots used to sink
+//! the whole fleet, compared with the *par* of the puzzle.
+//!
+//! Every puzzle comes from a seed, so the same puzzle can be shared by its code, like `"K3Z9QF"`.
+//!
+use crate::engine::fleet::{Fleet, ShipKind};
+use crate::engine::grid::{self, Cell, CellState, Grid};
+use crate::engine::rules::ClassicRules;
+use crate::engine::strategy::{SmartStrategy, Strategy, StrategyView};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use thiserror::Error;
+
+/// Represents a puzzle error.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum PuzzleError {
+    /// The string is not a valid puzzle code.
+    #[error("{0} is not a valid puzzle code")]
+    InvalidCode(String),
+
+    /// The cell can't be shot, because it is out of the board or has already been shot.
+    #[error(transparent)]
+    Shot(#[from] grid::Error),
+
+    /// All the ships have already been sunk.
+    #[error("the puzzle is already solved")]
+    AlreadySolved,
+}
+
+/// A hidden fleet to sink in as few shots as possible.
+#[derive(Debug, Clone)]
+pub struct Puzzle {
+    seed: u64,
+    fleet: Fleet,
+    shots_grid: Grid,
+    shots: u32,
+    par: u32,
+}
+
+impl Puzzle {
+    // Random puzzles use seeds with at most 6 digits in base 36, to keep their codes short.
+    const RANDOM_SEEDS: u64 = 36u64.pow(6);
+    const CODE_LENGTH: usize = 6;
+
+    /// Creates the puzzle of the given seed: the same seed always hides the same fleet.
+    pub fn new(seed: u64) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let fleet = Fleet::build(|kind| kind.random_with(&mut rng));
+        let par = par(&fleet, seed);
+
+        Self {
+            seed,
+            fleet,
+            shots_grid: Grid::default(),
+            shots: 0,
+            par,
+        }
+    }
+
+    /// Creates a new puzzle from a random seed.
+    pub fn random() -> Self {
+        Self::new(rand::rng().random_range(0..Self::RANDOM_SEEDS))
+    }
+
+    /// Creates the puzzle with the given code, as returned by [Puzzle::code].
+    ///
+    /// Codes are case-insensitive.
+    pub fn from_code(code: &str) -> Result<Self, PuzzleError> {
+        let trimmed = code.trim();
+        if trimmed.is_empty() {
+            return Err(PuzzleError::InvalidCode(code.to_string()));
+        }
+
+        u64::from_str_radix(trimmed, 36)
+            .map(Self::new)
+            .map_err(|_| PuzzleError::InvalidCode(code.to_string()))
+    }
+
+    /// Returns the code to share the puzzle: its seed in base 36.
+    pub fn code(&self) -> String {
+        let mut digits = Vec::new();
+        let mut seed = self.seed;
+        while seed > 0 || digits.len() < Self::CODE_LENGTH {
+            digits.push(char::from_digit((seed % 36) as u32, 36).unwrap());
+            seed /= 36;
+        }
+
+        digits.iter().rev().collect::<String>().to_uppercase()
+    }
+
+    /// Returns the seed of the puzzle.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Returns the hidden fleet.
+    pub fn fleet(&self) -> &Fleet {
+        &self.fleet
+    }
+
+    /// Returns the grid of the shots fired so far, with their results.
+    pub fn shots_grid(&self) -> &Grid {
+        &self.shots_grid
+    }
+
+    /// Returns the number of shots fired so far.
+    pub fn shots(&self) -> u32 {
+        self.shots
+    }
+
+    /// Returns the number of shots expected to sink the fleet.
+    ///
+    /// The par is the number of shots the computer, at the normal difficulty, needs to solve the
+    /// same puzzle.
+    pub fn par(&self) -> u32 {
+        self.par
+    }
+
+    /// Returns the shots used above the par, or below it when negative.
+    pub fn score(&self) -> i64 {
+        i64::from(self.shots) - i64::from(self.par)
+    }
+
+    /// Returns `true` when the whole fleet has been sunk.
+    pub fn is_solved(&self) -> bool {
+        self.fleet.is_sunk()
+    }
+
+    /// Shoots the given cell and returns the kind of ship hit, if any.
+    ///
+    /// A cell can be shot only once, and no shot can be fired once the puzzle is solved.
+    pub fn shoot(&mut self, cell: &Cell) -> Result<Option<ShipKind>, PuzzleError> {
+        if self.is_solved() {
+            return Err(PuzzleError::AlreadySolved);
+        } else if *self.shots_grid.at(cell) != CellState::Empty {
+            return Err(grid::Error::AlreadyShot(*cell).into());
+        }
+
+        let hit = self.fleet.hit_at(cell).ship;
+        let state = if hit.is_some() {
+            CellState::Hit
+        } else {
+            CellState::Miss
+        };
+        self.shots_grid.mark(cell, state);
+        self.shots += 1;
+
+        Ok(hit)
+    }
+}
+
+/// Returns the number of shots the computer, at the normal difficulty, needs to sink the fleet.
+///
+/// The computer plays with the given seed, so the par of a fleet is always the same for the same
+/// seed.
+pub fn par(fleet: &Fleet, seed: u64) -> u32 {
+    let mut fleet = fleet.clone();
+    let mut strategy = SmartStrategy::with_seed(seed);
+    let mut grid = Grid::default();
+    let mut sunk = Vec::new();
+    let no_shots = Grid::default();
+    let mut shots = 0;
+    while !fleet.is_sunk() {
+        let cell = strategy
+            .next_move(&StrategyView::new(&grid, &sunk, &no_shots, &ClassicRules))
+            .unwrap();
+        let outcome = fleet.hit_at(&cell);
+        if let Some(kind) = outcome.ship {
+            grid.mark(&cell, CellState::Hit);
+            if outcome.sunk {
+                sunk.push(kind.clone());
+            }
+            strategy.notify_hit(kind);
+        } else {
+            grid.mark(&cell, CellState::Miss);
+        }
+        shots += 1;
+    }
+
+    shots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, "000000")]
+    #[case(35, "00000Z")]
+    #[case(36u64.pow(6) - 1, "ZZZZZZ")]
+    #[case(36u64.pow(6), "1000000")]
+    fn test_code(#[case] seed: u64, #[case] code: &str) {
+        let puzzle = Puzzle::new(seed);
+        assert_eq!(puzzle.code(), code);
+        assert_eq!(Puzzle::from_code(code).unwrap().seed(), seed);
+    }
+
+    #[rstest]
+    fn test_code_is_case_insensitive() {
+        assert_eq!(Puzzle::from_code(" k3z9qf ").unwrap().code(), "K3Z9QF");
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("K3-9QF")]
+    #[case("ZZZZZZZZZZZZZZZZ")]
+    fn test_invalid_code(#[case] code: &str) {
+        assert_eq!(
+            Puzzle::from_code(code).unwrap_err(),
+            PuzzleError::InvalidCode(code.to_string())
+        );
+    }
+
+    #[rstest]
+    fn test_same_seed_same_puzzle() {
+        let first = Puzzle::new(42);
+        let second = Puzzle::new(42);
+        assert_eq!(first.fleet().layout(), second.fleet().layout());
+        assert_eq!(first.par(), second.par());
+        assert!(first.par() >= 17 && first.par() <= 100);
+    }
+
+    #[rstest]
+    fn test_random_puzzle_has_short_code() {
+        assert_eq!(Puzzle::random().code().len(), 6);
+    }
+
+    #[rstest]
+    fn test_solve() {
+        let mut puzzle = Puzzle::new(7);
+        let ship_cells: Vec<Cell> = puzzle
+            .fleet()
+            .as_ref()
+            .iter()
+            .flat_map(|ship| ship.occupied_cells())
+            .collect();
+        let miss = (0..10)
+            .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+            .find(|cell| !ship_cells.contains(cell))
+            .unwrap();
+
+        assert_eq!(puzzle.shoot(&miss), Ok(None));
+        assert_eq!(
+            puzzle.shoot(&miss),
+            Err(PuzzleError::Shot(grid::Error::AlreadyShot(miss)))
+        );
+        assert_eq!(*puzzle.shots_grid().at(&miss), CellState::Miss);
+
+        for cell in ship_cells {
+            assert!(puzzle.shoot(&cell).unwrap().is_some());
+        }
+
+        assert!(puzzle.is_solved());
+        assert_eq!(puzzle.shots(), 18);
+        assert_eq!(puzzle.score(), 18 - i64::from(puzzle.par()));
+        assert_eq!(puzzle.shoot(&miss), Err(PuzzleError::AlreadySolved));
+    }
+}
@@ -0,0 +1,250 @@
+//! Replays: the record of a game, which can be saved and played back move by move.
+//!
+//! A [Replay] keeps the fleets of both players as they were placed and every shot fired during
+//! the game. It is built while the game is played by a [ReplayRecorder], subscribed to the game
+//! as an observer. The [positions](Replay::positions) of a replay are the snapshots of both
+//! grids after every shot, so that a viewer can step forward and rewind through the game.
+//!
+//! The replay only knows the shots: the scans, the mines, the damage of a handicap and the
+//! relocations of the Submarine are not shown.
+//!
+use crate::engine::fleet::{Fleet, FleetError, ShipKind};
+use crate::engine::game::{Game, Side};
+use crate::engine::grid::{Cell, CellState, Grid};
+use crate::engine::observer::GameObserver;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+/// A shot of a replay.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+pub struct ReplayShot {
+    /// The turn of the shot, counted from 1.
+    pub turn: u32,
+
+    /// The side which fired the shot.
+    pub shooter: Side,
+
+    /// The cell which has been shot.
+    pub cell: Cell,
+}
+
+/// The record of a game: the players, their fleets and the shots, in the order they were fired.
+///
+/// The players and the fleets are listed from the human side, and the fleets are encoded like
+/// [Fleet::to_code].
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub players: [String; 2],
+    pub fleets: [String; 2],
+    #[serde(default)]
+    pub shots: Vec<ReplayShot>,
+}
+
+/// A snapshot of a replay, after some of its shots.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Position {
+    /// The grids of the fleets of the human and the computer sides, with the shots fired at
+    /// them by the opponent.
+    pub grids: [Grid; 2],
+
+    /// The last shot fired, if any.
+    pub last: Option<ReplayShot>,
+}
+
+impl Replay {
+    /// Creates the replay of a game which is about to start, without any shot.
+    ///
+    /// It returns `None` if the players of the game have not been set.
+    pub fn new(game: &Game) -> Option<Self> {
+        let (human, computer) = (game.human()?, game.computer()?);
+
+        Some(Self {
+            players: [human.name().to_string(), computer.name().to_string()],
+            fleets: [human.fleet().to_code(), computer.fleet().to_code()],
+            shots: Vec::new(),
+        })
+    }
+
+    /// Returns the snapshots of the game, from the fleets without any shot to the end of the
+    /// game, one after every shot.
+    ///
+    /// An error is returned if the code of a fleet is not valid.
+    pub fn positions(&self) -> Result<Vec<Position>, FleetError> {
+        let mut grids = [
+            Grid::from_ships(Fleet::from_code(&self.fleets[0])?.as_ref()),
+            Grid::from_ships(Fleet::from_code(&self.fleets[1])?.as_ref()),
+        ];
+        let mut positions = Vec::with_capacity(self.shots.len() + 1);
+        positions.push(Position {
+            grids: grids.clone(),
+            last: None,
+        });
+        for shot in &self.shots {
+            let target = &mut grids[shot.shooter.opponent() as usize];
+            let state = match target.at(&shot.cell) {
+                CellState::Occupied | CellState::Hit => CellState::Hit,
+                _ => CellState::Miss,
+            };
+            target.mark(&shot.cell, state);
+            positions.push(Position {
+                grids: grids.clone(),
+                last: Some(*shot),
+            });
+        }
+
+        Ok(positions)
+    }
+}
+
+/// An observer which adds the shots of the game to a shared replay.
+#[derive(Debug, Clone)]
+pub struct ReplayRecorder {
+    replay: Rc<RefCell<Replay>>,
+    turn: u32,
+}
+
+impl ReplayRecorder {
+    /// Creates a recorder adding the shots to the given replay.
+    pub fn new(replay: Rc<RefCell<Replay>>) -> Self {
+        Self { replay, turn: 1 }
+    }
+}
+
+impl GameObserver for ReplayRecorder {
+    fn on_shot(&mut self, shooter: Side, cell: &Cell, _hit: Option<&ShipKind>) {
+        self.replay.borrow_mut().shots.push(ReplayShot {
+            turn: self.turn,
+            shooter,
+            cell: *cell,
+        });
+    }
+
+    fn on_turn_end(&mut self, turn: u32) {
+        self.turn = turn + 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::tests::fixed_fleet;
+    use crate::engine::game::GameBuilder;
+    use crate::engine::strategy::SmartStrategy;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_record_and_replay(fixed_fleet: Fleet) {
+        let mut game = GameBuilder::new()
+            .seed(7)
+            .human("Alice", fixed_fleet)
+            .build()
+            .unwrap();
+        let replay = Rc::new(RefCell::new(Replay::new(&game).unwrap()));
+        game.subscribe(Box::new(ReplayRecorder::new(replay.clone())));
+
+        let events: Vec<_> = game.turns(SmartStrategy::with_seed(1)).collect();
+        let replay = replay.borrow();
+        let positions = replay.positions().unwrap();
+
+        assert_eq!(
+            replay.players,
+            ["Alice".to_string(), "Computer".to_string()]
+        );
+        assert_eq!(
+            replay.shots.len(),
+            events.iter().map(|e| e.shots.len()).sum::<usize>()
+        );
+        assert_eq!(replay.shots.last().unwrap().turn, game.turns_played());
+        assert_eq!(positions.len(), replay.shots.len() + 1);
+        assert_eq!(positions[0].last, None);
+        assert!(
+            positions[0]
+                .grids
+                .iter()
+                .all(|grid| !grid.is_empty() && grid.at(&Cell::bounded(0, 0)) != &CellState::Hit)
+        );
+        // The loser has no ship left afloat at the end of the replay.
+        let end = positions.last().unwrap();
+        let loser = game.winner().unwrap().opponent() as usize;
+        assert!(
+            (0..10)
+                .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+                .all(|cell| end.grids[loser].at(&cell) != &CellState::Occupied)
+        );
+    }
+
+    #[rstest]
+    fn test_positions() {
+        let replay = Replay {
+            players: ["Alice".to_string(), "Bob".to_string()],
+            fleets: [
+                "A1H/A3H/A5H/A7H/A9H".to_string(),
+                "A1V/C1V/E1V/G1V/I1V".to_string(),
+            ],
+            shots: vec![
+                ReplayShot {
+                    turn: 1,
+                    shooter: Side::Human,
+                    cell: Cell::bounded(9, 9),
+                },
+                ReplayShot {
+                    turn: 1,
+                    shooter: Side::Computer,
+                    cell: Cell::bounded(0, 0),
+                },
+            ],
+        };
+
+        let positions = replay.positions().unwrap();
+
+        assert_eq!(positions.len(), 3);
+        assert_eq!(
+            positions[1].grids[1].at(&Cell::bounded(9, 9)),
+            &CellState::Miss
+        );
+        assert_eq!(
+            positions[1].grids[0].at(&Cell::bounded(0, 0)),
+            &CellState::Occupied
+        );
+        assert_eq!(
+            positions[2].grids[0].at(&Cell::bounded(0, 0)),
+            &CellState::Hit
+        );
+        assert_eq!(positions[2].last, Some(replay.shots[1]));
+    }
+
+    #[rstest]
+    fn test_serialization() {
+        let replay = Replay {
+            players: ["Alice".to_string(), "Computer".to_string()],
+            fleets: [
+                "A1H/A3H/A5H/A7H/A9H".to_string(),
+                "A1V/C1V/E1V/G1V/I1V".to_string(),
+            ],
+            shots: vec![ReplayShot {
+                turn: 1,
+                shooter: Side::Computer,
+                cell: Cell::bounded(5, 7),
+            }],
+        };
+
+        let toml = toml::to_string(&replay).unwrap();
+
+        assert!(toml.contains("shooter = \"computer\""));
+        assert!(toml.contains("cell = \"F8\""));
+        assert_eq!(toml::from_str::<Replay>(&toml).unwrap(), replay);
+        assert!(toml::from_str::<Replay>(&toml.replace("F8", "K11")).is_err());
+    }
+
+    #[rstest]
+    fn test_invalid_fleet() {
+        let replay = Replay {
+            players: ["Alice".to_string(), "Computer".to_string()],
+            fleets: ["A1H".to_string(), "A1V/C1V/E1V/G1V/I1V".to_string()],
+            shots: Vec::new(),
+        };
+
+        assert!(replay.positions().is_err());
+    }
+}
@@ -0,0 +1,408 @@
+//! The rules of the naval battle.
+//!
+//! The [Rules] trait collects the decisions which may change from a variant of the game to
+//! another, like who shoots first, how many shots a side fires, which fleets are allowed or when
+//! a player has lost. Every decision has the classic behaviour by default, so that a variant
+//! overrides only what it changes. [ClassicRules] are the rules of
+//! the traditional game, while [SeaMinesRules] hide some mines among the ships and [FogRules]
+//! hide the result of some shots, while [MovingSubmarineRules] let the Submarine move once and
+//! [StreakRules] let a player shoot again after a hit. With [LimitedAmmoRules], a player who
+//! runs out of shells loses, and with [SimultaneousRules] both sides fire at the same time, so
+//! that a game can end in a draw. With [SalvoRules], a side fires a shot for every ship it has
+//! afloat.
+//!
+//! A [Variant] names every ruleset, so that it can be chosen in the configuration. Variants can
+//! be played together with [CombinedRules], like a salvo with limited ammo.
+//!
+use crate::engine::fleet::Fleet;
+use crate::engine::game::{ShotReport, Side};
+use crate::engine::player::Player;
+use rand::{Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use std::fmt::Debug;
+use strum::Display;
+use strum_macros::EnumIter;
+
+/// The variants of the game, each with its own rules.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize, Display, EnumIter)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Variant {
+    /// The [ClassicRules].
+    #[default]
+    Classic,
+
+    /// The [SeaMinesRules].
+    #[strum(to_string = "Sea mines")]
+    SeaMines,
+
+    /// The [FogRules].
+    #[strum(to_string = "Fog of war")]
+    Fog,
+
+    /// The [MovingSubmarineRules].
+    #[strum(to_string = "Moving submarine")]
+    MovingSubmarine,
+
+    /// The [StreakRules].
+    Streak,
+
+    /// The [LimitedAmmoRules].
+    #[strum(to_string = "Limited ammo")]
+    LimitedAmmo,
+
+    /// The [SimultaneousRules].
+    #[strum(to_string = "Simultaneous fire")]
+    Simultaneous,
+
+    /// The [SalvoRules].
+    Salvo,
+}
+
+impl Variant {
+    /// Returns the rules of the variant.
+    pub fn rules(&self) -> Box<dyn Rules> {
+        match self {
+            Variant::Classic => Box::new(ClassicRules),
+            Variant::SeaMines => Box::new(SeaMinesRules),
+            Variant::Fog => Box::new(FogRules),
+            Variant::MovingSubmarine => Box::new(MovingSubmarineRules),
+            Variant::Streak => Box::new(StreakRules),
+            Variant::LimitedAmmo => Box::new(LimitedAmmoRules),
+            Variant::Simultaneous => Box::new(SimultaneousRules),
+            Variant::Salvo => Box::new(SalvoRules),
+        }
+    }
+}
+
+/// The rules used by a [crate::engine::game::Game].
+pub trait Rules: Debug {
+    /// Returns the name of these rules.
+    fn name(&self) -> &str;
+
+    /// Chooses the side which shoots first.
+    ///
+    /// By default, both sides have the same probability to shoot first.
+    fn first_side(&self, rng: &mut dyn RngCore) -> Side {
+        if rng.random_bool(0.5) {
+            Side::Human
+        } else {
+            Side::Computer
+        }
+    }
+
+    /// Returns `true` if the given player has lost the game.
+    ///
+    /// By default, a player loses when their whole fleet is sunk.
+    fn has_lost(&self, player: &Player) -> bool {
+        player.has_lost()
+    }
+
+    /// Returns `true` if the given fleet can be deployed.
+    ///
+    /// By default, every fleet whose ships don't overlap is allowed.
+    fn allows_fleet(&self, _fleet: &Fleet) -> bool {
+        true
+    }
+
+    /// Returns the number of mines every player places on the free cells of their board.
+    ///
+    /// By default, there are no mines.
+    fn mines(&self) -> usize {
+        0
+    }
+
+    /// Returns the width of the band of rows or columns fogged at every turn.
+    ///
+    /// By default, there is no fog.
+    fn fog_width(&self) -> u8 {
+        0
+    }
+
+    /// Returns how many times in a game every player can relocate their Submarine instead of
+    /// firing.
+    ///
+    /// By default, ships never move.
+    fn relocations(&self) -> u8 {
+        0
+    }
+
+    /// Returns the number of shots the given player fires in every turn.
+    ///
+    /// By default, every side fires once per turn.
+    fn shots(&self, _player: &Player) -> usize {
+        1
+    }
+
+    /// Returns `true` if the shooter shoots again in the same turn after the given shot, besides
+    /// the [Rules::shots] of the turn.
+    ///
+    /// By default, a side never shoots again.
+    fn shoots_again(&self, _shot: &ShotReport) -> bool {
+        false
+    }
+
+    /// Returns the number of shells every player has for the whole game.
+    ///
+    /// By default, the shells are unlimited.
+    fn ammo(&self) -> Option<u32> {
+        None
+    }
+
+    /// Returns `true` if both sides choose their shots before any of them is resolved, so that
+    /// both fleets can be sunk in the same turn.
+    ///
+    /// By default, the sides fire in turn and the first to sink the enemy fleet wins.
+    fn simultaneous(&self) -> bool {
+        false
+    }
+
+    /// Returns how many times in a game the human player can ask where to shoot.
+    ///
+    /// By default, three hints are given.
+    fn hints(&self) -> u8 {
+        3
+    }
+}
+
+/// The rules of the traditional game: a random side shoots first, and a player loses when
+/// their whole fleet is sunk.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ClassicRules;
+
+impl Rules for ClassicRules {
+    fn name(&self) -> &str {
+        "Classic"
+    }
+}
+
+/// The classic rules, but every player secretly places two mines on the free cells of their
+/// board: shooting a mined cell makes the attacker lose a random cell of their own fleet.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SeaMinesRules;
+
+impl SeaMinesRules {
+    /// The number of mines of every player.
+    pub const MINES: usize = 2;
+}
+
+impl Rules for SeaMinesRules {
+    fn name(&self) -> &str {
+        "Sea mines"
+    }
+
+    fn mines(&self) -> usize {
+        Self::MINES
+    }
+}
+
+/// The classic rules, but at every turn a random band of rows or columns is fogged: the result
+/// of a shot into the fog stays unknown until the fog lifts.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct FogRules;
+
+impl FogRules {
+    /// The number of rows or columns fogged at every turn.
+    pub const FOG_WIDTH: u8 = 3;
+}
+
+impl Rules for FogRules {
+    fn name(&self) -> &str {
+        "Fog of war"
+    }
+
+    fn fog_width(&self) -> u8 {
+        Self::FOG_WIDTH
+    }
+}
+
+/// The classic rules, but once per game every player can move their Submarine to any valid
+/// empty position instead of firing. The Submarine keeps its damage.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct MovingSubmarineRules;
+
+impl MovingSubmarineRules {
+    /// The number of relocations of every player.
+    pub const RELOCATIONS: u8 = 1;
+}
+
+impl Rules for MovingSubmarineRules {
+    fn name(&self) -> &str {
+        "Moving submarine"
+    }
+
+    fn relocations(&self) -> u8 {
+        Self::RELOCATIONS
+    }
+}
+
+/// The classic rules with the common house rule of the streak: a player keeps shooting while
+/// they hit.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct StreakRules;
+
+impl Rules for StreakRules {
+    fn name(&self) -> &str {
+        "Streak"
+    }
+
+    fn shoots_again(&self, shot: &ShotReport) -> bool {
+        shot.hit.is_some()
+    }
+}
+
+/// The classic rules, but every player has a limited number of shells for the whole game: a
+/// player loses when they have fired the last one without sinking the enemy fleet.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LimitedAmmoRules;
+
+impl LimitedAmmoRules {
+    /// The number of shells of every player.
+    pub const AMMO: u32 = 60;
+}
+
+impl Rules for LimitedAmmoRules {
+    fn name(&self) -> &str {
+        "Limited ammo"
+    }
+
+    fn has_lost(&self, player: &Player) -> bool {
+        player.has_lost() || player.is_out_of_ammo()
+    }
+
+    fn ammo(&self) -> Option<u32> {
+        Some(Self::AMMO)
+    }
+}
+
+/// The classic rules, but both sides fire at the same time: the shots of a turn are resolved
+/// together, and the game is drawn when both fleets are sunk in the same turn.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SimultaneousRules;
+
+impl Rules for SimultaneousRules {
+    fn name(&self) -> &str {
+        "Simultaneous fire"
+    }
+
+    fn simultaneous(&self) -> bool {
+        true
+    }
+}
+
+/// The classic rules with a salvo at every turn: a player fires a shot for every ship they have
+/// afloat, so that they fire fewer and fewer shots as their fleet is sunk.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct SalvoRules;
+
+impl Rules for SalvoRules {
+    fn name(&self) -> &str {
+        "Salvo"
+    }
+
+    fn shots(&self, player: &Player) -> usize {
+        player.fleet().remaining_ships().len()
+    }
+}
+
+/// The rules of several variants played together, like a salvo with limited ammo.
+///
+/// A player loses as soon as one of the rules says so, and a fleet is allowed only if every
+/// rule allows it. The other decisions take the most of every rule: the most mines, the widest
+/// fog, the most shots and the fewest shells. The first rules choose who shoots first.
+#[derive(Debug)]
+pub struct CombinedRules {
+    name: String,
+    rules: Vec<Box<dyn Rules>>,
+}
+
+impl CombinedRules {
+    /// Combines the given rules, which are the classic ones when none is given.
+    pub fn new(rules: Vec<Box<dyn Rules>>) -> Self {
+        let rules = match rules.is_empty() {
+            true => vec![Box::new(ClassicRules) as Box<dyn Rules>],
+            false => rules,
+        };
+        let names: Vec<&str> = rules.iter().map(|rules| rules.name()).collect();
+
+        Self {
+            name: names.join(" + "),
+            rules,
+        }
+    }
+
+    /// Combines the rules of the given variants.
+    pub fn of(variants: &[Variant]) -> Self {
+        Self::new(variants.iter().map(Variant::rules).collect())
+    }
+}
+
+impl Rules for CombinedRules {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn first_side(&self, rng: &mut dyn RngCore) -> Side {
+        self.rules[0].first_side(rng)
+    }
+
+    fn has_lost(&self, player: &Player) -> bool {
+        self.rules.iter().any(|rules| rules.has_lost(player))
+    }
+
+    fn allows_fleet(&self, fleet: &Fleet) -> bool {
+        self.rules.iter().all(|rules| rules.allows_fleet(fleet))
+    }
+
+    fn mines(&self) -> usize {
+        self.rules
+            .iter()
+            .map(|rules| rules.mines())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn fog_width(&self) -> u8 {
+        self.rules
+            .iter()
+            .map(|rules| rules.fog_width())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn relocations(&self) -> u8 {
+        self.rules
+            .iter()
+            .map(|rules| rules.relocations())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn shots(&self, player: &Player) -> usize {
+        self.rules
+            .iter()
+            .map(|rules| rules.shots(player))
+            .max()
+            .unwrap_or(1)
+    }
+
+    fn shoots_again(&self, shot: &ShotReport) -> bool {
+        self.rules.iter().any(|rules| rules.shoots_again(shot))
+    }
+
+    fn ammo(&self) -> Option<u32> {
+        self.rules.iter().filter_map(|rules| rules.ammo()).min()
+    }
+
+    fn simultaneous(&self) -> bool {
+        self.rules.iter().any(|rules| rules.simultaneous())
+    }
+
+    fn hints(&self) -> u8 {
+        self.rules
+            .iter()
+            .map(|rules| rules.hints())
+            .min()
+            .unwrap_or(3)
+    }
+}
@@ -0,0 +1,347 @@
+//! Computer players scripted by the user, in the [Rhai](https://rhai.rs) language.
+//!
+//! A [Script] defines a `next_move(view)` function, which is called for every move of the
+//! computer with what it knows about the battle, and returns the cell to shoot at:
+//!
+//! ```text
+//! fn next_move(view) {
+//!     for cell in view.hits {
+//!         for next in [offset(cell, 1, 0), offset(cell, -1, 0), offset(cell, 0, 1), offset(cell, 0, -1)] {
+//!             if next in view.unexplored {
+//!                 return next;
+//!             }
+//!         }
+//!     }
+//!     view.unexplored[0]
+//! }
+//! ```
+//!
+//! The view is an object map with the `unexplored`, `hits` and `misses` arrays of cells, like
+//! `"B7"`, and the `sunk` array of the kinds of the enemy ships sunk, like `"destroyer"`. The
+//! `offset(cell, dx, dy)` function returns the cell at the given distance, or `()` when it is off
+//! the board.
+//!
+//! The scripts are only available when the crate is built with the `scripting` feature.
+//!
+use crate::engine::fleet::ShipKind;
+use crate::engine::grid::{Cell, CellState};
+#[cfg(feature = "async")]
+use crate::engine::strategy::{Preparation, Signal};
+use crate::engine::strategy::{Strategy, StrategyView};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rhai::{AST, Array, Dynamic, Engine, Map, Scope};
+use std::str::FromStr;
+use std::sync::Arc;
+#[cfg(feature = "async")]
+use std::sync::Mutex;
+#[cfg(feature = "async")]
+use std::thread;
+use thiserror::Error;
+
+/// Reasons why a script can't play.
+#[derive(Debug, Error, PartialEq, Eq, Clone)]
+pub enum ScriptError {
+    /// The script is not valid Rhai code.
+    #[error("cannot compile the script {name}: {message}")]
+    Compile { name: String, message: String },
+
+    /// The script file can't be found.
+    #[error("unknown script {0}")]
+    Unknown(String),
+
+    /// The script doesn't define the function called for every move.
+    #[error("the script {0} has no next_move(view) function")]
+    MissingNextMove(String),
+}
+
+/// A compiled script, ready to play any number of games.
+#[derive(Debug, Clone)]
+pub struct Script {
+    name: String,
+    ast: AST,
+}
+
+impl Script {
+    /// Compiles the given source, checking that it defines a `next_move(view)` function.
+    pub fn compile(name: &str, source: &str) -> Result<Self, ScriptError> {
+        let ast = engine().compile(source).map_err(|e| ScriptError::Compile {
+            name: name.to_string(),
+            message: e.to_string(),
+        })?;
+        if !ast
+            .iter_functions()
+            .any(|function| function.name == "next_move" && function.params.len() == 1)
+        {
+            return Err(ScriptError::MissingNextMove(name.to_string()));
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            ast,
+        })
+    }
+
+    /// Returns the name of the script.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A strategy which asks a [Script] for every move.
+///
+/// When the script fails, or it returns a cell which is not unexplored, the strategy fires at a
+/// random unexplored cell instead, so that a faulty script can't stop the game.
+#[derive(Debug)]
+pub struct ScriptStrategy {
+    script: Script,
+    engine: Arc<Engine>,
+    rng: StdRng,
+
+    // The answer of the script to the move prepared, once it has been called.
+    #[cfg(feature = "async")]
+    prepared: Option<Arc<Mutex<Option<String>>>>,
+}
+
+impl ScriptStrategy {
+    pub fn new(script: Script) -> Self {
+        Self::with_rng(script, StdRng::from_os_rng())
+    }
+
+    /// Creates a strategy whose fallback moves are always the same for the same seed.
+    pub fn with_seed(script: Script, seed: u64) -> Self {
+        Self::with_rng(script, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(script: Script, rng: StdRng) -> Self {
+        Self {
+            script,
+            engine: Arc::new(engine()),
+            rng,
+            #[cfg(feature = "async")]
+            prepared: None,
+        }
+    }
+
+    // Calls the script, unless its answer has been prepared, returning the cell it has chosen,
+    // if it is a valid one.
+    fn scripted_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        #[cfg(feature = "async")]
+        let cell = match self.prepared.take() {
+            Some(answer) => answer.lock().unwrap().take(),
+            None => call(&self.engine, &self.script.ast, script_view(view)),
+        };
+        #[cfg(not(feature = "async"))]
+        let cell = call(&self.engine, &self.script.ast, script_view(view));
+
+        Cell::from_str(&cell?)
+            .ok()
+            .filter(|cell| view.is_unexplored(cell))
+    }
+}
+
+impl Strategy for ScriptStrategy {
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        self.scripted_move(view).or_else(|| {
+            let unexplored = cells(|cell| view.is_unexplored(cell));
+            let fallback = unexplored.choose(&mut self.rng).copied();
+            fallback.or_else(|| Some(Cell::random_with(&mut self.rng)))
+        })
+    }
+
+    // The copy calls the script again for a move prepared by the original.
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        Some(Box::new(Self {
+            script: self.script.clone(),
+            engine: self.engine.clone(),
+            rng: self.rng.clone(),
+            #[cfg(feature = "async")]
+            prepared: None,
+        }))
+    }
+
+    /// Calls the script on a thread of its own, so that a slow script doesn't hold the game.
+    #[cfg(feature = "async")]
+    fn prepare_move(&mut self, view: &StrategyView) -> Option<Preparation> {
+        let (signal, answer) = (Signal::default(), Arc::<Mutex<Option<String>>>::default());
+        let (engine, ast, view) = (
+            self.engine.clone(),
+            self.script.ast.clone(),
+            script_view(view),
+        );
+        let (ready, answered) = (signal.clone(), answer.clone());
+        thread::spawn(move || {
+            *answered.lock().unwrap() = call(&engine, &ast, view);
+            ready.notify();
+        });
+        self.prepared = Some(answer);
+
+        Some(signal.preparation())
+    }
+}
+
+// Calls the `next_move` function of the script with the view, returning its answer.
+fn call(engine: &Engine, ast: &AST, view: Map) -> Option<String> {
+    engine
+        .call_fn(&mut Scope::new(), ast, "next_move", (view,))
+        .ok()
+}
+
+// Returns an engine with the helpers available to the scripts, and a limit on the operations of
+// every call, so that a script stuck in a loop fails instead of hanging the game.
+fn engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(1_000_000);
+    engine.register_fn("offset", |cell: &str, dx: i64, dy: i64| -> Dynamic {
+        Cell::from_str(cell)
+            .ok()
+            .zip(i8::try_from(dx).ok().zip(i8::try_from(dy).ok()))
+            .and_then(|(cell, (dx, dy))| cell.offset(dx, dy))
+            .map_or(Dynamic::UNIT, |cell| Dynamic::from(cell.to_string()))
+    });
+
+    engine
+}
+
+// Returns the view as an object map of arrays of strings, which the scripts can read.
+fn script_view(view: &StrategyView) -> Map {
+    let strings = |cells: Vec<Cell>| -> Array {
+        cells
+            .iter()
+            .map(|cell| Dynamic::from(cell.to_string()))
+            .collect()
+    };
+    let sunk: Array = view
+        .sunk
+        .iter()
+        .map(|kind| Dynamic::from(kind_name(kind)))
+        .collect();
+
+    let mut map = Map::new();
+    map.insert(
+        "unexplored".into(),
+        strings(cells(|cell| view.is_unexplored(cell))).into(),
+    );
+    map.insert(
+        "hits".into(),
+        strings(cells(|cell| *view.shots.at(cell) == CellState::Hit)).into(),
+    );
+    map.insert(
+        "misses".into(),
+        strings(cells(|cell| *view.shots.at(cell) == CellState::Miss)).into(),
+    );
+    map.insert("sunk".into(), sunk.into());
+
+    map
+}
+
+// Returns the cells of the board which satisfy the predicate, row by row.
+fn cells<P: Fn(&Cell) -> bool>(predicate: P) -> Vec<Cell> {
+    (0..10)
+        .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+        .filter(|cell| predicate(cell))
+        .collect()
+}
+
+// Returns the name of the ship kind, as written in the configuration.
+fn kind_name(kind: &ShipKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::grid::Grid;
+    use crate::engine::rules::ClassicRules;
+    use rstest::rstest;
+
+    const HUNTER: &str = r#"
+        fn next_move(view) {
+            for cell in view.hits {
+                for next in [offset(cell, 1, 0), offset(cell, -1, 0)] {
+                    if next in view.unexplored {
+                        return next;
+                    }
+                }
+            }
+            view.unexplored[0]
+        }
+    "#;
+
+    fn view_move(strategy: &mut ScriptStrategy, shots: &Grid, sunk: &[ShipKind]) -> Cell {
+        let opponent = Grid::default();
+        let view = StrategyView::new(shots, sunk, &opponent, &ClassicRules);
+        strategy.next_move(&view).unwrap()
+    }
+
+    #[rstest]
+    #[case("fn next_move(view) { \"A1\" }", Ok(()))]
+    #[case("fn next_move(view) { ", Err(()))]
+    #[case("fn next(view) { \"A1\" }", Err(()))]
+    #[case("fn next_move() { \"A1\" }", Err(()))]
+    fn test_compile(#[case] source: &str, #[case] expected: Result<(), ()>) {
+        assert_eq!(
+            Script::compile("test", source).map(|_| ()).map_err(|_| ()),
+            expected
+        );
+    }
+
+    #[rstest]
+    fn test_script_moves() {
+        let mut strategy = ScriptStrategy::with_seed(Script::compile("hunter", HUNTER).unwrap(), 1);
+        let mut shots = Grid::default();
+        assert_eq!(view_move(&mut strategy, &shots, &[]), Cell::bounded(0, 0));
+
+        shots.mark(&Cell::bounded(0, 0), CellState::Miss);
+        shots.mark(&Cell::bounded(4, 4), CellState::Hit);
+        assert_eq!(view_move(&mut strategy, &shots, &[]), Cell::bounded(5, 4));
+    }
+
+    #[cfg(feature = "async")]
+    #[rstest]
+    fn test_prepared_moves() {
+        use crate::engine::strategy::tests::block_on;
+
+        let mut strategy = ScriptStrategy::with_seed(Script::compile("hunter", HUNTER).unwrap(), 1);
+        let mut shots = Grid::default();
+        shots.mark(&Cell::bounded(4, 4), CellState::Hit);
+        let opponent = Grid::default();
+        let view = StrategyView::new(&shots, &[], &opponent, &ClassicRules);
+
+        block_on(strategy.prepare_move(&view).unwrap());
+
+        assert_eq!(strategy.next_move(&view), Some(Cell::bounded(5, 4)));
+        assert_eq!(strategy.next_move(&view), Some(Cell::bounded(5, 4)));
+    }
+
+    #[rstest]
+    fn test_script_view() {
+        let script =
+            r#"fn next_move(view) { if view.sunk == ["destroyer"] { "J10" } else { "A1" } }"#;
+        let mut strategy = ScriptStrategy::with_seed(Script::compile("sunk", script).unwrap(), 1);
+
+        let shots = Grid::default();
+        assert_eq!(
+            view_move(&mut strategy, &shots, &[ShipKind::Destroyer]),
+            Cell::bounded(9, 9)
+        );
+    }
+
+    #[rstest]
+    #[case("fn next_move(view) { \"A1\" }")]
+    #[case("fn next_move(view) { \"K11\" }")]
+    #[case("fn next_move(view) { 42 }")]
+    #[case("fn next_move(view) { loop {} }")]
+    fn test_faulty_script(#[case] source: &str) {
+        let mut strategy = ScriptStrategy::with_seed(Script::compile("faulty", source).unwrap(), 1);
+        let mut shots = Grid::default();
+        shots.mark(&Cell::bounded(0, 0), CellState::Miss);
+
+        let cell = view_move(&mut strategy, &shots, &[]);
+        assert!(*shots.at(&cell) == CellState::Empty);
+    }
+}
@@ -0,0 +1,386 @@
+//! Series of games against the computer.
+//!
+//! A [Series] is a sequence of games which ends when a side has won the majority of them, like
+//! in a "best of 3" or "best of 5" match. The series keeps the score, and starts every game
+//! with the same player name, difficulty, time control, variant and handicap.
+//!
+//! A series can also be the single game of a [DailyChallenge], which has a par: the shots the
+//! computer needs to sink the same fleet.
+//!
+use crate::engine::action::PowerUps;
+use crate::engine::daily::DailyChallenge;
+use crate::engine::fleet::Fleet;
+use crate::engine::game::{Difficulty, Game, GameBuilder, GameError, GamePhase, Handicap, Side};
+use crate::engine::grid::Cell;
+use crate::engine::map::Map;
+use crate::engine::puzzle;
+use crate::engine::rules::Variant;
+#[cfg(feature = "scripting")]
+use crate::engine::script::Script;
+use crate::engine::strategy::{Adaptation, MonteCarloStrategy};
+use std::time::Duration;
+
+/// The number of games won by each side.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Score {
+    /// The games won by the human player.
+    pub human: u8,
+
+    /// The games won by the computer player.
+    pub computer: u8,
+}
+
+impl Score {
+    /// Returns the number of games won by the given side.
+    pub fn wins(&self, side: Side) -> u8 {
+        match side {
+            Side::Human => self.human,
+            Side::Computer => self.computer,
+        }
+    }
+
+    fn add_win(&mut self, side: Side) {
+        match side {
+            Side::Human => self.human += 1,
+            Side::Computer => self.computer += 1,
+        }
+    }
+}
+
+/// A "best of N" series of games between a human player and the computer.
+///
+/// The series wraps the game being played. When a game is over, the next one is started by
+/// [Series::start_game], until a side has won more than half of the games.
+#[derive(Debug)]
+pub struct Series {
+    best_of: u8,
+    player_name: String,
+    difficulty: Difficulty,
+    solver_budget: usize,
+    adaptation: Adaptation,
+    habits: Vec<u32>,
+    #[cfg(feature = "scripting")]
+    script: Option<Script>,
+    opponent_command: Option<Vec<String>>,
+    time_control: Option<Duration>,
+    handicap: u8,
+    handicap_option: Handicap,
+    power_ups: PowerUps,
+    variant: Variant,
+    map: Option<Map>,
+    score: Score,
+    game: Game,
+    daily: Option<DailyChallenge>,
+    par: Option<u32>,
+}
+
+impl Series {
+    /// Creates a new series of at most `best_of` games, which is at least 1.
+    ///
+    /// The first game is not started yet.
+    pub fn new(best_of: u8, player_name: &str, difficulty: Difficulty) -> Self {
+        Self {
+            best_of: best_of.max(1),
+            player_name: player_name.to_string(),
+            difficulty,
+            solver_budget: MonteCarloStrategy::NODE_BUDGET,
+            adaptation: Adaptation::default(),
+            habits: Vec::new(),
+            #[cfg(feature = "scripting")]
+            script: None,
+            opponent_command: None,
+            time_control: None,
+            handicap: 0,
+            handicap_option: Handicap::default(),
+            power_ups: PowerUps::default(),
+            variant: Variant::default(),
+            map: None,
+            score: Score::default(),
+            game: Game::new(),
+            daily: None,
+            par: None,
+        }
+    }
+
+    /// Creates a series of a single game: the battle of the given daily challenge.
+    pub fn daily(challenge: DailyChallenge, player_name: &str, difficulty: Difficulty) -> Self {
+        Self {
+            daily: Some(challenge),
+            ..Self::new(1, player_name, difficulty)
+        }
+    }
+
+    /// Returns the daily challenge played by the series, if any.
+    pub fn daily_challenge(&self) -> Option<DailyChallenge> {
+        self.daily
+    }
+
+    /// Returns the par of the daily challenge, once its game has started.
+    pub fn par(&self) -> Option<u32> {
+        self.par
+    }
+
+    /// Set the time of each side in every game, or `None` to play without time control.
+    pub fn set_time_control(&mut self, time: Option<Duration>) {
+        self.time_control = time;
+    }
+
+    /// Set how many placements the hard computer player can explore to solve the endgame of
+    /// every game.
+    pub fn set_solver_budget(&mut self, nodes: usize) {
+        self.solver_budget = nodes;
+    }
+
+    /// Set how strongly the adaptive computer player keeps every game close.
+    pub fn set_adaptation(&mut self, adaptation: Adaptation) {
+        self.adaptation = adaptation;
+    }
+
+    /// Set the placement habits of the human player, for the learning computer player of the
+    /// next games.
+    pub fn set_habits(&mut self, habits: &[u32]) {
+        self.habits = habits.to_vec();
+    }
+
+    /// Set the script playing the computer player of the next games, or `None` to play against
+    /// the built-in computer.
+    #[cfg(feature = "scripting")]
+    pub fn set_script(&mut self, script: Option<Script>) {
+        self.script = script;
+    }
+
+    /// Set the command line of the external program playing the computer player of the next
+    /// games, or `None` to play against the built-in computer.
+    pub fn set_opponent_command(&mut self, command: Option<Vec<String>>) {
+        self.opponent_command = command;
+    }
+
+    /// Set the number of cells of the human fleet damaged before every game, as a handicap.
+    pub fn set_handicap(&mut self, cells: u8) {
+        self.handicap = cells;
+    }
+
+    /// Set the handicap which balances every game.
+    pub fn set_handicap_option(&mut self, handicap: Handicap) {
+        self.handicap_option = handicap;
+    }
+
+    /// Set the power-ups of each side in every game.
+    pub fn set_power_ups(&mut self, power_ups: PowerUps) {
+        self.power_ups = power_ups;
+    }
+
+    /// Set the variant of every game.
+    pub fn set_variant(&mut self, variant: Variant) {
+        self.variant = variant;
+    }
+
+    /// Returns the variant of the games.
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    /// Set the map of every game, or `None` to play on the open sea.
+    pub fn set_map(&mut self, map: Option<Map>) {
+        self.map = map;
+    }
+
+    /// Returns the maximum number of games of the series.
+    pub fn best_of(&self) -> u8 {
+        self.best_of
+    }
+
+    /// Returns the name of the human player.
+    pub fn player_name(&self) -> &str {
+        &self.player_name
+    }
+
+    /// Returns the difficulty of the computer player.
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// Returns the current game.
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    /// Returns the current game, to play it.
+    pub fn game_mut(&mut self) -> &mut Game {
+        &mut self.game
+    }
+
+    /// Returns the score of the series, including the current game if it is over.
+    pub fn score(&self) -> Score {
+        let mut score = self.score;
+        if let GamePhase::Finished { winner } = self.game.phase() {
+            score.add_win(winner);
+        }
+
+        score
+    }
+
+    /// Returns the side which has won the series, if any.
+    pub fn winner(&self) -> Option<Side> {
+        let score = self.score();
+        [Side::Human, Side::Computer]
+            .into_iter()
+            .find(|side| score.wins(*side) > self.best_of / 2)
+    }
+
+    /// Returns `true` when a side has won the series.
+    pub fn is_over(&self) -> bool {
+        self.winner().is_some()
+    }
+
+    /// Starts the next game of the series, with the given fleet for the human player.
+    ///
+    /// An error is returned if the current game is still in progress or if the series is over.
+    pub fn start_game(&mut self, fleet: Fleet) -> Result<(), GameError> {
+        self.start_game_with_mines(fleet, &[])
+    }
+
+    /// Starts the next game of the series, with the given fleet and mines for the human player.
+    ///
+    /// The mines are needed only by the variants with mines: the ones which are not given are
+    /// placed at random.
+    pub fn start_game_with_mines(&mut self, fleet: Fleet, mines: &[Cell]) -> Result<(), GameError> {
+        if self.is_over() {
+            return Err(GameError::SeriesOver);
+        } else if self.game.phase() == GamePhase::InProgress {
+            return Err(GameError::AlreadyStarted);
+        }
+
+        let builder = match self.daily {
+            Some(challenge) => challenge.builder(),
+            None => GameBuilder::new(),
+        };
+        let mut builder = builder
+            .variant(self.variant)
+            .human(&self.player_name, fleet)
+            .mines(mines)
+            .computer(self.difficulty)
+            .solver_budget(self.solver_budget)
+            .adaptation(self.adaptation)
+            .habits(&self.habits)
+            .handicap(self.handicap)
+            .handicap_option(self.handicap_option)
+            .power_ups(self.power_ups);
+        if let Some(time) = self.time_control {
+            builder = builder.time_control(time);
+        }
+        if let Some(map) = &self.map {
+            builder = builder.map(map.clone());
+        }
+        #[cfg(feature = "scripting")]
+        if let Some(script) = &self.script {
+            builder = builder.script(script.clone());
+        }
+        if let Some(command) = &self.opponent_command {
+            builder = builder.opponent_command(command);
+        }
+
+        let game = builder.build()?;
+        if let Some(challenge) = self.daily {
+            let computer = game.computer().unwrap();
+            self.par = Some(puzzle::par(computer.fleet(), challenge.seed()));
+        }
+        self.score = self.score();
+        self.game = game;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::tests::fixed_fleet;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_series(fixed_fleet: Fleet) {
+        let mut series = Series::new(3, "Human", Difficulty::Easy);
+        assert_eq!(series.score(), Score::default());
+        assert_eq!(series.game().phase(), GamePhase::Setup);
+
+        series.start_game(fixed_fleet.clone()).unwrap();
+        assert_eq!(series.game().human().unwrap().name(), "Human");
+        assert_eq!(
+            series.start_game(fixed_fleet.clone()),
+            Err(GameError::AlreadyStarted)
+        );
+
+        series.game_mut().resign(Side::Human).unwrap();
+        assert_eq!(
+            series.score(),
+            Score {
+                human: 0,
+                computer: 1
+            }
+        );
+        assert!(!series.is_over());
+
+        series.start_game(fixed_fleet.clone()).unwrap();
+        assert_eq!(series.score().computer, 1);
+        series.game_mut().resign(Side::Computer).unwrap();
+        series.start_game(fixed_fleet.clone()).unwrap();
+        series.game_mut().resign(Side::Human).unwrap();
+
+        assert_eq!(
+            series.score(),
+            Score {
+                human: 1,
+                computer: 2
+            }
+        );
+        assert_eq!(series.winner(), Some(Side::Computer));
+        assert_eq!(series.start_game(fixed_fleet), Err(GameError::SeriesOver));
+    }
+
+    #[rstest]
+    #[case(0, 1)]
+    #[case(1, 1)]
+    #[case(5, 3)]
+    fn test_wins_needed(fixed_fleet: Fleet, #[case] best_of: u8, #[case] wins: u8) {
+        let mut series = Series::new(best_of, "Human", Difficulty::Easy);
+        for _ in 0..wins {
+            assert!(!series.is_over());
+            series.start_game(fixed_fleet.clone()).unwrap();
+            series.game_mut().resign(Side::Computer).unwrap();
+        }
+
+        assert_eq!(series.winner(), Some(Side::Human));
+    }
+
+    #[rstest]
+    fn test_power_ups_in_every_game(fixed_fleet: Fleet) {
+        let mut series = Series::new(3, "Human", Difficulty::Easy);
+        series.set_power_ups(PowerUps::STANDARD);
+        for _ in 0..2 {
+            series.start_game(fixed_fleet.clone()).unwrap();
+            assert_eq!(series.game().power_ups(Side::Human), PowerUps::STANDARD);
+            series.game_mut().resign(Side::Computer).unwrap();
+        }
+    }
+
+    #[rstest]
+    fn test_daily_series(fixed_fleet: Fleet) {
+        let challenge = DailyChallenge::for_day(20_000);
+        let mut series = Series::daily(challenge, "Human", Difficulty::Normal);
+        assert_eq!(series.best_of(), 1);
+        assert_eq!(series.daily_challenge(), Some(challenge));
+        assert_eq!(series.par(), None);
+
+        series.start_game(fixed_fleet.clone()).unwrap();
+        let par = series.par().unwrap();
+        assert!((17..=100).contains(&par));
+
+        let mut again = Series::daily(challenge, "Other", Difficulty::Easy);
+        again.start_game(fixed_fleet).unwrap();
+        assert_eq!(again.par(), Some(par));
+
+        series.game_mut().resign(Side::Human).unwrap();
+        assert_eq!(series.winner(), Some(Side::Computer));
+    }
+}
@@ -1,100 +1,938 @@
-use crate::engine::fleet::ShipKind;
-use crate::engine::grid::Cell;
+use crate::engine::fleet::{FleetSampler, Ship, ShipKind};
+use crate::engine::grid::{Cell, CellState, Grid};
+use crate::engine::parallel;
+use crate::engine::rules::Rules;
+use rand::rngs::StdRng;
+use rand::seq::IndexedRandom;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
 use std::fmt::Debug;
+#[cfg(feature = "async")]
+use std::future::{self, Future};
+#[cfg(feature = "async")]
+use std::pin::Pin;
+#[cfg(feature = "async")]
+use std::sync::{Arc, Mutex};
+#[cfg(feature = "async")]
+use std::task::{Poll, Waker};
+use strum::{Display, IntoEnumIterator};
+use strum_macros::{EnumIter, EnumString};
+use tracing::debug;
+
+/// What a player knows about the battle when choosing their next move.
+///
+/// The view is built by the engine before every move, so a strategy doesn't need to keep its
+/// own copy of the board.
+#[derive(Debug, Clone, Copy)]
+pub struct StrategyView<'a> {
+    /// The shots grid of the player: the hits, the misses and the charted islands.
+    pub shots: &'a Grid,
+
+    /// The enemy ships sunk by the player, in the order they were sunk.
+    pub sunk: &'a [ShipKind],
+
+    /// The shots grid of the opponent: where they fired at the player's fleet, and with which
+    /// result.
+    pub opponent_shots: &'a Grid,
+
+    /// The rules of the game.
+    pub rules: &'a dyn Rules,
+}
+
+impl<'a> StrategyView<'a> {
+    /// Creates the view of a player with the given shots grid and sunk ships, against an
+    /// opponent with the given shots grid.
+    pub fn new(
+        shots: &'a Grid,
+        sunk: &'a [ShipKind],
+        opponent_shots: &'a Grid,
+        rules: &'a dyn Rules,
+    ) -> Self {
+        Self {
+            shots,
+            sunk,
+            opponent_shots,
+            rules,
+        }
+    }
+
+    /// Checks whether the player has not shot at the cell yet, and it is not an island.
+    pub fn is_unexplored(&self, cell: &Cell) -> bool {
+        *self.shots.at(cell) == CellState::Empty
+    }
+}
 
 /// The Strategy trait for implementing different move strategies for players.
 ///
 /// Every player uses its given stategy implementation to decide which is the next move.
 /// If the strategy returns `None`, the game engine should ask the user for the next move.
 pub trait Strategy: Debug {
-    /// Return the next move for the player.
+    /// Return the next move for the player, who knows what the view shows.
     ///
     /// It can return `None` if no move is available (e.g., for human players).
-    fn next_move(&mut self) -> Option<Cell>;
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell>;
 
     /// Notify the strategy that a ship has been hit and which was it.
     fn notify_hit(&mut self, _kind: ShipKind) {}
+
+    /// Notify the strategy that an enemy ship occupies the given cells.
+    fn notify_revealed(&mut self, _cells: &[Cell]) {}
+
+    /// Returns how much the strategy likes every unexplored cell for its next move, from 0 to 1,
+    /// so that its reasoning can be shown. The heatmap doesn't change the next moves.
+    ///
+    /// It returns `None` if the strategy doesn't weigh the cells.
+    fn heatmap(&mut self, _view: &StrategyView) -> Option<HashMap<Cell, f32>> {
+        None
+    }
+
+    /// Returns a copy of the strategy, with what it has learnt so far, which a
+    /// [snapshot](crate::engine::game::Snapshot) of the game brings back when it is restored.
+    ///
+    /// It returns `None` if the strategy can't be copied, like another program: then it keeps
+    /// what it learns after the snapshot.
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        None
+    }
+
+    /// Starts choosing the next move for the player, who knows what the view shows, and
+    /// returns a future which completes once [Strategy::next_move] can answer at once with the
+    /// same view.
+    ///
+    /// A strategy which waits for its moves, like another program, lets an asynchronous game
+    /// await them instead. It returns `None` if it never waits.
+    #[cfg(feature = "async")]
+    fn prepare_move(&mut self, _view: &StrategyView) -> Option<Preparation> {
+        None
+    }
+}
+
+/// A move being prepared by a [Strategy], built with the `async` feature.
+#[cfg(feature = "async")]
+pub type Preparation = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+// Tells a preparation that the move is ready, from any thread.
+#[cfg(feature = "async")]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Signal(Arc<Mutex<(bool, Option<Waker>)>>);
+
+#[cfg(feature = "async")]
+impl Signal {
+    // Completes the preparations of the signal. Notifying it again does nothing.
+    pub(crate) fn notify(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.0 = true;
+        if let Some(waker) = state.1.take() {
+            waker.wake();
+        }
+    }
+
+    // Returns a preparation which completes once the signal is notified.
+    pub(crate) fn preparation(&self) -> Preparation {
+        let signal = self.clone();
+        Box::pin(future::poll_fn(move |context| {
+            let mut state = signal.0.lock().unwrap();
+            match state.0 {
+                true => Poll::Ready(()),
+                false => {
+                    state.1 = Some(context.waker().clone());
+                    Poll::Pending
+                }
+            }
+        }))
+    }
+}
+
+impl<S: Strategy + ?Sized> Strategy for Box<S> {
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        (**self).next_move(view)
+    }
+
+    fn notify_hit(&mut self, kind: ShipKind) {
+        (**self).notify_hit(kind)
+    }
+
+    fn notify_revealed(&mut self, cells: &[Cell]) {
+        (**self).notify_revealed(cells)
+    }
+
+    fn heatmap(&mut self, view: &StrategyView) -> Option<HashMap<Cell, f32>> {
+        (**self).heatmap(view)
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        (**self).snapshot()
+    }
+
+    #[cfg(feature = "async")]
+    fn prepare_move(&mut self, view: &StrategyView) -> Option<Preparation> {
+        (**self).prepare_move(view)
+    }
+}
+
+impl<S: Strategy + ?Sized> Strategy for &mut S {
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        (**self).next_move(view)
+    }
+
+    fn notify_hit(&mut self, kind: ShipKind) {
+        (**self).notify_hit(kind)
+    }
+
+    fn notify_revealed(&mut self, cells: &[Cell]) {
+        (**self).notify_revealed(cells)
+    }
+
+    fn heatmap(&mut self, view: &StrategyView) -> Option<HashMap<Cell, f32>> {
+        (**self).heatmap(view)
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        (**self).snapshot()
+    }
+
+    #[cfg(feature = "async")]
+    fn prepare_move(&mut self, view: &StrategyView) -> Option<Preparation> {
+        (**self).prepare_move(view)
+    }
+}
+
+/// The strategies which can be chosen by name, like the ones of the players of a match between
+/// two computers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Display, EnumIter, EnumString)]
+#[strum(serialize_all = "lowercase")]
+pub enum StrategyKind {
+    /// A [RandomStrategy].
+    Random,
+
+    /// A [SmartStrategy].
+    Smart,
+
+    /// A [MonteCarloStrategy], which fires where the heat of the ships is highest.
+    Heatmap,
+}
+
+impl StrategyKind {
+    /// Creates a new strategy of this kind.
+    pub fn strategy(&self) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Random => Box::new(RandomStrategy::new()),
+            StrategyKind::Smart => Box::new(SmartStrategy::new()),
+            StrategyKind::Heatmap => Box::new(MonteCarloStrategy::new()),
+        }
+    }
+
+    /// Creates a new strategy of this kind, which always plays the same moves for the same seed.
+    pub fn strategy_with_seed(&self, seed: u64) -> Box<dyn Strategy> {
+        match self {
+            StrategyKind::Random => Box::new(RandomStrategy::with_seed(seed)),
+            StrategyKind::Smart => Box::new(SmartStrategy::with_seed(seed)),
+            StrategyKind::Heatmap => Box::new(MonteCarloStrategy::with_seed(seed)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RandomStrategy {
+    rng: StdRng,
+}
+
+impl RandomStrategy {
+    pub fn new() -> Self {
+        Self {
+            rng: StdRng::from_os_rng(),
+        }
+    }
+
+    /// Creates a strategy which always plays the same moves for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
 }
 
-#[derive(Debug)]
-pub struct RandomStrategy;
+impl Default for RandomStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Strategy for RandomStrategy {
-    fn next_move(&mut self) -> Option<Cell> {
-        Some(Cell::random())
+    fn next_move(&mut self, _view: &StrategyView) -> Option<Cell> {
+        Some(Cell::random_with(&mut self.rng))
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        Some(Box::new(self.clone()))
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct SmartStrategy {
-    moves: Vec<Cell>,
+    moves: HashSet<Cell>,
+    last_move: Option<Cell>,
+    // The cells hit so far.
+    hits: Vec<Cell>,
+    // The cells never chosen at random, drawn by swapping them out: a cell shot since it was
+    // added is skipped when drawn.
+    unshot: Vec<Cell>,
     candidates_moves: Vec<Cell>,
+    rng: StdRng,
 }
 
 impl SmartStrategy {
     pub fn new() -> Self {
+        Self::with_rng(StdRng::from_os_rng())
+    }
+
+    /// Creates a strategy which always plays the same moves for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
         Self {
-            moves: Vec::new(),
+            moves: HashSet::new(),
+            last_move: None,
+            hits: Vec::new(),
+            unshot: (0..100).map(|i| Cell::bounded(i % 10, i / 10)).collect(),
             candidates_moves: Vec::new(),
+            rng,
         }
     }
+
+    // Draws a cell never chosen at random, or `None` when there is none left.
+    fn random_move(&mut self) -> Option<Cell> {
+        if self.unshot.is_empty() {
+            return None;
+        }
+        let index = self.rng.random_range(0..self.unshot.len());
+        Some(self.unshot.swap_remove(index))
+    }
+}
+
+impl Default for SmartStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Strategy for SmartStrategy {
-    fn next_move(&mut self) -> Option<Cell> {
-        let next = loop {
-            let cell = if let Some(cell) = self.candidates_moves.pop() {
-                cell
-            } else {
-                Cell::random()
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        let (next, targeting) = loop {
+            let (cell, targeting) = match self.candidates_moves.pop() {
+                Some(cell) => (cell, true),
+                None => (self.random_move()?, false),
             };
 
-            if !self.moves.contains(&cell) {
-                break cell;
+            if !self.moves.contains(&cell) && view.is_unexplored(&cell) {
+                break (cell, targeting);
             }
         };
 
-        self.moves.push(next);
+        debug!(cell = %next, targeting, "smart move");
+        self.moves.insert(next);
+        self.last_move = Some(next);
 
         Some(next)
     }
 
     fn notify_hit(&mut self, kind: ShipKind) {
+        let Some(last_move) = self.last_move else {
+            return;
+        };
         let size = kind.size();
-        let last_move = *self.moves.last().unwrap();
+
+        // An earlier hit next to this one, or at the end of a line of hits leading to it, tells
+        // the orientation of the ship: it lies on the line through both hits. The farthest one
+        // within the reach of the ship bounds it best.
+        let previous = self
+            .hits
+            .iter()
+            .filter(|cell| cell.manhattan_distance(&last_move) < size)
+            .filter(|cell| {
+                Cell::between(cell, &last_move)
+                    .is_some_and(|between| between.iter().all(|cell| self.hits.contains(cell)))
+            })
+            .max_by_key(|cell| cell.manhattan_distance(&last_move))
+            .copied();
+        self.hits.push(last_move);
+        let on_line = |cell: &Cell| match previous {
+            Some(previous) => cell.is_aligned_with(&previous) && cell.is_aligned_with(&last_move),
+            None => true,
+        };
+
+        // The cells left by the earlier hit across the line, or too far along it, can't be part
+        // of the ship.
+        if let Some(previous) = previous {
+            self.candidates_moves.retain(|cell| {
+                !cell.is_aligned_with(&previous)
+                    || cell.manhattan_distance(&previous) >= size
+                    || (on_line(cell) && cell.manhattan_distance(&last_move) < size)
+            });
+        }
 
         let mut new_candidates = Vec::new();
 
-        for i in 1..size {
-            if last_move.x() + i < 10
-                && let Ok(cell) = Cell::new(last_move.x() + i, last_move.y())
-                && !self.moves.contains(&cell)
-            {
-                new_candidates.push(cell);
+        for i in 1..size as i8 {
+            for (dx, dy) in [(i, 0), (-i, 0), (0, i), (0, -i)] {
+                if let Some(cell) = last_move.offset(dx, dy)
+                    && !self.moves.contains(&cell)
+                    && on_line(&cell)
+                    && previous.is_none_or(|previous| cell.manhattan_distance(&previous) < size)
+                {
+                    new_candidates.push(cell);
+                }
             }
+        }
+
+        self.candidates_moves.extend(new_candidates);
+    }
 
-            if last_move.x() >= i
-                && let Ok(cell) = Cell::new(last_move.x() - i, last_move.y())
-                && !self.moves.contains(&cell)
-            {
-                new_candidates.push(cell);
+    fn notify_revealed(&mut self, cells: &[Cell]) {
+        let new_candidates = cells.iter().filter(|cell| !self.moves.contains(cell));
+        self.candidates_moves.extend(new_candidates);
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+/// A strategy which guesses where the enemy ships are.
+///
+/// Before every move, it draws many fleets which agree with the hits, the misses and the sunk
+/// ships of its view, then it fires at the unexplored cell occupied most often by those fleets.
+/// When no fleet agrees with the view, like when the enemy has an extra ship, it fires next to
+/// its hits, or at random.
+///
+/// In the endgame, when few placements of the ships are left, every fleet which agrees with the
+/// view is enumerated instead, as long as the search fits in the node budget: the cell chosen is
+/// then the one most likely to hit.
+///
+/// The strategy may also remember the habits of the opponent: the cells where they have placed
+/// their ships in the past games count up to twice as much.
+#[derive(Debug, Clone)]
+pub struct MonteCarloStrategy {
+    rng: StdRng,
+    node_budget: usize,
+    habits: Vec<u32>,
+}
+
+impl MonteCarloStrategy {
+    // How many fleets are drawn for every move, in batches which draw their share of them with
+    // their own generator.
+    const SAMPLES: usize = 1000;
+    const BATCHES: usize = 8;
+
+    /// The default number of placements explored to solve the endgame exactly.
+    pub const NODE_BUDGET: usize = 100_000;
+
+    pub fn new() -> Self {
+        Self::with_rng(StdRng::from_os_rng())
+    }
+
+    /// Creates a strategy which always plays the same moves for the same seed.
+    pub fn with_seed(seed: u64) -> Self {
+        Self::with_rng(StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(rng: StdRng) -> Self {
+        Self {
+            rng,
+            node_budget: Self::NODE_BUDGET,
+            habits: Vec::new(),
+        }
+    }
+
+    /// Sets how many placements of the ships can be explored to solve the endgame exactly, or 0
+    /// to always draw the fleets at random.
+    pub fn with_node_budget(mut self, node_budget: usize) -> Self {
+        self.node_budget = node_budget;
+        self
+    }
+
+    /// Biases the moves toward the cells where the opponent usually places their ships.
+    ///
+    /// The habits count how many times the opponent has placed a ship on every cell, from `A1`
+    /// to `J10` row by row.
+    pub fn with_habits(mut self, habits: &[u32]) -> Self {
+        self.habits = habits.to_vec();
+        self
+    }
+
+    // Returns the weight of every cell: the same for all of them without habits, or up to twice
+    // as much for the cells used most often by the opponent.
+    fn weights(&self) -> [u64; 100] {
+        let most_used = self.habits.iter().max().copied().unwrap_or(0).max(1);
+        let mut weights = [u64::from(most_used); 100];
+        for (weight, habit) in weights.iter_mut().zip(&self.habits) {
+            *weight += u64::from(*habit);
+        }
+
+        weights
+    }
+
+    // Counts the fleets which agree with the view, all of them if the budget is enough, or the
+    // ones drawn at random. A batch stops at the first fleet which can't be drawn within the
+    // budget of the sampler, as the next ones would hardly be luckier.
+    //
+    // The batches are seeded from the generator of the strategy, so that the moves are the same
+    // for the same seed, whether the batches are drawn on all the cores or not.
+    fn occurrences(&mut self, sampler: &FleetSampler) -> [u32; 100] {
+        let solved = sampler.enumerate_with(self.node_budget, || [0u32; 100], count, add);
+        if let Some(occurrences) = solved {
+            return occurrences;
+        }
+
+        let seeds: Vec<u64> = (0..Self::BATCHES).map(|_| self.rng.random()).collect();
+        let batches = parallel::map(&seeds, |seed| {
+            let mut rng = StdRng::seed_from_u64(*seed);
+            let mut occurrences = [0u32; 100];
+            for _ in 0..Self::SAMPLES / Self::BATCHES {
+                let Some(fleet) = sampler.draw_with(FleetSampler::DRAW_BUDGET, &mut rng) else {
+                    break;
+                };
+                count(&mut occurrences, &fleet);
             }
+            occurrences
+        });
+
+        batches.into_iter().fold([0; 100], add)
+    }
+
+    // Chooses an unexplored cell next to a hit, or any unexplored cell.
+    fn fallback(&mut self, view: &StrategyView, unexplored: &[Cell]) -> Option<Cell> {
+        let next_to_hits: Vec<Cell> = unexplored
+            .iter()
+            .filter(|cell| {
+                [(1, 0), (-1, 0), (0, 1), (0, -1)].iter().any(|(dx, dy)| {
+                    cell.offset(*dx, *dy)
+                        .is_some_and(|next| *view.shots.at(&next) == CellState::Hit)
+                })
+            })
+            .copied()
+            .collect();
+
+        match next_to_hits.is_empty() {
+            true => unexplored.choose(&mut self.rng).copied(),
+            false => next_to_hits.choose(&mut self.rng).copied(),
+        }
+    }
+}
+
+// Adds the cells occupied by the fleet to the occurrences of every cell.
+fn count(occurrences: &mut [u32; 100], fleet: &[Ship]) {
+    for cell in fleet.iter().flat_map(|ship| ship.occupied_cells()) {
+        occurrences[10 * cell.y() as usize + cell.x() as usize] += 1;
+    }
+}
+
+// Adds up the occurrences of every cell.
+fn add(mut occurrences: [u32; 100], other: [u32; 100]) -> [u32; 100] {
+    for (occurrence, other) in occurrences.iter_mut().zip(other) {
+        *occurrence += other;
+    }
+    occurrences
+}
+
+impl Default for MonteCarloStrategy {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MonteCarloStrategy {
+    // Returns every unexplored cell with its score: how often it is occupied by the fleets which
+    // agree with the view, times its weight.
+    fn scores(&mut self, view: &StrategyView) -> Vec<(Cell, u64)> {
+        let kinds: Vec<ShipKind> = ShipKind::iter().collect();
+        let sampler = FleetSampler::new(&kinds, view.shots, view.sunk);
+        let occurrences = self.occurrences(&sampler);
+        let weights = self.weights();
+
+        (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .filter(|cell| view.is_unexplored(cell))
+            .map(|cell| {
+                let index = 10 * cell.y() as usize + cell.x() as usize;
+                (cell, u64::from(occurrences[index]) * weights[index])
+            })
+            .collect()
+    }
+}
+
+impl Strategy for MonteCarloStrategy {
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        let scores = self.scores(view);
+        let most = scores.iter().map(|(_, score)| *score).max().unwrap_or(0);
+        if most == 0 {
+            debug!("no fleet fits the shots, falling back");
+            let unexplored: Vec<Cell> = scores.into_iter().map(|(cell, _)| cell).collect();
+            return self.fallback(view, &unexplored);
+        }
+
+        let best: Vec<Cell> = scores
+            .into_iter()
+            .filter(|(_, score)| *score == most)
+            .map(|(cell, _)| cell)
+            .collect();
+        let next = best.choose(&mut self.rng).copied();
+        debug!(cell = ?next, score = most, ties = best.len(), "monte carlo move");
+        next
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        Some(Box::new(self.clone()))
+    }
+
+    fn heatmap(&mut self, view: &StrategyView) -> Option<HashMap<Cell, f32>> {
+        // The generator is restored, so that the next move is the same as without the heatmap.
+        let rng = self.rng.clone();
+        let scores = self.scores(view);
+        self.rng = rng;
+
+        let most = scores
+            .iter()
+            .map(|(_, score)| *score)
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        Some(
+            scores
+                .into_iter()
+                .map(|(cell, score)| (cell, score as f32 / most as f32))
+                .collect(),
+        )
+    }
+}
+
+/// How strongly the [AdaptiveStrategy] pulls the game back to a close match.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Display, EnumIter, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Adaptation {
+    /// The computer adapts slowly to the accuracy of the human player.
+    Gentle,
+
+    /// The computer adapts to the accuracy of the human player.
+    #[default]
+    Moderate,
+
+    /// The computer quickly matches the accuracy of the human player.
+    Strong,
+}
+
+impl Adaptation {
+    // How much the chance of a strong move changes for every point of difference between the
+    // accuracies of the two sides.
+    fn strength(&self) -> f64 {
+        match self {
+            Adaptation::Gentle => 0.5,
+            Adaptation::Moderate => 1.0,
+            Adaptation::Strong => 2.0,
+        }
+    }
+}
+
+/// A strategy which keeps the game close.
+///
+/// It compares its own accuracy with the accuracy of the opponent, as seen on the two shots
+/// grids of its view. When it is behind, it plays the moves of a [MonteCarloStrategy] more
+/// often; when it is ahead, it fires more often away from its hits, like a deliberate miss.
+/// With the same accuracy, half of its moves are strong.
+#[derive(Debug, Clone)]
+pub struct AdaptiveStrategy {
+    strong: MonteCarloStrategy,
+    adaptation: Adaptation,
+    rng: StdRng,
+}
+
+impl AdaptiveStrategy {
+    pub fn new(adaptation: Adaptation) -> Self {
+        Self::with_rng(adaptation, StdRng::from_os_rng())
+    }
+
+    /// Creates a strategy which always plays the same moves for the same seed.
+    pub fn with_seed(adaptation: Adaptation, seed: u64) -> Self {
+        Self::with_rng(adaptation, StdRng::seed_from_u64(seed))
+    }
+
+    fn with_rng(adaptation: Adaptation, mut rng: StdRng) -> Self {
+        Self {
+            strong: MonteCarloStrategy::with_seed(rng.random()),
+            adaptation,
+            rng,
+        }
+    }
 
-            if last_move.y() + i < 10
-                && let Ok(cell) = Cell::new(last_move.x(), last_move.y() + i)
-                && !self.moves.contains(&cell)
-            {
-                new_candidates.push(cell);
+    /// Returns the chance of playing a strong move in the given view, from 0 to 1.
+    pub fn strong_move_chance(&self, view: &StrategyView) -> f64 {
+        let gap = match (view.opponent_shots.hit_ratio(), view.shots.hit_ratio()) {
+            (Some(opponent), Some(own)) => opponent - own,
+            _ => 0.0,
+        };
+
+        (0.5 + self.adaptation.strength() * gap).clamp(0.0, 1.0)
+    }
+
+    // Chooses an unexplored cell which is not next to a hit, or any unexplored cell.
+    fn weak_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        let unexplored: Vec<Cell> = (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .filter(|cell| view.is_unexplored(cell))
+            .collect();
+        let away_from_hits: Vec<Cell> = unexplored
+            .iter()
+            .filter(|cell| {
+                [(1, 0), (-1, 0), (0, 1), (0, -1)].iter().all(|(dx, dy)| {
+                    cell.offset(*dx, *dy)
+                        .is_none_or(|next| *view.shots.at(&next) != CellState::Hit)
+                })
+            })
+            .copied()
+            .collect();
+
+        match away_from_hits.is_empty() {
+            true => unexplored.choose(&mut self.rng).copied(),
+            false => away_from_hits.choose(&mut self.rng).copied(),
+        }
+    }
+}
+
+impl Strategy for AdaptiveStrategy {
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        let chance = self.strong_move_chance(view);
+        let strong = self.rng.random_bool(chance);
+        debug!(chance, strong, "adaptive move");
+        if strong {
+            self.strong.next_move(view)
+        } else {
+            self.weak_move(view)
+        }
+    }
+
+    fn heatmap(&mut self, view: &StrategyView) -> Option<HashMap<Cell, f32>> {
+        self.strong.heatmap(view)
+    }
+
+    fn snapshot(&self) -> Option<Box<dyn Strategy>> {
+        Some(Box::new(self.clone()))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::engine::fleet::{Fleet, tests::fixed_fleet};
+    use crate::engine::rules::ClassicRules;
+    use rstest::rstest;
+    use std::str::FromStr;
+
+    #[rstest]
+    #[case("random", StrategyKind::Random)]
+    #[case("smart", StrategyKind::Smart)]
+    #[case("heatmap", StrategyKind::Heatmap)]
+    fn test_strategy_kind(#[case] name: &str, #[case] kind: StrategyKind) {
+        assert_eq!(StrategyKind::from_str(name), Ok(kind));
+        assert_eq!(kind.to_string(), name);
+        assert!(StrategyKind::from_str("psychic").is_err());
+    }
+
+    #[rstest]
+    fn test_monte_carlo_chases_hits() {
+        let mut shots = Grid::default();
+        shots.mark(&Cell::bounded(4, 4), CellState::Hit);
+        let opponent_shots = Grid::default();
+        let view = StrategyView::new(&shots, &[], &opponent_shots, &ClassicRules);
+        let mut strategy = MonteCarloStrategy::with_seed(7);
+
+        let next = strategy.next_move(&view).unwrap();
+        assert_eq!(next.manhattan_distance(&Cell::bounded(4, 4)), 1);
+    }
+
+    #[rstest]
+    fn test_smart_shoots_every_cell_once() {
+        let mut shots = Grid::default();
+        let opponent_shots = Grid::default();
+        let mut strategy = SmartStrategy::with_seed(7);
+
+        for _ in 0..100 {
+            let view = StrategyView::new(&shots, &[], &opponent_shots, &ClassicRules);
+            let cell = strategy.next_move(&view).unwrap();
+            assert!(view.is_unexplored(&cell));
+            shots.mark(&cell, CellState::Miss);
+        }
+
+        let view = StrategyView::new(&shots, &[], &opponent_shots, &ClassicRules);
+        assert_eq!(strategy.next_move(&view), None);
+    }
+
+    #[rstest]
+    fn test_smart_follows_the_ship() {
+        // A hit before any move is ignored.
+        let mut strategy = SmartStrategy::with_seed(7);
+        strategy.notify_hit(ShipKind::Battleship);
+        assert!(strategy.candidates_moves.is_empty());
+
+        // Two hits next to each other lie on the same ship, whatever its kind: only the rest of
+        // the row within reach is left.
+        for hit in [Cell::bounded(4, 4), Cell::bounded(5, 4)] {
+            strategy.last_move = Some(hit);
+            strategy.moves.insert(hit);
+            strategy.notify_hit(ShipKind::Battleship);
+        }
+        let targets = |strategy: &mut SmartStrategy| {
+            let mut targets = std::mem::take(&mut strategy.candidates_moves);
+            targets.retain(|cell| !strategy.moves.contains(cell));
+            targets.sort();
+            targets.dedup();
+            targets
+        };
+        assert_eq!(
+            targets(&mut strategy),
+            [(2, 4), (3, 4), (6, 4), (7, 4)].map(|(x, y)| Cell::bounded(x, y))
+        );
+
+        // Two hits apart may be on two ships of the same kind: both are targeted all around.
+        let mut strategy = SmartStrategy::with_seed(7);
+        for hit in [Cell::bounded(4, 4), Cell::bounded(4, 6)] {
+            strategy.last_move = Some(hit);
+            strategy.moves.insert(hit);
+            strategy.notify_hit(ShipKind::Destroyer);
+        }
+        assert_eq!(
+            targets(&mut strategy),
+            [(3, 4), (3, 6), (4, 3), (4, 5), (4, 7), (5, 4), (5, 6)]
+                .map(|(x, y)| Cell::bounded(x, y))
+        );
+    }
+
+    #[rstest]
+    fn test_monte_carlo_sinks_the_fleet(fixed_fleet: Fleet) {
+        let mut fleet = fixed_fleet;
+        let mut shots = Grid::default();
+        let mut sunk = Vec::new();
+        let opponent_shots = Grid::default();
+        let mut strategy = MonteCarloStrategy::with_seed(7);
+
+        while !fleet.is_sunk() {
+            let view = StrategyView::new(&shots, &sunk, &opponent_shots, &ClassicRules);
+            let cell = strategy.next_move(&view).unwrap();
+            assert!(view.is_unexplored(&cell));
+            let outcome = fleet.hit_at(&cell);
+            match outcome.ship {
+                Some(kind) => {
+                    shots.mark(&cell, CellState::Hit);
+                    if outcome.sunk {
+                        sunk.push(kind);
+                    }
+                }
+                None => shots.mark(&cell, CellState::Miss),
             }
+        }
+
+        let fired = 100 - shots.count(&CellState::Empty);
+        assert!(fired < 80, "{fired} shots");
+    }
 
-            if last_move.y() >= i
-                && let Ok(cell) = Cell::new(last_move.x(), last_move.y() - i)
-                && !self.moves.contains(&cell)
-            {
-                new_candidates.push(cell);
+    // Makes a shots grid with the given hits and misses, in the first rows.
+    fn shots(hits: u8, misses: u8) -> Grid {
+        let mut grid = Grid::default();
+        for i in 0..hits + misses {
+            let state = if i < hits {
+                CellState::Hit
+            } else {
+                CellState::Miss
+            };
+            grid.mark(&Cell::bounded(i % 10, i / 10), state);
+        }
+
+        grid
+    }
+
+    #[rstest]
+    #[case(Adaptation::Moderate, (0, 0), (0, 0), 0.5)]
+    #[case(Adaptation::Moderate, (5, 5), (1, 9), 0.9)]
+    #[case(Adaptation::Moderate, (1, 9), (5, 5), 0.1)]
+    #[case(Adaptation::Gentle, (5, 5), (1, 9), 0.7)]
+    #[case(Adaptation::Strong, (5, 5), (1, 9), 1.0)]
+    #[case(Adaptation::Strong, (0, 10), (10, 0), 0.0)]
+    fn test_strong_move_chance(
+        #[case] adaptation: Adaptation,
+        #[case] opponent: (u8, u8),
+        #[case] own: (u8, u8),
+        #[case] chance: f64,
+    ) {
+        let (own, opponent) = (shots(own.0, own.1), shots(opponent.0, opponent.1));
+        let view = StrategyView::new(&own, &[], &opponent, &ClassicRules);
+        let strategy = AdaptiveStrategy::with_seed(adaptation, 7);
+
+        assert!((strategy.strong_move_chance(&view) - chance).abs() < 1e-9);
+    }
+
+    #[rstest]
+    fn test_adaptive_misses_on_purpose() {
+        let mut own = Grid::default();
+        own.mark(&Cell::bounded(4, 4), CellState::Hit);
+        let opponent = shots(0, 10);
+        let view = StrategyView::new(&own, &[], &opponent, &ClassicRules);
+        let mut strategy = AdaptiveStrategy::with_seed(Adaptation::Strong, 7);
+
+        for _ in 0..10 {
+            let next = strategy.next_move(&view).unwrap();
+            assert!((next.x() as i8 - 4).abs() + (next.y() as i8 - 4).abs() > 1);
+        }
+    }
+
+    #[rstest]
+    fn test_monte_carlo_habits() {
+        let strategy = MonteCarloStrategy::with_seed(7);
+        assert!(strategy.weights().iter().all(|weight| *weight == 1));
+
+        let mut habits = vec![0; 100];
+        habits[0] = 4;
+        habits[1] = 2;
+        let strategy = strategy.with_habits(&habits);
+        let weights = strategy.weights();
+        assert_eq!(weights[..3], [8, 6, 4]);
+        assert!(weights[3..].iter().all(|weight| *weight == 4));
+    }
+
+    // Runs the future on this thread until it completes.
+    #[cfg(feature = "async")]
+    pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+        struct Unpark(std::thread::Thread);
+        impl std::task::Wake for Unpark {
+            fn wake(self: Arc<Self>) {
+                self.0.unpark();
             }
         }
 
-        self.candidates_moves.extend(new_candidates);
+        let waker = Waker::from(Arc::new(Unpark(std::thread::current())));
+        let mut future = std::pin::pin!(future);
+        loop {
+            let context = &mut std::task::Context::from_waker(&waker);
+            if let Poll::Ready(output) = future.as_mut().poll(context) {
+                return output;
+            }
+            std::thread::park();
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[rstest]
+    fn test_signal() {
+        let signal = Signal::default();
+        let mut preparation = signal.preparation();
+        let context = &mut std::task::Context::from_waker(Waker::noop());
+        assert!(preparation.as_mut().poll(context).is_pending());
+
+        let notifier = signal.clone();
+        std::thread::spawn(move || notifier.notify());
+        block_on(preparation);
+
+        assert!(signal.preparation().as_mut().poll(context).is_ready());
     }
 }
@@ -0,0 +1,260 @@
+//! The tutorial: a guided first game, teaching how to place a ship, fire and read the grids.
+//!
+//! A [Tutorial] follows a scripted sequence of [Step]s. Every step has a prompt for the player
+//! and the [TutorialAction] it expects: the tutorial moves on to the next step only when the
+//! player does what is asked. There is no opponent shooting back: the player places a single
+//! ship on their own grid, then sinks a single enemy Destroyer.
+//!
+use crate::engine::fleet::{Ship, ShipKind, ShipOrientation};
+use crate::engine::grid::{Cell, CellState, Grid};
+use std::fmt;
+use thiserror::Error;
+
+/// Represents a tutorial error.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum TutorialError {
+    /// The player has done something else than what the step asks.
+    #[error("Not quite: {0}")]
+    Unexpected(TutorialAction),
+
+    /// All the steps have already been done.
+    #[error("the tutorial is over")]
+    Over,
+}
+
+/// Something the player can do during the tutorial.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum TutorialAction {
+    /// Goes on, after reading the prompt.
+    Continue,
+
+    /// Places a ship of the kind, from the cell and with the orientation.
+    Place(ShipKind, Cell, ShipOrientation),
+
+    /// Fires at the cell of the enemy grid.
+    Fire(Cell),
+}
+
+impl fmt::Display for TutorialAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TutorialAction::Continue => write!(f, "press Enter"),
+            TutorialAction::Place(kind, cell, ShipOrientation::Horizontal) => {
+                write!(f, "place the {kind} horizontally, from {cell}")
+            }
+            TutorialAction::Place(kind, cell, ShipOrientation::Vertical) => {
+                write!(f, "place the {kind} vertically, from {cell}")
+            }
+            TutorialAction::Fire(cell) => write!(f, "fire at {cell}"),
+        }
+    }
+}
+
+/// A step of the tutorial: the prompt shown to the player and the action it expects.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Step {
+    pub prompt: &'static str,
+    pub action: TutorialAction,
+}
+
+/// A guided game, going through its steps one by one.
+#[derive(Debug, Clone)]
+pub struct Tutorial {
+    steps: Vec<Step>,
+    current: usize,
+    ships: Vec<Ship>,
+    enemy: Ship,
+    shots_grid: Grid,
+}
+
+impl Tutorial {
+    /// Creates the tutorial, at its first step.
+    pub fn new() -> Self {
+        let enemy = ShipKind::Destroyer
+            .ship(Cell::bounded(4, 4), ShipOrientation::Horizontal)
+            .expect("the enemy Destroyer is on the board");
+
+        Self {
+            steps: steps(),
+            current: 0,
+            ships: Vec::new(),
+            enemy,
+            shots_grid: Grid::default(),
+        }
+    }
+
+    /// Returns the current step, or `None` when the tutorial is over.
+    pub fn step(&self) -> Option<&Step> {
+        self.steps.get(self.current)
+    }
+
+    /// Returns the number of the current step, from 1, and the number of steps.
+    pub fn progress(&self) -> (usize, usize) {
+        ((self.current + 1).min(self.steps.len()), self.steps.len())
+    }
+
+    /// Returns `true` when every step has been done.
+    pub fn is_over(&self) -> bool {
+        self.current >= self.steps.len()
+    }
+
+    /// Returns the ships placed by the player.
+    pub fn ships(&self) -> &[Ship] {
+        &self.ships
+    }
+
+    /// Returns the grid of the shots fired at the enemy, with their results.
+    pub fn shots_grid(&self) -> &Grid {
+        &self.shots_grid
+    }
+
+    /// Returns `true` when the enemy Destroyer has been sunk.
+    pub fn is_enemy_sunk(&self) -> bool {
+        self.enemy.is_sunk()
+    }
+
+    /// Does the action, if it is the one the current step expects, and moves on to the next
+    /// step.
+    pub fn perform(&mut self, action: TutorialAction) -> Result<(), TutorialError> {
+        let expected = &self.step().ok_or(TutorialError::Over)?.action;
+        if action != *expected {
+            return Err(TutorialError::Unexpected(expected.clone()));
+        }
+
+        match action {
+            TutorialAction::Continue => {}
+            TutorialAction::Place(kind, cell, orientation) => {
+                self.ships.extend(kind.ship(cell, orientation));
+            }
+            TutorialAction::Fire(cell) => {
+                let state = match self.enemy.hit_at(&cell) {
+                    true => CellState::Hit,
+                    false => CellState::Miss,
+                };
+                self.shots_grid.mark(&cell, state);
+            }
+        }
+        self.current += 1;
+
+        Ok(())
+    }
+}
+
+impl Default for Tutorial {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// The script of the tutorial.
+fn steps() -> Vec<Step> {
+    vec![
+        Step {
+            prompt: "Welcome aboard, admiral! This tutorial shows you how to play your first \
+                battle. Press Enter to begin.",
+            action: TutorialAction::Continue,
+        },
+        Step {
+            prompt: "The left grid is your sea. Place your Destroyer there: press v to turn it \
+                vertically, move it with the arrow keys to C3 and press Enter.",
+            action: TutorialAction::Place(
+                ShipKind::Destroyer,
+                Cell::bounded(2, 2),
+                ShipOrientation::Vertical,
+            ),
+        },
+        Step {
+            prompt: "Well done! In a real battle, you place your whole fleet in the same way, \
+                and the computer fires at this grid. Press Enter.",
+            action: TutorialAction::Continue,
+        },
+        Step {
+            prompt: "The right grid is the enemy sea, where a Destroyer is hiding. Aim at B2 \
+                with the arrow keys and press Enter to fire.",
+            action: TutorialAction::Fire(Cell::bounded(1, 1)),
+        },
+        Step {
+            prompt: "A miss: the cell is marked, so that you don't fire there again. Press \
+                Enter.",
+            action: TutorialAction::Continue,
+        },
+        Step {
+            prompt: "Now fire at E5.",
+            action: TutorialAction::Fire(Cell::bounded(4, 4)),
+        },
+        Step {
+            prompt: "A hit! Ships lie in straight lines, so the rest of it is next to E5. Fire \
+                at F5.",
+            action: TutorialAction::Fire(Cell::bounded(5, 4)),
+        },
+        Step {
+            prompt: "You sank the enemy Destroyer! You are ready for your first battle. Press \
+                Enter to deploy your fleet.",
+            action: TutorialAction::Continue,
+        },
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    // Does every step of the tutorial as it expects.
+    fn complete(tutorial: &mut Tutorial) {
+        while let Some(step) = tutorial.step() {
+            tutorial.perform(step.action.clone()).unwrap();
+        }
+    }
+
+    #[rstest]
+    fn test_complete_tutorial() {
+        let mut tutorial = Tutorial::new();
+        assert_eq!(tutorial.progress(), (1, 8));
+
+        complete(&mut tutorial);
+
+        assert!(tutorial.is_over());
+        assert!(tutorial.is_enemy_sunk());
+        assert_eq!(tutorial.ships().len(), 1);
+        assert_eq!(
+            *tutorial.shots_grid().at(&Cell::bounded(1, 1)),
+            CellState::Miss
+        );
+        assert_eq!(
+            *tutorial.shots_grid().at(&Cell::bounded(5, 4)),
+            CellState::Hit
+        );
+        assert_eq!(tutorial.progress(), (8, 8));
+        assert_eq!(
+            tutorial.perform(TutorialAction::Continue),
+            Err(TutorialError::Over)
+        );
+    }
+
+    #[rstest]
+    #[case(TutorialAction::Fire(Cell::bounded(2, 2)))]
+    #[case(TutorialAction::Place(
+        ShipKind::Destroyer,
+        Cell::bounded(2, 2),
+        ShipOrientation::Horizontal
+    ))]
+    #[case(TutorialAction::Place(
+        ShipKind::Destroyer,
+        Cell::bounded(3, 2),
+        ShipOrientation::Vertical
+    ))]
+    fn test_unexpected_action(#[case] action: TutorialAction) {
+        let mut tutorial = Tutorial::new();
+        tutorial.perform(TutorialAction::Continue).unwrap();
+
+        let error = tutorial.perform(action).unwrap_err();
+
+        assert_eq!(
+            error.to_string(),
+            "Not quite: place the Destroyer vertically, from C3"
+        );
+        assert_eq!(tutorial.progress(), (2, 8));
+        assert!(tutorial.ships().is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+//! The weather over the boards.
+//!
+//! In the [fog of war](crate::engine::rules::FogRules) variant, a random band of rows or
+//! columns is fogged at every turn. Shots into the [Fog] are resolved as usual, but their
+//! result stays hidden from the shooter until the fog lifts from their cell.
+//!
+use crate::engine::game::Side;
+use crate::engine::grid::Cell;
+use rand::Rng;
+
+/// The fogged cells of the current turn, and the shots whose result is still hidden.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Fog {
+    cells: Vec<Cell>,
+    hidden: Vec<(Side, Cell)>,
+}
+
+impl Fog {
+    /// Moves the fog over a random band of rows or columns of the given width.
+    ///
+    /// The shots out of the new band are revealed, while the ones still in the fog stay hidden.
+    /// A width of 0 lifts the fog entirely.
+    pub fn roll_with<R: Rng + ?Sized>(&mut self, width: u8, rng: &mut R) {
+        let width = width.min(10);
+        self.cells = if width == 0 {
+            Vec::new()
+        } else {
+            let start = rng.random_range(0..=10 - width);
+            let rows = rng.random_bool(0.5);
+            (start..start + width)
+                .flat_map(|band| {
+                    (0..10).map(move |i| match rows {
+                        true => Cell::bounded(i, band),
+                        false => Cell::bounded(band, i),
+                    })
+                })
+                .collect()
+        };
+
+        let cells = &self.cells;
+        self.hidden.retain(|(_, cell)| cells.contains(cell));
+    }
+
+    /// Returns the fogged cells.
+    pub fn cells(&self) -> &[Cell] {
+        &self.cells
+    }
+
+    /// Checks whether the cell is fogged.
+    pub fn covers(&self, cell: &Cell) -> bool {
+        self.cells.contains(cell)
+    }
+
+    /// Hides the result of the shot of the given side at the cell, if the cell is fogged.
+    ///
+    /// Returns `true` if the result is hidden.
+    pub fn hide(&mut self, shooter: Side, cell: &Cell) -> bool {
+        if !self.covers(cell) {
+            return false;
+        }
+
+        if !self.is_hidden(shooter, cell) {
+            self.hidden.push((shooter, *cell));
+        }
+        true
+    }
+
+    /// Checks whether the result of the shot of the given side at the cell is hidden.
+    pub fn is_hidden(&self, shooter: Side, cell: &Cell) -> bool {
+        self.hidden.contains(&(shooter, *cell))
+    }
+
+    /// Returns the cells shot by the given side whose result is hidden.
+    pub fn hidden(&self, shooter: Side) -> Vec<Cell> {
+        self.hidden
+            .iter()
+            .filter(|(side, _)| *side == shooter)
+            .map(|(_, cell)| *cell)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case(0, 0)]
+    #[case(2, 20)]
+    #[case(3, 30)]
+    #[case(12, 100)]
+    fn test_band(#[case] width: u8, #[case] cells: usize) {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut fog = Fog::default();
+        for _ in 0..10 {
+            fog.roll_with(width, &mut rng);
+            assert_eq!(fog.cells().len(), cells);
+
+            let rows = fog.cells().iter().all(|cell| {
+                fog.covers(&Cell::bounded(0, cell.y())) && fog.covers(&Cell::bounded(9, cell.y()))
+            });
+            let columns = fog.cells().iter().all(|cell| {
+                fog.covers(&Cell::bounded(cell.x(), 0)) && fog.covers(&Cell::bounded(cell.x(), 9))
+            });
+            assert!(rows || columns);
+        }
+    }
+
+    #[rstest]
+    fn test_hide() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut fog = Fog::default();
+        fog.roll_with(3, &mut rng);
+        let fogged = fog.cells()[0];
+        let clear = (0..100)
+            .map(|i| Cell::bounded(i % 10, i / 10))
+            .find(|cell| !fog.covers(cell))
+            .unwrap();
+
+        assert!(fog.hide(Side::Human, &fogged));
+        assert!(fog.hide(Side::Human, &fogged));
+        assert!(!fog.hide(Side::Computer, &clear));
+        assert!(fog.is_hidden(Side::Human, &fogged));
+        assert!(!fog.is_hidden(Side::Computer, &fogged));
+        assert_eq!(fog.hidden(Side::Human), [fogged]);
+
+        fog.roll_with(0, &mut rng);
+        assert!(fog.cells().is_empty());
+        assert!(fog.hidden(Side::Human).is_empty());
+    }
+}
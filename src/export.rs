@@ -0,0 +1,266 @@
+//! Export of the finished games, to share them.
+//!
+//! A [Replay] is rendered frame by frame, one frame after every shot, without a terminal: as an
+//! [asciinema](https://asciinema.org) cast of the grids drawn in text, or as an animated GIF of
+//! the grids drawn in colored squares, with the `gif` feature.
+//!
+use crate::cli;
+use crate::engine::fleet::FleetError;
+use crate::engine::grid::Cell;
+use crate::engine::replay::{Position, Replay};
+use serde_json::json;
+use std::io::{self, Write};
+use thiserror::Error;
+
+/// Represents an export error.
+#[derive(Debug, Error)]
+pub enum ExportError {
+    /// A fleet of the replay is not valid.
+    #[error("invalid replay: {0}")]
+    Replay(#[from] FleetError),
+
+    /// The export can't be written.
+    #[error("cannot write the export: {0}")]
+    Io(#[from] io::Error),
+
+    /// The GIF can't be encoded.
+    #[cfg(feature = "gif")]
+    #[error("cannot encode the GIF: {0}")]
+    Gif(#[from] gif::EncodingError),
+}
+
+// How long every frame is shown, in seconds. The last frame of a GIF is shown longer, before it
+// loops.
+const FRAME_DURATION: f64 = 0.5;
+// The size of the terminal of the cast, in columns and rows.
+const CAST_WIDTH: usize = 52;
+const CAST_HEIGHT: usize = 14;
+
+/// Writes the replay as an asciinema cast, in the version 2 format: a header line, then an
+/// output event for every frame.
+pub fn asciinema<W: Write>(replay: &Replay, mut output: W) -> Result<(), ExportError> {
+    let header = json!({
+        "version": 2,
+        "width": CAST_WIDTH,
+        "height": CAST_HEIGHT,
+        "title": format!("{} vs {}", replay.players[0], replay.players[1]),
+    });
+    writeln!(output, "{header}")?;
+
+    for (i, position) in replay.positions()?.iter().enumerate() {
+        let event = json!([i as f64 * FRAME_DURATION, "o", text_frame(replay, position)]);
+        writeln!(output, "{event}")?;
+    }
+
+    Ok(())
+}
+
+// Draws the position in text, clearing the screen first: the last shot, then the fleets of both
+// players side by side, with the shots fired at them.
+fn text_frame(replay: &Replay, position: &Position) -> String {
+    let last = match position.last {
+        Some(shot) => format!(
+            "Turn {}: {} fires at {}",
+            shot.turn, replay.players[shot.shooter as usize], shot.cell
+        ),
+        None => format!("{} vs {}", replay.players[0], replay.players[1]),
+    };
+    let header = "   A B C D E F G H I J";
+    let mut rows = vec![
+        format!("\x1b[2J\x1b[H{last}"),
+        String::new(),
+        format!("   {:<23}   {}", replay.players[0], replay.players[1]),
+        format!("{header}    {header}"),
+    ];
+    for y in 0..10 {
+        let row = |side: usize| -> String {
+            (0..10)
+                .map(|x| {
+                    cli::paint(
+                        ' ',
+                        Some(position.grids[side].at(&Cell::bounded(x, y))),
+                        true,
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        rows.push(format!(
+            "{:02} {}    {:02} {}",
+            y + 1,
+            row(0),
+            y + 1,
+            row(1)
+        ));
+    }
+
+    rows.join("\r\n") + "\r\n"
+}
+
+/// Writes the replay as an animated GIF, which loops forever.
+#[cfg(feature = "gif")]
+pub fn gif<W: Write>(replay: &Replay, output: W) -> Result<(), ExportError> {
+    let (width, height) = image::SIZE;
+    let mut encoder = gif::Encoder::new(output, width, height, &image::PALETTE)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    let positions = replay.positions()?;
+    for (i, position) in positions.iter().enumerate() {
+        let mut frame =
+            gif::Frame::from_indexed_pixels(width, height, image::frame(position), None);
+        // The delay is in hundredths of a second.
+        frame.delay = match i + 1 == positions.len() {
+            true => (FRAME_DURATION * 600.0) as u16,
+            false => (FRAME_DURATION * 100.0) as u16,
+        };
+        encoder.write_frame(&frame)?;
+    }
+
+    Ok(())
+}
+
+// Draws the positions as images made of colored squares, one for every cell, with the grids of
+// both players side by side.
+#[cfg(feature = "gif")]
+mod image {
+    use super::*;
+    use crate::engine::grid::CellState;
+
+    // The side of a cell, and the margin around and between the grids, in pixels.
+    const CELL: u16 = 12;
+    const MARGIN: u16 = 12;
+    /// The width and the height of the images.
+    pub const SIZE: (u16, u16) = (CELL * 20 + MARGIN * 3, CELL * 10 + MARGIN * 2);
+
+    // The colors of the palette, by index.
+    const BACKGROUND: u8 = 0;
+    const WATER: u8 = 1;
+    const SHIP: u8 = 2;
+    const MISS: u8 = 3;
+    const HIT: u8 = 4;
+    const BLOCKED: u8 = 5;
+    const LAST_SHOT: u8 = 6;
+    /// The palette of the images, as RGB triples.
+    pub const PALETTE: [u8; 21] = [
+        16, 24, 32, // background
+        30, 80, 150, // water
+        150, 150, 150, // ship
+        220, 220, 220, // miss
+        200, 30, 30, // hit
+        90, 130, 60, // blocked
+        240, 200, 40, // last shot
+    ];
+
+    /// Draws the position, with the last shot outlined.
+    pub fn frame(position: &Position) -> Vec<u8> {
+        let (width, height) = SIZE;
+        let mut pixels = vec![BACKGROUND; usize::from(width) * usize::from(height)];
+        for (side, grid) in position.grids.iter().enumerate() {
+            let left = MARGIN + side as u16 * (CELL * 10 + MARGIN);
+            for cell in (0..10).flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y))) {
+                let color = match grid.at(&cell) {
+                    CellState::Empty => WATER,
+                    CellState::Occupied => SHIP,
+                    CellState::Miss => MISS,
+                    CellState::Hit => HIT,
+                    CellState::Blocked => BLOCKED,
+                };
+                let last = position.last.is_some_and(|shot| {
+                    shot.cell == cell && shot.shooter.opponent() as usize == side
+                });
+                let (x0, y0) = (
+                    left + u16::from(cell.x()) * CELL,
+                    MARGIN + u16::from(cell.y()) * CELL,
+                );
+                // Every square leaves a pixel of background between the cells.
+                for dy in 0..CELL - 1 {
+                    for dx in 0..CELL - 1 {
+                        let border = dx == 0 || dy == 0 || dx == CELL - 2 || dy == CELL - 2;
+                        let index =
+                            usize::from(y0 + dy) * usize::from(width) + usize::from(x0 + dx);
+                        pixels[index] = match last && border {
+                            true => LAST_SHOT,
+                            false => color,
+                        };
+                    }
+                }
+            }
+        }
+
+        pixels
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::game::Side;
+    use crate::engine::replay::ReplayShot;
+    use rstest::{fixture, rstest};
+
+    #[fixture]
+    fn replay() -> Replay {
+        Replay {
+            players: ["Ishmael".to_string(), "Computer".to_string()],
+            fleets: [
+                "A1H/A3H/A5H/A7H/A9H".to_string(),
+                "A1V/C1V/E1V/G1V/I1V".to_string(),
+            ],
+            shots: vec![
+                ReplayShot {
+                    turn: 1,
+                    shooter: Side::Human,
+                    cell: Cell::bounded(4, 4),
+                },
+                ReplayShot {
+                    turn: 1,
+                    shooter: Side::Computer,
+                    cell: Cell::bounded(9, 9),
+                },
+            ],
+        }
+    }
+
+    #[rstest]
+    fn test_asciinema(replay: Replay) {
+        let mut output = Vec::new();
+
+        asciinema(&replay, &mut output).unwrap();
+
+        let cast = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = cast.lines().collect();
+        assert_eq!(lines.len(), 4);
+        let header: serde_json::Value = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(header["version"], 2);
+        assert_eq!(header["title"], "Ishmael vs Computer");
+        let event: serde_json::Value = serde_json::from_str(lines[2]).unwrap();
+        assert_eq!(event[0], 0.5);
+        assert_eq!(event[1], "o");
+        assert!(
+            event[2]
+                .as_str()
+                .unwrap()
+                .contains("Turn 1: Ishmael fires at E5")
+        );
+    }
+
+    #[rstest]
+    fn test_invalid_replay(mut replay: Replay) {
+        replay.fleets[0] = "A1H".to_string();
+
+        let result = asciinema(&replay, Vec::new());
+
+        assert!(matches!(result, Err(ExportError::Replay(_))));
+    }
+
+    #[cfg(feature = "gif")]
+    #[rstest]
+    fn test_gif(replay: Replay) {
+        let mut output = Vec::new();
+
+        gif(&replay, &mut output).unwrap();
+
+        assert!(output.starts_with(b"GIF89a"));
+        assert_eq!(output.last(), Some(&0x3b));
+    }
+}
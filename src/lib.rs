@@ -0,0 +1,33 @@
+//! Naval: a battleship game played in the terminal.
+//!
+//! The crate is split in two parts: the [engine], which holds the game logic, and the
+//! front-ends, which render the game and handle the user input: the [tui], the [cli] for a
+//! line-based game, the [mail] for a game by correspondence, the [bot] for external programs
+//! and, with the `server` feature, the HTTP API of the `server`, which the [net] client plays
+//! with, encrypted by the `tls` module with the `tls` feature. The messages of both protocols
+//! make the wire format of the [proto] module. Finished games can be shared with the [export]s
+//! of their replays. The [config] and the translations of the [locale] are shared by all of them,
+//! and the [metrics] count the games played by the computer strategies and on the server.
+//!
+//! The terminal front-ends, the `tui`, `cli`, `mail` and `export` modules, are built by the
+//! default `tui` feature. Without it, the engine builds for `wasm32-unknown-unknown` as well.
+//!
+pub mod bot;
+#[cfg(feature = "tui")]
+pub mod cli;
+pub mod config;
+pub mod engine;
+#[cfg(feature = "tui")]
+pub mod export;
+pub mod locale;
+#[cfg(feature = "tui")]
+pub mod mail;
+pub mod metrics;
+pub mod net;
+pub mod proto;
+#[cfg(feature = "server")]
+pub mod server;
+#[cfg(feature = "tls")]
+pub mod tls;
+#[cfg(feature = "tui")]
+pub mod tui;
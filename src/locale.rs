@@ -0,0 +1,578 @@
+//! This module contains the translations of the texts shown to the player.
+//!
+//! Every text is a [Text] key, translated by the [Locale] of the player: English or Italian.
+//! The texts with values, like the cell of a shot, have numbered placeholders, like `{0}`, which
+//! [Locale::format] fills in, so that a translation can put the values in its own order.
+//!
+//! The locale is chosen in the configuration or, if it is not set, from the `LC_ALL`,
+//! `LC_MESSAGES` and `LANG` environment variables, like `it_IT.UTF-8`. English is the default.
+//!
+use crate::engine::game::GameError;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt::Display;
+use strum::Display;
+use strum_macros::EnumIter;
+
+/// The languages of the game.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Display, EnumIter, Serialize, Deserialize)]
+pub enum Locale {
+    #[default]
+    #[serde(rename = "en")]
+    English,
+
+    #[serde(rename = "it")]
+    #[strum(serialize = "Italiano")]
+    Italian,
+}
+
+/// The texts shown to the player, which are translated.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, EnumIter)]
+pub enum Text {
+    // The battle screen.
+    OpponentGrid,
+    Tactical,
+    EnemyFleet,
+    YourFleet,
+    Weapon,
+    Shot,
+    Bombard,
+    Scan,
+    MoveSubmarine,
+    ConfirmShot,
+    AlreadyShot,
+    GameOver,
+    YourTurn,
+    ComputerTurn,
+    ShotsFired,
+    TryHint,
+    HitShootAgain,
+    ShootAgain,
+    SubmarineMoved,
+    ComputerThinking,
+    ComputerFired,
+    Hit,
+    Miss,
+    Mine,
+    Sunk,
+    YouSank,
+    Fogged,
+    YourShotAt,
+    ComputerShotAt,
+    Coordinates,
+    CoordinatesKeys,
+
+    // The popup at the end of a game.
+    MatchOver,
+    YouResigned,
+    YourTimeUp,
+    ComputerTimeUp,
+    YourShellsOut,
+    ComputerShellsOut,
+    Draw,
+    YouWin,
+    YouLose,
+    ShellsLeft,
+    DailyOver,
+    Continue,
+    SeriesWon,
+    SeriesLost,
+    NextGame,
+
+    // The setup screen.
+    DeployFleet,
+    Help,
+    Welcome,
+    Use,
+    HelpMove,
+    HelpHorizontal,
+    HelpVertical,
+    HelpPlace,
+    HelpSelect,
+    HelpPickUp,
+    HelpLayout,
+    HelpAutoPlace,
+    HelpSaveLayout,
+    HelpSettings,
+    HelpLeaderboard,
+    HelpNetwork,
+    HelpMines,
+    HelpRemoveMine,
+    SavedLayouts,
+    LayoutName,
+    LayoutNameKeys,
+    Selected,
+    SelectedKeys,
+    PlaceYour,
+    Size,
+    OnFreeCell,
+    FleetReady,
+    StartBattle,
+    ShipPlaced,
+    LayoutDeployed,
+    LayoutInvalid,
+    LayoutSaved,
+    ShipOnIsland,
+    FleetDeployed,
+    OpenSea,
+    BuiltInComputer,
+
+    // The command line game.
+    TypeHelp,
+    InvalidCommand,
+    MissingFile,
+    YourShot,
+    GameSaved,
+    CannotSave,
+    CommandsHelp,
+    YouFire,
+    ComputerFires,
+    FogHidden,
+    MineExplodes,
+    ShipSunk,
+    HitShot,
+    MissShot,
+    EnemyWaters,
+    YourFleetWaters,
+    CliYouWin,
+    CliYouLose,
+    CliDraw,
+
+    // The game by correspondence.
+    MailHelp,
+    MailOpening,
+    MailSendCode,
+    MailPasteCode,
+    MailFirst,
+    MailSecond,
+    MailOpponentFires,
+    MailSaved,
+    MailInvalidCode,
+    MailOwnCode,
+    MailUnexpectedCode,
+    MailOutOfOrder,
+    MailCheckFleet,
+    MailFleetChecked,
+    MailChangedFleet,
+    MailInvalidFleet,
+    MailWrongReport,
+}
+
+impl Locale {
+    /// Returns the locale of the environment, or English if it is not supported.
+    pub fn from_env() -> Self {
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|name| env::var(name).ok())
+            .find(|value| !value.is_empty())
+            .and_then(|value| Self::from_tag(&value))
+            .unwrap_or_default()
+    }
+
+    /// Returns the locale of a language tag, like `it`, `it_IT` or `it_IT.UTF-8`.
+    pub fn from_tag(tag: &str) -> Option<Self> {
+        let language = tag.split(['_', '-', '.', '@']).next()?;
+        match language.to_ascii_lowercase().as_str() {
+            "en" | "c" | "posix" => Some(Locale::English),
+            "it" => Some(Locale::Italian),
+            _ => None,
+        }
+    }
+
+    /// Returns the translation of the text, with its placeholders.
+    pub fn text(self, text: Text) -> &'static str {
+        match self {
+            Locale::English => english(text),
+            Locale::Italian => italian(text),
+        }
+    }
+
+    /// Returns the translation of the text, with the placeholders `{0}`, `{1}`, ... replaced by
+    /// the given values.
+    pub fn format(self, text: Text, values: &[&dyn Display]) -> String {
+        values
+            .iter()
+            .enumerate()
+            .fold(self.text(text).to_string(), |text, (i, value)| {
+                text.replace(&format!("{{{i}}}"), &value.to_string())
+            })
+    }
+
+    /// Returns the translation of the message of a game error.
+    ///
+    /// The errors of the mines come from another module, and they are shown in English.
+    pub fn error(self, error: &GameError) -> String {
+        if self == Locale::English {
+            return error.to_string();
+        }
+
+        match error {
+            GameError::NotReady => {
+                "La partita non è pronta: i giocatori non sono stati scelti".into()
+            }
+            GameError::AlreadyStarted => "La partita è già iniziata".into(),
+            GameError::AlreadyOver => "La partita è già finita".into(),
+            GameError::MissingHuman => "La partita ha bisogno di un giocatore umano".into(),
+            GameError::UnsupportedBoardSize(size) => {
+                format!("Le griglie {size}x{size} non sono supportate")
+            }
+            GameError::UnsupportedFleet => "È supportata solo la flotta standard".into(),
+            GameError::SeriesOver => "La serie è già finita".into(),
+            GameError::FleetNotAllowed => "Le regole non permettono questa flotta".into(),
+            GameError::OnIsland(cell) => format!("{cell} è un'isola"),
+            GameError::PowerUpUnavailable => {
+                "Nessun potenziamento rimasto per questa azione".into()
+            }
+            GameError::RelocationUnavailable => "Il sottomarino non può più spostarsi".into(),
+            GameError::HintUnavailable => "Nessun suggerimento rimasto".into(),
+            GameError::InvalidRelocation(cell) => {
+                format!("Il sottomarino non può spostarsi in {cell}")
+            }
+            GameError::Mines(e) => e.to_string(),
+        }
+    }
+}
+
+fn english(text: Text) -> &'static str {
+    match text {
+        Text::OpponentGrid => "Opponent Grid",
+        Text::Tactical => "Tactical",
+        Text::EnemyFleet => "Enemy Fleet",
+        Text::YourFleet => "Your Fleet",
+        Text::Weapon => " Weapon: ",
+        Text::Shot => "Shot",
+        Text::Bombard => "Bombard ×{0}",
+        Text::Scan => "Scan ×{0}",
+        Text::MoveSubmarine => "Move sub ×{0}",
+        Text::ConfirmShot => "Fire at {0}? Enter or Y to confirm",
+        Text::AlreadyShot => "{0} has already been shot",
+        Text::GameOver => "Game over",
+        Text::YourTurn => "Your turn",
+        Text::ComputerTurn => "Computer's turn",
+        Text::ShotsFired => "{0} shots fired",
+        Text::TryHint => "Try {0} ({1} hints left)",
+        Text::HitShootAgain => "Hit! Shoot again.",
+        Text::ShootAgain => "Shoot again.",
+        Text::SubmarineMoved => "The enemy Submarine has moved!",
+        Text::ComputerThinking => "Computer is thinking…",
+        Text::ComputerFired => "Computer fired at {0} — {1}",
+        Text::Hit => "Hit",
+        Text::Miss => "Miss",
+        Text::Mine => "Mine",
+        Text::Sunk => "{0} sunk",
+        Text::YouSank => "You sank their {0}!",
+        Text::Fogged => "Hidden by the fog",
+        Text::YourShotAt => "Your shot at {0}: {1}.",
+        Text::ComputerShotAt => "Computer fired at {0}: {1}.",
+        Text::Coordinates => " Target: ",
+        Text::CoordinatesKeys => " (type a cell, like B7, then Enter)",
+
+        Text::MatchOver => "Match Over!",
+        Text::YouResigned => "You resigned. ",
+        Text::YourTimeUp => "Your time is up. ",
+        Text::ComputerTimeUp => "The computer ran out of time. ",
+        Text::YourShellsOut => "You ran out of shells. ",
+        Text::ComputerShellsOut => "The computer ran out of shells. ",
+        Text::Draw => "Both fleets are sunk. It's a draw!",
+        Text::YouWin => "You WIN!!!",
+        Text::YouLose => "You lose! :(",
+        Text::ShellsLeft => "Shells left: {0} for you, {1} for the computer",
+        Text::DailyOver => "Come back tomorrow for a new challenge! Press Enter to continue.",
+        Text::Continue => "Press Enter to continue.",
+        Text::SeriesWon => "You win the series! Press Enter for a new one.",
+        Text::SeriesLost => "The computer wins the series. Press Enter for a new one.",
+        Text::NextGame => "Press Enter for the next game.",
+
+        Text::DeployFleet => "Deploy your fleet",
+        Text::Help => "Help",
+        Text::Welcome => "Welcome to Naval - The Battleship Game",
+        Text::Use => "Use:",
+        Text::HelpMove => "- the arrow keys: to move the ship",
+        Text::HelpHorizontal => "- {0}: to put the ship horizontally",
+        Text::HelpVertical => "- {0}: to put the ship vertically",
+        Text::HelpPlace => "- Enter: to place it",
+        Text::HelpSelect => "- Tab: to select a placed ship",
+        Text::HelpPickUp => "- Enter on a selected ship: to pick it up again",
+        Text::HelpLayout => "- 1-9: to deploy a saved layout",
+        Text::HelpAutoPlace => "- {0}: to deploy the fleet with the next placement strategy",
+        Text::HelpSaveLayout => "- {0}: to save your fleet as a layout",
+        Text::HelpSettings => "- {0}: to open the settings",
+        Text::HelpLeaderboard => "- {0}: to see the leaderboard",
+        Text::HelpNetwork => "- {0}: to play against someone on the network",
+        Text::HelpMines => "- Enter, once the fleet is deployed: to place one of your {0} mines",
+        Text::HelpRemoveMine => "- Backspace: to remove the last mine",
+        Text::SavedLayouts => "Saved layouts:",
+        Text::LayoutName => "Name of the layout: ",
+        Text::LayoutNameKeys => " (Enter to save, Esc to cancel)",
+        Text::Selected => "Selected ",
+        Text::SelectedKeys => ": press Enter to pick it up, Tab for the next one",
+        Text::PlaceYour => "Please, place your ",
+        Text::Size => " [size: ",
+        Text::OnFreeCell => " on a free cell [left: ",
+        Text::FleetReady => "Your fleet is ready: press ",
+        Text::StartBattle => " to start the battle",
+        Text::ShipPlaced => "{0} placed",
+        Text::LayoutDeployed => "Layout {0} deployed",
+        Text::LayoutInvalid => "Layout {0} is not valid: {1}",
+        Text::LayoutSaved => "Layout {0} saved",
+        Text::ShipOnIsland => "a ship is on the island at {0}",
+        Text::FleetDeployed => "Fleet deployed with the {0} placement",
+        Text::OpenSea => "{0}, playing on the open sea",
+        Text::BuiltInComputer => "{0}, playing against the built-in computer",
+
+        Text::TypeHelp => "Type help for the commands.",
+        Text::InvalidCommand => "{0}. Type help for the commands",
+        Text::MissingFile => "save needs a file name, like save game.txt",
+        Text::YourShot => "Your shot: ",
+        Text::GameSaved => "Game saved to {0}",
+        Text::CannotSave => "Can't save to {0}: {1}",
+        Text::CommandsHelp => {
+            "Commands:
+  A1..J10      shoot at the cell
+  show         show your fleet and your shots
+  save <file>  save the record of the game to the file
+  help         show this help
+  quit         leave the game"
+        }
+        Text::YouFire => "You fire at {0}: {1}",
+        Text::ComputerFires => "The computer fires at {0}: {1}",
+        Text::FogHidden => "hidden by the fog.",
+        Text::MineExplodes => "a mine explodes!",
+        Text::ShipSunk => "{0} sunk!",
+        Text::HitShot => "hit!",
+        Text::MissShot => "miss.",
+        Text::EnemyWaters => "Enemy waters",
+        Text::YourFleetWaters => "Your fleet",
+        Text::CliYouWin => "You win!",
+        Text::CliYouLose => "You lose!",
+        Text::CliDraw => "Both fleets are sunk: it's a draw!",
+
+        Text::MailHelp => {
+            "Type a cell to fire, like B7, and paste the codes of your opponent. Type show to see \
+             the grids, quit to go on later."
+        }
+        Text::MailOpening => "Send this opening code to your opponent, then paste theirs: {0}",
+        Text::MailSendCode => "Send this code to your opponent: {0}",
+        Text::MailPasteCode => "Code of your opponent: ",
+        Text::MailFirst => "You fire first.",
+        Text::MailSecond => "Your opponent fires first: paste their code when it arrives.",
+        Text::MailOpponentFires => "Your opponent fires at {0}: {1}",
+        Text::MailSaved => "Game saved: run naval mail again to go on.",
+        Text::MailInvalidCode => "{0} is not a valid code",
+        Text::MailOwnCode => "This is your own code: paste the one of your opponent",
+        Text::MailUnexpectedCode => "This code was not expected now",
+        Text::MailOutOfOrder => {
+            "This is the code of turn {1}, but the one of turn {0} was expected"
+        }
+        Text::MailCheckFleet => {
+            "Paste the last code of your opponent, which reveals their fleet, to check their reports."
+        }
+        Text::MailFleetChecked => "The fleet of your opponent matches all their reports.",
+        Text::MailChangedFleet => {
+            "The fleet of your opponent is not the one of their opening code: they moved their ships!"
+        }
+        Text::MailInvalidFleet => "The fleet revealed by your opponent is not valid: {0}",
+        Text::MailWrongReport => "Your shot at {0} was reported as {1}, but it was {2}",
+    }
+}
+
+fn italian(text: Text) -> &'static str {
+    match text {
+        Text::OpponentGrid => "Griglia avversaria",
+        Text::Tactical => "Tattica",
+        Text::EnemyFleet => "Flotta nemica",
+        Text::YourFleet => "La tua flotta",
+        Text::Weapon => " Arma: ",
+        Text::Shot => "Colpo",
+        Text::Bombard => "Bombardamento ×{0}",
+        Text::Scan => "Scansione ×{0}",
+        Text::MoveSubmarine => "Sposta sottomarino ×{0}",
+        Text::ConfirmShot => "Sparare in {0}? Invio o Y per confermare",
+        Text::AlreadyShot => "Hai già sparato in {0}",
+        Text::GameOver => "Partita finita",
+        Text::YourTurn => "Il tuo turno",
+        Text::ComputerTurn => "Turno del computer",
+        Text::ShotsFired => "{0} colpi sparati",
+        Text::TryHint => "Prova {0} (suggerimenti rimasti: {1})",
+        Text::HitShootAgain => "Colpito! Spara ancora.",
+        Text::ShootAgain => "Spara ancora.",
+        Text::SubmarineMoved => "Il sottomarino nemico si è spostato!",
+        Text::ComputerThinking => "Il computer sta pensando…",
+        Text::ComputerFired => "Il computer ha sparato in {0} — {1}",
+        Text::Hit => "Colpito",
+        Text::Miss => "Acqua",
+        Text::Mine => "Mina",
+        Text::Sunk => "{0} affondato",
+        Text::YouSank => "Hai affondato il loro {0}!",
+        Text::Fogged => "Nascosto dalla nebbia",
+        Text::YourShotAt => "Il tuo colpo in {0}: {1}.",
+        Text::ComputerShotAt => "Il computer ha sparato in {0}: {1}.",
+        Text::Coordinates => " Bersaglio: ",
+        Text::CoordinatesKeys => " (scrivi una cella, come B7, poi Invio)",
+
+        Text::MatchOver => "Partita finita!",
+        Text::YouResigned => "Ti sei arreso. ",
+        Text::YourTimeUp => "Il tuo tempo è scaduto. ",
+        Text::ComputerTimeUp => "Il tempo del computer è scaduto. ",
+        Text::YourShellsOut => "Hai finito le munizioni. ",
+        Text::ComputerShellsOut => "Il computer ha finito le munizioni. ",
+        Text::Draw => "Entrambe le flotte sono affondate. È un pareggio!",
+        Text::YouWin => "HAI VINTO!!!",
+        Text::YouLose => "Hai perso! :(",
+        Text::ShellsLeft => "Munizioni rimaste: {0} a te, {1} al computer",
+        Text::DailyOver => "Torna domani per una nuova sfida! Premi Invio per continuare.",
+        Text::Continue => "Premi Invio per continuare.",
+        Text::SeriesWon => "Hai vinto la serie! Premi Invio per una nuova serie.",
+        Text::SeriesLost => "Il computer ha vinto la serie. Premi Invio per una nuova serie.",
+        Text::NextGame => "Premi Invio per la prossima partita.",
+
+        Text::DeployFleet => "Schiera la tua flotta",
+        Text::Help => "Aiuto",
+        Text::Welcome => "Benvenuto in Naval - La battaglia navale",
+        Text::Use => "Usa:",
+        Text::HelpMove => "- le frecce: per muovere la nave",
+        Text::HelpHorizontal => "- {0}: per mettere la nave in orizzontale",
+        Text::HelpVertical => "- {0}: per mettere la nave in verticale",
+        Text::HelpPlace => "- Invio: per piazzarla",
+        Text::HelpSelect => "- Tab: per selezionare una nave piazzata",
+        Text::HelpPickUp => "- Invio su una nave selezionata: per riprenderla",
+        Text::HelpLayout => "- 1-9: per schierare uno schema salvato",
+        Text::HelpAutoPlace => "- {0}: per schierare la flotta con la prossima strategia",
+        Text::HelpSaveLayout => "- {0}: per salvare la flotta come schema",
+        Text::HelpSettings => "- {0}: per aprire le impostazioni",
+        Text::HelpLeaderboard => "- {0}: per vedere la classifica",
+        Text::HelpNetwork => "- {0}: per giocare contro qualcuno in rete",
+        Text::HelpMines => "- Invio, schierata la flotta: per piazzare una delle tue {0} mine",
+        Text::HelpRemoveMine => "- Backspace: per togliere l'ultima mina",
+        Text::SavedLayouts => "Schemi salvati:",
+        Text::LayoutName => "Nome dello schema: ",
+        Text::LayoutNameKeys => " (Invio per salvare, Esc per annullare)",
+        Text::Selected => "Selezionata: ",
+        Text::SelectedKeys => ". Premi Invio per riprenderla, Tab per la prossima",
+        Text::PlaceYour => "Piazza la tua nave: ",
+        Text::Size => " [dimensione: ",
+        Text::OnFreeCell => " su una cella libera [rimaste: ",
+        Text::FleetReady => "La tua flotta è pronta: premi ",
+        Text::StartBattle => " per iniziare la battaglia",
+        Text::ShipPlaced => "{0} schierato",
+        Text::LayoutDeployed => "Schema {0} schierato",
+        Text::LayoutInvalid => "Lo schema {0} non è valido: {1}",
+        Text::LayoutSaved => "Schema {0} salvato",
+        Text::ShipOnIsland => "una nave è sull'isola in {0}",
+        Text::FleetDeployed => "Flotta schierata con la strategia {0}",
+        Text::OpenSea => "{0}, si gioca in mare aperto",
+        Text::BuiltInComputer => "{0}, si gioca contro il computer predefinito",
+
+        Text::TypeHelp => "Scrivi help per i comandi.",
+        Text::InvalidCommand => "{0}. Scrivi help per i comandi",
+        Text::MissingFile => "save ha bisogno del nome di un file, come save partita.txt",
+        Text::YourShot => "Il tuo colpo: ",
+        Text::GameSaved => "Partita salvata in {0}",
+        Text::CannotSave => "Impossibile salvare in {0}: {1}",
+        Text::CommandsHelp => {
+            "Comandi:
+  A1..J10      spara nella cella
+  show         mostra la tua flotta e i tuoi colpi
+  save <file>  salva la cronaca della partita nel file
+  help         mostra questo aiuto
+  quit         abbandona la partita"
+        }
+        Text::YouFire => "Spari in {0}: {1}",
+        Text::ComputerFires => "Il computer spara in {0}: {1}",
+        Text::FogHidden => "nascosto dalla nebbia.",
+        Text::MineExplodes => "esplode una mina!",
+        Text::ShipSunk => "{0} affondato!",
+        Text::HitShot => "colpito!",
+        Text::MissShot => "acqua.",
+        Text::EnemyWaters => "Acque nemiche",
+        Text::YourFleetWaters => "La tua flotta",
+        Text::CliYouWin => "Hai vinto!",
+        Text::CliYouLose => "Hai perso!",
+        Text::CliDraw => "Entrambe le flotte sono affondate: è un pareggio!",
+
+        Text::MailHelp => {
+            "Scrivi una cella per sparare, come B7, e incolla i codici del tuo avversario. Scrivi \
+             show per vedere le griglie, quit per continuare più tardi."
+        }
+        Text::MailOpening => {
+            "Manda questo codice di apertura al tuo avversario, poi incolla il suo: {0}"
+        }
+        Text::MailSendCode => "Manda questo codice al tuo avversario: {0}",
+        Text::MailPasteCode => "Codice del tuo avversario: ",
+        Text::MailFirst => "Il primo colpo è tuo.",
+        Text::MailSecond => {
+            "Il primo colpo è del tuo avversario: incolla il suo codice quando arriva."
+        }
+        Text::MailOpponentFires => "Il tuo avversario spara in {0}: {1}",
+        Text::MailSaved => "Partita salvata: lancia di nuovo naval mail per continuare.",
+        Text::MailInvalidCode => "{0} non è un codice valido",
+        Text::MailOwnCode => "Questo è il tuo codice: incolla quello del tuo avversario",
+        Text::MailUnexpectedCode => "Questo codice non era atteso adesso",
+        Text::MailOutOfOrder => {
+            "Questo è il codice del turno {1}, ma era atteso quello del turno {0}"
+        }
+        Text::MailCheckFleet => {
+            "Incolla l'ultimo codice del tuo avversario, che rivela la sua flotta, per verificare i \
+             suoi resoconti."
+        }
+        Text::MailFleetChecked => {
+            "La flotta del tuo avversario corrisponde a tutti i suoi resoconti."
+        }
+        Text::MailChangedFleet => {
+            "La flotta del tuo avversario non è quella del suo codice di apertura: ha spostato le \
+             navi!"
+        }
+        Text::MailInvalidFleet => "La flotta rivelata dal tuo avversario non è valida: {0}",
+        Text::MailWrongReport => "Il tuo colpo in {0} è stato segnato come {1}, ma era {2}",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::grid::Cell;
+    use rstest::rstest;
+    use strum::IntoEnumIterator;
+
+    #[rstest]
+    #[case("it", Some(Locale::Italian))]
+    #[case("it_IT.UTF-8", Some(Locale::Italian))]
+    #[case("it-CH", Some(Locale::Italian))]
+    #[case("en_GB.UTF-8", Some(Locale::English))]
+    #[case("C", Some(Locale::English))]
+    #[case("fr_FR.UTF-8", None)]
+    #[case("", None)]
+    fn test_from_tag(#[case] tag: &str, #[case] expected: Option<Locale>) {
+        assert_eq!(Locale::from_tag(tag), expected);
+    }
+
+    #[rstest]
+    #[case(Locale::English, "Try B7 (2 hints left)")]
+    #[case(Locale::Italian, "Prova B7 (suggerimenti rimasti: 2)")]
+    fn test_format(#[case] locale: Locale, #[case] expected: &str) {
+        let cell = Cell::bounded(1, 6);
+        assert_eq!(locale.format(Text::TryHint, &[&cell, &2]), expected);
+    }
+
+    #[rstest]
+    fn test_translations_have_the_same_placeholders() {
+        let placeholders = |text: &str| -> Vec<usize> {
+            (0..3)
+                .filter(|i| text.contains(&format!("{{{i}}}")))
+                .collect()
+        };
+        for text in Text::iter() {
+            let english = Locale::English.text(text);
+            let italian = Locale::Italian.text(text);
+            assert!(!italian.is_empty(), "{text:?}");
+            assert_eq!(placeholders(english), placeholders(italian), "{text:?}");
+        }
+    }
+
+    #[rstest]
+    fn test_error() {
+        let error = GameError::OnIsland(Cell::bounded(0, 0));
+        assert_eq!(Locale::English.error(&error), "A1 is an island");
+        assert_eq!(Locale::Italian.error(&error), "A1 è un'isola");
+    }
+}
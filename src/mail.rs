@@ -0,0 +1,419 @@
+//! This module contains the Naval Battle game by correspondence.
+//!
+//! Each player runs the game in their own terminal, and the two clients exchange the short
+//! codes of a [Correspondence] over a chat or an email: the player copies the code printed after
+//! their shot, and pastes the code of their opponent when it arrives. No connection is needed,
+//! and a turn can wait for days: the game is saved after every code, and goes on from where it
+//! was when it is run again.
+//!
+//! At the end of the game, both players send a last code which reveals their fleet, so that each
+//! client checks that the fleet of the opponent was not moved during the game, and that every
+//! shot was reported as it hit it.
+//!
+//! The game by correspondence follows the classic rules, with one shot per turn, and the fleet
+//! of the player is deployed at random.
+//!
+use crate::cli;
+use crate::config::{self, Config};
+use crate::engine::correspondence::{
+    Correspondence, CorrespondenceError, Inconsistency, Report, Status,
+};
+use crate::engine::fleet::Fleet;
+use crate::engine::grid::Cell;
+use crate::locale::{Locale, Text};
+use std::io::{self, BufRead, Write};
+use std::path::PathBuf;
+use std::str::FromStr;
+
+/// The Naval Battle game by correspondence.
+///
+/// The game asks for a shot when the player fires next, and for the code of the opponent
+/// otherwise, until a side has won, the player quits or the input is over.
+pub struct NavalBattleMail<R, W> {
+    side: Correspondence,
+    path: Option<PathBuf>,
+    input: R,
+    output: W,
+    colors: bool,
+    locale: Locale,
+}
+
+impl<R: BufRead, W: Write> NavalBattleMail<R, W> {
+    /// Goes on with the game in progress, or starts a new one with a random fleet, in the
+    /// configured language. The game is saved in the config directory.
+    ///
+    /// An error is returned if the game in progress can't be loaded.
+    pub fn new(config: &Config, input: R, output: W) -> Result<Self, config::Error> {
+        let path = Correspondence::path();
+        let saved = match &path {
+            Some(path) => Correspondence::load_from(path)?,
+            None => None,
+        };
+        let side = match saved {
+            Some(side) => side,
+            None => Correspondence::new(&Fleet::build(|kind| kind.random())),
+        };
+        let mut mail = Self::with_side(side, input, output);
+        mail.path = path;
+        mail.set_locale(config.locale());
+
+        Ok(mail)
+    }
+
+    /// Creates a front-end to play the given side, which is not saved.
+    pub fn with_side(side: Correspondence, input: R, output: W) -> Self {
+        Self {
+            side,
+            path: None,
+            input,
+            output,
+            colors: false,
+            locale: Locale::default(),
+        }
+    }
+
+    /// Prints the grids in colors, or in plain text when `colors` is `false` (the default).
+    pub fn set_colors(&mut self, colors: bool) {
+        self.colors = colors;
+    }
+
+    /// Prints the messages in the given language, English by default.
+    pub fn set_locale(&mut self, locale: Locale) {
+        self.locale = locale;
+    }
+
+    /// Returns the side of the player.
+    pub fn side(&self) -> &Correspondence {
+        &self.side
+    }
+
+    /// Plays the game until it is over, until the player quits or until the input is over.
+    ///
+    /// An invalid cell or code is reported, and the line is asked again.
+    pub fn run(&mut self) -> io::Result<()> {
+        writeln!(self.output, "{}", self.locale.text(Text::MailHelp))?;
+        self.show_grids()?;
+        let text = match self.side.status() {
+            Status::Opening => Some(Text::MailOpening),
+            Status::TheirTurn => Some(Text::MailSendCode),
+            _ => None,
+        };
+        if let (Some(text), Some(code)) = (text, self.side.outgoing()) {
+            writeln!(self.output, "{}", self.locale.format(text, &[&code]))?;
+        }
+
+        let mut announced = false;
+        loop {
+            let status = self.side.status();
+            if matches!(status, Status::Won | Status::Lost) {
+                if !announced {
+                    announced = true;
+                    self.announce(status)?;
+                }
+                // The game is over once the fleet of the opponent has been verified.
+                if self.side.verify().is_some() {
+                    return self.remove();
+                }
+            }
+            let prompt = match status {
+                Status::YourTurn => Text::YourShot,
+                _ => Text::MailPasteCode,
+            };
+            write!(self.output, "{}", self.locale.text(prompt))?;
+            self.output.flush()?;
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                writeln!(self.output)?;
+                return Ok(());
+            }
+
+            match line.trim().to_ascii_lowercase().as_str() {
+                "" => {}
+                "quit" => {
+                    if self.path.is_some() {
+                        writeln!(self.output, "{}", self.locale.text(Text::MailSaved))?;
+                    }
+                    return Ok(());
+                }
+                "show" => self.show_grids()?,
+                "help" => writeln!(self.output, "{}", self.locale.text(Text::MailHelp))?,
+                _ if self.side.status() == Status::YourTurn => self.fire(line.trim())?,
+                _ => self.receive(&line)?,
+            }
+        }
+    }
+
+    // Fires at the cell, and prints the code of the turn.
+    fn fire(&mut self, cell: &str) -> io::Result<()> {
+        let result = Cell::from_str(cell)
+            .map_err(|e| self.locale.format(Text::InvalidCommand, &[&e]))
+            .and_then(|cell| self.side.fire(cell).map_err(|e| self.error(&e)));
+        match result {
+            Ok(code) => {
+                self.save()?;
+                writeln!(
+                    self.output,
+                    "{}",
+                    self.locale.format(Text::MailSendCode, &[&code])
+                )
+            }
+            Err(message) => writeln!(self.output, "{message}"),
+        }
+    }
+
+    // Reads a code of the opponent, and prints what it tells.
+    fn receive(&mut self, code: &str) -> io::Result<()> {
+        let verified = self.side.verify().is_some();
+        let received = match self.side.receive(code) {
+            Ok(received) => received,
+            Err(e) => return writeln!(self.output, "{}", self.error(&e)),
+        };
+        self.save()?;
+
+        if let Some((cell, report)) = &received.yours {
+            let result = self.describe(report);
+            writeln!(
+                self.output,
+                "{}",
+                self.locale.format(Text::YouFire, &[cell, &result])
+            )?;
+        }
+        if let Some((cell, report)) = &received.theirs {
+            let result = self.describe(report);
+            writeln!(
+                self.output,
+                "{}",
+                self.locale
+                    .format(Text::MailOpponentFires, &[cell, &result])
+            )?;
+        }
+
+        if let Some(inconsistencies) = self.side.verify()
+            && !verified
+        {
+            return self.report_verification(&inconsistencies);
+        }
+        match (received.yours, received.theirs, self.side.status()) {
+            (None, None, Status::YourTurn) => {
+                writeln!(self.output, "{}", self.locale.text(Text::MailFirst))
+            }
+            (None, None, _) => writeln!(self.output, "{}", self.locale.text(Text::MailSecond)),
+            (_, Some(_), _) => self.show_grids(),
+            _ => Ok(()),
+        }
+    }
+
+    // Prints the code which reveals the fleet of the player and the result, then asks for the
+    // last code of the opponent if their fleet has not been revealed yet.
+    fn announce(&mut self, status: Status) -> io::Result<()> {
+        if let Some(code) = self.side.outgoing() {
+            writeln!(
+                self.output,
+                "{}",
+                self.locale.format(Text::MailSendCode, &[&code])
+            )?;
+        }
+        let result = match status {
+            Status::Won => Text::CliYouWin,
+            _ => Text::CliYouLose,
+        };
+        writeln!(self.output, "{}", self.locale.text(result))?;
+        if self.side.verify().is_none() {
+            writeln!(self.output, "{}", self.locale.text(Text::MailCheckFleet))?;
+        }
+
+        Ok(())
+    }
+
+    // Prints whether the revealed fleet of the opponent matches their opening and their
+    // reports, or every inconsistency found.
+    fn report_verification(&mut self, inconsistencies: &[Inconsistency]) -> io::Result<()> {
+        if inconsistencies.is_empty() {
+            return writeln!(self.output, "{}", self.locale.text(Text::MailFleetChecked));
+        }
+        for inconsistency in inconsistencies {
+            let message = match inconsistency {
+                Inconsistency::Commitment => self.locale.text(Text::MailChangedFleet).to_string(),
+                Inconsistency::Fleet(e) => self.locale.format(Text::MailInvalidFleet, &[e]),
+                Inconsistency::Report {
+                    cell,
+                    reported,
+                    actual,
+                } => self.locale.format(
+                    Text::MailWrongReport,
+                    &[cell, &self.describe(reported), &self.describe(actual)],
+                ),
+            };
+            writeln!(self.output, "{message}")?;
+        }
+
+        Ok(())
+    }
+
+    // Removes the saved game, once it is over.
+    fn remove(&mut self) -> io::Result<()> {
+        if let Some(path) = &self.path
+            && let Err(e) = Correspondence::remove_from(path)
+        {
+            writeln!(
+                self.output,
+                "{}",
+                self.locale.format(Text::CannotSave, &[&path.display(), &e])
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Saves the game, if it has a path, reporting an error without stopping the game.
+    fn save(&mut self) -> io::Result<()> {
+        match &self.path {
+            Some(path) => match self.side.save_to(path) {
+                Ok(()) => Ok(()),
+                Err(e) => writeln!(
+                    self.output,
+                    "{}",
+                    self.locale.format(Text::CannotSave, &[&path.display(), &e])
+                ),
+            },
+            None => Ok(()),
+        }
+    }
+
+    // Prints the shots of the player next to their fleet, with the shots of the opponent.
+    fn show_grids(&mut self) -> io::Result<()> {
+        let grids = cli::side_by_side(
+            &self.side.shots_grid(),
+            &[],
+            &self.side.fleet_grid(),
+            self.colors,
+            self.locale,
+        );
+
+        writeln!(self.output, "{grids}\n")
+    }
+
+    // Describes the result of a shot.
+    fn describe(&self, report: &Report) -> String {
+        match report {
+            Report::Miss => self.locale.text(Text::MissShot).to_string(),
+            Report::Hit => self.locale.text(Text::HitShot).to_string(),
+            Report::Sunk(kind) => self.locale.format(Text::ShipSunk, &[kind]),
+        }
+    }
+
+    // Returns the message of a refused shot or code.
+    fn error(&self, error: &CorrespondenceError) -> String {
+        match error {
+            CorrespondenceError::InvalidCode(code) => {
+                self.locale.format(Text::MailInvalidCode, &[code])
+            }
+            CorrespondenceError::OwnCode => self.locale.text(Text::MailOwnCode).to_string(),
+            CorrespondenceError::UnexpectedCode => {
+                self.locale.text(Text::MailUnexpectedCode).to_string()
+            }
+            CorrespondenceError::OutOfOrder { expected, found } => {
+                self.locale.format(Text::MailOutOfOrder, &[expected, found])
+            }
+            CorrespondenceError::AlreadyShot(cell) => {
+                self.locale.format(Text::AlreadyShot, &[cell])
+            }
+            _ => error.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::correspondence::tests::{opened, sink};
+    use rstest::rstest;
+    use std::io::Cursor;
+
+    fn side(code: &str, salt: u8) -> Correspondence {
+        Correspondence::with_salt(&Fleet::from_code(code).unwrap(), [salt; 16])
+    }
+
+    fn play(side: Correspondence, input: &str) -> (Correspondence, String) {
+        let mut output = Vec::new();
+        let mut mail =
+            NavalBattleMail::with_side(side, Cursor::new(input.to_string()), &mut output);
+        mail.run().unwrap();
+        let side = mail.side;
+
+        (side, String::from_utf8(output).unwrap())
+    }
+
+    #[rstest]
+    fn test_opening() {
+        let (alice, bob) = (
+            side("A1H/A3H/A5H/A7H/A9H", 1),
+            side("A1V/C1V/E1V/G1V/I1V", 2),
+        );
+        let (own, opponent) = (alice.opening(), bob.opening());
+
+        let (alice, output) = play(alice, &format!("nonsense\n{own}\n{opponent}\n"));
+
+        assert!(output.contains(&format!(
+            "Send this opening code to your opponent, then paste theirs: {own}"
+        )));
+        assert!(output.contains("nonsense is not a valid code"));
+        assert!(output.contains("This is your own code"));
+        assert!(output.contains(match alice.status() {
+            Status::YourTurn => "You fire first.",
+            _ => "Your opponent fires first",
+        }));
+        assert_ne!(alice.status(), Status::Opening);
+    }
+
+    #[rstest]
+    fn test_turns() {
+        let (mut alice, mut bob) = (
+            side("A1H/A3H/A5H/A7H/A9H", 1),
+            side("A1V/C1V/E1V/G1V/I1V", 2),
+        );
+        alice.receive(&bob.opening()).unwrap();
+        bob.receive(&alice.opening()).unwrap();
+        let (first, mut second) = match alice.status() {
+            Status::YourTurn => (alice, bob),
+            _ => (bob, alice),
+        };
+
+        let (first, output) = play(first, "K1\nA1\nA1\n");
+        let code = first.outgoing().unwrap();
+        second.receive(&code).unwrap();
+        let reply = second.fire(Cell::bounded(9, 9)).unwrap();
+        let (first, output_after) = play(first, &format!("{reply}\n{reply}\nA1\nquit\n"));
+
+        assert!(output.contains("K1 does not represent a valid cell. Type help"));
+        assert!(output.contains(&format!("Send this code to your opponent: {code}")));
+        assert!(output.contains("Code of your opponent: A1 is not a valid code"));
+        assert!(output_after.contains(&format!("Send this code to your opponent: {code}")));
+        assert!(output_after.contains("You fire at A1: hit!"));
+        assert!(output_after.contains("Your opponent fires at J10: miss."));
+        assert!(output_after.contains("Your shot: A1 has already been shot"));
+        assert_eq!(first.status(), Status::YourTurn);
+    }
+
+    #[rstest]
+    fn test_end_of_game() {
+        let (mut winner, mut loser) = opened();
+        sink(&mut winner, &mut loser);
+        let reveal = loser.outgoing().unwrap();
+        winner.receive(&reveal).unwrap();
+
+        let (_, output) = play(winner.clone(), "");
+        let (loser, loser_output) = play(loser, &format!("{}\n", winner.outgoing().unwrap()));
+
+        assert!(output.contains(&format!(
+            "Send this code to your opponent: {}\nYou win!",
+            winner.outgoing().unwrap()
+        )));
+        assert!(!output.contains("Code of your opponent"));
+        assert!(loser_output.contains(&format!(
+            "Send this code to your opponent: {reveal}\nYou lose!\nPaste the last code"
+        )));
+        assert!(loser_output.contains("The fleet of your opponent matches all their reports."));
+        assert_eq!(loser.verify(), Some(Vec::new()));
+    }
+}
@@ -1,9 +1,287 @@
-mod engine;
-mod tui;
-
-use std::io;
-use tui::NavalBattleTui;
+use naval::bot::NavalBattleBot;
+use naval::cli::{self, NavalBattleCli};
+use naval::config::Config;
+use naval::engine::replay::Replay;
+use naval::engine::strategy::StrategyKind;
+use naval::export;
+use naval::mail::NavalBattleMail;
+use naval::metrics::Metrics;
+#[cfg(feature = "server")]
+use naval::server::ServerOptions;
+use naval::tui::NavalBattleTui;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Mutex;
+use std::{env, fs, io, iter, process};
+use strum::IntoEnumIterator;
+use tracing::Level;
+use tracing_subscriber::fmt::format::FmtSpan;
 
 fn main() -> io::Result<()> {
-    ratatui::run(|terminal| NavalBattleTui::new().run(terminal))
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--log-file") {
+        args.remove(index);
+        if index == args.len() {
+            eprintln!("missing file after --log-file\nusage: naval --log-file FILE [COMMAND]");
+            process::exit(2);
+        }
+        log_to(Path::new(&args.remove(index)))?;
+    }
+
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("{e}, using the default configuration");
+        Config::default()
+    });
+
+    match args.first().map(String::as_str) {
+        None => ratatui::run(|terminal| NavalBattleTui::new(config).run(terminal)),
+        Some("--debug-ai") => ratatui::run(|terminal| {
+            let mut tui = NavalBattleTui::new(config);
+            tui.set_debug_ai(true);
+            tui.run(terminal)
+        }),
+        Some("--tutorial") => ratatui::run(|terminal| {
+            let mut tui = NavalBattleTui::new(config);
+            tui.start_tutorial();
+            tui.run(terminal)
+        }),
+        Some("watch") => {
+            let (a, b) = watch_strategies(args.iter().skip(1).cloned()).unwrap_or_else(|e| {
+                eprintln!("{e}\nusage: naval watch [--a STRATEGY] [--b STRATEGY]");
+                process::exit(2);
+            });
+            let mut tui = NavalBattleTui::new(config);
+            tui.start_spectating(a, b).map_err(io::Error::other)?;
+            ratatui::run(|terminal| tui.run(terminal))
+        }
+        Some("sim") => {
+            let (games, seed, (a, b)) = games_option(args.iter().skip(1).cloned(), 100)
+                .and_then(|(games, seed, rest)| {
+                    Ok((games, seed, watch_strategies(rest.into_iter())?))
+                })
+                .unwrap_or_else(|e| {
+                    eprintln!(
+                        "{e}\nusage: naval sim [--games N] [--seed N] [--a STRATEGY] [--b STRATEGY]"
+                    );
+                    process::exit(2);
+                });
+            let matches = vec![(a, b); games as usize];
+            let metrics =
+                Metrics::play_all(config.variant, &matches, seed).map_err(io::Error::other)?;
+            print!("{}", metrics.summary());
+            Ok(())
+        }
+        Some("tournament") => {
+            let (games, seed) = games_option(args.iter().skip(1).cloned(), 10)
+                .and_then(|(games, seed, rest)| match rest.first() {
+                    Some(arg) => Err(format!("unknown option: {arg}")),
+                    None => Ok((games, seed)),
+                })
+                .unwrap_or_else(|e| {
+                    eprintln!("{e}\nusage: naval tournament [--games N] [--seed N]");
+                    process::exit(2);
+                });
+            let matches: Vec<(StrategyKind, StrategyKind)> = StrategyKind::iter()
+                .flat_map(|a| {
+                    StrategyKind::iter()
+                        .filter(move |b| *b != a)
+                        .flat_map(move |b| iter::repeat_n((a, b), games as usize))
+                })
+                .collect();
+            let metrics =
+                Metrics::play_all(config.variant, &matches, seed).map_err(io::Error::other)?;
+            print!("{}", metrics.summary());
+            Ok(())
+        }
+        Some("replay") => {
+            let replay = load_replay(args.get(1).cloned())?;
+            let mut tui = NavalBattleTui::new(config);
+            tui.start_replay(&replay).map_err(io::Error::other)?;
+            ratatui::run(|terminal| tui.run(terminal))
+        }
+        Some("export") => {
+            let Some(file) = args.get(1).map(PathBuf::from) else {
+                eprintln!("missing export file\nusage: naval export FILE [REPLAY]");
+                process::exit(2);
+            };
+            let gif = file.extension().is_some_and(|extension| extension == "gif");
+            if gif && cfg!(not(feature = "gif")) {
+                eprintln!("the GIF export needs the gif feature, export a .cast file instead");
+                process::exit(2);
+            }
+            let replay = load_replay(args.get(2).cloned())?;
+            let output = io::BufWriter::new(fs::File::create(&file)?);
+            match gif {
+                #[cfg(feature = "gif")]
+                true => export::gif(&replay, output),
+                _ => export::asciinema(&replay, output),
+            }
+            .map_err(io::Error::other)
+        }
+        Some("cli") => {
+            let mut cli = NavalBattleCli::new(&config, io::stdin().lock(), io::stdout())
+                .map_err(io::Error::other)?;
+            cli.set_colors(io::stdout().is_terminal());
+            cli.run()
+        }
+        Some("mail") => {
+            let mut mail = NavalBattleMail::new(&config, io::stdin().lock(), io::stdout())
+                .map_err(io::Error::other)?;
+            mail.set_colors(io::stdout().is_terminal());
+            mail.run()
+        }
+        Some("bot") if args.get(1).map(String::as_str) == Some("--json") => {
+            let game = cli::configured_game(&config).map_err(io::Error::other)?;
+            NavalBattleBot::new(game, io::stdin().lock(), io::stdout()).run()
+        }
+        #[cfg(feature = "server")]
+        Some("serve") => {
+            let (address, options) =
+                serve_options(args.iter().skip(1).cloned()).unwrap_or_else(|e| {
+                    eprintln!(
+                        "{e}\nusage: naval serve [ADDR] [--idle MINUTES] [--save DIR] [--tls]"
+                    );
+                    process::exit(2);
+                });
+            naval::server::serve(config, &address, &options)
+        }
+        #[cfg(not(feature = "server"))]
+        Some("serve") => {
+            eprintln!("the HTTP API needs the server feature");
+            process::exit(2);
+        }
+        #[cfg(feature = "schema")]
+        Some("schema") => {
+            let schema = serde_json::to_string_pretty(&naval::proto::schema())?;
+            println!("{schema}");
+            Ok(())
+        }
+        #[cfg(not(feature = "schema"))]
+        Some("schema") => {
+            eprintln!("the schema of the protocol needs the schema feature");
+            process::exit(2);
+        }
+        Some(command) => {
+            eprintln!(
+                "unknown command: {command}\nusage: naval [--log-file FILE] [--debug-ai | --tutorial | watch | sim | tournament | replay [FILE] | export FILE [REPLAY] | cli | mail | bot --json | serve [ADDR] [--idle MINUTES] [--save DIR] [--tls] | schema]"
+            );
+            process::exit(2);
+        }
+    }
+}
+
+// Writes the spans and the events of the game to the given file, as a JSON object per line, at
+// the level given by the `NAVAL_LOG` variable, `debug` by default. The terminal is left to the
+// game.
+fn log_to(path: &Path) -> io::Result<()> {
+    let file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)?;
+    let level = env::var("NAVAL_LOG")
+        .ok()
+        .and_then(|level| level.parse().ok())
+        .unwrap_or(Level::DEBUG);
+    tracing_subscriber::fmt()
+        .json()
+        .with_writer(Mutex::new(file))
+        .with_max_level(level)
+        .with_span_events(FmtSpan::CLOSE)
+        .init();
+
+    Ok(())
+}
+
+// Reads the strategies of the two sides of `naval watch` from `--a` and `--b`, both smart by
+// default.
+fn watch_strategies(
+    mut args: impl Iterator<Item = String>,
+) -> Result<(StrategyKind, StrategyKind), String> {
+    let (mut a, mut b) = (StrategyKind::Smart, StrategyKind::Smart);
+    while let Some(arg) = args.next() {
+        let side = match arg.as_str() {
+            "--a" => &mut a,
+            "--b" => &mut b,
+            _ => return Err(format!("unknown option: {arg}")),
+        };
+        let name = args.next().ok_or(format!("missing strategy after {arg}"))?;
+        *side = StrategyKind::from_str(&name).map_err(|_| format!("unknown strategy: {name}"))?;
+    }
+
+    Ok((a, b))
+}
+
+// Reads the number of games of `naval sim` or `naval tournament` from `--games`, with the given
+// default, and the seed which makes them the same on every run from `--seed`, and returns the
+// other arguments.
+fn games_option(
+    mut args: impl Iterator<Item = String>,
+    default: u32,
+) -> Result<(u32, Option<u64>, Vec<String>), String> {
+    let (mut games, mut seed, mut rest) = (default, None, Vec::new());
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--games" => {
+                let value = args.next().ok_or("missing number after --games")?;
+                games = value
+                    .parse()
+                    .map_err(|_| format!("invalid number of games: {value}"))?;
+            }
+            "--seed" => {
+                let value = args.next().ok_or("missing number after --seed")?;
+                seed = Some(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid seed: {value}"))?,
+                );
+            }
+            _ => rest.push(arg),
+        }
+    }
+
+    Ok((games, seed, rest))
+}
+
+// Reads the address of `naval serve`, 127.0.0.1:8080 by default, the minutes after which an
+// idle game is dropped from `--idle`, 30 by default, the folder where the duels are saved
+// from `--save`, and whether the connections are encrypted from `--tls`, with the certificate
+// kept in the config folder.
+#[cfg(feature = "server")]
+fn serve_options(
+    mut args: impl Iterator<Item = String>,
+) -> Result<(String, ServerOptions), String> {
+    let mut address = "127.0.0.1:8080".to_string();
+    let mut options = ServerOptions::default();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--idle" => {
+                let value = args.next().ok_or("missing minutes after --idle")?;
+                let minutes: u64 = value
+                    .parse()
+                    .map_err(|_| format!("invalid minutes: {value}"))?;
+                options.idle = std::time::Duration::from_secs(minutes * 60);
+            }
+            "--save" => {
+                let dir = args.next().ok_or("missing folder after --save")?;
+                options.save_dir = Some(PathBuf::from(dir));
+            }
+            #[cfg(feature = "tls")]
+            "--tls" => options.tls = Some(Config::dir().ok_or("no config folder for --tls")?),
+            _ if arg.starts_with("--") => return Err(format!("unknown option: {arg}")),
+            _ => address = arg,
+        }
+    }
+
+    Ok((address, options))
+}
+
+// Loads the replay from the given file, or the replay of the last battle played in the TUI.
+fn load_replay(path: Option<String>) -> io::Result<Replay> {
+    let path = match path {
+        Some(path) => PathBuf::from(path),
+        None => Replay::path().ok_or(io::Error::other("no config directory available"))?,
+    };
+
+    Replay::load_from(&path).map_err(io::Error::other)
 }
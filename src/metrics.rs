@@ -0,0 +1,520 @@
+//! This module contains the metrics of the games: how many games are played, how many shots they
+//! take, how long the computer thinks about its moves and how often every player wins.
+//!
+//! The [Metrics] are collected by the `sim` and `tournament` commands, which let the computer
+//! strategies play against each other and print a [summary](Metrics::summary) of them, and by
+//! the server, which exposes them in the [Prometheus](Metrics::prometheus) text format. The
+//! moves of a strategy are timed by wrapping it in a [Timed] strategy.
+//!
+use crate::engine::clock::Instant;
+use crate::engine::fleet::{Fleet, ShipKind};
+use crate::engine::game::{GameBuilder, GameError, GamePhase};
+use crate::engine::grid::Cell;
+use crate::engine::parallel;
+use crate::engine::rules::Variant;
+#[cfg(feature = "async")]
+use crate::engine::strategy::Preparation;
+use crate::engine::strategy::{Strategy, StrategyKind, StrategyView};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::rc::Rc;
+use std::time::Duration;
+
+/// The metrics of the games played, by the players named after their strategy, their difficulty
+/// or their seat.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Metrics {
+    games: u64,
+    draws: u64,
+    shots: Summary,
+    decisions: Summary,
+    players: BTreeMap<String, Record>,
+}
+
+// The count, the sum and the bounds of some observations.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Summary {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+}
+
+impl Summary {
+    fn observe(&mut self, value: f64) {
+        match self.count {
+            0 => (self.min, self.max) = (value, value),
+            _ => (self.min, self.max) = (self.min.min(value), self.max.max(value)),
+        }
+        self.count += 1;
+        self.sum += value;
+    }
+
+    fn merge(&mut self, other: &Summary) {
+        if other.count == 0 {
+            return;
+        }
+        match self.count {
+            0 => (self.min, self.max) = (other.min, other.max),
+            _ => (self.min, self.max) = (self.min.min(other.min), self.max.max(other.max)),
+        }
+        self.count += other.count;
+        self.sum += other.sum;
+    }
+
+    fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+}
+
+// The games played and won by a player.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct Record {
+    games: u64,
+    wins: u64,
+}
+
+impl Metrics {
+    /// Creates the metrics of no game.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records a game between the players, which has ended with the given number of shots,
+    /// fired by both of them. The winner is the position of a player, or `None` for a draw.
+    pub fn record_game(&mut self, players: [&str; 2], winner: Option<usize>, shots: u32) {
+        self.games += 1;
+        if winner.is_none() {
+            self.draws += 1;
+        }
+        self.shots.observe(f64::from(shots));
+        for (index, player) in players.into_iter().enumerate() {
+            let record = self.players.entry(player.to_string()).or_default();
+            record.games += 1;
+            if winner == Some(index) {
+                record.wins += 1;
+            }
+        }
+    }
+
+    /// Records how long the computer has taken to choose a move.
+    pub fn record_decision(&mut self, latency: Duration) {
+        self.decisions.observe(latency.as_secs_f64());
+    }
+
+    /// Adds the metrics of other games to these ones.
+    pub fn merge(&mut self, other: &Metrics) {
+        self.games += other.games;
+        self.draws += other.draws;
+        self.shots.merge(&other.shots);
+        self.decisions.merge(&other.decisions);
+        for (player, other) in &other.players {
+            let record = self.players.entry(player.clone()).or_default();
+            record.games += other.games;
+            record.wins += other.wins;
+        }
+    }
+
+    /// Returns the number of games played to their end.
+    pub fn games(&self) -> u64 {
+        self.games
+    }
+
+    /// Returns the mean number of shots of a game, or `None` without games.
+    pub fn mean_shots(&self) -> Option<f64> {
+        self.shots.mean()
+    }
+
+    /// Returns the mean time taken by the computer to choose a move, or `None` without moves.
+    pub fn mean_decision(&self) -> Option<Duration> {
+        self.decisions.mean().map(Duration::from_secs_f64)
+    }
+
+    /// Returns the share of the games of the player which they have won, from 0 to 1, or `None`
+    /// if they have played no game.
+    pub fn win_rate(&self, player: &str) -> Option<f64> {
+        self.players
+            .get(player)
+            .filter(|record| record.games > 0)
+            .map(|record| record.wins as f64 / record.games as f64)
+    }
+
+    /// Plays a game of the variant between the strategies, on random fleets, and records it
+    /// with the moves of both strategies. The first strategy plays the side of the human player.
+    ///
+    /// A game which the strategies can't end, as one of them has no more moves, is not recorded.
+    pub fn play(
+        &mut self,
+        variant: Variant,
+        a: StrategyKind,
+        b: StrategyKind,
+    ) -> Result<(), GameError> {
+        self.play_with(variant, a, b, StdRng::from_os_rng())
+    }
+
+    /// Like [Metrics::play], but the fleets and the moves are drawn from the seed: the same seed
+    /// plays the same game.
+    pub fn play_seeded(
+        &mut self,
+        variant: Variant,
+        a: StrategyKind,
+        b: StrategyKind,
+        seed: u64,
+    ) -> Result<(), GameError> {
+        self.play_with(variant, a, b, StdRng::seed_from_u64(seed))
+    }
+
+    /// Plays a game of the variant for every pair of strategies, the first one against the
+    /// second one, and returns their metrics.
+    ///
+    /// With the `parallel` feature, the games are played on all the cores. With a seed, every
+    /// game is drawn from the seed and its position, so that the same games are played, with the
+    /// same results, on every run.
+    pub fn play_all(
+        variant: Variant,
+        matches: &[(StrategyKind, StrategyKind)],
+        seed: Option<u64>,
+    ) -> Result<Metrics, GameError> {
+        let games: Vec<(u64, StrategyKind, StrategyKind)> = (0..)
+            .zip(matches)
+            .map(|(index, (a, b))| (index, *a, *b))
+            .collect();
+        let played = parallel::map(&games, |(index, a, b)| {
+            let mut metrics = Metrics::new();
+            match seed {
+                Some(seed) => metrics.play_seeded(variant, *a, *b, seed.wrapping_add(*index)),
+                None => metrics.play(variant, *a, *b),
+            }
+            .map(|_| metrics)
+        });
+
+        played
+            .into_iter()
+            .try_fold(Metrics::new(), |mut metrics, game| {
+                metrics.merge(&game?);
+                Ok(metrics)
+            })
+    }
+
+    // Plays a game whose fleets and moves are drawn from the generator, and records it.
+    fn play_with(
+        &mut self,
+        variant: Variant,
+        a: StrategyKind,
+        b: StrategyKind,
+        mut rng: StdRng,
+    ) -> Result<(), GameError> {
+        let mut game = GameBuilder::new()
+            .variant(variant)
+            .seed(rng.random())
+            .human(
+                &a.to_string(),
+                Fleet::build(|kind| kind.random_with(&mut rng)),
+            )
+            .build()?;
+        let (a_timed, b_timed) = (
+            Timed::new(a.strategy_with_seed(rng.random())),
+            Timed::new(b.strategy_with_seed(rng.random())),
+        );
+        let decisions = [a_timed.decisions(), b_timed.decisions()];
+
+        let shots: usize = game
+            .autoplay(a_timed, b_timed)
+            .map(|event| event.shots.len())
+            .sum();
+        for latency in decisions.iter().flat_map(|decisions| decisions.take()) {
+            self.record_decision(latency);
+        }
+        let winner = match game.phase() {
+            GamePhase::Finished { winner } => Some(winner as usize),
+            GamePhase::Drawn => None,
+            _ => return Ok(()),
+        };
+        self.record_game([&a.to_string(), &b.to_string()], winner, shots as u32);
+
+        Ok(())
+    }
+
+    /// Returns the metrics as a table, with the win rate of every player.
+    pub fn summary(&self) -> String {
+        let mut table = String::new();
+        let _ = writeln!(table, "{:<16}{}", "Games", self.games);
+        let _ = writeln!(table, "{:<16}{}", "Draws", self.draws);
+        let _ = match self.shots.mean() {
+            Some(mean) => writeln!(
+                table,
+                "{:<16}{mean:.1} (min {}, max {})",
+                "Shots per game", self.shots.min, self.shots.max
+            ),
+            None => writeln!(table, "{:<16}-", "Shots per game"),
+        };
+        let _ = match self.decisions.mean() {
+            Some(mean) => writeln!(
+                table,
+                "{:<16}{:.3} ms (max {:.3} ms)",
+                "Decision time",
+                mean * 1000.0,
+                self.decisions.max * 1000.0
+            ),
+            None => writeln!(table, "{:<16}-", "Decision time"),
+        };
+
+        let width = self
+            .players
+            .keys()
+            .map(String::len)
+            .max()
+            .unwrap_or(0)
+            .max(6);
+        let _ = writeln!(
+            table,
+            "\n{:<width$}  {:>6}  {:>6}  {:>8}",
+            "Player", "Games", "Wins", "Win rate"
+        );
+        for (player, record) in &self.players {
+            let rate = 100.0 * record.wins as f64 / record.games.max(1) as f64;
+            let _ = writeln!(
+                table,
+                "{player:<width$}  {:>6}  {:>6}  {rate:>7.1}%",
+                record.games, record.wins
+            );
+        }
+
+        table
+    }
+
+    /// Returns the metrics in the text format of Prometheus, with the name of the players as the
+    /// `player` label.
+    pub fn prometheus(&self) -> String {
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            let _ = writeln!(text, "# HELP naval_{name} {help}");
+            let _ = writeln!(text, "# TYPE naval_{name} {kind}");
+            for (suffix, value) in samples {
+                let _ = writeln!(text, "naval_{name}{suffix} {value}");
+            }
+        };
+        let by_player = |value: &dyn Fn(&Record) -> String| {
+            self.players
+                .iter()
+                .map(|(player, record)| {
+                    (format!("{{player=\"{}\"}}", escape(player)), value(record))
+                })
+                .collect()
+        };
+
+        metric(
+            "games_total",
+            "counter",
+            "The games played to their end.",
+            vec![(String::new(), self.games.to_string())],
+        );
+        metric(
+            "draws_total",
+            "counter",
+            "The games ended in a draw.",
+            vec![(String::new(), self.draws.to_string())],
+        );
+        metric(
+            "shots_per_game",
+            "summary",
+            "The shots fired in a game, by both sides.",
+            vec![
+                ("_sum".to_string(), self.shots.sum.to_string()),
+                ("_count".to_string(), self.shots.count.to_string()),
+            ],
+        );
+        metric(
+            "decision_seconds",
+            "summary",
+            "The time taken by the computer to choose a move.",
+            vec![
+                ("_sum".to_string(), self.decisions.sum.to_string()),
+                ("_count".to_string(), self.decisions.count.to_string()),
+            ],
+        );
+        metric(
+            "player_games_total",
+            "counter",
+            "The games played to their end, by player.",
+            by_player(&|record| record.games.to_string()),
+        );
+        metric(
+            "player_wins_total",
+            "counter",
+            "The games won, by player.",
+            by_player(&|record| record.wins.to_string()),
+        );
+        metric(
+            "player_win_rate",
+            "gauge",
+            "The share of their games won, by player.",
+            by_player(&|record| (record.wins as f64 / record.games.max(1) as f64).to_string()),
+        );
+
+        text
+    }
+}
+
+// Escapes the value of a Prometheus label.
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// A [Strategy] which times the moves chosen by another one.
+///
+/// The times are kept by the [decisions](Timed::decisions) of the strategy, which can be read
+/// while the strategy is owned by a game.
+#[derive(Debug)]
+pub struct Timed<S> {
+    strategy: S,
+    decisions: Rc<RefCell<Vec<Duration>>>,
+}
+
+impl<S: Strategy> Timed<S> {
+    /// Creates a strategy which times the moves of the given one.
+    pub fn new(strategy: S) -> Self {
+        Self {
+            strategy,
+            decisions: Rc::default(),
+        }
+    }
+
+    /// Returns the times taken to choose the moves, shared with the strategy.
+    pub fn decisions(&self) -> Rc<RefCell<Vec<Duration>>> {
+        self.decisions.clone()
+    }
+}
+
+impl<S: Strategy> Strategy for Timed<S> {
+    fn next_move(&mut self, view: &StrategyView) -> Option<Cell> {
+        let start = Instant::now();
+        let next = self.strategy.next_move(view);
+        self.decisions.borrow_mut().push(start.elapsed());
+
+        next
+    }
+
+    fn notify_hit(&mut self, kind: ShipKind) {
+        self.strategy.notify_hit(kind)
+    }
+
+    fn notify_revealed(&mut self, cells: &[Cell]) {
+        self.strategy.notify_revealed(cells)
+    }
+
+    fn heatmap(&mut self, view: &StrategyView) -> Option<HashMap<Cell, f32>> {
+        self.strategy.heatmap(view)
+    }
+
+    #[cfg(feature = "async")]
+    fn prepare_move(&mut self, view: &StrategyView) -> Option<Preparation> {
+        self.strategy.prepare_move(view)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_record_game() {
+        let mut metrics = Metrics::new();
+        metrics.record_game(["smart", "random"], Some(0), 80);
+        metrics.record_game(["smart", "random"], Some(1), 100);
+        metrics.record_game(["random", "smart"], Some(1), 60);
+        metrics.record_game(["smart", "smart"], None, 90);
+
+        assert_eq!(metrics.games(), 4);
+        assert_eq!(metrics.mean_shots(), Some(82.5));
+        assert_eq!(metrics.win_rate("smart"), Some(0.4));
+        assert_eq!(metrics.win_rate("random"), Some(1.0 / 3.0));
+        assert_eq!(metrics.win_rate("heatmap"), None);
+    }
+
+    #[rstest]
+    fn test_merge() {
+        let (mut a, mut b, mut both) = (Metrics::new(), Metrics::new(), Metrics::new());
+        a.record_game(["smart", "random"], Some(0), 80);
+        a.record_decision(Duration::from_millis(2));
+        b.record_game(["heatmap", "smart"], None, 70);
+        b.record_decision(Duration::from_millis(4));
+        both.record_game(["smart", "random"], Some(0), 80);
+        both.record_decision(Duration::from_millis(2));
+        both.record_game(["heatmap", "smart"], None, 70);
+        both.record_decision(Duration::from_millis(4));
+
+        a.merge(&b);
+        a.merge(&Metrics::new());
+
+        assert_eq!(a, both);
+        assert_eq!(a.mean_decision(), Some(Duration::from_millis(3)));
+    }
+
+    #[rstest]
+    fn test_play() {
+        let mut metrics = Metrics::new();
+        metrics
+            .play(Variant::Classic, StrategyKind::Smart, StrategyKind::Random)
+            .unwrap();
+
+        assert_eq!(metrics.games(), 1);
+        assert!(metrics.mean_shots().unwrap() >= 17.0);
+        assert!(metrics.mean_decision().is_some());
+        let rates = [metrics.win_rate("smart"), metrics.win_rate("random")];
+        assert_eq!(rates.iter().flatten().sum::<f64>(), 1.0);
+    }
+
+    #[rstest]
+    fn test_play_all() {
+        let matches = [
+            (StrategyKind::Smart, StrategyKind::Random),
+            (StrategyKind::Random, StrategyKind::Smart),
+            (StrategyKind::Smart, StrategyKind::Random),
+        ];
+        let results = |metrics: Metrics| {
+            let rates = ["smart", "random"].map(|player| metrics.win_rate(player));
+            (metrics.games(), metrics.mean_shots(), rates)
+        };
+
+        let metrics = Metrics::play_all(Variant::Classic, &matches, Some(7)).unwrap();
+        assert_eq!(metrics.games(), 3);
+        // The same seed plays the same games.
+        let again = Metrics::play_all(Variant::Classic, &matches, Some(7)).unwrap();
+        assert_eq!(results(metrics), results(again));
+    }
+
+    #[rstest]
+    fn test_summary() {
+        let mut metrics = Metrics::new();
+        metrics.record_game(["heatmap", "random"], Some(0), 50);
+        let summary = metrics.summary();
+
+        assert!(summary.contains("Games           1\n"));
+        assert!(summary.contains("Shots per game  50.0 (min 50, max 50)\n"));
+        assert!(summary.contains("Decision time   -\n"));
+        assert!(summary.contains("heatmap       1       1    100.0%\n"));
+        assert!(summary.contains("random        1       0      0.0%\n"));
+    }
+
+    #[rstest]
+    fn test_prometheus() {
+        let mut metrics = Metrics::new();
+        metrics.record_game(["first", "say \"hi\""], Some(1), 42);
+        metrics.record_decision(Duration::from_millis(500));
+        let text = metrics.prometheus();
+
+        assert!(text.contains("# TYPE naval_games_total counter\nnaval_games_total 1\n"));
+        assert!(text.contains("naval_shots_per_game_sum 42\nnaval_shots_per_game_count 1\n"));
+        assert!(text.contains("naval_decision_seconds_sum 0.5\n"));
+        assert!(text.contains("naval_player_wins_total{player=\"first\"} 0\n"));
+        assert!(text.contains("naval_player_win_rate{player=\"say \\\"hi\\\"\"} 1\n"));
+    }
+}
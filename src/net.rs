@@ -0,0 +1,857 @@
+//! This module contains the network play of Naval Battle: the messages of the HTTP API served
+//! with the `server` feature, and a [Client] to play with them.
+//!
+//! The messages are JSON objects, described by the types of this module, so that the server and
+//! its clients agree on them. The requests and their bodies are listed by the `server` module.
+//!
+//! Before any other request, a client says [Hello] to the server with the
+//! [version](PROTOCOL_VERSION) of the protocol it speaks, and the variants and the board sizes
+//! it can play. The server answers with what both sides can play, in a [Welcome], or refuses a
+//! client it can't play with, so that a mismatch is told at once instead of in the middle of a
+//! game.
+//!
+//! The client only needs the standard library: it sends every request on a new connection, so
+//! that a dropped connection never leaves it in a broken state. A player who has lost their
+//! client keeps the token of their duel, and [rejoins](Client::rejoin) it with a new client.
+//!
+//! With the `tls` feature, a client given the [certificate](Client::with_certificate) of the
+//! server encrypts its requests, so that the shots and the chat don't travel in plaintext.
+//!
+use crate::bot::BotEvent;
+use crate::engine::duel::Duel;
+use crate::engine::fleet::ShipKind;
+use crate::engine::game::{Difficulty, Side};
+use crate::engine::grid::Cell;
+use crate::engine::rules::Variant;
+use serde::de::{DeserializeOwned, IntoDeserializer};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use thiserror::Error;
+use tracing::{debug, debug_span};
+
+/// The opponent of the player who creates a game.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Opponent {
+    #[default]
+    Computer,
+
+    /// Another player, who joins the game.
+    Human,
+}
+
+/// The version of the protocol spoken by this build. It changes whenever a message changes in a
+/// way the other side can't read.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// The body of `POST /hello`: the version of the protocol spoken by a side, with the variants
+/// and the board sizes it can play.
+///
+/// The variants unknown to this build are skipped, so that a newer side can offer more of them.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Hello {
+    pub version: u32,
+    #[serde(deserialize_with = "known_variants")]
+    pub variants: Vec<Variant>,
+    pub board_sizes: Vec<u8>,
+}
+
+impl Hello {
+    /// Returns the hello of this build, which plays the variants of a [Duel] on a board of the
+    /// given size.
+    pub fn new(board_size: u8) -> Self {
+        Self {
+            version: PROTOCOL_VERSION,
+            variants: Duel::VARIANTS.to_vec(),
+            board_sizes: vec![board_size],
+        }
+    }
+
+    /// Accepts the hello of a client, returning what both sides can play, or why they can't
+    /// play together.
+    pub fn accept(&self, client: &Hello) -> Result<Welcome, HandshakeError> {
+        if client.version != self.version {
+            return Err(HandshakeError::Version {
+                client: client.version,
+                server: self.version,
+            });
+        }
+        let variants: Vec<Variant> = self
+            .variants
+            .iter()
+            .filter(|variant| client.variants.contains(variant))
+            .copied()
+            .collect();
+        if variants.is_empty() {
+            return Err(HandshakeError::NoVariant);
+        }
+        let board_size = self
+            .board_sizes
+            .iter()
+            .filter(|size| client.board_sizes.contains(size))
+            .max()
+            .copied()
+            .ok_or(HandshakeError::NoBoardSize)?;
+
+        Ok(Welcome {
+            version: self.version,
+            variants,
+            board_size,
+        })
+    }
+}
+
+// Reads the variants of a hello, skipping those unknown to this build.
+fn known_variants<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Variant>, D::Error> {
+    let names = Vec::<String>::deserialize(deserializer)?;
+
+    Ok(names
+        .iter()
+        .filter_map(|name| {
+            let name: serde::de::value::StrDeserializer<serde::de::value::Error> =
+                name.as_str().into_deserializer();
+            Variant::deserialize(name).ok()
+        })
+        .collect())
+}
+
+/// The answer to `POST /hello`: the variants both sides can play, and the size of the board of
+/// their games.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Welcome {
+    pub version: u32,
+    pub variants: Vec<Variant>,
+    pub board_size: u8,
+}
+
+/// Represents the reason why a client and a server can't play together.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Error)]
+pub enum HandshakeError {
+    /// The sides speak different versions of the protocol.
+    #[error(
+        "the client speaks version {client} of the protocol, the server version {server}: both must be updated"
+    )]
+    Version { client: u32, server: u32 },
+
+    /// The server has been started before the protocol had a handshake.
+    #[error("the server doesn't know the protocol of this game, it must be updated")]
+    Unversioned,
+
+    /// The sides have no variant in common.
+    #[error("the client and the server have no variant in common")]
+    NoVariant,
+
+    /// The sides have no board size in common.
+    #[error("the client and the server have no board size in common")]
+    NoBoardSize,
+}
+
+/// The body of `POST /games`.
+///
+/// The difficulty only applies to the games against the computer, and the title to the games
+/// against another player, which are listed in the lobby.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct NewGame {
+    pub name: Option<String>,
+    pub title: Option<String>,
+    pub opponent: Opponent,
+    pub difficulty: Option<Difficulty>,
+    pub variant: Option<Variant>,
+}
+
+/// The body of `POST /games/{id}/join`.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct JoinRequest {
+    pub name: Option<String>,
+}
+
+/// The body of `POST /games/match`: the player is paired with the open game of the variant which
+/// has waited the longest, or opens a new one.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(default, deny_unknown_fields)]
+pub struct MatchRequest {
+    pub name: Option<String>,
+    pub variant: Option<Variant>,
+}
+
+/// The body of `POST /games/{id}/placements`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct Placements {
+    pub fleet: String,
+}
+
+/// The body of `POST /games/{id}/shots`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ShotRequest {
+    pub cell: String,
+}
+
+/// The body of `POST /games/{id}/chat`: a message for the opponent of a duel.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(deny_unknown_fields)]
+pub struct ChatRequest {
+    pub text: String,
+}
+
+impl ChatRequest {
+    /// The most characters of a message.
+    pub const MAX_LENGTH: usize = 200;
+}
+
+/// A message of the chat of a duel, as the player knows it.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct ChatMessage {
+    /// The position of the message in the chat, from 1.
+    pub seq: u32,
+
+    /// The name of the player who has sent the message.
+    pub sender: String,
+
+    /// Whether the player has sent the message.
+    pub yours: bool,
+
+    /// The text of the message.
+    pub text: String,
+}
+
+/// A game of the lobby, waiting for a second player.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct OpenGame {
+    pub id: u64,
+    pub title: String,
+
+    /// The name of the player who has opened the game.
+    pub host: String,
+
+    pub variant: Variant,
+
+    /// The seconds since the game has been opened.
+    pub waiting: u64,
+}
+
+/// The phase of a game, as seen by the client.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "snake_case")]
+pub enum Phase {
+    /// The game waits for the fleets of the players.
+    Setup,
+    InProgress,
+    Finished,
+    Drawn,
+}
+
+/// The shots fired by a side, by their result.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Shots {
+    pub hits: Vec<Cell>,
+    pub misses: Vec<Cell>,
+}
+
+/// The time spent by the players of a duel on their turns, in seconds.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct Clocks {
+    /// The time of the player, with the current turn while it is theirs.
+    pub yours: u64,
+
+    /// The time of the opponent, with the current turn while it is theirs.
+    pub opponent: u64,
+}
+
+/// The state of a game, as the player knows it.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct GameState {
+    pub id: u64,
+    pub opponent: Opponent,
+    pub phase: Phase,
+
+    /// The name of the opponent, once they have joined the game.
+    pub opponent_name: Option<String>,
+
+    /// The winner, once the game is finished.
+    pub winner: Option<Side>,
+
+    /// Whether the game waits for a shot of the player.
+    pub your_turn: bool,
+
+    /// The number of turns played.
+    pub turn: u32,
+
+    /// The fleet of the player, once deployed.
+    pub fleet: Option<String>,
+
+    /// The ships of the player which are still afloat.
+    pub ships_left: Vec<ShipKind>,
+
+    /// The ships of the opponent sunk by the player.
+    pub sunk: Vec<ShipKind>,
+
+    /// The shots of the player.
+    pub shots: Shots,
+
+    /// The shots of the opponent.
+    pub opponent_shots: Shots,
+
+    /// The time spent by the players of a duel on their turns.
+    #[serde(default)]
+    pub clocks: Clocks,
+
+    /// The token of the player, given only when they create or join a duel.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<String>,
+
+    /// The [hash](crate::engine::game::Game::state_hash) of the battle on the server, in
+    /// hexadecimal, once it has started.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hash: Option<String>,
+}
+
+/// The response of `GET /games/{id}/hash`: the hash of the battle on the server, like the one of
+/// the [GameState]. A client whose last state has another hash has missed a change, and fetches
+/// the state again.
+#[derive(Debug, PartialEq, Eq, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct StateHash {
+    pub hash: Option<String>,
+}
+
+/// The response of `POST /games/{id}/shots`: the shots fired during the turn, then the state of
+/// the game.
+///
+/// In a game against the computer, the shots are those of both sides. In a duel, the shot is
+/// only the one of the player, as the opponent fires later.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub struct TurnResult {
+    pub shots: Vec<BotEvent>,
+    pub state: GameState,
+}
+
+/// The duel a player is playing on a server, kept by their client so that they can rejoin it.
+#[derive(Debug, PartialEq, Eq, Clone, Serialize, Deserialize)]
+pub struct Session {
+    /// The address of the server.
+    pub server: String,
+
+    /// The id of the duel.
+    pub id: u64,
+
+    /// The token of the player.
+    pub token: String,
+}
+
+/// Represents an error of the client.
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// The server can't be reached, or the connection has been dropped.
+    #[error("the server can't be reached: {0}")]
+    Io(#[from] io::Error),
+
+    /// The server has refused the request, with the status code and the message of the error.
+    #[error("{message} ({status})")]
+    Api { status: u16, message: String },
+
+    /// The answer of the server is not a valid HTTP response.
+    #[error("invalid response from the server")]
+    Response,
+
+    /// The body of the answer is not the expected message.
+    #[error("invalid message from the server: {0}")]
+    Json(#[from] serde_json::Error),
+
+    /// The client and the server can't play together.
+    #[error(transparent)]
+    Handshake(#[from] HandshakeError),
+
+    /// The connection can't be encrypted, like when the server shows another certificate.
+    #[cfg(feature = "tls")]
+    #[error("TLS error: {0}")]
+    Tls(#[from] crate::tls::TlsError),
+
+    /// The requests must be encrypted, but the game has been built without the `tls` feature.
+    #[cfg(not(feature = "tls"))]
+    #[error("encrypted connections need the `tls` feature")]
+    NoTls,
+}
+
+/// A client of the HTTP API, to play against another player through a server.
+///
+/// The client keeps the token of the player once they have created or joined a duel, and sends
+/// it with the following requests.
+#[derive(Debug, Clone)]
+pub struct Client {
+    address: String,
+    token: Option<String>,
+    certificate: Option<PathBuf>,
+}
+
+impl Client {
+    // How long the client waits for the server to connect, and then to answer.
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    /// Creates a client of the server at the given address, like `127.0.0.1:8080`.
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            token: None,
+            certificate: None,
+        }
+    }
+
+    /// Creates a client of the server at the given address, for the player with the token of a
+    /// duel.
+    pub fn with_token(address: &str, token: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            token: Some(token.to_string()),
+            certificate: None,
+        }
+    }
+
+    /// Encrypts the requests with TLS, trusting only the certificate of the server kept in the
+    /// given PEM file. Without the `tls` feature, every request fails instead.
+    pub fn with_certificate(mut self, certificate: &Path) -> Self {
+        self.certificate = Some(certificate.to_path_buf());
+        self
+    }
+
+    /// Returns the token of the player, once they have created or joined a duel.
+    pub fn token(&self) -> Option<&str> {
+        self.token.as_deref()
+    }
+
+    /// Says hello to the server, and returns what both sides can play. The server refuses a
+    /// client it can't play with.
+    pub fn hello(&self, hello: &Hello) -> Result<Welcome, ClientError> {
+        match self.request("POST", "/hello", Some(serde_json::to_string(hello)?)) {
+            Err(ClientError::Api { status: 404, .. }) => Err(HandshakeError::Unversioned.into()),
+            result => result,
+        }
+    }
+
+    /// Lists the games of the lobby, waiting for a second player.
+    pub fn games(&self) -> Result<Vec<OpenGame>, ClientError> {
+        self.request("GET", "/games", None)
+    }
+
+    /// Creates a game, and keeps the token of the player.
+    pub fn create(&mut self, settings: &NewGame) -> Result<GameState, ClientError> {
+        let state: GameState =
+            self.request("POST", "/games", Some(serde_json::to_string(settings)?))?;
+        self.keep_token(&state);
+
+        Ok(state)
+    }
+
+    /// Joins the duel, and keeps the token of the player.
+    pub fn join(&mut self, id: u64, request: &JoinRequest) -> Result<GameState, ClientError> {
+        let path = format!("/games/{id}/join");
+        let state: GameState =
+            self.request("POST", &path, Some(serde_json::to_string(request)?))?;
+        self.keep_token(&state);
+
+        Ok(state)
+    }
+
+    /// Pairs the player with an opponent, and keeps the token of the player.
+    pub fn find_match(&mut self, request: &MatchRequest) -> Result<GameState, ClientError> {
+        let body = serde_json::to_string(request)?;
+        let state: GameState = self.request("POST", "/games/match", Some(body))?;
+        self.keep_token(&state);
+
+        Ok(state)
+    }
+
+    /// Rejoins the duel of the player with their token, and returns its whole state.
+    pub fn rejoin(&self) -> Result<GameState, ClientError> {
+        self.request("POST", "/games/rejoin", None)
+    }
+
+    /// Deploys the fleet of the player.
+    pub fn place(&self, id: u64, placements: &Placements) -> Result<GameState, ClientError> {
+        let path = format!("/games/{id}/placements");
+        self.request("POST", &path, Some(serde_json::to_string(placements)?))
+    }
+
+    /// Fires the shot of the player.
+    pub fn shoot(&self, id: u64, shot: &ShotRequest) -> Result<TurnResult, ClientError> {
+        let path = format!("/games/{id}/shots");
+        self.request("POST", &path, Some(serde_json::to_string(shot)?))
+    }
+
+    /// Returns the state of the game.
+    pub fn state(&self, id: u64) -> Result<GameState, ClientError> {
+        self.request("GET", &format!("/games/{id}/state"), None)
+    }
+
+    /// Returns the hash of the battle on the server, to be compared with the one of the last
+    /// state of the game.
+    pub fn state_hash(&self, id: u64) -> Result<StateHash, ClientError> {
+        self.request("GET", &format!("/games/{id}/hash"), None)
+    }
+
+    /// Sends a message to the opponent of the duel.
+    pub fn say(&self, id: u64, message: &ChatRequest) -> Result<ChatMessage, ClientError> {
+        let path = format!("/games/{id}/chat");
+        self.request("POST", &path, Some(serde_json::to_string(message)?))
+    }
+
+    /// Returns the messages of the chat of the duel which follow the given position.
+    pub fn chat(&self, id: u64, after: u32) -> Result<Vec<ChatMessage>, ClientError> {
+        self.request("GET", &format!("/games/{id}/chat?after={after}"), None)
+    }
+
+    fn keep_token(&mut self, state: &GameState) {
+        if let Some(token) = &state.token {
+            self.token = Some(token.clone());
+        }
+    }
+
+    // Sends the request on a new connection, and reads the message answered.
+    fn request<T: DeserializeOwned>(
+        &self,
+        method: &str,
+        path: &str,
+        body: Option<String>,
+    ) -> Result<T, ClientError> {
+        let _span = debug_span!("request", method, path).entered();
+        let address = self
+            .address
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::from(io::ErrorKind::AddrNotAvailable))?;
+        let stream = TcpStream::connect_timeout(&address, Self::TIMEOUT)?;
+        stream.set_read_timeout(Some(Self::TIMEOUT))?;
+        stream.set_write_timeout(Some(Self::TIMEOUT))?;
+
+        let mut request = format!(
+            "{method} {path} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\nAccept: application/json\r\n",
+            self.address
+        );
+        if let Some(token) = &self.token {
+            request.push_str(&format!("Authorization: Bearer {token}\r\n"));
+        }
+        let body = body.unwrap_or_default();
+        if !body.is_empty() {
+            request.push_str("Content-Type: application/json\r\n");
+        }
+        request.push_str(&format!("Content-Length: {}\r\n\r\n{body}", body.len()));
+        debug!(body, "sent");
+
+        let response = match &self.certificate {
+            Some(certificate) => exchange(self.encrypt(stream, certificate)?, &request)?,
+            None => exchange(stream, &request)?,
+        };
+        let (status, body) = parse_response(&response)?;
+        debug!(status, body = %String::from_utf8_lossy(body), "received");
+        match status {
+            200..=299 => Ok(serde_json::from_slice(body)?),
+            _ => Err(ClientError::Api {
+                status,
+                message: serde_json::from_slice::<serde_json::Value>(body)
+                    .ok()
+                    .and_then(|error| error["error"].as_str().map(str::to_string))
+                    .unwrap_or_else(|| String::from_utf8_lossy(body).into_owned()),
+            }),
+        }
+    }
+
+    // Wraps the connection in a TLS session with the server, which must show the certificate.
+    #[cfg(feature = "tls")]
+    fn encrypt(
+        &self,
+        stream: TcpStream,
+        certificate: &Path,
+    ) -> Result<impl Read + Write, ClientError> {
+        use crate::tls::{self, TlsError};
+        use rustls::pki_types::ServerName;
+
+        // The certificate is pinned, so the name only matters to servers which check it.
+        let host = self
+            .address
+            .rsplit_once(':')
+            .map_or(self.address.as_str(), |(host, _)| host);
+        let name = ServerName::try_from(host.trim_matches(['[', ']']).to_string())
+            .unwrap_or_else(|_| ServerName::try_from("localhost").unwrap());
+        let connection = rustls::ClientConnection::new(tls::client_config(certificate)?, name)
+            .map_err(TlsError::from)?;
+
+        Ok(rustls::StreamOwned::new(connection, stream))
+    }
+
+    #[cfg(not(feature = "tls"))]
+    fn encrypt(&self, _: TcpStream, _: &Path) -> Result<TcpStream, ClientError> {
+        Err(ClientError::NoTls)
+    }
+}
+
+// Writes the request on the connection, and reads the whole response.
+fn exchange(mut stream: impl Read + Write, request: &str) -> io::Result<Vec<u8>> {
+    stream.write_all(request.as_bytes())?;
+    let mut response = Vec::new();
+    stream.read_to_end(&mut response)?;
+
+    Ok(response)
+}
+
+// Splits an HTTP response into its status code and its body. The server closes the connection
+// after the body, whose length is given by its headers.
+fn parse_response(response: &[u8]) -> Result<(u16, &[u8]), ClientError> {
+    let end = response
+        .windows(4)
+        .position(|window| window == b"\r\n\r\n")
+        .ok_or(ClientError::Response)?;
+    let head = std::str::from_utf8(&response[..end]).map_err(|_| ClientError::Response)?;
+    let status = head
+        .split_whitespace()
+        .nth(1)
+        .and_then(|status| status.parse().ok())
+        .ok_or(ClientError::Response)?;
+
+    Ok((status, &response[end + 4..]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::io::{BufRead, BufReader};
+    use std::net::TcpListener;
+    use std::thread;
+
+    // Serves a single connection with the given response, and returns the request received.
+    fn serve(response: String) -> (String, thread::JoinHandle<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut request = String::new();
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    length = value.trim().parse().unwrap();
+                }
+                request.push_str(&line);
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            request.push_str(&String::from_utf8(body).unwrap());
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+
+            request
+        });
+
+        (address, server)
+    }
+
+    #[rstest]
+    fn test_games() {
+        let (address, server) = serve(
+            "HTTP/1.1 200 OK\r\n\r\n\
+             [{\"id\":1,\"title\":\"Ahoy\",\"host\":\"Ishmael\",\"variant\":\"salvo\",\"waiting\":3}]"
+                .to_string(),
+        );
+
+        let games = Client::new(&address).games().unwrap();
+        let request = server.join().unwrap();
+
+        assert!(request.starts_with("GET /games HTTP/1.1\r\n"));
+        assert_eq!(
+            games,
+            vec![OpenGame {
+                id: 1,
+                title: "Ahoy".to_string(),
+                host: "Ishmael".to_string(),
+                variant: Variant::Salvo,
+                waiting: 3,
+            }]
+        );
+    }
+
+    #[rstest]
+    fn test_token() {
+        let state = GameState {
+            id: 4,
+            opponent: Opponent::Human,
+            phase: Phase::Setup,
+            opponent_name: None,
+            winner: None,
+            your_turn: false,
+            turn: 0,
+            fleet: None,
+            ships_left: Vec::new(),
+            sunk: Vec::new(),
+            shots: Shots::default(),
+            opponent_shots: Shots::default(),
+            clocks: Clocks::default(),
+            token: Some("c0ffee".to_string()),
+            hash: None,
+        };
+        let response = format!(
+            "HTTP/1.1 200 OK\r\n\r\n{}",
+            serde_json::to_string(&state).unwrap()
+        );
+        let (address, server) = serve(response);
+        let mut client = Client::new(&address);
+
+        assert_eq!(client.join(4, &JoinRequest::default()).unwrap(), state);
+        assert!(server.join().unwrap().starts_with("POST /games/4/join"));
+        assert_eq!(client.token(), Some("c0ffee"));
+
+        let (address, server) =
+            serve("HTTP/1.1 404 Not Found\r\n\r\n{\"error\":\"game 4 not found\"}".to_string());
+        client.address = address;
+        let error = client.state(4).unwrap_err();
+
+        assert!(
+            server
+                .join()
+                .unwrap()
+                .contains("Authorization: Bearer c0ffee\r\n")
+        );
+        assert!(matches!(
+            error,
+            ClientError::Api { status: 404, ref message } if message == "game 4 not found"
+        ));
+    }
+
+    #[rstest]
+    fn test_rejoin() {
+        // A server which doesn't know the clocks yet.
+        let (address, server) = serve(
+            "HTTP/1.1 200 OK\r\n\r\n\
+             {\"id\":7,\"opponent\":\"human\",\"phase\":\"in_progress\",\
+             \"opponent_name\":\"Queequeg\",\"winner\":null,\"your_turn\":true,\"turn\":3,\
+             \"fleet\":\"A1H/A3H/A5H/A7H/A9H\",\"ships_left\":[],\"sunk\":[],\
+             \"shots\":{\"hits\":[],\"misses\":[]},\
+             \"opponent_shots\":{\"hits\":[],\"misses\":[]}}"
+                .to_string(),
+        );
+
+        let state = Client::with_token(&address, "c0ffee").rejoin().unwrap();
+        let request = server.join().unwrap();
+
+        assert!(request.starts_with("POST /games/rejoin HTTP/1.1\r\n"));
+        assert!(request.contains("Authorization: Bearer c0ffee\r\n"));
+        assert_eq!((state.id, state.turn, state.your_turn), (7, 3, true));
+        assert_eq!(state.clocks, Clocks::default());
+    }
+
+    #[rstest]
+    fn test_accept_hello() {
+        let server = Hello::new(10);
+        let client = |version, variants: &[Variant], board_sizes: &[u8]| Hello {
+            version,
+            variants: variants.to_vec(),
+            board_sizes: board_sizes.to_vec(),
+        };
+
+        assert_eq!(
+            server.accept(&client(1, &[Variant::Salvo, Variant::Classic], &[8, 10])),
+            Ok(Welcome {
+                version: 1,
+                variants: vec![Variant::Classic, Variant::Salvo],
+                board_size: 10,
+            })
+        );
+        assert_eq!(
+            server.accept(&client(2, &Duel::VARIANTS, &[10])),
+            Err(HandshakeError::Version {
+                client: 2,
+                server: 1
+            })
+        );
+        assert_eq!(
+            server.accept(&client(1, &[Variant::SeaMines], &[10])),
+            Err(HandshakeError::NoVariant)
+        );
+        assert_eq!(
+            server.accept(&client(1, &Duel::VARIANTS, &[12])),
+            Err(HandshakeError::NoBoardSize)
+        );
+    }
+
+    #[rstest]
+    fn test_hello_skips_unknown_variants() {
+        let hello: Hello = serde_json::from_str(
+            r#"{"version":1,"variants":["classic","torpedoes"],"board_sizes":[10]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(hello.variants, vec![Variant::Classic]);
+    }
+
+    #[rstest]
+    fn test_hello() {
+        let (address, server) = serve(
+            "HTTP/1.1 200 OK\r\n\r\n{\"version\":1,\"variants\":[\"streak\"],\"board_size\":10}"
+                .to_string(),
+        );
+
+        let welcome = Client::new(&address).hello(&Hello::new(10)).unwrap();
+        let request = server.join().unwrap();
+
+        assert!(request.starts_with("POST /hello HTTP/1.1\r\n"));
+        assert!(request.ends_with(
+            r#"{"version":1,"variants":["classic","streak","salvo"],"board_sizes":[10]}"#
+        ));
+        assert_eq!(welcome.variants, vec![Variant::Streak]);
+
+        // A server started before the handshake.
+        let (address, server) = serve("HTTP/1.1 404 Not Found\r\n\r\n".to_string());
+        let error = Client::new(&address).hello(&Hello::new(10)).unwrap_err();
+        server.join().unwrap();
+
+        assert!(matches!(
+            error,
+            ClientError::Handshake(HandshakeError::Unversioned)
+        ));
+    }
+
+    #[rstest]
+    fn test_unreachable() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        drop(listener);
+
+        assert!(matches!(
+            Client::new(&address).games(),
+            Err(ClientError::Io(_))
+        ));
+    }
+
+    #[rstest]
+    #[case(b"HTTP/1.1 201 Created\r\nx: y\r\n\r\n{}", Some((201, &b"{}"[..])))]
+    #[case(b"HTTP/1.1 201 Created", None)]
+    #[case(b"garbage\r\n\r\n", None)]
+    fn test_parse_response(#[case] response: &[u8], #[case] expected: Option<(u16, &[u8])>) {
+        assert_eq!(parse_response(response).ok(), expected);
+    }
+}
@@ -0,0 +1,93 @@
+//! This module defines the wire format of Naval Battle: the messages of the network protocol,
+//! spoken with the `server` through the [net](crate::net) client, and those of the
+//! [bot](crate::bot) protocol, spoken by the external programs. They are JSON objects, whose
+//! types are gathered here, so that third-party clients read them from a single place.
+//!
+//! The format is versioned: [VERSION] changes whenever a message changes in a way the other side
+//! can't read, and the network clients check it with the [Hello] handshake. New optional keys
+//! and new variants don't change it, as the messages of this version ignore them.
+//!
+//! An error of the network protocol is answered as `{"error":"<message>"}`, with the status code
+//! of the error.
+//!
+//! Built with the `schema` feature, `schema` describes every message as a JSON Schema, generated
+//! from these types and their documentation. `naval schema` prints it.
+//!
+pub use crate::bot::{BotCommand, BotEvent, ShotResult};
+pub use crate::engine::fleet::ShipKind;
+pub use crate::engine::game::{Difficulty, Side};
+pub use crate::engine::grid::Cell;
+pub use crate::engine::rules::Variant;
+pub use crate::net::{
+    ChatMessage, ChatRequest, Clocks, GameState, Hello, JoinRequest, MatchRequest, NewGame,
+    OpenGame, Opponent, PROTOCOL_VERSION as VERSION, Phase, Placements, ShotRequest, Shots,
+    StateHash, TurnResult, Welcome,
+};
+
+/// Returns the JSON Schema of the messages: a document with the [VERSION] of the format, whose
+/// definitions are the messages and the types they are made of, by name.
+#[cfg(feature = "schema")]
+pub fn schema() -> serde_json::Value {
+    let mut generator = schemars::SchemaGenerator::default();
+    // The requests of the network protocol, then its answers.
+    generator.subschema_for::<Hello>();
+    generator.subschema_for::<NewGame>();
+    generator.subschema_for::<JoinRequest>();
+    generator.subschema_for::<MatchRequest>();
+    generator.subschema_for::<Placements>();
+    generator.subschema_for::<ShotRequest>();
+    generator.subschema_for::<ChatRequest>();
+    generator.subschema_for::<Welcome>();
+    generator.subschema_for::<OpenGame>();
+    generator.subschema_for::<GameState>();
+    generator.subschema_for::<TurnResult>();
+    generator.subschema_for::<StateHash>();
+    generator.subschema_for::<ChatMessage>();
+    // The commands of the bot protocol, then its events.
+    generator.subschema_for::<BotCommand>();
+    generator.subschema_for::<BotEvent>();
+
+    serde_json::json!({
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "title": "Naval Battle protocol",
+        "version": VERSION,
+        "$defs": generator.definitions(),
+    })
+}
+
+#[cfg(all(test, feature = "schema"))]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    fn test_schema() {
+        let schema = schema();
+        let definitions = schema["$defs"].as_object().unwrap();
+
+        assert_eq!(schema["version"], VERSION);
+        for name in [
+            "Hello",
+            "NewGame",
+            "GameState",
+            "TurnResult",
+            "BotCommand",
+            "BotEvent",
+            "Variant",
+            "ShipKind",
+        ] {
+            assert!(definitions.contains_key(name), "{name} is missing");
+        }
+        assert_eq!(
+            definitions["Shots"]["properties"]["hits"]["items"]["$ref"],
+            "#/$defs/Cell"
+        );
+        assert_eq!(definitions["Cell"]["type"], "string");
+        assert_eq!(definitions["Cell"]["pattern"], "^[A-J]([1-9]|10)$");
+        assert!(
+            definitions["GameState"]["properties"]["clocks"]["description"]
+                .as_str()
+                .is_some_and(|description| description.contains("time spent"))
+        );
+    }
+}
@@ -0,0 +1,2024 @@
+//! This module contains the HTTP API of Naval Battle, built with the `server` feature.
+//!
+//! The API lets web or mobile clients play against the computer players of the
+//! [engine](crate::engine), or against each other. Every request and response body is a JSON
+//! object, described in the [net](crate::net) module:
+//!
+//! | Request                       | Body                                        | Response              |
+//! |-------------------------------|---------------------------------------------|-----------------------|
+//! | `POST /hello`                 | `{"version":1,"variants":["classic"],...}`  | the [Welcome]         |
+//! | `GET /games`                  |                                             | the [OpenGame]s       |
+//! | `POST /games`                 | `{"name":"Ishmael","difficulty":"hard"}`    | the new [GameState]   |
+//! | `POST /games/match`           | `{"name":"Queequeg","variant":"salvo"}`     | the [GameState]       |
+//! | `POST /games/{id}/join`       | `{"name":"Queequeg"}`                       | the [GameState]       |
+//! | `POST /games/{id}/placements` | `{"fleet":"A1H/A3H/A5H/A7H/A9H"}`           | the [GameState]       |
+//! | `POST /games/{id}/shots`      | `{"cell":"B7"}`                             | the [TurnResult]      |
+//! | `GET /games/{id}/state`       |                                             | the [GameState]       |
+//! | `GET /games/{id}/hash`        |                                             | the [StateHash]       |
+//! | `POST /games/{id}/chat`       | `{"text":"Fire at will!"}`                  | the [ChatMessage]     |
+//! | `GET /games/{id}/chat?after=3`|                                             | the [ChatMessage]s    |
+//! | `POST /games/rejoin`          |                                             | the [GameState]       |
+//! | `GET /metrics`                |                                             | the [Metrics]         |
+//!
+//! A client says [Hello] first, with the version of the protocol it speaks and what it can
+//! play: the server answers with the variants and the board size both can play, or refuses it
+//! with `409 Conflict` and the reason of the mismatch.
+//!
+//! A new game waits for the fleet of the player, whose keys are all optional: the `name`, the
+//! `difficulty` and the `variant` default to the configuration of the server. The fleet is
+//! written like [Fleet::to_code], and the battle starts as soon as it is deployed. The shots of
+//! a turn are described like the `shot` events of the [bot](crate::bot).
+//!
+//! A game created with `"opponent":"human"` is a [Duel] instead, which a second player joins
+//! with `POST /games/{id}/join`. The state returned when creating or joining a duel holds the
+//! `token` of the player, which the following requests of the player must send as
+//! `Authorization: Bearer <token>`. The state of a duel is told from the side of the player
+//! sending the request: the `human` side is the player, and the `computer` side is their
+//! opponent. The shots of a duel are fired one at a time, when the state says `your_turn`, and
+//! the `clocks` of the state count the seconds each player has spent on their turns.
+//!
+//! Once the battle has started, the state holds the `hash` of the battle, which changes with
+//! every shot: a client polls `GET /games/{id}/hash` to tell cheaply whether its last state is
+//! still the one of the server, and fetches the state again when it isn't.
+//!
+//! A player who has lost their connection, or their client, sends their token to
+//! `POST /games/rejoin`: the answer is the whole state of their duel, from which they go on.
+//!
+//! The players of a duel can talk to each other: a message is sent with `POST /games/{id}/chat`,
+//! and the messages which follow the last one read, by their `seq`, are read with
+//! `GET /games/{id}/chat?after=<seq>`, every message of the chat without `after`.
+//!
+//! The duels waiting for a second player make the lobby, listed by `GET /games` from the one
+//! which has waited the longest. A duel is opened with a `title` for the lobby, and with the
+//! `variant` of its rules, classic by default, among those a [Duel] can play. A player who
+//! doesn't choose a duel asks `POST /games/match` to be paired: they join the open duel of the
+//! variant which has waited the longest, or open a new one, and the answer is `201 Created`.
+//!
+//! The [Metrics] of the games played to their end are answered by `GET /metrics`, in the text
+//! format of Prometheus. The games against the computer are won by the `player` or by the
+//! `computer` of their difficulty, like `computer (hard)`, and the duels by the `first` or the
+//! `second` player to shoot. The time taken by the computer to choose its moves is measured
+//! on the turns it plays.
+//!
+//! An error is answered with its status code, like `404 Not Found` for an unknown game, and a
+//! body like `{"error":"game 7 not found"}`.
+//!
+//! The games are kept in memory as long as the server runs, and they are dropped once nobody has
+//! played them for a while. With a [save folder](ServerOptions::save_dir), every duel is saved
+//! there too as soon as it changes, and the duels saved are resumed when the server starts
+//! again. As the games of the engine can't be shared between threads, they are
+//! split among a few [SessionStore]s, each one living on a thread of its own, and the handlers
+//! send them the work to do. The server stops on `Ctrl-C` or, on Unix, on `SIGTERM`, once the
+//! requests being served are answered.
+//!
+//! Built with the `tls` feature, the server encrypts its connections when its options give the
+//! [folder of its identity](ServerOptions::tls): a self-signed certificate is generated there on
+//! the first run, and the players give it to their clients, which trust only that one.
+//!
+use crate::bot::{BotEvent, ShotResult};
+use crate::config::Config;
+use crate::engine::correspondence::Report;
+use crate::engine::duel::{Duel, DuelError};
+use crate::engine::fleet::{Fleet, FleetError};
+use crate::engine::game::{Game, GameBuilder, GameError, GamePhase, Side};
+use crate::engine::grid::{self, Cell, CellState, Grid};
+use crate::engine::rules::Variant;
+use crate::metrics::Metrics;
+pub use crate::net::{
+    ChatMessage, ChatRequest, Clocks, GameState, HandshakeError, Hello, JoinRequest, MatchRequest,
+    NewGame, OpenGame, Opponent, Phase, Placements, ShotRequest, Shots, StateHash, TurnResult,
+    Welcome,
+};
+#[cfg(feature = "tls")]
+use crate::tls::Identity;
+use axum::extract::{Path, Query, State};
+use axum::http::{HeaderMap, StatusCode, header};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{get, post};
+use axum::serve::Listener;
+use axum::{Json, Router};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+#[cfg(feature = "tls")]
+use std::net::SocketAddr;
+use std::path::{Path as FilePath, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
+use std::{fs, io, thread};
+use thiserror::Error;
+#[cfg(feature = "tls")]
+use tokio::net::TcpStream;
+use tokio::sync::{Mutex, Notify, oneshot};
+#[cfg(feature = "tls")]
+use tokio_rustls::server::TlsStream;
+
+/// The errors of the API.
+#[derive(Debug, Error)]
+pub enum ApiError {
+    /// There is no game with the id.
+    #[error("game {0} not found")]
+    NotFound(u64),
+
+    /// The game is a duel, and the request has no token of its players.
+    #[error("game {0} needs the token of a player")]
+    Unauthorized(u64),
+
+    /// The game is played against the computer, so it can't be joined.
+    #[error("game {0} is played against the computer")]
+    NotADuel(u64),
+
+    /// The fleet of the game has already been deployed.
+    #[error("the fleet of game {0} has already been deployed")]
+    AlreadyPlaced(u64),
+
+    /// The fleet of the game has not been deployed yet.
+    #[error("the fleet of game {0} has not been deployed yet")]
+    NotPlaced(u64),
+
+    /// No duel is played with the token.
+    #[error("no game is played with this token")]
+    UnknownToken,
+
+    /// The chat message is empty, or too long.
+    #[error("a message must have from 1 to {} characters", ChatRequest::MAX_LENGTH)]
+    InvalidMessage,
+
+    /// The fleet is not valid.
+    #[error("invalid fleet: {0}")]
+    Fleet(#[from] FleetError),
+
+    /// The cell is not valid.
+    #[error(transparent)]
+    Cell(#[from] grid::Error),
+
+    /// The game refused the fleet or the shot.
+    #[error(transparent)]
+    Game(#[from] GameError),
+
+    /// The duel refused the player, the fleet or the shot.
+    #[error(transparent)]
+    Duel(#[from] DuelError),
+
+    /// The client can't play with the server.
+    #[error(transparent)]
+    Handshake(#[from] HandshakeError),
+}
+
+impl ApiError {
+    /// Returns the status code of the error.
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) | ApiError::UnknownToken => StatusCode::NOT_FOUND,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Duel(DuelError::AlreadyShot(_) | DuelError::Unsupported(_)) => {
+                StatusCode::UNPROCESSABLE_ENTITY
+            }
+            ApiError::NotADuel(_)
+            | ApiError::AlreadyPlaced(_)
+            | ApiError::NotPlaced(_)
+            | ApiError::Game(GameError::AlreadyOver)
+            | ApiError::Duel(_)
+            | ApiError::Handshake(_) => StatusCode::CONFLICT,
+            _ => StatusCode::UNPROCESSABLE_ENTITY,
+        }
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body = json!({ "error": self.to_string() });
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+impl Shots {
+    // Collects the shots marked on the grid, leaving out the hidden cells.
+    fn from_grid(grid: &Grid, hidden: &[Cell]) -> Self {
+        let mut shots = Self::default();
+        for cell in (0..10).flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y))) {
+            match grid.at(&cell) {
+                _ if hidden.contains(&cell) => {}
+                CellState::Hit => shots.hits.push(cell),
+                CellState::Miss => shots.misses.push(cell),
+                _ => {}
+            }
+        }
+
+        shots
+    }
+}
+
+impl GameState {
+    // The state of a game whose fleets have not been deployed yet.
+    fn setup(id: u64, opponent: Opponent) -> Self {
+        Self {
+            id,
+            opponent,
+            phase: Phase::Setup,
+            opponent_name: None,
+            winner: None,
+            your_turn: false,
+            turn: 0,
+            fleet: None,
+            ships_left: Vec::new(),
+            sunk: Vec::new(),
+            shots: Shots::default(),
+            opponent_shots: Shots::default(),
+            clocks: Clocks::default(),
+            token: None,
+            hash: None,
+        }
+    }
+}
+
+// A game of a store, with the last time it has been played.
+struct Session {
+    game: Match,
+    last_active: Instant,
+}
+
+enum Match {
+    // A game against the computer: the settings of the player, then the game once the fleet is
+    // deployed.
+    Computer {
+        settings: NewGame,
+        game: Option<Box<Game>>,
+        reported: (u32, usize),
+    },
+
+    // A duel, with the tokens of its seats, its title in the lobby, when it has been opened, the
+    // messages of its chat, with the seat of their sender, and the clocks of its seats, without
+    // the current turn, which has started at `since`.
+    Duel {
+        duel: Box<Duel>,
+        tokens: Vec<String>,
+        title: String,
+        opened: Instant,
+        chat: Vec<(usize, String)>,
+        clocks: [Duration; 2],
+        since: Instant,
+    },
+}
+
+// A duel as it is saved in the save folder: the fleets deployed and the shots fired are played
+// again to resume it. The clocks are in milliseconds, and the time waited in the lobby in
+// seconds.
+#[derive(Debug, Serialize, Deserialize)]
+struct SavedDuel {
+    variant: Variant,
+    names: Vec<String>,
+    tokens: Vec<String>,
+    title: String,
+    waiting: u64,
+    fleets: [Option<String>; 2],
+    first: usize,
+    shots: Vec<(usize, Cell)>,
+    chat: Vec<(usize, String)>,
+    clocks: [u64; 2],
+}
+
+impl SavedDuel {
+    // Describes the duel to be saved, or returns `None` for a game against the computer.
+    fn from_match(game: &Match) -> Option<Self> {
+        let Match::Duel {
+            duel,
+            tokens,
+            title,
+            opened,
+            chat,
+            clocks,
+            ..
+        } = game
+        else {
+            return None;
+        };
+
+        Some(Self {
+            variant: duel.variant(),
+            names: (0..2)
+                .filter_map(|seat| duel.name(seat).map(str::to_string))
+                .collect(),
+            tokens: tokens.clone(),
+            title: title.clone(),
+            waiting: opened.elapsed().as_secs(),
+            fleets: [0, 1].map(|seat| duel.player(seat).map(|player| player.fleet().to_code())),
+            first: duel.first().unwrap_or_default(),
+            shots: duel
+                .shots()
+                .iter()
+                .map(|shot| (shot.seat, shot.cell))
+                .collect(),
+            chat: chat.clone(),
+            clocks: clocks.map(|clock| clock.as_millis() as u64),
+        })
+    }
+
+    // Resumes the duel, whose current turn starts again now.
+    fn resume(self) -> Result<Match, ApiError> {
+        let mut fleets = [None, None];
+        for (fleet, code) in fleets.iter_mut().zip(self.fleets) {
+            *fleet = code.as_deref().map(Fleet::from_code).transpose()?;
+        }
+        let duel = Duel::resume(self.variant, &self.names, fleets, self.first, &self.shots)?;
+        let now = Instant::now();
+
+        Ok(Match::Duel {
+            duel: Box::new(duel),
+            tokens: self.tokens,
+            title: self.title,
+            opened: now
+                .checked_sub(Duration::from_secs(self.waiting))
+                .unwrap_or(now),
+            chat: self.chat,
+            clocks: self.clocks.map(Duration::from_millis),
+            since: now,
+        })
+    }
+}
+
+/// The options of a server.
+#[derive(Debug, Clone)]
+pub struct ServerOptions {
+    /// How long a game is kept without being played, 30 minutes by default.
+    pub idle: Duration,
+
+    /// The folder where the duels are saved, to be resumed when the server starts again. The
+    /// duels are only kept in memory without it, like the games against the computer.
+    pub save_dir: Option<PathBuf>,
+
+    /// The folder of the certificate and of the key of the server, to encrypt the connections
+    /// with TLS. The connections are in plaintext without it. See [Identity].
+    #[cfg(feature = "tls")]
+    pub tls: Option<PathBuf>,
+}
+
+impl Default for ServerOptions {
+    fn default() -> Self {
+        Self {
+            idle: Duration::from_secs(30 * 60),
+            save_dir: None,
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+}
+
+/// The games played through the API, kept in memory.
+///
+/// The settings which are not given by the client, like the difficulty, are taken from the
+/// configuration of the server, like its handicap and its map. A game which has not been played
+/// for longer than the idle time is dropped by [SessionStore::expire].
+pub struct SessionStore {
+    config: Config,
+    idle: Duration,
+    save_dir: Option<PathBuf>,
+    sessions: HashMap<u64, Session>,
+    next_id: u64,
+    id_step: u64,
+    metrics: Metrics,
+}
+
+impl SessionStore {
+    /// Creates a store without any game, with the given configuration and idle time.
+    pub fn new(config: Config, idle: Duration) -> Self {
+        let options = ServerOptions {
+            idle,
+            ..ServerOptions::default()
+        };
+        Self::shard(config, &options, 0, 1)
+    }
+
+    // Creates the store with the given index among the stores of a server, which gives the ids
+    // of its games: the ids of the first store of three are 1, 4, 7 and so on. The duels of the
+    // store found in the save folder are resumed.
+    fn shard(config: Config, options: &ServerOptions, index: usize, count: usize) -> Self {
+        let mut store = Self {
+            config,
+            idle: options.idle,
+            save_dir: options.save_dir.clone(),
+            sessions: HashMap::new(),
+            next_id: index as u64 + 1,
+            id_step: count as u64,
+            metrics: Metrics::new(),
+        };
+        if let Some(dir) = options.save_dir.clone() {
+            store.restore(&dir, index);
+        }
+
+        store
+    }
+
+    // Resumes the duels saved in the folder whose ids belong to the store. A duel which can't be
+    // resumed is left in the folder.
+    fn restore(&mut self, dir: &FilePath, index: usize) {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return;
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            let Some(id) = path
+                .file_name()
+                .and_then(|name| name.to_str()?.strip_suffix(".json")?.parse::<u64>().ok())
+                .filter(|id| id.checked_sub(1).map(|id| id % self.id_step) == Some(index as u64))
+            else {
+                continue;
+            };
+            let resumed = fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| {
+                    serde_json::from_str::<SavedDuel>(&json).map_err(|e| e.to_string())
+                })
+                .and_then(|saved| saved.resume().map_err(|e| e.to_string()));
+            match resumed {
+                Ok(game) => {
+                    self.sessions.insert(
+                        id,
+                        Session {
+                            game,
+                            last_active: Instant::now(),
+                        },
+                    );
+                    self.next_id = self.next_id.max(id + self.id_step);
+                }
+                Err(e) => eprintln!("can't resume game {id}: {e}"),
+            }
+        }
+    }
+
+    // Saves the duel in the save folder, if any. A duel which can't be saved is only kept in
+    // memory.
+    fn save(&self, id: u64) {
+        let (Some(dir), Some(session)) = (&self.save_dir, self.sessions.get(&id)) else {
+            return;
+        };
+        let Some(saved) = SavedDuel::from_match(&session.game) else {
+            return;
+        };
+        let written = serde_json::to_string(&saved)
+            .map_err(io::Error::from)
+            .and_then(|json| fs::write(dir.join(format!("{id}.json")), json));
+        if let Err(e) = written {
+            eprintln!("can't save game {id}: {e}");
+        }
+    }
+
+    /// Creates a new game, waiting for the fleet of the player, or a duel waiting for a second
+    /// player too.
+    ///
+    /// An error is returned if a duel can't be played with the variant.
+    pub fn create(&mut self, settings: NewGame) -> Result<GameState, ApiError> {
+        let token = Self::token();
+        let game = match settings.opponent {
+            Opponent::Computer => Match::Computer {
+                settings,
+                game: None,
+                reported: (0, 0),
+            },
+            Opponent::Human => {
+                let host = Self::player_name(settings.name, 0);
+                let duel = Duel::with_variant(&host, settings.variant.unwrap_or_default())?;
+                Match::Duel {
+                    duel: Box::new(duel),
+                    tokens: vec![token.clone()],
+                    title: settings.title.unwrap_or_else(|| format!("{host}'s game")),
+                    opened: Instant::now(),
+                    chat: Vec::new(),
+                    clocks: [Duration::ZERO; 2],
+                    since: Instant::now(),
+                }
+            }
+        };
+        let id = self.next_id;
+        self.next_id += self.id_step;
+        let mut state = Self::state_of(id, &game, Some(0));
+        if let Match::Duel { .. } = game {
+            state.token = Some(token);
+        }
+        self.sessions.insert(
+            id,
+            Session {
+                game,
+                last_active: Instant::now(),
+            },
+        );
+        self.save(id);
+
+        Ok(state)
+    }
+
+    /// Lets a second player join the duel.
+    ///
+    /// An error is returned if the game doesn't exist, if it is played against the computer,
+    /// or if the duel already has two players.
+    pub fn join(&mut self, id: u64, request: JoinRequest) -> Result<GameState, ApiError> {
+        let session = self.session(id)?;
+        let Match::Duel { duel, tokens, .. } = &mut session.game else {
+            return Err(ApiError::NotADuel(id));
+        };
+        let seat = duel.join(&Self::player_name(request.name, 1))?;
+        let token = Self::token();
+        tokens.push(token.clone());
+        let state = GameState {
+            token: Some(token),
+            ..Self::state_of(id, &session.game, Some(seat))
+        };
+        self.save(id);
+
+        Ok(state)
+    }
+
+    /// Deploys the fleet of the player, and starts the battle against the computer, or the
+    /// duel once both fleets are deployed.
+    ///
+    /// An error is returned if the game doesn't exist, if the token is not the one of a player
+    /// of the duel, if the fleet has already been deployed, or if the fleet is not valid or not
+    /// allowed by the rules.
+    pub fn place(
+        &mut self,
+        id: u64,
+        token: Option<&str>,
+        placements: &Placements,
+    ) -> Result<GameState, ApiError> {
+        let config = self.config.clone();
+        let session = self.session(id)?;
+        let seat = Self::seat(id, &session.game, token)?;
+        let fleet = Fleet::from_code(&placements.fleet)?;
+        match &mut session.game {
+            Match::Computer { game: Some(_), .. } => return Err(ApiError::AlreadyPlaced(id)),
+            Match::Computer { settings, game, .. } => {
+                let name = settings.name.as_ref().unwrap_or(&config.player_name);
+                let mut builder = GameBuilder::new()
+                    .variant(settings.variant.unwrap_or(config.variant))
+                    .human(name, fleet)
+                    .computer(settings.difficulty.unwrap_or(config.difficulty))
+                    .solver_budget(config.solver_budget as usize)
+                    .adaptation(config.adaptation)
+                    .handicap_option(config.handicap);
+                if let Some(map) = config.map().ok().flatten() {
+                    builder = builder.map(map);
+                }
+                *game = Some(Box::new(builder.build()?));
+            }
+            Match::Duel { duel, since, .. } => {
+                duel.deploy(seat.unwrap_or_default(), fleet)?;
+                *since = Instant::now();
+            }
+        }
+        let state = Self::state_of(id, &session.game, seat);
+        self.save(id);
+
+        Ok(state)
+    }
+
+    /// Fires the shot of the player, and plays the turn of the computer.
+    ///
+    /// An error is returned if the game doesn't exist, if the token is not the one of a player
+    /// of the duel, if the battle has not started or if the shot is not valid.
+    pub fn shoot(
+        &mut self,
+        id: u64,
+        token: Option<&str>,
+        shot: &ShotRequest,
+    ) -> Result<TurnResult, ApiError> {
+        let difficulty = self.config.difficulty;
+        let session = self.session(id)?;
+        let seat = Self::seat(id, &session.game, token)?;
+        let cell = Cell::from_str(&shot.cell)?;
+        let (mut decision, mut finished) = (None, None);
+        let shots = match &mut session.game {
+            Match::Computer { game: None, .. } => return Err(ApiError::NotPlaced(id)),
+            Match::Computer {
+                game: Some(game),
+                reported,
+                settings,
+            } => {
+                let start = Instant::now();
+                game.play_turn(&cell)?;
+                if game.last_computer_move().is_some() {
+                    decision = Some(start.elapsed());
+                }
+                if game.is_over() {
+                    let difficulty = settings.difficulty.unwrap_or(difficulty);
+                    let players = ["player".to_string(), format!("computer ({difficulty})")];
+                    finished = Some((players, game.winner().map(|winner| winner as usize)));
+                }
+                Self::turn_events(game, reported)
+            }
+            Match::Duel {
+                duel,
+                clocks,
+                since,
+                ..
+            } => {
+                let seat = seat.unwrap_or_default();
+                let shot = duel.fire(seat, &cell)?;
+                clocks[seat] += since.elapsed();
+                *since = Instant::now();
+                let (result, ship) = match &shot.report {
+                    Report::Miss => (ShotResult::Miss, None),
+                    Report::Hit => (ShotResult::Hit, None),
+                    Report::Sunk(kind) => (ShotResult::Sunk, Some(kind.clone())),
+                };
+                let events = vec![BotEvent::Shot {
+                    turn: shot.turn,
+                    shooter: Side::Human,
+                    cell: shot.cell.to_string(),
+                    result,
+                    ship,
+                }];
+                if let (Some(winner), Some(first)) = (duel.winner(), duel.first()) {
+                    let players = ["first".to_string(), "second".to_string()];
+                    finished = Some((players, Some(usize::from(winner != first))));
+                }
+                events
+            }
+        };
+
+        let result = TurnResult {
+            shots,
+            state: Self::state_of(id, &session.game, seat),
+        };
+        if let Some(latency) = decision {
+            self.metrics.record_decision(latency);
+        }
+        if let Some(([a, b], winner)) = finished {
+            let fired = [&result.state.shots, &result.state.opponent_shots]
+                .iter()
+                .map(|shots| shots.hits.len() + shots.misses.len())
+                .sum::<usize>();
+            self.metrics.record_game([&a, &b], winner, fired as u32);
+        }
+        self.save(id);
+
+        Ok(result)
+    }
+
+    /// Returns the state of the game.
+    ///
+    /// An error is returned if the game doesn't exist, or if the token is not the one of a
+    /// player of the duel.
+    pub fn state(&mut self, id: u64, token: Option<&str>) -> Result<GameState, ApiError> {
+        let session = self.session(id)?;
+        let seat = Self::seat(id, &session.game, token)?;
+
+        Ok(Self::state_of(id, &session.game, seat))
+    }
+
+    /// Returns the hash of the battle, without the rest of its state.
+    ///
+    /// An error is returned if the game doesn't exist, or if the token is not the one of a
+    /// player of the duel.
+    pub fn state_hash(&mut self, id: u64, token: Option<&str>) -> Result<StateHash, ApiError> {
+        let session = self.session(id)?;
+        Self::seat(id, &session.game, token)?;
+
+        Ok(StateHash {
+            hash: Self::hash_of(&session.game),
+        })
+    }
+
+    /// Returns the metrics of the games played in this store.
+    pub fn metrics(&self) -> &Metrics {
+        &self.metrics
+    }
+
+    /// Sends the message of the player to the chat of the duel.
+    ///
+    /// An error is returned if the game doesn't exist, if it is played against the computer, if
+    /// the token is not the one of a player of the duel, or if the message is empty or too long.
+    pub fn say(
+        &mut self,
+        id: u64,
+        token: Option<&str>,
+        message: &ChatRequest,
+    ) -> Result<ChatMessage, ApiError> {
+        let session = self.session(id)?;
+        let seat = Self::seat(id, &session.game, token)?.ok_or(ApiError::NotADuel(id))?;
+        let Match::Duel { duel, chat, .. } = &mut session.game else {
+            return Err(ApiError::NotADuel(id));
+        };
+        let text = message.text.trim();
+        if text.is_empty() || text.chars().count() > ChatRequest::MAX_LENGTH {
+            return Err(ApiError::InvalidMessage);
+        }
+        chat.push((seat, text.to_string()));
+        let message = Self::chat_message(duel, seat, chat.len(), &chat[chat.len() - 1]);
+        self.save(id);
+
+        Ok(message)
+    }
+
+    /// Returns the messages of the chat of the duel which follow the given position, as the
+    /// player knows them.
+    ///
+    /// An error is returned if the game doesn't exist, if it is played against the computer, or
+    /// if the token is not the one of a player of the duel.
+    pub fn chat(
+        &mut self,
+        id: u64,
+        token: Option<&str>,
+        after: u32,
+    ) -> Result<Vec<ChatMessage>, ApiError> {
+        let session = self.session(id)?;
+        let seat = Self::seat(id, &session.game, token)?.ok_or(ApiError::NotADuel(id))?;
+        let Match::Duel { duel, chat, .. } = &session.game else {
+            return Err(ApiError::NotADuel(id));
+        };
+
+        Ok(chat
+            .iter()
+            .enumerate()
+            .skip(after as usize)
+            .map(|(index, message)| Self::chat_message(duel, seat, index + 1, message))
+            .collect())
+    }
+
+    /// Returns the whole state of the duel played by the player with the token, so that they
+    /// can go on from a new connection.
+    ///
+    /// An error is returned if no duel of the store is played with the token.
+    pub fn rejoin(&mut self, token: &str) -> Result<GameState, ApiError> {
+        let id = self
+            .sessions
+            .iter()
+            .find_map(|(id, session)| match &session.game {
+                Match::Duel { tokens, .. } if tokens.iter().any(|seat| seat == token) => Some(*id),
+                _ => None,
+            })
+            .ok_or(ApiError::UnknownToken)?;
+        let session = self.session(id)?;
+        let seat = Self::seat(id, &session.game, Some(token))?;
+
+        Ok(GameState {
+            token: Some(token.to_string()),
+            ..Self::state_of(id, &session.game, seat)
+        })
+    }
+
+    /// Returns the duels waiting for a second player, from the one which has waited the longest.
+    pub fn open_games(&self) -> Vec<OpenGame> {
+        let now = Instant::now();
+        let mut games: Vec<(u64, &Session)> = self
+            .sessions
+            .iter()
+            .map(|(id, session)| (*id, session))
+            .collect();
+        games.sort_by_key(|(id, _)| *id);
+
+        games
+            .into_iter()
+            .filter_map(|(id, session)| match &session.game {
+                Match::Duel {
+                    duel,
+                    title,
+                    opened,
+                    ..
+                } if duel.name(1).is_none() => Some(OpenGame {
+                    id,
+                    title: title.clone(),
+                    host: duel.name(0).unwrap_or_default().to_string(),
+                    variant: duel.variant(),
+                    waiting: now.saturating_duration_since(*opened).as_secs(),
+                }),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Drops the games which have not been played for longer than the idle time, with their
+    /// saves, and returns how many they are.
+    pub fn expire(&mut self, now: Instant) -> usize {
+        let expired: Vec<u64> = self
+            .sessions
+            .iter()
+            .filter(|(_, session)| now.saturating_duration_since(session.last_active) > self.idle)
+            .map(|(id, _)| *id)
+            .collect();
+        for id in &expired {
+            self.sessions.remove(id);
+            if let Some(dir) = &self.save_dir {
+                let _ = fs::remove_file(dir.join(format!("{id}.json")));
+            }
+        }
+
+        expired.len()
+    }
+
+    // Returns the game, marking it as played now.
+    fn session(&mut self, id: u64) -> Result<&mut Session, ApiError> {
+        let session = self.sessions.get_mut(&id).ok_or(ApiError::NotFound(id))?;
+        session.last_active = Instant::now();
+
+        Ok(session)
+    }
+
+    // Returns the seat of the player with the token in a duel, or `None` in a game against the
+    // computer.
+    fn seat(id: u64, game: &Match, token: Option<&str>) -> Result<Option<usize>, ApiError> {
+        match game {
+            Match::Computer { .. } => Ok(None),
+            Match::Duel { tokens, .. } => tokens
+                .iter()
+                .position(|seat| Some(seat.as_str()) == token)
+                .map(Some)
+                .ok_or(ApiError::Unauthorized(id)),
+        }
+    }
+
+    // Tells the message at the position of the chat, from the side of the player on the seat.
+    fn chat_message(
+        duel: &Duel,
+        seat: usize,
+        seq: usize,
+        (sender, text): &(usize, String),
+    ) -> ChatMessage {
+        ChatMessage {
+            seq: seq as u32,
+            sender: duel.name(*sender).unwrap_or_default().to_string(),
+            yours: *sender == seat,
+            text: text.clone(),
+        }
+    }
+
+    // Makes a new token for a player of a duel.
+    fn token() -> String {
+        format!("{:032x}", rand::rng().random::<u128>())
+    }
+
+    // Returns the name of the player of a duel, or a name after their seat.
+    fn player_name(name: Option<String>, seat: usize) -> String {
+        name.unwrap_or_else(|| format!("Player {}", seat + 1))
+    }
+
+    // Describes the shots of the last turn against the computer which have not been reported.
+    // With a streak or a salvo, the same turn is played by several shots of the player.
+    fn turn_events(game: &Game, reported: &mut (u32, usize)) -> Vec<BotEvent> {
+        let Some(turn) = game.last_turn() else {
+            return Vec::new();
+        };
+        let skipped = match *reported {
+            (reported_turn, shots) if reported_turn == turn.turn => shots,
+            _ => 0,
+        };
+        *reported = (turn.turn, turn.shots.len());
+
+        turn.shots
+            .iter()
+            .skip(skipped)
+            .map(|shot| BotEvent::from((turn.turn, shot)))
+            .collect()
+    }
+
+    // Tells the state of the game, from the side of the player on the seat of a duel.
+    fn state_of(id: u64, game: &Match, seat: Option<usize>) -> GameState {
+        let state = match game {
+            Match::Computer {
+                game: Some(game), ..
+            } => Self::game_state(id, game),
+            Match::Computer { game: None, .. } => GameState::setup(id, Opponent::Computer),
+            Match::Duel {
+                duel,
+                clocks,
+                since,
+                ..
+            } => {
+                let seat = seat.unwrap_or_default();
+                GameState {
+                    clocks: Self::clocks(duel, clocks, *since, seat),
+                    ..Self::duel_state(id, duel, seat)
+                }
+            }
+        };
+
+        GameState {
+            hash: Self::hash_of(game),
+            ..state
+        }
+    }
+
+    // Returns the hash of the battle in hexadecimal, once it has started.
+    fn hash_of(game: &Match) -> Option<String> {
+        let hash = match game {
+            Match::Computer {
+                game: Some(game), ..
+            } => game.state_hash(),
+            Match::Computer { game: None, .. } => return None,
+            Match::Duel { duel, .. } if duel.is_started() => duel.state_hash(),
+            Match::Duel { .. } => return None,
+        };
+
+        Some(format!("{hash:016x}"))
+    }
+
+    // Tells the time spent by the players of the duel, with the current turn, from the side of
+    // the player on the seat.
+    fn clocks(duel: &Duel, clocks: &[Duration; 2], since: Instant, seat: usize) -> Clocks {
+        let spent = |side: usize| {
+            let current = match duel.to_move() {
+                Some(next) if next == side => since.elapsed(),
+                _ => Duration::ZERO,
+            };
+            (clocks[side] + current).as_secs()
+        };
+
+        Clocks {
+            yours: spent(seat),
+            opponent: spent(1 - seat),
+        }
+    }
+
+    fn game_state(id: u64, game: &Game) -> GameState {
+        let (Some(human), Some(computer)) = (game.human(), game.computer()) else {
+            return GameState::setup(id, Opponent::Computer);
+        };
+
+        GameState {
+            phase: match game.phase() {
+                GamePhase::Setup => Phase::Setup,
+                GamePhase::InProgress => Phase::InProgress,
+                GamePhase::Finished { .. } => Phase::Finished,
+                GamePhase::Drawn => Phase::Drawn,
+            },
+            winner: game.winner(),
+            your_turn: !game.is_over(),
+            turn: game.turns_played(),
+            fleet: Some(human.fleet().to_code()),
+            ships_left: human.fleet().remaining_ships(),
+            sunk: human.sunk_kinds().to_vec(),
+            shots: Shots::from_grid(human.shots_grid(), &game.fog().hidden(Side::Human)),
+            opponent_shots: Shots::from_grid(computer.shots_grid(), &[]),
+            opponent_name: Some(computer.name().to_string()),
+            ..GameState::setup(id, Opponent::Computer)
+        }
+    }
+
+    fn duel_state(id: u64, duel: &Duel, seat: usize) -> GameState {
+        let mut state = GameState::setup(id, Opponent::Human);
+        state.opponent_name = duel.name(1 - seat).map(str::to_string);
+        if let Some(player) = duel.player(seat) {
+            state.fleet = Some(player.fleet().to_code());
+            state.ships_left = player.fleet().remaining_ships();
+            state.sunk = player.sunk_kinds().to_vec();
+            state.shots = Shots::from_grid(player.shots_grid(), &[]);
+        }
+        if let Some(opponent) = duel.player(1 - seat) {
+            state.opponent_shots = Shots::from_grid(opponent.shots_grid(), &[]);
+        }
+        if duel.is_started() {
+            state.phase = match duel.winner() {
+                Some(_) => Phase::Finished,
+                None => Phase::InProgress,
+            };
+        }
+        state.winner = duel.winner().map(|winner| match winner == seat {
+            true => Side::Human,
+            false => Side::Computer,
+        });
+        state.your_turn = duel.to_move() == Some(seat);
+        state.turn = duel.turn();
+
+        state
+    }
+}
+
+// The work sent to a store by a handler.
+type Job = Box<dyn FnOnce(&mut SessionStore) + Send>;
+
+// The handle of the stores, shared by the handlers. The players asking to be paired wait for
+// each other, so that two of them never open two duels instead of playing the same one.
+#[derive(Clone)]
+struct Stores {
+    shards: Arc<[Sender<Job>]>,
+    next: Arc<AtomicUsize>,
+    pairing: Arc<Mutex<()>>,
+    hello: Arc<Hello>,
+}
+
+impl Stores {
+    // How often a store looks for idle games.
+    const SWEEP: Duration = Duration::from_secs(60);
+
+    // Starts a store for every available core, each one on its own thread.
+    fn spawn(config: Config, options: &ServerOptions) -> Self {
+        let count = thread::available_parallelism().map_or(1, usize::from);
+        let shards = (0..count)
+            .map(|index| {
+                let (sender, receiver) = mpsc::channel::<Job>();
+                let config = config.clone();
+                let options = options.clone();
+                thread::spawn(move || {
+                    let mut store = SessionStore::shard(config, &options, index, count);
+                    let mut swept = Instant::now();
+                    loop {
+                        match receiver.recv_timeout(Self::SWEEP) {
+                            Ok(job) => job(&mut store),
+                            Err(RecvTimeoutError::Timeout) => {}
+                            Err(RecvTimeoutError::Disconnected) => break,
+                        }
+                        if swept.elapsed() >= Self::SWEEP {
+                            store.expire(Instant::now());
+                            swept = Instant::now();
+                        }
+                    }
+                });
+                sender
+            })
+            .collect();
+
+        Self {
+            shards,
+            next: Arc::new(AtomicUsize::new(0)),
+            pairing: Arc::new(Mutex::new(())),
+            hello: Arc::new(Hello::new(Config::BOARD_SIZE)),
+        }
+    }
+
+    // Returns the duels waiting for a second player in every store, from the one which has
+    // waited the longest.
+    async fn open_games(&self) -> Vec<OpenGame> {
+        let mut games = Vec::new();
+        for shard in 0..self.shards.len() {
+            games.extend(self.run(shard, |store| store.open_games()).await);
+        }
+        games.sort_by_key(|game| std::cmp::Reverse(game.waiting));
+
+        games
+    }
+
+    // Pairs the player with the open duel of the variant which has waited the longest, or opens
+    // a new one. Returns whether the duel has been opened, with its state.
+    //
+    // A duel may be joined by someone else, or dropped, before the player joins it: the player
+    // tries the next one.
+    async fn pair(&self, request: MatchRequest) -> Result<(bool, GameState), ApiError> {
+        let _pairing = self.pairing.lock().await;
+        let variant = request.variant.unwrap_or_default();
+        for game in self.open_games().await {
+            if game.variant != variant {
+                continue;
+            }
+            let join = JoinRequest {
+                name: request.name.clone(),
+            };
+            match self
+                .on(game.id, move |store| store.join(game.id, join))
+                .await
+            {
+                Ok(state) => return Ok((false, state)),
+                Err(ApiError::NotFound(_) | ApiError::Duel(DuelError::Full)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        let settings = NewGame {
+            name: request.name,
+            opponent: Opponent::Human,
+            variant: Some(variant),
+            ..NewGame::default()
+        };
+        let state = self.create(move |store| store.create(settings)).await?;
+
+        Ok((true, state))
+    }
+
+    // Gathers the metrics of every store.
+    async fn metrics(&self) -> Metrics {
+        let mut metrics = Metrics::new();
+        for shard in 0..self.shards.len() {
+            metrics.merge(&self.run(shard, |store| store.metrics().clone()).await);
+        }
+
+        metrics
+    }
+
+    // Looks for the duel played with the token in every store, and returns its whole state.
+    async fn rejoin(&self, token: String) -> Result<GameState, ApiError> {
+        for shard in 0..self.shards.len() {
+            let token = token.clone();
+            match self.run(shard, move |store| store.rejoin(&token)).await {
+                Err(ApiError::UnknownToken) => {}
+                result => return result,
+            }
+        }
+
+        Err(ApiError::UnknownToken)
+    }
+
+    // Runs the work on the store of a new game, taking turns among the stores.
+    async fn create<T, F>(&self, work: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SessionStore) -> T + Send + 'static,
+    {
+        let shard = self.next.fetch_add(1, Ordering::Relaxed) % self.shards.len();
+        self.run(shard, work).await
+    }
+
+    // Runs the work on the store of the game.
+    async fn on<T, F>(&self, id: u64, work: F) -> Result<T, ApiError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SessionStore) -> Result<T, ApiError> + Send + 'static,
+    {
+        let index = id.checked_sub(1).ok_or(ApiError::NotFound(id))?;
+        let shard = (index % self.shards.len() as u64) as usize;
+
+        self.run(shard, work).await
+    }
+
+    // Runs the work on the thread of the store, and waits for its result.
+    async fn run<T, F>(&self, shard: usize, work: F) -> T
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut SessionStore) -> T + Send + 'static,
+    {
+        let (sender, receiver) = oneshot::channel();
+        let job: Job = Box::new(move |store| {
+            let _ = sender.send(work(store));
+        });
+        self.shards[shard]
+            .send(job)
+            .expect("the session store has stopped");
+
+        receiver.await.expect("the session store has stopped")
+    }
+}
+
+/// Returns the routes of the API, with new stores using the given configuration and options.
+pub fn router(config: Config, options: &ServerOptions) -> Router {
+    Router::new()
+        .route("/hello", post(hello))
+        .route("/games", get(lobby).post(create))
+        .route("/games/match", post(pair))
+        .route("/games/rejoin", post(rejoin))
+        .route("/games/{id}/join", post(join))
+        .route("/games/{id}/placements", post(place))
+        .route("/games/{id}/shots", post(shoot))
+        .route("/games/{id}/state", get(state))
+        .route("/games/{id}/hash", get(state_hash))
+        .route("/games/{id}/chat", get(chat).post(say))
+        .route("/metrics", get(metrics))
+        .with_state(Stores::spawn(config, options))
+}
+
+/// Serves the API at the given address, like `127.0.0.1:8080`, until the process is asked to
+/// stop. The save folder of the options is created if it is missing, and so is the certificate
+/// of the server when the connections are encrypted.
+pub fn serve(config: Config, address: &str, options: &ServerOptions) -> io::Result<()> {
+    if let Some(dir) = &options.save_dir {
+        fs::create_dir_all(dir)?;
+    }
+    tokio::runtime::Runtime::new()?.block_on(async {
+        let listener = tokio::net::TcpListener::bind(address).await?;
+        let local = listener.local_addr()?;
+        #[cfg(feature = "tls")]
+        if let Some(dir) = &options.tls {
+            let identity = Identity::new(dir);
+            let mut names = vec!["localhost".to_string()];
+            if !local.ip().is_unspecified() {
+                names.push(local.ip().to_string());
+            }
+            if identity.generate(&names).map_err(io::Error::other)? {
+                eprintln!(
+                    "generated a self-signed certificate for {}",
+                    names.join(", ")
+                );
+            }
+            let listener = TlsListener::new(
+                listener,
+                identity.server_config().map_err(io::Error::other)?,
+            )?;
+            eprintln!(
+                "serving Naval Battle with TLS at {local}, with the certificate {}",
+                identity.certificate_path().display()
+            );
+            return run(listener, router(config, options)).await;
+        }
+        eprintln!("serving Naval Battle at {local}");
+        run(listener, router(config, options)).await
+    })
+}
+
+// Serves the routes on the listener, until the process is asked to stop.
+async fn run<L>(listener: L, router: Router) -> io::Result<()>
+where
+    L: Listener,
+    L::Addr: std::fmt::Debug,
+{
+    axum::serve(listener, router)
+        .with_graceful_shutdown(shutdown())
+        .await?;
+    eprintln!("stopped serving Naval Battle");
+
+    Ok(())
+}
+
+// Accepts the connections of a listener, and hands over those whose TLS handshake succeeds.
+// The handshakes run on tasks of their own, so that a slow client doesn't hold up the others.
+#[cfg(feature = "tls")]
+struct TlsListener {
+    address: SocketAddr,
+    connections: tokio::sync::mpsc::Receiver<(TlsStream<TcpStream>, SocketAddr)>,
+}
+
+#[cfg(feature = "tls")]
+impl TlsListener {
+    // How long a client has to complete the handshake.
+    const HANDSHAKE: Duration = Duration::from_secs(10);
+
+    fn new(
+        mut listener: tokio::net::TcpListener,
+        config: Arc<rustls::ServerConfig>,
+    ) -> io::Result<Self> {
+        let address = listener.local_addr()?;
+        let acceptor = tokio_rustls::TlsAcceptor::from(config);
+        let (sender, connections) = tokio::sync::mpsc::channel(64);
+        tokio::spawn(async move {
+            while !sender.is_closed() {
+                let (stream, peer) = Listener::accept(&mut listener).await;
+                let (acceptor, sender) = (acceptor.clone(), sender.clone());
+                tokio::spawn(async move {
+                    let handshake = tokio::time::timeout(Self::HANDSHAKE, acceptor.accept(stream));
+                    if let Ok(Ok(stream)) = handshake.await {
+                        let _ = sender.send((stream, peer)).await;
+                    }
+                });
+            }
+        });
+
+        Ok(Self {
+            address,
+            connections,
+        })
+    }
+}
+
+#[cfg(feature = "tls")]
+impl Listener for TlsListener {
+    type Io = TlsStream<TcpStream>;
+    type Addr = SocketAddr;
+
+    async fn accept(&mut self) -> (Self::Io, Self::Addr) {
+        match self.connections.recv().await {
+            Some(connection) => connection,
+            // The task accepting the connections only stops once the listener is dropped.
+            None => std::future::pending().await,
+        }
+    }
+
+    fn local_addr(&self) -> io::Result<Self::Addr> {
+        Ok(self.address)
+    }
+}
+
+// Waits for `Ctrl-C` or, on Unix, for `SIGTERM`.
+async fn shutdown() {
+    let stop = Arc::new(Notify::new());
+    let interrupted = stop.clone();
+    tokio::spawn(async move {
+        if tokio::signal::ctrl_c().await.is_ok() {
+            interrupted.notify_one();
+        }
+    });
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{SignalKind, signal};
+        if let Ok(mut terminate) = signal(SignalKind::terminate()) {
+            let terminated = stop.clone();
+            tokio::spawn(async move {
+                terminate.recv().await;
+                terminated.notify_one();
+            });
+        }
+    }
+
+    stop.notified().await;
+}
+
+// Returns the token sent as `Authorization: Bearer <token>`, if any.
+fn bearer(headers: &HeaderMap) -> Option<String> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    value.strip_prefix("Bearer ").map(str::to_string)
+}
+
+async fn hello(
+    State(stores): State<Stores>,
+    Json(hello): Json<Hello>,
+) -> Result<Json<Welcome>, ApiError> {
+    Ok(Json(stores.hello.accept(&hello)?))
+}
+
+async fn lobby(State(stores): State<Stores>) -> Json<Vec<OpenGame>> {
+    Json(stores.open_games().await)
+}
+
+async fn create(
+    State(stores): State<Stores>,
+    settings: Option<Json<NewGame>>,
+) -> Result<(StatusCode, Json<GameState>), ApiError> {
+    let settings = settings.map(|Json(settings)| settings).unwrap_or_default();
+    let state = stores.create(move |store| store.create(settings)).await?;
+
+    Ok((StatusCode::CREATED, Json(state)))
+}
+
+async fn pair(
+    State(stores): State<Stores>,
+    request: Option<Json<MatchRequest>>,
+) -> Result<(StatusCode, Json<GameState>), ApiError> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let (opened, state) = stores.pair(request).await?;
+    let status = match opened {
+        true => StatusCode::CREATED,
+        false => StatusCode::OK,
+    };
+
+    Ok((status, Json(state)))
+}
+
+async fn join(
+    State(stores): State<Stores>,
+    Path(id): Path<u64>,
+    request: Option<Json<JoinRequest>>,
+) -> Result<Json<GameState>, ApiError> {
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    stores
+        .on(id, move |store| store.join(id, request))
+        .await
+        .map(Json)
+}
+
+async fn place(
+    State(stores): State<Stores>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+    Json(placements): Json<Placements>,
+) -> Result<Json<GameState>, ApiError> {
+    let token = bearer(&headers);
+    stores
+        .on(id, move |store| {
+            store.place(id, token.as_deref(), &placements)
+        })
+        .await
+        .map(Json)
+}
+
+async fn shoot(
+    State(stores): State<Stores>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+    Json(shot): Json<ShotRequest>,
+) -> Result<Json<TurnResult>, ApiError> {
+    let token = bearer(&headers);
+    stores
+        .on(id, move |store| store.shoot(id, token.as_deref(), &shot))
+        .await
+        .map(Json)
+}
+
+async fn metrics(State(stores): State<Stores>) -> impl IntoResponse {
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        stores.metrics().await.prometheus(),
+    )
+}
+
+async fn state(
+    State(stores): State<Stores>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+) -> Result<Json<GameState>, ApiError> {
+    let token = bearer(&headers);
+    stores
+        .on(id, move |store| store.state(id, token.as_deref()))
+        .await
+        .map(Json)
+}
+
+async fn state_hash(
+    State(stores): State<Stores>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+) -> Result<Json<StateHash>, ApiError> {
+    let token = bearer(&headers);
+    stores
+        .on(id, move |store| store.state_hash(id, token.as_deref()))
+        .await
+        .map(Json)
+}
+
+async fn say(
+    State(stores): State<Stores>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+    Json(message): Json<ChatRequest>,
+) -> Result<(StatusCode, Json<ChatMessage>), ApiError> {
+    let token = bearer(&headers);
+    let message = stores
+        .on(id, move |store| store.say(id, token.as_deref(), &message))
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(message)))
+}
+
+async fn rejoin(
+    State(stores): State<Stores>,
+    headers: HeaderMap,
+) -> Result<Json<GameState>, ApiError> {
+    let token = bearer(&headers).ok_or(ApiError::UnknownToken)?;
+    stores.rejoin(token).await.map(Json)
+}
+
+// The query of `GET /games/{id}/chat`.
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(default)]
+struct ChatQuery {
+    after: u32,
+}
+
+async fn chat(
+    State(stores): State<Stores>,
+    Path(id): Path<u64>,
+    headers: HeaderMap,
+    Query(query): Query<ChatQuery>,
+) -> Result<Json<Vec<ChatMessage>>, ApiError> {
+    let token = bearer(&headers);
+    stores
+        .on(id, move |store| {
+            store.chat(id, token.as_deref(), query.after)
+        })
+        .await
+        .map(Json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::game::Difficulty;
+    use crate::engine::rules::Variant;
+    use rstest::rstest;
+
+    fn store() -> SessionStore {
+        SessionStore::new(Config::default(), Duration::from_secs(60))
+    }
+
+    fn placements(fleet: &str) -> Placements {
+        Placements {
+            fleet: fleet.to_string(),
+        }
+    }
+
+    fn shot(cell: &str) -> ShotRequest {
+        ShotRequest {
+            cell: cell.to_string(),
+        }
+    }
+
+    #[rstest]
+    fn test_create_and_place() {
+        let mut store = store();
+
+        let created = store
+            .create(NewGame {
+                name: Some("Ishmael".to_string()),
+                difficulty: Some(Difficulty::Easy),
+                ..NewGame::default()
+            })
+            .unwrap();
+        let placed = store
+            .place(created.id, None, &placements("A1H/A3H/A5H/A7H/A9H"))
+            .unwrap();
+
+        assert_eq!(created.phase, Phase::Setup);
+        assert_eq!(created.token, None);
+        assert_eq!(placed.phase, Phase::InProgress);
+        assert!(placed.your_turn);
+        assert_eq!(placed.fleet.as_deref(), Some("A1H/A3H/A5H/A7H/A9H"));
+        assert_eq!(placed.ships_left.len(), 5);
+        assert_eq!(store.create(NewGame::default()).unwrap().id, created.id + 1);
+        assert!(matches!(
+            store.place(created.id, None, &placements("A1H/A3H/A5H/A7H/A9H")),
+            Err(ApiError::AlreadyPlaced(_))
+        ));
+    }
+
+    #[rstest]
+    fn test_shoot() {
+        let mut store = store();
+        let id = store.create(NewGame::default()).unwrap().id;
+
+        assert!(matches!(
+            store.shoot(id, None, &shot("B7")),
+            Err(ApiError::NotPlaced(_))
+        ));
+        store
+            .place(id, None, &placements("A1V/C1V/E1V/G1V/I1V"))
+            .unwrap();
+        let result = store.shoot(id, None, &shot("B7")).unwrap();
+
+        assert_eq!(result.shots.len(), 2);
+        assert!(result.shots.iter().any(|event| matches!(
+            event,
+            BotEvent::Shot { shooter: Side::Human, cell, .. } if cell == "B7"
+        )));
+        assert_eq!(result.state.turn, 1);
+        assert_eq!(
+            result.state.shots.hits.len() + result.state.shots.misses.len(),
+            1
+        );
+        assert_eq!(store.state(id, None).unwrap(), result.state);
+    }
+
+    #[rstest]
+    fn test_duel() {
+        let mut store = store();
+        let created = store
+            .create(NewGame {
+                name: Some("Ishmael".to_string()),
+                opponent: Opponent::Human,
+                ..NewGame::default()
+            })
+            .unwrap();
+        let id = created.id;
+        let first = created.token.unwrap();
+
+        let joined = store.join(id, JoinRequest::default()).unwrap();
+        let second = joined.token.unwrap();
+        assert_ne!(first, second);
+        assert!(matches!(
+            store.join(id, JoinRequest::default()),
+            Err(ApiError::Duel(DuelError::Full))
+        ));
+        assert!(matches!(
+            store.place(id, None, &placements("A1H/A3H/A5H/A7H/A9H")),
+            Err(ApiError::Unauthorized(_))
+        ));
+
+        store
+            .place(id, Some(&first), &placements("A1H/A3H/A5H/A7H/A9H"))
+            .unwrap();
+        let placed = store
+            .place(id, Some(&second), &placements("A1V/C1V/E1V/G1V/I1V"))
+            .unwrap();
+        assert_eq!(placed.phase, Phase::InProgress);
+        assert_eq!(placed.token, None);
+
+        let (shooter, waiting) = match placed.your_turn {
+            true => (second, first),
+            false => (first, second),
+        };
+        assert!(matches!(
+            store.shoot(id, Some(&waiting), &shot("A1")),
+            Err(ApiError::Duel(DuelError::NotYourTurn))
+        ));
+        let result = store.shoot(id, Some(&shooter), &shot("A1")).unwrap();
+        assert!(matches!(
+            result.shots.as_slice(),
+            [BotEvent::Shot {
+                shooter: Side::Human,
+                result: ShotResult::Hit,
+                ..
+            }]
+        ));
+        assert!(!result.state.your_turn);
+
+        let opponent = store.state(id, Some(&waiting)).unwrap();
+        assert!(opponent.your_turn);
+        assert_eq!(opponent.opponent_shots.hits.len(), 1);
+        assert_eq!(opponent.turn, 1);
+    }
+
+    #[rstest]
+    fn test_state_hash() {
+        let mut store = store();
+        let id = store.create(NewGame::default()).unwrap().id;
+        assert_eq!(store.state_hash(id, None).unwrap(), StateHash::default());
+
+        let placed = store
+            .place(id, None, &placements("A1V/C1V/E1V/G1V/I1V"))
+            .unwrap();
+        let hash = store.state_hash(id, None).unwrap().hash;
+        assert!(hash.as_ref().is_some_and(|hash| hash.len() == 16));
+        assert_eq!(placed.hash, hash);
+
+        let result = store.shoot(id, None, &shot("B7")).unwrap();
+        assert_ne!(result.state.hash, hash);
+        assert_eq!(store.state_hash(id, None).unwrap().hash, result.state.hash);
+        assert!(matches!(
+            store.state_hash(id + 1, None),
+            Err(ApiError::NotFound(_))
+        ));
+    }
+
+    #[rstest]
+    fn test_metrics() {
+        let mut store = store();
+        let created = store
+            .create(NewGame {
+                opponent: Opponent::Human,
+                ..NewGame::default()
+            })
+            .unwrap();
+        let id = created.id;
+        let first = created.token.unwrap();
+        let second = store
+            .join(id, JoinRequest::default())
+            .unwrap()
+            .token
+            .unwrap();
+        store
+            .place(id, Some(&first), &placements("A1H/A3H/A5H/A7H/A9H"))
+            .unwrap();
+        let placed = store
+            .place(id, Some(&second), &placements("A1V/C1V/E1V/G1V/I1V"))
+            .unwrap();
+        let opened = !placed.your_turn;
+
+        // The player who opened the duel sinks the whole fleet, while the other one misses.
+        let cells = |columns: [&str; 5], rows: u8| -> Vec<String> {
+            (1..=rows)
+                .flat_map(|row| columns.map(|column| format!("{column}{row}")))
+                .collect()
+        };
+        let mut targets = [
+            cells(["A", "C", "E", "G", "I"], 5).into_iter(),
+            cells(["F", "G", "H", "I", "J"], 10).into_iter(),
+        ];
+        assert!(
+            store
+                .metrics()
+                .prometheus()
+                .contains("naval_games_total 0\n")
+        );
+        while store.state(id, Some(&first)).unwrap().phase == Phase::InProgress {
+            let (token, cells) = match store.state(id, Some(&first)).unwrap().your_turn {
+                true => (&first, &mut targets[0]),
+                false => (&second, &mut targets[1]),
+            };
+            let cell = cells.next().unwrap();
+            store.shoot(id, Some(token), &shot(&cell)).unwrap();
+        }
+
+        let metrics = store.metrics();
+        assert_eq!(metrics.games(), 1);
+        assert_eq!(metrics.mean_decision(), None);
+        let (won, lost) = match opened {
+            true => ("first", "second"),
+            false => ("second", "first"),
+        };
+        assert_eq!(metrics.win_rate(won), Some(1.0));
+        assert_eq!(metrics.win_rate(lost), Some(0.0));
+    }
+
+    #[rstest]
+    fn test_open_games() {
+        let mut store = store();
+        let salvo = store
+            .create(NewGame {
+                name: Some("Ishmael".to_string()),
+                title: Some("Ahoy".to_string()),
+                opponent: Opponent::Human,
+                variant: Some(Variant::Salvo),
+                ..NewGame::default()
+            })
+            .unwrap();
+        let classic = store
+            .create(NewGame {
+                name: Some("Starbuck".to_string()),
+                opponent: Opponent::Human,
+                ..NewGame::default()
+            })
+            .unwrap();
+        store.create(NewGame::default()).unwrap();
+
+        let games = store.open_games();
+        assert_eq!(games.len(), 2);
+        assert_eq!(games[0].id, salvo.id);
+        assert_eq!(games[0].title, "Ahoy");
+        assert_eq!(games[0].host, "Ishmael");
+        assert_eq!(games[0].variant, Variant::Salvo);
+        assert_eq!(games[1].title, "Starbuck's game");
+        assert_eq!(games[1].variant, Variant::Classic);
+
+        let joined = store.join(salvo.id, JoinRequest::default()).unwrap();
+        assert_eq!(joined.opponent_name.as_deref(), Some("Ishmael"));
+        assert_eq!(
+            store
+                .open_games()
+                .iter()
+                .map(|game| game.id)
+                .collect::<Vec<_>>(),
+            vec![classic.id]
+        );
+
+        let unsupported = store
+            .create(NewGame {
+                opponent: Opponent::Human,
+                variant: Some(Variant::Fog),
+                ..NewGame::default()
+            })
+            .unwrap_err();
+        assert_eq!(unsupported.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
+    #[rstest]
+    fn test_pair() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let stores = Stores::spawn(Config::default(), &ServerOptions::default());
+        let request = |name: &str, variant| MatchRequest {
+            name: Some(name.to_string()),
+            variant: Some(variant),
+        };
+
+        runtime.block_on(async {
+            let (opened, first) = stores
+                .pair(request("Ishmael", Variant::Streak))
+                .await
+                .unwrap();
+            assert!(opened);
+            assert_eq!(stores.open_games().await.len(), 1);
+
+            let (opened, other) = stores
+                .pair(request("Starbuck", Variant::Classic))
+                .await
+                .unwrap();
+            assert!(opened);
+            assert_ne!(other.id, first.id);
+
+            let (opened, second) = stores
+                .pair(request("Queequeg", Variant::Streak))
+                .await
+                .unwrap();
+            assert!(!opened);
+            assert_eq!(second.id, first.id);
+            assert_eq!(second.opponent_name.as_deref(), Some("Ishmael"));
+            assert!(second.token.is_some());
+            assert_eq!(stores.open_games().await.len(), 1);
+        });
+    }
+
+    #[rstest]
+    fn test_chat() {
+        let mut store = store();
+        let created = store
+            .create(NewGame {
+                name: Some("Ishmael".to_string()),
+                opponent: Opponent::Human,
+                ..NewGame::default()
+            })
+            .unwrap();
+        let (id, first) = (created.id, created.token.unwrap());
+        let second = store
+            .join(id, JoinRequest::default())
+            .unwrap()
+            .token
+            .unwrap();
+        let say = |text: &str| ChatRequest {
+            text: text.to_string(),
+        };
+
+        let sent = store.say(id, Some(&first), &say(" Ahoy! ")).unwrap();
+        store.say(id, Some(&second), &say("Ahoy")).unwrap();
+
+        assert_eq!(
+            sent,
+            ChatMessage {
+                seq: 1,
+                sender: "Ishmael".to_string(),
+                yours: true,
+                text: "Ahoy!".to_string(),
+            }
+        );
+        let chat = store.chat(id, Some(&second), 0).unwrap();
+        assert_eq!(chat.len(), 2);
+        assert!(!chat[0].yours);
+        assert_eq!(chat[1].sender, "Player 2");
+        assert_eq!(store.chat(id, Some(&first), 1).unwrap()[0].seq, 2);
+        assert!(store.chat(id, Some(&first), 2).unwrap().is_empty());
+
+        let long = "a".repeat(ChatRequest::MAX_LENGTH + 1);
+        for text in ["  ", long.as_str()] {
+            let error = store.say(id, Some(&first), &say(text)).unwrap_err();
+            assert_eq!(error.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        }
+        assert!(matches!(
+            store.chat(id, None, 0),
+            Err(ApiError::Unauthorized(_))
+        ));
+        let computer = store.create(NewGame::default()).unwrap().id;
+        assert!(matches!(
+            store.say(computer, None, &say("Ahoy")),
+            Err(ApiError::NotADuel(_))
+        ));
+    }
+
+    #[rstest]
+    fn test_expire() {
+        let mut store = SessionStore::new(Config::default(), Duration::from_secs(60));
+        let id = store.create(NewGame::default()).unwrap().id;
+
+        assert_eq!(store.expire(Instant::now()), 0);
+        assert!(store.state(id, None).is_ok());
+        assert_eq!(store.expire(Instant::now() + Duration::from_secs(61)), 1);
+        assert!(matches!(store.state(id, None), Err(ApiError::NotFound(_))));
+    }
+
+    #[rstest]
+    fn test_hello() {
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let stores = Stores::spawn(Config::default(), &ServerOptions::default());
+        let older = Hello {
+            version: 0,
+            ..Hello::new(10)
+        };
+
+        let Json(welcome) = runtime
+            .block_on(hello(State(stores.clone()), Json(Hello::new(10))))
+            .unwrap();
+        let refused = runtime
+            .block_on(hello(State(stores), Json(older)))
+            .unwrap_err();
+
+        assert_eq!(welcome.variants, Duel::VARIANTS.to_vec());
+        assert_eq!(welcome.board_size, 10);
+        assert_eq!(refused.status(), StatusCode::CONFLICT);
+        assert!(refused.to_string().contains("version 0"));
+    }
+
+    #[rstest]
+    fn test_shards() {
+        let mut first = SessionStore::shard(Config::default(), &ServerOptions::default(), 0, 3);
+        let mut second = SessionStore::shard(Config::default(), &ServerOptions::default(), 1, 3);
+
+        let ids: Vec<u64> = (0..2)
+            .flat_map(|_| {
+                [
+                    first.create(NewGame::default()).unwrap().id,
+                    second.create(NewGame::default()).unwrap().id,
+                ]
+            })
+            .collect();
+
+        assert_eq!(ids, vec![1, 2, 4, 5]);
+    }
+
+    #[rstest]
+    fn test_errors() {
+        let mut store = store();
+        let id = store.create(NewGame::default()).unwrap().id;
+
+        let unknown = store.state(id + 1, None).unwrap_err();
+        let fleet = store.place(id, None, &placements("A1H")).unwrap_err();
+        store
+            .place(id, None, &placements("A1H/A3H/A5H/A7H/A9H"))
+            .unwrap();
+        let cell = store.shoot(id, None, &shot("K11")).unwrap_err();
+        let join = store.join(id, JoinRequest::default()).unwrap_err();
+
+        assert_eq!(unknown.status(), StatusCode::NOT_FOUND);
+        assert_eq!(unknown.to_string(), format!("game {} not found", id + 1));
+        assert_eq!(fleet.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(cell.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(join.status(), StatusCode::CONFLICT);
+    }
+
+    #[rstest]
+    fn test_settings() {
+        assert!(serde_json::from_str::<NewGame>(r#"{"difficulty":"hard"}"#).is_ok());
+        assert!(serde_json::from_str::<NewGame>(r#"{"opponent":"human"}"#).is_ok());
+        assert!(serde_json::from_str::<NewGame>(r#"{"difficulty":"impossible"}"#).is_err());
+        assert!(serde_json::from_str::<NewGame>(r#"{"level":"hard"}"#).is_err());
+    }
+
+    // Starts a duel in the store, and returns its id with the tokens of the player who fires
+    // first and of the other one.
+    fn started_duel(store: &mut SessionStore) -> (u64, String, String) {
+        let created = store
+            .create(NewGame {
+                name: Some("Ishmael".to_string()),
+                opponent: Opponent::Human,
+                variant: Some(Variant::Streak),
+                ..NewGame::default()
+            })
+            .unwrap();
+        let first = created.token.unwrap();
+        let second = store
+            .join(created.id, JoinRequest::default())
+            .unwrap()
+            .token
+            .unwrap();
+        store
+            .place(created.id, Some(&first), &placements("A1H/A3H/A5H/A7H/A9H"))
+            .unwrap();
+        let placed = store
+            .place(
+                created.id,
+                Some(&second),
+                &placements("A1V/C1V/E1V/G1V/I1V"),
+            )
+            .unwrap();
+
+        match placed.your_turn {
+            true => (created.id, second, first),
+            false => (created.id, first, second),
+        }
+    }
+
+    #[rstest]
+    fn test_rejoin() {
+        let mut store = store();
+        let (id, shooter, _) = started_duel(&mut store);
+        store.shoot(id, Some(&shooter), &shot("J10")).unwrap();
+
+        let rejoined = store.rejoin(&shooter).unwrap();
+
+        assert_eq!(rejoined.id, id);
+        assert_eq!(rejoined.token.as_deref(), Some(shooter.as_str()));
+        assert_eq!(rejoined.shots.misses, vec![Cell::from_str("J10").unwrap()]);
+        assert!(!rejoined.your_turn);
+        assert_eq!(rejoined.clocks, Clocks::default());
+        assert!(matches!(
+            store.rejoin("c0ffee"),
+            Err(ApiError::UnknownToken)
+        ));
+        assert_eq!(ApiError::UnknownToken.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[rstest]
+    fn test_save_and_resume() {
+        let dir = std::env::temp_dir().join(format!("naval-test-saves-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let options = ServerOptions {
+            idle: Duration::from_secs(60),
+            save_dir: Some(dir.clone()),
+            ..ServerOptions::default()
+        };
+        let mut store = SessionStore::shard(Config::default(), &options, 0, 1);
+        let (id, shooter, waiting) = started_duel(&mut store);
+        store.shoot(id, Some(&shooter), &shot("A1")).unwrap();
+        let before = store.state(id, Some(&waiting)).unwrap();
+        store
+            .say(
+                id,
+                Some(&waiting),
+                &ChatRequest {
+                    text: "Ahoy".to_string(),
+                },
+            )
+            .unwrap();
+        store.create(NewGame::default()).unwrap();
+
+        let mut resumed = SessionStore::shard(Config::default(), &options, 0, 1);
+        let after = resumed.state(id, Some(&waiting)).unwrap();
+        let chat = resumed.chat(id, Some(&waiting), 0).unwrap();
+        let next = resumed.create(NewGame::default()).unwrap().id;
+        assert_eq!(resumed.expire(Instant::now() + Duration::from_secs(61)), 2);
+        let left = fs::read_dir(&dir).unwrap().count();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(after, before);
+        assert_eq!(chat.len(), 1);
+        assert!(chat[0].yours);
+        assert!(resumed.rejoin(&shooter).is_err());
+        assert_eq!(next, id + 1);
+        assert_eq!(left, 0);
+    }
+
+    #[cfg(feature = "tls")]
+    #[rstest]
+    fn test_tls() {
+        use crate::net::Client;
+
+        let dir =
+            std::env::temp_dir().join(format!("naval-test-server-tls-{}", std::process::id()));
+        let (identity, other) = (Identity::new(&dir), Identity::new(&dir.join("other")));
+        identity.generate(&["localhost".to_string()]).unwrap();
+        other.generate(&["localhost".to_string()]).unwrap();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let address = runtime.block_on(async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let address = listener.local_addr().unwrap().to_string();
+            let listener = TlsListener::new(listener, identity.server_config().unwrap()).unwrap();
+            let routes = router(Config::default(), &ServerOptions::default());
+            tokio::spawn(async move { axum::serve(listener, routes).await });
+            address
+        });
+
+        let trusted = Client::new(&address)
+            .with_certificate(&identity.certificate_path())
+            .games();
+        let untrusted = Client::new(&address)
+            .with_certificate(&other.certificate_path())
+            .games();
+        let plaintext = Client::new(&address).games();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(trusted.unwrap().is_empty());
+        assert!(untrusted.is_err());
+        assert!(plaintext.is_err());
+    }
+}
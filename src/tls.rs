@@ -0,0 +1,212 @@
+//! This module contains the TLS encryption of the network games, built with the `tls` feature.
+//!
+//! The server proves who it is with the certificate of its [Identity], which is generated and
+//! self-signed on its first run. As no authority vouches for such a certificate, the clients
+//! pin it: a [client configuration](client_config) trusts only the certificate handed to its
+//! player, whatever the name the server is reached by.
+//!
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::crypto::{CryptoProvider, ring};
+use rustls::pki_types::pem::{self, PemObject};
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, ServerName, UnixTime};
+use rustls::{ClientConfig, DigitallySignedStruct, ServerConfig, SignatureScheme};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Represents an error of the TLS encryption.
+#[derive(Debug, Error)]
+pub enum TlsError {
+    /// The certificate or the key can't be read or written.
+    #[error(transparent)]
+    Io(#[from] io::Error),
+
+    /// The certificate or the key is not a valid PEM file.
+    #[error("invalid PEM file: {0}")]
+    Pem(#[from] pem::Error),
+
+    /// The self-signed certificate can't be generated.
+    #[error("can't generate the certificate: {0}")]
+    Generate(#[from] rcgen::Error),
+
+    /// The certificate or the key can't be used to encrypt the connections.
+    #[error(transparent)]
+    Rustls(#[from] rustls::Error),
+}
+
+/// The certificate of a server and its private key, kept as the `server.pem` and `server.key`
+/// files of a folder.
+#[derive(Debug, Clone)]
+pub struct Identity {
+    dir: PathBuf,
+}
+
+impl Identity {
+    const CERTIFICATE_NAME: &'static str = "server.pem";
+    const KEY_NAME: &'static str = "server.key";
+
+    /// Creates the identity kept in the given folder.
+    pub fn new(dir: &Path) -> Self {
+        Self {
+            dir: dir.to_path_buf(),
+        }
+    }
+
+    /// Returns the path of the certificate, which the players give to their clients.
+    pub fn certificate_path(&self) -> PathBuf {
+        self.dir.join(Self::CERTIFICATE_NAME)
+    }
+
+    fn key_path(&self) -> PathBuf {
+        self.dir.join(Self::KEY_NAME)
+    }
+
+    /// Generates a self-signed certificate valid for the given names, like `localhost`, unless
+    /// the folder already has one. Returns `true` when the certificate has been generated.
+    ///
+    /// The key is only readable by its owner, on Unix.
+    pub fn generate(&self, names: &[String]) -> Result<bool, TlsError> {
+        if self.certificate_path().exists() && self.key_path().exists() {
+            return Ok(false);
+        }
+
+        let generated = rcgen::generate_simple_self_signed(names)?;
+        fs::create_dir_all(&self.dir)?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        options
+            .open(self.key_path())?
+            .write_all(generated.signing_key.serialize_pem().as_bytes())?;
+        fs::write(self.certificate_path(), generated.cert.pem())?;
+
+        Ok(true)
+    }
+
+    /// Returns the configuration of a server which proves its identity with the certificate.
+    pub fn server_config(&self) -> Result<Arc<ServerConfig>, TlsError> {
+        let certificates = CertificateDer::pem_file_iter(self.certificate_path())?
+            .collect::<Result<Vec<_>, _>>()?;
+        let key = PrivateKeyDer::from_pem_file(self.key_path())?;
+        let config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+            .with_safe_default_protocol_versions()?
+            .with_no_client_auth()
+            .with_single_cert(certificates, key)?;
+
+        Ok(Arc::new(config))
+    }
+}
+
+/// Returns the configuration of a client which trusts only the certificate of the PEM file.
+pub fn client_config(certificate: &Path) -> Result<Arc<ClientConfig>, TlsError> {
+    let provider = Arc::new(ring::default_provider());
+    let verifier = PinnedCertificate {
+        certificate: CertificateDer::from_pem_file(certificate)?,
+        provider: provider.clone(),
+    };
+    let config = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions()?
+        .dangerous()
+        .with_custom_certificate_verifier(Arc::new(verifier))
+        .with_no_client_auth();
+
+    Ok(Arc::new(config))
+}
+
+// Accepts the server which shows the pinned certificate, and proves to own it.
+#[derive(Debug)]
+struct PinnedCertificate {
+    certificate: CertificateDer<'static>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for PinnedCertificate {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        _intermediates: &[CertificateDer<'_>],
+        _server_name: &ServerName<'_>,
+        _ocsp_response: &[u8],
+        _now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        match end_entity.as_ref() == self.certificate.as_ref() {
+            true => Ok(ServerCertVerified::assertion()),
+            false => Err(rustls::Error::InvalidCertificate(
+                rustls::CertificateError::UnknownIssuer,
+            )),
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        rustls::crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+    use std::env;
+
+    #[rstest]
+    fn test_generate_once() {
+        let dir = env::temp_dir().join(format!("naval-test-tls-{}", std::process::id()));
+        let identity = Identity::new(&dir);
+
+        let generated = identity.generate(&["localhost".to_string()]).unwrap();
+        let certificate = fs::read_to_string(identity.certificate_path()).unwrap();
+        let regenerated = identity.generate(&["localhost".to_string()]).unwrap();
+        let unchanged = fs::read_to_string(identity.certificate_path()).unwrap() == certificate;
+        let configs = (
+            identity.server_config().is_ok(),
+            client_config(&identity.certificate_path()).is_ok(),
+        );
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert!(generated);
+        assert!(!regenerated);
+        assert!(unchanged);
+        assert_eq!(configs, (true, true));
+    }
+
+    #[rstest]
+    fn test_missing_certificate() {
+        let dir = env::temp_dir().join("naval-test-tls-missing");
+
+        assert!(Identity::new(&dir).server_config().is_err());
+        assert!(client_config(&dir.join("server.pem")).is_err());
+    }
+}
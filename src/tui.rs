@@ -4,13 +4,35 @@
 //! It provides a terminal user interface for playing a naval battle game against a computer opponent.
 //! The game consists of two main phases: setup and battle. During the setup phase, the human player deploys their fleet on a grid.
 //! During the battle phase, the human player and the computer take turns attacking each other's fleets until one player wins.
+//! Games are played in a series, which is over when a player has won most of them.
 //!
-use crate::engine::game::Game;
-use crate::tui::{state::NavalBattleState, widgets::workbench::Workbench};
+use crate::config::{
+    CampaignProgress, Config, DailyResult, DailyResults, Leaderboard, PlacementHabits,
+};
+use crate::engine::{
+    action::PowerUps,
+    campaign::Campaign,
+    daily::DailyChallenge,
+    fleet::FleetError,
+    game::{GameError, GameMode, GamePhase, Side},
+    replay::{Replay, ReplayRecorder},
+    series::Series,
+    strategy::StrategyKind,
+};
+use crate::tui::{
+    sound::Bell,
+    state::NavalBattleState,
+    widgets::{toast::Notifications, workbench::Workbench},
+};
 use crossterm::event::{self, Event, KeyCode, KeyEvent};
 use ratatui::{DefaultTerminal, Frame};
+use std::cell::RefCell;
 use std::io;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use tracing::{info, info_span, trace};
 
+mod sound;
 pub mod state;
 mod widgets;
 
@@ -28,48 +50,132 @@ mod widgets;
 /// For each player, the application asks for a shot to the current player, it evaluates if the opponent fleet is sunk or not,
 /// and switch turns until one of the players has lost.
 pub struct NavalBattleTui {
-    game: Game,
+    config: Config,
+    series: Series,
+    campaign: Option<(Campaign, usize)>,
     state: NavalBattleState,
+    notifications: Notifications,
     exit: bool,
     enter_pressed: bool,
+    recorded: bool,
+    replay: Option<Rc<RefCell<Replay>>>,
+    debug_ai: bool,
 }
 
 impl NavalBattleTui {
+    // How often the application is updated and redrawn without any event, so that the clocks
+    // are kept up to date.
+    const TICK: Duration = Duration::from_millis(250);
+    // How often the application is updated and redrawn while an animation is playing.
+    const FRAME: Duration = Duration::from_millis(40);
+
     /// Creates a new Naval Battle TUI application
     ///
     /// As the application starts, a new computer player is created with a random fleet deployment.
     /// The human player is not created yet, as it will be created during the setup phase.
     /// The setup state is the default state when the application starts.
-    pub fn new() -> Self {
+    ///
+    /// The given configuration is used for every match and can be changed from the settings screen.
+    pub fn new(config: Config) -> Self {
         Self {
-            game: Game::new(),
-            state: NavalBattleState::default(),
+            state: NavalBattleState::home(&config),
+            series: new_series(&config),
+            campaign: None,
+            notifications: Notifications::default(),
+            config,
             exit: false,
             enter_pressed: false,
+            recorded: false,
+            replay: None,
+            debug_ai: false,
         }
     }
 
+    /// Chooses whether the battle shows the probabilities of the computer strategy over the
+    /// grid of the player, to see why the computer shoots where it does.
+    pub fn set_debug_ai(&mut self, debug_ai: bool) {
+        self.debug_ai = debug_ai;
+    }
+
+    /// Starts with the tutorial, which guides a new player through their first shots, instead
+    /// of the first state of the configured mode.
+    pub fn start_tutorial(&mut self) {
+        self.state = NavalBattleState::tutorial(&self.config);
+    }
+
+    /// Starts with a match between the given computer strategies, to watch, instead of the
+    /// first state of the configured mode. The first strategy plays on the left.
+    ///
+    /// An error is returned if the match can't be set up with the configured variant.
+    pub fn start_spectating(&mut self, a: StrategyKind, b: StrategyKind) -> Result<(), GameError> {
+        self.state = NavalBattleState::spectator(&self.config, a, b)?;
+        Ok(())
+    }
+
+    /// Starts with the given replay, to watch a saved game again, instead of the first state of
+    /// the configured mode.
+    ///
+    /// An error is returned if a fleet of the replay is not valid.
+    pub fn start_replay(&mut self, replay: &Replay) -> Result<(), FleetError> {
+        self.state = NavalBattleState::replay(&self.config, replay)?;
+        Ok(())
+    }
+
     /// Runs the application's main loop until the user quits
     ///
     /// It renders the current application state, then it is waiting for events according to the
     /// actual application state.
+    ///
+    /// The wait never blocks: the state is updated on every event and on every tick, at a fixed
+    /// interval, so that the clocks count down and the animations play while the user thinks.
+    /// The events don't postpone the next tick.
     pub fn run(&mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        let _span = info_span!("tui").entered();
+        let mut next_tick = Instant::now();
         while !self.exit {
             terminal.draw(|frame| self.draw(frame))?;
-            self.handle_events()?;
-            self.state.update(&mut self.game);
+            self.handle_events(next_tick)?;
+            let now = Instant::now();
+            if now >= next_tick {
+                next_tick = now + self.tick_rate();
+                self.notifications.tick();
+            }
+            self.state.update(&mut self.series, &mut self.notifications);
             self.check_for_state_change()?;
         }
         Ok(())
     }
 
+    // Returns how often the application ticks in the current state.
+    fn tick_rate(&self) -> Duration {
+        match self.state.is_animating() {
+            true => Self::FRAME,
+            false => Self::TICK,
+        }
+    }
+
     fn draw(&self, frame: &mut Frame) {
-        let workbench = Workbench(&self.state);
+        let workbench = Workbench(
+            &self.state,
+            &self.config.keybindings,
+            &self.series,
+            &self.notifications,
+        );
         frame.render_widget(&workbench, frame.area());
     }
 
-    fn handle_events(&mut self) -> io::Result<()> {
+    // Waits for an event until the next tick, at most, and handles it.
+    fn handle_events(&mut self, next_tick: Instant) -> io::Result<()> {
+        // An animation which has just started ticks sooner than the scheduled tick.
+        let timeout = next_tick
+            .saturating_duration_since(Instant::now())
+            .min(self.tick_rate());
+        if !event::poll(timeout)? {
+            return Ok(());
+        }
+
         let event = event::read()?;
+        trace!(?event, "terminal event");
         if !self.handle_app_events(&event) {
             self.state.handle_events(event);
         }
@@ -78,42 +184,199 @@ impl NavalBattleTui {
     }
 
     fn check_for_state_change(&mut self) -> io::Result<()> {
-        // If the application is in setup mode but the human player has been created, switch to battle mode.
+        // If the application is in setup mode but the game has started, switch to battle mode.
         // If the application is in battle mode, wait for user input.
         if let NavalBattleState::Setup { .. } = self.state
-            && self.game.is_ready()
+            && self.series.game().phase() == GamePhase::InProgress
+        {
+            info!(mode = %self.config.mode, variant = %self.config.variant, "battle started");
+            self.state = NavalBattleState::battle(&self.series, &self.config);
+            if self.config.sound {
+                self.series.game_mut().subscribe(Box::new(Bell));
+            }
+            self.replay =
+                Replay::new(self.series.game()).map(|replay| Rc::new(RefCell::new(replay)));
+            if let Some(replay) = &self.replay {
+                let recorder = ReplayRecorder::new(replay.clone());
+                self.series.game_mut().subscribe(Box::new(recorder));
+            }
+            if let NavalBattleState::Battle(model) = &mut self.state {
+                model.set_debug_ai(self.debug_ai);
+            }
+        } else if let NavalBattleState::Settings(settings) = &mut self.state
+            && settings.is_closed()
+        {
+            // The settings are applied to the next setup, but only if they have been saved.
+            // A new series is started when the settings of the games have changed.
+            if let Some(config) = settings.saved_config().cloned() {
+                if let Err(e) = config.save() {
+                    settings.set_error(e.to_string());
+                    return Ok(());
+                }
+                if config.player_name != self.config.player_name
+                    || config.mode != self.config.mode
+                    || config.difficulty != self.config.difficulty
+                    || config.solver_budget != self.config.solver_budget
+                    || config.adaptation != self.config.adaptation
+                    || config.time_control != self.config.time_control
+                    || config.best_of != self.config.best_of
+                    || config.power_ups != self.config.power_ups
+                    || config.variant != self.config.variant
+                    || config.map != self.config.map
+                    || config.script != self.config.script
+                    || config.opponent_command != self.config.opponent_command
+                    || config.handicap != self.config.handicap
+                {
+                    self.series = new_series(&config);
+                }
+                self.config = config;
+            }
+            self.state = NavalBattleState::home(&self.config);
+        } else if let NavalBattleState::Campaign(campaign) = &self.state
+            && let Some((campaign, battle)) = campaign.chosen()
+        {
+            self.series = campaign_series(&self.config, campaign, battle);
+            self.campaign = Some((campaign, battle));
+            self.state = NavalBattleState::setup(&self.config);
+        } else if let NavalBattleState::Leaderboard(leaderboard) = &self.state
+            && leaderboard.is_closed()
         {
-            self.state = NavalBattleState::battle(&self.game);
+            self.state = NavalBattleState::home(&self.config);
+        } else if let NavalBattleState::Tutorial(tutorial) = &self.state
+            && tutorial.is_finished()
+        {
+            self.state = NavalBattleState::setup(&self.config);
+        } else if let NavalBattleState::Spectator(spectator) = &self.state
+            && spectator.is_closed()
+        {
+            self.state = NavalBattleState::home(&self.config);
+        } else if let NavalBattleState::Replay(replay) = &self.state
+            && replay.is_closed()
+        {
+            self.state = NavalBattleState::home(&self.config);
+        } else if let NavalBattleState::Network(network) = &self.state
+            && network.is_closed()
+        {
+            self.state = NavalBattleState::home(&self.config);
+        } else if self.match_is_over() && !self.recorded {
+            self.record_result();
         } else if let NavalBattleState::Battle { .. } = self.state
             && self.match_is_over()
             && self.enter_pressed
         {
-            self.game = Game::new();
-            self.state = NavalBattleState::setup();
+            if self.series.is_over() {
+                self.series = new_series(&self.config);
+                self.campaign = None;
+            }
+            self.state = NavalBattleState::home(&self.config);
             self.enter_pressed = false;
+            self.recorded = false;
         }
 
         Ok(())
     }
 
+    // Adds the result of the finished game to the leaderboard, to the daily results in the daily
+    // mode and to the campaign progress in the campaign mode. They are not essential to play, so
+    // the result is lost if it can't be saved. A drawn game counts as played but not won. The
+    // fleet of the player is always added to their placement habits, and the game is saved as the
+    // last replay.
+    fn record_result(&mut self) {
+        self.recorded = true;
+        if let Some(replay) = self.replay.take() {
+            let _ = replay.borrow().save();
+        }
+        if let Some(human) = self.series.game().human()
+            && let Ok(mut habits) = PlacementHabits::load()
+        {
+            habits.record(human.name(), human.fleet());
+            let _ = habits.save();
+            self.series.set_habits(habits.get(human.name()));
+        }
+
+        let game = self.series.game();
+        let Some(human) = game.human() else {
+            return;
+        };
+        let won = match game.phase() {
+            GamePhase::Finished { winner } => winner == Side::Human,
+            GamePhase::Drawn => false,
+            GamePhase::Setup | GamePhase::InProgress => return,
+        };
+
+        if let Ok(mut leaderboard) = Leaderboard::load() {
+            leaderboard.record(human.name(), won, human.shots());
+            let _ = leaderboard.save();
+        }
+
+        if let (Some(challenge), Some(par)) = (self.series.daily_challenge(), self.series.par())
+            && let Ok(mut results) = DailyResults::load()
+        {
+            let result = DailyResult {
+                won,
+                shots: human.shots(),
+                par,
+            };
+            if results.record(&challenge.date(), result) {
+                let _ = results.save();
+            }
+        }
+
+        if let Some((campaign, battle)) = self.campaign
+            && won
+            && let Ok(mut progress) = CampaignProgress::load()
+        {
+            progress.record_win(campaign.id, battle);
+            let _ = progress.save();
+        }
+    }
+
     fn match_is_over(&self) -> bool {
         if let NavalBattleState::Battle { .. } = self.state {
-            self.game.is_over()
+            self.series.game().is_over()
         } else {
             false
         }
     }
 
     // Handles application-level events, such as quitting the application. If the event is handled, returns true.
+    //
+    // Application keys are disabled while the user is typing, so that any character can be typed.
     fn handle_app_events(&mut self, event: &Event) -> bool {
+        let bindings = &self.config.keybindings;
+        let typing = self.state.is_typing();
         match event {
             Event::Key(KeyEvent {
-                code: KeyCode::Char('q') | KeyCode::Char('Q'),
+                code: KeyCode::Char(c),
                 ..
-            }) => {
+            }) if !typing && c.eq_ignore_ascii_case(&bindings.quit) => {
                 self.exit();
                 true
             }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(c),
+                ..
+            }) if !typing && self.state.is_home() && c.eq_ignore_ascii_case(&bindings.settings) => {
+                self.state = NavalBattleState::settings(&self.config);
+                true
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(c),
+                ..
+            }) if !typing
+                && self.state.is_home()
+                && c.eq_ignore_ascii_case(&bindings.leaderboard) =>
+            {
+                self.state = NavalBattleState::leaderboard();
+                true
+            }
+            Event::Key(KeyEvent {
+                code: KeyCode::Char(c),
+                ..
+            }) if !typing && self.state.is_home() && c.eq_ignore_ascii_case(&bindings.network) => {
+                self.state = NavalBattleState::network(&self.config);
+                true
+            }
             Event::Key(KeyEvent {
                 code: KeyCode::Enter,
                 ..
@@ -129,3 +392,63 @@ impl NavalBattleTui {
         self.exit = true;
     }
 }
+
+// Creates a new series of games with the configured player name, difficulty and time control.
+// In the daily mode, the series is the single game of today's challenge.
+fn new_series(config: &Config) -> Series {
+    let mut series = match config.mode {
+        GameMode::Daily => Series::daily(
+            DailyChallenge::today(),
+            &config.player_name,
+            config.difficulty,
+        ),
+        _ => Series::new(config.best_of, &config.player_name, config.difficulty),
+    };
+    apply_game_settings(&mut series, config);
+
+    series
+}
+
+// Creates the series of the single game of a campaign battle, with the configured player name,
+// time control, power-ups and variant.
+fn campaign_series(config: &Config, campaign: Campaign, battle: usize) -> Series {
+    let battle = campaign.battles[battle];
+    let mut series = Series::new(1, &config.player_name, battle.difficulty);
+    series.set_handicap(battle.handicap);
+    apply_game_settings(&mut series, config);
+
+    series
+}
+
+// Applies the configured time control, power-ups, variant, map, script, opponent command,
+// handicap, solver budget and adaptation to every game of the series, with the placement habits
+// of the player.
+//
+// A map or a script which can't be loaded is left out: the setup screen tells the player why.
+fn apply_game_settings(series: &mut Series, config: &Config) {
+    series.set_variant(config.variant);
+    series.set_map(config.map().ok().flatten());
+    #[cfg(feature = "scripting")]
+    series.set_script(config.script().ok().flatten());
+    series.set_opponent_command(config.opponent_command.clone());
+    series.set_handicap_option(config.handicap);
+    series.set_solver_budget(config.solver_budget as usize);
+    series.set_adaptation(config.adaptation);
+    if let Ok(habits) = PlacementHabits::load() {
+        series.set_habits(habits.get(&config.player_name));
+    }
+    series.set_time_control(
+        config
+            .time_control
+            .map(|minutes| Duration::from_secs(60 * u64::from(minutes))),
+    );
+    if config.power_ups {
+        series.set_power_ups(PowerUps::STANDARD);
+    }
+}
+
+impl Default for NavalBattleTui {
+    fn default() -> Self {
+        Self::new(Config::default())
+    }
+}
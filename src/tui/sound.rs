@@ -0,0 +1,40 @@
+//! Sound feedback for the battles, played on the terminal bell.
+//!
+use crate::engine::fleet::ShipKind;
+use crate::engine::game::Side;
+use crate::engine::grid::Cell;
+use crate::engine::observer::GameObserver;
+use std::io::{self, Write};
+
+/// An observer which rings the terminal bell on the events of a game worth a sound.
+///
+/// The bell has a single tone, so the events are told apart by the number of rings: one for a
+/// hit, two for a sunk ship and three for the victory of the player. A miss is silent.
+#[derive(Debug, Default)]
+pub struct Bell;
+
+impl Bell {
+    fn ring(times: usize) {
+        let mut output = io::stdout();
+        let _ = output.write_all(&b"\x07".repeat(times));
+        let _ = output.flush();
+    }
+}
+
+impl GameObserver for Bell {
+    fn on_shot(&mut self, _shooter: Side, _cell: &Cell, hit: Option<&ShipKind>) {
+        if hit.is_some() {
+            Self::ring(1);
+        }
+    }
+
+    fn on_ship_sunk(&mut self, _owner: Side, _kind: &ShipKind) {
+        Self::ring(2);
+    }
+
+    fn on_game_over(&mut self, winner: Side) {
+        if winner == Side::Human {
+            Self::ring(3);
+        }
+    }
+}
@@ -15,8 +15,19 @@
 //! has its own model. Application sends requests to the actual state object, and this one dispatches
 //! the requests to the real model.
 //!
-use crate::engine::game::Game;
-use crate::tui::widgets::{battle::BattleStateModel, setup::SetupStateModel};
+use crate::config::Config;
+use crate::engine::fleet::FleetError;
+use crate::engine::game::GameError;
+use crate::engine::game::GameMode;
+use crate::engine::replay::Replay;
+use crate::engine::series::Series;
+use crate::engine::strategy::StrategyKind;
+use crate::tui::widgets::{
+    battle::BattleStateModel, campaign::CampaignStateModel, leaderboard::LeaderboardStateModel,
+    network::NetworkStateModel, puzzle::PuzzleStateModel, replay::ReplayStateModel,
+    settings::SettingsStateModel, setup::SetupStateModel, spectator::SpectatorStateModel,
+    toast::Notifications, tutorial::TutorialStateModel,
+};
 use crossterm::event::{Event, KeyEvent};
 use ratatui::prelude::{Buffer, Rect, Widget};
 use std::default::Default;
@@ -28,34 +39,157 @@ pub trait StateModel {
     /// Handles user input events according to the current state.
     fn handle_key_events(&mut self, key_event: KeyEvent);
 
-    /// Updates the game state according to the current interface state.
-    fn update(&mut self, game: &mut Game);
+    /// Updates the series, and its current game, according to the current interface state.
+    ///
+    /// The model can tell the user what has happened with the notifications.
+    fn update(&mut self, series: &mut Series, notifications: &mut Notifications);
 
     /// Builds the corresponding UI widget for the current state.
     fn widget(&self) -> impl Widget;
 }
 
-/// The application states: Setup or Battle.
+/// The application states: Setup, Battle, Settings, Leaderboard, Puzzle, Campaign, Tutorial,
+/// Spectator, Replay or Network.
 ///
 /// Setup state allows the user to deploy their fleet on the grid.
 /// Battle state allows the user to play against the computer.
+/// Settings state allows the user to change the configuration.
+/// Leaderboard state ranks the local players by their results.
+/// Puzzle state allows the user to sink a hidden fleet, in the puzzle [mode](GameMode).
+/// Campaign state allows the user to choose the campaign to play, in the campaign mode.
+/// Tutorial state guides a new player through the placement of a ship and their first shots.
+/// Spectator state shows a match between two computer strategies, played automatically.
+/// Replay state plays a saved game back, shot by shot.
+/// Network state lists the games of a server, and plays one against another player.
+///
+/// The states follow the [phase](crate::engine::game::GamePhase) of the game: Setup, Settings,
+/// Leaderboard, Puzzle, Campaign and Tutorial are shown while the game is being set up, Battle is shown while it is in progress
+/// and when it is finished. Spectator plays its own game, Replay shows a saved one and Network plays
+/// one on a server, outside of the series.
 pub enum NavalBattleState {
     Setup(SetupStateModel),
-    Battle(BattleStateModel),
+    Battle(Box<BattleStateModel>),
+    Settings(SettingsStateModel),
+    Leaderboard(LeaderboardStateModel),
+    Puzzle(PuzzleStateModel),
+    Campaign(CampaignStateModel),
+    Tutorial(TutorialStateModel),
+    Spectator(Box<SpectatorStateModel>),
+    Replay(ReplayStateModel),
+    Network(Box<NetworkStateModel>),
 }
 
 impl NavalBattleState {
     /// Creates a new setup state with an empty self.deploy_grid, ready to be populated by the user.
-    pub fn setup() -> Self {
-        Self::Setup(SetupStateModel::default())
+    pub fn setup(config: &Config) -> Self {
+        Self::Setup(SetupStateModel::new(config))
+    }
+
+    /// Creates the first state of the configured mode: the setup of a battle, a puzzle or the
+    /// choice of a campaign.
+    pub fn home(config: &Config) -> Self {
+        match config.mode {
+            GameMode::Battle | GameMode::Daily => Self::setup(config),
+            GameMode::Puzzle => Self::puzzle(config),
+            GameMode::Campaign => Self::campaign(),
+        }
     }
 
     /// Creates a new battle state ready to start the battle between the computer and the user.
-    pub fn battle(game: &Game) -> Self {
-        let mut model = BattleStateModel::default();
-        model.update_grid(game.computer().unwrap(), game.human().unwrap());
+    pub fn battle(series: &Series, config: &Config) -> Self {
+        let mut model = BattleStateModel::new(config);
+        model.update_series(series);
+
+        Self::Battle(Box::new(model))
+    }
+
+    /// Creates a new settings state to edit a copy of the given configuration.
+    pub fn settings(config: &Config) -> Self {
+        Self::Settings(SettingsStateModel::new(config))
+    }
+
+    /// Creates a new leaderboard state with the results saved so far.
+    pub fn leaderboard() -> Self {
+        Self::Leaderboard(LeaderboardStateModel::new())
+    }
+
+    /// Creates a new puzzle state with a random puzzle.
+    pub fn puzzle(config: &Config) -> Self {
+        Self::Puzzle(PuzzleStateModel::new(config))
+    }
+
+    /// Creates a new campaign state with the progress saved so far.
+    pub fn campaign() -> Self {
+        Self::Campaign(CampaignStateModel::new())
+    }
+
+    /// Creates a new tutorial state, at its first step.
+    pub fn tutorial(config: &Config) -> Self {
+        Self::Tutorial(TutorialStateModel::new(config))
+    }
+
+    /// Creates a new spectator state, with a match between the given strategies.
+    pub fn spectator(config: &Config, a: StrategyKind, b: StrategyKind) -> Result<Self, GameError> {
+        Ok(Self::Spectator(Box::new(SpectatorStateModel::new(
+            config, a, b,
+        )?)))
+    }
+
+    /// Creates a new replay state, at the start of the given replay.
+    pub fn replay(config: &Config, replay: &Replay) -> Result<Self, FleetError> {
+        Ok(Self::Replay(ReplayStateModel::new(config, replay)?))
+    }
+
+    /// Creates a new network state, on the lobby of the configured server.
+    pub fn network(config: &Config) -> Self {
+        Self::Network(Box::new(NetworkStateModel::new(config)))
+    }
 
-        Self::Battle(model)
+    /// Returns `true` when the state is the first one of its mode, where the settings and the
+    /// leaderboard can be opened.
+    pub fn is_home(&self) -> bool {
+        matches!(
+            self,
+            NavalBattleState::Setup(_)
+                | NavalBattleState::Puzzle(_)
+                | NavalBattleState::Campaign(_)
+        )
+    }
+
+    /// Returns `true` when the user is typing some text, so that keys are not commands.
+    pub fn is_typing(&self) -> bool {
+        match self {
+            NavalBattleState::Setup(state) => state.is_typing(),
+            NavalBattleState::Battle(state) => state.is_typing(),
+            NavalBattleState::Settings(_) => true,
+            NavalBattleState::Leaderboard(_) => false,
+            NavalBattleState::Puzzle(state) => state.is_typing(),
+            NavalBattleState::Campaign(_) => false,
+            NavalBattleState::Tutorial(_) => false,
+            NavalBattleState::Spectator(_) => false,
+            NavalBattleState::Replay(_) => false,
+            NavalBattleState::Network(state) => state.is_typing(),
+        }
+    }
+
+    /// Returns the information shown in the status bar for the current state, if any.
+    pub fn status(&self) -> Option<String> {
+        match self {
+            NavalBattleState::Battle(state) => Some(state.status()),
+            NavalBattleState::Spectator(state) => Some(state.status()),
+            NavalBattleState::Replay(state) => Some(state.status()),
+            NavalBattleState::Network(state) => Some(state.status()),
+            _ => None,
+        }
+    }
+
+    /// Returns `true` when the state is being animated, so that it must be redrawn often.
+    pub fn is_animating(&self) -> bool {
+        match self {
+            NavalBattleState::Battle(state) => state.is_animating(),
+            NavalBattleState::Spectator(state) => state.is_animating(),
+            _ => false,
+        }
     }
 
     /// Dispatches events to be handled according to the current state.
@@ -64,15 +198,31 @@ impl NavalBattleState {
             match self {
                 NavalBattleState::Setup(state) => state.handle_key_events(key_event),
                 NavalBattleState::Battle(state) => state.handle_key_events(key_event),
+                NavalBattleState::Settings(state) => state.handle_key_events(key_event),
+                NavalBattleState::Leaderboard(state) => state.handle_key_events(key_event),
+                NavalBattleState::Puzzle(state) => state.handle_key_events(key_event),
+                NavalBattleState::Campaign(state) => state.handle_key_events(key_event),
+                NavalBattleState::Tutorial(state) => state.handle_key_events(key_event),
+                NavalBattleState::Spectator(state) => state.handle_key_events(key_event),
+                NavalBattleState::Replay(state) => state.handle_key_events(key_event),
+                NavalBattleState::Network(state) => state.handle_key_events(key_event),
             }
         }
     }
 
     /// Updates the player objects according to the current state.
-    pub fn update(&mut self, game: &mut Game) {
+    pub fn update(&mut self, series: &mut Series, notifications: &mut Notifications) {
         match self {
-            NavalBattleState::Setup(state) => state.update(game),
-            NavalBattleState::Battle(state) => state.update(game),
+            NavalBattleState::Setup(state) => state.update(series, notifications),
+            NavalBattleState::Battle(state) => state.update(series, notifications),
+            NavalBattleState::Settings(state) => state.update(series, notifications),
+            NavalBattleState::Leaderboard(state) => state.update(series, notifications),
+            NavalBattleState::Puzzle(state) => state.update(series, notifications),
+            NavalBattleState::Campaign(state) => state.update(series, notifications),
+            NavalBattleState::Tutorial(state) => state.update(series, notifications),
+            NavalBattleState::Spectator(state) => state.update(series, notifications),
+            NavalBattleState::Replay(state) => state.update(series, notifications),
+            NavalBattleState::Network(state) => state.update(series, notifications),
         }
     }
 
@@ -81,6 +231,14 @@ impl NavalBattleState {
         match self {
             NavalBattleState::Setup(state) => state.widget().render(area, buf),
             NavalBattleState::Battle(state) => state.widget().render(area, buf),
+            NavalBattleState::Settings(state) => state.widget().render(area, buf),
+            NavalBattleState::Leaderboard(state) => state.widget().render(area, buf),
+            NavalBattleState::Puzzle(state) => state.widget().render(area, buf),
+            NavalBattleState::Campaign(state) => state.widget().render(area, buf),
+            NavalBattleState::Tutorial(state) => state.widget().render(area, buf),
+            NavalBattleState::Spectator(state) => state.widget().render(area, buf),
+            NavalBattleState::Replay(state) => state.widget().render(area, buf),
+            NavalBattleState::Network(state) => state.widget().render(area, buf),
         }
     }
 }
@@ -88,6 +246,6 @@ impl NavalBattleState {
 /// The default state is the setup screen
 impl Default for NavalBattleState {
     fn default() -> Self {
-        NavalBattleState::setup()
+        NavalBattleState::setup(&Config::default())
     }
 }
@@ -7,7 +7,20 @@
 //! Basically, the workbench draws the minimal items of the entire application, like the border, title, and instructions, and
 //! it takes a *content* to be rendered inside the workbench itself.
 //!
+pub mod ammo;
 pub mod battle;
+pub mod campaign;
+pub mod clock;
+pub mod fleet;
 pub mod grid;
+pub mod leaderboard;
+pub mod network;
+pub mod puzzle;
+pub mod replay;
+pub mod score;
+pub mod settings;
 pub mod setup;
+pub mod spectator;
+pub mod toast;
+pub mod tutorial;
 pub mod workbench;
@@ -0,0 +1,44 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    prelude::{Line, Span, Stylize, Widget},
+};
+
+/// A widget that shows the shells left to both sides, when they are limited.
+///
+/// The shells turn yellow when they are running low, and red when they are over.
+pub struct AmmoWidget {
+    human: u32,
+    computer: u32,
+}
+
+impl AmmoWidget {
+    const LOW_AMMO: u32 = 10;
+
+    /// Creates a new widget for the shells of the human and the computer.
+    pub fn new(human: u32, computer: u32) -> Self {
+        Self { human, computer }
+    }
+
+    fn ammo_spans(label: &str, ammo: u32) -> Vec<Span<'static>> {
+        let shells = Span::raw(format!(" {ammo} "));
+        let shells = match ammo {
+            0 => shells.red().bold(),
+            ammo if ammo <= Self::LOW_AMMO => shells.yellow().bold(),
+            _ => shells.bold(),
+        };
+
+        vec![Span::raw(format!("{label} ")).gray(), shells]
+    }
+}
+
+impl Widget for AmmoWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut spans = vec![Span::raw("Shells: ").gray()];
+        spans.extend(Self::ammo_spans("You", self.human));
+        spans.push(Span::raw("   "));
+        spans.extend(Self::ammo_spans("Computer", self.computer));
+
+        Line::from(spans).centered().render(area, buf);
+    }
+}
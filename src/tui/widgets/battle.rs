@@ -1,15 +1,30 @@
-use crate::engine::game::Game;
+use crate::config::{Config, KeyBindings, TurnPace};
+use crate::engine::action::{PlayerAction, PowerUps};
+use crate::engine::daily::DailyChallenge;
+use crate::engine::game::{GameError, ScanReport, ShotReport, Side, TurnEvent};
+use crate::engine::series::{Score, Series};
+use crate::locale::{Locale, Text};
 use crate::{
     engine::{
-        grid::{Cell, Grid},
+        clock::Clock,
+        fleet::{Ship, ShipKind, ShipOrientation, ShipStatus},
+        grid::{Cell, CellState, CoordinateFormat, Grid},
         player::Player,
+        weather::Fog,
     },
     tui::{
         state::StateModel,
-        widgets::grid::{GridModel, Layer},
+        widgets::{
+            ammo::AmmoWidget,
+            clock::ClockWidget,
+            fleet::FleetStatusWidget,
+            grid::{CursorMove, GridDelta, GridModel, Layer},
+            score::ScoreWidget,
+            toast::Notifications,
+        },
     },
 };
-use crossterm::event::{KeyCode, KeyEvent};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::{
     buffer::Buffer,
     layout::{Constraint, Direction, Layout, Rect},
@@ -18,73 +33,707 @@ use ratatui::{
     text::Span,
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
 };
+use std::collections::HashMap;
+use std::mem;
+use std::str::FromStr;
+use std::time::{Duration, Instant};
 
 /// Tracks how the battle goes
+///
+/// The human player can resign at any time with Ctrl+R. When the game has a time control, the
+/// model also tracks the clocks of both sides. The score of the series is shown above the grids,
+/// together with the shells left to both sides when they are limited.
+///
+/// When the game has power-ups, Tab switches the weapon between a single shot, a bombardment
+/// and a scan, whose area is highlighted on the opponent grid. The scanned areas stay
+/// highlighted: green when a ship has been detected, blue otherwise.
+///
+/// When the rules let the Submarine move, Tab also selects its relocation: the arrow keys move
+/// the Submarine on the tactical grid, the orientation keys turn it, and Enter relocates it
+/// instead of firing.
+///
+/// With the streak rule, the player is prompted to shoot again after every hit, and with the
+/// salvo until they have fired a shot for every ship afloat.
+///
+/// In the variants with mines, the player's own mines are shown on the tactical grid, and the
+/// enemy mines which have exploded on the opponent grid.
+///
+/// On a map, the islands are shown on both grids. In the fog of war, the fogged cells are grayed
+/// on the opponent grid, and the shots whose result is still hidden are marked with "?".
+///
+/// Unless the turns are played at once, the shots of the computer are held back after the
+/// player has fired, while the computer is "thinking": for a moment, or until a key is pressed,
+/// according to the configured pace. The engine has already resolved them, but the screen shows
+/// them only then, so that the turns feel sequential.
+///
+/// Every shot is animated for a moment when it is resolved: a splash for a miss, an explosion for
+/// a hit. The shots hidden by the fog are not animated.
+///
+/// The last shot of the computer is outlined on the tactical grid, and told above it with its
+/// result.
+///
+/// The enemy ships sunk by the player are outlined on the opponent grid, unless they have sunk
+/// in the fog.
+///
+/// When the handicap reveals a ship, it is highlighted on the opponent grid if it is an enemy
+/// ship, or on the tactical grid if it is one of the player's ships.
+///
+/// The player can ask where to shoot with "?", as many times as the rules allow: the suggested
+/// cell is marked on the opponent grid until the next shot.
+///
+/// When the shots must be confirmed, Enter selects the target, which is highlighted, and a
+/// second Enter (or "y") on the same target fires. A cell already shot can't be selected.
+///
+/// The player can also take notes on the unexplored cells of the opponent grid, until the end of
+/// the game: "1" marks the cell under the cursor as suspected with "!", "2" rules it out with "-"
+/// and "0" erases the note.
+///
+/// The grids are built again only when they may look different: after a key, a move of the
+/// game, a frame of the animations or a roll of the waves.
+///
+/// When the water is animated, waves roll across the empty cells of both grids.
+///
+/// When debugging the computer player, the tactical grid, where the computer shoots, is shaded
+/// with how much its strategy likes every cell for the next move.
 pub struct BattleStateModel {
     player1_has_shot: bool,
+    player1_resigns: bool,
+    player1_asks_hint: bool,
+    hint: Option<Cell>,
+    notes: HashMap<Cell, char>,
+    debug_ai: bool,
+    heatmap: Option<HashMap<Cell, f32>>,
+    weapon: Weapon,
+    power_ups: PowerUps,
+    relocations: u8,
+    orientation: ShipOrientation,
+    message: Option<String>,
+    scans: Vec<ScanReport>,
+    sunk: Vec<Cell>,
+    computer_shot: Option<ShotReport>,
+    impacts: Vec<Impact>,
+    turn: Turn,
+    pace: TurnPace,
+    player1_continues: bool,
+    confirm_shots: bool,
+    pending: Option<Cell>,
+    accessibility: bool,
+    narration: Vec<String>,
+    notation: CoordinateFormat,
+    coordinates: String,
+    revealed: Option<(Side, Ship)>,
     player1_won: Option<bool>,
+    final_turn: Option<TurnEvent>,
+    clocks: Option<(Clock, Clock)>,
+    ammo: Option<(u32, u32)>,
+    best_of: u8,
+    score: Score,
+    series_winner: Option<Side>,
+    daily: Option<(DailyChallenge, Option<u32>)>,
     tactical_grid: GridModel,
     opponent_grid: GridModel,
-    computer_shots: Vec<Cell>,
+    computer_fleet: Vec<ShipStatus>,
+    human_fleet: Vec<ShipStatus>,
+    waves: Option<Instant>,
+    keybindings: KeyBindings,
+    locale: Locale,
+    // Whether the grids must be built again, and the revision of the game and the number of
+    // turns they have been built for. Whether their overlays must be drawn again, the frame of
+    // the waves they have been drawn for, and how many layers of both grids lie below them.
+    stale: bool,
+    drawn: Option<(u64, u32)>,
+    redraw: bool,
+    frame: Option<usize>,
+    layers: (usize, usize),
 }
 
 impl BattleStateModel {
     /// Updates the grids to reflect the current state of the game
-    pub fn update_grid(&mut self, _computer: &Player, human: &Player) {
-        let cursor = *self.opponent_grid.cursor().unwrap();
-        self.opponent_grid = GridModel::new(human.shots_grid().clone());
-        self.opponent_grid.set_cursor(&cursor);
+    ///
+    /// When the given turn is the only one played since the last update, and no Submarine has
+    /// moved in it, only the cells shot by the player are updated: otherwise the whole grids
+    /// are compared with the game. The layers which follow the cursor and the animations are
+    /// left to [BattleStateModel::update_overlays].
+    pub fn update_grid(
+        &mut self,
+        computer: &Player,
+        human: &Player,
+        fog: &Fog,
+        turn: Option<&TurnEvent>,
+    ) {
+        match turn.filter(|turn| turn.relocated.is_empty()) {
+            Some(turn) => {
+                let shots = turn
+                    .shots
+                    .iter()
+                    .filter(|shot| shot.shooter == Side::Human)
+                    .map(|shot| shot.cell);
+                self.opponent_grid
+                    .apply(GridDelta::of(shots, human.shots_grid()));
+            }
+            None => {
+                let delta = GridDelta::between(self.opponent_grid.grid(), human.shots_grid());
+                self.opponent_grid.apply(delta);
+
+                // The computer shots grid charts the islands of the map too.
+                let mut tactical_grid = Grid::from_ships(human.fleet().as_ref());
+                for cell in computer.shots_grid().cells(&CellState::Blocked) {
+                    tactical_grid.mark(&cell, CellState::Blocked);
+                }
+                let delta = GridDelta::between(self.tactical_grid.grid(), &tactical_grid);
+                self.tactical_grid.apply(delta);
+            }
+        }
 
-        self.tactical_grid = GridModel::new(Grid::from_ships(human.fleet().as_ref()));
+        self.opponent_grid.clear_layers();
+        for scan in &self.scans {
+            self.opponent_grid
+                .push_layer(Layer::Scan(scan.cells.clone(), scan.detected));
+        }
+        self.opponent_grid
+            .push_layer(Layer::Sunk(self.sunk.clone()));
+        if let Some((Side::Computer, ship)) = &self.revealed {
+            self.opponent_grid
+                .push_layer(Layer::Ship(ship.occupied_cells()));
+        }
+        self.opponent_grid
+            .push_layer(Layer::Mines(computer.mines().exploded().to_vec()));
+        self.opponent_grid
+            .push_layer(Layer::Fog(fog.cells().to_vec(), fog.hidden(Side::Human)));
+
+        self.tactical_grid.clear_layers();
+        if let Some(heatmap) = &self.heatmap {
+            self.tactical_grid.push_layer(Layer::Heat(heatmap.clone()));
+        }
+        // The damage of the human fleet includes the handicap, which is not a computer shot.
+        let held = self.turn.held_cells();
+        let shots = computer.shots_grid();
+        let mut damage: Vec<Cell> = shots
+            .cells(&CellState::Miss)
+            .chain(shots.cells(&CellState::Hit))
+            .chain(human.fleet().as_ref().iter().flat_map(|ship| ship.hits()))
+            .collect();
+        damage.retain(|cell| !held.contains(cell));
+        self.tactical_grid.push_layer(Layer::Shots(damage));
+        if let Some((Side::Human, ship)) = &self.revealed {
+            self.tactical_grid
+                .push_layer(Layer::Selection(ship.occupied_cells()));
+        }
         self.tactical_grid
-            .push_layer(Layer::Shots(self.computer_shots.clone()));
+            .push_layer(Layer::Mines(human.mines().cells().to_vec()));
+        if let Some(shot) = &self.computer_shot {
+            self.tactical_grid.push_layer(Layer::LastShot(shot.cell));
+        }
+        self.layers = (
+            self.opponent_grid.layer_count(),
+            self.tactical_grid.layer_count(),
+        );
+
+        self.computer_fleet = computer.fleet().status();
+        if self.turn == Turn::Player {
+            self.human_fleet = human.fleet().status();
+        }
+    }
+
+    /// Updates the layers of the grids which follow the cursor and the animations, over the
+    /// ones left by [BattleStateModel::update_grid].
+    pub fn update_overlays(&mut self, human: &Player) {
+        let (opponent_layers, tactical_layers) = self.layers;
+        let cursor = *self.opponent_grid.cursor().unwrap();
+        self.opponent_grid.truncate_layers(opponent_layers);
+        self.opponent_grid.set_waves(self.wave_frame());
+        if matches!(self.weapon, Weapon::Bombard | Weapon::Scan) {
+            let action = self.weapon.action(cursor, self.orientation);
+            self.opponent_grid.push_layer(Layer::Target(action.cells()));
+        }
+        // The notes are kept on the explored cells, but their results are shown instead.
+        let notes = self
+            .notes
+            .iter()
+            .filter(|(cell, _)| *self.opponent_grid.at(cell) == CellState::Empty)
+            .map(|(cell, note)| (*cell, *note))
+            .collect();
+        self.opponent_grid.push_layer(Layer::Marks(notes));
+        if let Some(hint) = self.hint {
+            self.opponent_grid.push_layer(Layer::Hint(hint));
+        }
+        if let Some(pending) = self.pending {
+            self.opponent_grid.push_layer(Layer::Target(vec![pending]));
+        }
+        for impact in self.impacts.iter().filter(|i| i.shooter == Side::Human) {
+            self.opponent_grid.push_layer(impact.layer());
+        }
+
+        let submarine = human
+            .fleet()
+            .get(&ShipKind::Submarine)
+            .map(Ship::first_cell);
+        let tactical_cursor = self
+            .tactical_grid
+            .cursor()
+            .copied()
+            .or(submarine)
+            .unwrap_or(Cell::bounded(0, 0));
+        self.tactical_grid.truncate_layers(tactical_layers);
+        self.tactical_grid.disable_cursor();
+        self.tactical_grid.set_waves(self.wave_frame());
+        for impact in self.impacts.iter().filter(|i| i.shooter == Side::Computer) {
+            self.tactical_grid.push_layer(impact.layer());
+        }
+        if self.weapon == Weapon::Relocate {
+            self.tactical_grid.set_cursor(&tactical_cursor);
+            if let Some(ship) = ShipKind::Submarine.ship(tactical_cursor, self.orientation) {
+                self.tactical_grid
+                    .push_layer(Layer::Ship(ship.occupied_cells()));
+            }
+        }
+    }
+
+    /// Updates the grids, the clocks, the shells and the score to reflect the current state of
+    /// the series
+    pub fn update_series(&mut self, series: &Series) {
+        self.stale = true;
+        self.refresh(series);
+    }
+
+    // Updates the clocks, the shells and the score, the grids if the game has changed, and their
+    // overlays if the view has changed.
+    fn refresh(&mut self, series: &Series) {
+        let game = series.game();
+        self.clocks = game
+            .clock(Side::Human)
+            .zip(game.clock(Side::Computer))
+            .map(|(human, computer)| (*human, *computer));
+        self.power_ups = game.power_ups(Side::Human);
+        self.relocations = game.relocations(Side::Human);
+        if !self.weapon.is_available(self.power_ups, self.relocations) {
+            self.weapon = Weapon::Shot;
+        }
+        let (computer, human) = (game.computer().unwrap(), game.human().unwrap());
+        self.ammo = human.ammo().zip(computer.ammo());
+
+        let drawn = Some((game.revision(), game.turns_played()));
+        let changed = drawn != self.drawn;
+        if self.stale || changed {
+            self.revealed = [Side::Human, Side::Computer]
+                .into_iter()
+                .find_map(|side| game.revealed_ship(side).map(|ship| (side, ship.clone())));
+            // The last turn has no computer shot while the human player shoots again, and its
+            // shots are held back while the computer is thinking.
+            if let Some(shot) = game
+                .last_turn()
+                .filter(|_| self.turn == Turn::Player)
+                .and_then(|turn| {
+                    turn.shots
+                        .iter()
+                        .rfind(|shot| shot.shooter == Side::Computer)
+                })
+            {
+                self.computer_shot = Some(shot.clone());
+            }
+            // The last turn has changed only the cells it shot, if no other turn has been played
+            // since the grids have been built.
+            let turn = self
+                .drawn
+                .filter(|(_, turns)| !changed || turns + 1 == game.turns_played())
+                .and(game.last_turn())
+                .filter(|turn| turn.turn == game.turns_played());
+            self.update_grid(computer, human, game.fog(), turn);
+            (self.stale, self.drawn, self.redraw) = (false, drawn, true);
+        }
+
+        let frame = self.wave_frame();
+        if self.redraw || self.is_animating() || frame != self.frame {
+            self.update_overlays(human);
+            (self.redraw, self.frame) = (false, frame);
+        }
+
+        self.best_of = series.best_of();
+        self.score = series.score();
+        self.series_winner = series.winner();
+        self.daily = series
+            .daily_challenge()
+            .map(|challenge| (challenge, series.par()));
     }
 }
 
-impl Default for BattleStateModel {
-    fn default() -> Self {
-        let tactical_grid = GridModel::new(Grid::default());
+impl BattleStateModel {
+    // How long the computer thinks, at the delayed pace.
+    const THINKING: Duration = Duration::from_secs(1);
+
+    // The keys which take notes on the opponent grid, and the marks of the notes.
+    const NOTES: [char; 3] = ['1', '2', '0'];
+    const SUSPECTED: char = '!';
+    const RULED_OUT: char = '-';
+
+    /// Creates a new battle model, with the grids drawn in the configured theme.
+    pub fn new(config: &Config) -> Self {
+        let mut tactical_grid = GridModel::new(Grid::default());
         let mut opponent_grid = GridModel::new(Grid::default());
 
+        tactical_grid.set_theme(config.theme);
+        opponent_grid.set_theme(config.theme);
         opponent_grid.enable_cursor();
 
         Self {
             player1_has_shot: false,
+            player1_resigns: false,
+            player1_asks_hint: false,
+            hint: None,
+            notes: HashMap::new(),
+            debug_ai: false,
+            heatmap: None,
+            weapon: Weapon::Shot,
+            power_ups: PowerUps::default(),
+            relocations: 0,
+            orientation: ShipOrientation::Horizontal,
+            message: None,
+            scans: Vec::new(),
+            sunk: Vec::new(),
+            computer_shot: None,
+            impacts: Vec::new(),
+            turn: Turn::Player,
+            pace: config.turn_pace,
+            player1_continues: false,
+            confirm_shots: config.confirm_shots,
+            pending: None,
+            accessibility: config.accessibility,
+            narration: Vec::new(),
+            notation: config.coordinate_format,
+            coordinates: String::new(),
+            revealed: None,
             player1_won: None,
+            final_turn: None,
+            clocks: None,
+            ammo: None,
+            best_of: config.best_of,
+            score: Score::default(),
+            series_winner: None,
+            daily: None,
             tactical_grid,
             opponent_grid,
-            computer_shots: Vec::new(),
+            computer_fleet: Vec::new(),
+            human_fleet: Vec::new(),
+            waves: config.animated_water.then(Instant::now),
+            keybindings: config.keybindings.clone(),
+            locale: config.locale(),
+            stale: true,
+            drawn: None,
+            redraw: true,
+            frame: None,
+            layers: (0, 0),
+        }
+    }
+
+    // Selects the target under the cursor, or fires if it has already been selected.
+    fn select_target(&mut self) {
+        let cursor = *self.opponent_grid.cursor().unwrap();
+        let state = self.opponent_grid.at(&cursor).clone();
+        if self.weapon == Weapon::Shot && state != CellState::Empty {
+            self.pending = None;
+            self.message = match state {
+                CellState::Blocked => Some(self.locale.error(&GameError::OnIsland(cursor))),
+                _ => Some(self.locale.format(Text::AlreadyShot, &[&cursor])),
+            };
+        } else if self.pending == Some(cursor) {
+            self.pending = None;
+            self.player1_has_shot = true;
+        } else {
+            self.pending = Some(cursor);
+            self.message = Some(self.locale.format(Text::ConfirmShot, &[&cursor]));
+        }
+    }
+
+    // Returns the cell under the cursor if it has already been shot, so that a shot there would
+    // be wasted.
+    fn already_shot(&self) -> Option<Cell> {
+        let cursor = *self.opponent_grid.cursor()?;
+        let shot = matches!(
+            self.opponent_grid.at(&cursor),
+            CellState::Hit | CellState::Miss
+        );
+
+        (self.weapon == Weapon::Shot && shot).then_some(cursor)
+    }
+
+    // Returns the frame of the waves, which move every half a second, if the water is animated.
+    fn wave_frame(&self) -> Option<usize> {
+        self.waves
+            .map(|start| (start.elapsed().as_millis() / 500) as usize)
+    }
+
+    /// Returns what the status bar tells about the battle: the cell under the cursor, whose turn
+    /// it is and how many shots the player has fired.
+    pub fn status(&self) -> String {
+        let cursor = match self.weapon {
+            Weapon::Relocate => self.tactical_grid.cursor(),
+            _ => self.opponent_grid.cursor(),
+        };
+        let turn = match (&self.turn, self.player1_won) {
+            (_, Some(_)) => Text::GameOver,
+            (Turn::Computer { .. }, None) => Text::ComputerTurn,
+            (Turn::Player, None) => Text::YourTurn,
+        };
+        let turn = self.locale.text(turn);
+        let grid = self.opponent_grid.grid();
+        let shots = grid.count(&CellState::Hit) + grid.count(&CellState::Miss);
+        let shots = self.locale.format(Text::ShotsFired, &[&shots]);
+
+        match cursor {
+            Some(cursor) => format!("{cursor} · {turn} · {shots}"),
+            None => format!("{turn} · {shots}"),
+        }
+    }
+
+    /// Returns `true` while a shot is being animated, so that the screen is redrawn often.
+    pub fn is_animating(&self) -> bool {
+        !self.impacts.is_empty()
+    }
+
+    /// Chooses whether the tactical grid shows the probabilities of the computer strategy.
+    pub fn set_debug_ai(&mut self, debug_ai: bool) {
+        self.debug_ai = debug_ai;
+        self.stale = true;
+    }
+
+    /// Returns `true` while the player is typing the coordinates of a target, so that the keys
+    /// are not commands.
+    pub fn is_typing(&self) -> bool {
+        !self.coordinates.is_empty()
+    }
+
+    // Edits the coordinates typed by the player, and aims at them on Enter, as if the cursor had
+    // been moved there.
+    fn handle_coordinates_events(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Char(c)
+                if (c.is_ascii_alphanumeric() || c == '-') && self.coordinates.len() < 6 =>
+            {
+                self.coordinates.push(c.to_ascii_uppercase())
+            }
+            KeyCode::Backspace => {
+                self.coordinates.pop();
+            }
+            KeyCode::Esc => self.coordinates.clear(),
+            KeyCode::Enter => match Cell::from_str(&mem::take(&mut self.coordinates)) {
+                Ok(cell) => {
+                    match self.weapon {
+                        Weapon::Relocate => self.tactical_grid.set_cursor(&cell),
+                        _ => self.opponent_grid.set_cursor(&cell),
+                    }
+                    self.handle_key_events(KeyEvent::from(KeyCode::Enter));
+                }
+                Err(e) => self.message = Some(e.to_string()),
+            },
+            _ => {}
         }
     }
 }
 
 impl StateModel for BattleStateModel {
     fn handle_key_events(&mut self, key_event: KeyEvent) {
+        self.redraw = true;
+        // While the computer is thinking, any key shows its shots.
+        if let Turn::Computer { .. } = self.turn {
+            self.player1_continues = true;
+            return;
+        } else if self.is_typing() {
+            self.handle_coordinates_events(key_event);
+            return;
+        }
+
+        // The Submarine is relocated on the tactical grid, while the shots aim at the opponent.
+        let grid = match self.weapon {
+            Weapon::Relocate => &mut self.tactical_grid,
+            _ => &mut self.opponent_grid,
+        };
+        let bindings = &self.keybindings;
         match key_event.code {
-            KeyCode::Left => self.opponent_grid.move_cursor(|c| c.move_left()),
-            KeyCode::Right => self.opponent_grid.move_cursor(|c| c.move_right()),
-            KeyCode::Up => self.opponent_grid.move_cursor(|c| c.move_up()),
-            KeyCode::Down => self.opponent_grid.move_cursor(|c| c.move_down()),
+            KeyCode::Left => grid.step_cursor(CursorMove::Left),
+            KeyCode::Right => grid.step_cursor(CursorMove::Right),
+            KeyCode::Up => grid.step_cursor(CursorMove::Up),
+            KeyCode::Down => grid.step_cursor(CursorMove::Down),
+            KeyCode::Enter if self.confirm_shots && self.weapon != Weapon::Relocate => {
+                self.select_target()
+            }
+            KeyCode::Char('y' | 'Y') if self.pending.is_some() => self.select_target(),
             KeyCode::Enter => {
                 self.player1_has_shot = true;
             }
+            KeyCode::Tab => {
+                self.weapon = self.weapon.next(self.power_ups, self.relocations);
+                self.message = None;
+                self.pending = None;
+            }
+            KeyCode::Char(c)
+                if self.weapon == Weapon::Relocate
+                    && c.eq_ignore_ascii_case(&bindings.horizontal) =>
+            {
+                self.orientation = ShipOrientation::Horizontal
+            }
+            KeyCode::Char(c)
+                if self.weapon == Weapon::Relocate
+                    && c.eq_ignore_ascii_case(&bindings.vertical) =>
+            {
+                self.orientation = ShipOrientation::Vertical
+            }
+            // In the accessibility mode, the first letter of a cell starts typing its coordinates,
+            // or its first digit in the numeric notation, where the digits don't take notes, or
+            // the `r` of its row in the row and column notation.
+            KeyCode::Char(c)
+                if self.accessibility
+                    && (('a'..='j').contains(&c.to_ascii_lowercase())
+                        || (self.notation == CoordinateFormat::Numeric && c.is_ascii_digit())
+                        || (self.notation == CoordinateFormat::RowColumn
+                            && c.eq_ignore_ascii_case(&'r')
+                            && !key_event.modifiers.contains(KeyModifiers::CONTROL))) =>
+            {
+                self.coordinates.push(c.to_ascii_uppercase())
+            }
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.player1_resigns = true;
+            }
+            KeyCode::Char('?') => {
+                self.player1_asks_hint = true;
+            }
+            KeyCode::Char(c) if self.weapon != Weapon::Relocate && Self::NOTES.contains(&c) => {
+                let cell = *self.opponent_grid.cursor().unwrap();
+                match c {
+                    '1' => self.notes.insert(cell, Self::SUSPECTED),
+                    '2' => self.notes.insert(cell, Self::RULED_OUT),
+                    _ => self.notes.remove(&cell),
+                };
+            }
 
             _ => {}
         }
     }
 
-    fn update(&mut self, game: &mut Game) {
-        if self.player1_has_shot {
-            match game.play_turn(self.opponent_grid.cursor().unwrap()) {
+    fn update(&mut self, series: &mut Series, notifications: &mut Notifications) {
+        let game = series.game_mut();
+        if let Turn::Computer { since, shots } = &self.turn
+            && (self.player1_continues
+                || (self.pace == TurnPace::Delayed && since.elapsed() >= Self::THINKING))
+        {
+            let now = Instant::now();
+            self.impacts
+                .extend(shots.iter().map(|shot| Impact::new(shot, now)));
+            self.narration.extend(
+                shots
+                    .iter()
+                    .map(|shot| narrate(shot, self.locale, self.notation)),
+            );
+            self.turn = Turn::Player;
+            self.message = None;
+            self.stale = true;
+        }
+        self.player1_continues = false;
+
+        if let Some(side) = game.check_time() {
+            self.player1_won = Some(side == Side::Computer);
+        } else if self.player1_resigns {
+            if game.resign(Side::Human).is_ok() {
+                self.player1_won = Some(false);
+            }
+        } else if self.player1_asks_hint {
+            self.message = match game.hint() {
+                Ok(cell) => {
+                    self.hint = Some(cell);
+                    Some(self.locale.format(Text::TryHint, &[&cell, &game.hints()]))
+                }
+                Err(e) => Some(self.locale.error(&e)),
+            };
+        } else if let Some(cell) = self.already_shot().filter(|_| self.player1_has_shot) {
+            notifications.push(self.locale.format(Text::AlreadyShot, &[&cell]));
+        } else if self.player1_has_shot {
+            self.hint = None;
+            let cursor = match self.weapon {
+                Weapon::Relocate => *self.tactical_grid.cursor().unwrap(),
+                _ => *self.opponent_grid.cursor().unwrap(),
+            };
+            // While the human player shoots again, the shots fired so far stay in the turn.
+            let fired = match game.human_shoots_again() {
+                true => game.last_turn().map_or(0, |turn| turn.shots.len()),
+                false => 0,
+            };
+            match game.play_action(self.weapon.action(cursor, self.orientation)) {
                 Ok(winner) => {
-                    if let Some(computer_shot) = game.last_computer_move() {
-                        self.computer_shots.push(*computer_shot);
+                    let now = Instant::now();
+                    let (human_shots, computer_shots): (Vec<_>, Vec<_>) = game
+                        .last_turn()
+                        .iter()
+                        .flat_map(|turn| turn.shots.iter().skip(fired))
+                        .cloned()
+                        .partition(|shot| shot.shooter == Side::Human);
+                    self.impacts.extend(
+                        human_shots
+                            .iter()
+                            .filter(|shot| !shot.fogged)
+                            .map(|shot| Impact::new(shot, now)),
+                    );
+                    self.narration = human_shots
+                        .iter()
+                        .map(|shot| narrate(shot, self.locale, self.notation))
+                        .collect();
+                    for shot in human_shots.iter().filter(|shot| shot.sunk && !shot.fogged) {
+                        if let Some(kind) = &shot.hit {
+                            notifications.push(self.locale.format(Text::YouSank, &[kind]));
+                        }
+                    }
+                    let relocated = game
+                        .last_turn()
+                        .is_some_and(|turn| turn.relocated.contains(&Side::Computer));
+                    let hit = game
+                        .last_turn()
+                        .and_then(|turn| turn.shots.last())
+                        .is_some_and(|shot| shot.hit.is_some());
+                    self.message = if game.human_shoots_again() && hit {
+                        Some(self.locale.text(Text::HitShootAgain).to_string())
+                    } else if game.human_shoots_again() {
+                        Some(self.locale.text(Text::ShootAgain).to_string())
+                    } else if relocated {
+                        Some(self.locale.text(Text::SubmarineMoved).to_string())
+                    } else {
+                        None
+                    };
+                    if let Some(scan) = game.last_turn().and_then(|turn| turn.scan.clone()) {
+                        self.scans.push(scan);
                     }
+                    self.sunk.extend(
+                        game.last_turn()
+                            .iter()
+                            .flat_map(|turn| &turn.shots)
+                            .filter(|shot| shot.shooter == Side::Human && !shot.fogged)
+                            .flat_map(|shot| shot.sunk_cells.iter().copied()),
+                    );
 
                     if let Some(human) = winner {
                         self.player1_won = Some(human);
                     }
+                    self.heatmap = None;
+
+                    // The shots of the computer are held back, unless the game is over.
+                    if self.pace == TurnPace::Instant || winner.is_some() {
+                        self.impacts
+                            .extend(computer_shots.iter().map(|shot| Impact::new(shot, now)));
+                        self.narration.extend(
+                            computer_shots
+                                .iter()
+                                .map(|shot| narrate(shot, self.locale, self.notation)),
+                        );
+                    } else if !computer_shots.is_empty() {
+                        self.turn = Turn::Computer {
+                            since: now,
+                            shots: computer_shots,
+                        };
+                        self.message = Some(self.locale.text(Text::ComputerThinking).to_string());
+                    }
                 }
+                Err(
+                    e @ (GameError::InvalidRelocation(_)
+                    | GameError::RelocationUnavailable
+                    | GameError::PowerUpUnavailable),
+                ) => self.message = Some(self.locale.error(&e)),
                 Err(e) => {
                     panic!("{e}");
                 }
@@ -92,8 +741,21 @@ impl StateModel for BattleStateModel {
         }
 
         self.player1_has_shot = false;
+        self.player1_resigns = false;
+        self.player1_asks_hint = false;
+        let impacts = self.impacts.len();
+        self.impacts.retain(|impact| !impact.is_over());
+        self.redraw |= self.impacts.len() != impacts;
+        if game.is_over() {
+            self.final_turn = game.last_turn().cloned();
+            self.heatmap = None;
+        } else if self.debug_ai && self.heatmap.is_none() {
+            // The heatmap is computed once per turn, as it is as expensive as a computer move.
+            self.heatmap = game.computer_heatmap();
+            self.stale = true;
+        }
 
-        self.update_grid(game.computer().unwrap(), game.human().unwrap());
+        self.refresh(series);
     }
 
     fn widget(&self) -> impl Widget {
@@ -101,18 +763,257 @@ impl StateModel for BattleStateModel {
     }
 }
 
+// Whose turn the battle shows: the player's, or the computer's, which is thinking about the
+// shots it has already fired.
+#[derive(Debug, PartialEq, Eq)]
+enum Turn {
+    Player,
+    Computer {
+        since: Instant,
+        shots: Vec<ShotReport>,
+    },
+}
+
+impl Turn {
+    // Returns the cells of the shots held back.
+    fn held_cells(&self) -> Vec<Cell> {
+        match self {
+            Turn::Player => Vec::new(),
+            Turn::Computer { shots, .. } => shots.iter().map(|shot| shot.cell).collect(),
+        }
+    }
+}
+
+// A shot being animated on the grid of the opponent of the shooter.
+struct Impact {
+    shooter: Side,
+    cell: Cell,
+    hit: bool,
+    start: Instant,
+}
+
+impl Impact {
+    // How long every frame of the animation is shown.
+    const FRAME: Duration = Duration::from_millis(150);
+    const FRAMES: u32 = 4;
+
+    fn new(shot: &ShotReport, start: Instant) -> Self {
+        Self {
+            shooter: shot.shooter,
+            cell: shot.cell,
+            hit: shot.hit.is_some(),
+            start,
+        }
+    }
+
+    fn frame(&self) -> usize {
+        (self.start.elapsed().as_millis() / Self::FRAME.as_millis()) as usize
+    }
+
+    fn is_over(&self) -> bool {
+        self.start.elapsed() >= Self::FRAME * Self::FRAMES
+    }
+
+    fn layer(&self) -> Layer {
+        Layer::Impact(self.cell, self.hit, self.frame())
+    }
+}
+
+// Tells the result of a shot, like "Miss" or "Destroyer sunk", as the human player sees it.
+fn shot_result(shot: &ShotReport, locale: Locale) -> String {
+    match (&shot.hit, shot.sunk) {
+        _ if shot.fogged && shot.shooter == Side::Human => locale.text(Text::Fogged).to_string(),
+        _ if shot.mine => locale.text(Text::Mine).to_string(),
+        (Some(kind), true) => locale.format(Text::Sunk, &[kind]),
+        (Some(_), false) => locale.text(Text::Hit).to_string(),
+        (None, _) => locale.text(Text::Miss).to_string(),
+    }
+}
+
+// Tells where the computer has fired and the result, like "Computer fired at E6 — Miss".
+fn computer_shot(shot: &ShotReport, locale: Locale, notation: CoordinateFormat) -> String {
+    locale.format(
+        Text::ComputerFired,
+        &[&notation.format(&shot.cell), &shot_result(shot, locale)],
+    )
+}
+
+// Narrates a shot in a sentence, like "Your shot at C4: Hit.".
+fn narrate(shot: &ShotReport, locale: Locale, notation: CoordinateFormat) -> String {
+    let text = match shot.shooter {
+        Side::Human => Text::YourShotAt,
+        Side::Computer => Text::ComputerShotAt,
+    };
+
+    locale.format(
+        text,
+        &[&notation.format(&shot.cell), &shot_result(shot, locale)],
+    )
+}
+
 pub struct BattleWidget<'state>(&'state BattleStateModel);
 
+impl<'state> BattleWidget<'state> {
+    const SIDEBAR_WIDTH: u16 = 36;
+    // The size a grid needs to be drawn with its border, without squeezing its cells.
+    const GRID_WIDTH: u16 = 47;
+    const GRID_HEIGHT: u16 = 25;
+
+    // Splits the area into the opponent grid, the tactical grid and the sidebar.
+    //
+    // The grids are side by side, unless the terminal is too narrow for them but tall enough to
+    // stack them: then the opponent grid is above the tactical one.
+    fn layout(area: Rect) -> [Rect; 3] {
+        let narrow = area.width < 2 * Self::GRID_WIDTH + Self::SIDEBAR_WIDTH;
+        let tall = area.height >= 2 * Self::GRID_HEIGHT;
+        let [grids, sidebar] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(Self::SIDEBAR_WIDTH)])
+                .areas(area);
+        let direction = match narrow && tall {
+            true => Direction::Vertical,
+            false => Direction::Horizontal,
+        };
+        let [opponent, tactical] = Layout::default()
+            .direction(direction)
+            .constraints([Constraint::Fill(1), Constraint::Fill(1)])
+            .areas(grids);
+
+        [opponent, tactical, sidebar]
+    }
+
+    // Shows the weapons the player can choose, when they have power-ups or relocations left.
+    fn weapon_line(&self) -> Option<Line<'static>> {
+        let power_ups = self.0.power_ups;
+        let relocations = self.0.relocations;
+        let locale = self.0.locale;
+        if power_ups == PowerUps::default() && relocations == 0 {
+            return None;
+        }
+
+        let mut spans = vec![Span::raw(locale.text(Text::Weapon))];
+        for weapon in Weapon::ALL {
+            let label = match weapon {
+                Weapon::Shot => locale.text(Text::Shot).to_string(),
+                Weapon::Bombard => locale.format(Text::Bombard, &[&power_ups.bombardments]),
+                Weapon::Scan => locale.format(Text::Scan, &[&power_ups.scans]),
+                Weapon::Relocate => locale.format(Text::MoveSubmarine, &[&relocations]),
+            };
+            let span = if weapon == self.0.weapon {
+                Span::raw(label).reversed()
+            } else if weapon.is_available(power_ups, relocations) {
+                Span::raw(label)
+            } else {
+                Span::raw(label).dark_gray()
+            };
+            if weapon != Weapon::Shot {
+                spans.push(Span::raw(" | "));
+            }
+            spans.push(span);
+        }
+        spans.push(Span::raw(" <Tab> ").blue().bold());
+
+        Some(Line::from(spans))
+    }
+
+    // Shows the coordinates typed by the player and narrates the last turn, in the accessibility
+    // mode.
+    fn narration(&self) -> Paragraph<'static> {
+        let locale = self.0.locale;
+        let target = match self.0.coordinates.is_empty() {
+            true => Span::raw(locale.text(Text::CoordinatesKeys)).gray(),
+            false => Span::raw(format!("{}_", self.0.coordinates))
+                .yellow()
+                .bold(),
+        };
+
+        Paragraph::new(vec![
+            Line::from(vec![
+                Span::raw(locale.text(Text::Coordinates)).bold(),
+                target,
+            ]),
+            Line::from(format!(" {}", self.0.narration.join(" "))),
+        ])
+        .wrap(Wrap { trim: false })
+    }
+
+    // Explains how the match has been lost, if it didn't end with a sunk fleet.
+    fn final_message(&self) -> &'static str {
+        let turn = self.0.final_turn.as_ref();
+        let text = if turn.is_some_and(|turn| turn.resigned == Some(Side::Human)) {
+            Text::YouResigned
+        } else if turn.is_some_and(|turn| turn.timed_out == Some(Side::Human)) {
+            Text::YourTimeUp
+        } else if turn.is_some_and(|turn| turn.timed_out == Some(Side::Computer)) {
+            Text::ComputerTimeUp
+        } else if self.0.player1_won == Some(false) && self.0.ammo.is_some_and(|(h, _)| h == 0) {
+            Text::YourShellsOut
+        } else if self.0.player1_won == Some(true) && self.0.ammo.is_some_and(|(_, c)| c == 0) {
+            Text::ComputerShellsOut
+        } else {
+            return "";
+        };
+
+        self.0.locale.text(text)
+    }
+
+    // Tells who has won the series, or how to go on with the next game.
+    fn series_message(&self) -> &'static str {
+        let text = if self.0.daily.is_some() {
+            Text::DailyOver
+        } else if self.0.best_of == 1 {
+            Text::Continue
+        } else {
+            match self.0.series_winner {
+                Some(Side::Human) => Text::SeriesWon,
+                Some(Side::Computer) => Text::SeriesLost,
+                None => Text::NextGame,
+            }
+        };
+
+        self.0.locale.text(text)
+    }
+}
+
 impl<'state> Widget for BattleWidget<'state> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let layout = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(area);
+        let [header, area] =
+            Layout::vertical([Constraint::Length(1), Constraint::Fill(1)]).areas(area);
+        let score = match self.0.daily {
+            Some((challenge, par)) => ScoreWidget::daily(challenge, par),
+            None => ScoreWidget::new(self.0.best_of, self.0.score),
+        };
+        let parts = 1 + usize::from(self.0.clocks.is_some()) + usize::from(self.0.ammo.is_some());
+        let header = Layout::horizontal(vec![Constraint::Fill(1); parts]).split(header);
+        score.render(header[0], buf);
+        let mut next = 1;
+        if let Some((human, computer)) = self.0.clocks {
+            ClockWidget::new(human, computer).render(header[next], buf);
+            next += 1;
+        }
+        if let Some((human, computer)) = self.0.ammo {
+            AmmoWidget::new(human, computer).render(header[next], buf);
+        }
 
-        let opponent_block = Block::bordered()
-            .title(Line::from("Opponent Grid".bold()))
+        let narration_height = match self.0.accessibility {
+            true => 3,
+            false => 0,
+        };
+        let [area, narration] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(narration_height)])
+                .areas(area);
+        if self.0.accessibility {
+            self.narration().render(narration, buf);
+        }
+
+        let layout = Self::layout(area);
+        let locale = self.0.locale;
+
+        let mut opponent_block = Block::bordered()
+            .title(Line::from(locale.text(Text::OpponentGrid).bold()))
             .border_set(border::THICK);
+        if let Some(weapon) = self.weapon_line() {
+            opponent_block = opponent_block.title_bottom(weapon.centered());
+        }
 
         self.0
             .opponent_grid
@@ -121,9 +1022,16 @@ impl<'state> Widget for BattleWidget<'state> {
 
         opponent_block.render(layout[0], buf);
 
-        let tactical_block = Block::bordered()
-            .title(Line::from("Tactical".bold()))
+        let mut tactical_block = Block::bordered()
+            .title(Line::from(locale.text(Text::Tactical).bold()))
             .border_set(border::THICK);
+        if let Some(message) = &self.0.message {
+            tactical_block = tactical_block.title_bottom(Line::from(message.clone()).centered());
+        }
+        if let Some(shot) = &self.0.computer_shot {
+            tactical_block = tactical_block
+                .title(Line::from(computer_shot(shot, locale, self.0.notation)).right_aligned());
+        }
 
         self.0
             .tactical_grid
@@ -132,6 +1040,12 @@ impl<'state> Widget for BattleWidget<'state> {
 
         tactical_block.render(layout[1], buf);
 
+        let sidebar = Layout::vertical([Constraint::Fill(1), Constraint::Fill(1)]).split(layout[2]);
+        FleetStatusWidget::new(locale.text(Text::EnemyFleet), &self.0.computer_fleet)
+            .render(sidebar[0], buf);
+        FleetStatusWidget::new(locale.text(Text::YourFleet), &self.0.human_fleet)
+            .render(sidebar[1], buf);
+
         if let Some(player1_won) = self.0.player1_won {
             let popup_area = Rect {
                 x: area.width / 4,
@@ -140,24 +1054,170 @@ impl<'state> Widget for BattleWidget<'state> {
                 height: area.height / 3,
             };
             Clear.render(popup_area, buf);
-            let bad_popup = Paragraph::new(if player1_won {
-                Span::raw("You WIN!!!").bold()
+            let message = self.final_message();
+            let draw = self.0.final_turn.as_ref().is_some_and(|turn| turn.draw);
+            let result = if draw {
+                Span::raw(locale.text(Text::Draw)).bold()
+            } else if player1_won {
+                Span::raw(format!("{message}{}", locale.text(Text::YouWin))).bold()
             } else {
-                Span::raw("You lose! :(").bold()
-            })
-            .wrap(Wrap { trim: true })
-            .style(Style::new().black())
-            .centered()
-            .block(
-                Block::new()
-                    .title("Match Over!")
-                    .title_style(Style::new().black().bold())
-                    .borders(Borders::ALL)
-                    .border_style(Style::new().red())
-                    .on_white(),
-            );
+                Span::raw(format!("{message}{}", locale.text(Text::YouLose))).bold()
+            };
+            let mut lines = vec![Line::from(result)];
+            if let Some((human, computer)) = self.0.ammo {
+                lines.push(Line::from(
+                    locale.format(Text::ShellsLeft, &[&human, &computer]),
+                ));
+            }
+            lines.push(Line::from(""));
+            lines.push(Line::from(self.series_message()));
+            let bad_popup = Paragraph::new(lines)
+                .wrap(Wrap { trim: true })
+                .style(Style::new().black())
+                .centered()
+                .block(
+                    Block::new()
+                        .title(locale.text(Text::MatchOver))
+                        .title_style(Style::new().black().bold())
+                        .borders(Borders::ALL)
+                        .border_style(Style::new().red())
+                        .on_white(),
+                );
 
             bad_popup.render(popup_area, buf);
         }
     }
 }
+
+// The weapons the player can switch between during the battle.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Weapon {
+    Shot,
+    Bombard,
+    Scan,
+    Relocate,
+}
+
+impl Weapon {
+    const ALL: [Weapon; 4] = [
+        Weapon::Shot,
+        Weapon::Bombard,
+        Weapon::Scan,
+        Weapon::Relocate,
+    ];
+
+    // Returns the action of the weapon aimed at the cell. The orientation is the one of the
+    // relocated Submarine.
+    fn action(self, cell: Cell, orientation: ShipOrientation) -> PlayerAction {
+        match self {
+            Weapon::Shot => PlayerAction::Shot(cell),
+            Weapon::Bombard => PlayerAction::Bombard(cell),
+            Weapon::Scan => PlayerAction::Scan(cell),
+            Weapon::Relocate => PlayerAction::Relocate(cell, orientation),
+        }
+    }
+
+    fn is_available(self, power_ups: PowerUps, relocations: u8) -> bool {
+        match self {
+            Weapon::Relocate => relocations > 0,
+            _ => power_ups.allows(&self.action(Cell::bounded(0, 0), ShipOrientation::Horizontal)),
+        }
+    }
+
+    // Returns the next weapon which can be used, back to the single shot after the last one.
+    fn next(self, power_ups: PowerUps, relocations: u8) -> Weapon {
+        let position = Self::ALL
+            .iter()
+            .position(|weapon| *weapon == self)
+            .unwrap_or(0);
+        Self::ALL
+            .into_iter()
+            .cycle()
+            .skip(position + 1)
+            .find(|weapon| weapon.is_available(power_ups, relocations))
+            .unwrap_or(Weapon::Shot)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::fleet::{Fleet, tests::fixed_fleet};
+    use crate::engine::game::Difficulty;
+    use rstest::rstest;
+
+    fn accessible(notation: CoordinateFormat) -> BattleStateModel {
+        BattleStateModel::new(&Config {
+            accessibility: true,
+            coordinate_format: notation,
+            ..Config::default()
+        })
+    }
+
+    #[rstest]
+    #[case('1', Some(BattleStateModel::SUSPECTED))]
+    #[case('2', Some(BattleStateModel::RULED_OUT))]
+    #[case('0', None)]
+    fn test_notes_in_accessibility_mode(#[case] key: char, #[case] expected: Option<char>) {
+        let mut model = accessible(CoordinateFormat::Letter);
+        let cell = *model.opponent_grid.cursor().unwrap();
+        model.notes.insert(cell, BattleStateModel::RULED_OUT);
+
+        model.handle_key_events(KeyEvent::from(KeyCode::Char(key)));
+
+        assert!(!model.is_typing());
+        assert_eq!(model.notes.get(&cell).copied(), expected);
+    }
+
+    #[rstest]
+    #[case("2-7")]
+    #[case("b7")]
+    fn test_type_coordinates(#[case] typed: &str) {
+        let mut model = accessible(CoordinateFormat::Numeric);
+
+        for c in typed.chars() {
+            model.handle_key_events(KeyEvent::from(KeyCode::Char(c)));
+        }
+        assert!(model.is_typing());
+        model.handle_key_events(KeyEvent::from(KeyCode::Enter));
+
+        assert!(!model.is_typing());
+        assert_eq!(model.opponent_grid.cursor(), Some(&Cell::bounded(1, 6)));
+        assert!(model.player1_has_shot);
+        assert!(model.notes.is_empty());
+    }
+
+    #[rstest]
+    fn test_update_the_shot_cells(fixed_fleet: Fleet) {
+        let mut series = Series::new(3, "Human", Difficulty::Easy);
+        series.start_game(fixed_fleet).unwrap();
+        let mut model = BattleStateModel::new(&Config::default());
+        model.update_series(&series);
+
+        // A cell which is not shot in the turn is left as it is.
+        let mut marked = Grid::default();
+        marked.mark(&Cell::bounded(9, 9), CellState::Hit);
+        model
+            .opponent_grid
+            .apply(GridDelta::between(&Grid::default(), &marked));
+        series.game_mut().play_turn(&Cell::bounded(4, 4)).unwrap();
+        model.refresh(&series);
+        let shots = series.game().human().unwrap().shots_grid();
+        assert_eq!(
+            model.opponent_grid.at(&Cell::bounded(4, 4)),
+            shots.at(&Cell::bounded(4, 4))
+        );
+        assert_eq!(
+            model.opponent_grid.at(&Cell::bounded(9, 9)),
+            &CellState::Hit
+        );
+
+        // Moving the cursor only draws the overlays again.
+        model.handle_key_events(KeyEvent::from(KeyCode::Right));
+        model.refresh(&series);
+        assert_eq!(
+            model.opponent_grid.at(&Cell::bounded(9, 9)),
+            &CellState::Hit
+        );
+    }
+}
@@ -0,0 +1,158 @@
+use crate::config::CampaignProgress;
+use crate::engine::campaign::Campaign;
+use crate::engine::series::Series;
+use crate::tui::{state::StateModel, widgets::toast::Notifications};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    prelude::{Line, Span, Stylize, Text, Widget},
+    symbols::border,
+    widgets::{Block, Paragraph},
+};
+
+/// Model for the campaign selection state.
+///
+/// The model lists the campaigns with the progress of the player, which is loaded when the
+/// screen is opened. When the user picks a campaign, the application starts its next battle.
+pub struct CampaignStateModel {
+    progress: CampaignProgress,
+    selected: usize,
+    chosen: Option<(Campaign, usize)>,
+    message: Option<String>,
+}
+
+impl CampaignStateModel {
+    /// Creates a new campaign model with the progress saved so far.
+    ///
+    /// If the progress can't be loaded, every campaign starts from its first battle and the
+    /// error is shown.
+    pub fn new() -> Self {
+        let (progress, message) = match CampaignProgress::load() {
+            Ok(progress) => (progress, None),
+            Err(e) => (CampaignProgress::default(), Some(e.to_string())),
+        };
+
+        Self {
+            progress,
+            selected: 0,
+            chosen: None,
+            message,
+        }
+    }
+
+    /// Returns the campaign chosen by the user, with the position of the battle to play.
+    pub fn chosen(&self) -> Option<(Campaign, usize)> {
+        self.chosen
+    }
+
+    fn campaign(&self) -> Campaign {
+        Campaign::ALL[self.selected]
+    }
+
+    fn reset(&mut self) {
+        let campaign = self.campaign();
+        self.progress.reset(campaign.id);
+        self.message = Some(match self.progress.save() {
+            Ok(()) => format!("{} starts again", campaign.name),
+            Err(e) => e.to_string(),
+        });
+    }
+}
+
+impl Default for CampaignStateModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateModel for CampaignStateModel {
+    fn handle_key_events(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(Campaign::ALL.len() - 1),
+            KeyCode::Enter => {
+                let campaign = self.campaign();
+                let won = self.progress.won(campaign.id);
+                self.chosen = Some((campaign, campaign.next_battle(won)));
+            }
+            KeyCode::Char(c) if c.eq_ignore_ascii_case(&'r') => self.reset(),
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, _series: &mut Series, _notifications: &mut Notifications) {}
+
+    fn widget(&self) -> impl Widget {
+        CampaignWidget(self)
+    }
+}
+
+/// Widget for the campaign selection state.
+pub struct CampaignWidget<'state>(&'state CampaignStateModel);
+
+impl<'state> CampaignWidget<'state> {
+    // Shows the campaign with its progress and, when it is selected, its battles.
+    fn campaign_lines(&self, index: usize, campaign: Campaign) -> Vec<Line<'state>> {
+        let won = self.0.progress.won(campaign.id);
+        let progress = if campaign.is_complete(won) {
+            Span::raw("complete").green().bold()
+        } else {
+            Span::raw(format!("{won}/{} battles won", campaign.battles.len())).yellow()
+        };
+        let title = Line::from(vec![
+            Span::raw(format!("{:<16}", campaign.name)).bold(),
+            progress,
+        ]);
+
+        if index != self.0.selected {
+            return vec![title.centered()];
+        }
+
+        let mut lines = vec![title.reversed().centered()];
+        for (i, battle) in campaign.battles.iter().enumerate() {
+            let handicap = match battle.handicap {
+                0 => String::new(),
+                cells => format!(", {cells} damaged cells"),
+            };
+            let line = Line::from(format!(
+                "{}. {} ({}{handicap})",
+                i + 1,
+                battle.name,
+                battle.difficulty
+            ));
+            lines.push(if i < won { line.gray() } else { line }.centered());
+        }
+
+        lines
+    }
+}
+
+impl<'state> Widget for CampaignWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from("Campaigns".bold()))
+            .border_set(border::THICK);
+
+        let mut lines = vec![Line::from("")];
+        for (i, campaign) in Campaign::ALL.into_iter().enumerate() {
+            lines.extend(self.campaign_lines(i, campaign));
+            lines.push(Line::from(""));
+        }
+        lines.extend([
+            Line::from("Use:").bold().centered(),
+            Line::from("- the up and down arrows: to choose a campaign").centered(),
+            Line::from("- Enter: to play its next battle").centered(),
+            Line::from("- r: to start it again").centered(),
+        ]);
+
+        if let Some(message) = &self.0.message {
+            lines.push(Line::from(""));
+            lines.push(Line::from(message.clone()).italic().centered());
+        }
+
+        Paragraph::new(Text::from(lines))
+            .block(block)
+            .render(area, buf);
+    }
+}
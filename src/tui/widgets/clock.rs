@@ -0,0 +1,53 @@
+use crate::engine::clock::Clock;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    prelude::{Line, Span, Stylize, Widget},
+};
+use std::time::{Duration, Instant};
+
+/// A widget that shows the time left to both sides, like a chess clock.
+///
+/// The running clock is highlighted, and a clock without time left is red.
+pub struct ClockWidget {
+    human: Clock,
+    computer: Clock,
+}
+
+impl ClockWidget {
+    /// Creates a new widget for the clocks of the human and the computer.
+    pub fn new(human: Clock, computer: Clock) -> Self {
+        Self { human, computer }
+    }
+
+    fn clock_spans(label: &str, clock: &Clock, now: Instant) -> Vec<Span<'static>> {
+        let remaining = clock.remaining(now);
+        let time = Span::raw(format!(" {} ", format_time(remaining)));
+        let time = if remaining.is_zero() {
+            time.red().bold()
+        } else if clock.is_running() {
+            time.black().on_yellow().bold()
+        } else {
+            time.bold()
+        };
+
+        vec![Span::raw(format!("{label} ")).gray(), time]
+    }
+}
+
+// Formats a duration as minutes and seconds, rounding up so that 0:00 means no time left.
+fn format_time(time: Duration) -> String {
+    let seconds = time.as_millis().div_ceil(1000);
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+impl Widget for ClockWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let now = Instant::now();
+        let mut spans = Self::clock_spans("You", &self.human, now);
+        spans.push(Span::raw("   "));
+        spans.extend(Self::clock_spans("Computer", &self.computer, now));
+
+        Line::from(spans).centered().render(area, buf);
+    }
+}
@@ -0,0 +1,51 @@
+use crate::engine::fleet::ShipStatus;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    prelude::{Line, Span, Stylize, Widget},
+    symbols::border,
+    widgets::{Block, Paragraph},
+};
+
+/// A widget that lists the ships of a fleet and how much damage they took.
+///
+/// Every ship is shown with its kind, its size and its status: afloat, damaged or sunk.
+pub struct FleetStatusWidget<'state> {
+    title: &'state str,
+    ships: &'state [ShipStatus],
+}
+
+impl<'state> FleetStatusWidget<'state> {
+    /// Creates a new widget for the given ships, framed in a block with the given title.
+    pub fn new(title: &'state str, ships: &'state [ShipStatus]) -> Self {
+        Self { title, ships }
+    }
+
+    fn ship_line(ship: &ShipStatus) -> Line<'state> {
+        let status = if ship.sunk {
+            Span::raw("sunk").red().bold()
+        } else if ship.is_damaged() {
+            Span::raw(format!("damaged {}/{}", ship.hits, ship.size)).yellow()
+        } else {
+            Span::raw("afloat").green()
+        };
+
+        Line::from(vec![
+            Span::raw(format!(" {:<17}", ship.kind)),
+            Span::raw(format!("[{}] ", ship.size)).gray(),
+            status,
+        ])
+    }
+}
+
+impl<'state> Widget for FleetStatusWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from(self.title.bold()))
+            .border_set(border::THICK);
+
+        let lines: Vec<Line> = self.ships.iter().map(Self::ship_line).collect();
+
+        Paragraph::new(lines).block(block).render(area, buf);
+    }
+}
@@ -1,22 +1,49 @@
-use crate::engine::fleet::Ship;
+use crate::config::Theme;
+use crate::engine::fleet::{Ship, ShipCells};
 use crate::engine::grid::{Cell, CellState, Grid};
 use ratatui::buffer::Buffer;
 use ratatui::layout::{Alignment, Constraint, Layout, Rect, Spacing};
-use ratatui::prelude::{Stylize, Widget};
+use ratatui::prelude::{Color, Stylize, Widget};
 use ratatui::symbols::merge::MergeStrategy;
 use ratatui::widgets::{Block, Paragraph};
+use std::collections::HashMap;
 
 pub enum Layer {
-    Ship(Ship),
+    /// The cells of a ship being placed, taken once when the layer is pushed.
+    Ship(ShipCells),
+    /// The cells of the selected ship.
+    Selection(ShipCells),
     Shots(Vec<Cell>),
+    Target(Vec<Cell>),
+    Scan(Vec<Cell>, bool),
+    /// The cells of the ships which have been sunk.
+    Sunk(Vec<Cell>),
+    Mines(Vec<Cell>),
+    /// The fogged cells, and the shots whose result is hidden by the fog.
+    Fog(Vec<Cell>, Vec<Cell>),
+    /// The cell suggested to the player.
+    Hint(Cell),
+    /// The cell of the last shot of the opponent, outlined.
+    LastShot(Cell),
+    /// A shot being resolved, a hit if the flag is set, at the given frame of its animation.
+    Impact(Cell, bool, usize),
+    /// A weight from 0 to 1 for the unexplored cells, like the probabilities of a strategy,
+    /// shaded from the water color to red.
+    Heat(HashMap<Cell, f32>),
+    /// Glyphs written on the cells, like the notes of the player, without changing their color.
+    Marks(Vec<(Cell, char)>),
 }
 
 impl Layer {
     fn apply<'block>(&self, cell: &Cell, state: &CellState, block: Block<'block>) -> Block<'block> {
         match self {
-            Self::Ship(ship) => match state {
-                CellState::Empty if ship.occupied_cells().contains(cell) => block.on_yellow(),
-                CellState::Occupied if ship.occupied_cells().contains(cell) => block.on_red(),
+            Self::Ship(cells) => match state {
+                CellState::Empty if cells.contains(cell) => block.on_yellow(),
+                CellState::Occupied if cells.contains(cell) => block.on_red(),
+                _ => block,
+            },
+            Self::Selection(cells) => match state {
+                CellState::Occupied if cells.contains(cell) => block.on_yellow(),
                 _ => block,
             },
             Self::Shots(cells) => match state {
@@ -24,13 +51,175 @@ impl Layer {
                 CellState::Occupied if cells.contains(cell) => block.on_red(),
                 _ => block,
             },
+            Self::Target(cells) => match state {
+                CellState::Empty if cells.contains(cell) => block.on_yellow(),
+                _ => block,
+            },
+            Self::Scan(cells, true) => match state {
+                CellState::Empty if cells.contains(cell) => block.on_green(),
+                _ => block,
+            },
+            Self::Scan(cells, false) => match state {
+                CellState::Empty if cells.contains(cell) => block.on_blue(),
+                _ => block,
+            },
+            Self::Sunk(cells) if cells.contains(cell) => block.on_red(),
+            Self::Sunk(_) => block,
+            Self::Mines(cells) => match state {
+                CellState::Empty | CellState::Miss if cells.contains(cell) => block.on_cyan(),
+                CellState::Occupied if cells.contains(cell) => block.on_red(),
+                _ => block,
+            },
+            Self::Fog(_, hidden) if hidden.contains(cell) => block.on_dark_gray(),
+            Self::Fog(cells, _) => match state {
+                CellState::Empty if cells.contains(cell) => block.on_gray(),
+                _ => block,
+            },
+            Self::Hint(hint) if hint == cell => block.on_light_green(),
+            Self::Hint(_) => block,
+            Self::LastShot(shot) if shot == cell => block.yellow().bold(),
+            Self::LastShot(_) => block,
+            Self::Impact(impact, hit, frame) if impact == cell => {
+                let frames = if *hit { &EXPLOSION } else { &SPLASH };
+                block.bg(frames[frame % frames.len()].0)
+            }
+            Self::Impact(..) => block,
+            Self::Heat(heat) => match (state, heat.get(cell)) {
+                (CellState::Empty | CellState::Occupied, Some(heat)) => block.bg(heat_color(*heat)),
+                _ => block,
+            },
+            Self::Marks(_) => block,
         }
     }
+
+    // Returns the symbol shown by the layer on the cell, if any.
+    fn symbol(&self, cell: &Cell) -> Option<char> {
+        match self {
+            Self::Fog(_, hidden) if hidden.contains(cell) => Some('?'),
+            Self::Hint(hint) if hint == cell => Some('*'),
+            Self::Impact(impact, hit, frame) if impact == cell => {
+                let frames = if *hit { &EXPLOSION } else { &SPLASH };
+                Some(frames[frame % frames.len()].1)
+            }
+            Self::Marks(marks) => marks
+                .iter()
+                .find(|(marked, _)| marked == cell)
+                .map(|(_, mark)| *mark),
+            _ => None,
+        }
+    }
+}
+
+/// The frames of the animation of a miss: the color and the symbol of the cell.
+const SPLASH: [(Color, char); 4] = [
+    (Color::White, '.'),
+    (Color::LightCyan, 'o'),
+    (Color::Cyan, 'O'),
+    (Color::LightBlue, '~'),
+];
+
+/// The frames of the animation of a hit: the color and the symbol of the cell.
+const EXPLOSION: [(Color, char); 4] = [
+    (Color::Yellow, '*'),
+    (Color::LightRed, '#'),
+    (Color::Yellow, '*'),
+    (Color::Red, '#'),
+];
+
+/// The steps the cursor of a grid can make.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum CursorMove {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// Returns the background color of a cell in the given state.
+fn cell_color(theme: Theme, state: &CellState) -> Color {
+    match (theme, state) {
+        (Theme::Classic, CellState::Empty) => Color::LightBlue,
+        (Theme::Classic, CellState::Occupied) => Color::LightGreen,
+        (Theme::Classic, CellState::Miss) => Color::LightCyan,
+        (Theme::Classic, CellState::Hit) => Color::LightRed,
+        (Theme::Classic, CellState::Blocked) => Color::LightYellow,
+        (Theme::HighContrast, CellState::Empty) => Color::Black,
+        (Theme::HighContrast, CellState::Occupied) => Color::White,
+        (Theme::HighContrast, CellState::Miss) => Color::Blue,
+        (Theme::HighContrast, CellState::Hit) => Color::Red,
+        (Theme::HighContrast, CellState::Blocked) => Color::DarkGray,
+    }
+}
+
+/// Returns the color of a cell with the given weight: the lowest weights barely tint the water,
+/// the highest ones are red.
+fn heat_color(heat: f32) -> Color {
+    const WATER: (f32, f32, f32) = (173.0, 216.0, 230.0);
+    const HOT: (f32, f32, f32) = (220.0, 20.0, 20.0);
+
+    let heat = heat.clamp(0.0, 1.0);
+    let mix = |water: f32, hot: f32| (water + (hot - water) * heat).round() as u8;
+    Color::Rgb(
+        mix(WATER.0, HOT.0),
+        mix(WATER.1, HOT.1),
+        mix(WATER.2, HOT.2),
+    )
+}
+
+/// Returns the background color of a wave on an empty cell, slightly different from the water.
+fn wave_color(theme: Theme) -> Color {
+    match theme {
+        Theme::Classic => Color::Rgb(150, 200, 235),
+        Theme::HighContrast => Color::Rgb(0, 0, 60),
+    }
+}
+
+/// The cells which differ between two grids, with their state in the newer one.
+///
+/// A delta is applied to a [GridModel] to update only the cells which have changed.
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct GridDelta(Vec<(Cell, CellState)>);
+
+impl GridDelta {
+    /// Returns the cells whose state in the new grid is different from the old one.
+    pub fn between(old: &Grid, new: &Grid) -> Self {
+        let states = [
+            CellState::Empty,
+            CellState::Occupied,
+            CellState::Miss,
+            CellState::Hit,
+            CellState::Blocked,
+        ];
+        let mut changed = states.iter().fold(0, |changed, state| {
+            changed | (old.bits(state) ^ new.bits(state))
+        });
+
+        let mut cells = Vec::with_capacity(changed.count_ones() as usize);
+        while changed != 0 {
+            let bit = changed.trailing_zeros() as u8;
+            let cell = Cell::bounded(bit % 10, bit / 10);
+            cells.push((cell, new.at(&cell).clone()));
+            changed &= changed - 1;
+        }
+
+        Self(cells)
+    }
+
+    /// Returns the given cells with their state in the new grid, like the cells shot in a turn,
+    /// without looking at the other cells.
+    pub fn of<I: IntoIterator<Item = Cell>>(cells: I, new: &Grid) -> Self {
+        Self(
+            cells
+                .into_iter()
+                .map(|cell| (cell, new.at(&cell).clone()))
+                .collect(),
+        )
+    }
 }
 
 /// The state for a grid widget
 ///
-/// Besides the grid itself, the model also keeps track of the cursor position and layers.
+/// Besides the grid itself, the model also keeps track of the cursor position, layers and theme.
 /// Cursor is a grid position used to highlight the current active cell.
 /// Layers are used to overlay additional information on the grid. Layers are designed as a stack:
 ///  the last pushed layer is rendered on top of all other layers. You can push or pop layers.
@@ -38,6 +227,9 @@ pub struct GridModel {
     grid: Grid,
     cursor: Option<Cell>,
     layers: Vec<Layer>,
+    theme: Theme,
+    wrap_around: bool,
+    waves: Option<usize>,
 }
 
 impl GridModel {
@@ -49,12 +241,53 @@ impl GridModel {
             grid,
             cursor: None,
             layers: Vec::new(),
+            theme: Theme::default(),
+            wrap_around: true,
+            waves: None,
         }
     }
 
+    /// Choose whether the cursor wraps around the edges of the grid.
+    ///
+    /// When wrap-around is disabled, the cursor stops on the edges instead. It is enabled by default.
+    pub fn set_wrap_around(&mut self, wrap_around: bool) {
+        self.wrap_around = wrap_around;
+    }
+
+    /// Set the color palette used to render this grid.
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Animates the water of the empty cells with waves, at the given frame, or stops them.
+    ///
+    /// The waves roll diagonally across the grid as the frame grows. They are drawn under the
+    /// layers.
+    pub fn set_waves(&mut self, frame: Option<usize>) {
+        self.waves = frame;
+    }
+
     /// Set a new cursor position for this grid.
     pub fn set_cursor(&mut self, p0: &Cell) {
-        self.cursor = Some(p0.clone());
+        self.cursor = Some(*p0);
+    }
+
+    /// Returns the state of the given cell of this grid.
+    pub fn at(&self, cell: &Cell) -> &CellState {
+        self.grid.at(cell)
+    }
+
+    /// Returns the grid drawn by this model, under its layers.
+    pub fn grid(&self) -> &Grid {
+        &self.grid
+    }
+
+    /// Updates the cells changed by the delta, keeping the cursor, the layers and the look of
+    /// the grid.
+    pub fn apply(&mut self, delta: GridDelta) {
+        for (cell, state) in delta.0 {
+            self.grid.mark(&cell, state);
+        }
     }
 
     /// Returns the cursor cell of this grid.
@@ -98,6 +331,21 @@ impl GridModel {
         self.layers.pop()
     }
 
+    /// Removes all the layers from the grid.
+    pub fn clear_layers(&mut self) {
+        self.layers.clear();
+    }
+
+    /// Returns how many layers are over the grid.
+    pub fn layer_count(&self) -> usize {
+        self.layers.len()
+    }
+
+    /// Removes the layers above the given number of bottom layers, which are kept.
+    pub fn truncate_layers(&mut self, len: usize) {
+        self.layers.truncate(len);
+    }
+
     /// Add a ship to the grid
     pub fn add_ship(&mut self, ship: &Ship) {
         self.grid.add_ship(ship);
@@ -115,6 +363,23 @@ impl GridModel {
             self.enable_cursor()
         }
     }
+
+    /// Moves the cursor one step in the given direction.
+    ///
+    /// On the edges, the cursor wraps around or stops, according to the wrap-around option.
+    pub fn step_cursor(&mut self, step: CursorMove) {
+        let wrap_around = self.wrap_around;
+        self.move_cursor(|cell| match (step, wrap_around) {
+            (CursorMove::Left, true) => cell.move_left(),
+            (CursorMove::Right, true) => cell.move_right(),
+            (CursorMove::Up, true) => cell.move_up(),
+            (CursorMove::Down, true) => cell.move_down(),
+            (CursorMove::Left, false) => _ = cell.try_move_left(),
+            (CursorMove::Right, false) => _ = cell.try_move_right(),
+            (CursorMove::Up, false) => _ = cell.try_move_up(),
+            (CursorMove::Down, false) => _ = cell.try_move_down(),
+        });
+    }
 }
 
 /// A widget that renders a grid.
@@ -133,16 +398,20 @@ impl<'app> GridWidget<'app> {
     }
 
     fn cell_block<'c>(&'app self, cell: &Cell, cell_block: Block<'c>) -> Block<'c> {
-        let block = match self.grid_model.grid.at(cell) {
-            CellState::Empty => cell_block.on_light_blue(),
-            CellState::Occupied => cell_block.on_light_green(),
-            CellState::Miss => cell_block.on_light_cyan(),
-            CellState::Hit => cell_block.on_light_red(),
+        let state = self.grid_model.grid.at(cell);
+        let wave = self.grid_model.waves.is_some_and(|frame| {
+            (cell.x() as usize + 2 * cell.y() as usize + 100 - frame % 100).is_multiple_of(5)
+        });
+        let color = match state {
+            CellState::Empty if wave => wave_color(self.grid_model.theme),
+            _ => cell_color(self.grid_model.theme, state),
         };
+        let block = cell_block.bg(color);
 
-        self.grid_model.layers.iter().fold(block, |block, layer| {
-            layer.apply(cell, self.grid_model.grid.at(cell), block)
-        })
+        self.grid_model
+            .layers
+            .iter()
+            .fold(block, |block, layer| layer.apply(cell, state, block))
     }
 }
 
@@ -182,6 +451,16 @@ impl<'app> Widget for &GridWidget<'app> {
                             && current_cell == *cursor
                         {
                             "X".to_string()
+                        } else if *self.grid_model.grid.at(&current_cell) == CellState::Blocked {
+                            "^".to_string()
+                        } else if let Some(symbol) = self
+                            .grid_model
+                            .layers
+                            .iter()
+                            .rev()
+                            .find_map(|layer| layer.symbol(&current_cell))
+                        {
+                            symbol.to_string()
                         } else {
                             String::new()
                         }
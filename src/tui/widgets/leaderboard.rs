@@ -0,0 +1,145 @@
+use crate::config::Leaderboard;
+use crate::engine::series::Series;
+use crate::tui::{state::StateModel, widgets::toast::Notifications};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    prelude::{Line, Stylize, Widget},
+    symbols::border,
+    widgets::{Block, Paragraph, Row, Table},
+};
+
+/// Model for the leaderboard state.
+///
+/// The model ranks the local players by their results, which are loaded when the screen is
+/// opened. The ranking can be exported as CSV with E.
+pub struct LeaderboardStateModel {
+    leaderboard: Leaderboard,
+    closed: bool,
+    message: Option<String>,
+}
+
+impl LeaderboardStateModel {
+    /// Creates a new leaderboard model with the results saved so far.
+    ///
+    /// If the results can't be loaded, the leaderboard is empty and the error is shown.
+    pub fn new() -> Self {
+        let (leaderboard, message) = match Leaderboard::load() {
+            Ok(leaderboard) => (leaderboard, None),
+            Err(e) => (Leaderboard::default(), Some(e.to_string())),
+        };
+
+        Self {
+            leaderboard,
+            closed: false,
+            message,
+        }
+    }
+
+    /// Returns `true` when the user has left the leaderboard screen.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    fn export(&mut self) {
+        self.message = Some(match self.leaderboard.export_csv() {
+            Ok(path) => format!("Exported to {}", path.display()),
+            Err(e) => e.to_string(),
+        });
+    }
+}
+
+impl Default for LeaderboardStateModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StateModel for LeaderboardStateModel {
+    fn handle_key_events(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Enter => self.closed = true,
+            KeyCode::Char(c) if c.eq_ignore_ascii_case(&'e') => self.export(),
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, _series: &mut Series, _notifications: &mut Notifications) {}
+
+    fn widget(&self) -> impl Widget {
+        LeaderboardWidget(self)
+    }
+}
+
+/// Widget for the leaderboard state.
+pub struct LeaderboardWidget<'state>(&'state LeaderboardStateModel);
+
+impl<'state> Widget for LeaderboardWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from("Leaderboard".bold()))
+            .border_set(border::THICK);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [table_area, help_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(4)]).areas(inner);
+
+        let rows =
+            self.0
+                .leaderboard
+                .ranking()
+                .into_iter()
+                .enumerate()
+                .map(|(i, (name, record))| {
+                    Row::new(vec![
+                        format!("{}", i + 1),
+                        name.to_string(),
+                        format!("{}", record.games),
+                        format!("{}", record.wins),
+                        format!("{:.0}%", record.win_rate() * 100.0),
+                        record
+                            .average_shots_to_win()
+                            .map(|shots| format!("{shots:.1}"))
+                            .unwrap_or_else(|| "-".to_string()),
+                    ])
+                });
+
+        Table::new(
+            rows,
+            [
+                Constraint::Length(4),
+                Constraint::Fill(1),
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Length(9),
+                Constraint::Length(14),
+            ],
+        )
+        .header(
+            Row::new(vec![
+                "#",
+                "Player",
+                "Games",
+                "Wins",
+                "Win rate",
+                "Shots to win",
+            ])
+            .yellow()
+            .bold(),
+        )
+        .render(table_area, buf);
+
+        let mut lines = vec![
+            Line::from(""),
+            Line::from("- E: to export the leaderboard as CSV").centered(),
+            Line::from("- Enter or Esc: to go back").centered(),
+        ];
+        if let Some(message) = &self.0.message {
+            lines[0] = Line::from(message.clone()).italic().centered();
+        }
+
+        Paragraph::new(lines).render(help_area, buf);
+    }
+}
@@ -0,0 +1,849 @@
+use crate::bot::{BotEvent, ShotResult};
+use crate::config::Config;
+use crate::engine::fleet::Fleet;
+use crate::engine::game::Side;
+use crate::engine::grid::{Cell, CellState, Grid};
+use crate::engine::placement::Placement;
+use crate::engine::series::Series;
+use crate::net::{
+    ChatMessage, ChatRequest, Client, ClientError, GameState, Hello, JoinRequest, MatchRequest,
+    NewGame, OpenGame, Opponent, Phase, Placements, Session, ShotRequest, Shots, TurnResult,
+    Welcome,
+};
+use crate::tui::{
+    state::StateModel,
+    widgets::{
+        grid::{CursorMove, GridModel, Layer},
+        toast::Notifications,
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    prelude::{Line, Span, Stylize, Text, Widget},
+    symbols::border,
+    widgets::{Block, Paragraph, Wrap},
+};
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+use strum::IntoEnumIterator;
+use tracing::{info, warn};
+
+// The answer of the server to a request of the model.
+enum Reply {
+    Welcome(Welcome),
+    Games(Vec<OpenGame>),
+    Joined(Client, GameState),
+    Rejoined(Option<GameState>),
+    State(GameState),
+    Turn(TurnResult),
+    Update(Option<GameState>, Vec<ChatMessage>, bool),
+    Said(ChatMessage),
+}
+
+// A request to the server, run on another thread with the client of the model.
+type Request = Box<dyn FnOnce(Client) -> Result<Reply, ClientError> + Send>;
+
+/// Model for the network state, where the player plays a duel against someone else through the
+/// server of the configuration.
+///
+/// The state opens on the lobby, which lists the games waiting for a second player, once the
+/// server has welcomed the client: only the games of the variants both can play are listed. The
+/// player joins one of them, opens a new one with the configured variant, or asks the server to
+/// pair them with an opponent. Then the fleet is deployed, one placement strategy after the other,
+/// and the battle is played like against the computer, while the shots of the opponent are
+/// fetched from the server.
+///
+/// A duel left before its end is kept as the network [Session] of the player, who rejoins it the
+/// next time the state is opened on the same server. A request which fails, like when the
+/// connection drops, makes the next poll fetch the whole state of the game again.
+///
+/// Once in a game, T shows the chat with the opponent, and focuses its input line: Enter sends
+/// the message typed and Esc leaves the input, while T hides the chat again.
+///
+/// The requests are sent one at a time on another thread, so that the screen never waits for the
+/// network. The player leaves with Esc, or with Enter once the game is over.
+pub struct NetworkStateModel {
+    client: Client,
+    config: Config,
+    welcome: Option<Welcome>,
+    games: Vec<OpenGame>,
+    selected: usize,
+    game: Option<GameState>,
+    synced: Instant,
+    resync: bool,
+    fleet: Fleet,
+    placement: Placement,
+    targets: GridModel,
+    queue: VecDeque<Request>,
+    request: Option<Receiver<Result<Reply, ClientError>>>,
+    acting: bool,
+    polled: Option<Instant>,
+    chat: Vec<ChatMessage>,
+    chat_open: bool,
+    typing: Option<String>,
+    message: Option<String>,
+    closed: bool,
+}
+
+impl NetworkStateModel {
+    // How often the lobby is listed again.
+    const LOBBY_POLL: Duration = Duration::from_secs(3);
+    // How often the state of the game is fetched while the player waits for their opponent.
+    const GAME_POLL: Duration = Duration::from_secs(1);
+    // The entries of the lobby before its games.
+    const ACTIONS: usize = 2;
+
+    /// Creates a new network state on the lobby of the configured server, rejoining the duel
+    /// the player has left on it, if any.
+    pub fn new(config: &Config) -> Self {
+        let mut targets = GridModel::new(Grid::from_ships(&[]));
+        targets.set_theme(config.theme);
+        targets.enable_cursor();
+
+        let mut model = Self {
+            client: Self::client(config, None),
+            config: config.clone(),
+            welcome: None,
+            games: Vec::new(),
+            selected: 0,
+            game: None,
+            synced: Instant::now(),
+            resync: false,
+            fleet: Placement::Random.strategy(rand::random()).deploy(&[]),
+            placement: Placement::Random,
+            targets,
+            queue: VecDeque::new(),
+            request: None,
+            acting: false,
+            polled: None,
+            chat: Vec::new(),
+            chat_open: false,
+            typing: None,
+            message: None,
+            closed: false,
+        };
+        model.send(Self::hello());
+        let session = Session::path().and_then(|path| Session::load_from(&path).ok().flatten());
+        if let Some(session) = session.filter(|session| session.server == config.server) {
+            model.rejoin(&session);
+        }
+
+        model
+    }
+
+    // Returns a client of the configured server, with the token of the player if any, which
+    // encrypts its requests when the configuration gives the certificate of the server.
+    fn client(config: &Config, token: Option<&str>) -> Client {
+        let client = match token {
+            Some(token) => Client::with_token(&config.server, token),
+            None => Client::new(&config.server),
+        };
+        match &config.server_certificate {
+            Some(certificate) => client.with_certificate(certificate),
+            None => client,
+        }
+    }
+
+    // Says hello to the server, which tells what both can play.
+    fn hello() -> Request {
+        let hello = Hello::new(Config::BOARD_SIZE);
+        Box::new(move |client| Ok(Reply::Welcome(client.hello(&hello)?)))
+    }
+
+    /// Returns `true` when the player has left the network game.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns `true` while the player types a message of the chat.
+    pub fn is_typing(&self) -> bool {
+        self.typing.is_some()
+    }
+
+    /// Returns `true` once the player has joined a game.
+    pub fn is_playing(&self) -> bool {
+        self.game.is_some()
+    }
+
+    /// Returns the names of the players, once the player has joined a game.
+    pub fn title(&self) -> String {
+        match &self.game {
+            Some(GameState {
+                opponent_name: Some(opponent),
+                ..
+            }) => format!(" {} vs {opponent} ", self.config.player_name),
+            _ => " Network games ".to_string(),
+        }
+    }
+
+    /// Returns the turn of the game and who plays it, or how the game has ended.
+    pub fn status(&self) -> String {
+        let Some(game) = &self.game else {
+            return format!("Lobby of {}", self.config.server);
+        };
+        let opponent = game.opponent_name.as_deref().unwrap_or("your opponent");
+        match game.phase {
+            Phase::Setup if game.opponent_name.is_none() => {
+                "Waiting for an opponent to join".to_string()
+            }
+            Phase::Setup if game.fleet.is_some() => format!("Waiting for {opponent} to deploy"),
+            Phase::Setup => "Deploy your fleet".to_string(),
+            Phase::InProgress if game.your_turn => {
+                format!("Turn {} — your turn ({})", game.turn, self.clocks(game))
+            }
+            Phase::InProgress => {
+                format!(
+                    "Turn {} — {opponent} is aiming ({})",
+                    game.turn,
+                    self.clocks(game)
+                )
+            }
+            Phase::Finished if game.winner == Some(Side::Human) => {
+                "You win! Enter to leave".to_string()
+            }
+            Phase::Finished => format!("{opponent} wins, Enter to leave"),
+            Phase::Drawn => "Draw, Enter to leave".to_string(),
+        }
+    }
+
+    // Tells the time spent by both players, counting the current turn since the state has been
+    // received.
+    fn clocks(&self, game: &GameState) -> String {
+        let elapsed = self.synced.elapsed().as_secs();
+        let (yours, opponent) = match game.your_turn {
+            true => (game.clocks.yours + elapsed, game.clocks.opponent),
+            false => (game.clocks.yours, game.clocks.opponent + elapsed),
+        };
+        let clock = |secs: u64| format!("{}:{:02}", secs / 60, secs % 60);
+
+        format!(
+            "you {}, {} {}",
+            clock(yours),
+            game.opponent_name.as_deref().unwrap_or("opponent"),
+            clock(opponent)
+        )
+    }
+
+    // Queues an action of the player. The keys of the actions are ignored until it is answered.
+    fn send<F>(&mut self, request: F)
+    where
+        F: FnOnce(Client) -> Result<Reply, ClientError> + Send + 'static,
+    {
+        self.acting = true;
+        self.queue.push_back(Box::new(request));
+    }
+
+    // Sends the next queued request to the server on another thread, once the last one is
+    // answered. The answer is received by the update.
+    fn dispatch(&mut self) {
+        if self.request.is_some() {
+            return;
+        }
+        let Some(request) = self.queue.pop_front() else {
+            return;
+        };
+        let (sender, receiver) = mpsc::channel();
+        let client = self.client.clone();
+        // The messages of the request are logged within the span of the application.
+        let span = tracing::Span::current();
+        thread::spawn(move || {
+            let _span = span.entered();
+            let _ = sender.send(request(client));
+        });
+        self.request = Some(receiver);
+        self.polled = Some(Instant::now());
+    }
+
+    // Rejoins the duel of the session. The server answers 404 once the duel has been dropped.
+    fn rejoin(&mut self, session: &Session) {
+        self.client = Self::client(&self.config, Some(&session.token));
+        self.message = Some(format!("Rejoining game {}...", session.id));
+        self.send(|client| match client.rejoin() {
+            Ok(state) => Ok(Reply::Rejoined(Some(state))),
+            Err(ClientError::Api { status: 404, .. }) => Ok(Reply::Rejoined(None)),
+            Err(e) => Err(e),
+        });
+    }
+
+    // Keeps the duel joined as the session of the player, or forgets it once it is over.
+    fn keep_session(&mut self) {
+        let Some(path) = Session::path() else {
+            return;
+        };
+        let session = match (&self.game, self.client.token()) {
+            (Some(game), Some(token)) if matches!(game.phase, Phase::Setup | Phase::InProgress) => {
+                Some(Session {
+                    server: self.config.server.clone(),
+                    id: game.id,
+                    token: token.to_string(),
+                })
+            }
+            _ => None,
+        };
+        if let Err(e) = match session {
+            Some(session) => session.save_to(&path),
+            None => Session::remove_from(&path),
+        } {
+            self.message = Some(e.to_string());
+        }
+    }
+
+    // Acts on the selected entry of the lobby: pairs the player, opens a new game or joins it.
+    fn choose(&mut self) {
+        let Some(welcome) = &self.welcome else {
+            return;
+        };
+        if self.selected < Self::ACTIONS && !welcome.variants.contains(&self.config.variant) {
+            self.message = Some(format!(
+                "{} can't be played on this server",
+                self.config.variant
+            ));
+            return;
+        }
+        let name = Some(self.config.player_name.clone());
+        let variant = Some(self.config.variant);
+        match self.selected.checked_sub(Self::ACTIONS) {
+            None if self.selected == 0 => self.send(move |mut client| {
+                let state = client.find_match(&MatchRequest { name, variant })?;
+                Ok(Reply::Joined(client, state))
+            }),
+            None => self.send(move |mut client| {
+                let state = client.create(&NewGame {
+                    name,
+                    opponent: Opponent::Human,
+                    variant,
+                    ..NewGame::default()
+                })?;
+                Ok(Reply::Joined(client, state))
+            }),
+            Some(index) => {
+                let Some(id) = self.games.get(index).map(|game| game.id) else {
+                    return;
+                };
+                self.send(move |mut client| {
+                    let state = client.join(id, &JoinRequest { name })?;
+                    Ok(Reply::Joined(client, state))
+                })
+            }
+        }
+    }
+
+    // Deploys the fleet with the placement strategy after the last one used.
+    fn auto_place(&mut self) {
+        self.placement = Placement::iter()
+            .skip_while(|placement| *placement != self.placement)
+            .nth(1)
+            .unwrap_or(Placement::Random);
+        self.fleet = self.placement.strategy(rand::random()).deploy(&[]);
+    }
+
+    // Sends the deployed fleet to the server.
+    fn confirm_fleet(&mut self, id: u64) {
+        let placements = Placements {
+            fleet: self.fleet.to_code(),
+        };
+        self.send(move |client| Ok(Reply::State(client.place(id, &placements)?)));
+    }
+
+    // Fires at the cell under the cursor, if it has not been shot yet.
+    fn fire(&mut self, id: u64) {
+        let Some(cell) = self.targets.cursor().copied() else {
+            return;
+        };
+        if self.targets.at(&cell) != &CellState::Empty {
+            return;
+        }
+        let shot = ShotRequest {
+            cell: cell.to_string(),
+        };
+        self.send(move |client| Ok(Reply::Turn(client.shoot(id, &shot)?)));
+    }
+
+    // Queues a message of the chat, to be sent between the other requests.
+    fn say(&mut self, text: String) {
+        let Some(id) = self.game.as_ref().map(|game| game.id) else {
+            return;
+        };
+        self.queue.push_back(Box::new(move |client| {
+            Ok(Reply::Said(client.say(id, &ChatRequest { text })?))
+        }));
+    }
+
+    // Asks the server what has changed: the games of the lobby, or the new messages of the chat
+    // and, while the player waits for their opponent, the state of the game. Otherwise, the hash
+    // of the battle tells whether the last state received is still the one of the server.
+    fn poll(&mut self) {
+        let elapsed = |period| self.polled.is_none_or(|polled| polled.elapsed() >= period);
+        if self.request.is_some() || !self.queue.is_empty() {
+            return;
+        }
+        let request: Request = match &self.game {
+            None if elapsed(Self::LOBBY_POLL) => match self.welcome {
+                Some(_) => Box::new(|client| Ok(Reply::Games(client.games()?))),
+                None => Self::hello(),
+            },
+            Some(game) if elapsed(Self::GAME_POLL) => {
+                let id = game.id;
+                let waiting = Self::is_waiting(game) || self.resync;
+                let after = self.chat.last().map_or(0, |message| message.seq);
+                let known = game.hash.clone();
+                Box::new(move |client| {
+                    let (state, desynced) = match waiting {
+                        true => (Some(client.state(id)?), false),
+                        false => match client.state_hash(id) {
+                            Ok(hash) => (None, hash.hash != known),
+                            // A server older than the hashes can't tell.
+                            Err(ClientError::Api { status: 404, .. }) => (None, false),
+                            Err(e) => return Err(e),
+                        },
+                    };
+                    Ok(Reply::Update(state, client.chat(id, after)?, desynced))
+                })
+            }
+            _ => return,
+        };
+        self.queue.push_back(request);
+    }
+
+    // Returns `true` while the game waits for the opponent.
+    fn is_waiting(game: &GameState) -> bool {
+        match game.phase {
+            Phase::Setup => game.opponent_name.is_none() || game.fleet.is_some(),
+            Phase::InProgress => !game.your_turn,
+            Phase::Finished | Phase::Drawn => false,
+        }
+    }
+
+    fn receive(&mut self, reply: Reply, notifications: &mut Notifications) {
+        match reply {
+            Reply::Welcome(welcome) => self.welcome = Some(welcome),
+            Reply::Games(mut games) => {
+                if let Some(welcome) = &self.welcome {
+                    games.retain(|game| welcome.variants.contains(&game.variant));
+                }
+                self.games = games;
+                self.selected = self.selected.min(self.games.len() + Self::ACTIONS - 1);
+            }
+            Reply::Joined(client, state) => {
+                self.client = client;
+                self.update_game(state, notifications);
+            }
+            Reply::Rejoined(Some(state)) => {
+                info!(id = state.id, "game rejoined");
+                notifications.push(format!("Game {} rejoined", state.id));
+                self.update_game(state, notifications);
+            }
+            Reply::Rejoined(None) => {
+                self.client = Self::client(&self.config, None);
+                self.message = Some("Your last game is no longer on the server".to_string());
+                self.keep_session();
+            }
+            Reply::State(state) => self.update_game(state, notifications),
+            Reply::Turn(result) => {
+                for event in &result.shots {
+                    if let BotEvent::Shot {
+                        cell, result, ship, ..
+                    } = event
+                    {
+                        notifications.push(match (result, ship) {
+                            (ShotResult::Sunk, Some(ship)) => format!("{cell}: {ship} sunk!"),
+                            (ShotResult::Hit | ShotResult::Sunk, _) => format!("{cell}: hit!"),
+                            _ => format!("{cell}: miss"),
+                        });
+                    }
+                }
+                self.update_game(result.state, notifications);
+            }
+            Reply::Update(state, messages, desynced) => {
+                if let Some(state) = state {
+                    self.update_game(state, notifications);
+                }
+                if desynced {
+                    warn!("the game differs from the one of the server, resyncing");
+                    self.resync = true;
+                }
+                for message in messages {
+                    self.keep_message(message, notifications);
+                }
+            }
+            Reply::Said(message) => self.keep_message(message, notifications),
+        }
+    }
+
+    // Adds a message to the chat, unless it is already there. The messages of the opponent are
+    // notified while the chat is hidden.
+    fn keep_message(&mut self, message: ChatMessage, notifications: &mut Notifications) {
+        if self.chat.iter().any(|known| known.seq == message.seq) {
+            return;
+        }
+        if !message.yours && !self.chat_open {
+            notifications.push(format!("{}: {}", message.sender, message.text));
+        }
+        let index = self.chat.partition_point(|known| known.seq < message.seq);
+        self.chat.insert(index, message);
+    }
+
+    // Edits the message typed in the chat, sending it with Enter.
+    fn type_key(&mut self, key_event: KeyEvent) {
+        let Some(text) = &mut self.typing else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Char(c) if text.chars().count() < ChatRequest::MAX_LENGTH => text.push(c),
+            KeyCode::Backspace => {
+                text.pop();
+            }
+            KeyCode::Enter if !text.trim().is_empty() => {
+                let text = std::mem::take(text);
+                self.say(text);
+            }
+            KeyCode::Esc => self.typing = None,
+            _ => {}
+        }
+    }
+
+    // Keeps the new state of the game, telling the player where their opponent has fired.
+    fn update_game(&mut self, state: GameState, notifications: &mut Notifications) {
+        if let (Some(game), Some(opponent)) = (&self.game, &state.opponent_name) {
+            let fired = |shots: &Shots, cell: &Cell| {
+                shots.hits.contains(cell) || shots.misses.contains(cell)
+            };
+            for cell in &state.opponent_shots.hits {
+                if !fired(&game.opponent_shots, cell) {
+                    notifications.push(format!("{opponent} hits {cell}"));
+                }
+            }
+            for cell in &state.opponent_shots.misses {
+                if !fired(&game.opponent_shots, cell) {
+                    notifications.push(format!("{opponent} misses {cell}"));
+                }
+            }
+        }
+
+        let cursor = self.targets.cursor().copied();
+        self.targets = GridModel::new(shots_grid(&state.shots));
+        self.targets.set_theme(self.config.theme);
+        self.targets.enable_cursor();
+        if let Some(cursor) = cursor {
+            self.targets.set_cursor(&cursor);
+        }
+        if let Some(fleet) = state
+            .fleet
+            .as_deref()
+            .and_then(|code| Fleet::from_code(code).ok())
+        {
+            self.fleet = fleet;
+        }
+        let changed = self.game.as_ref().map(|game| game.phase) != Some(state.phase);
+        self.game = Some(state);
+        self.synced = Instant::now();
+        self.resync = false;
+        if changed {
+            self.keep_session();
+        }
+    }
+
+    // Returns the grid of the fleet of the player, with the shots fired at it by the opponent.
+    fn fleet_grid(&self) -> GridModel {
+        let mut grid = GridModel::new(Grid::from_ships(self.fleet.as_ref()));
+        grid.set_theme(self.config.theme);
+        if let Some(game) = &self.game {
+            let shots = &game.opponent_shots;
+            grid.push_layer(Layer::Shots(
+                shots.hits.iter().chain(&shots.misses).copied().collect(),
+            ));
+        }
+
+        grid
+    }
+}
+
+// Marks the shots of the player on an empty grid.
+fn shots_grid(shots: &Shots) -> Grid {
+    let mut grid = Grid::from_ships(&[]);
+    for cell in &shots.hits {
+        grid.mark(cell, CellState::Hit);
+    }
+    for cell in &shots.misses {
+        grid.mark(cell, CellState::Miss);
+    }
+
+    grid
+}
+
+impl StateModel for NetworkStateModel {
+    fn handle_key_events(&mut self, key_event: KeyEvent) {
+        if self.typing.is_some() {
+            self.type_key(key_event);
+            return;
+        }
+        match key_event.code {
+            KeyCode::Esc => {
+                self.closed = true;
+                return;
+            }
+            KeyCode::Char('t' | 'T') if self.game.is_some() => {
+                self.chat_open = !self.chat_open;
+                self.typing = self.chat_open.then(String::new);
+                return;
+            }
+            _ => {}
+        }
+        // The actions wait for the answer to the last one.
+        if self.acting {
+            return;
+        }
+
+        let auto_place = self.config.keybindings.auto_place;
+        match self.game.as_ref().map(|game| (game.id, game.phase)) {
+            None => match key_event.code {
+                KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+                KeyCode::Down => {
+                    self.selected = (self.selected + 1).min(self.games.len() + Self::ACTIONS - 1)
+                }
+                KeyCode::Enter => self.choose(),
+                _ => {}
+            },
+            Some((id, Phase::Setup)) if self.game.as_ref().is_some_and(|g| g.fleet.is_none()) => {
+                match key_event.code {
+                    KeyCode::Char(c) if c.eq_ignore_ascii_case(&auto_place) => self.auto_place(),
+                    KeyCode::Enter => self.confirm_fleet(id),
+                    _ => {}
+                }
+            }
+            Some((id, Phase::InProgress)) => match key_event.code {
+                KeyCode::Left => self.targets.step_cursor(CursorMove::Left),
+                KeyCode::Right => self.targets.step_cursor(CursorMove::Right),
+                KeyCode::Up => self.targets.step_cursor(CursorMove::Up),
+                KeyCode::Down => self.targets.step_cursor(CursorMove::Down),
+                KeyCode::Enter if self.game.as_ref().is_some_and(|g| g.your_turn) => self.fire(id),
+                _ => {}
+            },
+            Some((_, Phase::Finished | Phase::Drawn)) if key_event.code == KeyCode::Enter => {
+                self.closed = true
+            }
+            Some(_) => {}
+        }
+    }
+
+    fn update(&mut self, _series: &mut Series, notifications: &mut Notifications) {
+        if let Some(request) = &self.request {
+            match request.try_recv() {
+                Ok(Ok(reply)) => {
+                    self.request = None;
+                    self.message = None;
+                    self.receive(reply, notifications);
+                }
+                Ok(Err(e)) => {
+                    warn!(error = %e, "request failed, resyncing");
+                    self.request = None;
+                    self.resync = true;
+                    self.message = Some(e.to_string());
+                }
+                Err(TryRecvError::Empty) => return,
+                Err(TryRecvError::Disconnected) => self.request = None,
+            }
+            self.acting = self.acting && !self.queue.is_empty();
+        }
+
+        self.poll();
+        self.dispatch();
+    }
+
+    fn widget(&self) -> impl Widget {
+        NetworkWidget(self)
+    }
+}
+
+/// Widget for the network state.
+pub struct NetworkWidget<'state>(&'state NetworkStateModel);
+
+impl<'state> NetworkWidget<'state> {
+    // Lists the actions and the open games, highlighting the selected one.
+    fn lobby_lines(&self) -> Vec<Line<'state>> {
+        let model = self.0;
+        let actions = [
+            "Find an opponent".to_string(),
+            format!("Open a new game ({})", model.config.variant),
+        ];
+        let games = model.games.iter().map(|game| {
+            format!(
+                "{:<24} {:<16} {:<18} {:>2}:{:02}",
+                game.title,
+                game.host,
+                game.variant.to_string(),
+                game.waiting / 60,
+                game.waiting % 60
+            )
+        });
+
+        let mut lines = vec![Line::from("")];
+        for (i, entry) in actions.into_iter().chain(games).enumerate() {
+            let line = match i {
+                _ if i == model.selected => Line::from(entry).reversed(),
+                i if i < NetworkStateModel::ACTIONS => Line::from(entry).bold(),
+                _ => Line::from(entry),
+            };
+            lines.push(line.centered());
+            if i + 1 == NetworkStateModel::ACTIONS {
+                lines.push(Line::from(""));
+            }
+        }
+        if model.games.is_empty() {
+            lines.push(
+                Line::from("No game is waiting for a second player")
+                    .italic()
+                    .centered(),
+            );
+        }
+        lines.extend([
+            Line::from(""),
+            Line::from("Use:").bold().centered(),
+            Line::from("- the up and down arrows: to choose a game").centered(),
+            Line::from("- Enter: to join it").centered(),
+            Line::from("- Esc: to leave").centered(),
+        ]);
+
+        lines
+    }
+
+    // Tells the player what to do while the fleets are being deployed.
+    fn setup_lines(&self, game: &GameState) -> Vec<Line<'state>> {
+        let model = self.0;
+        let mut lines = vec![Line::from("")];
+        match &game.opponent_name {
+            Some(opponent) => lines.push(Line::from(vec![
+                Span::raw("Playing against "),
+                Span::raw(opponent.clone()).yellow().bold(),
+            ])),
+            None => lines.push(Line::from("Waiting for an opponent to join...").italic()),
+        }
+        lines.push(Line::from(""));
+        if game.fleet.is_none() {
+            lines.extend([
+                Line::from(vec![
+                    Span::raw("Fleet deployed by the "),
+                    Span::raw(model.placement.to_string()).yellow().bold(),
+                    Span::raw(" placement"),
+                ]),
+                Line::from(""),
+                Line::from("Use:").bold(),
+                Line::from(format!(
+                    "- {}: to deploy it with the next placement",
+                    model.config.keybindings.auto_place
+                )),
+                Line::from("- Enter: to confirm it"),
+            ]);
+        }
+
+        lines.into_iter().map(Line::centered).collect()
+    }
+
+    // Shows the last messages of the chat which fit in the area, above the message being typed.
+    fn render_chat(&self, area: Rect, buf: &mut Buffer) {
+        let model = self.0;
+        let block = Block::bordered()
+            .title(Line::from("Chat".bold()))
+            .title_bottom(Line::from(" T to hide ").right_aligned())
+            .border_set(border::THICK);
+        let inner = block.inner(area);
+        block.render(area, buf);
+
+        let [history, input] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(inner);
+        let lines: Vec<Line> = model
+            .chat
+            .iter()
+            .map(|message| {
+                let sender = Span::raw(format!("{}: ", message.sender)).bold();
+                Line::from(vec![
+                    match message.yours {
+                        true => sender.cyan(),
+                        false => sender.yellow(),
+                    },
+                    Span::raw(message.text.clone()),
+                ])
+            })
+            .collect();
+        let skipped = lines.len().saturating_sub(history.height as usize);
+        Paragraph::new(Text::from(
+            lines.into_iter().skip(skipped).collect::<Vec<_>>(),
+        ))
+        .wrap(Wrap { trim: false })
+        .render(history, buf);
+
+        let line = match &model.typing {
+            Some(text) => Line::from(format!("> {text}_")),
+            None => Line::from("Press T twice to write a message").italic(),
+        };
+        line.render(input, buf);
+    }
+}
+
+impl<'state> Widget for NetworkWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let model = self.0;
+        let [content, message] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        if let Some(text) = &model.message {
+            Line::from(text.clone())
+                .italic()
+                .centered()
+                .render(message, buf);
+        }
+
+        let Some(game) = &model.game else {
+            let block = Block::bordered()
+                .title(Line::from(
+                    format!("Games on {}", model.config.server).bold(),
+                ))
+                .border_set(border::THICK);
+            Paragraph::new(Text::from(self.lobby_lines()))
+                .block(block)
+                .render(content, buf);
+            return;
+        };
+
+        let content = match model.chat_open {
+            true => {
+                let [content, chat] =
+                    Layout::vertical([Constraint::Fill(1), Constraint::Length(8)]).areas(content);
+                self.render_chat(chat, buf);
+                content
+            }
+            false => content,
+        };
+        let [left, right] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(content);
+        let fleet_block = Block::bordered()
+            .title(Line::from("Your fleet".bold()))
+            .border_set(border::THICK);
+        model
+            .fleet_grid()
+            .widget()
+            .render(fleet_block.inner(left), buf);
+        fleet_block.render(left, buf);
+
+        match game.phase {
+            Phase::Setup => {
+                let block = Block::bordered()
+                    .title(Line::from("Setup".bold()))
+                    .border_set(border::THICK);
+                Paragraph::new(Text::from(self.setup_lines(game)))
+                    .block(block)
+                    .render(right, buf);
+            }
+            _ => {
+                let block = Block::bordered()
+                    .title(Line::from("Enemy waters".bold()))
+                    .border_set(border::THICK);
+                model.targets.widget().render(block.inner(right), buf);
+                block.render(right, buf);
+            }
+        }
+    }
+}
@@ -0,0 +1,236 @@
+use crate::config::{Config, Theme};
+use crate::engine::fleet::ShipStatus;
+use crate::engine::grid::{Cell, Grid};
+use crate::engine::puzzle::Puzzle;
+use crate::engine::series::Series;
+use crate::tui::{
+    state::StateModel,
+    widgets::{
+        fleet::FleetStatusWidget,
+        grid::{CursorMove, GridModel},
+        toast::Notifications,
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    prelude::{Line, Span, Stylize, Text, Widget},
+    symbols::border,
+    widgets::{Block, Paragraph},
+};
+use std::cmp::Ordering;
+
+/// Model for the puzzle state.
+///
+/// The player shoots at a hidden fleet until it is sunk. A new random puzzle can be started with
+/// N, and a shared one can be played by typing its code after C.
+pub struct PuzzleStateModel {
+    puzzle: Puzzle,
+    grid: GridModel,
+    fleet: Vec<ShipStatus>,
+    theme: Theme,
+    code: Option<String>,
+    message: Option<String>,
+}
+
+impl PuzzleStateModel {
+    const MAX_CODE_LENGTH: usize = 13;
+
+    /// Creates a new model for a random puzzle, with the grid drawn in the configured theme.
+    pub fn new(config: &Config) -> Self {
+        let mut model = Self {
+            puzzle: Puzzle::random(),
+            grid: GridModel::new(Grid::default()),
+            fleet: Vec::new(),
+            theme: config.theme,
+            code: None,
+            message: None,
+        };
+        model.grid.enable_cursor();
+        model.update_grid();
+
+        model
+    }
+
+    /// Returns `true` when the user is typing the code of a puzzle.
+    pub fn is_typing(&self) -> bool {
+        self.code.is_some()
+    }
+
+    // Redraws the grid with the shots fired so far, keeping the cursor where it was.
+    fn update_grid(&mut self) {
+        let cursor = self.grid.cursor().copied().unwrap_or(Cell::bounded(0, 0));
+        self.grid = GridModel::new(self.puzzle.shots_grid().clone());
+        self.grid.set_theme(self.theme);
+        self.grid.set_cursor(&cursor);
+        self.fleet = self.puzzle.fleet().status();
+    }
+
+    fn start(&mut self, puzzle: Puzzle) {
+        self.puzzle = puzzle;
+        self.message = None;
+        self.update_grid();
+    }
+
+    fn shoot(&mut self) {
+        let cell = *self.grid.cursor().unwrap();
+        self.message = self.puzzle.shoot(&cell).err().map(|e| e.to_string());
+        self.update_grid();
+    }
+
+    // Edits the code of the puzzle to play, and starts it on Enter.
+    fn handle_code_events(&mut self, key_event: KeyEvent) {
+        let Some(code) = self.code.as_mut() else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Char(c) if c.is_ascii_alphanumeric() && code.len() < Self::MAX_CODE_LENGTH => {
+                code.push(c.to_ascii_uppercase())
+            }
+            KeyCode::Backspace => _ = code.pop(),
+            KeyCode::Enter => {
+                match Puzzle::from_code(code) {
+                    Ok(puzzle) => self.start(puzzle),
+                    Err(e) => self.message = Some(e.to_string()),
+                }
+                self.code = None;
+            }
+            KeyCode::Esc => self.code = None,
+            _ => {}
+        }
+    }
+}
+
+impl StateModel for PuzzleStateModel {
+    fn handle_key_events(&mut self, key_event: KeyEvent) {
+        if self.code.is_some() {
+            self.handle_code_events(key_event);
+            return;
+        }
+
+        match key_event.code {
+            KeyCode::Left => self.grid.step_cursor(CursorMove::Left),
+            KeyCode::Right => self.grid.step_cursor(CursorMove::Right),
+            KeyCode::Up => self.grid.step_cursor(CursorMove::Up),
+            KeyCode::Down => self.grid.step_cursor(CursorMove::Down),
+            KeyCode::Enter => self.shoot(),
+            KeyCode::Char(c) if c.eq_ignore_ascii_case(&'n') => self.start(Puzzle::random()),
+            KeyCode::Char(c) if c.eq_ignore_ascii_case(&'c') => self.code = Some(String::new()),
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, _series: &mut Series, _notifications: &mut Notifications) {}
+
+    fn widget(&self) -> impl Widget {
+        PuzzleWidget(self)
+    }
+}
+
+/// Widget for the puzzle state.
+pub struct PuzzleWidget<'state>(&'state PuzzleStateModel);
+
+impl<'state> PuzzleWidget<'state> {
+    const SIDEBAR_WIDTH: u16 = 36;
+
+    // Tells how many shots have been used against the par, and how the puzzle has been solved.
+    fn score_lines(&self) -> Vec<Line<'state>> {
+        let puzzle = &self.0.puzzle;
+        let mut lines = vec![
+            Line::from(vec![
+                Span::raw(" Code: ").gray(),
+                Span::raw(puzzle.code()).yellow().bold(),
+            ]),
+            Line::from(vec![
+                Span::raw(" Shots: ").gray(),
+                Span::raw(format!("{}", puzzle.shots())).bold(),
+                Span::raw("  Par: ").gray(),
+                Span::raw(format!("{}", puzzle.par())).bold(),
+            ]),
+        ];
+
+        if puzzle.is_solved() {
+            let result = match puzzle.score().cmp(&0) {
+                Ordering::Less => format!(" Solved {} under par!", -puzzle.score())
+                    .green()
+                    .bold(),
+                Ordering::Equal => " Solved at par!".yellow().bold(),
+                Ordering::Greater => format!(" Solved {} over par", puzzle.score()).red().bold(),
+            };
+            lines.push(Line::from(""));
+            lines.push(Line::from(result));
+        }
+
+        lines
+    }
+
+    fn help_lines(&self) -> Vec<Line<'state>> {
+        if let Some(code) = &self.0.code {
+            return vec![
+                Line::from(vec![
+                    Span::raw(" Code: ").gray(),
+                    Span::raw(format!("{code}_")).yellow().bold(),
+                ]),
+                Line::from(" Enter to play it, Esc to cancel").gray(),
+            ];
+        }
+
+        let mut lines = vec![
+            Line::from(" Use:").bold(),
+            Line::from(" - the arrow keys: to aim"),
+            Line::from(" - Enter: to shoot"),
+            Line::from(" - n: for a new puzzle"),
+            Line::from(" - c: to type the code of a puzzle"),
+        ];
+        if let Some(message) = &self.0.message {
+            lines.push(Line::from(""));
+            lines.push(Line::from(format!(" {message}")).italic());
+        }
+
+        lines
+    }
+}
+
+impl<'state> Widget for PuzzleWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [grid_area, sidebar] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Length(Self::SIDEBAR_WIDTH)])
+                .areas(area);
+
+        let grid_block = Block::bordered()
+            .title(Line::from("Puzzle".bold()))
+            .border_set(border::THICK);
+
+        self.0
+            .grid
+            .widget()
+            .render(grid_block.inner(grid_area), buf);
+
+        grid_block.render(grid_area, buf);
+
+        let [score_area, fleet_area, help_area] = Layout::vertical([
+            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Fill(1),
+        ])
+        .areas(sidebar);
+
+        let score_block = Block::bordered()
+            .title(Line::from("Score".bold()))
+            .border_set(border::THICK);
+        Paragraph::new(Text::from(self.score_lines()))
+            .block(score_block)
+            .render(score_area, buf);
+
+        FleetStatusWidget::new("Hidden Fleet", &self.0.fleet).render(fleet_area, buf);
+
+        let help_block = Block::bordered()
+            .title(Line::from("Help".bold()))
+            .border_set(border::THICK);
+        Paragraph::new(Text::from(self.help_lines()))
+            .block(help_block)
+            .render(help_area, buf);
+    }
+}
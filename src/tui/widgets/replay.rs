@@ -0,0 +1,134 @@
+use crate::config::{Config, Theme};
+use crate::engine::fleet::FleetError;
+use crate::engine::replay::{Position, Replay};
+use crate::engine::series::Series;
+use crate::tui::{
+    state::StateModel,
+    widgets::{
+        grid::{GridModel, Layer},
+        toast::Notifications,
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    prelude::{Line, Stylize, Widget},
+    symbols::border,
+    widgets::Block,
+};
+
+/// Model for the replay state, where a saved game is played back shot by shot.
+///
+/// Both fleets are shown openly, each one with the shots fired at it by the opponent, as they
+/// were after the current shot, which is outlined. The Right and Left arrows step forward and
+/// back through the shots, Home and End jump to the start and to the end of the game. The
+/// viewer is closed with Esc.
+pub struct ReplayStateModel {
+    players: [String; 2],
+    positions: Vec<Position>,
+    current: usize,
+    theme: Theme,
+    closed: bool,
+}
+
+impl ReplayStateModel {
+    /// Creates a new model at the start of the replay, with the grids drawn in the configured
+    /// theme.
+    ///
+    /// An error is returned if a fleet of the replay is not valid.
+    pub fn new(config: &Config, replay: &Replay) -> Result<Self, FleetError> {
+        Ok(Self {
+            players: replay.players.clone(),
+            positions: replay.positions()?,
+            current: 0,
+            theme: config.theme,
+            closed: false,
+        })
+    }
+
+    /// Returns `true` when the viewer has been closed.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns the names of the players of the replay.
+    pub fn title(&self) -> String {
+        format!(" Replay: {} vs {} ", self.players[0], self.players[1])
+    }
+
+    /// Returns the current shot and its turn.
+    pub fn status(&self) -> String {
+        let shots = self.positions.len() - 1;
+        match self.positions[self.current].last {
+            Some(shot) => format!(
+                "Shot {}/{shots} — turn {}: {} fired at {}",
+                self.current, shot.turn, self.players[shot.shooter as usize], shot.cell
+            ),
+            None => format!("Shot 0/{shots} — the fleets before the battle"),
+        }
+    }
+
+    // Returns the grid of the fleet of the given side, outlining the current shot if it has
+    // been fired at it.
+    fn grid(&self, side: usize) -> GridModel {
+        let position = &self.positions[self.current];
+        let mut grid = GridModel::new(position.grids[side].clone());
+        grid.set_theme(self.theme);
+        if let Some(shot) = position.last
+            && shot.shooter.opponent() as usize == side
+        {
+            grid.push_layer(Layer::LastShot(shot.cell));
+        }
+
+        grid
+    }
+}
+
+impl StateModel for ReplayStateModel {
+    fn handle_key_events(&mut self, key_event: KeyEvent) {
+        let last = self.positions.len() - 1;
+        match key_event.code {
+            KeyCode::Right => self.current = (self.current + 1).min(last),
+            KeyCode::Left => self.current = self.current.saturating_sub(1),
+            KeyCode::Home => self.current = 0,
+            KeyCode::End => self.current = last,
+            KeyCode::Esc => self.closed = true,
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, _series: &mut Series, _notifications: &mut Notifications) {}
+
+    fn widget(&self) -> impl Widget {
+        ReplayWidget(self)
+    }
+}
+
+/// Widget for the replay state.
+pub struct ReplayWidget<'state>(&'state ReplayStateModel);
+
+impl<'state> Widget for ReplayWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let model = self.0;
+        let [grids, help] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [human_area, computer_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(grids);
+
+        for (side, area) in [human_area, computer_area].into_iter().enumerate() {
+            let block = Block::bordered()
+                .title(Line::from(
+                    format!("Fleet of {}", model.players[side]).bold(),
+                ))
+                .border_set(border::THICK);
+            let grid = model.grid(side);
+            grid.widget().render(block.inner(area), buf);
+            block.render(area, buf);
+        }
+
+        Line::from("Left/Right to step, Home/End to jump, Esc to leave")
+            .centered()
+            .render(help, buf);
+    }
+}
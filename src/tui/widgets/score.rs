@@ -0,0 +1,87 @@
+use crate::config::DailyResult;
+use crate::engine::daily::DailyChallenge;
+use crate::engine::series::Score;
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    prelude::{Line, Span, Stylize, Widget},
+};
+
+/// A widget that shows the score of the series, like "Best of 3  You 2 – 1 Computer".
+///
+/// In the daily mode, the widget shows the date and the par of the challenge instead, with the
+/// result of the player if they have already played it.
+pub struct ScoreWidget {
+    best_of: u8,
+    score: Score,
+    daily: Option<(DailyChallenge, Option<u32>)>,
+    result: Option<DailyResult>,
+}
+
+impl ScoreWidget {
+    /// Creates a new widget for the given score, in a series of at most `best_of` games.
+    pub fn new(best_of: u8, score: Score) -> Self {
+        Self {
+            best_of,
+            score,
+            daily: None,
+            result: None,
+        }
+    }
+
+    /// Creates a new widget for the given daily challenge and its par, if it is known yet.
+    pub fn daily(challenge: DailyChallenge, par: Option<u32>) -> Self {
+        Self {
+            daily: Some((challenge, par)),
+            ..Self::new(1, Score::default())
+        }
+    }
+
+    /// Shows the result of the daily challenge, if the player has already played it.
+    pub fn with_result(mut self, result: Option<DailyResult>) -> Self {
+        self.result = result;
+        self
+    }
+
+    /// Returns the score as a line of text.
+    pub fn line(&self) -> Line<'static> {
+        if let Some((challenge, par)) = self.daily {
+            return self.daily_line(challenge, par);
+        }
+
+        Line::from(vec![
+            Span::raw(format!("Best of {}  ", self.best_of)).gray(),
+            Span::raw("You ").bold(),
+            Span::raw(format!("{} – {}", self.score.human, self.score.computer))
+                .yellow()
+                .bold(),
+            Span::raw(" Computer").bold(),
+        ])
+    }
+
+    fn daily_line(&self, challenge: DailyChallenge, par: Option<u32>) -> Line<'static> {
+        let mut line = Line::from(vec![
+            Span::raw("Daily challenge ").gray(),
+            Span::raw(challenge.date()).yellow().bold(),
+        ]);
+        if let Some(par) = par {
+            line.push_span(Span::raw("  Par ").gray());
+            line.push_span(Span::raw(format!("{par}")).bold());
+        }
+        match self.result {
+            Some(result) if result.won => {
+                line.push_span(Span::raw(format!("  Won in {} shots", result.shots)).green())
+            }
+            Some(_) => line.push_span(Span::raw("  Lost").red()),
+            None => {}
+        }
+
+        line
+    }
+}
+
+impl Widget for ScoreWidget {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        self.line().centered().render(area, buf);
+    }
+}
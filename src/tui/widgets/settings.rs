@@ -0,0 +1,422 @@
+use crate::config::{Config, KeyBindings, Theme, TurnPace};
+use crate::engine::game::{Difficulty, GameMode, Handicap};
+use crate::engine::grid::CoordinateFormat;
+use crate::engine::map::Map;
+use crate::engine::rules::Variant;
+use crate::engine::series::Series;
+use crate::engine::strategy::Adaptation;
+use crate::locale::Locale;
+use crate::tui::{state::StateModel, widgets::toast::Notifications};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    prelude::{Buffer, Line, Rect, Span, Stylize, Text, Widget},
+    symbols::border,
+    widgets::{Block, Paragraph},
+};
+use std::iter;
+use strum::IntoEnumIterator;
+
+/// The settings which can be changed from the settings screen.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Field {
+    PlayerName,
+    Mode,
+    Variant,
+    Difficulty,
+    Adaptation,
+    Theme,
+    Water,
+    TurnPace,
+    TimeControl,
+    BestOf,
+    PowerUps,
+    Sound,
+    ConfirmShots,
+    Accessibility,
+    Coordinates,
+    Language,
+    Map,
+    Handicap,
+    Key(Command),
+}
+
+/// The commands whose key can be changed from the settings screen.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Command {
+    Quit,
+    Horizontal,
+    Vertical,
+    Settings,
+    SaveLayout,
+    AutoPlace,
+    Leaderboard,
+    Network,
+}
+
+impl Command {
+    fn label(self) -> &'static str {
+        match self {
+            Command::Quit => "Quit",
+            Command::Horizontal => "Horizontal",
+            Command::Vertical => "Vertical",
+            Command::Settings => "Settings",
+            Command::SaveLayout => "Save layout",
+            Command::AutoPlace => "Auto place",
+            Command::Leaderboard => "Leaderboard",
+            Command::Network => "Network games",
+        }
+    }
+
+    fn key(self, bindings: &KeyBindings) -> char {
+        match self {
+            Command::Quit => bindings.quit,
+            Command::Horizontal => bindings.horizontal,
+            Command::Vertical => bindings.vertical,
+            Command::Settings => bindings.settings,
+            Command::SaveLayout => bindings.save_layout,
+            Command::AutoPlace => bindings.auto_place,
+            Command::Leaderboard => bindings.leaderboard,
+            Command::Network => bindings.network,
+        }
+    }
+
+    fn key_mut(self, bindings: &mut KeyBindings) -> &mut char {
+        match self {
+            Command::Quit => &mut bindings.quit,
+            Command::Horizontal => &mut bindings.horizontal,
+            Command::Vertical => &mut bindings.vertical,
+            Command::Settings => &mut bindings.settings,
+            Command::SaveLayout => &mut bindings.save_layout,
+            Command::AutoPlace => &mut bindings.auto_place,
+            Command::Leaderboard => &mut bindings.leaderboard,
+            Command::Network => &mut bindings.network,
+        }
+    }
+}
+
+impl Field {
+    const ALL: [Field; 26] = [
+        Field::PlayerName,
+        Field::Mode,
+        Field::Variant,
+        Field::Difficulty,
+        Field::Adaptation,
+        Field::Theme,
+        Field::Water,
+        Field::TurnPace,
+        Field::TimeControl,
+        Field::BestOf,
+        Field::PowerUps,
+        Field::Sound,
+        Field::ConfirmShots,
+        Field::Accessibility,
+        Field::Coordinates,
+        Field::Language,
+        Field::Map,
+        Field::Handicap,
+        Field::Key(Command::Quit),
+        Field::Key(Command::Horizontal),
+        Field::Key(Command::Vertical),
+        Field::Key(Command::Settings),
+        Field::Key(Command::SaveLayout),
+        Field::Key(Command::AutoPlace),
+        Field::Key(Command::Leaderboard),
+        Field::Key(Command::Network),
+    ];
+    const COMMANDS: [Command; 8] = [
+        Command::Quit,
+        Command::Horizontal,
+        Command::Vertical,
+        Command::Settings,
+        Command::SaveLayout,
+        Command::AutoPlace,
+        Command::Leaderboard,
+        Command::Network,
+    ];
+}
+
+/// Model for the settings state.
+///
+/// The model edits a copy of the configuration. When the user leaves the screen, the
+/// application reads the edited configuration back and saves it, unless the user discarded the
+/// changes.
+pub struct SettingsStateModel {
+    config: Config,
+    selected: usize,
+    closed: bool,
+    save: bool,
+    error: Option<String>,
+}
+
+impl SettingsStateModel {
+    const MAX_NAME_LENGTH: usize = 20;
+    const TIME_CONTROLS: [Option<u16>; 5] = [None, Some(1), Some(3), Some(5), Some(10)];
+    const SERIES_LENGTHS: [u8; 3] = [1, 3, 5];
+
+    /// Creates a new settings model to edit a copy of the given configuration.
+    pub fn new(config: &Config) -> Self {
+        Self {
+            config: config.clone(),
+            selected: 0,
+            closed: false,
+            save: false,
+            error: None,
+        }
+    }
+
+    /// Returns `true` when the user has left the settings screen.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns the edited configuration if the user asked to save it.
+    pub fn saved_config(&self) -> Option<&Config> {
+        if self.closed && self.save {
+            Some(&self.config)
+        } else {
+            None
+        }
+    }
+
+    /// Reports that the configuration could not be saved, and keeps the screen open.
+    pub fn set_error(&mut self, error: String) {
+        self.error = Some(error);
+        self.closed = false;
+        self.save = false;
+    }
+
+    fn field(&self) -> Field {
+        Field::ALL[self.selected]
+    }
+
+    // Binds the key to the command, unless it is already bound to another one. The keys are
+    // case-insensitive.
+    fn bind(&mut self, command: Command, key: char) {
+        let key = key.to_ascii_lowercase();
+        let bindings = &mut self.config.keybindings;
+        let taken = Field::COMMANDS
+            .into_iter()
+            .find(|other| *other != command && other.key(bindings) == key);
+        match taken {
+            Some(other) => self.error = Some(format!("{key} is already the {} key", other.label())),
+            None => {
+                *command.key_mut(bindings) = key;
+                self.error = None;
+            }
+        }
+    }
+
+    // Changes the selected setting to the next (or previous) available value.
+    fn cycle(&mut self, forward: bool) {
+        match self.field() {
+            Field::PlayerName => {}
+            Field::Mode => self.config.mode = cycle(GameMode::iter(), self.config.mode, forward),
+            Field::Variant => {
+                self.config.variant = cycle(Variant::iter(), self.config.variant, forward)
+            }
+            Field::Difficulty => {
+                self.config.difficulty = cycle(Difficulty::iter(), self.config.difficulty, forward)
+            }
+            Field::Adaptation => {
+                self.config.adaptation = cycle(Adaptation::iter(), self.config.adaptation, forward)
+            }
+            Field::Theme => self.config.theme = cycle(Theme::iter(), self.config.theme, forward),
+            Field::Water => self.config.animated_water = !self.config.animated_water,
+            Field::TurnPace => {
+                self.config.turn_pace = cycle(TurnPace::iter(), self.config.turn_pace, forward)
+            }
+            Field::TimeControl => {
+                self.config.time_control = cycle(
+                    Self::TIME_CONTROLS.into_iter(),
+                    self.config.time_control,
+                    forward,
+                )
+            }
+            Field::BestOf => {
+                self.config.best_of = cycle(
+                    Self::SERIES_LENGTHS.into_iter(),
+                    self.config.best_of,
+                    forward,
+                )
+            }
+            Field::PowerUps => self.config.power_ups = !self.config.power_ups,
+            Field::Sound => self.config.sound = !self.config.sound,
+            Field::ConfirmShots => self.config.confirm_shots = !self.config.confirm_shots,
+            Field::Accessibility => self.config.accessibility = !self.config.accessibility,
+            Field::Coordinates => {
+                self.config.coordinate_format = cycle(
+                    CoordinateFormat::iter(),
+                    self.config.coordinate_format,
+                    forward,
+                )
+            }
+            Field::Language => {
+                let locales = iter::once(None).chain(Locale::iter().map(Some));
+                self.config.locale = cycle(locales, self.config.locale, forward);
+            }
+            Field::Map => {
+                let maps =
+                    iter::once(None).chain(Map::BUILT_IN.iter().map(|(name, _)| Some(*name)));
+                let map = cycle(maps, self.config.map.as_deref(), forward);
+                self.config.map = map.map(str::to_string);
+            }
+            Field::Handicap => {
+                self.config.handicap = cycle(Handicap::iter(), self.config.handicap, forward)
+            }
+            Field::Key(_) => {}
+        }
+    }
+}
+
+// Returns the value after (or before) `current` in `values`, wrapping around at the ends.
+fn cycle<T: PartialEq + Copy>(values: impl Iterator<Item = T>, current: T, forward: bool) -> T {
+    let values: Vec<T> = values.collect();
+    let index = values.iter().position(|v| *v == current).unwrap_or(0);
+    let next = if forward {
+        (index + 1) % values.len()
+    } else {
+        (index + values.len() - 1) % values.len()
+    };
+
+    values[next]
+}
+
+impl StateModel for SettingsStateModel {
+    fn handle_key_events(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Up => self.selected = self.selected.saturating_sub(1),
+            KeyCode::Down => self.selected = (self.selected + 1).min(Field::ALL.len() - 1),
+            KeyCode::Left => self.cycle(false),
+            KeyCode::Right => self.cycle(true),
+            KeyCode::Backspace if self.field() == Field::PlayerName => {
+                self.config.player_name.pop();
+            }
+            KeyCode::Char(c)
+                if self.field() == Field::PlayerName
+                    && self.config.player_name.chars().count() < Self::MAX_NAME_LENGTH =>
+            {
+                self.config.player_name.push(c);
+            }
+            KeyCode::Char(c) if c.is_ascii_graphic() => {
+                if let Field::Key(command) = self.field() {
+                    self.bind(command, c);
+                }
+            }
+            KeyCode::Enter if !self.config.player_name.trim().is_empty() => {
+                self.closed = true;
+                self.save = true;
+            }
+            KeyCode::Esc => {
+                self.closed = true;
+                self.save = false;
+            }
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, _series: &mut Series, _notifications: &mut Notifications) {}
+
+    fn widget(&self) -> impl Widget {
+        SettingsWidget(self)
+    }
+}
+
+/// Widget for the settings state.
+pub struct SettingsWidget<'state>(&'state SettingsStateModel);
+
+impl<'state> SettingsWidget<'state> {
+    fn field_line(&self, field: Field) -> Line<'state> {
+        let config = &self.0.config;
+        let (label, value) = match field {
+            Field::PlayerName => ("Player name", format!("{}_", config.player_name)),
+            Field::Mode => ("Mode", format!("< {} >", config.mode)),
+            Field::Variant => ("Rules", format!("< {} >", config.variant)),
+            Field::Difficulty => ("Difficulty", format!("< {} >", config.difficulty)),
+            Field::Adaptation => ("Adaptation", format!("< {} >", config.adaptation)),
+            Field::Theme => ("Theme", format!("< {} >", config.theme)),
+            Field::Water if config.animated_water => ("Water", "< Animated >".to_string()),
+            Field::Water => ("Water", "< Still >".to_string()),
+            Field::TurnPace => ("Enemy turn", format!("< {} >", config.turn_pace)),
+            Field::TimeControl => match config.time_control {
+                Some(minutes) => ("Time control", format!("< {minutes} min >")),
+                None => ("Time control", "< Off >".to_string()),
+            },
+            Field::BestOf => ("Series", format!("< Best of {} >", config.best_of)),
+            Field::PowerUps if config.power_ups => ("Power-ups", "< On >".to_string()),
+            Field::PowerUps => ("Power-ups", "< Off >".to_string()),
+            Field::Sound if config.sound => ("Sound", "< On >".to_string()),
+            Field::Sound => ("Sound", "< Off >".to_string()),
+            Field::ConfirmShots if config.confirm_shots => ("Confirm", "< On >".to_string()),
+            Field::ConfirmShots => ("Confirm", "< Off >".to_string()),
+            Field::Accessibility if config.accessibility => ("Accessible", "< On >".to_string()),
+            Field::Accessibility => ("Accessible", "< Off >".to_string()),
+            Field::Coordinates => ("Coordinates", format!("< {} >", config.coordinate_format)),
+            Field::Language => match config.locale {
+                Some(locale) => ("Language", format!("< {locale} >")),
+                None => ("Language", "< Auto >".to_string()),
+            },
+            Field::Map => match &config.map {
+                Some(name) => ("Map", format!("< {name} >")),
+                None => ("Map", "< Open sea >".to_string()),
+            },
+            Field::Handicap => ("Handicap", format!("< {} >", config.handicap)),
+            Field::Key(command) => (
+                command.label(),
+                format!("[ {} ]", command.key(&config.keybindings)),
+            ),
+        };
+
+        let line = Line::from(vec![
+            Span::raw(format!("{label:>12}: ")).gray(),
+            Span::raw(value).yellow().bold(),
+        ]);
+
+        if field == self.0.field() {
+            line.reversed()
+        } else {
+            line
+        }
+    }
+}
+
+impl<'state> Widget for SettingsWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let block = Block::bordered()
+            .title(Line::from("Settings".bold()))
+            .border_set(border::THICK);
+
+        let mut lines = vec![Line::from("")];
+        lines.extend(
+            Field::ALL
+                .iter()
+                .map(|field| self.field_line(*field).centered()),
+        );
+        lines.extend([
+            Line::from(""),
+            Line::from("Use:").bold().centered(),
+            Line::from("- the up and down arrows: to choose a setting").centered(),
+            Line::from("- the left and right arrows: to change it").centered(),
+            Line::from("- the keyboard: to type your name, or the key of a command").centered(),
+            Line::from("- Enter: to save the settings").centered(),
+            Line::from("- Esc: to discard the changes").centered(),
+        ]);
+
+        if let Some(path) = Config::path() {
+            lines.push(Line::from(""));
+            lines.push(
+                Line::from(format!("Saved to {}", path.display()))
+                    .gray()
+                    .centered(),
+            );
+        }
+
+        if let Some(error) = &self.0.error {
+            lines.push(Line::from(""));
+            lines.push(Line::from(error.clone()).red().bold().centered());
+        }
+
+        Paragraph::new(Text::from(lines))
+            .block(block)
+            .render(area, buf);
+    }
+}
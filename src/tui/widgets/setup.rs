@@ -1,13 +1,21 @@
-use crate::engine::game::Game;
+use crate::config::{Config, DailyResult, DailyResults, LayoutPresets};
+use crate::engine::daily::DailyChallenge;
+use crate::engine::game::GameMode;
+use crate::engine::series::{Score, Series};
+use crate::locale::{self, Locale};
 use crate::{
     engine::{
         fleet::{Fleet, Ship, ShipKind, ShipOrientation},
-        grid::Grid,
-        player::Player,
+        grid::{Cell, CellState, Grid},
+        placement::Placement,
     },
     tui::{
         state::StateModel,
-        widgets::grid::{GridModel, Layer},
+        widgets::{
+            grid::{CursorMove, GridModel, Layer},
+            score::ScoreWidget,
+            toast::Notifications,
+        },
     },
 };
 use crossterm::event::{KeyCode, KeyEvent};
@@ -16,18 +24,45 @@ use ratatui::{
     symbols::border,
     widgets::{Block, Paragraph},
 };
+use strum::IntoEnumIterator;
 
 /// Model for the setup state.
 ///
 /// During the setup phase, the player positions their ships on the grid. Every type of ship
 /// must be placed. The model tracks these placements, and once all ships are in position,
-/// the setup phase is complete.
+/// the player confirms the fleet and the setup phase is complete.
+///
+/// Until the fleet is confirmed, the player can select an already placed ship and pick it up,
+/// so that it can be moved or rotated again.
+///
+/// The player can also deploy the whole fleet from a saved layout, or with one placement
+/// strategy after the other, and save the deployed fleet as a new layout.
+///
+/// When the configured variant has mines, the player places them on free cells once the whole
+/// fleet is deployed.
+///
+/// On a map, neither ships nor mines can be placed on the islands.
 pub struct SetupStateModel {
     deploy_grid: GridModel,
     current_kind: Option<ShipKind>,
     current_orientation: ShipOrientation,
+    placed: Option<ShipKind>,
     ships: Vec<Ship>,
-    kind_iter: std::slice::Iter<'static, ShipKind>,
+    mines: Vec<Cell>,
+    mines_to_place: usize,
+    islands: Vec<Cell>,
+    selected: Option<usize>,
+    confirmed: bool,
+    config: Config,
+    locale: Locale,
+    presets: LayoutPresets,
+    auto_placement: Option<Placement>,
+    naming: Option<String>,
+    message: Option<String>,
+    best_of: u8,
+    score: Score,
+    daily: Option<DailyChallenge>,
+    daily_result: Option<DailyResult>,
 }
 
 impl SetupStateModel {
@@ -38,34 +73,254 @@ impl SetupStateModel {
         ShipKind::Submarine,
         ShipKind::Destroyer,
     ];
+    const MAX_LAYOUT_NAME_LENGTH: usize = 20;
+
+    /// Returns `true` while the player is typing the name of a layout.
+    pub fn is_typing(&self) -> bool {
+        self.naming.is_some()
+    }
 
     fn update_grid(&mut self) {
-        self.deploy_grid.pop_layer();
+        self.deploy_grid.clear_layers();
+        if !self.mines.is_empty() {
+            self.deploy_grid
+                .push_layer(Layer::Mines(self.mines.clone()));
+        }
+        if let Some(index) = self.selected {
+            self.deploy_grid
+                .push_layer(Layer::Selection(self.ships[index].occupied_cells()));
+        } else if let Some(ref kind) = self.current_kind
+            && let Some(ship) = kind.ship(
+                *self.deploy_grid.cursor().unwrap(),
+                self.current_orientation,
+            )
+        {
+            self.deploy_grid
+                .push_layer(Layer::Ship(ship.occupied_cells()));
+        } else if self.is_placing_mines() {
+            let cursor = *self.deploy_grid.cursor().unwrap();
+            self.deploy_grid.push_layer(Layer::Mines(vec![cursor]));
+        }
+    }
+
+    // Returns `true` when the fleet is deployed, but some mines are still to place.
+    fn is_placing_mines(&self) -> bool {
+        self.current_kind.is_none() && self.mines.len() < self.mines_to_place
+    }
+
+    // Places a mine under the cursor, if it is a free cell of the sea.
+    fn place_mine(&mut self) {
+        let cursor = *self.deploy_grid.cursor().unwrap();
+        if !self.mines.contains(&cursor)
+            && !self.islands.contains(&cursor)
+            && self
+                .ships
+                .iter()
+                .all(|ship| !ship.occupied_cells().contains(&cursor))
+        {
+            self.mines.push(cursor);
+        }
+    }
+
+    // Returns the first kind of ship, in fleet order, which has not been placed yet.
+    fn next_kind(&self) -> Option<ShipKind> {
+        Self::SHIP_KINDS
+            .iter()
+            .find(|kind| self.ships.iter().all(|ship| ship.kind() != *kind))
+            .cloned()
+    }
+
+    // Places the current ship under the cursor, if it fits and doesn't overlap other ships,
+    // mines or islands.
+    fn place_ship(&mut self) {
         if let Some(ref kind) = self.current_kind
             && let Some(ship) = kind.ship(
                 *self.deploy_grid.cursor().unwrap(),
                 self.current_orientation,
             )
+            && self.ships.iter().all(|s| !ship.is_overlapping(s))
+            && ship
+                .occupied_cells()
+                .iter()
+                .all(|cell| !self.mines.contains(cell) && !self.islands.contains(cell))
         {
-            self.deploy_grid.push_layer(Layer::Ship(ship));
+            self.deploy_grid.add_ship(&ship);
+            self.placed = Some(ship.kind().clone());
+            self.ships.push(ship);
+            self.current_kind = self.next_kind();
+        }
+    }
+
+    // Selects the next placed ship. After the last one, the selection is cleared.
+    fn select_next(&mut self) {
+        self.selected = match self.selected {
+            Some(index) if index + 1 < self.ships.len() => Some(index + 1),
+            Some(_) => None,
+            None if self.ships.is_empty() => None,
+            None => Some(0),
+        };
+    }
+
+    // Removes the selected ship from the grid and makes it the ship to place.
+    //
+    // The ship which was being placed, if any, goes back among the ones still to place.
+    fn pick_up_selected(&mut self) {
+        if let Some(index) = self.selected.take() {
+            let ship = self.ships.remove(index);
+            self.current_orientation = ship.orientation();
+            self.current_kind = Some(ship.kind().clone());
+
+            self.deploy_grid = new_deploy_grid(&self.config, &self.ships, &self.islands);
+            self.deploy_grid.set_cursor(&ship.first_cell());
+        }
+    }
+
+    // Replaces the placed ships with the saved layout at the given position in the presets list.
+    fn deploy_preset(&mut self, index: usize) {
+        let Some(name) = self.presets.names().get(index).map(|name| name.to_string()) else {
+            return;
+        };
+
+        let fleet = self
+            .presets
+            .get(&name)
+            .ok_or("invalid code".to_string())
+            .and_then(|layout| Fleet::from_layout(&layout).map_err(|e| e.to_string()))
+            .and_then(
+                |fleet| match self.islands.iter().find(|c| fleet.is_occupied(c)) {
+                    Some(cell) => Err(self.locale.format(locale::Text::ShipOnIsland, &[cell])),
+                    None => Ok(fleet),
+                },
+            );
+        match fleet {
+            Ok(fleet) => {
+                let cursor = *self.deploy_grid.cursor().unwrap();
+                self.ships = fleet.as_ref().to_vec();
+                self.mines.retain(|mine| !fleet.is_occupied(mine));
+                self.current_kind = None;
+                self.deploy_grid = new_deploy_grid(&self.config, &self.ships, &self.islands);
+                self.deploy_grid.set_cursor(&cursor);
+                self.message = Some(self.locale.format(locale::Text::LayoutDeployed, &[&name]));
+            }
+            Err(e) => {
+                self.message = Some(
+                    self.locale
+                        .format(locale::Text::LayoutInvalid, &[&name, &e]),
+                )
+            }
+        }
+    }
+
+    // Replaces the placed ships with a fleet deployed by the placement strategy after the last
+    // one used.
+    fn auto_place(&mut self) {
+        let placement = match self.auto_placement {
+            Some(last) => Placement::iter()
+                .skip_while(|placement| *placement != last)
+                .nth(1)
+                .unwrap_or(Placement::Random),
+            None => Placement::Random,
+        };
+        let fleet = placement.strategy(rand::random()).deploy(&self.islands);
+
+        let cursor = *self.deploy_grid.cursor().unwrap();
+        self.ships = fleet.as_ref().to_vec();
+        self.mines.retain(|mine| !fleet.is_occupied(mine));
+        self.current_kind = None;
+        self.deploy_grid = new_deploy_grid(&self.config, &self.ships, &self.islands);
+        self.deploy_grid.set_cursor(&cursor);
+        self.auto_placement = Some(placement);
+        self.message = Some(
+            self.locale
+                .format(locale::Text::FleetDeployed, &[&placement]),
+        );
+    }
+
+    // Saves the deployed fleet as a layout with the given name.
+    fn save_preset(&mut self, name: &str) {
+        let layout = Fleet::new(&self.fleet_ships()).unwrap().layout();
+        self.presets.insert(name, &layout);
+        self.message = match self.presets.save() {
+            Ok(()) => Some(self.locale.format(locale::Text::LayoutSaved, &[&name])),
+            Err(e) => Some(e.to_string()),
+        };
+    }
+
+    // Edits the name of the layout to save, and saves it on Enter.
+    fn handle_naming_events(&mut self, key_event: KeyEvent) {
+        let Some(name) = &mut self.naming else {
+            return;
+        };
+
+        match key_event.code {
+            KeyCode::Char(c) if name.chars().count() < Self::MAX_LAYOUT_NAME_LENGTH => name.push(c),
+            KeyCode::Backspace => _ = name.pop(),
+            KeyCode::Enter if !name.trim().is_empty() => {
+                let name = name.trim().to_string();
+                self.naming = None;
+                self.save_preset(&name);
+            }
+            KeyCode::Esc => self.naming = None,
+            _ => {}
         }
     }
+
+    // Returns the placed ships in the order expected by the fleet.
+    fn fleet_ships(&self) -> Vec<Ship> {
+        Self::SHIP_KINDS
+            .iter()
+            .filter_map(|kind| self.ships.iter().find(|ship| ship.kind() == kind).cloned())
+            .collect()
+    }
 }
 
-impl Default for SetupStateModel {
+impl SetupStateModel {
     /// Creates a new setup state with an empty deployment grid and no placed ships.
     /// The first ship to be placed is the Aircraft Carrier.
-    fn default() -> Self {
-        let mut kind_iter = Self::SHIP_KINDS.iter();
-        let mut deploy_grid = GridModel::new(Grid::default());
+    ///
+    /// The configuration gives the player name, the computer difficulty, the theme and the keys.
+    pub fn new(config: &Config) -> Self {
+        let locale = config.locale();
+        let (presets, mut message) = match LayoutPresets::load() {
+            Ok(presets) => (presets, None),
+            Err(e) => (LayoutPresets::default(), Some(e.to_string())),
+        };
+        let islands = match config.map() {
+            Ok(map) => map.map(|map| map.islands().to_vec()).unwrap_or_default(),
+            Err(e) => {
+                message = Some(locale.format(locale::Text::OpenSea, &[&e]));
+                Vec::new()
+            }
+        };
+        #[cfg(feature = "scripting")]
+        if let Err(e) = config.script() {
+            message = Some(locale.format(locale::Text::BuiltInComputer, &[&e]));
+        }
+
+        let mut deploy_grid = new_deploy_grid(config, &[], &islands);
         deploy_grid.enable_cursor(); // Ensures the cursor is enabled
 
         let mut model = Self {
             deploy_grid,
-            current_kind: kind_iter.next().cloned(),
+            current_kind: Self::SHIP_KINDS.first().cloned(),
             current_orientation: ShipOrientation::Horizontal,
+            placed: None,
             ships: Vec::new(),
-            kind_iter,
+            mines: Vec::new(),
+            mines_to_place: config.variant.rules().mines(),
+            islands,
+            selected: None,
+            confirmed: false,
+            config: config.clone(),
+            locale,
+            presets,
+            auto_placement: None,
+            naming: None,
+            message,
+            best_of: config.best_of,
+            score: Score::default(),
+            daily: None,
+            daily_result: today_result(config),
         };
 
         model.update_grid();
@@ -74,43 +329,94 @@ impl Default for SetupStateModel {
     }
 }
 
+// Returns the result of today's challenge in the daily mode, if the player has already played it.
+fn today_result(config: &Config) -> Option<DailyResult> {
+    if config.mode != GameMode::Daily {
+        return None;
+    }
+
+    let date = DailyChallenge::today().date();
+    DailyResults::load()
+        .ok()
+        .and_then(|results| results.get(&date).copied())
+}
+
+// Builds a deployment grid with the given ships and islands on it.
+fn new_deploy_grid(config: &Config, ships: &[Ship], islands: &[Cell]) -> GridModel {
+    let mut grid = Grid::from_ships(ships);
+    for island in islands {
+        grid.mark(island, CellState::Blocked);
+    }
+
+    let mut deploy_grid = GridModel::new(grid);
+    deploy_grid.set_theme(config.theme);
+    deploy_grid.set_wrap_around(false); // Ships are easier to place near the edges
+    deploy_grid
+}
+
 impl StateModel for SetupStateModel {
     fn handle_key_events(&mut self, key_event: KeyEvent) {
-        match key_event.code {
-            KeyCode::Left => self.deploy_grid.move_cursor(|c| c.move_left()),
-            KeyCode::Right => self.deploy_grid.move_cursor(|c| c.move_right()),
-            KeyCode::Up => self.deploy_grid.move_cursor(|c| c.move_up()),
-            KeyCode::Down => self.deploy_grid.move_cursor(|c| c.move_down()),
-            KeyCode::Enter => {
-                if let Some(ref kind) = self.current_kind
-                    && let Some(ship) = kind.ship(
-                        *self.deploy_grid.cursor().unwrap(),
-                        self.current_orientation,
-                    )
-                    && self.ships.iter().all(|s| !ship.is_overlapping(s))
+        if self.naming.is_some() {
+            self.handle_naming_events(key_event);
+        } else if self.selected.is_some() {
+            // While a placed ship is selected, any key but Tab and Enter cancels the selection.
+            match key_event.code {
+                KeyCode::Tab => self.select_next(),
+                KeyCode::Enter => self.pick_up_selected(),
+                _ => self.selected = None,
+            }
+        } else {
+            let bindings = &self.config.keybindings;
+            match key_event.code {
+                KeyCode::Left => self.deploy_grid.step_cursor(CursorMove::Left),
+                KeyCode::Right => self.deploy_grid.step_cursor(CursorMove::Right),
+                KeyCode::Up => self.deploy_grid.step_cursor(CursorMove::Up),
+                KeyCode::Down => self.deploy_grid.step_cursor(CursorMove::Down),
+                KeyCode::Tab => self.select_next(),
+                KeyCode::Backspace => _ = self.mines.pop(),
+                KeyCode::Enter if self.is_placing_mines() => self.place_mine(),
+                KeyCode::Enter if self.current_kind.is_none() => self.confirmed = true,
+                KeyCode::Enter => self.place_ship(),
+                KeyCode::Char(c) if c.eq_ignore_ascii_case(&bindings.horizontal) => {
+                    self.current_orientation = ShipOrientation::Horizontal
+                }
+                KeyCode::Char(c) if c.eq_ignore_ascii_case(&bindings.vertical) => {
+                    self.current_orientation = ShipOrientation::Vertical
+                }
+                KeyCode::Char(c)
+                    if self.current_kind.is_none()
+                        && c.eq_ignore_ascii_case(&bindings.save_layout) =>
                 {
-                    self.deploy_grid.add_ship(&ship);
-                    self.ships.push(ship.clone());
-                    self.current_kind = self.kind_iter.next().cloned();
+                    self.naming = Some(String::new())
                 }
+                KeyCode::Char(c) if c.eq_ignore_ascii_case(&bindings.auto_place) => {
+                    self.auto_place()
+                }
+                KeyCode::Char(c @ '1'..='9') => {
+                    self.deploy_preset(c.to_digit(10).unwrap() as usize - 1)
+                }
+                _ => {}
             }
-            KeyCode::Char('h') | KeyCode::Char('H') => {
-                self.current_orientation = ShipOrientation::Horizontal
-            }
-            KeyCode::Char('v') | KeyCode::Char('V') => {
-                self.current_orientation = ShipOrientation::Vertical
-            }
-            _ => {}
         }
 
         self.update_grid();
     }
 
-    fn update(&mut self, game: &mut Game) {
-        if self.current_kind.is_none() {
-            let human = Player::new("player 1", Fleet::new(self.ships.as_slice()).unwrap());
-            game.set_human_player(human);
+    fn update(&mut self, series: &mut Series, notifications: &mut Notifications) {
+        if let Some(kind) = self.placed.take() {
+            notifications.push(self.locale.format(locale::Text::ShipPlaced, &[&kind]));
         }
+        if self.confirmed {
+            let fleet = Fleet::new(&self.fleet_ships()).unwrap();
+            if let Err(e) = series.start_game_with_mines(fleet, &self.mines) {
+                self.message = Some(self.locale.error(&e));
+            }
+            self.confirmed = false;
+        }
+
+        self.best_of = series.best_of();
+        self.score = series.score();
+        self.daily = series.daily_challenge();
     }
 
     fn widget(&self) -> impl Widget {
@@ -126,6 +432,59 @@ impl<'state> SetupWidget<'state> {
     pub fn new(state: &'state SetupStateModel) -> Self {
         Self(state)
     }
+
+    fn score_widget(&self) -> ScoreWidget {
+        match self.0.daily {
+            Some(challenge) => ScoreWidget::daily(challenge, None).with_result(self.0.daily_result),
+            None => ScoreWidget::new(self.0.best_of, self.0.score),
+        }
+    }
+
+    // Tells the player what to do next: place a ship, pick up the selected one or start the battle.
+    fn status_line(&self) -> Line<'state> {
+        let locale = self.0.locale;
+        let text = |text| Span::raw(locale.text(text)).gray();
+        if let Some(name) = &self.0.naming {
+            Line::from(vec![
+                text(locale::Text::LayoutName),
+                Span::raw(format!("{name}_")).yellow().bold(),
+                text(locale::Text::LayoutNameKeys),
+            ])
+        } else if let Some(index) = self.0.selected {
+            Line::from(vec![
+                text(locale::Text::Selected),
+                Span::raw(format!("{}", self.0.ships[index].kind()))
+                    .yellow()
+                    .bold(),
+                text(locale::Text::SelectedKeys),
+            ])
+        } else if let Some(kind) = &self.0.current_kind {
+            Line::from(vec![
+                text(locale::Text::PlaceYour),
+                Span::raw(format!("{}", kind)).yellow().bold(),
+                text(locale::Text::Size),
+                Span::raw(format!("{}", kind.size())).yellow().italic(),
+                Span::raw("]").gray(),
+            ])
+        } else if self.0.is_placing_mines() {
+            Line::from(vec![
+                text(locale::Text::PlaceYour),
+                Span::raw(locale.text(locale::Text::Mine)).yellow().bold(),
+                text(locale::Text::OnFreeCell),
+                Span::raw(format!("{}", self.0.mines_to_place - self.0.mines.len()))
+                    .yellow()
+                    .italic(),
+                Span::raw("]").gray(),
+            ])
+        } else {
+            Line::from(vec![
+                text(locale::Text::FleetReady),
+                Span::raw("Enter").yellow().bold(),
+                text(locale::Text::StartBattle),
+            ])
+        }
+        .centered()
+    }
 }
 
 impl<'state> Widget for SetupWidget<'state> {
@@ -136,8 +495,9 @@ impl<'state> Widget for SetupWidget<'state> {
             .constraints(vec![Constraint::Percentage(50), Constraint::Percentage(50)])
             .split(area);
 
+        let locale = self.0.locale;
         let deploy_block = Block::bordered()
-            .title(Line::from("Deploy your fleet".bold()))
+            .title(Line::from(locale.text(locale::Text::DeployFleet).bold()))
             .border_set(border::THICK);
 
         deploy_grid
@@ -146,38 +506,131 @@ impl<'state> Widget for SetupWidget<'state> {
 
         deploy_block.render(layout[0], buf);
 
+        let bindings = &self.0.config.keybindings;
         let notes_block = Block::bordered()
-            .title(Line::from("Help".bold()))
+            .title(Line::from(locale.text(locale::Text::Help).bold()))
             .border_set(border::THICK);
 
-        let help_text = Text::from(vec![
-            Line::from("Welcome to Naval - The Battleship Game")
-                .red()
-                .bold()
-                .centered(),
+        let line = |text| Line::from(locale.text(text)).centered();
+        let key_line = |text, key: char| Line::from(locale.format(text, &[&key])).centered();
+        let mut help_text = Text::from(vec![
+            line(locale::Text::Welcome).red().bold(),
+            self.score_widget().line().centered(),
             Line::from(""),
-            Line::from("Use:").bold().centered(),
-            Line::from("- the arrow keys: to move the ship").centered(),
-            Line::from("- h: to put the ship horizontally").centered(),
-            Line::from("- v: to put the ship vertically").centered(),
-            Line::from("- Enter: to place it.").centered(),
-            Line::from(""),
-            Line::from(vec![
-                Span::raw("Please, place your ").gray(),
-                Span::raw(format!("{}", self.0.current_kind.as_ref().unwrap()))
-                    .yellow()
-                    .bold(),
-                Span::raw(" [size: ").gray(),
-                Span::raw(format!("{}", self.0.current_kind.as_ref().unwrap().size()))
-                    .yellow()
-                    .italic(),
-                Span::raw("]").gray(),
-            ])
-            .centered(),
+            line(locale::Text::Use).bold(),
+            line(locale::Text::HelpMove),
+            key_line(locale::Text::HelpHorizontal, bindings.horizontal),
+            key_line(locale::Text::HelpVertical, bindings.vertical),
+            line(locale::Text::HelpPlace),
+            line(locale::Text::HelpSelect),
+            line(locale::Text::HelpPickUp),
+            line(locale::Text::HelpLayout),
+            key_line(locale::Text::HelpAutoPlace, bindings.auto_place),
+            key_line(locale::Text::HelpSaveLayout, bindings.save_layout),
+            key_line(locale::Text::HelpSettings, bindings.settings),
+            key_line(locale::Text::HelpLeaderboard, bindings.leaderboard),
+            key_line(locale::Text::HelpNetwork, bindings.network),
         ]);
+        if self.0.mines_to_place > 0 {
+            help_text.push_line(
+                Line::from(locale.format(locale::Text::HelpMines, &[&self.0.mines_to_place]))
+                    .centered(),
+            );
+            help_text.push_line(line(locale::Text::HelpRemoveMine));
+        }
+        help_text.push_line(Line::from(""));
+        help_text.push_line(self.status_line());
+
+        if let Some(message) = &self.0.message {
+            help_text.push_line(Line::from(message.clone()).italic().centered());
+        }
+
+        let names = self.0.presets.names();
+        if !names.is_empty() {
+            help_text.push_line(Line::from(""));
+            help_text.push_line(line(locale::Text::SavedLayouts).bold());
+            for (i, name) in names.iter().take(9).enumerate() {
+                help_text.push_line(Line::from(format!("{}. {name}", i + 1)).centered());
+            }
+        }
 
         let text = Paragraph::new(help_text).block(notes_block);
 
         text.render(layout[1], buf);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::grid::Cell;
+    use rstest::rstest;
+
+    fn press(model: &mut SetupStateModel, code: KeyCode) {
+        model.handle_key_events(KeyEvent::from(code));
+    }
+
+    #[rstest]
+    fn test_pick_up_ship() {
+        let mut model = SetupStateModel::new(&Config::default());
+        press(&mut model, KeyCode::Enter);
+        assert_eq!(model.ships.len(), 1);
+        assert_eq!(model.current_kind, Some(ShipKind::Battleship));
+
+        press(&mut model, KeyCode::Tab);
+        assert_eq!(model.selected, Some(0));
+        press(&mut model, KeyCode::Enter);
+
+        assert!(model.ships.is_empty());
+        assert_eq!(model.selected, None);
+        assert_eq!(model.current_kind, Some(ShipKind::AircraftCarrier));
+        assert_eq!(model.deploy_grid.cursor(), Some(&Cell::bounded(0, 0)));
+    }
+
+    #[rstest]
+    fn test_move_picked_up_ship() {
+        let mut model = SetupStateModel::new(&Config::default());
+        press(&mut model, KeyCode::Enter);
+        press(&mut model, KeyCode::Tab);
+        press(&mut model, KeyCode::Enter);
+
+        press(&mut model, KeyCode::Down);
+        press(&mut model, KeyCode::Down);
+        press(&mut model, KeyCode::Char('v'));
+        press(&mut model, KeyCode::Enter);
+
+        let carrier = ShipKind::AircraftCarrier
+            .ship(Cell::bounded(0, 2), ShipOrientation::Vertical)
+            .unwrap();
+        assert_eq!(model.ships, vec![carrier]);
+        assert_eq!(model.current_kind, Some(ShipKind::Battleship));
+    }
+
+    #[rstest]
+    fn test_replace_ship_while_placing_another() {
+        let mut model = SetupStateModel::new(&Config::default());
+        press(&mut model, KeyCode::Enter);
+        assert_eq!(model.current_kind, Some(ShipKind::Battleship));
+
+        press(&mut model, KeyCode::Tab);
+        press(&mut model, KeyCode::Enter);
+        assert_eq!(model.current_kind, Some(ShipKind::AircraftCarrier));
+
+        // The Battleship goes back among the ships to place, after the Carrier.
+        press(&mut model, KeyCode::Enter);
+        assert_eq!(model.ships.len(), 1);
+        assert_eq!(model.current_kind, Some(ShipKind::Battleship));
+    }
+
+    #[rstest]
+    fn test_cancel_selection() {
+        let mut model = SetupStateModel::new(&Config::default());
+        press(&mut model, KeyCode::Enter);
+        press(&mut model, KeyCode::Tab);
+        press(&mut model, KeyCode::Esc);
+
+        assert_eq!(model.selected, None);
+        assert_eq!(model.ships.len(), 1);
+        assert_eq!(model.current_kind, Some(ShipKind::Battleship));
+    }
+}
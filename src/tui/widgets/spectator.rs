@@ -0,0 +1,182 @@
+use crate::config::{Config, Theme};
+use crate::engine::fleet::Fleet;
+use crate::engine::game::{Game, GameBuilder, GameError, GamePhase, Side};
+use crate::engine::grid::{Cell, CellState, Grid};
+use crate::engine::player::Player;
+use crate::engine::series::Series;
+use crate::engine::strategy::{Strategy, StrategyKind};
+use crate::tui::{
+    state::StateModel,
+    widgets::{
+        grid::{GridModel, Layer},
+        toast::Notifications,
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    prelude::{Line, Stylize, Widget},
+    symbols::border,
+    widgets::Block,
+};
+use std::time::{Duration, Instant};
+
+/// Model for the spectator state, where two computer strategies play a match against each other.
+///
+/// Both fleets are shown openly, each one with the shots fired at it by the other strategy.
+/// The turns are played automatically, one at every step: the speed can be changed with the
+/// `+` and `-` keys, and Space pauses the match. The spectator leaves with Esc, or with Enter
+/// when the match is over.
+pub struct SpectatorStateModel {
+    game: Game,
+    strategy: Box<dyn Strategy>,
+    kinds: (StrategyKind, StrategyKind),
+    theme: Theme,
+    speed: usize,
+    paused: bool,
+    last_step: Instant,
+    closed: bool,
+}
+
+impl SpectatorStateModel {
+    // The delays between two turns, from the slowest to the fastest speed.
+    const DELAYS: [Duration; 5] = [
+        Duration::from_millis(2000),
+        Duration::from_millis(1000),
+        Duration::from_millis(500),
+        Duration::from_millis(250),
+        Duration::from_millis(100),
+    ];
+    const DEFAULT_SPEED: usize = 2;
+
+    /// Creates a new match between the strategies, with random fleets and the configured
+    /// variant. The first strategy plays the side of the human player.
+    pub fn new(config: &Config, a: StrategyKind, b: StrategyKind) -> Result<Self, GameError> {
+        let mut game = GameBuilder::new()
+            .variant(config.variant)
+            .human(&a.to_string(), Fleet::build(|kind| kind.random()))
+            .build()?;
+        game.set_computer_strategy(b.strategy());
+
+        Ok(Self {
+            game,
+            strategy: a.strategy(),
+            kinds: (a, b),
+            theme: config.theme,
+            speed: Self::DEFAULT_SPEED,
+            paused: false,
+            last_step: Instant::now(),
+            closed: false,
+        })
+    }
+
+    /// Returns `true` when the spectator has left the match.
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+
+    /// Returns `true` while the match is being played, so that the turns follow each other
+    /// without waiting for the tick of the application.
+    pub fn is_animating(&self) -> bool {
+        !self.paused && !self.game.is_over()
+    }
+
+    /// Returns the names of the strategies playing the match.
+    pub fn title(&self) -> String {
+        format!(" {} vs {} ", self.kinds.0, self.kinds.1)
+    }
+
+    /// Returns the turn of the match and its speed, or whether it is paused or over.
+    pub fn status(&self) -> String {
+        let turn = self.game.turns_played();
+        match self.game.phase() {
+            GamePhase::Finished { winner } => {
+                let kind = match winner {
+                    Side::Human => self.kinds.0,
+                    Side::Computer => self.kinds.1,
+                };
+                format!("Turn {turn} — {kind} wins, Enter to leave")
+            }
+            GamePhase::Drawn => format!("Turn {turn} — draw, Enter to leave"),
+            _ if self.paused => format!("Turn {turn} — paused"),
+            _ => format!(
+                "Turn {turn} — {} ms per turn",
+                Self::DELAYS[self.speed].as_millis()
+            ),
+        }
+    }
+
+    // Returns the grid of the fleet of the player, with the shots fired at it by the opponent.
+    fn fleet_grid(&self, player: &Player, opponent: &Player) -> GridModel {
+        let mut grid = GridModel::new(Grid::from_ships(player.fleet().as_ref()));
+        grid.set_theme(self.theme);
+        let shots = (0..10)
+            .flat_map(|y| (0..10).map(move |x| Cell::bounded(x, y)))
+            .filter(|cell| {
+                matches!(
+                    opponent.shots_grid().at(cell),
+                    CellState::Miss | CellState::Hit
+                )
+            })
+            .collect();
+        grid.push_layer(Layer::Shots(shots));
+
+        grid
+    }
+}
+
+impl StateModel for SpectatorStateModel {
+    fn handle_key_events(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Char('+') => self.speed = (self.speed + 1).min(Self::DELAYS.len() - 1),
+            KeyCode::Char('-') => self.speed = self.speed.saturating_sub(1),
+            KeyCode::Char(' ') => self.paused = !self.paused,
+            KeyCode::Enter if self.game.is_over() => self.closed = true,
+            KeyCode::Esc => self.closed = true,
+            _ => {}
+        }
+    }
+
+    fn update(&mut self, _series: &mut Series, _notifications: &mut Notifications) {
+        if self.is_animating() && self.last_step.elapsed() >= Self::DELAYS[self.speed] {
+            self.last_step = Instant::now();
+            self.game.turns(self.strategy.as_mut()).next();
+        }
+    }
+
+    fn widget(&self) -> impl Widget {
+        SpectatorWidget(self)
+    }
+}
+
+/// Widget for the spectator state.
+pub struct SpectatorWidget<'state>(&'state SpectatorStateModel);
+
+impl<'state> Widget for SpectatorWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let model = self.0;
+        let (Some(a), Some(b)) = (model.game.human(), model.game.computer()) else {
+            return;
+        };
+        let [grids, help] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(area);
+        let [a_area, b_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(grids);
+
+        for (area, player, opponent, kind) in
+            [(a_area, a, b, model.kinds.0), (b_area, b, a, model.kinds.1)]
+        {
+            let block = Block::bordered()
+                .title(Line::from(format!("Fleet of {kind}").bold()))
+                .border_set(border::THICK);
+            let grid = model.fleet_grid(player, opponent);
+            grid.widget().render(block.inner(area), buf);
+            block.render(area, buf);
+        }
+
+        Line::from("+/- to change the speed, Space to pause, Esc to leave")
+            .centered()
+            .render(help, buf);
+    }
+}
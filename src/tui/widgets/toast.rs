@@ -0,0 +1,71 @@
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    prelude::{Line, Stylize, Widget},
+    widgets::{Block, Clear, Paragraph},
+};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The transient notifications of the application, like "Ship placed".
+///
+/// Any state model can push a notification, which is shown in a corner of the screen until it
+/// is dismissed by the [tick](Notifications::tick) of the application, a few seconds later. Only
+/// the most recent notifications are kept.
+#[derive(Debug, Default)]
+pub struct Notifications {
+    toasts: VecDeque<(String, Instant)>,
+}
+
+impl Notifications {
+    // How long a notification is shown.
+    const DURATION: Duration = Duration::from_secs(3);
+    // How many notifications are shown at the same time, at most.
+    const MAX: usize = 3;
+
+    /// Shows a new notification, below the ones already shown.
+    pub fn push(&mut self, message: impl Into<String>) {
+        self.toasts.push_back((message.into(), Instant::now()));
+        if self.toasts.len() > Self::MAX {
+            self.toasts.pop_front();
+        }
+    }
+
+    /// Dismisses the notifications which have been shown long enough.
+    pub fn tick(&mut self) {
+        self.toasts
+            .retain(|(_, since)| since.elapsed() < Self::DURATION);
+    }
+
+    /// Returns the messages of the notifications shown, from the oldest.
+    pub fn messages(&self) -> impl Iterator<Item = &str> {
+        self.toasts.iter().map(|(message, _)| message.as_str())
+    }
+}
+
+/// Widget for the notifications, stacked in the top right corner of the area.
+pub struct ToastWidget<'state>(pub &'state Notifications);
+
+impl<'state> ToastWidget<'state> {
+    const HEIGHT: u16 = 3;
+}
+
+impl<'state> Widget for ToastWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let mut y = area.y;
+        for message in self.0.messages() {
+            if y + Self::HEIGHT > area.bottom() {
+                break;
+            }
+
+            let width = (message.chars().count() as u16 + 4).min(area.width);
+            let toast_area = Rect::new(area.right() - width, y, width, Self::HEIGHT);
+            Clear.render(toast_area, buf);
+            Paragraph::new(Line::from(message).bold())
+                .centered()
+                .block(Block::bordered().yellow())
+                .render(toast_area, buf);
+            y += Self::HEIGHT;
+        }
+    }
+}
@@ -0,0 +1,224 @@
+use crate::config::{Config, KeyBindings, Theme};
+use crate::engine::fleet::ShipOrientation;
+use crate::engine::grid::{Cell, Grid};
+use crate::engine::series::Series;
+use crate::engine::tutorial::{Tutorial, TutorialAction};
+use crate::tui::{
+    state::StateModel,
+    widgets::{
+        grid::{CursorMove, GridModel, Layer},
+        toast::Notifications,
+    },
+};
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::{
+    buffer::Buffer,
+    layout::{Constraint, Layout, Rect},
+    prelude::{Line, Stylize, Text, Widget},
+    symbols::border,
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+};
+
+/// Model for the tutorial state.
+///
+/// The player goes through the steps of the [Tutorial], reading the prompt of every step and
+/// doing what it asks: placing their ship on the left grid, and firing at the enemy on the
+/// right one. The grid where the player acts has the cursor, and the cell the step is about is
+/// highlighted.
+///
+/// When the tutorial is over, or the player skips it with Esc, the application goes on with the
+/// setup of a real battle.
+pub struct TutorialStateModel {
+    tutorial: Tutorial,
+    fleet_grid: GridModel,
+    enemy_grid: GridModel,
+    orientation: ShipOrientation,
+    theme: Theme,
+    keybindings: KeyBindings,
+    message: Option<String>,
+    skipped: bool,
+}
+
+impl TutorialStateModel {
+    /// Creates a new model at the first step of the tutorial, with the grids drawn in the
+    /// configured theme.
+    pub fn new(config: &Config) -> Self {
+        let mut model = Self {
+            tutorial: Tutorial::new(),
+            fleet_grid: GridModel::new(Grid::default()),
+            enemy_grid: GridModel::new(Grid::default()),
+            orientation: ShipOrientation::Horizontal,
+            theme: config.theme,
+            keybindings: config.keybindings.clone(),
+            message: None,
+            skipped: false,
+        };
+        model.update_grids();
+
+        model
+    }
+
+    /// Returns `true` when the tutorial is over, or the player has skipped it.
+    pub fn is_finished(&self) -> bool {
+        self.skipped || self.tutorial.is_over()
+    }
+
+    // Returns the action expected by the current step, if any.
+    fn expected(&self) -> Option<&TutorialAction> {
+        self.tutorial.step().map(|step| &step.action)
+    }
+
+    // Returns the grid where the player acts at the current step.
+    fn active_grid(&mut self) -> Option<&mut GridModel> {
+        match self.expected() {
+            Some(TutorialAction::Place(..)) => Some(&mut self.fleet_grid),
+            Some(TutorialAction::Fire(_)) => Some(&mut self.enemy_grid),
+            _ => None,
+        }
+    }
+
+    // Redraws the grids, keeping the cursors where they were. Only the grid where the player
+    // acts has the cursor, and it previews the ship to place.
+    fn update_grids(&mut self) {
+        let fleet_cursor = self.fleet_grid.cursor().copied();
+        let enemy_cursor = self.enemy_grid.cursor().copied();
+        self.fleet_grid = GridModel::new(Grid::from_ships(self.tutorial.ships()));
+        self.enemy_grid = GridModel::new(self.tutorial.shots_grid().clone());
+        self.fleet_grid.set_theme(self.theme);
+        self.enemy_grid.set_theme(self.theme);
+
+        match self.expected().cloned() {
+            Some(TutorialAction::Place(kind, first, _)) => {
+                let cursor = fleet_cursor.unwrap_or(Cell::bounded(0, 0));
+                self.fleet_grid.set_cursor(&cursor);
+                self.fleet_grid.push_layer(Layer::Hint(first));
+                if let Some(ship) = kind.ship(cursor, self.orientation) {
+                    self.fleet_grid
+                        .push_layer(Layer::Ship(ship.occupied_cells()));
+                }
+            }
+            Some(TutorialAction::Fire(target)) => {
+                let cursor = enemy_cursor.unwrap_or(Cell::bounded(0, 0));
+                self.enemy_grid.set_cursor(&cursor);
+                self.enemy_grid.push_layer(Layer::Hint(target));
+            }
+            _ => {}
+        }
+    }
+
+    // Does what the player has asked for on Enter, at the current step.
+    fn perform(&mut self) {
+        let action = match self.expected() {
+            Some(TutorialAction::Continue) => TutorialAction::Continue,
+            Some(TutorialAction::Place(kind, ..)) => TutorialAction::Place(
+                kind.clone(),
+                *self.fleet_grid.cursor().unwrap(),
+                self.orientation,
+            ),
+            Some(TutorialAction::Fire(_)) => {
+                TutorialAction::Fire(*self.enemy_grid.cursor().unwrap())
+            }
+            None => return,
+        };
+        self.message = self.tutorial.perform(action).err().map(|e| e.to_string());
+    }
+}
+
+impl StateModel for TutorialStateModel {
+    fn handle_key_events(&mut self, key_event: KeyEvent) {
+        let bindings = &self.keybindings;
+        match key_event.code {
+            KeyCode::Left | KeyCode::Right | KeyCode::Up | KeyCode::Down => {
+                let step = match key_event.code {
+                    KeyCode::Left => CursorMove::Left,
+                    KeyCode::Right => CursorMove::Right,
+                    KeyCode::Up => CursorMove::Up,
+                    _ => CursorMove::Down,
+                };
+                if let Some(grid) = self.active_grid() {
+                    grid.step_cursor(step);
+                }
+            }
+            KeyCode::Char(c) if c.eq_ignore_ascii_case(&bindings.horizontal) => {
+                self.orientation = ShipOrientation::Horizontal
+            }
+            KeyCode::Char(c) if c.eq_ignore_ascii_case(&bindings.vertical) => {
+                self.orientation = ShipOrientation::Vertical
+            }
+            KeyCode::Enter => self.perform(),
+            KeyCode::Esc => self.skipped = true,
+            _ => {}
+        }
+
+        self.update_grids();
+    }
+
+    fn update(&mut self, _series: &mut Series, _notifications: &mut Notifications) {}
+
+    fn widget(&self) -> impl Widget {
+        TutorialWidget(self)
+    }
+}
+
+/// Widget for the tutorial state.
+pub struct TutorialWidget<'state>(&'state TutorialStateModel);
+
+impl<'state> TutorialWidget<'state> {
+    const PROMPT_HEIGHT: u16 = 8;
+
+    // Shows the prompt of the current step over the grids, with the progress and the mistake
+    // of the player, if any.
+    fn prompt(&self) -> Paragraph<'state> {
+        let (step, steps) = self.0.tutorial.progress();
+        let mut lines = vec![Line::from(
+            self.0.tutorial.step().map_or("", |step| step.prompt),
+        )];
+        if let Some(message) = &self.0.message {
+            lines.push(Line::from(""));
+            lines.push(Line::from(message.clone()).italic().red());
+        }
+
+        Paragraph::new(Text::from(lines))
+            .wrap(Wrap { trim: true })
+            .centered()
+            .block(
+                Block::new()
+                    .title(format!(" Tutorial {step}/{steps} "))
+                    .title_bottom(Line::from(" Esc to skip ").right_aligned())
+                    .borders(Borders::ALL)
+                    .border_set(border::THICK)
+                    .yellow(),
+            )
+    }
+}
+
+impl<'state> Widget for TutorialWidget<'state> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [grids, prompt_area] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(Self::PROMPT_HEIGHT)])
+                .areas(area);
+        let [fleet_area, enemy_area] =
+            Layout::horizontal([Constraint::Fill(1), Constraint::Fill(1)]).areas(grids);
+
+        let fleet_block = Block::bordered()
+            .title(Line::from("Your Fleet".bold()))
+            .border_set(border::THICK);
+        self.0
+            .fleet_grid
+            .widget()
+            .render(fleet_block.inner(fleet_area), buf);
+        fleet_block.render(fleet_area, buf);
+
+        let enemy_block = Block::bordered()
+            .title(Line::from("Enemy Waters".bold()))
+            .border_set(border::THICK);
+        self.0
+            .enemy_grid
+            .widget()
+            .render(enemy_block.inner(enemy_area), buf);
+        enemy_block.render(enemy_area, buf);
+
+        Clear.render(prompt_area, buf);
+        self.prompt().render(prompt_area, buf);
+    }
+}
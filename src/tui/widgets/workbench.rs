@@ -1,6 +1,12 @@
-use crate::tui::state::NavalBattleState;
+use crate::config::KeyBindings;
+use crate::engine::series::Series;
+use crate::tui::{
+    state::NavalBattleState,
+    widgets::toast::{Notifications, ToastWidget},
+};
 use ratatui::{
-    prelude::{Buffer, Line, Rect, Stylize, Widget},
+    layout::{Constraint, Layout},
+    prelude::{Buffer, Line, Rect, Span, Stylize, Widget},
     symbols::border,
     widgets::Block,
 };
@@ -8,18 +14,88 @@ use ratatui::{
 /// The main window of the application.
 ///
 /// The main window takes a *content* which is a boxed widget that will be rendered within the workbench.
-pub struct Workbench<'state>(pub &'state NavalBattleState);
+/// The title tells who plays the series being set up or played, and its score.
+/// Below the content, a status bar shows what the state tells about itself, like the cell under
+/// the cursor during the battle, and the keys of the commands available in the state.
+/// The notifications are shown over the content, in its top right corner.
+pub struct Workbench<'state>(
+    pub &'state NavalBattleState,
+    pub &'state KeyBindings,
+    pub &'state Series,
+    pub &'state Notifications,
+);
+
+impl<'state> Workbench<'state> {
+    // Returns the title of the window, like "Alice vs Computer (Hard) — 2:1" during a series.
+    fn title(&self) -> String {
+        let series = self.2;
+        match self.0 {
+            NavalBattleState::Setup(_) | NavalBattleState::Battle(_) => {
+                let score = series.score();
+                format!(
+                    " {} vs Computer ({}) — {}:{} ",
+                    series.player_name(),
+                    series.difficulty(),
+                    score.human,
+                    score.computer
+                )
+            }
+            NavalBattleState::Spectator(state) => state.title(),
+            NavalBattleState::Replay(state) => state.title(),
+            NavalBattleState::Network(state) => state.title(),
+            _ => " Naval Battle ".to_string(),
+        }
+    }
+
+    // Returns the keys of the commands available in the current state.
+    fn key_hints(&self) -> Line<'static> {
+        let bindings = self.1;
+        let key = |key: char| format!("<{}> ", key.to_ascii_uppercase()).blue().bold();
+        let mut hints = vec![Span::raw(" Quit "), key(bindings.quit)];
+        if self.0.is_home() && !self.0.is_typing() {
+            hints.extend([Span::raw(" Settings "), key(bindings.settings)]);
+            hints.extend([Span::raw(" Leaderboard "), key(bindings.leaderboard)]);
+            hints.extend([Span::raw(" Network "), key(bindings.network)]);
+        }
+        if let NavalBattleState::Battle(_) = self.0 {
+            hints.extend([Span::raw(" Hint "), "<?> ".blue().bold()]);
+            hints.extend([Span::raw(" Notes "), "<1/2/0> ".blue().bold()]);
+            hints.extend([Span::raw(" Resign "), "<Ctrl+R> ".blue().bold()]);
+        }
+        if let NavalBattleState::Spectator(_) = self.0 {
+            hints.extend([Span::raw(" Speed "), "<+/-> ".blue().bold()]);
+            hints.extend([Span::raw(" Pause "), "<Space> ".blue().bold()]);
+        }
+        if let NavalBattleState::Replay(_) = self.0 {
+            hints.extend([Span::raw(" Step "), "<←/→> ".blue().bold()]);
+        }
+        if let NavalBattleState::Network(state) = self.0 {
+            if state.is_playing() {
+                hints.extend([Span::raw(" Chat "), "<T> ".blue().bold()]);
+            }
+            hints.extend([Span::raw(" Leave "), "<Esc> ".blue().bold()]);
+        }
+
+        Line::from(hints)
+    }
+}
 
 impl<'state> Widget for &Workbench<'state> {
     fn render(self, area: Rect, buf: &mut Buffer) {
-        let title = Line::from(" Naval Battle ".bold());
-        let instructions = Line::from(vec![" Quit ".into(), "<Q> ".blue().bold()]);
+        let title = Line::from(self.title().bold());
         let block = Block::bordered()
             .title(title.centered())
-            .title_bottom(instructions.centered())
             .border_set(border::THICK);
+        let [content, status_bar] =
+            Layout::vertical([Constraint::Fill(1), Constraint::Length(1)]).areas(block.inner(area));
+
+        block.render(area, buf);
+        self.0.render(content, buf);
+        ToastWidget(self.3).render(content, buf);
 
-        block.clone().render(area, buf);
-        self.0.render(block.inner(area), buf);
+        if let Some(status) = self.0.status() {
+            Line::from(format!(" {status}")).render(status_bar, buf);
+        }
+        self.key_hints().right_aligned().render(status_bar, buf);
     }
 }